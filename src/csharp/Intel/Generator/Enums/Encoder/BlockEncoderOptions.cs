@@ -21,5 +21,10 @@ namespace Generator.Enums.Encoder {
 
 		[Comment("The #(r:BlockEncoder)# should return #(r:ConstantOffsets)#")]
 		ReturnConstantOffsets		= 0x00000008,
+
+		[Comment("Mitigates the Intel JCC erratum (Skylake-family CPUs). #(c:NOP)#s are added before branches (#(c:Jcc)#, #(c:JMP)#, #(c:CALL)#, #(c:RET)#, etc) and macro-fused pairs (eg. #(c:CMP)#/#(c:TEST)# + #(c:Jcc)#) so they don't cross or end on a 32-byte boundary. The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits).",
+			CSharp = "Mitigates the Intel JCC erratum (Skylake-family CPUs). #(c:NOP)#s are added before branches (#(c:Jcc)#, #(c:JMP)#, #(c:CALL)#, #(c:RET)#, etc) and macro-fused pairs (eg. #(c:CMP)#/#(c:TEST)# + #(c:Jcc)#) so they don't cross or end on a 32-byte boundary. The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits). It's currently only supported by the Rust #(r:BlockEncoder)# and is ignored.",
+			Java = "Mitigates the Intel JCC erratum (Skylake-family CPUs). #(c:NOP)#s are added before branches (#(c:Jcc)#, #(c:JMP)#, #(c:CALL)#, #(c:RET)#, etc) and macro-fused pairs (eg. #(c:CMP)#/#(c:TEST)# + #(c:Jcc)#) so they don't cross or end on a 32-byte boundary. The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits). It's currently only supported by the Rust #(r:BlockEncoder)# and is ignored.")]
+		JccErratum					= 0x00000010,
 	}
 }
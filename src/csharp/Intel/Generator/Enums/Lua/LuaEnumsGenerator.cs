@@ -65,6 +65,7 @@ namespace Generator.Enums.Lua {
 			toFullFileInfo.Add(TypeIds.EncodingKind, new FullEnumFileInfo(dirs.GetLuaFilename("EncodingKind.lua")));
 			toFullFileInfo.Add(TypeIds.FlowControl, new FullEnumFileInfo(dirs.GetLuaFilename("FlowControl.lua")));
 			toFullFileInfo.Add(TypeIds.FormatMnemonicOptions, new FullEnumFileInfo(dirs.GetLuaFilename("FormatMnemonicOptions.lua")));
+			toFullFileInfo.Add(TypeIds.FormatterTextKind, new FullEnumFileInfo(dirs.GetLuaFilename("FormatterTextKind.lua")));
 			toFullFileInfo.Add(TypeIds.MandatoryPrefix, new FullEnumFileInfo(dirs.GetLuaFilename("MandatoryPrefix.lua")));
 			toFullFileInfo.Add(TypeIds.MemorySize, new FullEnumFileInfo(dirs.GetLuaFilename("MemorySize.lua")));
 			toFullFileInfo.Add(TypeIds.MemorySizeOptions, new FullEnumFileInfo(dirs.GetLuaFilename("MemorySizeOptions.lua")));
@@ -78,6 +79,10 @@ namespace Generator.Enums.Lua {
 			toFullFileInfo.Add(TypeIds.RepPrefixKind, new FullEnumFileInfo(dirs.GetLuaFilename("RepPrefixKind.lua")));
 			toFullFileInfo.Add(TypeIds.RflagsBits, new FullEnumFileInfo(dirs.GetLuaFilename("RflagsBits.lua")));
 			toFullFileInfo.Add(TypeIds.RoundingControl, new FullEnumFileInfo(dirs.GetLuaFilename("RoundingControl.lua")));
+			toFullFileInfo.Add(TypeIds.SymbolFlags, new FullEnumFileInfo(dirs.GetLuaFilename("SymbolFlags.lua")));
+			toFullFileInfo.Add(TypeIds.PrefixKind, new FullEnumFileInfo(dirs.GetLuaFilename("PrefixKind.lua")));
+			toFullFileInfo.Add(TypeIds.NumberKind, new FullEnumFileInfo(dirs.GetLuaFilename("NumberKind.lua")));
+			toFullFileInfo.Add(TypeIds.DecoratorKind, new FullEnumFileInfo(dirs.GetLuaFilename("DecoratorKind.lua")));
 			toFullFileInfo.Add(TypeIds.TupleType, new FullEnumFileInfo(dirs.GetLuaFilename("TupleType.lua")));
 			toFullFileInfo.Add(TypeIds.FormatterSyntax, new FullEnumFileInfo(dirs.GetLuaFilename("FormatterSyntax.lua")));
 			toFullFileInfo.Add(TypeIds.MvexConvFn, new FullEnumFileInfo(dirs.GetLuaFilename("MvexConvFn.lua")));
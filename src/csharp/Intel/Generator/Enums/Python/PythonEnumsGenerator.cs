@@ -84,6 +84,12 @@ namespace Generator.Enums.Python {
 			toFullFileInfo.Add(TypeIds.RoundingControl, new FullEnumFileInfo(dirs.GetPythonPyFilename("RoundingControl.py")));
 			toFullFileInfo.Add(TypeIds.TupleType, new FullEnumFileInfo(dirs.GetPythonPyFilename("TupleType.py")));
 			toFullFileInfo.Add(TypeIds.FormatterSyntax, new FullEnumFileInfo(dirs.GetPythonPyFilename("FormatterSyntax.py")));
+			toFullFileInfo.Add(TypeIds.FormatterTextKind, new FullEnumFileInfo(dirs.GetPythonPyFilename("FormatterTextKind.py")));
+			toFullFileInfo.Add(TypeIds.NumberBase, new FullEnumFileInfo(dirs.GetPythonPyFilename("NumberBase.py")));
+			toFullFileInfo.Add(TypeIds.SymbolFlags, new FullEnumFileInfo(dirs.GetPythonPyFilename("SymbolFlags.py")));
+			toFullFileInfo.Add(TypeIds.PrefixKind, new FullEnumFileInfo(dirs.GetPythonPyFilename("PrefixKind.py")));
+			toFullFileInfo.Add(TypeIds.NumberKind, new FullEnumFileInfo(dirs.GetPythonPyFilename("NumberKind.py")));
+			toFullFileInfo.Add(TypeIds.DecoratorKind, new FullEnumFileInfo(dirs.GetPythonPyFilename("DecoratorKind.py")));
 			toFullFileInfo.Add(TypeIds.MvexConvFn, new FullEnumFileInfo(dirs.GetPythonPyFilename("MvexConvFn.py")));
 			toFullFileInfo.Add(TypeIds.MvexRegMemConv, new FullEnumFileInfo(dirs.GetPythonPyFilename("MvexRegMemConv.py")));
 			toFullFileInfo.Add(TypeIds.MvexTupleTypeLutKind, new FullEnumFileInfo(dirs.GetPythonPyFilename("MvexTupleTypeLutKind.py")));
@@ -72,6 +72,11 @@ namespace Generator.Enums.RustJS {
 			toPartialFileInfo.Add(TypeIds.RoundingControl, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("rounding_control.rs"), RustConstants.AttributeCopyClone));
 			toPartialFileInfo.Add(TypeIds.TupleType, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("tuple_type.rs"), new[] { RustConstants.AttributeCopyClone, RustConstants.AttributeAllowNonCamelCaseTypes }));
 			toPartialFileInfo.Add(TypeIds.FormatterSyntax, new PartialEnumFileInfo("FormatterSyntax", dirs.GetRustJSFilename("formatter.rs")));
+			toPartialFileInfo.Add(TypeIds.FormatterTextKind, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("formatter_text_kind.rs"), RustConstants.AttributeCopyClone));
+			toPartialFileInfo.Add(TypeIds.SymbolFlags, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("symbol_flags.rs"), RustConstants.AttributeCopyClone));
+			toPartialFileInfo.Add(TypeIds.PrefixKind, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("prefix_kind.rs"), RustConstants.AttributeCopyClone));
+			toPartialFileInfo.Add(TypeIds.NumberKind, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("number_kind.rs"), RustConstants.AttributeCopyClone));
+			toPartialFileInfo.Add(TypeIds.DecoratorKind, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("decorator_kind.rs"), RustConstants.AttributeCopyClone));
 			toPartialFileInfo.Add(TypeIds.MvexConvFn, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("mvex_cvt_fn.rs"), new[] { RustConstants.AttributeCopyClone, RustConstants.AttributeAllowNonCamelCaseTypes }));
 			toPartialFileInfo.Add(TypeIds.MvexRegMemConv, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("mvex_rm_conv.rs"), new[] { RustConstants.AttributeCopyClone, RustConstants.AttributeAllowNonCamelCaseTypes }));
 			toPartialFileInfo.Add(TypeIds.MvexTupleTypeLutKind, new PartialEnumFileInfo("Enum", dirs.GetRustJSFilename("mvex_tt_lut.rs"), new[] { RustConstants.AttributeCopyClone, RustConstants.AttributeAllowNonCamelCaseTypes }));
@@ -130,6 +130,8 @@ namespace Iced.Intel {
 		ReturnNewInstructionOffsets = 0x00000004,
 		/// <summary>The <see cref="BlockEncoder"/> should return <see cref="ConstantOffsets"/></summary>
 		ReturnConstantOffsets = 0x00000008,
+		/// <summary>Mitigates the Intel JCC erratum (Skylake-family CPUs). <c>NOP</c>s are added before branches (<c>Jcc</c>, <c>JMP</c>, <c>CALL</c>, <c>RET</c>, etc) and macro-fused pairs (eg. <c>CMP</c>/<c>TEST</c> + <c>Jcc</c>) so they don't cross or end on a 32-byte boundary. The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits). It's currently only supported by the Rust <see cref="BlockEncoder"/> and is ignored.</summary>
+		JccErratum = 0x00000010,
 	}
 	// GENERATOR-END: BlockEncoderOptions
 
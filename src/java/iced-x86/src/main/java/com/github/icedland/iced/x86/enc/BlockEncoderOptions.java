@@ -32,4 +32,8 @@ public final class BlockEncoderOptions {
 	 * The {@link com.github.icedland.iced.x86.enc.BlockEncoder} should return {@link com.github.icedland.iced.x86.ConstantOffsets}
 	 */
 	public static final int RETURN_CONSTANT_OFFSETS = 0x0000_0008;
+	/**
+	 * Mitigates the Intel JCC erratum (Skylake-family CPUs).<!-- --> {@code NOP}s are added before branches ({@code Jcc}, {@code JMP}, {@code CALL}, {@code RET}, etc) and macro-fused pairs (eg.<!-- --> {@code CMP}/{@code TEST} + {@code Jcc}) so they don't cross or end on a 32-byte boundary.<!-- --> The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits).<!-- --> It's currently only supported by the Rust {@link com.github.icedland.iced.x86.enc.BlockEncoder} and is ignored.<!-- -->
+	 */
+	public static final int JCC_ERRATUM = 0x0000_0010;
 }
@@ -0,0 +1,1544 @@
+/*
+ * SPDX-License-Identifier: MIT
+ * Copyright (C) 2018-present iced project and contributors
+ *
+ * This file was generated by cbindgen, don't edit it. Update it by running:
+ *     ICED_CAPI_UPDATE_HEADER=1 cargo test --test header
+ */
+
+#ifndef ICED_X86_H
+#define ICED_X86_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Opcode info of a `Code` value, returned by `iced_op_code_info()`. It's a static object and must not be freed.
+typedef struct IcedOpCodeInfo IcedOpCodeInfo;
+
+// No option is set
+#define ICED_BLOCK_ENCODER_OPTIONS_NONE 0
+
+// By default, branches get updated if the target is too far away, eg. `Jcc SHORT` -> `Jcc NEAR` or if 64-bit mode, `Jcc + JMP [RIP+mem]`. If this option is enabled, no branches are fixed.
+#define ICED_BLOCK_ENCODER_OPTIONS_DONT_FIX_BRANCHES 1
+
+// The block encoder result will contain relocation infos, see `iced_block_encoder_result_reloc_infos()`
+#define ICED_BLOCK_ENCODER_OPTIONS_RETURN_RELOC_INFOS 2
+
+// The block encoder result will contain the new instruction offsets, see `iced_block_encoder_result_new_instruction_offsets()`
+#define ICED_BLOCK_ENCODER_OPTIONS_RETURN_NEW_INSTRUCTION_OFFSETS 4
+
+// The block encoder result will contain the constant offsets, see `iced_block_encoder_result_constant_offsets()`
+#define ICED_BLOCK_ENCODER_OPTIONS_RETURN_CONSTANT_OFFSETS 8
+
+// Mitigates the Intel JCC erratum (Skylake-family CPUs). `NOP`s are added before branches (`Jcc`, `JMP`, `CALL`, `RET`, etc) and macro-fused pairs (eg. `CMP`/`TEST` + `Jcc`) so they don't cross or end on a 32-byte boundary. The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits).
+#define ICED_BLOCK_ENCODER_OPTIONS_JCC_ERRATUM 16
+
+// No option is enabled
+#define ICED_DECODER_OPTIONS_NONE 0
+
+// Disable some checks for invalid encodings of instructions, eg. most instructions can't use a `LOCK` prefix so if one is found, they're decoded as `Code::INVALID` unless this option is enabled.
+#define ICED_DECODER_OPTIONS_NO_INVALID_CHECK 1
+
+// AMD decoder: allow 16-bit branch/ret instructions in 64-bit mode, no `o64 CALL/JMP FAR [mem], o64 LSS/LFS/LGS`, `UD0` has no modr/m byte, decode `LOCK MOV CR`. The AMD decoder can still decode Intel instructions.
+#define ICED_DECODER_OPTIONS_AMD 2
+
+// Decode opcodes `0F0D` and `0F18-0F1F` as reserved-nop instructions (eg. `Code::Reservednop_rm32_r32_0F1D`)
+#define ICED_DECODER_OPTIONS_FORCE_RESERVED_NOP 4
+
+// Decode `UMOV` instructions
+#define ICED_DECODER_OPTIONS_UMOV 8
+
+// Decode `XBTS`/`IBTS`
+#define ICED_DECODER_OPTIONS_XBTS 16
+
+// Decode `0FA6`/`0FA7` as `CMPXCHG`
+#define ICED_DECODER_OPTIONS_CMPXCHG486A 32
+
+// Decode some old removed FPU instructions (eg. `FRSTPM`)
+#define ICED_DECODER_OPTIONS_OLD_FPU 64
+
+// Decode `PCOMMIT`
+#define ICED_DECODER_OPTIONS_PCOMMIT 128
+
+// Decode 286 `STOREALL`/`LOADALL` (`0F04` and `0F05`)
+#define ICED_DECODER_OPTIONS_LOADALL286 256
+
+// Decode 386 `LOADALL`
+#define ICED_DECODER_OPTIONS_LOADALL386 512
+
+// Decode `CL1INVMB`
+#define ICED_DECODER_OPTIONS_CL1INVMB 1024
+
+// Decode `MOV r32,tr` and `MOV tr,r32`
+#define ICED_DECODER_OPTIONS_MOV_TR 2048
+
+// Decode `JMPE` instructions
+#define ICED_DECODER_OPTIONS_JMPE 4096
+
+// Don't decode `PAUSE`, decode `NOP` instead
+#define ICED_DECODER_OPTIONS_NO_PAUSE 8192
+
+// Don't decode `WBNOINVD`, decode `WBINVD` instead
+#define ICED_DECODER_OPTIONS_NO_WBNOINVD 16384
+
+// Decode undocumented Intel `RDUDBG` and `WRUDBG` instructions
+#define ICED_DECODER_OPTIONS_UDBG 32768
+
+// Don't decode `TZCNT`, decode `BSF` instead
+#define ICED_DECODER_OPTIONS_NO_MPFX_0FBC 65536
+
+// Don't decode `LZCNT`, decode `BSR` instead
+#define ICED_DECODER_OPTIONS_NO_MPFX_0FBD 131072
+
+// Don't decode `LAHF` and `SAHF` in 64-bit mode
+#define ICED_DECODER_OPTIONS_NO_LAHF_SAHF_64 262144
+
+// Decode `MPX` instructions
+#define ICED_DECODER_OPTIONS_MPX 524288
+
+// Decode most Cyrix instructions: `FPU`, `EMMI`, `SMM`, `DDI`
+#define ICED_DECODER_OPTIONS_CYRIX 1048576
+
+// Decode Cyrix `SMINT 0F7E` (Cyrix 6x86 or earlier)
+#define ICED_DECODER_OPTIONS_CYRIX_SMINT_0F7E 2097152
+
+// Decode Cyrix `DMI` instructions (AMD Geode GX/LX)
+#define ICED_DECODER_OPTIONS_CYRIX_DMI 4194304
+
+// Decode Centaur `ALTINST`
+#define ICED_DECODER_OPTIONS_ALTINST 8388608
+
+// Decode Intel Knights Corner instructions (requires the `mvex` feature)
+#define ICED_DECODER_OPTIONS_KNC 16777216
+
+// GNU Assembler (AT&T) syntax
+#define ICED_FORMATTER_SYNTAX_GAS 0
+
+// Intel XED syntax
+#define ICED_FORMATTER_SYNTAX_INTEL 1
+
+// masm syntax
+#define ICED_FORMATTER_SYNTAX_MASM 2
+
+// nasm syntax
+#define ICED_FORMATTER_SYNTAX_NASM 3
+
+// `uppercase_prefixes` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_UPPERCASE_PREFIXES 0
+
+// `uppercase_mnemonics` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_UPPERCASE_MNEMONICS 1
+
+// `uppercase_registers` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_UPPERCASE_REGISTERS 2
+
+// `uppercase_keywords` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_UPPERCASE_KEYWORDS 3
+
+// `uppercase_decorators` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_UPPERCASE_DECORATORS 4
+
+// `uppercase_all` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_UPPERCASE_ALL 5
+
+// `space_after_operand_separator` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR 6
+
+// `space_after_memory_bracket` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SPACE_AFTER_MEMORY_BRACKET 7
+
+// `space_between_memory_add_operators` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_ADD_OPERATORS 8
+
+// `space_between_memory_mul_operators` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_MUL_OPERATORS 9
+
+// `scale_before_index` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SCALE_BEFORE_INDEX 10
+
+// `always_show_scale` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_ALWAYS_SHOW_SCALE 11
+
+// `always_show_segment_register` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER 12
+
+// `show_zero_displacements` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SHOW_ZERO_DISPLACEMENTS 13
+
+// `leading_zeros` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_LEADING_ZEROS 14
+
+// `uppercase_hex` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_UPPERCASE_HEX 15
+
+// `small_hex_numbers_in_decimal` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SMALL_HEX_NUMBERS_IN_DECIMAL 16
+
+// `add_leading_zero_to_hex_numbers` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_ADD_LEADING_ZERO_TO_HEX_NUMBERS 17
+
+// `branch_leading_zeros` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_BRANCH_LEADING_ZEROS 18
+
+// `signed_immediate_operands` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SIGNED_IMMEDIATE_OPERANDS 19
+
+// `signed_memory_displacements` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SIGNED_MEMORY_DISPLACEMENTS 20
+
+// `displacement_leading_zeros` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_DISPLACEMENT_LEADING_ZEROS 21
+
+// `rip_relative_addresses` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES 22
+
+// `show_branch_size` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SHOW_BRANCH_SIZE 23
+
+// `use_pseudo_ops` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_USE_PSEUDO_OPS 24
+
+// `show_symbol_address` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS 25
+
+// `gas_naked_registers` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_GAS_NAKED_REGISTERS 26
+
+// `gas_show_mnemonic_size_suffix` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_GAS_SHOW_MNEMONIC_SIZE_SUFFIX 27
+
+// `gas_space_after_memory_operand_comma` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_GAS_SPACE_AFTER_MEMORY_OPERAND_COMMA 28
+
+// `masm_add_ds_prefix32` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_MASM_ADD_DS_PREFIX32 29
+
+// `masm_symbol_displ_in_brackets` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_MASM_SYMBOL_DISPL_IN_BRACKETS 30
+
+// `masm_displ_in_brackets` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_MASM_DISPL_IN_BRACKETS 31
+
+// `nasm_show_sign_extended_immediate_size` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_NASM_SHOW_SIGN_EXTENDED_IMMEDIATE_SIZE 32
+
+// `prefer_st0` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_PREFER_ST0 33
+
+// `show_useless_prefixes` option, a `bool` (0 or 1)
+#define ICED_FORMATTER_OPTION_SHOW_USELESS_PREFIXES 34
+
+// `first_operand_char_index` option, a `uint32_t`
+#define ICED_FORMATTER_OPTION_FIRST_OPERAND_CHAR_INDEX 35
+
+// `tab_size` option, a `uint32_t`
+#define ICED_FORMATTER_OPTION_TAB_SIZE 36
+
+// `hex_digit_group_size` option, a `uint32_t`
+#define ICED_FORMATTER_OPTION_HEX_DIGIT_GROUP_SIZE 37
+
+// `decimal_digit_group_size` option, a `uint32_t`
+#define ICED_FORMATTER_OPTION_DECIMAL_DIGIT_GROUP_SIZE 38
+
+// `octal_digit_group_size` option, a `uint32_t`
+#define ICED_FORMATTER_OPTION_OCTAL_DIGIT_GROUP_SIZE 39
+
+// `binary_digit_group_size` option, a `uint32_t`
+#define ICED_FORMATTER_OPTION_BINARY_DIGIT_GROUP_SIZE 40
+
+// `number_base` option, a `NumberBase` enum value
+#define ICED_FORMATTER_OPTION_NUMBER_BASE 41
+
+// `memory_size_options` option, a `MemorySizeOptions` enum value
+#define ICED_FORMATTER_OPTION_MEMORY_SIZE_OPTIONS 42
+
+// `cc_b` option, a `CC_b` enum value
+#define ICED_FORMATTER_OPTION_CC_B 43
+
+// `cc_ae` option, a `CC_ae` enum value
+#define ICED_FORMATTER_OPTION_CC_AE 44
+
+// `cc_e` option, a `CC_e` enum value
+#define ICED_FORMATTER_OPTION_CC_E 45
+
+// `cc_ne` option, a `CC_ne` enum value
+#define ICED_FORMATTER_OPTION_CC_NE 46
+
+// `cc_be` option, a `CC_be` enum value
+#define ICED_FORMATTER_OPTION_CC_BE 47
+
+// `cc_a` option, a `CC_a` enum value
+#define ICED_FORMATTER_OPTION_CC_A 48
+
+// `cc_p` option, a `CC_p` enum value
+#define ICED_FORMATTER_OPTION_CC_P 49
+
+// `cc_np` option, a `CC_np` enum value
+#define ICED_FORMATTER_OPTION_CC_NP 50
+
+// `cc_l` option, a `CC_l` enum value
+#define ICED_FORMATTER_OPTION_CC_L 51
+
+// `cc_ge` option, a `CC_ge` enum value
+#define ICED_FORMATTER_OPTION_CC_GE 52
+
+// `cc_le` option, a `CC_le` enum value
+#define ICED_FORMATTER_OPTION_CC_LE 53
+
+// `cc_g` option, a `CC_g` enum value
+#define ICED_FORMATTER_OPTION_CC_G 54
+
+// `hex_prefix` string option
+#define ICED_FORMATTER_STRING_OPTION_HEX_PREFIX 0
+
+// `hex_suffix` string option
+#define ICED_FORMATTER_STRING_OPTION_HEX_SUFFIX 1
+
+// `decimal_prefix` string option
+#define ICED_FORMATTER_STRING_OPTION_DECIMAL_PREFIX 2
+
+// `decimal_suffix` string option
+#define ICED_FORMATTER_STRING_OPTION_DECIMAL_SUFFIX 3
+
+// `octal_prefix` string option
+#define ICED_FORMATTER_STRING_OPTION_OCTAL_PREFIX 4
+
+// `octal_suffix` string option
+#define ICED_FORMATTER_STRING_OPTION_OCTAL_SUFFIX 5
+
+// `binary_prefix` string option
+#define ICED_FORMATTER_STRING_OPTION_BINARY_PREFIX 6
+
+// `binary_suffix` string option
+#define ICED_FORMATTER_STRING_OPTION_BINARY_SUFFIX 7
+
+// `digit_separator` string option
+#define ICED_FORMATTER_STRING_OPTION_DIGIT_SEPARATOR 8
+
+// `space_after_operand_separator` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR 0
+
+// `rip_relative_addresses` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES 1
+
+// `use_pseudo_ops` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_USE_PSEUDO_OPS 2
+
+// `show_symbol_address` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS 3
+
+// `always_show_segment_register` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER 4
+
+// `always_show_memory_size` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_MEMORY_SIZE 5
+
+// `uppercase_hex` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX 6
+
+// `use_hex_prefix` fast formatter option, a `bool` (0 or 1)
+#define ICED_FAST_FORMATTER_OPTION_USE_HEX_PREFIX 7
+
+// No option is enabled
+#define ICED_INSTRUCTION_INFO_OPTIONS_NONE 0
+
+// Don't include memory usage, ie. `used_memory` is empty
+#define ICED_INSTRUCTION_INFO_OPTIONS_NO_MEMORY_USAGE 1
+
+// Don't include register usage, ie. `used_registers` is empty
+#define ICED_INSTRUCTION_INFO_OPTIONS_NO_REGISTER_USAGE 2
+
+// Result of `iced_block_encoder_encode()`. Freed by `iced_block_encoder_result_free()`.
+typedef struct IcedBlockEncoderResult IcedBlockEncoderResult;
+
+// Decodes 16/32/64-bit x86 instructions. Created by `iced_decoder_new()` and freed by `iced_decoder_free()`.
+typedef struct IcedDecoder IcedDecoder;
+
+// Encodes instructions decoded by the decoder or instructions created by the user.
+// Created by `iced_encoder_new()` and freed by `iced_encoder_free()`.
+//
+// All encoded bytes are appended to an internal buffer, see `iced_encoder_buffer()` and `iced_encoder_clear_buffer()`.
+typedef struct IcedEncoder IcedEncoder;
+
+// Fast formatter with less formatting options and with a masm-like syntax. Created by `iced_fast_formatter_new()`
+// and freed by `iced_fast_formatter_free()`.
+typedef struct IcedFastFormatter IcedFastFormatter;
+
+// Formats instructions using GNU Assembler, Intel XED, masm or nasm syntax. Created by `iced_formatter_new()`
+// and freed by `iced_formatter_free()`.
+typedef struct IcedFormatter IcedFormatter;
+
+// Creates `IcedInstructionInfo`s. Created by `iced_instruction_info_factory_new()` and freed by
+// `iced_instruction_info_factory_free()`.
+typedef struct IcedInstructionInfoFactory IcedInstructionInfoFactory;
+
+// Return value of functions that can fail. It's [`ICED_OK`] (0) if the function succeeded.
+//
+// [`ICED_OK`]: constant.ICED_OK.html
+typedef uint32_t IcedStatus;
+
+// A 16/32/64-bit x86 instruction. It's a POD type that can be copied with `memcpy()` and zero-initialized
+// (an all zero instruction is a valid `INVALID` instruction).
+//
+// The fields are private, use the `iced_instruction_*()` functions to read or write them.
+typedef struct {
+  uint64_t data[5];
+} IcedInstruction;
+
+// Relocation info returned by the block encoder if `ICED_BLOCK_ENCODER_OPTIONS_RETURN_RELOC_INFOS` is used
+typedef struct {
+  // Address
+  uint64_t address;
+  // Relocation kind (a `RelocKind` enum value)
+  uint32_t kind;
+} IcedRelocInfo;
+
+// Constant offsets (immediate and displacement offsets and sizes) of an instruction
+typedef struct {
+  // The offset of the displacement, if any
+  uint8_t displacement_offset;
+  // Size in bytes of the displacement, or 0 if there's no displacement
+  uint8_t displacement_size;
+  // The offset of the first immediate, if any
+  uint8_t immediate_offset;
+  // Size in bytes of the first immediate, or 0 if there's no immediate
+  uint8_t immediate_size;
+  // The offset of the second immediate, if any
+  uint8_t immediate_offset2;
+  // Size in bytes of the second immediate, or 0 if there's no second immediate
+  uint8_t immediate_size2;
+} IcedConstantOffsets;
+
+// Symbol returned by an [`IcedSymbolResolver`]
+//
+// [`IcedSymbolResolver`]: struct.IcedSymbolResolver.html
+typedef struct {
+  // The address of the symbol. It's initialized to the address passed to the resolver.
+  uint64_t address;
+  // NUL-terminated UTF-8 symbol. It's copied by the formatter so it only needs to be valid until the resolver returns.
+  const char *text;
+  // Text kind (a `FormatterTextKind` enum value), default is `FormatterTextKind::Label`
+  uint32_t kind;
+  // Symbol flags (`SymbolFlags`), default is 0
+  uint32_t flags;
+  // Symbol size (a `MemorySize` enum value) or 0 (`MemorySize::Unknown`) if it's unknown
+  uint32_t symbol_size;
+} IcedSymbolResult;
+
+// Symbol resolver callback used by the formatters
+typedef struct {
+  // Passed to `symbol`
+  void *user_data;
+  // Tries to resolve a symbol. Returns `true` and initializes `result` if a symbol was found.
+  //
+  // * `user_data`: `user_data` field
+  // * `instruction`: Instruction
+  // * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+  // * `instruction_operand`: Instruction operand number, 0-based, or -1 if it's an operand created by the formatter.
+  // * `address`: Address
+  // * `address_size`: Size of `address` in bytes (eg. 1, 2, 4 or 8)
+  // * `result`: (out) Symbol
+  bool (*symbol)(void *user_data, const IcedInstruction *instruction, uint32_t operand, int32_t instruction_operand, uint64_t address, uint32_t address_size, IcedSymbolResult *result);
+} IcedSymbolResolver;
+
+// Formatter output callback used by `iced_formatter_format_output()`
+typedef struct {
+  // Passed to `write`
+  void *user_data;
+  // Writes text
+  //
+  // * `user_data`: `user_data` field
+  // * `text`: NUL-terminated UTF-8 text. It's only valid until the callback returns.
+  // * `len`: Length of `text` in bytes, not including the NUL byte
+  // * `kind`: Text kind (a `FormatterTextKind` enum value)
+  void (*write)(void *user_data, const char *text, size_t len, uint32_t kind);
+} IcedFormatterOutput;
+
+// A register used by an instruction
+typedef struct {
+  // Register (a `Register` enum value)
+  uint32_t register_;
+  // Register access (an `OpAccess` enum value)
+  uint32_t access;
+} IcedUsedRegister;
+
+// A memory location used by an instruction
+typedef struct {
+  // Effective segment register or `Register::None` if the segment register is ignored (a `Register` enum value)
+  uint32_t segment;
+  // Base register or `Register::None` if none (a `Register` enum value)
+  uint32_t base;
+  // Index register or `Register::None` if none (a `Register` enum value)
+  uint32_t index;
+  // Index scale (1, 2, 4 or 8)
+  uint32_t scale;
+  // Displacement
+  uint64_t displacement;
+  // Size of location (a `MemorySize` enum value)
+  uint32_t memory_size;
+  // Memory access (an `OpAccess` enum value)
+  uint32_t access;
+  // Address size (a `CodeSize` enum value)
+  uint32_t address_size;
+  // VSIB size (`0`, `4` or `8`)
+  uint32_t vsib_size;
+} IcedUsedMemory;
+
+// Instruction info returned by `iced_instruction_info_factory_info()`. The pointers are owned by the factory and are
+// valid until the next call to `iced_instruction_info_factory_info()` or until the factory is freed.
+typedef struct {
+  // All accessed registers. Some instructions (eg. `XSAVE`) don't return all accessed registers.
+  const IcedUsedRegister *used_registers;
+  // Number of elements in `used_registers`
+  size_t used_registers_count;
+  // All accessed memory locations
+  const IcedUsedMemory *used_memory;
+  // Number of elements in `used_memory`
+  size_t used_memory_count;
+  // Operand accesses of operands 0-4 (`OpAccess` enum values)
+  uint32_t op_accesses[5];
+} IcedInstructionInfo;
+
+// Success
+#define ICED_OK 0
+
+// A required pointer is null
+#define ICED_ERROR_NULL_POINTER 1
+
+// An argument is invalid, eg. an invalid enum value or operand index
+#define ICED_ERROR_INVALID_ARGUMENT 2
+
+// The operation failed, eg. the instruction couldn't be encoded. Call [`iced_last_error()`] to get the error message.
+//
+// [`iced_last_error()`]: fn.iced_last_error.html
+#define ICED_ERROR_FAILED 3
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Encodes instructions. Any number of branches can be part of the block. Branches are fixed so they can reach
+// their targets, eg. short branches are converted to near branches if needed.
+//
+// # Arguments
+//
+// * `bitness`: 16, 32 or 64
+// * `instructions`: Instructions to encode. It can be null if `count` is 0.
+// * `count`: Number of instructions
+// * `rip`: Base IP of all encoded instructions
+// * `options`: Encoder options (`BlockEncoderOptions` flags), eg. `ICED_BLOCK_ENCODER_OPTIONS_NONE`
+// * `result`: (out) Encoded instructions. Free it with `iced_block_encoder_result_free()`.
+//
+// # Errors
+//
+// Returns `ICED_ERROR_FAILED` if the instructions couldn't be encoded, see `iced_last_error()`.
+IcedStatus iced_block_encoder_encode(uint32_t bitness, const IcedInstruction *instructions, size_t count, uint64_t rip, uint32_t options, IcedBlockEncoderResult **result);
+
+// Frees the result. `result` can be null.
+void iced_block_encoder_result_free(IcedBlockEncoderResult *result);
+
+// Gets the base IP of all encoded instructions
+uint64_t iced_block_encoder_result_rip(const IcedBlockEncoderResult *result);
+
+// Gets the encoded bytes. The pointer is valid until the result is freed.
+//
+// # Arguments
+//
+// * `result`: Result
+// * `len`: (out) Number of bytes
+const uint8_t *iced_block_encoder_result_code(const IcedBlockEncoderResult *result, size_t *len);
+
+// Gets the offsets of the instructions relative to the base IP. It's empty unless
+// `ICED_BLOCK_ENCODER_OPTIONS_RETURN_NEW_INSTRUCTION_OFFSETS` was used. The pointer is valid until the result is freed.
+//
+// # Arguments
+//
+// * `result`: Result
+// * `count`: (out) Number of offsets
+const uint32_t *iced_block_encoder_result_new_instruction_offsets(const IcedBlockEncoderResult *result, size_t *count);
+
+// Gets the relocations. It's empty unless `ICED_BLOCK_ENCODER_OPTIONS_RETURN_RELOC_INFOS` was used.
+// The pointer is valid until the result is freed.
+//
+// # Arguments
+//
+// * `result`: Result
+// * `count`: (out) Number of relocations
+const IcedRelocInfo *iced_block_encoder_result_reloc_infos(const IcedBlockEncoderResult *result, size_t *count);
+
+// Gets the constant offsets of each instruction. It's empty unless `ICED_BLOCK_ENCODER_OPTIONS_RETURN_CONSTANT_OFFSETS`
+// was used. The pointer is valid until the result is freed.
+//
+// # Arguments
+//
+// * `result`: Result
+// * `count`: (out) Number of constant offsets
+const IcedConstantOffsets *iced_block_encoder_result_constant_offsets(const IcedBlockEncoderResult *result, size_t *count);
+
+// Creates a decoder. Returns null if `bitness` or `data` is invalid, see `iced_last_error()`.
+//
+// # Arguments
+//
+// * `bitness`: 16, 32 or 64
+// * `data`: Data to decode. It must be valid until the decoder is freed. It can be null if `data_len` is 0.
+// * `data_len`: Size of `data` in bytes
+// * `ip`: `RIP` value of the first byte in `data`
+// * `options`: Decoder options (`DecoderOptions` flags), eg. `ICED_DECODER_OPTIONS_NONE`
+IcedDecoder *iced_decoder_new(uint32_t bitness, const uint8_t *data, size_t data_len, uint64_t ip, uint32_t options);
+
+// Frees a decoder. `decoder` can be null.
+void iced_decoder_free(IcedDecoder *decoder);
+
+// Gets the bitness (16, 32 or 64)
+uint32_t iced_decoder_bitness(const IcedDecoder *decoder);
+
+// Gets the current `IP`/`EIP`/`RIP` value, see also `iced_decoder_position()`
+uint64_t iced_decoder_ip(const IcedDecoder *decoder);
+
+// Sets the current `IP`/`EIP`/`RIP` value. This function only updates the IP value, it doesn't change the data position,
+// use `iced_decoder_set_position()` to change the position.
+IcedStatus iced_decoder_set_ip(IcedDecoder *decoder, uint64_t ip);
+
+// Gets the current data position. This value is always <= `iced_decoder_max_position()`.
+size_t iced_decoder_position(const IcedDecoder *decoder);
+
+// Sets the current data position, which is the index into the data passed to `iced_decoder_new()`.
+// This value is always <= `iced_decoder_max_position()`.
+IcedStatus iced_decoder_set_position(IcedDecoder *decoder, size_t position);
+
+// Gets the max value that can be passed to `iced_decoder_set_position()`. This is the size of the data that gets
+// decoded to instructions and it's the length of the data that was passed to `iced_decoder_new()`.
+size_t iced_decoder_max_position(const IcedDecoder *decoder);
+
+// Returns `true` if there's at least one more byte to decode. It doesn't verify that the next instruction is valid,
+// it only checks if there's at least one more byte to read.
+bool iced_decoder_can_decode(const IcedDecoder *decoder);
+
+// Gets the last decoder error (a `DecoderError` enum value). Unless you need to know the reason it failed,
+// it's better to check `iced_instruction_is_invalid()`.
+uint32_t iced_decoder_last_error(const IcedDecoder *decoder);
+
+// Decodes the next instruction. If there's an error, the returned instruction is invalid
+// (`iced_instruction_is_invalid()`) and `iced_decoder_last_error()` returns the reason.
+//
+// # Arguments
+//
+// * `decoder`: Decoder
+// * `instruction`: (out) Decoded instruction
+IcedStatus iced_decoder_decode(IcedDecoder *decoder, IcedInstruction *instruction);
+
+// Decodes at most `count` instructions and stops if there's no more data. Returns the number of decoded instructions.
+//
+// # Arguments
+//
+// * `decoder`: Decoder
+// * `instructions`: (out) Decoded instructions
+// * `count`: Max number of instructions to decode
+size_t iced_decoder_decode_many(IcedDecoder *decoder, IcedInstruction *instructions, size_t count);
+
+// Gets the offsets of the constants (memory displacement and immediate) in the decoded instruction.
+// The caller can check if there are any relocations at those addresses.
+//
+// # Arguments
+//
+// * `decoder`: Decoder
+// * `instruction`: The latest instruction that was decoded by this decoder
+// * `offsets`: (out) Constant offsets
+IcedStatus iced_decoder_get_constant_offsets(const IcedDecoder *decoder, const IcedInstruction *instruction, IcedConstantOffsets *offsets);
+
+// Creates an encoder. Returns null if `bitness` is invalid, see `iced_last_error()`.
+//
+// # Arguments
+//
+// * `bitness`: 16, 32 or 64
+IcedEncoder *iced_encoder_new(uint32_t bitness);
+
+// Frees an encoder. `encoder` can be null.
+void iced_encoder_free(IcedEncoder *encoder);
+
+// Encodes an instruction and appends the bytes to the internal buffer
+//
+// # Arguments
+//
+// * `encoder`: Encoder
+// * `instruction`: Instruction to encode
+// * `rip`: `RIP` of the encoded instruction
+// * `len`: (out) Size of the encoded instruction. It can be null.
+//
+// # Errors
+//
+// Returns `ICED_ERROR_FAILED` if the instruction can't be encoded, see `iced_last_error()`.
+IcedStatus iced_encoder_encode(IcedEncoder *encoder, const IcedInstruction *instruction, uint64_t rip, size_t *len);
+
+// Gets all encoded bytes. The returned pointer is valid until the next call to a function that modifies the encoder.
+//
+// # Arguments
+//
+// * `encoder`: Encoder
+// * `len`: (out) Number of bytes in the buffer
+const uint8_t *iced_encoder_buffer(IcedEncoder *encoder, size_t *len);
+
+// Removes all bytes from the internal buffer
+IcedStatus iced_encoder_clear_buffer(IcedEncoder *encoder);
+
+// Gets the offsets of the constants (memory displacement and immediate) in the encoded instruction.
+// The caller can use this information to add relocations if needed.
+//
+// # Arguments
+//
+// * `encoder`: Encoder
+// * `offsets`: (out) Constant offsets of the last encoded instruction
+IcedStatus iced_encoder_get_constant_offsets(const IcedEncoder *encoder, IcedConstantOffsets *offsets);
+
+// Gets the bitness (16, 32 or 64)
+uint32_t iced_encoder_bitness(const IcedEncoder *encoder);
+
+// Checks if 2-byte VEX encoding is disabled, which encodes all VEX instructions with the 3-byte VEX encoding (default: `false`)
+bool iced_encoder_prevent_vex2(const IcedEncoder *encoder);
+
+// Disables 2-byte VEX encoding and encodes all VEX instructions with the 3-byte VEX encoding (default: `false`)
+IcedStatus iced_encoder_set_prevent_vex2(IcedEncoder *encoder, bool value);
+
+// Gets the value of the `VEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+uint32_t iced_encoder_vex_wig(const IcedEncoder *encoder);
+
+// Sets the value of the `VEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+IcedStatus iced_encoder_set_vex_wig(IcedEncoder *encoder, uint32_t value);
+
+// Gets the value of the `VEX.L` bit to use if it's an instruction that ignores the bit (default: 0)
+uint32_t iced_encoder_vex_lig(const IcedEncoder *encoder);
+
+// Sets the value of the `VEX.L` bit to use if it's an instruction that ignores the bit (default: 0)
+IcedStatus iced_encoder_set_vex_lig(IcedEncoder *encoder, uint32_t value);
+
+// Gets the value of the `EVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+uint32_t iced_encoder_evex_wig(const IcedEncoder *encoder);
+
+// Sets the value of the `EVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+IcedStatus iced_encoder_set_evex_wig(IcedEncoder *encoder, uint32_t value);
+
+// Gets the value of the `EVEX.L'L` bits to use if it's an instruction that ignores the bits (default: 0)
+uint32_t iced_encoder_evex_lig(const IcedEncoder *encoder);
+
+// Sets the value of the `EVEX.L'L` bits to use if it's an instruction that ignores the bits (default: 0)
+IcedStatus iced_encoder_set_evex_lig(IcedEncoder *encoder, uint32_t value);
+
+// Gets the value of the `MVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+uint32_t iced_encoder_mvex_wig(const IcedEncoder *encoder);
+
+// Sets the value of the `MVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+IcedStatus iced_encoder_set_mvex_wig(IcedEncoder *encoder, uint32_t value);
+
+// Gets the error message of the last function that failed on the current thread. It's never null.
+// The string is valid until the next function call that fails on the same thread.
+const char *iced_last_error(void);
+
+// Creates a formatter. Returns null if `syntax` is invalid.
+//
+// # Arguments
+//
+// * `syntax`: Syntax, eg. `ICED_FORMATTER_SYNTAX_NASM`
+// * `symbol_resolver`: Symbol resolver or null. It's copied so it doesn't need to outlive this call but
+//   `user_data` must be valid until the formatter is freed.
+IcedFormatter *iced_formatter_new(uint32_t syntax, const IcedSymbolResolver *symbol_resolver);
+
+// Frees a formatter. `formatter` can be null.
+void iced_formatter_free(IcedFormatter *formatter);
+
+// Formats the whole instruction: prefixes, mnemonic, operands.
+//
+// The string is copied to `buffer` and is truncated if it's too small. It returns the length of the formatted string
+// (not including the NUL byte) which can be greater than or equal to `size` if it was truncated (like `snprintf()`).
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `buffer`: (out) Formatted string, always NUL-terminated unless `size` is 0. It can be null if `size` is 0.
+// * `size`: Size of `buffer` in bytes
+size_t iced_formatter_format(IcedFormatter *formatter, const IcedInstruction *instruction, char *buffer, size_t size);
+
+// Formats the whole instruction and writes the text and text kinds to `output`
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `output`: Output
+IcedStatus iced_formatter_format_output(IcedFormatter *formatter, const IcedInstruction *instruction, const IcedFormatterOutput *output);
+
+// Formats the mnemonic and/or any prefixes, see `iced_formatter_format()` for the return value
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `options`: Options (`FormatMnemonicOptions` flags), eg. 0 (`FormatMnemonicOptions::NONE`)
+// * `buffer`: (out) Formatted string
+// * `size`: Size of `buffer` in bytes
+size_t iced_formatter_format_mnemonic(IcedFormatter *formatter, const IcedInstruction *instruction, uint32_t options, char *buffer, size_t size);
+
+// Gets the number of operands that will be formatted. A formatter can add and remove operands.
+uint32_t iced_formatter_operand_count(IcedFormatter *formatter, const IcedInstruction *instruction);
+
+// Formats an operand
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+//   See `iced_formatter_operand_count()`
+// * `buffer`: (out) Formatted string, see `iced_formatter_format()`
+// * `size`: Size of `buffer` in bytes
+// * `len`: (out) Length of the formatted string, see the return value of `iced_formatter_format()`. It can be null.
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+IcedStatus iced_formatter_format_operand(IcedFormatter *formatter, const IcedInstruction *instruction, uint32_t operand, char *buffer, size_t size, size_t *len);
+
+// Formats an operand separator, see `iced_formatter_format()` for the return value
+size_t iced_formatter_format_operand_separator(IcedFormatter *formatter, const IcedInstruction *instruction, char *buffer, size_t size);
+
+// Formats all operands, see `iced_formatter_format()` for the return value
+size_t iced_formatter_format_all_operands(IcedFormatter *formatter, const IcedInstruction *instruction, char *buffer, size_t size);
+
+// Formats a register, see `iced_formatter_format()` for the return value
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `register`: Register (a `Register` enum value)
+// * `buffer`: (out) Formatted string
+// * `size`: Size of `buffer` in bytes
+size_t iced_formatter_format_register(IcedFormatter *formatter, uint32_t register_, char *buffer, size_t size);
+
+// Gets the operand access (an `OpAccess` enum value) of a formatter operand
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+// * `access`: (out) Operand access or 0 (`OpAccess::None`) if it's an operand created by the formatter
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+IcedStatus iced_formatter_op_access(IcedFormatter *formatter, const IcedInstruction *instruction, uint32_t operand, uint32_t *access);
+
+// Converts a formatter operand index to an instruction operand index
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+// * `instruction_operand`: (out) Instruction operand or -1 if it's an operand created by the formatter
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+IcedStatus iced_formatter_get_instruction_operand(IcedFormatter *formatter, const IcedInstruction *instruction, uint32_t operand, int32_t *instruction_operand);
+
+// Converts an instruction operand index to a formatter operand index
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `instruction_operand`: Instruction operand
+// * `operand`: (out) Formatter operand or -1 if the instruction operand isn't used by the formatter
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `instruction_operand` is invalid
+IcedStatus iced_formatter_get_formatter_operand(IcedFormatter *formatter, const IcedInstruction *instruction, uint32_t instruction_operand, int32_t *operand);
+
+// Gets a formatter option
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `option`: Option, eg. `ICED_FORMATTER_OPTION_UPPERCASE_ALL`
+// * `value`: (out) Value of the option
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+IcedStatus iced_formatter_get_option(const IcedFormatter *formatter, uint32_t option, uint32_t *value);
+
+// Sets a formatter option
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `option`: Option, eg. `ICED_FORMATTER_OPTION_UPPERCASE_ALL`
+// * `value`: New value. `bool` options use 0 and 1 and enum options use the enum value.
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` or `value` is invalid
+IcedStatus iced_formatter_set_option(IcedFormatter *formatter, uint32_t option, uint32_t value);
+
+// Gets a formatter string option
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `option`: Option, eg. `ICED_FORMATTER_STRING_OPTION_HEX_PREFIX`
+// * `buffer`: (out) Value of the option, see `iced_formatter_format()`
+// * `size`: Size of `buffer` in bytes
+// * `len`: (out) Length of the string, see the return value of `iced_formatter_format()`. It can be null.
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+IcedStatus iced_formatter_get_string_option(const IcedFormatter *formatter, uint32_t option, char *buffer, size_t size, size_t *len);
+
+// Sets a formatter string option
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `option`: Option, eg. `ICED_FORMATTER_STRING_OPTION_HEX_PREFIX`
+// * `value`: NUL-terminated UTF-8 string
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid or if `value` isn't valid UTF-8
+IcedStatus iced_formatter_set_string_option(IcedFormatter *formatter, uint32_t option, const char *value);
+
+// Creates a fast formatter. Returns null on failure.
+//
+// # Arguments
+//
+// * `symbol_resolver`: Symbol resolver or null, see `iced_formatter_new()`
+IcedFastFormatter *iced_fast_formatter_new(const IcedSymbolResolver *symbol_resolver);
+
+// Frees a fast formatter. `formatter` can be null.
+void iced_fast_formatter_free(IcedFastFormatter *formatter);
+
+// Formats the whole instruction: prefixes, mnemonic, operands. See `iced_formatter_format()` for the return value.
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `instruction`: Instruction
+// * `buffer`: (out) Formatted string
+// * `size`: Size of `buffer` in bytes
+size_t iced_fast_formatter_format(IcedFastFormatter *formatter, const IcedInstruction *instruction, char *buffer, size_t size);
+
+// Gets a fast formatter option
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `option`: Option, eg. `ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX`
+// * `value`: (out) Value of the option (0 or 1)
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+IcedStatus iced_fast_formatter_get_option(const IcedFastFormatter *formatter, uint32_t option, uint32_t *value);
+
+// Sets a fast formatter option
+//
+// # Arguments
+//
+// * `formatter`: Formatter
+// * `option`: Option, eg. `ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX`
+// * `value`: New value (0 or 1)
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+IcedStatus iced_fast_formatter_set_option(IcedFastFormatter *formatter, uint32_t option, uint32_t value);
+
+// Creates an instruction info factory
+IcedInstructionInfoFactory *iced_instruction_info_factory_new(void);
+
+// Frees an instruction info factory. `factory` can be null.
+void iced_instruction_info_factory_free(IcedInstructionInfoFactory *factory);
+
+// Creates an instruction info
+//
+// # Arguments
+//
+// * `factory`: Factory
+// * `instruction`: Instruction
+// * `options`: Options (`InstructionInfoOptions` flags), eg. `ICED_INSTRUCTION_INFO_OPTIONS_NONE`
+// * `info`: (out) Instruction info
+IcedStatus iced_instruction_info_factory_info(IcedInstructionInfoFactory *factory, const IcedInstruction *instruction, uint32_t options, IcedInstructionInfo *info);
+
+// Creates an instruction with no operands
+//
+// # Arguments
+//
+// * `code`: Code (a `Code` enum value)
+// * `instruction`: (out) Created instruction
+IcedStatus iced_instruction_create(uint32_t code, IcedInstruction *instruction);
+
+// Checks if two instructions are equal. Some fields are ignored, eg. the IP, see `iced_instruction_eq_all_bits()`.
+bool iced_instruction_eq(const IcedInstruction *a, const IcedInstruction *b);
+
+// Checks if two instructions are equal, comparing all bits, not ignoring anything
+bool iced_instruction_eq_all_bits(const IcedInstruction *a, const IcedInstruction *b);
+
+// Gets an operand's kind (an `OpKind` enum value)
+//
+// # Arguments
+//
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-4
+// * `op_kind`: (out) Operand kind
+IcedStatus iced_instruction_op_kind(const IcedInstruction *instruction, uint32_t operand, uint32_t *op_kind);
+
+// Sets an operand's kind
+//
+// # Arguments
+//
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-4
+// * `op_kind`: Operand kind (an `OpKind` enum value)
+IcedStatus iced_instruction_set_op_kind(IcedInstruction *instruction, uint32_t operand, uint32_t op_kind);
+
+// Gets the operand's register value (a `Register` enum value). Use this function if the operand has kind `OpKind::Register`.
+//
+// # Arguments
+//
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-4
+// * `register`: (out) Register
+IcedStatus iced_instruction_op_register(const IcedInstruction *instruction, uint32_t operand, uint32_t *register_);
+
+// Sets the operand's register value. Use this function if the operand has kind `OpKind::Register`.
+//
+// # Arguments
+//
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-4
+// * `register`: Register (a `Register` enum value)
+IcedStatus iced_instruction_set_op_register(IcedInstruction *instruction, uint32_t operand, uint32_t register_);
+
+// Gets an operand's immediate value
+//
+// # Arguments
+//
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-4
+// * `immediate`: (out) Immediate, sign/zero extended to 64 bits
+IcedStatus iced_instruction_immediate(const IcedInstruction *instruction, uint32_t operand, uint64_t *immediate);
+
+// Sets an operand's immediate value
+//
+// # Arguments
+//
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-4
+// * `immediate`: New immediate
+IcedStatus iced_instruction_set_immediate_i64(IcedInstruction *instruction, uint32_t operand, int64_t immediate);
+
+// Sets an operand's immediate value
+//
+// # Arguments
+//
+// * `instruction`: Instruction
+// * `operand`: Operand number, 0-4
+// * `immediate`: New immediate
+IcedStatus iced_instruction_set_immediate_u64(IcedInstruction *instruction, uint32_t operand, uint64_t immediate);
+
+// Gets the 64-bit IP of the instruction
+uint64_t iced_instruction_ip(const IcedInstruction *instruction);
+
+// Gets the 64-bit IP of the next instruction
+uint64_t iced_instruction_next_ip(const IcedInstruction *instruction);
+
+// Gets the length of the instruction, 0-15 bytes
+size_t iced_instruction_len(const IcedInstruction *instruction);
+
+// Gets the instruction code (a `Code` enum value)
+uint32_t iced_instruction_code(const IcedInstruction *instruction);
+
+// Gets the mnemonic (a `Mnemonic` enum value)
+uint32_t iced_instruction_mnemonic(const IcedInstruction *instruction);
+
+// Gets the code size (a `CodeSize` enum value) when the instruction was decoded
+uint32_t iced_instruction_code_size(const IcedInstruction *instruction);
+
+// Checks if it's an invalid instruction
+bool iced_instruction_is_invalid(const IcedInstruction *instruction);
+
+// Gets the operand count. An instruction can have 0-5 operands.
+uint32_t iced_instruction_op_count(const IcedInstruction *instruction);
+
+// Checks if the instruction has the `LOCK` prefix
+bool iced_instruction_has_lock_prefix(const IcedInstruction *instruction);
+
+// Checks if the instruction has the `REP`/`REPE` prefix
+bool iced_instruction_has_rep_prefix(const IcedInstruction *instruction);
+
+// Checks if the instruction has the `REPNE` prefix
+bool iced_instruction_has_repne_prefix(const IcedInstruction *instruction);
+
+// Gets the segment override prefix (a `Register` enum value) or `Register::None` if none
+uint32_t iced_instruction_segment_prefix(const IcedInstruction *instruction);
+
+// Gets the effective segment register used to reference the memory location (a `Register` enum value)
+uint32_t iced_instruction_memory_segment(const IcedInstruction *instruction);
+
+// Gets the memory operand's base register (a `Register` enum value) or `Register::None` if none
+uint32_t iced_instruction_memory_base(const IcedInstruction *instruction);
+
+// Gets the memory operand's index register (a `Register` enum value) or `Register::None` if none
+uint32_t iced_instruction_memory_index(const IcedInstruction *instruction);
+
+// Gets the index register scale value, valid values are 1, 2, 4, 8
+uint32_t iced_instruction_memory_index_scale(const IcedInstruction *instruction);
+
+// Gets the memory operand's displacement
+uint64_t iced_instruction_memory_displacement64(const IcedInstruction *instruction);
+
+// Gets the size of the memory displacement in bytes (0, 1, 2, 4 or 8)
+uint32_t iced_instruction_memory_displ_size(const IcedInstruction *instruction);
+
+// Gets the size of the memory location (a `MemorySize` enum value)
+uint32_t iced_instruction_memory_size(const IcedInstruction *instruction);
+
+// Checks if the memory operand is `RIP`/`EIP` relative
+bool iced_instruction_is_ip_rel_memory_operand(const IcedInstruction *instruction);
+
+// Gets the `RIP`/`EIP` relative address (the displacement). Only valid if `iced_instruction_is_ip_rel_memory_operand()` returns `true`.
+uint64_t iced_instruction_ip_rel_memory_address(const IcedInstruction *instruction);
+
+// Gets the target of a near branch or 0 if it's not a near branch
+uint64_t iced_instruction_near_branch_target(const IcedInstruction *instruction);
+
+// Gets the opmask register (a `Register` enum value, `Register::K1`-`Register::K7`) or `Register::None` if none
+uint32_t iced_instruction_op_mask(const IcedInstruction *instruction);
+
+// Gets the flow control info (a `FlowControl` enum value)
+uint32_t iced_instruction_flow_control(const IcedInstruction *instruction);
+
+// Gets the encoding (a `EncodingKind` enum value), eg. legacy, 3DNow!, VEX, EVEX, XOP
+uint32_t iced_instruction_encoding(const IcedInstruction *instruction);
+
+// Gets the condition code (a `ConditionCode` enum value) if it's `Jcc`, `SETcc`, `CMOVcc`, `LOOPcc` else `ConditionCode::None`
+uint32_t iced_instruction_condition_code(const IcedInstruction *instruction);
+
+// Checks if it's a stack instruction, eg. `PUSH`, `POP`, `CALL`, `RET`
+bool iced_instruction_is_stack_instruction(const IcedInstruction *instruction);
+
+// Gets the number of bytes added to `SP`/`ESP`/`RSP` or 0 if it's not an instruction that pushes or pops data
+int32_t iced_instruction_stack_pointer_increment(const IcedInstruction *instruction);
+
+// All flags that are read by the CPU when executing the instruction (`RflagsBits` flags)
+uint32_t iced_instruction_rflags_read(const IcedInstruction *instruction);
+
+// All flags that are written by the CPU, except those flags that are known to be undefined, always set or always cleared (`RflagsBits` flags)
+uint32_t iced_instruction_rflags_written(const IcedInstruction *instruction);
+
+// All flags that are modified by the CPU (`RflagsBits` flags)
+uint32_t iced_instruction_rflags_modified(const IcedInstruction *instruction);
+
+// Sets the 64-bit IP of the instruction
+IcedStatus iced_instruction_set_ip(IcedInstruction *instruction, uint64_t value);
+
+// Sets the 64-bit IP of the next instruction
+IcedStatus iced_instruction_set_next_ip(IcedInstruction *instruction, uint64_t value);
+
+// Sets the length of the instruction, 0-15 bytes
+IcedStatus iced_instruction_set_len(IcedInstruction *instruction, size_t value);
+
+// Sets the instruction code (a `Code` enum value)
+IcedStatus iced_instruction_set_code(IcedInstruction *instruction, uint32_t value);
+
+// Sets the `LOCK` prefix
+IcedStatus iced_instruction_set_has_lock_prefix(IcedInstruction *instruction, bool value);
+
+// Sets the `REP`/`REPE` prefix
+IcedStatus iced_instruction_set_has_rep_prefix(IcedInstruction *instruction, bool value);
+
+// Sets the `REPNE` prefix
+IcedStatus iced_instruction_set_has_repne_prefix(IcedInstruction *instruction, bool value);
+
+// Sets the segment override prefix (a `Register` enum value) or `Register::None` if none
+IcedStatus iced_instruction_set_segment_prefix(IcedInstruction *instruction, uint32_t value);
+
+// Sets the memory operand's base register (a `Register` enum value) or `Register::None` if none
+IcedStatus iced_instruction_set_memory_base(IcedInstruction *instruction, uint32_t value);
+
+// Sets the memory operand's index register (a `Register` enum value) or `Register::None` if none
+IcedStatus iced_instruction_set_memory_index(IcedInstruction *instruction, uint32_t value);
+
+// Sets the index register scale value, valid values are 1, 2, 4, 8
+IcedStatus iced_instruction_set_memory_index_scale(IcedInstruction *instruction, uint32_t value);
+
+// Sets the memory operand's displacement
+IcedStatus iced_instruction_set_memory_displacement64(IcedInstruction *instruction, uint64_t value);
+
+// Sets the size of the memory displacement in bytes (0, 1, 2, 4 or 8)
+IcedStatus iced_instruction_set_memory_displ_size(IcedInstruction *instruction, uint32_t value);
+
+// Sets the target of a 64-bit near branch (the operand kind must be `OpKind::NearBranch64`)
+IcedStatus iced_instruction_set_near_branch64(IcedInstruction *instruction, uint64_t value);
+
+// Sets the opmask register (a `Register` enum value, `Register::K1`-`Register::K7`) or `Register::None` if none
+IcedStatus iced_instruction_set_op_mask(IcedInstruction *instruction, uint32_t value);
+
+// Gets the opcode info of a `Code` value. The returned pointer is valid for the lifetime of the program.
+// Returns null if `code` is invalid.
+//
+// # Arguments
+//
+// * `code`: Code (a `Code` enum value)
+const IcedOpCodeInfo *iced_op_code_info(uint32_t code);
+
+// Gets the code (a `Code` enum value)
+uint32_t iced_op_code_info_code(const IcedOpCodeInfo *info);
+
+// Gets the mnemonic (a `Mnemonic` enum value)
+uint32_t iced_op_code_info_mnemonic(const IcedOpCodeInfo *info);
+
+// Gets the encoding (an `EncodingKind` enum value)
+uint32_t iced_op_code_info_encoding(const IcedOpCodeInfo *info);
+
+// `true` if it's an instruction, `false` if it's eg. `Code::INVALID`, `db`, `dw`, `dd`, `dq`, `zero_bytes`
+bool iced_op_code_info_is_instruction(const IcedOpCodeInfo *info);
+
+// `true` if it's an instruction available in 16-bit mode
+bool iced_op_code_info_mode16(const IcedOpCodeInfo *info);
+
+// `true` if it's an instruction available in 32-bit mode
+bool iced_op_code_info_mode32(const IcedOpCodeInfo *info);
+
+// `true` if it's an instruction available in 64-bit mode
+bool iced_op_code_info_mode64(const IcedOpCodeInfo *info);
+
+// `true` if an `FWAIT` (`9B`) instruction is added before the instruction
+bool iced_op_code_info_fwait(const IcedOpCodeInfo *info);
+
+// (Legacy encoding) Gets the required operand size (16,32,64) or 0
+uint32_t iced_op_code_info_operand_size(const IcedOpCodeInfo *info);
+
+// (Legacy encoding) Gets the required address size (16,32,64) or 0
+uint32_t iced_op_code_info_address_size(const IcedOpCodeInfo *info);
+
+// (VEX/XOP/EVEX) `L` / `L'L` value or default value if `is_lig()` is `true`
+uint32_t iced_op_code_info_l(const IcedOpCodeInfo *info);
+
+// (VEX/XOP/EVEX/MVEX) `W` value or default value if `is_wig()` or `is_wig32()` is `true`
+uint32_t iced_op_code_info_w(const IcedOpCodeInfo *info);
+
+// (VEX/XOP/EVEX) `true` if the `L` / `L'L` fields are ignored.
+bool iced_op_code_info_is_lig(const IcedOpCodeInfo *info);
+
+// (VEX/XOP/EVEX/MVEX) `true` if the `W` field is ignored in 16/32/64-bit modes
+bool iced_op_code_info_is_wig(const IcedOpCodeInfo *info);
+
+// (VEX/XOP/EVEX/MVEX) `true` if the `W` field is ignored in 16/32-bit modes (but not 64-bit mode)
+bool iced_op_code_info_is_wig32(const IcedOpCodeInfo *info);
+
+// (EVEX/MVEX) Gets the tuple type (a `TupleType` enum value)
+uint32_t iced_op_code_info_tuple_type(const IcedOpCodeInfo *info);
+
+// If it has a memory operand, gets the `MemorySize` (non-broadcast memory type) (a `MemorySize` enum value)
+uint32_t iced_op_code_info_memory_size(const IcedOpCodeInfo *info);
+
+// If it has a memory operand, gets the `MemorySize` (broadcast memory type) (a `MemorySize` enum value)
+uint32_t iced_op_code_info_broadcast_memory_size(const IcedOpCodeInfo *info);
+
+// (EVEX) `true` if the instruction supports broadcasting (`EVEX.b` bit) (if it has a memory operand)
+bool iced_op_code_info_can_broadcast(const IcedOpCodeInfo *info);
+
+// (EVEX/MVEX) `true` if the instruction supports rounding control
+bool iced_op_code_info_can_use_rounding_control(const IcedOpCodeInfo *info);
+
+// (EVEX/MVEX) `true` if the instruction supports suppress all exceptions
+bool iced_op_code_info_can_suppress_all_exceptions(const IcedOpCodeInfo *info);
+
+// (EVEX/MVEX) `true` if an opmask register can be used
+bool iced_op_code_info_can_use_op_mask_register(const IcedOpCodeInfo *info);
+
+// (EVEX/MVEX) `true` if a non-zero opmask register must be used
+bool iced_op_code_info_require_op_mask_register(const IcedOpCodeInfo *info);
+
+// (EVEX) `true` if the instruction supports zeroing masking (if one of the opmask registers `K1`-`K7` is used and destination operand is not a memory operand)
+bool iced_op_code_info_can_use_zeroing_masking(const IcedOpCodeInfo *info);
+
+// `true` if the `LOCK` (`F0`) prefix can be used
+bool iced_op_code_info_can_use_lock_prefix(const IcedOpCodeInfo *info);
+
+// `true` if the `XACQUIRE` (`F2`) prefix can be used
+bool iced_op_code_info_can_use_xacquire_prefix(const IcedOpCodeInfo *info);
+
+// `true` if the `XRELEASE` (`F3`) prefix can be used
+bool iced_op_code_info_can_use_xrelease_prefix(const IcedOpCodeInfo *info);
+
+// `true` if the `REP` / `REPE` (`F3`) prefixes can be used
+bool iced_op_code_info_can_use_rep_prefix(const IcedOpCodeInfo *info);
+
+// `true` if the `REPNE` (`F2`) prefix can be used
+bool iced_op_code_info_can_use_repne_prefix(const IcedOpCodeInfo *info);
+
+// `true` if the `BND` (`F2`) prefix can be used
+bool iced_op_code_info_can_use_bnd_prefix(const IcedOpCodeInfo *info);
+
+// `true` if the `HINT-TAKEN` (`3E`) and `HINT-NOT-TAKEN` (`2E`) prefixes can be used
+bool iced_op_code_info_can_use_hint_taken_prefix(const IcedOpCodeInfo *info);
+
+// `true` if the `NOTRACK` (`3E`) prefix can be used
+bool iced_op_code_info_can_use_notrack_prefix(const IcedOpCodeInfo *info);
+
+// `true` if rounding control is ignored (#UD is not generated)
+bool iced_op_code_info_ignores_rounding_control(const IcedOpCodeInfo *info);
+
+// `true` if the `LOCK` prefix can be used as an extra register bit (bit 3) to access registers 8-15 without a `REX` prefix (eg. in 32-bit mode)
+bool iced_op_code_info_amd_lock_reg_bit(const IcedOpCodeInfo *info);
+
+// `true` if the default operand size is 64 in 64-bit mode. A `66` prefix can switch to 16-bit operand size.
+bool iced_op_code_info_default_op_size64(const IcedOpCodeInfo *info);
+
+// `true` if the operand size is always 64 in 64-bit mode. A `66` prefix is ignored.
+bool iced_op_code_info_force_op_size64(const IcedOpCodeInfo *info);
+
+// `true` if the Intel decoder forces 64-bit operand size. A `66` prefix is ignored.
+bool iced_op_code_info_intel_force_op_size64(const IcedOpCodeInfo *info);
+
+// `true` if it can only be executed when CPL=0
+bool iced_op_code_info_must_be_cpl0(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed when CPL=0
+bool iced_op_code_info_cpl0(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed when CPL=1
+bool iced_op_code_info_cpl1(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed when CPL=2
+bool iced_op_code_info_cpl2(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed when CPL=3
+bool iced_op_code_info_cpl3(const IcedOpCodeInfo *info);
+
+// `true` if the instruction accesses the I/O address space (eg. `IN`, `OUT`, `INS`, `OUTS`)
+bool iced_op_code_info_is_input_output(const IcedOpCodeInfo *info);
+
+// `true` if it's one of the many nop instructions (does not include FPU nop instructions, eg. `FNOP`)
+bool iced_op_code_info_is_nop(const IcedOpCodeInfo *info);
+
+// `true` if it's one of the many reserved nop instructions (eg. `0F0D`, `0F18-0F1F`)
+bool iced_op_code_info_is_reserved_nop(const IcedOpCodeInfo *info);
+
+// `true` if it's a serializing instruction (Intel CPUs)
+bool iced_op_code_info_is_serializing_intel(const IcedOpCodeInfo *info);
+
+// `true` if it's a serializing instruction (AMD CPUs)
+bool iced_op_code_info_is_serializing_amd(const IcedOpCodeInfo *info);
+
+// `true` if the instruction requires either CPL=0 or CPL<=3 depending on some CPU option (eg. `CR4.TSD`, `CR4.PCE`, `CR4.UMIP`)
+bool iced_op_code_info_may_require_cpl0(const IcedOpCodeInfo *info);
+
+// `true` if it's a tracked `JMP`/`CALL` indirect instruction (CET)
+bool iced_op_code_info_is_cet_tracked(const IcedOpCodeInfo *info);
+
+// `true` if it's a non-temporal hint memory access (eg. `MOVNTDQ`)
+bool iced_op_code_info_is_non_temporal(const IcedOpCodeInfo *info);
+
+// `true` if it's a no-wait FPU instruction, eg. `FNINIT`
+bool iced_op_code_info_is_fpu_no_wait(const IcedOpCodeInfo *info);
+
+// `true` if the mod bits are ignored and it's assumed `modrm[7:6] == 11b`
+bool iced_op_code_info_ignores_mod_bits(const IcedOpCodeInfo *info);
+
+// `true` if the `66` prefix is not allowed (it will #UD)
+bool iced_op_code_info_no66(const IcedOpCodeInfo *info);
+
+// `true` if the `F2`/`F3` prefixes aren't allowed
+bool iced_op_code_info_nfx(const IcedOpCodeInfo *info);
+
+// `true` if the index reg's reg-num (vsib op) (if any) and register ops' reg-nums must be unique,
+bool iced_op_code_info_requires_unique_reg_nums(const IcedOpCodeInfo *info);
+
+// `true` if the destination register's reg-num must not be present in any other operand, eg. `MNEMONIC XMM1,YMM1,[RAX+ZMM1*2]`
+bool iced_op_code_info_requires_unique_dest_reg_num(const IcedOpCodeInfo *info);
+
+// `true` if it's a privileged instruction (all CPL=0 instructions (except `VMCALL`) and IOPL instructions `IN`, `INS`, `OUT`, `OUTS`, `CLI`, `STI`)
+bool iced_op_code_info_is_privileged(const IcedOpCodeInfo *info);
+
+// `true` if it reads/writes too many registers
+bool iced_op_code_info_is_save_restore(const IcedOpCodeInfo *info);
+
+// `true` if it's an instruction that implicitly uses the stack register, eg. `CALL`, `POP`, etc
+bool iced_op_code_info_is_stack_instruction(const IcedOpCodeInfo *info);
+
+// `true` if the instruction doesn't read the segment register if it uses a memory operand
+bool iced_op_code_info_ignores_segment(const IcedOpCodeInfo *info);
+
+// `true` if the opmask register is read and written (instead of just read). This also implies that it can't be `K0`.
+bool iced_op_code_info_is_op_mask_read_write(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed in real mode
+bool iced_op_code_info_real_mode(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed in protected mode
+bool iced_op_code_info_protected_mode(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed in virtual 8086 mode
+bool iced_op_code_info_virtual8086_mode(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed in compatibility mode
+bool iced_op_code_info_compatibility_mode(const IcedOpCodeInfo *info);
+
+// `true` if it can be executed in 64-bit mode
+bool iced_op_code_info_long_mode(const IcedOpCodeInfo *info);
+
+// `true` if it can be used outside SMM
+bool iced_op_code_info_use_outside_smm(const IcedOpCodeInfo *info);
+
+// `true` if it can be used in SMM
+bool iced_op_code_info_use_in_smm(const IcedOpCodeInfo *info);
+
+// `true` if it can be used outside an enclave (SGX)
+bool iced_op_code_info_use_outside_enclave_sgx(const IcedOpCodeInfo *info);
+
+// `true` if it can be used inside an enclave (SGX1)
+bool iced_op_code_info_use_in_enclave_sgx1(const IcedOpCodeInfo *info);
+
+// `true` if it can be used inside an enclave (SGX2)
+bool iced_op_code_info_use_in_enclave_sgx2(const IcedOpCodeInfo *info);
+
+// `true` if it can be used outside VMX operation
+bool iced_op_code_info_use_outside_vmx_op(const IcedOpCodeInfo *info);
+
+// `true` if it can be used in VMX root operation
+bool iced_op_code_info_use_in_vmx_root_op(const IcedOpCodeInfo *info);
+
+// `true` if it can be used in VMX non-root operation
+bool iced_op_code_info_use_in_vmx_non_root_op(const IcedOpCodeInfo *info);
+
+// `true` if it can be used outside SEAM
+bool iced_op_code_info_use_outside_seam(const IcedOpCodeInfo *info);
+
+// `true` if it can be used in SEAM
+bool iced_op_code_info_use_in_seam(const IcedOpCodeInfo *info);
+
+// `true` if #UD is generated in TDX non-root operation
+bool iced_op_code_info_tdx_non_root_gen_ud(const IcedOpCodeInfo *info);
+
+// `true` if #VE is generated in TDX non-root operation
+bool iced_op_code_info_tdx_non_root_gen_ve(const IcedOpCodeInfo *info);
+
+// `true` if an exception (eg. #GP(0), #VE) may be generated in TDX non-root operation
+bool iced_op_code_info_tdx_non_root_may_gen_ex(const IcedOpCodeInfo *info);
+
+// (Intel VMX) `true` if it causes a VM exit in VMX non-root operation
+bool iced_op_code_info_intel_vm_exit(const IcedOpCodeInfo *info);
+
+// (Intel VMX) `true` if it may cause a VM exit in VMX non-root operation
+bool iced_op_code_info_intel_may_vm_exit(const IcedOpCodeInfo *info);
+
+// (Intel VMX) `true` if it causes an SMM VM exit in VMX root operation (if dual-monitor treatment is activated)
+bool iced_op_code_info_intel_smm_vm_exit(const IcedOpCodeInfo *info);
+
+// (AMD SVM) `true` if it causes a #VMEXIT in guest mode
+bool iced_op_code_info_amd_vm_exit(const IcedOpCodeInfo *info);
+
+// (AMD SVM) `true` if it may cause a #VMEXIT in guest mode
+bool iced_op_code_info_amd_may_vm_exit(const IcedOpCodeInfo *info);
+
+// `true` if it causes a TSX abort inside a TSX transaction
+bool iced_op_code_info_tsx_abort(const IcedOpCodeInfo *info);
+
+// `true` if it causes a TSX abort inside a TSX transaction depending on the implementation
+bool iced_op_code_info_tsx_impl_abort(const IcedOpCodeInfo *info);
+
+// `true` if it may cause a TSX abort inside a TSX transaction depending on some condition
+bool iced_op_code_info_tsx_may_abort(const IcedOpCodeInfo *info);
+
+// `true` if it's decoded by iced's 16-bit Intel decoder
+bool iced_op_code_info_intel_decoder16(const IcedOpCodeInfo *info);
+
+// `true` if it's decoded by iced's 32-bit Intel decoder
+bool iced_op_code_info_intel_decoder32(const IcedOpCodeInfo *info);
+
+// `true` if it's decoded by iced's 64-bit Intel decoder
+bool iced_op_code_info_intel_decoder64(const IcedOpCodeInfo *info);
+
+// `true` if it's decoded by iced's 16-bit AMD decoder
+bool iced_op_code_info_amd_decoder16(const IcedOpCodeInfo *info);
+
+// `true` if it's decoded by iced's 32-bit AMD decoder
+bool iced_op_code_info_amd_decoder32(const IcedOpCodeInfo *info);
+
+// `true` if it's decoded by iced's 64-bit AMD decoder
+bool iced_op_code_info_amd_decoder64(const IcedOpCodeInfo *info);
+
+// Gets the decoder option that's needed to decode the instruction or `DecoderOptions::NONE`.
+uint32_t iced_op_code_info_decoder_option(const IcedOpCodeInfo *info);
+
+// Gets the opcode table (a `OpCodeTableKind` enum value)
+uint32_t iced_op_code_info_table(const IcedOpCodeInfo *info);
+
+// Gets the mandatory prefix (a `MandatoryPrefix` enum value)
+uint32_t iced_op_code_info_mandatory_prefix(const IcedOpCodeInfo *info);
+
+// Gets the opcode byte(s). The low byte(s) of this value is the opcode. The length is in `op_code_len()`.
+uint32_t iced_op_code_info_op_code(const IcedOpCodeInfo *info);
+
+// Gets the length of the opcode bytes (`op_code()`). The low bytes is the opcode value.
+uint32_t iced_op_code_info_op_code_len(const IcedOpCodeInfo *info);
+
+// `true` if it's part of a group
+bool iced_op_code_info_is_group(const IcedOpCodeInfo *info);
+
+// Group index (0-7) or -1. If it's 0-7, it's stored in the `reg` field of the `modrm` byte.
+int32_t iced_op_code_info_group_index(const IcedOpCodeInfo *info);
+
+// `true` if it's part of a modrm.rm group
+bool iced_op_code_info_is_rm_group(const IcedOpCodeInfo *info);
+
+// Group index (0-7) or -1. If it's 0-7, it's stored in the `rm` field of the `modrm` byte.
+int32_t iced_op_code_info_rm_group_index(const IcedOpCodeInfo *info);
+
+// Gets the number of operands
+uint32_t iced_op_code_info_op_count(const IcedOpCodeInfo *info);
+
+// Gets an operand's opkind (an `OpCodeOperandKind` enum value)
+//
+// # Arguments
+//
+// * `info`: Opcode info
+// * `operand`: Operand number, 0-4
+// * `kind`: (out) Operand kind
+//
+// # Errors
+//
+// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+IcedStatus iced_op_code_info_op_kind(const IcedOpCodeInfo *info, uint32_t operand, uint32_t *kind);
+
+// Gets the instruction string, eg. `VPBROADCASTB xmm1 {k1}{z}, xmm2/m8`. See `iced_formatter_format()` for the
+// return value.
+//
+// # Arguments
+//
+// * `info`: Opcode info
+// * `buffer`: (out) Instruction string
+// * `size`: Size of `buffer` in bytes
+size_t iced_op_code_info_instruction_string(const IcedOpCodeInfo *info, char *buffer, size_t size);
+
+// Gets the opcode string, eg. `EVEX.128.66.0F38.W0 78 /r`. See `iced_formatter_format()` for the return value.
+//
+// # Arguments
+//
+// * `info`: Opcode info
+// * `buffer`: (out) Opcode string
+// * `size`: Size of `buffer` in bytes
+size_t iced_op_code_info_op_code_string(const IcedOpCodeInfo *info, char *buffer, size_t size);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* ICED_X86_H */
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::decoder::IcedConstantOffsets;
+use crate::error::{set_last_error, status_from_error, IcedStatus, ICED_ERROR_FAILED, ICED_ERROR_NULL_POINTER, ICED_OK};
+use crate::instruction::IcedInstruction;
+use crate::utils::slice_from_raw;
+use iced_x86::{BlockEncoder, BlockEncoderResult, Instruction, InstructionBlock};
+use std::ptr;
+
+// Block encoder options (`BlockEncoderOptions` flags) passed to `iced_block_encoder_encode()`
+
+/// No option is set
+pub const ICED_BLOCK_ENCODER_OPTIONS_NONE: u32 = 0x0000_0000;
+/// By default, branches get updated if the target is too far away, eg. `Jcc SHORT` -> `Jcc NEAR` or if 64-bit mode, `Jcc + JMP [RIP+mem]`. If this option is enabled, no branches are fixed.
+pub const ICED_BLOCK_ENCODER_OPTIONS_DONT_FIX_BRANCHES: u32 = 0x0000_0001;
+/// The block encoder result will contain relocation infos, see `iced_block_encoder_result_reloc_infos()`
+pub const ICED_BLOCK_ENCODER_OPTIONS_RETURN_RELOC_INFOS: u32 = 0x0000_0002;
+/// The block encoder result will contain the new instruction offsets, see `iced_block_encoder_result_new_instruction_offsets()`
+pub const ICED_BLOCK_ENCODER_OPTIONS_RETURN_NEW_INSTRUCTION_OFFSETS: u32 = 0x0000_0004;
+/// The block encoder result will contain the constant offsets, see `iced_block_encoder_result_constant_offsets()`
+pub const ICED_BLOCK_ENCODER_OPTIONS_RETURN_CONSTANT_OFFSETS: u32 = 0x0000_0008;
+/// Mitigates the Intel JCC erratum (Skylake-family CPUs). `NOP`s are added before branches (`Jcc`, `JMP`, `CALL`, `RET`, etc) and macro-fused pairs (eg. `CMP`/`TEST` + `Jcc`) so they don't cross or end on a 32-byte boundary. The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits).
+pub const ICED_BLOCK_ENCODER_OPTIONS_JCC_ERRATUM: u32 = 0x0000_0010;
+
+/// Relocation info returned by the block encoder if `ICED_BLOCK_ENCODER_OPTIONS_RETURN_RELOC_INFOS` is used
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct IcedRelocInfo {
+	/// Address
+	pub address: u64,
+	/// Relocation kind (a `RelocKind` enum value)
+	pub kind: u32,
+}
+
+/// Result of `iced_block_encoder_encode()`. Freed by `iced_block_encoder_result_free()`.
+#[derive(Debug)]
+pub struct IcedBlockEncoderResult {
+	result: BlockEncoderResult,
+	reloc_infos: Vec<IcedRelocInfo>,
+	constant_offsets: Vec<IcedConstantOffsets>,
+}
+
+/// Encodes instructions. Any number of branches can be part of the block. Branches are fixed so they can reach
+/// their targets, eg. short branches are converted to near branches if needed.
+///
+/// # Arguments
+///
+/// * `bitness`: 16, 32 or 64
+/// * `instructions`: Instructions to encode. It can be null if `count` is 0.
+/// * `count`: Number of instructions
+/// * `rip`: Base IP of all encoded instructions
+/// * `options`: Encoder options (`BlockEncoderOptions` flags), eg. `ICED_BLOCK_ENCODER_OPTIONS_NONE`
+/// * `result`: (out) Encoded instructions. Free it with `iced_block_encoder_result_free()`.
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_FAILED` if the instructions couldn't be encoded, see `iced_last_error()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_block_encoder_encode(
+	bitness: u32, instructions: *const IcedInstruction, count: usize, rip: u64, options: u32, result: *mut *mut IcedBlockEncoderResult,
+) -> IcedStatus {
+	let result = match unsafe { result.as_mut() } {
+		Some(result) => result,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	*result = ptr::null_mut();
+	let instructions = match unsafe { slice_from_raw(instructions.cast::<Instruction>(), count) } {
+		Some(instructions) => instructions,
+		None => {
+			set_last_error("instructions is null");
+			return ICED_ERROR_NULL_POINTER;
+		}
+	};
+	match BlockEncoder::encode(bitness, InstructionBlock::new(instructions, rip), options) {
+		Ok(encoded) => {
+			let reloc_infos = encoded.reloc_infos.iter().map(|info| IcedRelocInfo { address: info.address, kind: info.kind as u32 }).collect();
+			let constant_offsets = encoded.constant_offsets.iter().map(IcedConstantOffsets::from_iced).collect();
+			*result = Box::into_raw(Box::new(IcedBlockEncoderResult { result: encoded, reloc_infos, constant_offsets }));
+			ICED_OK
+		}
+		Err(error) => status_from_error(ICED_ERROR_FAILED, &error),
+	}
+}
+
+/// Frees the result. `result` can be null.
+#[no_mangle]
+pub unsafe extern "C" fn iced_block_encoder_result_free(result: *mut IcedBlockEncoderResult) {
+	if !result.is_null() {
+		drop(unsafe { Box::from_raw(result) });
+	}
+}
+
+/// Gets the base IP of all encoded instructions
+#[no_mangle]
+pub unsafe extern "C" fn iced_block_encoder_result_rip(result: *const IcedBlockEncoderResult) -> u64 {
+	match unsafe { result.as_ref() } {
+		Some(result) => result.result.rip,
+		None => 0,
+	}
+}
+
+/// Gets the encoded bytes. The pointer is valid until the result is freed.
+///
+/// # Arguments
+///
+/// * `result`: Result
+/// * `len`: (out) Number of bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_block_encoder_result_code(result: *const IcedBlockEncoderResult, len: *mut usize) -> *const u8 {
+	match unsafe { (result.as_ref(), len.as_mut()) } {
+		(Some(result), Some(len)) => {
+			*len = result.result.code_buffer.len();
+			result.result.code_buffer.as_ptr()
+		}
+		_ => ptr::null(),
+	}
+}
+
+/// Gets the offsets of the instructions relative to the base IP. It's empty unless
+/// `ICED_BLOCK_ENCODER_OPTIONS_RETURN_NEW_INSTRUCTION_OFFSETS` was used. The pointer is valid until the result is freed.
+///
+/// # Arguments
+///
+/// * `result`: Result
+/// * `count`: (out) Number of offsets
+#[no_mangle]
+pub unsafe extern "C" fn iced_block_encoder_result_new_instruction_offsets(result: *const IcedBlockEncoderResult, count: *mut usize) -> *const u32 {
+	match unsafe { (result.as_ref(), count.as_mut()) } {
+		(Some(result), Some(count)) => {
+			*count = result.result.new_instruction_offsets.len();
+			result.result.new_instruction_offsets.as_ptr()
+		}
+		_ => ptr::null(),
+	}
+}
+
+/// Gets the relocations. It's empty unless `ICED_BLOCK_ENCODER_OPTIONS_RETURN_RELOC_INFOS` was used.
+/// The pointer is valid until the result is freed.
+///
+/// # Arguments
+///
+/// * `result`: Result
+/// * `count`: (out) Number of relocations
+#[no_mangle]
+pub unsafe extern "C" fn iced_block_encoder_result_reloc_infos(result: *const IcedBlockEncoderResult, count: *mut usize) -> *const IcedRelocInfo {
+	match unsafe { (result.as_ref(), count.as_mut()) } {
+		(Some(result), Some(count)) => {
+			*count = result.reloc_infos.len();
+			result.reloc_infos.as_ptr()
+		}
+		_ => ptr::null(),
+	}
+}
+
+/// Gets the constant offsets of each instruction. It's empty unless `ICED_BLOCK_ENCODER_OPTIONS_RETURN_CONSTANT_OFFSETS`
+/// was used. The pointer is valid until the result is freed.
+///
+/// # Arguments
+///
+/// * `result`: Result
+/// * `count`: (out) Number of constant offsets
+#[no_mangle]
+pub unsafe extern "C" fn iced_block_encoder_result_constant_offsets(
+	result: *const IcedBlockEncoderResult, count: *mut usize,
+) -> *const IcedConstantOffsets {
+	match unsafe { (result.as_ref(), count.as_mut()) } {
+		(Some(result), Some(count)) => {
+			*count = result.constant_offsets.len();
+			result.constant_offsets.as_ptr()
+		}
+		_ => ptr::null(),
+	}
+}
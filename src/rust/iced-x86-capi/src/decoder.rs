@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::{set_last_error, status_from_error, IcedStatus, ICED_ERROR_INVALID_ARGUMENT, ICED_ERROR_NULL_POINTER, ICED_OK};
+use crate::instruction::{to_iced, IcedInstruction};
+use crate::utils::slice_from_raw;
+use iced_x86::{ConstantOffsets, Decoder};
+
+// Decoder options (`DecoderOptions` flags) passed to `iced_decoder_new()`
+
+/// No option is enabled
+pub const ICED_DECODER_OPTIONS_NONE: u32 = 0x0000_0000;
+/// Disable some checks for invalid encodings of instructions, eg. most instructions can't use a `LOCK` prefix so if one is found, they're decoded as `Code::INVALID` unless this option is enabled.
+pub const ICED_DECODER_OPTIONS_NO_INVALID_CHECK: u32 = 0x0000_0001;
+/// AMD decoder: allow 16-bit branch/ret instructions in 64-bit mode, no `o64 CALL/JMP FAR [mem], o64 LSS/LFS/LGS`, `UD0` has no modr/m byte, decode `LOCK MOV CR`. The AMD decoder can still decode Intel instructions.
+pub const ICED_DECODER_OPTIONS_AMD: u32 = 0x0000_0002;
+/// Decode opcodes `0F0D` and `0F18-0F1F` as reserved-nop instructions (eg. `Code::Reservednop_rm32_r32_0F1D`)
+pub const ICED_DECODER_OPTIONS_FORCE_RESERVED_NOP: u32 = 0x0000_0004;
+/// Decode `UMOV` instructions
+pub const ICED_DECODER_OPTIONS_UMOV: u32 = 0x0000_0008;
+/// Decode `XBTS`/`IBTS`
+pub const ICED_DECODER_OPTIONS_XBTS: u32 = 0x0000_0010;
+/// Decode `0FA6`/`0FA7` as `CMPXCHG`
+pub const ICED_DECODER_OPTIONS_CMPXCHG486A: u32 = 0x0000_0020;
+/// Decode some old removed FPU instructions (eg. `FRSTPM`)
+pub const ICED_DECODER_OPTIONS_OLD_FPU: u32 = 0x0000_0040;
+/// Decode `PCOMMIT`
+pub const ICED_DECODER_OPTIONS_PCOMMIT: u32 = 0x0000_0080;
+/// Decode 286 `STOREALL`/`LOADALL` (`0F04` and `0F05`)
+pub const ICED_DECODER_OPTIONS_LOADALL286: u32 = 0x0000_0100;
+/// Decode 386 `LOADALL`
+pub const ICED_DECODER_OPTIONS_LOADALL386: u32 = 0x0000_0200;
+/// Decode `CL1INVMB`
+pub const ICED_DECODER_OPTIONS_CL1INVMB: u32 = 0x0000_0400;
+/// Decode `MOV r32,tr` and `MOV tr,r32`
+pub const ICED_DECODER_OPTIONS_MOV_TR: u32 = 0x0000_0800;
+/// Decode `JMPE` instructions
+pub const ICED_DECODER_OPTIONS_JMPE: u32 = 0x0000_1000;
+/// Don't decode `PAUSE`, decode `NOP` instead
+pub const ICED_DECODER_OPTIONS_NO_PAUSE: u32 = 0x0000_2000;
+/// Don't decode `WBNOINVD`, decode `WBINVD` instead
+pub const ICED_DECODER_OPTIONS_NO_WBNOINVD: u32 = 0x0000_4000;
+/// Decode undocumented Intel `RDUDBG` and `WRUDBG` instructions
+pub const ICED_DECODER_OPTIONS_UDBG: u32 = 0x0000_8000;
+/// Don't decode `TZCNT`, decode `BSF` instead
+pub const ICED_DECODER_OPTIONS_NO_MPFX_0FBC: u32 = 0x0001_0000;
+/// Don't decode `LZCNT`, decode `BSR` instead
+pub const ICED_DECODER_OPTIONS_NO_MPFX_0FBD: u32 = 0x0002_0000;
+/// Don't decode `LAHF` and `SAHF` in 64-bit mode
+pub const ICED_DECODER_OPTIONS_NO_LAHF_SAHF_64: u32 = 0x0004_0000;
+/// Decode `MPX` instructions
+pub const ICED_DECODER_OPTIONS_MPX: u32 = 0x0008_0000;
+/// Decode most Cyrix instructions: `FPU`, `EMMI`, `SMM`, `DDI`
+pub const ICED_DECODER_OPTIONS_CYRIX: u32 = 0x0010_0000;
+/// Decode Cyrix `SMINT 0F7E` (Cyrix 6x86 or earlier)
+pub const ICED_DECODER_OPTIONS_CYRIX_SMINT_0F7E: u32 = 0x0020_0000;
+/// Decode Cyrix `DMI` instructions (AMD Geode GX/LX)
+pub const ICED_DECODER_OPTIONS_CYRIX_DMI: u32 = 0x0040_0000;
+/// Decode Centaur `ALTINST`
+pub const ICED_DECODER_OPTIONS_ALTINST: u32 = 0x0080_0000;
+/// Decode Intel Knights Corner instructions (requires the `mvex` feature)
+pub const ICED_DECODER_OPTIONS_KNC: u32 = 0x0100_0000;
+
+/// Decodes 16/32/64-bit x86 instructions. Created by `iced_decoder_new()` and freed by `iced_decoder_free()`.
+pub struct IcedDecoder {
+	// The data is owned by the caller and must outlive the decoder
+	decoder: Decoder<'static>,
+}
+
+impl std::fmt::Debug for IcedDecoder {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("IcedDecoder").field("bitness", &self.decoder.bitness()).field("ip", &self.decoder.ip()).finish_non_exhaustive()
+	}
+}
+
+/// Constant offsets (immediate and displacement offsets and sizes) of an instruction
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct IcedConstantOffsets {
+	/// The offset of the displacement, if any
+	pub displacement_offset: u8,
+	/// Size in bytes of the displacement, or 0 if there's no displacement
+	pub displacement_size: u8,
+	/// The offset of the first immediate, if any
+	pub immediate_offset: u8,
+	/// Size in bytes of the first immediate, or 0 if there's no immediate
+	pub immediate_size: u8,
+	/// The offset of the second immediate, if any
+	pub immediate_offset2: u8,
+	/// Size in bytes of the second immediate, or 0 if there's no second immediate
+	pub immediate_size2: u8,
+}
+
+impl IcedConstantOffsets {
+	pub(crate) fn from_iced(offsets: &ConstantOffsets) -> Self {
+		Self {
+			displacement_offset: offsets.displacement_offset() as u8,
+			displacement_size: offsets.displacement_size() as u8,
+			immediate_offset: offsets.immediate_offset() as u8,
+			immediate_size: offsets.immediate_size() as u8,
+			immediate_offset2: offsets.immediate_offset2() as u8,
+			immediate_size2: offsets.immediate_size2() as u8,
+		}
+	}
+}
+
+/// Creates a decoder. Returns null if `bitness` or `data` is invalid, see `iced_last_error()`.
+///
+/// # Arguments
+///
+/// * `bitness`: 16, 32 or 64
+/// * `data`: Data to decode. It must be valid until the decoder is freed. It can be null if `data_len` is 0.
+/// * `data_len`: Size of `data` in bytes
+/// * `ip`: `RIP` value of the first byte in `data`
+/// * `options`: Decoder options (`DecoderOptions` flags), eg. `ICED_DECODER_OPTIONS_NONE`
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_new(bitness: u32, data: *const u8, data_len: usize, ip: u64, options: u32) -> *mut IcedDecoder {
+	let data = match unsafe { slice_from_raw(data, data_len) } {
+		Some(data) => data,
+		None => {
+			set_last_error("data is null");
+			return std::ptr::null_mut();
+		}
+	};
+	match Decoder::try_with_ip(bitness, data, ip, options) {
+		Ok(decoder) => Box::into_raw(Box::new(IcedDecoder { decoder })),
+		Err(error) => {
+			let _ = status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error);
+			std::ptr::null_mut()
+		}
+	}
+}
+
+/// Frees a decoder. `decoder` can be null.
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_free(decoder: *mut IcedDecoder) {
+	if !decoder.is_null() {
+		drop(unsafe { Box::from_raw(decoder) });
+	}
+}
+
+/// Gets the bitness (16, 32 or 64)
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_bitness(decoder: *const IcedDecoder) -> u32 {
+	match unsafe { decoder.as_ref() } {
+		Some(decoder) => decoder.decoder.bitness(),
+		None => 0,
+	}
+}
+
+/// Gets the current `IP`/`EIP`/`RIP` value, see also `iced_decoder_position()`
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_ip(decoder: *const IcedDecoder) -> u64 {
+	match unsafe { decoder.as_ref() } {
+		Some(decoder) => decoder.decoder.ip(),
+		None => 0,
+	}
+}
+
+/// Sets the current `IP`/`EIP`/`RIP` value. This function only updates the IP value, it doesn't change the data position,
+/// use `iced_decoder_set_position()` to change the position.
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_set_ip(decoder: *mut IcedDecoder, ip: u64) -> IcedStatus {
+	match unsafe { decoder.as_mut() } {
+		Some(decoder) => {
+			decoder.decoder.set_ip(ip);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the current data position. This value is always <= `iced_decoder_max_position()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_position(decoder: *const IcedDecoder) -> usize {
+	match unsafe { decoder.as_ref() } {
+		Some(decoder) => decoder.decoder.position(),
+		None => 0,
+	}
+}
+
+/// Sets the current data position, which is the index into the data passed to `iced_decoder_new()`.
+/// This value is always <= `iced_decoder_max_position()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_set_position(decoder: *mut IcedDecoder, position: usize) -> IcedStatus {
+	match unsafe { decoder.as_mut() } {
+		Some(decoder) => match decoder.decoder.set_position(position) {
+			Ok(()) => ICED_OK,
+			Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+		},
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the max value that can be passed to `iced_decoder_set_position()`. This is the size of the data that gets
+/// decoded to instructions and it's the length of the data that was passed to `iced_decoder_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_max_position(decoder: *const IcedDecoder) -> usize {
+	match unsafe { decoder.as_ref() } {
+		Some(decoder) => decoder.decoder.max_position(),
+		None => 0,
+	}
+}
+
+/// Returns `true` if there's at least one more byte to decode. It doesn't verify that the next instruction is valid,
+/// it only checks if there's at least one more byte to read.
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_can_decode(decoder: *const IcedDecoder) -> bool {
+	match unsafe { decoder.as_ref() } {
+		Some(decoder) => decoder.decoder.can_decode(),
+		None => false,
+	}
+}
+
+/// Gets the last decoder error (a `DecoderError` enum value). Unless you need to know the reason it failed,
+/// it's better to check `iced_instruction_is_invalid()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_last_error(decoder: *const IcedDecoder) -> u32 {
+	match unsafe { decoder.as_ref() } {
+		Some(decoder) => decoder.decoder.last_error() as u32,
+		None => 0,
+	}
+}
+
+/// Decodes the next instruction. If there's an error, the returned instruction is invalid
+/// (`iced_instruction_is_invalid()`) and `iced_decoder_last_error()` returns the reason.
+///
+/// # Arguments
+///
+/// * `decoder`: Decoder
+/// * `instruction`: (out) Decoded instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_decode(decoder: *mut IcedDecoder, instruction: *mut IcedInstruction) -> IcedStatus {
+	match unsafe { (decoder.as_mut(), instruction.as_mut()) } {
+		(Some(decoder), Some(instruction)) => {
+			*instruction = IcedInstruction::from_iced(decoder.decoder.decode());
+			ICED_OK
+		}
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Decodes at most `count` instructions and stops if there's no more data. Returns the number of decoded instructions.
+///
+/// # Arguments
+///
+/// * `decoder`: Decoder
+/// * `instructions`: (out) Decoded instructions
+/// * `count`: Max number of instructions to decode
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_decode_many(decoder: *mut IcedDecoder, instructions: *mut IcedInstruction, count: usize) -> usize {
+	let decoder = match unsafe { decoder.as_mut() } {
+		Some(decoder) => decoder,
+		None => return 0,
+	};
+	if instructions.is_null() {
+		return 0;
+	}
+	let mut decoded = 0;
+	while decoded < count && decoder.decoder.can_decode() {
+		unsafe { *instructions.add(decoded) = IcedInstruction::from_iced(decoder.decoder.decode()) };
+		decoded += 1;
+	}
+	decoded
+}
+
+/// Gets the offsets of the constants (memory displacement and immediate) in the decoded instruction.
+/// The caller can check if there are any relocations at those addresses.
+///
+/// # Arguments
+///
+/// * `decoder`: Decoder
+/// * `instruction`: The latest instruction that was decoded by this decoder
+/// * `offsets`: (out) Constant offsets
+#[no_mangle]
+pub unsafe extern "C" fn iced_decoder_get_constant_offsets(
+	decoder: *const IcedDecoder, instruction: *const IcedInstruction, offsets: *mut IcedConstantOffsets,
+) -> IcedStatus {
+	match unsafe { (decoder.as_ref(), to_iced(instruction), offsets.as_mut()) } {
+		(Some(decoder), Some(instruction), Some(offsets)) => {
+			*offsets = IcedConstantOffsets::from_iced(&decoder.decoder.get_constant_offsets(instruction));
+			ICED_OK
+		}
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
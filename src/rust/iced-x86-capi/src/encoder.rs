@@ -0,0 +1,263 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::decoder::IcedConstantOffsets;
+use crate::error::{status_from_error, IcedStatus, ICED_ERROR_FAILED, ICED_ERROR_INVALID_ARGUMENT, ICED_ERROR_NULL_POINTER, ICED_OK};
+use crate::instruction::{to_iced, IcedInstruction};
+use iced_x86::Encoder;
+
+/// Encodes instructions decoded by the decoder or instructions created by the user.
+/// Created by `iced_encoder_new()` and freed by `iced_encoder_free()`.
+///
+/// All encoded bytes are appended to an internal buffer, see `iced_encoder_buffer()` and `iced_encoder_clear_buffer()`.
+pub struct IcedEncoder {
+	encoder: Encoder,
+}
+
+impl std::fmt::Debug for IcedEncoder {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("IcedEncoder").field("bitness", &self.encoder.bitness()).finish_non_exhaustive()
+	}
+}
+
+/// Creates an encoder. Returns null if `bitness` is invalid, see `iced_last_error()`.
+///
+/// # Arguments
+///
+/// * `bitness`: 16, 32 or 64
+#[no_mangle]
+pub extern "C" fn iced_encoder_new(bitness: u32) -> *mut IcedEncoder {
+	match Encoder::try_new(bitness) {
+		Ok(encoder) => Box::into_raw(Box::new(IcedEncoder { encoder })),
+		Err(error) => {
+			let _ = status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error);
+			std::ptr::null_mut()
+		}
+	}
+}
+
+/// Frees an encoder. `encoder` can be null.
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_free(encoder: *mut IcedEncoder) {
+	if !encoder.is_null() {
+		drop(unsafe { Box::from_raw(encoder) });
+	}
+}
+
+/// Encodes an instruction and appends the bytes to the internal buffer
+///
+/// # Arguments
+///
+/// * `encoder`: Encoder
+/// * `instruction`: Instruction to encode
+/// * `rip`: `RIP` of the encoded instruction
+/// * `len`: (out) Size of the encoded instruction. It can be null.
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_FAILED` if the instruction can't be encoded, see `iced_last_error()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_encode(
+	encoder: *mut IcedEncoder, instruction: *const IcedInstruction, rip: u64, len: *mut usize,
+) -> IcedStatus {
+	let (encoder, instruction) = match unsafe { (encoder.as_mut(), to_iced(instruction)) } {
+		(Some(encoder), Some(instruction)) => (encoder, instruction),
+		_ => return ICED_ERROR_NULL_POINTER,
+	};
+	match encoder.encoder.encode(instruction, rip) {
+		Ok(size) => {
+			if let Some(len) = unsafe { len.as_mut() } {
+				*len = size;
+			}
+			ICED_OK
+		}
+		Err(error) => status_from_error(ICED_ERROR_FAILED, &error),
+	}
+}
+
+/// Gets all encoded bytes. The returned pointer is valid until the next call to a function that modifies the encoder.
+///
+/// # Arguments
+///
+/// * `encoder`: Encoder
+/// * `len`: (out) Number of bytes in the buffer
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_buffer(encoder: *mut IcedEncoder, len: *mut usize) -> *const u8 {
+	let (encoder, len) = match unsafe { (encoder.as_mut(), len.as_mut()) } {
+		(Some(encoder), Some(len)) => (encoder, len),
+		_ => return std::ptr::null(),
+	};
+	// Moving the vector doesn't move its data so the pointer is still valid after the buffer is given back
+	let buffer = encoder.encoder.take_buffer();
+	*len = buffer.len();
+	let data = buffer.as_ptr();
+	encoder.encoder.set_buffer(buffer);
+	data
+}
+
+/// Removes all bytes from the internal buffer
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_clear_buffer(encoder: *mut IcedEncoder) -> IcedStatus {
+	match unsafe { encoder.as_mut() } {
+		Some(encoder) => {
+			let mut buffer = encoder.encoder.take_buffer();
+			buffer.clear();
+			encoder.encoder.set_buffer(buffer);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the offsets of the constants (memory displacement and immediate) in the encoded instruction.
+/// The caller can use this information to add relocations if needed.
+///
+/// # Arguments
+///
+/// * `encoder`: Encoder
+/// * `offsets`: (out) Constant offsets of the last encoded instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_get_constant_offsets(encoder: *const IcedEncoder, offsets: *mut IcedConstantOffsets) -> IcedStatus {
+	match unsafe { (encoder.as_ref(), offsets.as_mut()) } {
+		(Some(encoder), Some(offsets)) => {
+			*offsets = IcedConstantOffsets::from_iced(&encoder.encoder.get_constant_offsets());
+			ICED_OK
+		}
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the bitness (16, 32 or 64)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_bitness(encoder: *const IcedEncoder) -> u32 {
+	match unsafe { encoder.as_ref() } {
+		Some(encoder) => encoder.encoder.bitness(),
+		None => 0,
+	}
+}
+
+/// Checks if 2-byte VEX encoding is disabled, which encodes all VEX instructions with the 3-byte VEX encoding (default: `false`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_prevent_vex2(encoder: *const IcedEncoder) -> bool {
+	match unsafe { encoder.as_ref() } {
+		Some(encoder) => encoder.encoder.prevent_vex2(),
+		None => false,
+	}
+}
+
+/// Disables 2-byte VEX encoding and encodes all VEX instructions with the 3-byte VEX encoding (default: `false`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_set_prevent_vex2(encoder: *mut IcedEncoder, value: bool) -> IcedStatus {
+	match unsafe { encoder.as_mut() } {
+		Some(encoder) => {
+			encoder.encoder.set_prevent_vex2(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the value of the `VEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_vex_wig(encoder: *const IcedEncoder) -> u32 {
+	match unsafe { encoder.as_ref() } {
+		Some(encoder) => encoder.encoder.vex_wig(),
+		None => 0,
+	}
+}
+
+/// Sets the value of the `VEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_set_vex_wig(encoder: *mut IcedEncoder, value: u32) -> IcedStatus {
+	match unsafe { encoder.as_mut() } {
+		Some(encoder) => {
+			encoder.encoder.set_vex_wig(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the value of the `VEX.L` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_vex_lig(encoder: *const IcedEncoder) -> u32 {
+	match unsafe { encoder.as_ref() } {
+		Some(encoder) => encoder.encoder.vex_lig(),
+		None => 0,
+	}
+}
+
+/// Sets the value of the `VEX.L` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_set_vex_lig(encoder: *mut IcedEncoder, value: u32) -> IcedStatus {
+	match unsafe { encoder.as_mut() } {
+		Some(encoder) => {
+			encoder.encoder.set_vex_lig(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the value of the `EVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_evex_wig(encoder: *const IcedEncoder) -> u32 {
+	match unsafe { encoder.as_ref() } {
+		Some(encoder) => encoder.encoder.evex_wig(),
+		None => 0,
+	}
+}
+
+/// Sets the value of the `EVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_set_evex_wig(encoder: *mut IcedEncoder, value: u32) -> IcedStatus {
+	match unsafe { encoder.as_mut() } {
+		Some(encoder) => {
+			encoder.encoder.set_evex_wig(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the value of the `EVEX.L'L` bits to use if it's an instruction that ignores the bits (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_evex_lig(encoder: *const IcedEncoder) -> u32 {
+	match unsafe { encoder.as_ref() } {
+		Some(encoder) => encoder.encoder.evex_lig(),
+		None => 0,
+	}
+}
+
+/// Sets the value of the `EVEX.L'L` bits to use if it's an instruction that ignores the bits (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_set_evex_lig(encoder: *mut IcedEncoder, value: u32) -> IcedStatus {
+	match unsafe { encoder.as_mut() } {
+		Some(encoder) => {
+			encoder.encoder.set_evex_lig(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the value of the `MVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_mvex_wig(encoder: *const IcedEncoder) -> u32 {
+	match unsafe { encoder.as_ref() } {
+		Some(encoder) => encoder.encoder.mvex_wig(),
+		None => 0,
+	}
+}
+
+/// Sets the value of the `MVEX.W` bit to use if it's an instruction that ignores the bit (default: 0)
+#[no_mangle]
+pub unsafe extern "C" fn iced_encoder_set_mvex_wig(encoder: *mut IcedEncoder, value: u32) -> IcedStatus {
+	match unsafe { encoder.as_mut() } {
+		Some(encoder) => {
+			encoder.encoder.set_mvex_wig(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
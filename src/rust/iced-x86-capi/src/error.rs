@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use iced_x86::IcedError;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+/// Return value of functions that can fail. It's [`ICED_OK`] (0) if the function succeeded.
+///
+/// [`ICED_OK`]: constant.ICED_OK.html
+pub type IcedStatus = u32;
+
+/// Success
+pub const ICED_OK: IcedStatus = 0;
+/// A required pointer is null
+pub const ICED_ERROR_NULL_POINTER: IcedStatus = 1;
+/// An argument is invalid, eg. an invalid enum value or operand index
+pub const ICED_ERROR_INVALID_ARGUMENT: IcedStatus = 2;
+/// The operation failed, eg. the instruction couldn't be encoded. Call [`iced_last_error()`] to get the error message.
+///
+/// [`iced_last_error()`]: fn.iced_last_error.html
+pub const ICED_ERROR_FAILED: IcedStatus = 3;
+
+thread_local! {
+	static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+pub(crate) fn set_last_error(message: &str) {
+	let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+	LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+}
+
+pub(crate) fn status_from_error(status: IcedStatus, error: &IcedError) -> IcedStatus {
+	set_last_error(&error.to_string());
+	status
+}
+
+/// Gets the error message of the last function that failed on the current thread. It's never null.
+/// The string is valid until the next function call that fails on the same thread.
+#[no_mangle]
+pub extern "C" fn iced_last_error() -> *const c_char {
+	LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+}
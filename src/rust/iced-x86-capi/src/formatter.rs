@@ -0,0 +1,989 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::{set_last_error, status_from_error, IcedStatus, ICED_ERROR_INVALID_ARGUMENT, ICED_ERROR_NULL_POINTER, ICED_OK};
+use crate::instruction::{to_iced, IcedInstruction};
+use crate::utils::{copy_str, to_enum};
+use iced_x86::{
+	FastFormatter, FastFormatterOptions, Formatter, FormatterOptions, FormatterOutput, FormatterTextKind, GasFormatter, Instruction, IntelFormatter,
+	MasmFormatter, MemorySize, NasmFormatter, Register, SymbolResolver, SymbolResult,
+};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+/// GNU Assembler (AT&T) syntax
+pub const ICED_FORMATTER_SYNTAX_GAS: u32 = 0;
+/// Intel XED syntax
+pub const ICED_FORMATTER_SYNTAX_INTEL: u32 = 1;
+/// masm syntax
+pub const ICED_FORMATTER_SYNTAX_MASM: u32 = 2;
+/// nasm syntax
+pub const ICED_FORMATTER_SYNTAX_NASM: u32 = 3;
+
+// Formatter options, see `iced_formatter_get_option()` and `iced_formatter_set_option()`
+
+/// `uppercase_prefixes` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_UPPERCASE_PREFIXES: u32 = 0;
+/// `uppercase_mnemonics` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_UPPERCASE_MNEMONICS: u32 = 1;
+/// `uppercase_registers` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_UPPERCASE_REGISTERS: u32 = 2;
+/// `uppercase_keywords` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_UPPERCASE_KEYWORDS: u32 = 3;
+/// `uppercase_decorators` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_UPPERCASE_DECORATORS: u32 = 4;
+/// `uppercase_all` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_UPPERCASE_ALL: u32 = 5;
+/// `space_after_operand_separator` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR: u32 = 6;
+/// `space_after_memory_bracket` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SPACE_AFTER_MEMORY_BRACKET: u32 = 7;
+/// `space_between_memory_add_operators` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_ADD_OPERATORS: u32 = 8;
+/// `space_between_memory_mul_operators` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_MUL_OPERATORS: u32 = 9;
+/// `scale_before_index` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SCALE_BEFORE_INDEX: u32 = 10;
+/// `always_show_scale` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_ALWAYS_SHOW_SCALE: u32 = 11;
+/// `always_show_segment_register` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER: u32 = 12;
+/// `show_zero_displacements` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SHOW_ZERO_DISPLACEMENTS: u32 = 13;
+/// `leading_zeros` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_LEADING_ZEROS: u32 = 14;
+/// `uppercase_hex` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_UPPERCASE_HEX: u32 = 15;
+/// `small_hex_numbers_in_decimal` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SMALL_HEX_NUMBERS_IN_DECIMAL: u32 = 16;
+/// `add_leading_zero_to_hex_numbers` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_ADD_LEADING_ZERO_TO_HEX_NUMBERS: u32 = 17;
+/// `branch_leading_zeros` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_BRANCH_LEADING_ZEROS: u32 = 18;
+/// `signed_immediate_operands` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SIGNED_IMMEDIATE_OPERANDS: u32 = 19;
+/// `signed_memory_displacements` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SIGNED_MEMORY_DISPLACEMENTS: u32 = 20;
+/// `displacement_leading_zeros` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_DISPLACEMENT_LEADING_ZEROS: u32 = 21;
+/// `rip_relative_addresses` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES: u32 = 22;
+/// `show_branch_size` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SHOW_BRANCH_SIZE: u32 = 23;
+/// `use_pseudo_ops` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_USE_PSEUDO_OPS: u32 = 24;
+/// `show_symbol_address` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS: u32 = 25;
+/// `gas_naked_registers` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_GAS_NAKED_REGISTERS: u32 = 26;
+/// `gas_show_mnemonic_size_suffix` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_GAS_SHOW_MNEMONIC_SIZE_SUFFIX: u32 = 27;
+/// `gas_space_after_memory_operand_comma` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_GAS_SPACE_AFTER_MEMORY_OPERAND_COMMA: u32 = 28;
+/// `masm_add_ds_prefix32` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_MASM_ADD_DS_PREFIX32: u32 = 29;
+/// `masm_symbol_displ_in_brackets` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_MASM_SYMBOL_DISPL_IN_BRACKETS: u32 = 30;
+/// `masm_displ_in_brackets` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_MASM_DISPL_IN_BRACKETS: u32 = 31;
+/// `nasm_show_sign_extended_immediate_size` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_NASM_SHOW_SIGN_EXTENDED_IMMEDIATE_SIZE: u32 = 32;
+/// `prefer_st0` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_PREFER_ST0: u32 = 33;
+/// `show_useless_prefixes` option, a `bool` (0 or 1)
+pub const ICED_FORMATTER_OPTION_SHOW_USELESS_PREFIXES: u32 = 34;
+/// `first_operand_char_index` option, a `uint32_t`
+pub const ICED_FORMATTER_OPTION_FIRST_OPERAND_CHAR_INDEX: u32 = 35;
+/// `tab_size` option, a `uint32_t`
+pub const ICED_FORMATTER_OPTION_TAB_SIZE: u32 = 36;
+/// `hex_digit_group_size` option, a `uint32_t`
+pub const ICED_FORMATTER_OPTION_HEX_DIGIT_GROUP_SIZE: u32 = 37;
+/// `decimal_digit_group_size` option, a `uint32_t`
+pub const ICED_FORMATTER_OPTION_DECIMAL_DIGIT_GROUP_SIZE: u32 = 38;
+/// `octal_digit_group_size` option, a `uint32_t`
+pub const ICED_FORMATTER_OPTION_OCTAL_DIGIT_GROUP_SIZE: u32 = 39;
+/// `binary_digit_group_size` option, a `uint32_t`
+pub const ICED_FORMATTER_OPTION_BINARY_DIGIT_GROUP_SIZE: u32 = 40;
+/// `number_base` option, a `NumberBase` enum value
+pub const ICED_FORMATTER_OPTION_NUMBER_BASE: u32 = 41;
+/// `memory_size_options` option, a `MemorySizeOptions` enum value
+pub const ICED_FORMATTER_OPTION_MEMORY_SIZE_OPTIONS: u32 = 42;
+/// `cc_b` option, a `CC_b` enum value
+pub const ICED_FORMATTER_OPTION_CC_B: u32 = 43;
+/// `cc_ae` option, a `CC_ae` enum value
+pub const ICED_FORMATTER_OPTION_CC_AE: u32 = 44;
+/// `cc_e` option, a `CC_e` enum value
+pub const ICED_FORMATTER_OPTION_CC_E: u32 = 45;
+/// `cc_ne` option, a `CC_ne` enum value
+pub const ICED_FORMATTER_OPTION_CC_NE: u32 = 46;
+/// `cc_be` option, a `CC_be` enum value
+pub const ICED_FORMATTER_OPTION_CC_BE: u32 = 47;
+/// `cc_a` option, a `CC_a` enum value
+pub const ICED_FORMATTER_OPTION_CC_A: u32 = 48;
+/// `cc_p` option, a `CC_p` enum value
+pub const ICED_FORMATTER_OPTION_CC_P: u32 = 49;
+/// `cc_np` option, a `CC_np` enum value
+pub const ICED_FORMATTER_OPTION_CC_NP: u32 = 50;
+/// `cc_l` option, a `CC_l` enum value
+pub const ICED_FORMATTER_OPTION_CC_L: u32 = 51;
+/// `cc_ge` option, a `CC_ge` enum value
+pub const ICED_FORMATTER_OPTION_CC_GE: u32 = 52;
+/// `cc_le` option, a `CC_le` enum value
+pub const ICED_FORMATTER_OPTION_CC_LE: u32 = 53;
+/// `cc_g` option, a `CC_g` enum value
+pub const ICED_FORMATTER_OPTION_CC_G: u32 = 54;
+
+/// `hex_prefix` string option
+pub const ICED_FORMATTER_STRING_OPTION_HEX_PREFIX: u32 = 0;
+/// `hex_suffix` string option
+pub const ICED_FORMATTER_STRING_OPTION_HEX_SUFFIX: u32 = 1;
+/// `decimal_prefix` string option
+pub const ICED_FORMATTER_STRING_OPTION_DECIMAL_PREFIX: u32 = 2;
+/// `decimal_suffix` string option
+pub const ICED_FORMATTER_STRING_OPTION_DECIMAL_SUFFIX: u32 = 3;
+/// `octal_prefix` string option
+pub const ICED_FORMATTER_STRING_OPTION_OCTAL_PREFIX: u32 = 4;
+/// `octal_suffix` string option
+pub const ICED_FORMATTER_STRING_OPTION_OCTAL_SUFFIX: u32 = 5;
+/// `binary_prefix` string option
+pub const ICED_FORMATTER_STRING_OPTION_BINARY_PREFIX: u32 = 6;
+/// `binary_suffix` string option
+pub const ICED_FORMATTER_STRING_OPTION_BINARY_SUFFIX: u32 = 7;
+/// `digit_separator` string option
+pub const ICED_FORMATTER_STRING_OPTION_DIGIT_SEPARATOR: u32 = 8;
+
+/// Symbol returned by an [`IcedSymbolResolver`]
+///
+/// [`IcedSymbolResolver`]: struct.IcedSymbolResolver.html
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct IcedSymbolResult {
+	/// The address of the symbol. It's initialized to the address passed to the resolver.
+	pub address: u64,
+	/// NUL-terminated UTF-8 symbol. It's copied by the formatter so it only needs to be valid until the resolver returns.
+	pub text: *const c_char,
+	/// Text kind (a `FormatterTextKind` enum value), default is `FormatterTextKind::Label`
+	pub kind: u32,
+	/// Symbol flags (`SymbolFlags`), default is 0
+	pub flags: u32,
+	/// Symbol size (a `MemorySize` enum value) or 0 (`MemorySize::Unknown`) if it's unknown
+	pub symbol_size: u32,
+}
+
+/// Symbol resolver callback used by the formatters
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct IcedSymbolResolver {
+	/// Passed to `symbol`
+	pub user_data: *mut c_void,
+	/// Tries to resolve a symbol. Returns `true` and initializes `result` if a symbol was found.
+	///
+	/// * `user_data`: `user_data` field
+	/// * `instruction`: Instruction
+	/// * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+	/// * `instruction_operand`: Instruction operand number, 0-based, or -1 if it's an operand created by the formatter.
+	/// * `address`: Address
+	/// * `address_size`: Size of `address` in bytes (eg. 1, 2, 4 or 8)
+	/// * `result`: (out) Symbol
+	pub symbol: Option<
+		unsafe extern "C" fn(
+			user_data: *mut c_void,
+			instruction: *const IcedInstruction,
+			operand: u32,
+			instruction_operand: i32,
+			address: u64,
+			address_size: u32,
+			result: *mut IcedSymbolResult,
+		) -> bool,
+	>,
+}
+
+/// Formatter output callback used by `iced_formatter_format_output()`
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct IcedFormatterOutput {
+	/// Passed to `write`
+	pub user_data: *mut c_void,
+	/// Writes text
+	///
+	/// * `user_data`: `user_data` field
+	/// * `text`: NUL-terminated UTF-8 text. It's only valid until the callback returns.
+	/// * `len`: Length of `text` in bytes, not including the NUL byte
+	/// * `kind`: Text kind (a `FormatterTextKind` enum value)
+	pub write: Option<unsafe extern "C" fn(user_data: *mut c_void, text: *const c_char, len: usize, kind: u32)>,
+}
+
+struct SymbolResolverAdapter {
+	resolver: IcedSymbolResolver,
+}
+
+impl SymbolResolver for SymbolResolverAdapter {
+	fn symbol(
+		&mut self, instruction: &Instruction, operand: u32, instruction_operand: Option<u32>, address: u64, address_size: u32,
+	) -> Option<SymbolResult<'_>> {
+		let symbol = self.resolver.symbol?;
+		let mut result =
+			IcedSymbolResult { address, text: ptr::null(), kind: FormatterTextKind::Label as u32, flags: 0, symbol_size: MemorySize::Unknown as u32 };
+		let instruction_operand = instruction_operand.map_or(-1, |op| op as i32);
+		let instruction: *const Instruction = instruction;
+		let instruction = instruction.cast::<IcedInstruction>();
+		// SAFETY: The caller of `iced_formatter_new()` guarantees that the callback is valid
+		if !unsafe { symbol(self.resolver.user_data, instruction, operand, instruction_operand, address, address_size, &mut result) }
+			|| result.text.is_null()
+		{
+			return None;
+		}
+		// SAFETY: The callback returned a NUL-terminated string
+		let text = unsafe { CStr::from_ptr(result.text) }.to_string_lossy().into_owned();
+		let kind = to_enum(result.kind, "FormatterTextKind").unwrap_or(FormatterTextKind::Label);
+		let mut symbol = SymbolResult::with_string_kind_flags(result.address, text, kind, result.flags);
+		symbol.symbol_size = match to_enum(result.symbol_size, "MemorySize") {
+			Ok(MemorySize::Unknown) | Err(_) => None,
+			Ok(size) => Some(size),
+		};
+		Some(symbol)
+	}
+}
+
+fn create_symbol_resolver(resolver: *const IcedSymbolResolver) -> Option<Box<dyn SymbolResolver>> {
+	// SAFETY: The caller passes in a valid pointer or null
+	match unsafe { resolver.as_ref() } {
+		Some(resolver) if resolver.symbol.is_some() => Some(Box::new(SymbolResolverAdapter { resolver: *resolver })),
+		_ => None,
+	}
+}
+
+struct FormatterOutputAdapter {
+	output: IcedFormatterOutput,
+	buffer: Vec<u8>,
+}
+
+impl FormatterOutput for FormatterOutputAdapter {
+	fn write(&mut self, text: &str, kind: FormatterTextKind) {
+		if let Some(write) = self.output.write {
+			self.buffer.clear();
+			self.buffer.extend_from_slice(text.as_bytes());
+			self.buffer.push(0);
+			// SAFETY: The caller of `iced_formatter_format_output()` guarantees that the callback is valid
+			unsafe { write(self.output.user_data, self.buffer.as_ptr().cast::<c_char>(), text.len(), kind as u32) };
+		}
+	}
+}
+
+/// Formats instructions using GNU Assembler, Intel XED, masm or nasm syntax. Created by `iced_formatter_new()`
+/// and freed by `iced_formatter_free()`.
+pub struct IcedFormatter {
+	formatter: Box<dyn Formatter>,
+	output: String,
+}
+
+impl std::fmt::Debug for IcedFormatter {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("IcedFormatter").finish_non_exhaustive()
+	}
+}
+
+/// Creates a formatter. Returns null if `syntax` is invalid.
+///
+/// # Arguments
+///
+/// * `syntax`: Syntax, eg. `ICED_FORMATTER_SYNTAX_NASM`
+/// * `symbol_resolver`: Symbol resolver or null. It's copied so it doesn't need to outlive this call but
+///   `user_data` must be valid until the formatter is freed.
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_new(syntax: u32, symbol_resolver: *const IcedSymbolResolver) -> *mut IcedFormatter {
+	let symbol_resolver = create_symbol_resolver(symbol_resolver);
+	let formatter: Box<dyn Formatter> = match syntax {
+		ICED_FORMATTER_SYNTAX_GAS => Box::new(GasFormatter::with_options(symbol_resolver, None)),
+		ICED_FORMATTER_SYNTAX_INTEL => Box::new(IntelFormatter::with_options(symbol_resolver, None)),
+		ICED_FORMATTER_SYNTAX_MASM => Box::new(MasmFormatter::with_options(symbol_resolver, None)),
+		ICED_FORMATTER_SYNTAX_NASM => Box::new(NasmFormatter::with_options(symbol_resolver, None)),
+		_ => {
+			set_last_error("Invalid formatter syntax");
+			return ptr::null_mut();
+		}
+	};
+	Box::into_raw(Box::new(IcedFormatter { formatter, output: String::new() }))
+}
+
+/// Frees a formatter. `formatter` can be null.
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_free(formatter: *mut IcedFormatter) {
+	if !formatter.is_null() {
+		drop(unsafe { Box::from_raw(formatter) });
+	}
+}
+
+/// Formats the whole instruction: prefixes, mnemonic, operands.
+///
+/// The string is copied to `buffer` and is truncated if it's too small. It returns the length of the formatted string
+/// (not including the NUL byte) which can be greater than or equal to `size` if it was truncated (like `snprintf()`).
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `buffer`: (out) Formatted string, always NUL-terminated unless `size` is 0. It can be null if `size` is 0.
+/// * `size`: Size of `buffer` in bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_format(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, buffer: *mut c_char, size: usize,
+) -> usize {
+	match unsafe { (formatter.as_mut(), to_iced(instruction)) } {
+		(Some(formatter), Some(instruction)) => {
+			formatter.output.clear();
+			formatter.formatter.format(instruction, &mut formatter.output);
+			unsafe { copy_str(&formatter.output, buffer, size) }
+		}
+		_ => unsafe { copy_str("", buffer, size) },
+	}
+}
+
+/// Formats the whole instruction and writes the text and text kinds to `output`
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `output`: Output
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_format_output(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, output: *const IcedFormatterOutput,
+) -> IcedStatus {
+	match unsafe { (formatter.as_mut(), to_iced(instruction), output.as_ref()) } {
+		(Some(formatter), Some(instruction), Some(output)) => {
+			let mut output = FormatterOutputAdapter { output: *output, buffer: Vec::new() };
+			formatter.formatter.format(instruction, &mut output);
+			ICED_OK
+		}
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Formats the mnemonic and/or any prefixes, see `iced_formatter_format()` for the return value
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `options`: Options (`FormatMnemonicOptions` flags), eg. 0 (`FormatMnemonicOptions::NONE`)
+/// * `buffer`: (out) Formatted string
+/// * `size`: Size of `buffer` in bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_format_mnemonic(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, options: u32, buffer: *mut c_char, size: usize,
+) -> usize {
+	match unsafe { (formatter.as_mut(), to_iced(instruction)) } {
+		(Some(formatter), Some(instruction)) => {
+			formatter.output.clear();
+			formatter.formatter.format_mnemonic_options(instruction, &mut formatter.output, options);
+			unsafe { copy_str(&formatter.output, buffer, size) }
+		}
+		_ => unsafe { copy_str("", buffer, size) },
+	}
+}
+
+/// Gets the number of operands that will be formatted. A formatter can add and remove operands.
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_operand_count(formatter: *mut IcedFormatter, instruction: *const IcedInstruction) -> u32 {
+	match unsafe { (formatter.as_mut(), to_iced(instruction)) } {
+		(Some(formatter), Some(instruction)) => formatter.formatter.operand_count(instruction),
+		_ => 0,
+	}
+}
+
+/// Formats an operand
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+///   See `iced_formatter_operand_count()`
+/// * `buffer`: (out) Formatted string, see `iced_formatter_format()`
+/// * `size`: Size of `buffer` in bytes
+/// * `len`: (out) Length of the formatted string, see the return value of `iced_formatter_format()`. It can be null.
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_format_operand(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, operand: u32, buffer: *mut c_char, size: usize, len: *mut usize,
+) -> IcedStatus {
+	let (formatter, instruction) = match unsafe { (formatter.as_mut(), to_iced(instruction)) } {
+		(Some(formatter), Some(instruction)) => (formatter, instruction),
+		_ => return ICED_ERROR_NULL_POINTER,
+	};
+	formatter.output.clear();
+	let status = match formatter.formatter.format_operand(instruction, &mut formatter.output, operand) {
+		Ok(()) => ICED_OK,
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	};
+	let text_len = unsafe { copy_str(&formatter.output, buffer, size) };
+	if let Some(len) = unsafe { len.as_mut() } {
+		*len = text_len;
+	}
+	status
+}
+
+/// Formats an operand separator, see `iced_formatter_format()` for the return value
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_format_operand_separator(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, buffer: *mut c_char, size: usize,
+) -> usize {
+	match unsafe { (formatter.as_mut(), to_iced(instruction)) } {
+		(Some(formatter), Some(instruction)) => {
+			formatter.output.clear();
+			formatter.formatter.format_operand_separator(instruction, &mut formatter.output);
+			unsafe { copy_str(&formatter.output, buffer, size) }
+		}
+		_ => unsafe { copy_str("", buffer, size) },
+	}
+}
+
+/// Formats all operands, see `iced_formatter_format()` for the return value
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_format_all_operands(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, buffer: *mut c_char, size: usize,
+) -> usize {
+	match unsafe { (formatter.as_mut(), to_iced(instruction)) } {
+		(Some(formatter), Some(instruction)) => {
+			formatter.output.clear();
+			formatter.formatter.format_all_operands(instruction, &mut formatter.output);
+			unsafe { copy_str(&formatter.output, buffer, size) }
+		}
+		_ => unsafe { copy_str("", buffer, size) },
+	}
+}
+
+/// Formats a register, see `iced_formatter_format()` for the return value
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `register`: Register (a `Register` enum value)
+/// * `buffer`: (out) Formatted string
+/// * `size`: Size of `buffer` in bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_format_register(formatter: *mut IcedFormatter, register: u32, buffer: *mut c_char, size: usize) -> usize {
+	match (unsafe { formatter.as_mut() }, to_enum::<Register>(register, "Register")) {
+		(Some(formatter), Ok(register)) => unsafe { copy_str(formatter.formatter.format_register(register), buffer, size) },
+		_ => unsafe { copy_str("", buffer, size) },
+	}
+}
+
+/// Gets the operand access (an `OpAccess` enum value) of a formatter operand
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+/// * `access`: (out) Operand access or 0 (`OpAccess::None`) if it's an operand created by the formatter
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_op_access(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, operand: u32, access: *mut u32,
+) -> IcedStatus {
+	match unsafe { (formatter.as_mut(), to_iced(instruction), access.as_mut()) } {
+		(Some(formatter), Some(instruction), Some(access)) => match formatter.formatter.op_access(instruction, operand) {
+			Ok(op_access) => {
+				*access = op_access.map_or(0, |op_access| op_access as u32);
+				ICED_OK
+			}
+			Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+		},
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Converts a formatter operand index to an instruction operand index
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-based. This is a formatter operand and isn't necessarily the same as an instruction operand.
+/// * `instruction_operand`: (out) Instruction operand or -1 if it's an operand created by the formatter
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_get_instruction_operand(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, operand: u32, instruction_operand: *mut i32,
+) -> IcedStatus {
+	match unsafe { (formatter.as_mut(), to_iced(instruction), instruction_operand.as_mut()) } {
+		(Some(formatter), Some(instruction), Some(instruction_operand)) => match formatter.formatter.get_instruction_operand(instruction, operand) {
+			Ok(op) => {
+				*instruction_operand = op.map_or(-1, |op| op as i32);
+				ICED_OK
+			}
+			Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+		},
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Converts an instruction operand index to a formatter operand index
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `instruction_operand`: Instruction operand
+/// * `operand`: (out) Formatter operand or -1 if the instruction operand isn't used by the formatter
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `instruction_operand` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_get_formatter_operand(
+	formatter: *mut IcedFormatter, instruction: *const IcedInstruction, instruction_operand: u32, operand: *mut i32,
+) -> IcedStatus {
+	match unsafe { (formatter.as_mut(), to_iced(instruction), operand.as_mut()) } {
+		(Some(formatter), Some(instruction), Some(operand)) => match formatter.formatter.get_formatter_operand(instruction, instruction_operand) {
+			Ok(op) => {
+				*operand = op.map_or(-1, |op| op as i32);
+				ICED_OK
+			}
+			Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+		},
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+fn get_option(options: &FormatterOptions, option: u32) -> Result<u32, IcedStatus> {
+	Ok(match option {
+		ICED_FORMATTER_OPTION_UPPERCASE_PREFIXES => options.uppercase_prefixes() as u32,
+		ICED_FORMATTER_OPTION_UPPERCASE_MNEMONICS => options.uppercase_mnemonics() as u32,
+		ICED_FORMATTER_OPTION_UPPERCASE_REGISTERS => options.uppercase_registers() as u32,
+		ICED_FORMATTER_OPTION_UPPERCASE_KEYWORDS => options.uppercase_keywords() as u32,
+		ICED_FORMATTER_OPTION_UPPERCASE_DECORATORS => options.uppercase_decorators() as u32,
+		ICED_FORMATTER_OPTION_UPPERCASE_ALL => options.uppercase_all() as u32,
+		ICED_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR => options.space_after_operand_separator() as u32,
+		ICED_FORMATTER_OPTION_SPACE_AFTER_MEMORY_BRACKET => options.space_after_memory_bracket() as u32,
+		ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_ADD_OPERATORS => options.space_between_memory_add_operators() as u32,
+		ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_MUL_OPERATORS => options.space_between_memory_mul_operators() as u32,
+		ICED_FORMATTER_OPTION_SCALE_BEFORE_INDEX => options.scale_before_index() as u32,
+		ICED_FORMATTER_OPTION_ALWAYS_SHOW_SCALE => options.always_show_scale() as u32,
+		ICED_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER => options.always_show_segment_register() as u32,
+		ICED_FORMATTER_OPTION_SHOW_ZERO_DISPLACEMENTS => options.show_zero_displacements() as u32,
+		ICED_FORMATTER_OPTION_LEADING_ZEROS => options.leading_zeros() as u32,
+		ICED_FORMATTER_OPTION_UPPERCASE_HEX => options.uppercase_hex() as u32,
+		ICED_FORMATTER_OPTION_SMALL_HEX_NUMBERS_IN_DECIMAL => options.small_hex_numbers_in_decimal() as u32,
+		ICED_FORMATTER_OPTION_ADD_LEADING_ZERO_TO_HEX_NUMBERS => options.add_leading_zero_to_hex_numbers() as u32,
+		ICED_FORMATTER_OPTION_BRANCH_LEADING_ZEROS => options.branch_leading_zeros() as u32,
+		ICED_FORMATTER_OPTION_SIGNED_IMMEDIATE_OPERANDS => options.signed_immediate_operands() as u32,
+		ICED_FORMATTER_OPTION_SIGNED_MEMORY_DISPLACEMENTS => options.signed_memory_displacements() as u32,
+		ICED_FORMATTER_OPTION_DISPLACEMENT_LEADING_ZEROS => options.displacement_leading_zeros() as u32,
+		ICED_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES => options.rip_relative_addresses() as u32,
+		ICED_FORMATTER_OPTION_SHOW_BRANCH_SIZE => options.show_branch_size() as u32,
+		ICED_FORMATTER_OPTION_USE_PSEUDO_OPS => options.use_pseudo_ops() as u32,
+		ICED_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS => options.show_symbol_address() as u32,
+		ICED_FORMATTER_OPTION_GAS_NAKED_REGISTERS => options.gas_naked_registers() as u32,
+		ICED_FORMATTER_OPTION_GAS_SHOW_MNEMONIC_SIZE_SUFFIX => options.gas_show_mnemonic_size_suffix() as u32,
+		ICED_FORMATTER_OPTION_GAS_SPACE_AFTER_MEMORY_OPERAND_COMMA => options.gas_space_after_memory_operand_comma() as u32,
+		ICED_FORMATTER_OPTION_MASM_ADD_DS_PREFIX32 => options.masm_add_ds_prefix32() as u32,
+		ICED_FORMATTER_OPTION_MASM_SYMBOL_DISPL_IN_BRACKETS => options.masm_symbol_displ_in_brackets() as u32,
+		ICED_FORMATTER_OPTION_MASM_DISPL_IN_BRACKETS => options.masm_displ_in_brackets() as u32,
+		ICED_FORMATTER_OPTION_NASM_SHOW_SIGN_EXTENDED_IMMEDIATE_SIZE => options.nasm_show_sign_extended_immediate_size() as u32,
+		ICED_FORMATTER_OPTION_PREFER_ST0 => options.prefer_st0() as u32,
+		ICED_FORMATTER_OPTION_SHOW_USELESS_PREFIXES => options.show_useless_prefixes() as u32,
+		ICED_FORMATTER_OPTION_FIRST_OPERAND_CHAR_INDEX => options.first_operand_char_index(),
+		ICED_FORMATTER_OPTION_TAB_SIZE => options.tab_size(),
+		ICED_FORMATTER_OPTION_HEX_DIGIT_GROUP_SIZE => options.hex_digit_group_size(),
+		ICED_FORMATTER_OPTION_DECIMAL_DIGIT_GROUP_SIZE => options.decimal_digit_group_size(),
+		ICED_FORMATTER_OPTION_OCTAL_DIGIT_GROUP_SIZE => options.octal_digit_group_size(),
+		ICED_FORMATTER_OPTION_BINARY_DIGIT_GROUP_SIZE => options.binary_digit_group_size(),
+		ICED_FORMATTER_OPTION_NUMBER_BASE => options.number_base() as u32,
+		ICED_FORMATTER_OPTION_MEMORY_SIZE_OPTIONS => options.memory_size_options() as u32,
+		ICED_FORMATTER_OPTION_CC_B => options.cc_b() as u32,
+		ICED_FORMATTER_OPTION_CC_AE => options.cc_ae() as u32,
+		ICED_FORMATTER_OPTION_CC_E => options.cc_e() as u32,
+		ICED_FORMATTER_OPTION_CC_NE => options.cc_ne() as u32,
+		ICED_FORMATTER_OPTION_CC_BE => options.cc_be() as u32,
+		ICED_FORMATTER_OPTION_CC_A => options.cc_a() as u32,
+		ICED_FORMATTER_OPTION_CC_P => options.cc_p() as u32,
+		ICED_FORMATTER_OPTION_CC_NP => options.cc_np() as u32,
+		ICED_FORMATTER_OPTION_CC_L => options.cc_l() as u32,
+		ICED_FORMATTER_OPTION_CC_GE => options.cc_ge() as u32,
+		ICED_FORMATTER_OPTION_CC_LE => options.cc_le() as u32,
+		ICED_FORMATTER_OPTION_CC_G => options.cc_g() as u32,
+		_ => {
+			set_last_error("Invalid formatter option");
+			return Err(ICED_ERROR_INVALID_ARGUMENT);
+		}
+	})
+}
+
+fn set_option(options: &mut FormatterOptions, option: u32, value: u32) -> Result<(), IcedStatus> {
+	match option {
+		ICED_FORMATTER_OPTION_UPPERCASE_PREFIXES => options.set_uppercase_prefixes(value != 0),
+		ICED_FORMATTER_OPTION_UPPERCASE_MNEMONICS => options.set_uppercase_mnemonics(value != 0),
+		ICED_FORMATTER_OPTION_UPPERCASE_REGISTERS => options.set_uppercase_registers(value != 0),
+		ICED_FORMATTER_OPTION_UPPERCASE_KEYWORDS => options.set_uppercase_keywords(value != 0),
+		ICED_FORMATTER_OPTION_UPPERCASE_DECORATORS => options.set_uppercase_decorators(value != 0),
+		ICED_FORMATTER_OPTION_UPPERCASE_ALL => options.set_uppercase_all(value != 0),
+		ICED_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR => options.set_space_after_operand_separator(value != 0),
+		ICED_FORMATTER_OPTION_SPACE_AFTER_MEMORY_BRACKET => options.set_space_after_memory_bracket(value != 0),
+		ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_ADD_OPERATORS => options.set_space_between_memory_add_operators(value != 0),
+		ICED_FORMATTER_OPTION_SPACE_BETWEEN_MEMORY_MUL_OPERATORS => options.set_space_between_memory_mul_operators(value != 0),
+		ICED_FORMATTER_OPTION_SCALE_BEFORE_INDEX => options.set_scale_before_index(value != 0),
+		ICED_FORMATTER_OPTION_ALWAYS_SHOW_SCALE => options.set_always_show_scale(value != 0),
+		ICED_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER => options.set_always_show_segment_register(value != 0),
+		ICED_FORMATTER_OPTION_SHOW_ZERO_DISPLACEMENTS => options.set_show_zero_displacements(value != 0),
+		ICED_FORMATTER_OPTION_LEADING_ZEROS => options.set_leading_zeros(value != 0),
+		ICED_FORMATTER_OPTION_UPPERCASE_HEX => options.set_uppercase_hex(value != 0),
+		ICED_FORMATTER_OPTION_SMALL_HEX_NUMBERS_IN_DECIMAL => options.set_small_hex_numbers_in_decimal(value != 0),
+		ICED_FORMATTER_OPTION_ADD_LEADING_ZERO_TO_HEX_NUMBERS => options.set_add_leading_zero_to_hex_numbers(value != 0),
+		ICED_FORMATTER_OPTION_BRANCH_LEADING_ZEROS => options.set_branch_leading_zeros(value != 0),
+		ICED_FORMATTER_OPTION_SIGNED_IMMEDIATE_OPERANDS => options.set_signed_immediate_operands(value != 0),
+		ICED_FORMATTER_OPTION_SIGNED_MEMORY_DISPLACEMENTS => options.set_signed_memory_displacements(value != 0),
+		ICED_FORMATTER_OPTION_DISPLACEMENT_LEADING_ZEROS => options.set_displacement_leading_zeros(value != 0),
+		ICED_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES => options.set_rip_relative_addresses(value != 0),
+		ICED_FORMATTER_OPTION_SHOW_BRANCH_SIZE => options.set_show_branch_size(value != 0),
+		ICED_FORMATTER_OPTION_USE_PSEUDO_OPS => options.set_use_pseudo_ops(value != 0),
+		ICED_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS => options.set_show_symbol_address(value != 0),
+		ICED_FORMATTER_OPTION_GAS_NAKED_REGISTERS => options.set_gas_naked_registers(value != 0),
+		ICED_FORMATTER_OPTION_GAS_SHOW_MNEMONIC_SIZE_SUFFIX => options.set_gas_show_mnemonic_size_suffix(value != 0),
+		ICED_FORMATTER_OPTION_GAS_SPACE_AFTER_MEMORY_OPERAND_COMMA => options.set_gas_space_after_memory_operand_comma(value != 0),
+		ICED_FORMATTER_OPTION_MASM_ADD_DS_PREFIX32 => options.set_masm_add_ds_prefix32(value != 0),
+		ICED_FORMATTER_OPTION_MASM_SYMBOL_DISPL_IN_BRACKETS => options.set_masm_symbol_displ_in_brackets(value != 0),
+		ICED_FORMATTER_OPTION_MASM_DISPL_IN_BRACKETS => options.set_masm_displ_in_brackets(value != 0),
+		ICED_FORMATTER_OPTION_NASM_SHOW_SIGN_EXTENDED_IMMEDIATE_SIZE => options.set_nasm_show_sign_extended_immediate_size(value != 0),
+		ICED_FORMATTER_OPTION_PREFER_ST0 => options.set_prefer_st0(value != 0),
+		ICED_FORMATTER_OPTION_SHOW_USELESS_PREFIXES => options.set_show_useless_prefixes(value != 0),
+		ICED_FORMATTER_OPTION_FIRST_OPERAND_CHAR_INDEX => options.set_first_operand_char_index(value),
+		ICED_FORMATTER_OPTION_TAB_SIZE => options.set_tab_size(value),
+		ICED_FORMATTER_OPTION_HEX_DIGIT_GROUP_SIZE => options.set_hex_digit_group_size(value),
+		ICED_FORMATTER_OPTION_DECIMAL_DIGIT_GROUP_SIZE => options.set_decimal_digit_group_size(value),
+		ICED_FORMATTER_OPTION_OCTAL_DIGIT_GROUP_SIZE => options.set_octal_digit_group_size(value),
+		ICED_FORMATTER_OPTION_BINARY_DIGIT_GROUP_SIZE => options.set_binary_digit_group_size(value),
+		ICED_FORMATTER_OPTION_NUMBER_BASE => options.set_number_base(to_enum(value, "NumberBase")?),
+		ICED_FORMATTER_OPTION_MEMORY_SIZE_OPTIONS => options.set_memory_size_options(to_enum(value, "MemorySizeOptions")?),
+		ICED_FORMATTER_OPTION_CC_B => options.set_cc_b(to_enum(value, "CC_b")?),
+		ICED_FORMATTER_OPTION_CC_AE => options.set_cc_ae(to_enum(value, "CC_ae")?),
+		ICED_FORMATTER_OPTION_CC_E => options.set_cc_e(to_enum(value, "CC_e")?),
+		ICED_FORMATTER_OPTION_CC_NE => options.set_cc_ne(to_enum(value, "CC_ne")?),
+		ICED_FORMATTER_OPTION_CC_BE => options.set_cc_be(to_enum(value, "CC_be")?),
+		ICED_FORMATTER_OPTION_CC_A => options.set_cc_a(to_enum(value, "CC_a")?),
+		ICED_FORMATTER_OPTION_CC_P => options.set_cc_p(to_enum(value, "CC_p")?),
+		ICED_FORMATTER_OPTION_CC_NP => options.set_cc_np(to_enum(value, "CC_np")?),
+		ICED_FORMATTER_OPTION_CC_L => options.set_cc_l(to_enum(value, "CC_l")?),
+		ICED_FORMATTER_OPTION_CC_GE => options.set_cc_ge(to_enum(value, "CC_ge")?),
+		ICED_FORMATTER_OPTION_CC_LE => options.set_cc_le(to_enum(value, "CC_le")?),
+		ICED_FORMATTER_OPTION_CC_G => options.set_cc_g(to_enum(value, "CC_g")?),
+		_ => {
+			set_last_error("Invalid formatter option");
+			return Err(ICED_ERROR_INVALID_ARGUMENT);
+		}
+	}
+	Ok(())
+}
+
+/// Gets a formatter option
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `option`: Option, eg. `ICED_FORMATTER_OPTION_UPPERCASE_ALL`
+/// * `value`: (out) Value of the option
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_get_option(formatter: *const IcedFormatter, option: u32, value: *mut u32) -> IcedStatus {
+	match unsafe { (formatter.as_ref(), value.as_mut()) } {
+		(Some(formatter), Some(value)) => match get_option(formatter.formatter.options(), option) {
+			Ok(option_value) => {
+				*value = option_value;
+				ICED_OK
+			}
+			Err(status) => status,
+		},
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets a formatter option
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `option`: Option, eg. `ICED_FORMATTER_OPTION_UPPERCASE_ALL`
+/// * `value`: New value. `bool` options use 0 and 1 and enum options use the enum value.
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` or `value` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_set_option(formatter: *mut IcedFormatter, option: u32, value: u32) -> IcedStatus {
+	match unsafe { formatter.as_mut() } {
+		Some(formatter) => match set_option(formatter.formatter.options_mut(), option, value) {
+			Ok(()) => ICED_OK,
+			Err(status) => status,
+		},
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+fn get_string_option(options: &FormatterOptions, option: u32) -> Result<&str, IcedStatus> {
+	Ok(match option {
+		ICED_FORMATTER_STRING_OPTION_HEX_PREFIX => options.hex_prefix(),
+		ICED_FORMATTER_STRING_OPTION_HEX_SUFFIX => options.hex_suffix(),
+		ICED_FORMATTER_STRING_OPTION_DECIMAL_PREFIX => options.decimal_prefix(),
+		ICED_FORMATTER_STRING_OPTION_DECIMAL_SUFFIX => options.decimal_suffix(),
+		ICED_FORMATTER_STRING_OPTION_OCTAL_PREFIX => options.octal_prefix(),
+		ICED_FORMATTER_STRING_OPTION_OCTAL_SUFFIX => options.octal_suffix(),
+		ICED_FORMATTER_STRING_OPTION_BINARY_PREFIX => options.binary_prefix(),
+		ICED_FORMATTER_STRING_OPTION_BINARY_SUFFIX => options.binary_suffix(),
+		ICED_FORMATTER_STRING_OPTION_DIGIT_SEPARATOR => options.digit_separator(),
+		_ => {
+			set_last_error("Invalid formatter string option");
+			return Err(ICED_ERROR_INVALID_ARGUMENT);
+		}
+	})
+}
+
+fn set_string_option(options: &mut FormatterOptions, option: u32, value: String) -> Result<(), IcedStatus> {
+	match option {
+		ICED_FORMATTER_STRING_OPTION_HEX_PREFIX => options.set_hex_prefix_string(value),
+		ICED_FORMATTER_STRING_OPTION_HEX_SUFFIX => options.set_hex_suffix_string(value),
+		ICED_FORMATTER_STRING_OPTION_DECIMAL_PREFIX => options.set_decimal_prefix_string(value),
+		ICED_FORMATTER_STRING_OPTION_DECIMAL_SUFFIX => options.set_decimal_suffix_string(value),
+		ICED_FORMATTER_STRING_OPTION_OCTAL_PREFIX => options.set_octal_prefix_string(value),
+		ICED_FORMATTER_STRING_OPTION_OCTAL_SUFFIX => options.set_octal_suffix_string(value),
+		ICED_FORMATTER_STRING_OPTION_BINARY_PREFIX => options.set_binary_prefix_string(value),
+		ICED_FORMATTER_STRING_OPTION_BINARY_SUFFIX => options.set_binary_suffix_string(value),
+		ICED_FORMATTER_STRING_OPTION_DIGIT_SEPARATOR => options.set_digit_separator_string(value),
+		_ => {
+			set_last_error("Invalid formatter string option");
+			return Err(ICED_ERROR_INVALID_ARGUMENT);
+		}
+	}
+	Ok(())
+}
+
+/// Gets a formatter string option
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `option`: Option, eg. `ICED_FORMATTER_STRING_OPTION_HEX_PREFIX`
+/// * `buffer`: (out) Value of the option, see `iced_formatter_format()`
+/// * `size`: Size of `buffer` in bytes
+/// * `len`: (out) Length of the string, see the return value of `iced_formatter_format()`. It can be null.
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_get_string_option(
+	formatter: *const IcedFormatter, option: u32, buffer: *mut c_char, size: usize, len: *mut usize,
+) -> IcedStatus {
+	let formatter = match unsafe { formatter.as_ref() } {
+		Some(formatter) => formatter,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	let (status, text) = match get_string_option(formatter.formatter.options(), option) {
+		Ok(text) => (ICED_OK, text),
+		Err(status) => (status, ""),
+	};
+	let text_len = unsafe { copy_str(text, buffer, size) };
+	if let Some(len) = unsafe { len.as_mut() } {
+		*len = text_len;
+	}
+	status
+}
+
+/// Sets a formatter string option
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `option`: Option, eg. `ICED_FORMATTER_STRING_OPTION_HEX_PREFIX`
+/// * `value`: NUL-terminated UTF-8 string
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid or if `value` isn't valid UTF-8
+#[no_mangle]
+pub unsafe extern "C" fn iced_formatter_set_string_option(formatter: *mut IcedFormatter, option: u32, value: *const c_char) -> IcedStatus {
+	let formatter = match unsafe { formatter.as_mut() } {
+		Some(formatter) if !value.is_null() => formatter,
+		_ => return ICED_ERROR_NULL_POINTER,
+	};
+	let value = match unsafe { CStr::from_ptr(value) }.to_str() {
+		Ok(value) => value.to_owned(),
+		Err(_) => {
+			set_last_error("The string isn't valid UTF-8");
+			return ICED_ERROR_INVALID_ARGUMENT;
+		}
+	};
+	match set_string_option(formatter.formatter.options_mut(), option, value) {
+		Ok(()) => ICED_OK,
+		Err(status) => status,
+	}
+}
+
+// Fast formatter options, see `iced_fast_formatter_get_option()` and `iced_fast_formatter_set_option()`
+
+/// `space_after_operand_separator` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR: u32 = 0;
+/// `rip_relative_addresses` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES: u32 = 1;
+/// `use_pseudo_ops` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_USE_PSEUDO_OPS: u32 = 2;
+/// `show_symbol_address` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS: u32 = 3;
+/// `always_show_segment_register` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER: u32 = 4;
+/// `always_show_memory_size` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_MEMORY_SIZE: u32 = 5;
+/// `uppercase_hex` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX: u32 = 6;
+/// `use_hex_prefix` fast formatter option, a `bool` (0 or 1)
+pub const ICED_FAST_FORMATTER_OPTION_USE_HEX_PREFIX: u32 = 7;
+
+/// Fast formatter with less formatting options and with a masm-like syntax. Created by `iced_fast_formatter_new()`
+/// and freed by `iced_fast_formatter_free()`.
+pub struct IcedFastFormatter {
+	formatter: FastFormatter,
+	output: String,
+}
+
+impl std::fmt::Debug for IcedFastFormatter {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("IcedFastFormatter").finish_non_exhaustive()
+	}
+}
+
+/// Creates a fast formatter. Returns null on failure.
+///
+/// # Arguments
+///
+/// * `symbol_resolver`: Symbol resolver or null, see `iced_formatter_new()`
+#[no_mangle]
+pub unsafe extern "C" fn iced_fast_formatter_new(symbol_resolver: *const IcedSymbolResolver) -> *mut IcedFastFormatter {
+	match FastFormatter::try_with_options(create_symbol_resolver(symbol_resolver)) {
+		Ok(formatter) => Box::into_raw(Box::new(IcedFastFormatter { formatter, output: String::new() })),
+		Err(error) => {
+			let _ = status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error);
+			ptr::null_mut()
+		}
+	}
+}
+
+/// Frees a fast formatter. `formatter` can be null.
+#[no_mangle]
+pub unsafe extern "C" fn iced_fast_formatter_free(formatter: *mut IcedFastFormatter) {
+	if !formatter.is_null() {
+		drop(unsafe { Box::from_raw(formatter) });
+	}
+}
+
+/// Formats the whole instruction: prefixes, mnemonic, operands. See `iced_formatter_format()` for the return value.
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `instruction`: Instruction
+/// * `buffer`: (out) Formatted string
+/// * `size`: Size of `buffer` in bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_fast_formatter_format(
+	formatter: *mut IcedFastFormatter, instruction: *const IcedInstruction, buffer: *mut c_char, size: usize,
+) -> usize {
+	match unsafe { (formatter.as_mut(), to_iced(instruction)) } {
+		(Some(formatter), Some(instruction)) => {
+			formatter.output.clear();
+			formatter.formatter.format(instruction, &mut formatter.output);
+			unsafe { copy_str(&formatter.output, buffer, size) }
+		}
+		_ => unsafe { copy_str("", buffer, size) },
+	}
+}
+
+fn get_fast_option(options: &FastFormatterOptions, option: u32) -> Result<u32, IcedStatus> {
+	Ok(match option {
+		ICED_FAST_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR => options.space_after_operand_separator() as u32,
+		ICED_FAST_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES => options.rip_relative_addresses() as u32,
+		ICED_FAST_FORMATTER_OPTION_USE_PSEUDO_OPS => options.use_pseudo_ops() as u32,
+		ICED_FAST_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS => options.show_symbol_address() as u32,
+		ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER => options.always_show_segment_register() as u32,
+		ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_MEMORY_SIZE => options.always_show_memory_size() as u32,
+		ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX => options.uppercase_hex() as u32,
+		ICED_FAST_FORMATTER_OPTION_USE_HEX_PREFIX => options.use_hex_prefix() as u32,
+		_ => {
+			set_last_error("Invalid fast formatter option");
+			return Err(ICED_ERROR_INVALID_ARGUMENT);
+		}
+	})
+}
+
+/// Gets a fast formatter option
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `option`: Option, eg. `ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX`
+/// * `value`: (out) Value of the option (0 or 1)
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_fast_formatter_get_option(formatter: *const IcedFastFormatter, option: u32, value: *mut u32) -> IcedStatus {
+	match unsafe { (formatter.as_ref(), value.as_mut()) } {
+		(Some(formatter), Some(value)) => match get_fast_option(formatter.formatter.options(), option) {
+			Ok(option_value) => {
+				*value = option_value;
+				ICED_OK
+			}
+			Err(status) => status,
+		},
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets a fast formatter option
+///
+/// # Arguments
+///
+/// * `formatter`: Formatter
+/// * `option`: Option, eg. `ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX`
+/// * `value`: New value (0 or 1)
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `option` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_fast_formatter_set_option(formatter: *mut IcedFastFormatter, option: u32, value: u32) -> IcedStatus {
+	let formatter = match unsafe { formatter.as_mut() } {
+		Some(formatter) => formatter,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	let options = formatter.formatter.options_mut();
+	match option {
+		ICED_FAST_FORMATTER_OPTION_SPACE_AFTER_OPERAND_SEPARATOR => options.set_space_after_operand_separator(value != 0),
+		ICED_FAST_FORMATTER_OPTION_RIP_RELATIVE_ADDRESSES => options.set_rip_relative_addresses(value != 0),
+		ICED_FAST_FORMATTER_OPTION_USE_PSEUDO_OPS => options.set_use_pseudo_ops(value != 0),
+		ICED_FAST_FORMATTER_OPTION_SHOW_SYMBOL_ADDRESS => options.set_show_symbol_address(value != 0),
+		ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_SEGMENT_REGISTER => options.set_always_show_segment_register(value != 0),
+		ICED_FAST_FORMATTER_OPTION_ALWAYS_SHOW_MEMORY_SIZE => options.set_always_show_memory_size(value != 0),
+		ICED_FAST_FORMATTER_OPTION_UPPERCASE_HEX => options.set_uppercase_hex(value != 0),
+		ICED_FAST_FORMATTER_OPTION_USE_HEX_PREFIX => options.set_use_hex_prefix(value != 0),
+		_ => {
+			set_last_error("Invalid fast formatter option");
+			return ICED_ERROR_INVALID_ARGUMENT;
+		}
+	}
+	ICED_OK
+}
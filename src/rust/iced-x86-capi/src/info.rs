@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::{IcedStatus, ICED_ERROR_NULL_POINTER, ICED_OK};
+use crate::instruction::{to_iced, IcedInstruction};
+use iced_x86::InstructionInfoFactory;
+use std::ptr;
+
+// Instruction info options (`InstructionInfoOptions` flags) passed to `iced_instruction_info_factory_info()`
+
+/// No option is enabled
+pub const ICED_INSTRUCTION_INFO_OPTIONS_NONE: u32 = 0x0000_0000;
+/// Don't include memory usage, ie. `used_memory` is empty
+pub const ICED_INSTRUCTION_INFO_OPTIONS_NO_MEMORY_USAGE: u32 = 0x0000_0001;
+/// Don't include register usage, ie. `used_registers` is empty
+pub const ICED_INSTRUCTION_INFO_OPTIONS_NO_REGISTER_USAGE: u32 = 0x0000_0002;
+
+/// A register used by an instruction
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct IcedUsedRegister {
+	/// Register (a `Register` enum value)
+	pub register: u32,
+	/// Register access (an `OpAccess` enum value)
+	pub access: u32,
+}
+
+/// A memory location used by an instruction
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct IcedUsedMemory {
+	/// Effective segment register or `Register::None` if the segment register is ignored (a `Register` enum value)
+	pub segment: u32,
+	/// Base register or `Register::None` if none (a `Register` enum value)
+	pub base: u32,
+	/// Index register or `Register::None` if none (a `Register` enum value)
+	pub index: u32,
+	/// Index scale (1, 2, 4 or 8)
+	pub scale: u32,
+	/// Displacement
+	pub displacement: u64,
+	/// Size of location (a `MemorySize` enum value)
+	pub memory_size: u32,
+	/// Memory access (an `OpAccess` enum value)
+	pub access: u32,
+	/// Address size (a `CodeSize` enum value)
+	pub address_size: u32,
+	/// VSIB size (`0`, `4` or `8`)
+	pub vsib_size: u32,
+}
+
+/// Instruction info returned by `iced_instruction_info_factory_info()`. The pointers are owned by the factory and are
+/// valid until the next call to `iced_instruction_info_factory_info()` or until the factory is freed.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct IcedInstructionInfo {
+	/// All accessed registers. Some instructions (eg. `XSAVE`) don't return all accessed registers.
+	pub used_registers: *const IcedUsedRegister,
+	/// Number of elements in `used_registers`
+	pub used_registers_count: usize,
+	/// All accessed memory locations
+	pub used_memory: *const IcedUsedMemory,
+	/// Number of elements in `used_memory`
+	pub used_memory_count: usize,
+	/// Operand accesses of operands 0-4 (`OpAccess` enum values)
+	pub op_accesses: [u32; 5],
+}
+
+/// Creates `IcedInstructionInfo`s. Created by `iced_instruction_info_factory_new()` and freed by
+/// `iced_instruction_info_factory_free()`.
+#[derive(Debug)]
+pub struct IcedInstructionInfoFactory {
+	factory: InstructionInfoFactory,
+	used_registers: Vec<IcedUsedRegister>,
+	used_memory: Vec<IcedUsedMemory>,
+}
+
+/// Creates an instruction info factory
+#[no_mangle]
+pub extern "C" fn iced_instruction_info_factory_new() -> *mut IcedInstructionInfoFactory {
+	Box::into_raw(Box::new(IcedInstructionInfoFactory {
+		factory: InstructionInfoFactory::new(),
+		used_registers: Vec::new(),
+		used_memory: Vec::new(),
+	}))
+}
+
+/// Frees an instruction info factory. `factory` can be null.
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_info_factory_free(factory: *mut IcedInstructionInfoFactory) {
+	if !factory.is_null() {
+		drop(unsafe { Box::from_raw(factory) });
+	}
+}
+
+/// Creates an instruction info
+///
+/// # Arguments
+///
+/// * `factory`: Factory
+/// * `instruction`: Instruction
+/// * `options`: Options (`InstructionInfoOptions` flags), eg. `ICED_INSTRUCTION_INFO_OPTIONS_NONE`
+/// * `info`: (out) Instruction info
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_info_factory_info(
+	factory: *mut IcedInstructionInfoFactory, instruction: *const IcedInstruction, options: u32, info: *mut IcedInstructionInfo,
+) -> IcedStatus {
+	let (factory, instruction, info) = match unsafe { (factory.as_mut(), to_iced(instruction), info.as_mut()) } {
+		(Some(factory), Some(instruction), Some(info)) => (factory, instruction, info),
+		_ => return ICED_ERROR_NULL_POINTER,
+	};
+	let iced_info = factory.factory.info_options(instruction, options);
+	factory.used_registers.clear();
+	factory
+		.used_registers
+		.extend(iced_info.used_registers().iter().map(|used| IcedUsedRegister { register: used.register() as u32, access: used.access() as u32 }));
+	factory.used_memory.clear();
+	factory.used_memory.extend(iced_info.used_memory().iter().map(|used| IcedUsedMemory {
+		segment: used.segment() as u32,
+		base: used.base() as u32,
+		index: used.index() as u32,
+		scale: used.scale(),
+		displacement: used.displacement(),
+		memory_size: used.memory_size() as u32,
+		access: used.access() as u32,
+		address_size: used.address_size() as u32,
+		vsib_size: used.vsib_size(),
+	}));
+	*info = IcedInstructionInfo {
+		used_registers: if factory.used_registers.is_empty() { ptr::null() } else { factory.used_registers.as_ptr() },
+		used_registers_count: factory.used_registers.len(),
+		used_memory: if factory.used_memory.is_empty() { ptr::null() } else { factory.used_memory.as_ptr() },
+		used_memory_count: factory.used_memory.len(),
+		op_accesses: [
+			iced_info.op0_access() as u32,
+			iced_info.op1_access() as u32,
+			iced_info.op2_access() as u32,
+			iced_info.op3_access() as u32,
+			iced_info.op4_access() as u32,
+		],
+	};
+	ICED_OK
+}
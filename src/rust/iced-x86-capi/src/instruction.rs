@@ -0,0 +1,705 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::{status_from_error, IcedStatus, ICED_ERROR_INVALID_ARGUMENT, ICED_ERROR_NULL_POINTER, ICED_OK};
+use crate::utils::to_enum;
+use iced_x86::{Code, Instruction, OpKind, Register};
+use std::mem;
+
+/// A 16/32/64-bit x86 instruction. It's a POD type that can be copied with `memcpy()` and zero-initialized
+/// (an all zero instruction is a valid `INVALID` instruction).
+///
+/// The fields are private, use the `iced_instruction_*()` functions to read or write them.
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct IcedInstruction {
+	data: [u64; 5],
+}
+const _: () = assert!(mem::size_of::<IcedInstruction>() == mem::size_of::<Instruction>());
+const _: () = assert!(mem::align_of::<IcedInstruction>() >= mem::align_of::<Instruction>());
+
+impl IcedInstruction {
+	#[inline]
+	pub(crate) fn from_iced(instruction: Instruction) -> Self {
+		// SAFETY: Same size and `Instruction` has no padding bytes
+		unsafe { mem::transmute(instruction) }
+	}
+}
+
+#[inline]
+pub(crate) unsafe fn to_iced<'a>(instruction: *const IcedInstruction) -> Option<&'a Instruction> {
+	unsafe { instruction.cast::<Instruction>().as_ref() }
+}
+
+#[inline]
+pub(crate) unsafe fn to_iced_mut<'a>(instruction: *mut IcedInstruction) -> Option<&'a mut Instruction> {
+	unsafe { instruction.cast::<Instruction>().as_mut() }
+}
+
+/// Creates an instruction with no operands
+///
+/// # Arguments
+///
+/// * `code`: Code (a `Code` enum value)
+/// * `instruction`: (out) Created instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_create(code: u32, instruction: *mut IcedInstruction) -> IcedStatus {
+	let instruction = match unsafe { instruction.as_mut() } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match to_enum::<Code>(code, "Code") {
+		Ok(code) => {
+			*instruction = IcedInstruction::from_iced(Instruction::with(code));
+			ICED_OK
+		}
+		Err(status) => status,
+	}
+}
+
+/// Checks if two instructions are equal. Some fields are ignored, eg. the IP, see `iced_instruction_eq_all_bits()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_eq(a: *const IcedInstruction, b: *const IcedInstruction) -> bool {
+	match unsafe { (to_iced(a), to_iced(b)) } {
+		(Some(a), Some(b)) => a == b,
+		_ => false,
+	}
+}
+
+/// Checks if two instructions are equal, comparing all bits, not ignoring anything
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_eq_all_bits(a: *const IcedInstruction, b: *const IcedInstruction) -> bool {
+	match unsafe { (to_iced(a), to_iced(b)) } {
+		(Some(a), Some(b)) => a.eq_all_bits(b),
+		_ => false,
+	}
+}
+
+/// Gets an operand's kind (an `OpKind` enum value)
+///
+/// # Arguments
+///
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-4
+/// * `op_kind`: (out) Operand kind
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_op_kind(instruction: *const IcedInstruction, operand: u32, op_kind: *mut u32) -> IcedStatus {
+	let (instruction, op_kind) = match unsafe { (to_iced(instruction), op_kind.as_mut()) } {
+		(Some(instruction), Some(op_kind)) => (instruction, op_kind),
+		_ => return ICED_ERROR_NULL_POINTER,
+	};
+	match instruction.try_op_kind(operand) {
+		Ok(value) => {
+			*op_kind = value as u32;
+			ICED_OK
+		}
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	}
+}
+
+/// Sets an operand's kind
+///
+/// # Arguments
+///
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-4
+/// * `op_kind`: Operand kind (an `OpKind` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_op_kind(instruction: *mut IcedInstruction, operand: u32, op_kind: u32) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	let op_kind = match to_enum::<OpKind>(op_kind, "OpKind") {
+		Ok(op_kind) => op_kind,
+		Err(status) => return status,
+	};
+	match instruction.try_set_op_kind(operand, op_kind) {
+		Ok(()) => ICED_OK,
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	}
+}
+
+/// Gets the operand's register value (a `Register` enum value). Use this function if the operand has kind `OpKind::Register`.
+///
+/// # Arguments
+///
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-4
+/// * `register`: (out) Register
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_op_register(instruction: *const IcedInstruction, operand: u32, register: *mut u32) -> IcedStatus {
+	let (instruction, register) = match unsafe { (to_iced(instruction), register.as_mut()) } {
+		(Some(instruction), Some(register)) => (instruction, register),
+		_ => return ICED_ERROR_NULL_POINTER,
+	};
+	match instruction.try_op_register(operand) {
+		Ok(value) => {
+			*register = value as u32;
+			ICED_OK
+		}
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	}
+}
+
+/// Sets the operand's register value. Use this function if the operand has kind `OpKind::Register`.
+///
+/// # Arguments
+///
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-4
+/// * `register`: Register (a `Register` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_op_register(instruction: *mut IcedInstruction, operand: u32, register: u32) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	let register = match to_enum::<Register>(register, "Register") {
+		Ok(register) => register,
+		Err(status) => return status,
+	};
+	match instruction.try_set_op_register(operand, register) {
+		Ok(()) => ICED_OK,
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	}
+}
+
+/// Gets an operand's immediate value
+///
+/// # Arguments
+///
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-4
+/// * `immediate`: (out) Immediate, sign/zero extended to 64 bits
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_immediate(instruction: *const IcedInstruction, operand: u32, immediate: *mut u64) -> IcedStatus {
+	let (instruction, immediate) = match unsafe { (to_iced(instruction), immediate.as_mut()) } {
+		(Some(instruction), Some(immediate)) => (instruction, immediate),
+		_ => return ICED_ERROR_NULL_POINTER,
+	};
+	match instruction.try_immediate(operand) {
+		Ok(value) => {
+			*immediate = value;
+			ICED_OK
+		}
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	}
+}
+
+/// Sets an operand's immediate value
+///
+/// # Arguments
+///
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-4
+/// * `immediate`: New immediate
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_immediate_i64(instruction: *mut IcedInstruction, operand: u32, immediate: i64) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match instruction.try_set_immediate_i64(operand, immediate) {
+		Ok(()) => ICED_OK,
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	}
+}
+
+/// Sets an operand's immediate value
+///
+/// # Arguments
+///
+/// * `instruction`: Instruction
+/// * `operand`: Operand number, 0-4
+/// * `immediate`: New immediate
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_immediate_u64(instruction: *mut IcedInstruction, operand: u32, immediate: u64) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match instruction.try_set_immediate_u64(operand, immediate) {
+		Ok(()) => ICED_OK,
+		Err(error) => status_from_error(ICED_ERROR_INVALID_ARGUMENT, &error),
+	}
+}
+
+/// Gets the 64-bit IP of the instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_ip(instruction: *const IcedInstruction) -> u64 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.ip(),
+		None => 0,
+	}
+}
+
+/// Gets the 64-bit IP of the next instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_next_ip(instruction: *const IcedInstruction) -> u64 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.next_ip(),
+		None => 0,
+	}
+}
+
+/// Gets the length of the instruction, 0-15 bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_len(instruction: *const IcedInstruction) -> usize {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.len(),
+		None => 0,
+	}
+}
+
+/// Gets the instruction code (a `Code` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_code(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.code() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the mnemonic (a `Mnemonic` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_mnemonic(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.mnemonic() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the code size (a `CodeSize` enum value) when the instruction was decoded
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_code_size(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.code_size() as u32,
+		None => 0,
+	}
+}
+
+/// Checks if it's an invalid instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_is_invalid(instruction: *const IcedInstruction) -> bool {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.is_invalid(),
+		None => false,
+	}
+}
+
+/// Gets the operand count. An instruction can have 0-5 operands.
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_op_count(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.op_count(),
+		None => 0,
+	}
+}
+
+/// Checks if the instruction has the `LOCK` prefix
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_has_lock_prefix(instruction: *const IcedInstruction) -> bool {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.has_lock_prefix(),
+		None => false,
+	}
+}
+
+/// Checks if the instruction has the `REP`/`REPE` prefix
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_has_rep_prefix(instruction: *const IcedInstruction) -> bool {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.has_rep_prefix(),
+		None => false,
+	}
+}
+
+/// Checks if the instruction has the `REPNE` prefix
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_has_repne_prefix(instruction: *const IcedInstruction) -> bool {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.has_repne_prefix(),
+		None => false,
+	}
+}
+
+/// Gets the segment override prefix (a `Register` enum value) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_segment_prefix(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.segment_prefix() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the effective segment register used to reference the memory location (a `Register` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_memory_segment(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.memory_segment() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the memory operand's base register (a `Register` enum value) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_memory_base(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.memory_base() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the memory operand's index register (a `Register` enum value) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_memory_index(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.memory_index() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the index register scale value, valid values are 1, 2, 4, 8
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_memory_index_scale(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.memory_index_scale(),
+		None => 0,
+	}
+}
+
+/// Gets the memory operand's displacement
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_memory_displacement64(instruction: *const IcedInstruction) -> u64 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.memory_displacement64(),
+		None => 0,
+	}
+}
+
+/// Gets the size of the memory displacement in bytes (0, 1, 2, 4 or 8)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_memory_displ_size(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.memory_displ_size(),
+		None => 0,
+	}
+}
+
+/// Gets the size of the memory location (a `MemorySize` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_memory_size(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.memory_size() as u32,
+		None => 0,
+	}
+}
+
+/// Checks if the memory operand is `RIP`/`EIP` relative
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_is_ip_rel_memory_operand(instruction: *const IcedInstruction) -> bool {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.is_ip_rel_memory_operand(),
+		None => false,
+	}
+}
+
+/// Gets the `RIP`/`EIP` relative address (the displacement). Only valid if `iced_instruction_is_ip_rel_memory_operand()` returns `true`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_ip_rel_memory_address(instruction: *const IcedInstruction) -> u64 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.ip_rel_memory_address(),
+		None => 0,
+	}
+}
+
+/// Gets the target of a near branch or 0 if it's not a near branch
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_near_branch_target(instruction: *const IcedInstruction) -> u64 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.near_branch_target(),
+		None => 0,
+	}
+}
+
+/// Gets the opmask register (a `Register` enum value, `Register::K1`-`Register::K7`) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_op_mask(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.op_mask() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the flow control info (a `FlowControl` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_flow_control(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.flow_control() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the encoding (a `EncodingKind` enum value), eg. legacy, 3DNow!, VEX, EVEX, XOP
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_encoding(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.encoding() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the condition code (a `ConditionCode` enum value) if it's `Jcc`, `SETcc`, `CMOVcc`, `LOOPcc` else `ConditionCode::None`
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_condition_code(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.condition_code() as u32,
+		None => 0,
+	}
+}
+
+/// Checks if it's a stack instruction, eg. `PUSH`, `POP`, `CALL`, `RET`
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_is_stack_instruction(instruction: *const IcedInstruction) -> bool {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.is_stack_instruction(),
+		None => false,
+	}
+}
+
+/// Gets the number of bytes added to `SP`/`ESP`/`RSP` or 0 if it's not an instruction that pushes or pops data
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_stack_pointer_increment(instruction: *const IcedInstruction) -> i32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.stack_pointer_increment(),
+		None => 0,
+	}
+}
+
+/// All flags that are read by the CPU when executing the instruction (`RflagsBits` flags)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_rflags_read(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.rflags_read(),
+		None => 0,
+	}
+}
+
+/// All flags that are written by the CPU, except those flags that are known to be undefined, always set or always cleared (`RflagsBits` flags)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_rflags_written(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.rflags_written(),
+		None => 0,
+	}
+}
+
+/// All flags that are modified by the CPU (`RflagsBits` flags)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_rflags_modified(instruction: *const IcedInstruction) -> u32 {
+	match unsafe { to_iced(instruction) } {
+		Some(instruction) => instruction.rflags_modified(),
+		None => 0,
+	}
+}
+
+/// Sets the 64-bit IP of the instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_ip(instruction: *mut IcedInstruction, value: u64) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_ip(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the 64-bit IP of the next instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_next_ip(instruction: *mut IcedInstruction, value: u64) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_next_ip(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the length of the instruction, 0-15 bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_len(instruction: *mut IcedInstruction, value: usize) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_len(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the instruction code (a `Code` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_code(instruction: *mut IcedInstruction, value: u32) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match to_enum::<Code>(value, "Code") {
+		Ok(value) => {
+			instruction.set_code(value);
+			ICED_OK
+		}
+		Err(status) => status,
+	}
+}
+
+/// Sets the `LOCK` prefix
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_has_lock_prefix(instruction: *mut IcedInstruction, value: bool) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_has_lock_prefix(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the `REP`/`REPE` prefix
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_has_rep_prefix(instruction: *mut IcedInstruction, value: bool) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_has_rep_prefix(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the `REPNE` prefix
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_has_repne_prefix(instruction: *mut IcedInstruction, value: bool) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_has_repne_prefix(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the segment override prefix (a `Register` enum value) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_segment_prefix(instruction: *mut IcedInstruction, value: u32) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match to_enum::<Register>(value, "Register") {
+		Ok(value) => {
+			instruction.set_segment_prefix(value);
+			ICED_OK
+		}
+		Err(status) => status,
+	}
+}
+
+/// Sets the memory operand's base register (a `Register` enum value) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_memory_base(instruction: *mut IcedInstruction, value: u32) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match to_enum::<Register>(value, "Register") {
+		Ok(value) => {
+			instruction.set_memory_base(value);
+			ICED_OK
+		}
+		Err(status) => status,
+	}
+}
+
+/// Sets the memory operand's index register (a `Register` enum value) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_memory_index(instruction: *mut IcedInstruction, value: u32) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match to_enum::<Register>(value, "Register") {
+		Ok(value) => {
+			instruction.set_memory_index(value);
+			ICED_OK
+		}
+		Err(status) => status,
+	}
+}
+
+/// Sets the index register scale value, valid values are 1, 2, 4, 8
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_memory_index_scale(instruction: *mut IcedInstruction, value: u32) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_memory_index_scale(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the memory operand's displacement
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_memory_displacement64(instruction: *mut IcedInstruction, value: u64) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_memory_displacement64(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the size of the memory displacement in bytes (0, 1, 2, 4 or 8)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_memory_displ_size(instruction: *mut IcedInstruction, value: u32) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_memory_displ_size(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the target of a 64-bit near branch (the operand kind must be `OpKind::NearBranch64`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_near_branch64(instruction: *mut IcedInstruction, value: u64) -> IcedStatus {
+	match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => {
+			instruction.set_near_branch64(value);
+			ICED_OK
+		}
+		None => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Sets the opmask register (a `Register` enum value, `Register::K1`-`Register::K7`) or `Register::None` if none
+#[no_mangle]
+pub unsafe extern "C" fn iced_instruction_set_op_mask(instruction: *mut IcedInstruction, value: u32) -> IcedStatus {
+	let instruction = match unsafe { to_iced_mut(instruction) } {
+		Some(instruction) => instruction,
+		None => return ICED_ERROR_NULL_POINTER,
+	};
+	match to_enum::<Register>(value, "Register") {
+		Ok(value) => {
+			instruction.set_op_mask(value);
+			ICED_OK
+		}
+		Err(status) => status,
+	}
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+//! C API for iced-x86
+//!
+//! All functions are exported with the `iced_` prefix and are declared in `include/iced_x86.h`.
+//!
+//! Rules:
+//!
+//! - Objects created by `iced_*_new()` must be freed by the corresponding `iced_*_free()` function.
+//! - Functions that can fail return an [`IcedStatus`]. The error message of the last failed call
+//!   on the current thread can be read with [`iced_last_error()`].
+//! - Enum values (`Code`, `Register`, `Mnemonic`, `OpKind`, etc.) are passed as `uint32_t` and have the same
+//!   values as the Rust/.NET/Java enums.
+//! - [`IcedInstruction`] is a POD type that can be copied with `memcpy()`. Its fields are private and must
+//!   only be accessed with the `iced_instruction_*()` functions.
+//! - Strings are UTF-8 and returned strings are NUL-terminated.
+//!
+//! [`IcedStatus`]: type.IcedStatus.html
+//! [`iced_last_error()`]: fn.iced_last_error.html
+//! [`IcedInstruction`]: struct.IcedInstruction.html
+
+#![allow(unknown_lints)]
+#![warn(absolute_paths_not_starting_with_crate)]
+#![warn(anonymous_parameters)]
+#![warn(elided_lifetimes_in_paths)]
+#![warn(explicit_outlives_requirements)]
+#![warn(invalid_html_tags)]
+#![warn(keyword_idents)]
+#![warn(macro_use_extern_crate)]
+#![warn(meta_variable_misuse)]
+#![warn(missing_docs)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(non_ascii_idents)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unsafe_op_in_unsafe_fn)]
+#![warn(unused_extern_crates)]
+#![warn(unused_import_braces)]
+#![warn(unused_lifetimes)]
+#![warn(unused_must_use)]
+#![warn(unused_results)]
+#![allow(clippy::cast_lossless)]
+#![allow(clippy::collapsible_else_if)]
+#![allow(clippy::collapsible_if)]
+#![allow(clippy::field_reassign_with_default)]
+#![allow(clippy::manual_range_contains)]
+#![allow(clippy::match_ref_pats)]
+#![allow(clippy::ptr_eq)]
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::type_complexity)]
+#![allow(clippy::upper_case_acronyms)]
+#![allow(clippy::wrong_self_convention)]
+#![warn(clippy::cloned_instead_of_copied)]
+#![warn(clippy::dbg_macro)]
+#![warn(clippy::debug_assert_with_mut_call)]
+#![warn(clippy::default_trait_access)]
+#![warn(clippy::doc_markdown)]
+#![warn(clippy::empty_line_after_outer_attr)]
+#![warn(clippy::expect_used)]
+#![warn(clippy::explicit_into_iter_loop)]
+#![warn(clippy::explicit_iter_loop)]
+#![warn(clippy::fallible_impl_from)]
+#![warn(clippy::get_unwrap)]
+#![warn(clippy::implicit_saturating_sub)]
+#![warn(clippy::large_digit_groups)]
+#![warn(clippy::let_unit_value)]
+#![warn(clippy::match_bool)]
+#![warn(clippy::match_wild_err_arm)]
+#![warn(clippy::missing_errors_doc)]
+#![warn(clippy::needless_borrow)]
+#![warn(clippy::print_stderr)]
+#![warn(clippy::print_stdout)]
+#![warn(clippy::rc_buffer)]
+#![warn(clippy::redundant_closure)]
+#![warn(clippy::redundant_closure_for_method_calls)]
+#![warn(clippy::same_functions_in_if_condition)]
+#![warn(clippy::todo)]
+#![warn(clippy::unimplemented)]
+#![warn(clippy::unreadable_literal)]
+#![warn(clippy::unused_self)]
+#![warn(clippy::unwrap_in_result)]
+#![warn(clippy::unwrap_used)]
+#![warn(clippy::useless_let_if_seq)]
+#![warn(clippy::useless_transmute)]
+#![warn(clippy::zero_sized_map_values)]
+// All exported functions are `unsafe` and have the same requirements: all pointers must be valid (or null
+// if the function says so) and objects must have been created by this library.
+#![allow(clippy::missing_safety_doc)]
+
+mod block_encoder;
+mod decoder;
+mod encoder;
+mod error;
+mod formatter;
+mod info;
+mod instruction;
+mod op_code_info;
+mod utils;
+
+pub use crate::block_encoder::*;
+pub use crate::decoder::*;
+pub use crate::encoder::*;
+pub use crate::error::*;
+pub use crate::formatter::*;
+pub use crate::info::*;
+pub use crate::instruction::*;
+pub use crate::op_code_info::*;
@@ -0,0 +1,1020 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::{set_last_error, IcedStatus, ICED_ERROR_INVALID_ARGUMENT, ICED_ERROR_NULL_POINTER, ICED_OK};
+use crate::utils::{copy_str, to_enum};
+use iced_x86::{Code, OpCodeInfo};
+use std::os::raw::c_char;
+use std::ptr;
+
+/// Opcode info of a `Code` value, returned by `iced_op_code_info()`. It's a static object and must not be freed.
+#[repr(transparent)]
+#[derive(Debug)]
+pub struct IcedOpCodeInfo {
+	info: OpCodeInfo,
+}
+
+/// Gets the opcode info of a `Code` value. The returned pointer is valid for the lifetime of the program.
+/// Returns null if `code` is invalid.
+///
+/// # Arguments
+///
+/// * `code`: Code (a `Code` enum value)
+#[no_mangle]
+pub extern "C" fn iced_op_code_info(code: u32) -> *const IcedOpCodeInfo {
+	match to_enum::<Code>(code, "Code") {
+		Ok(code) => {
+			let info: *const OpCodeInfo = code.op_code();
+			info.cast::<IcedOpCodeInfo>()
+		}
+		Err(_) => ptr::null(),
+	}
+}
+
+/// Gets the code (a `Code` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_code(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.code() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the mnemonic (a `Mnemonic` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_mnemonic(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.mnemonic() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the encoding (an `EncodingKind` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_encoding(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.encoding() as u32,
+		None => 0,
+	}
+}
+
+/// `true` if it's an instruction, `false` if it's eg. `Code::INVALID`, `db`, `dw`, `dd`, `dq`, `zero_bytes`
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_instruction(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_instruction(),
+		None => false,
+	}
+}
+
+/// `true` if it's an instruction available in 16-bit mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_mode16(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.mode16(),
+		None => false,
+	}
+}
+
+/// `true` if it's an instruction available in 32-bit mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_mode32(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.mode32(),
+		None => false,
+	}
+}
+
+/// `true` if it's an instruction available in 64-bit mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_mode64(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.mode64(),
+		None => false,
+	}
+}
+
+/// `true` if an `FWAIT` (`9B`) instruction is added before the instruction
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_fwait(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.fwait(),
+		None => false,
+	}
+}
+
+/// (Legacy encoding) Gets the required operand size (16,32,64) or 0
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_operand_size(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.operand_size(),
+		None => 0,
+	}
+}
+
+/// (Legacy encoding) Gets the required address size (16,32,64) or 0
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_address_size(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.address_size(),
+		None => 0,
+	}
+}
+
+/// (VEX/XOP/EVEX) `L` / `L'L` value or default value if `is_lig()` is `true`
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_l(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.l(),
+		None => 0,
+	}
+}
+
+/// (VEX/XOP/EVEX/MVEX) `W` value or default value if `is_wig()` or `is_wig32()` is `true`
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_w(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.w(),
+		None => 0,
+	}
+}
+
+/// (VEX/XOP/EVEX) `true` if the `L` / `L'L` fields are ignored.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_lig(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_lig(),
+		None => false,
+	}
+}
+
+/// (VEX/XOP/EVEX/MVEX) `true` if the `W` field is ignored in 16/32/64-bit modes
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_wig(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_wig(),
+		None => false,
+	}
+}
+
+/// (VEX/XOP/EVEX/MVEX) `true` if the `W` field is ignored in 16/32-bit modes (but not 64-bit mode)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_wig32(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_wig32(),
+		None => false,
+	}
+}
+
+/// (EVEX/MVEX) Gets the tuple type (a `TupleType` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_tuple_type(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.tuple_type() as u32,
+		None => 0,
+	}
+}
+
+/// If it has a memory operand, gets the `MemorySize` (non-broadcast memory type) (a `MemorySize` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_memory_size(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.memory_size() as u32,
+		None => 0,
+	}
+}
+
+/// If it has a memory operand, gets the `MemorySize` (broadcast memory type) (a `MemorySize` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_broadcast_memory_size(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.broadcast_memory_size() as u32,
+		None => 0,
+	}
+}
+
+/// (EVEX) `true` if the instruction supports broadcasting (`EVEX.b` bit) (if it has a memory operand)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_broadcast(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_broadcast(),
+		None => false,
+	}
+}
+
+/// (EVEX/MVEX) `true` if the instruction supports rounding control
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_rounding_control(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_rounding_control(),
+		None => false,
+	}
+}
+
+/// (EVEX/MVEX) `true` if the instruction supports suppress all exceptions
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_suppress_all_exceptions(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_suppress_all_exceptions(),
+		None => false,
+	}
+}
+
+/// (EVEX/MVEX) `true` if an opmask register can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_op_mask_register(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_op_mask_register(),
+		None => false,
+	}
+}
+
+/// (EVEX/MVEX) `true` if a non-zero opmask register must be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_require_op_mask_register(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.require_op_mask_register(),
+		None => false,
+	}
+}
+
+/// (EVEX) `true` if the instruction supports zeroing masking (if one of the opmask registers `K1`-`K7` is used and destination operand is not a memory operand)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_zeroing_masking(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_zeroing_masking(),
+		None => false,
+	}
+}
+
+/// `true` if the `LOCK` (`F0`) prefix can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_lock_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_lock_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if the `XACQUIRE` (`F2`) prefix can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_xacquire_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_xacquire_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if the `XRELEASE` (`F3`) prefix can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_xrelease_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_xrelease_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if the `REP` / `REPE` (`F3`) prefixes can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_rep_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_rep_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if the `REPNE` (`F2`) prefix can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_repne_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_repne_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if the `BND` (`F2`) prefix can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_bnd_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_bnd_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if the `HINT-TAKEN` (`3E`) and `HINT-NOT-TAKEN` (`2E`) prefixes can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_hint_taken_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_hint_taken_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if the `NOTRACK` (`3E`) prefix can be used
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_can_use_notrack_prefix(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.can_use_notrack_prefix(),
+		None => false,
+	}
+}
+
+/// `true` if rounding control is ignored (#UD is not generated)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_ignores_rounding_control(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.ignores_rounding_control(),
+		None => false,
+	}
+}
+
+/// `true` if the `LOCK` prefix can be used as an extra register bit (bit 3) to access registers 8-15 without a `REX` prefix (eg. in 32-bit mode)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_amd_lock_reg_bit(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.amd_lock_reg_bit(),
+		None => false,
+	}
+}
+
+/// `true` if the default operand size is 64 in 64-bit mode. A `66` prefix can switch to 16-bit operand size.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_default_op_size64(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.default_op_size64(),
+		None => false,
+	}
+}
+
+/// `true` if the operand size is always 64 in 64-bit mode. A `66` prefix is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_force_op_size64(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.force_op_size64(),
+		None => false,
+	}
+}
+
+/// `true` if the Intel decoder forces 64-bit operand size. A `66` prefix is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_intel_force_op_size64(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.intel_force_op_size64(),
+		None => false,
+	}
+}
+
+/// `true` if it can only be executed when CPL=0
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_must_be_cpl0(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.must_be_cpl0(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed when CPL=0
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_cpl0(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.cpl0(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed when CPL=1
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_cpl1(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.cpl1(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed when CPL=2
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_cpl2(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.cpl2(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed when CPL=3
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_cpl3(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.cpl3(),
+		None => false,
+	}
+}
+
+/// `true` if the instruction accesses the I/O address space (eg. `IN`, `OUT`, `INS`, `OUTS`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_input_output(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_input_output(),
+		None => false,
+	}
+}
+
+/// `true` if it's one of the many nop instructions (does not include FPU nop instructions, eg. `FNOP`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_nop(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_nop(),
+		None => false,
+	}
+}
+
+/// `true` if it's one of the many reserved nop instructions (eg. `0F0D`, `0F18-0F1F`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_reserved_nop(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_reserved_nop(),
+		None => false,
+	}
+}
+
+/// `true` if it's a serializing instruction (Intel CPUs)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_serializing_intel(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_serializing_intel(),
+		None => false,
+	}
+}
+
+/// `true` if it's a serializing instruction (AMD CPUs)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_serializing_amd(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_serializing_amd(),
+		None => false,
+	}
+}
+
+/// `true` if the instruction requires either CPL=0 or CPL<=3 depending on some CPU option (eg. `CR4.TSD`, `CR4.PCE`, `CR4.UMIP`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_may_require_cpl0(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.may_require_cpl0(),
+		None => false,
+	}
+}
+
+/// `true` if it's a tracked `JMP`/`CALL` indirect instruction (CET)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_cet_tracked(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_cet_tracked(),
+		None => false,
+	}
+}
+
+/// `true` if it's a non-temporal hint memory access (eg. `MOVNTDQ`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_non_temporal(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_non_temporal(),
+		None => false,
+	}
+}
+
+/// `true` if it's a no-wait FPU instruction, eg. `FNINIT`
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_fpu_no_wait(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_fpu_no_wait(),
+		None => false,
+	}
+}
+
+/// `true` if the mod bits are ignored and it's assumed `modrm[7:6] == 11b`
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_ignores_mod_bits(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.ignores_mod_bits(),
+		None => false,
+	}
+}
+
+/// `true` if the `66` prefix is not allowed (it will #UD)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_no66(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.no66(),
+		None => false,
+	}
+}
+
+/// `true` if the `F2`/`F3` prefixes aren't allowed
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_nfx(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.nfx(),
+		None => false,
+	}
+}
+
+/// `true` if the index reg's reg-num (vsib op) (if any) and register ops' reg-nums must be unique,
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_requires_unique_reg_nums(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.requires_unique_reg_nums(),
+		None => false,
+	}
+}
+
+/// `true` if the destination register's reg-num must not be present in any other operand, eg. `MNEMONIC XMM1,YMM1,[RAX+ZMM1*2]`
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_requires_unique_dest_reg_num(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.requires_unique_dest_reg_num(),
+		None => false,
+	}
+}
+
+/// `true` if it's a privileged instruction (all CPL=0 instructions (except `VMCALL`) and IOPL instructions `IN`, `INS`, `OUT`, `OUTS`, `CLI`, `STI`)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_privileged(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_privileged(),
+		None => false,
+	}
+}
+
+/// `true` if it reads/writes too many registers
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_save_restore(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_save_restore(),
+		None => false,
+	}
+}
+
+/// `true` if it's an instruction that implicitly uses the stack register, eg. `CALL`, `POP`, etc
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_stack_instruction(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_stack_instruction(),
+		None => false,
+	}
+}
+
+/// `true` if the instruction doesn't read the segment register if it uses a memory operand
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_ignores_segment(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.ignores_segment(),
+		None => false,
+	}
+}
+
+/// `true` if the opmask register is read and written (instead of just read). This also implies that it can't be `K0`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_op_mask_read_write(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_op_mask_read_write(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed in real mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_real_mode(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.real_mode(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed in protected mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_protected_mode(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.protected_mode(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed in virtual 8086 mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_virtual8086_mode(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.virtual8086_mode(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed in compatibility mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_compatibility_mode(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.compatibility_mode(),
+		None => false,
+	}
+}
+
+/// `true` if it can be executed in 64-bit mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_long_mode(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.long_mode(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used outside SMM
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_outside_smm(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_outside_smm(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used in SMM
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_in_smm(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_in_smm(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used outside an enclave (SGX)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_outside_enclave_sgx(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_outside_enclave_sgx(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used inside an enclave (SGX1)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_in_enclave_sgx1(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_in_enclave_sgx1(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used inside an enclave (SGX2)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_in_enclave_sgx2(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_in_enclave_sgx2(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used outside VMX operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_outside_vmx_op(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_outside_vmx_op(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used in VMX root operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_in_vmx_root_op(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_in_vmx_root_op(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used in VMX non-root operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_in_vmx_non_root_op(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_in_vmx_non_root_op(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used outside SEAM
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_outside_seam(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_outside_seam(),
+		None => false,
+	}
+}
+
+/// `true` if it can be used in SEAM
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_use_in_seam(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.use_in_seam(),
+		None => false,
+	}
+}
+
+/// `true` if #UD is generated in TDX non-root operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_tdx_non_root_gen_ud(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.tdx_non_root_gen_ud(),
+		None => false,
+	}
+}
+
+/// `true` if #VE is generated in TDX non-root operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_tdx_non_root_gen_ve(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.tdx_non_root_gen_ve(),
+		None => false,
+	}
+}
+
+/// `true` if an exception (eg. #GP(0), #VE) may be generated in TDX non-root operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_tdx_non_root_may_gen_ex(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.tdx_non_root_may_gen_ex(),
+		None => false,
+	}
+}
+
+/// (Intel VMX) `true` if it causes a VM exit in VMX non-root operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_intel_vm_exit(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.intel_vm_exit(),
+		None => false,
+	}
+}
+
+/// (Intel VMX) `true` if it may cause a VM exit in VMX non-root operation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_intel_may_vm_exit(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.intel_may_vm_exit(),
+		None => false,
+	}
+}
+
+/// (Intel VMX) `true` if it causes an SMM VM exit in VMX root operation (if dual-monitor treatment is activated)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_intel_smm_vm_exit(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.intel_smm_vm_exit(),
+		None => false,
+	}
+}
+
+/// (AMD SVM) `true` if it causes a #VMEXIT in guest mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_amd_vm_exit(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.amd_vm_exit(),
+		None => false,
+	}
+}
+
+/// (AMD SVM) `true` if it may cause a #VMEXIT in guest mode
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_amd_may_vm_exit(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.amd_may_vm_exit(),
+		None => false,
+	}
+}
+
+/// `true` if it causes a TSX abort inside a TSX transaction
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_tsx_abort(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.tsx_abort(),
+		None => false,
+	}
+}
+
+/// `true` if it causes a TSX abort inside a TSX transaction depending on the implementation
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_tsx_impl_abort(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.tsx_impl_abort(),
+		None => false,
+	}
+}
+
+/// `true` if it may cause a TSX abort inside a TSX transaction depending on some condition
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_tsx_may_abort(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.tsx_may_abort(),
+		None => false,
+	}
+}
+
+/// `true` if it's decoded by iced's 16-bit Intel decoder
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_intel_decoder16(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.intel_decoder16(),
+		None => false,
+	}
+}
+
+/// `true` if it's decoded by iced's 32-bit Intel decoder
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_intel_decoder32(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.intel_decoder32(),
+		None => false,
+	}
+}
+
+/// `true` if it's decoded by iced's 64-bit Intel decoder
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_intel_decoder64(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.intel_decoder64(),
+		None => false,
+	}
+}
+
+/// `true` if it's decoded by iced's 16-bit AMD decoder
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_amd_decoder16(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.amd_decoder16(),
+		None => false,
+	}
+}
+
+/// `true` if it's decoded by iced's 32-bit AMD decoder
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_amd_decoder32(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.amd_decoder32(),
+		None => false,
+	}
+}
+
+/// `true` if it's decoded by iced's 64-bit AMD decoder
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_amd_decoder64(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.amd_decoder64(),
+		None => false,
+	}
+}
+
+/// Gets the decoder option that's needed to decode the instruction or `DecoderOptions::NONE`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_decoder_option(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.decoder_option(),
+		None => 0,
+	}
+}
+
+/// Gets the opcode table (a `OpCodeTableKind` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_table(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.table() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the mandatory prefix (a `MandatoryPrefix` enum value)
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_mandatory_prefix(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.mandatory_prefix() as u32,
+		None => 0,
+	}
+}
+
+/// Gets the opcode byte(s). The low byte(s) of this value is the opcode. The length is in `op_code_len()`.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_op_code(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.op_code(),
+		None => 0,
+	}
+}
+
+/// Gets the length of the opcode bytes (`op_code()`). The low bytes is the opcode value.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_op_code_len(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.op_code_len(),
+		None => 0,
+	}
+}
+
+/// `true` if it's part of a group
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_group(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_group(),
+		None => false,
+	}
+}
+
+/// Group index (0-7) or -1. If it's 0-7, it's stored in the `reg` field of the `modrm` byte.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_group_index(info: *const IcedOpCodeInfo) -> i32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.group_index(),
+		None => 0,
+	}
+}
+
+/// `true` if it's part of a modrm.rm group
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_is_rm_group(info: *const IcedOpCodeInfo) -> bool {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.is_rm_group(),
+		None => false,
+	}
+}
+
+/// Group index (0-7) or -1. If it's 0-7, it's stored in the `rm` field of the `modrm` byte.
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_rm_group_index(info: *const IcedOpCodeInfo) -> i32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.rm_group_index(),
+		None => 0,
+	}
+}
+
+/// Gets the number of operands
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_op_count(info: *const IcedOpCodeInfo) -> u32 {
+	match unsafe { info.as_ref() } {
+		Some(info) => info.info.op_count(),
+		None => 0,
+	}
+}
+
+/// Gets an operand's opkind (an `OpCodeOperandKind` enum value)
+///
+/// # Arguments
+///
+/// * `info`: Opcode info
+/// * `operand`: Operand number, 0-4
+/// * `kind`: (out) Operand kind
+///
+/// # Errors
+///
+/// Returns `ICED_ERROR_INVALID_ARGUMENT` if `operand` is invalid
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_op_kind(info: *const IcedOpCodeInfo, operand: u32, kind: *mut u32) -> IcedStatus {
+	match unsafe { (info.as_ref(), kind.as_mut()) } {
+		(Some(info), Some(kind)) => match info.info.try_op_kind(operand) {
+			Ok(op_kind) => {
+				*kind = op_kind as u32;
+				ICED_OK
+			}
+			Err(error) => {
+				set_last_error(&error.to_string());
+				ICED_ERROR_INVALID_ARGUMENT
+			}
+		},
+		_ => ICED_ERROR_NULL_POINTER,
+	}
+}
+
+/// Gets the instruction string, eg. `VPBROADCASTB xmm1 {k1}{z}, xmm2/m8`. See `iced_formatter_format()` for the
+/// return value.
+///
+/// # Arguments
+///
+/// * `info`: Opcode info
+/// * `buffer`: (out) Instruction string
+/// * `size`: Size of `buffer` in bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_instruction_string(info: *const IcedOpCodeInfo, buffer: *mut c_char, size: usize) -> usize {
+	let text = match unsafe { info.as_ref() } {
+		Some(info) => info.info.instruction_string(),
+		None => "",
+	};
+	unsafe { copy_str(text, buffer, size) }
+}
+
+/// Gets the opcode string, eg. `EVEX.128.66.0F38.W0 78 /r`. See `iced_formatter_format()` for the return value.
+///
+/// # Arguments
+///
+/// * `info`: Opcode info
+/// * `buffer`: (out) Opcode string
+/// * `size`: Size of `buffer` in bytes
+#[no_mangle]
+pub unsafe extern "C" fn iced_op_code_info_op_code_string(info: *const IcedOpCodeInfo, buffer: *mut c_char, size: usize) -> usize {
+	let text = match unsafe { info.as_ref() } {
+		Some(info) => info.info.op_code_string(),
+		None => "",
+	};
+	unsafe { copy_str(text, buffer, size) }
+}
@@ -0,0 +1,39 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::{set_last_error, IcedStatus, ICED_ERROR_INVALID_ARGUMENT};
+use core::convert::TryFrom;
+use std::os::raw::c_char;
+use std::ptr;
+
+// Converts a `uint32_t` enum value from C to a Rust enum
+pub(crate) fn to_enum<T: TryFrom<usize>>(value: u32, name: &str) -> Result<T, IcedStatus> {
+	T::try_from(value as usize).map_err(|_| {
+		set_last_error(&format!("Invalid {} value: {}", name, value));
+		ICED_ERROR_INVALID_ARGUMENT
+	})
+}
+
+// Copies `text` to `buffer` (if it's not null) and NUL-terminates it. The string is truncated if the buffer is too
+// small. Returns the length of `text` in bytes (not including the NUL byte), like `snprintf()`.
+pub(crate) unsafe fn copy_str(text: &str, buffer: *mut c_char, size: usize) -> usize {
+	if !buffer.is_null() && size != 0 {
+		let len = text.len().min(size - 1);
+		unsafe {
+			ptr::copy_nonoverlapping(text.as_ptr().cast::<c_char>(), buffer, len);
+			*buffer.add(len) = 0;
+		}
+	}
+	text.len()
+}
+
+// Creates a slice from a pointer that can be null if `len` is 0
+pub(crate) unsafe fn slice_from_raw<'a, T>(data: *const T, len: usize) -> Option<&'a [T]> {
+	if len == 0 {
+		Some(&[])
+	} else if data.is_null() {
+		None
+	} else {
+		Some(unsafe { std::slice::from_raw_parts(data, len) })
+	}
+}
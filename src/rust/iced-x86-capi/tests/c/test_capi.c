@@ -0,0 +1,378 @@
+/*
+ * SPDX-License-Identifier: MIT
+ * Copyright (C) 2018-present iced project and contributors
+ */
+
+/* Compiled and executed by tests/c_api.rs */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "iced_x86.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                       \
+	do {                                                                  \
+		if (!(cond)) {                                                    \
+			fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+			failures++;                                                   \
+		}                                                                 \
+	} while (0)
+
+#define CHECK_STR(actual, expected)                                                                   \
+	do {                                                                                              \
+		if (strcmp((actual), (expected)) != 0) {                                                      \
+			fprintf(stderr, "%s:%d: expected \"%s\", got \"%s\"\n", __FILE__, __LINE__, (expected), (actual)); \
+			failures++;                                                                               \
+		}                                                                                             \
+	} while (0)
+
+/* mov [rsp+10h],rbx / push rbp / push rdi / call 7FFAC46ACF00h */
+static const uint8_t CODE[] = {0x48, 0x89, 0x5C, 0x24, 0x10, 0x55, 0x57, 0xE8, 0x50, 0x01, 0x00, 0x00};
+#define CODE_RIP 0x7FFAC46ACDA4ULL
+#define CALL_TARGET 0x7FFAC46ACF00ULL
+
+static void test_decoder(void) {
+	IcedDecoder *decoder;
+	IcedInstruction instrs[8];
+	size_t count;
+
+	decoder = iced_decoder_new(15, CODE, sizeof(CODE), CODE_RIP, ICED_DECODER_OPTIONS_NONE);
+	CHECK(decoder == NULL);
+	CHECK(strlen(iced_last_error()) != 0);
+
+	decoder = iced_decoder_new(64, CODE, sizeof(CODE), CODE_RIP, ICED_DECODER_OPTIONS_NONE);
+	CHECK(decoder != NULL);
+	CHECK(iced_decoder_bitness(decoder) == 64);
+	CHECK(iced_decoder_max_position(decoder) == sizeof(CODE));
+	count = iced_decoder_decode_many(decoder, instrs, 8);
+	CHECK(count == 4);
+	CHECK(!iced_decoder_can_decode(decoder));
+	CHECK(iced_instruction_ip(&instrs[0]) == CODE_RIP);
+	CHECK(iced_instruction_len(&instrs[0]) == 5);
+	CHECK(iced_instruction_next_ip(&instrs[0]) == CODE_RIP + 5);
+	CHECK(iced_instruction_op_count(&instrs[0]) == 2);
+	CHECK(iced_instruction_memory_displacement64(&instrs[0]) == 0x10);
+	CHECK(iced_instruction_near_branch_target(&instrs[3]) == CALL_TARGET);
+	CHECK(!iced_instruction_is_invalid(&instrs[3]));
+
+	CHECK(iced_decoder_set_position(decoder, 100) == ICED_ERROR_INVALID_ARGUMENT);
+	CHECK(iced_decoder_set_position(decoder, 0) == ICED_OK);
+	CHECK(iced_decoder_set_ip(decoder, CODE_RIP) == ICED_OK);
+	{
+		IcedInstruction instr;
+		IcedConstantOffsets offsets;
+		CHECK(iced_decoder_decode(decoder, &instr) == ICED_OK);
+		CHECK(iced_instruction_eq_all_bits(&instr, &instrs[0]));
+		CHECK(iced_decoder_get_constant_offsets(decoder, &instr, &offsets) == ICED_OK);
+		CHECK(offsets.displacement_offset == 4);
+		CHECK(offsets.displacement_size == 1);
+		CHECK(offsets.immediate_size == 0);
+	}
+	iced_decoder_free(decoder);
+}
+
+static void decode_code(IcedInstruction *instrs) {
+	IcedDecoder *decoder = iced_decoder_new(64, CODE, sizeof(CODE), CODE_RIP, ICED_DECODER_OPTIONS_NONE);
+	CHECK(iced_decoder_decode_many(decoder, instrs, 4) == 4);
+	iced_decoder_free(decoder);
+}
+
+typedef struct {
+	char text[256];
+	size_t mnemonic_count;
+	size_t register_count;
+} OutputState;
+
+static void write_output(void *user_data, const char *text, size_t len, uint32_t kind) {
+	OutputState *state = (OutputState *)user_data;
+	CHECK(strlen(text) == len);
+	strcat(state->text, text);
+	/* FormatterTextKind::Mnemonic and FormatterTextKind::Register */
+	if (kind == 3)
+		state->mnemonic_count++;
+	else if (kind == 8)
+		state->register_count++;
+}
+
+static bool resolve_symbol(void *user_data, const IcedInstruction *instruction, uint32_t operand, int32_t instruction_operand,
+                           uint64_t address, uint32_t address_size, IcedSymbolResult *result) {
+	(void)instruction;
+	(void)operand;
+	(void)instruction_operand;
+	(void)address_size;
+	if (address != *(const uint64_t *)user_data)
+		return false;
+	result->text = "my_func";
+	return true;
+}
+
+static void test_formatter(void) {
+	IcedInstruction instrs[4];
+	IcedFormatter *formatter;
+	char buffer[256];
+	size_t len;
+	uint32_t value;
+
+	decode_code(instrs);
+
+	CHECK(iced_formatter_new(100, NULL) == NULL);
+
+	formatter = iced_formatter_new(ICED_FORMATTER_SYNTAX_NASM, NULL);
+	CHECK(formatter != NULL);
+	len = iced_formatter_format(formatter, &instrs[0], buffer, sizeof(buffer));
+	CHECK_STR(buffer, "mov [rsp+10h],rbx");
+	CHECK(len == strlen("mov [rsp+10h],rbx"));
+
+	/* Truncated output */
+	len = iced_formatter_format(formatter, &instrs[0], buffer, 4);
+	CHECK_STR(buffer, "mov");
+	CHECK(len == strlen("mov [rsp+10h],rbx"));
+	CHECK(iced_formatter_format(formatter, &instrs[0], NULL, 0) == len);
+
+	CHECK(iced_formatter_set_option(formatter, ICED_FORMATTER_OPTION_UPPERCASE_MNEMONICS, 1) == ICED_OK);
+	CHECK(iced_formatter_get_option(formatter, ICED_FORMATTER_OPTION_UPPERCASE_MNEMONICS, &value) == ICED_OK);
+	CHECK(value == 1);
+	CHECK(iced_formatter_set_option(formatter, 12345, 1) == ICED_ERROR_INVALID_ARGUMENT);
+	CHECK(iced_formatter_set_string_option(formatter, ICED_FORMATTER_STRING_OPTION_HEX_SUFFIX, "") == ICED_OK);
+	CHECK(iced_formatter_set_string_option(formatter, ICED_FORMATTER_STRING_OPTION_HEX_PREFIX, "0x") == ICED_OK);
+	CHECK(iced_formatter_get_string_option(formatter, ICED_FORMATTER_STRING_OPTION_HEX_PREFIX, buffer, sizeof(buffer), &len) == ICED_OK);
+	CHECK_STR(buffer, "0x");
+	CHECK(len == 2);
+	iced_formatter_format(formatter, &instrs[0], buffer, sizeof(buffer));
+	CHECK_STR(buffer, "MOV [rsp+0x10],rbx");
+
+	iced_formatter_format_mnemonic(formatter, &instrs[0], 0, buffer, sizeof(buffer));
+	CHECK_STR(buffer, "MOV");
+	CHECK(iced_formatter_operand_count(formatter, &instrs[0]) == 2);
+	CHECK(iced_formatter_format_operand(formatter, &instrs[0], 1, buffer, sizeof(buffer), &len) == ICED_OK);
+	CHECK_STR(buffer, "rbx");
+	CHECK(iced_formatter_format_operand(formatter, &instrs[0], 5, buffer, sizeof(buffer), NULL) == ICED_ERROR_INVALID_ARGUMENT);
+	iced_formatter_format_all_operands(formatter, &instrs[0], buffer, sizeof(buffer));
+	CHECK_STR(buffer, "[rsp+0x10],rbx");
+	iced_formatter_format_register(formatter, iced_instruction_memory_base(&instrs[0]), buffer, sizeof(buffer));
+	CHECK_STR(buffer, "rsp");
+	{
+		int32_t op;
+		CHECK(iced_formatter_get_instruction_operand(formatter, &instrs[0], 1, &op) == ICED_OK);
+		CHECK(op == 1);
+		CHECK(iced_formatter_get_formatter_operand(formatter, &instrs[0], 0, &op) == ICED_OK);
+		CHECK(op == 0);
+	}
+	{
+		OutputState state;
+		IcedFormatterOutput output;
+		memset(&state, 0, sizeof(state));
+		output.user_data = &state;
+		output.write = write_output;
+		CHECK(iced_formatter_format_output(formatter, &instrs[0], &output) == ICED_OK);
+		CHECK_STR(state.text, "MOV [rsp+0x10],rbx");
+		CHECK(state.mnemonic_count == 1);
+		CHECK(state.register_count == 2);
+	}
+	iced_formatter_free(formatter);
+
+	formatter = iced_formatter_new(ICED_FORMATTER_SYNTAX_GAS, NULL);
+	iced_formatter_format(formatter, &instrs[0], buffer, sizeof(buffer));
+	CHECK_STR(buffer, "mov %rbx,0x10(%rsp)");
+	iced_formatter_free(formatter);
+
+	formatter = iced_formatter_new(ICED_FORMATTER_SYNTAX_INTEL, NULL);
+	iced_formatter_format(formatter, &instrs[0], buffer, sizeof(buffer));
+	CHECK_STR(buffer, "mov [rsp+10h],rbx");
+	iced_formatter_free(formatter);
+
+	formatter = iced_formatter_new(ICED_FORMATTER_SYNTAX_MASM, NULL);
+	iced_formatter_format(formatter, &instrs[0], buffer, sizeof(buffer));
+	CHECK_STR(buffer, "mov [rsp+10h],rbx");
+	iced_formatter_free(formatter);
+
+	{
+		uint64_t symbol_address = CALL_TARGET;
+		IcedSymbolResolver resolver;
+		IcedFastFormatter *fast_formatter;
+		resolver.user_data = &symbol_address;
+		resolver.symbol = resolve_symbol;
+
+		formatter = iced_formatter_new(ICED_FORMATTER_SYNTAX_NASM, &resolver);
+		iced_formatter_format(formatter, &instrs[3], buffer, sizeof(buffer));
+		CHECK_STR(buffer, "call my_func");
+		iced_formatter_format(formatter, &instrs[1], buffer, sizeof(buffer));
+		CHECK_STR(buffer, "push rbp");
+		iced_formatter_free(formatter);
+
+		fast_formatter = iced_fast_formatter_new(&resolver);
+		CHECK(fast_formatter != NULL);
+		iced_fast_formatter_format(fast_formatter, &instrs[3], buffer, sizeof(buffer));
+		CHECK_STR(buffer, "call my_func");
+		iced_fast_formatter_free(fast_formatter);
+	}
+
+	{
+		IcedFastFormatter *fast_formatter = iced_fast_formatter_new(NULL);
+		CHECK(iced_fast_formatter_set_option(fast_formatter, ICED_FAST_FORMATTER_OPTION_USE_HEX_PREFIX, 1) == ICED_OK);
+		CHECK(iced_fast_formatter_get_option(fast_formatter, ICED_FAST_FORMATTER_OPTION_USE_HEX_PREFIX, &value) == ICED_OK);
+		CHECK(value == 1);
+		iced_fast_formatter_format(fast_formatter, &instrs[0], buffer, sizeof(buffer));
+		CHECK_STR(buffer, "mov [rsp+0x10],rbx");
+		iced_fast_formatter_free(fast_formatter);
+	}
+}
+
+static void test_encoder(void) {
+	IcedInstruction instrs[4];
+	IcedEncoder *encoder;
+	const uint8_t *bytes;
+	size_t len;
+
+	decode_code(instrs);
+
+	CHECK(iced_encoder_new(15) == NULL);
+	encoder = iced_encoder_new(64);
+	CHECK(encoder != NULL);
+	CHECK(iced_encoder_encode(encoder, &instrs[0], 0x1000, &len) == ICED_OK);
+	CHECK(len == 5);
+	CHECK(iced_encoder_encode(encoder, &instrs[3], iced_instruction_ip(&instrs[3]), &len) == ICED_OK);
+	CHECK(len == 5);
+	bytes = iced_encoder_buffer(encoder, &len);
+	CHECK(len == 10);
+	CHECK(memcmp(bytes, CODE, 5) == 0);
+	CHECK(memcmp(bytes + 5, CODE + 7, 5) == 0);
+
+	/* A near branch that's too far away can't be encoded */
+	CHECK(iced_instruction_set_near_branch64(&instrs[3], 0x8000000000000000ULL) == ICED_OK);
+	CHECK(iced_encoder_encode(encoder, &instrs[3], 0x1000, &len) == ICED_ERROR_FAILED);
+	CHECK(strlen(iced_last_error()) != 0);
+	iced_encoder_free(encoder);
+}
+
+static void test_block_encoder(void) {
+	static const uint8_t RET[] = {0xC3};
+	IcedDecoder *decoder;
+	IcedInstruction instrs[4];
+	IcedBlockEncoderResult *result = NULL;
+	const uint8_t *code;
+	const uint32_t *offsets;
+	size_t len;
+
+	decode_code(instrs);
+
+	CHECK(iced_block_encoder_encode(64, instrs, 4, CODE_RIP,
+	                                ICED_BLOCK_ENCODER_OPTIONS_RETURN_NEW_INSTRUCTION_OFFSETS | ICED_BLOCK_ENCODER_OPTIONS_RETURN_RELOC_INFOS,
+	                                &result) == ICED_OK);
+	CHECK(result != NULL);
+	CHECK(iced_block_encoder_result_rip(result) == CODE_RIP);
+	code = iced_block_encoder_result_code(result, &len);
+	CHECK(len == sizeof(CODE));
+	CHECK(memcmp(code, CODE, sizeof(CODE)) == 0);
+	offsets = iced_block_encoder_result_new_instruction_offsets(result, &len);
+	CHECK(len == 4);
+	CHECK(offsets[0] == 0 && offsets[1] == 5 && offsets[2] == 6 && offsets[3] == 7);
+	/* The call is still a rel32 call so there are no relocations */
+	iced_block_encoder_result_reloc_infos(result, &len);
+	CHECK(len == 0);
+	iced_block_encoder_result_constant_offsets(result, &len);
+	CHECK(len == 0);
+	iced_block_encoder_result_free(result);
+
+	CHECK(iced_block_encoder_encode(15, instrs, 4, 0x1000, ICED_BLOCK_ENCODER_OPTIONS_NONE, &result) == ICED_ERROR_FAILED);
+	CHECK(result == NULL);
+
+	/* A ret ending on a 32-byte boundary gets a nop before it */
+	decoder = iced_decoder_new(64, RET, sizeof(RET), 0x101F, ICED_DECODER_OPTIONS_NONE);
+	CHECK(iced_decoder_decode_many(decoder, instrs, 4) == 1);
+	iced_decoder_free(decoder);
+	CHECK(iced_block_encoder_encode(64, instrs, 1, 0x101F, ICED_BLOCK_ENCODER_OPTIONS_JCC_ERRATUM, &result) == ICED_OK);
+	code = iced_block_encoder_result_code(result, &len);
+	CHECK(len == 2 && code[0] == 0x90 && code[1] == 0xC3);
+	iced_block_encoder_result_free(result);
+}
+
+static void test_info(void) {
+	IcedInstruction instrs[4];
+	IcedInstructionInfoFactory *factory;
+	IcedInstructionInfo info;
+
+	decode_code(instrs);
+
+	factory = iced_instruction_info_factory_new();
+	CHECK(iced_instruction_info_factory_info(factory, &instrs[0], ICED_INSTRUCTION_INFO_OPTIONS_NONE, &info) == ICED_OK);
+	CHECK(info.used_memory_count == 1);
+	CHECK(info.used_memory[0].base == iced_instruction_memory_base(&instrs[0]));
+	CHECK(info.used_memory[0].displacement == 0x10);
+	CHECK(info.used_memory[0].memory_size == iced_instruction_memory_size(&instrs[0]));
+	CHECK(info.used_registers_count == 2);
+	/* OpAccess::Write and OpAccess::Read */
+	CHECK(info.op_accesses[0] == 3);
+	CHECK(info.op_accesses[1] == 1);
+
+	CHECK(iced_instruction_info_factory_info(factory, &instrs[0], ICED_INSTRUCTION_INFO_OPTIONS_NO_MEMORY_USAGE, &info) == ICED_OK);
+	CHECK(info.used_memory_count == 0);
+	iced_instruction_info_factory_free(factory);
+}
+
+static void test_op_code_info(void) {
+	IcedInstruction instrs[4];
+	const IcedOpCodeInfo *info;
+	char buffer[256];
+	uint32_t kind;
+
+	decode_code(instrs);
+
+	CHECK(iced_op_code_info(0xFFFFFFFF) == NULL);
+	info = iced_op_code_info(iced_instruction_code(&instrs[0]));
+	CHECK(info != NULL);
+	CHECK(iced_op_code_info_code(info) == iced_instruction_code(&instrs[0]));
+	CHECK(iced_op_code_info_mnemonic(info) == iced_instruction_mnemonic(&instrs[0]));
+	CHECK(iced_op_code_info_is_instruction(info));
+	CHECK(iced_op_code_info_mode64(info));
+	CHECK(iced_op_code_info_op_code(info) == 0x89);
+	CHECK(iced_op_code_info_op_count(info) == 2);
+	CHECK(iced_op_code_info_op_kind(info, 0, &kind) == ICED_OK);
+	CHECK(iced_op_code_info_op_kind(info, 5, &kind) == ICED_ERROR_INVALID_ARGUMENT);
+	iced_op_code_info_instruction_string(info, buffer, sizeof(buffer));
+	CHECK_STR(buffer, "MOV r/m64, r64");
+	iced_op_code_info_op_code_string(info, buffer, sizeof(buffer));
+	CHECK_STR(buffer, "o64 89 /r");
+}
+
+static void test_instruction(void) {
+	IcedInstruction instrs[4];
+	IcedInstruction instr;
+	uint64_t imm;
+
+	decode_code(instrs);
+
+	instr = instrs[1];
+	CHECK(iced_instruction_eq(&instr, &instrs[1]));
+	CHECK(iced_instruction_set_ip(&instr, 0x1234) == ICED_OK);
+	CHECK(iced_instruction_eq(&instr, &instrs[1]));
+	CHECK(!iced_instruction_eq_all_bits(&instr, &instrs[1]));
+	CHECK(iced_instruction_is_stack_instruction(&instr));
+	CHECK(iced_instruction_stack_pointer_increment(&instr) == -8);
+
+	CHECK(iced_instruction_set_code(&instr, 0xFFFFFFFF) == ICED_ERROR_INVALID_ARGUMENT);
+	CHECK(iced_instruction_immediate(&instrs[0], 0, &imm) == ICED_ERROR_INVALID_ARGUMENT);
+	CHECK(iced_instruction_ip(NULL) == 0);
+	CHECK(iced_instruction_set_ip(NULL, 0) == ICED_ERROR_NULL_POINTER);
+
+	memset(&instr, 0, sizeof(instr));
+	CHECK(iced_instruction_is_invalid(&instr));
+}
+
+int main(void) {
+	test_decoder();
+	test_formatter();
+	test_encoder();
+	test_block_encoder();
+	test_info();
+	test_op_code_info();
+	test_instruction();
+	if (failures != 0) {
+		fprintf(stderr, "%d check(s) failed\n", failures);
+		return 1;
+	}
+	return 0;
+}
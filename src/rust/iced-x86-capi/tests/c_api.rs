@@ -0,0 +1,43 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+#![cfg(unix)]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// The shared library is in the same dir as the `deps` dir that contains this test executable
+fn target_dir() -> PathBuf {
+	let exe = env::current_exe().unwrap();
+	exe.parent().and_then(Path::parent).unwrap().to_path_buf()
+}
+
+// Compiles `tests/c/test_capi.c` with `$CC` (default `cc`), links it to the shared library and runs it
+#[test]
+fn c_api() {
+	let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+	let target_dir = target_dir();
+	let exe = target_dir.join("test_capi");
+	let cc = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+
+	let status = Command::new(cc)
+		.arg("-std=c99")
+		.arg("-Wall")
+		.arg("-Wextra")
+		.arg("-Werror")
+		.arg("-I")
+		.arg(crate_dir.join("include"))
+		.arg(crate_dir.join("tests").join("c").join("test_capi.c"))
+		.arg("-o")
+		.arg(&exe)
+		.arg(format!("-L{}", target_dir.display()))
+		.arg("-liced_x86_capi")
+		.arg(format!("-Wl,-rpath,{}", target_dir.display()))
+		.status()
+		.unwrap();
+	assert!(status.success(), "Couldn't compile the C test");
+
+	let status = Command::new(&exe).status().unwrap();
+	assert!(status.success(), "The C test failed");
+}
@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Verifies that `include/iced_x86.h` is up to date. Set `ICED_CAPI_UPDATE_HEADER=1` to regenerate it.
+#[test]
+fn header_is_up_to_date() {
+	let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+	let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml")).unwrap();
+	let bindings = cbindgen::Builder::new().with_crate(crate_dir).with_config(config).generate().unwrap();
+	let mut header = Vec::new();
+	bindings.write(&mut header);
+	let header = String::from_utf8(header).unwrap();
+
+	let header_path = crate_dir.join("include").join("iced_x86.h");
+	if env::var_os("ICED_CAPI_UPDATE_HEADER").is_some() {
+		fs::write(&header_path, &header).unwrap();
+	} else {
+		let existing = fs::read_to_string(&header_path).unwrap_or_default().replace("\r\n", "\n");
+		assert!(existing == header, "include/iced_x86.h is out of date, run: ICED_CAPI_UPDATE_HEADER=1 cargo test --test header");
+	}
+}
@@ -0,0 +1,931 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::block_encoder_options::BlockEncoderOptions;
+use crate::ex_utils::to_js_error;
+use crate::instruction::Instruction;
+use crate::mnemonic::{mnemonic_to_iced, Mnemonic};
+use crate::register::{register_to_iced, Register};
+use crate::rounding_control::{rounding_control_to_iced, RoundingControl};
+use wasm_bindgen::prelude::*;
+
+/// A label created by [`CodeAssembler.createLabel()`]
+///
+/// [`CodeAssembler.createLabel()`]: struct.CodeAssembler.html#method.create_label
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub struct CodeLabel(iced_x86_rust::code_asm::CodeLabel);
+
+/// An operand passed to one of [`CodeAssembler`]'s `emit*()` methods: a register, a memory operand, an immediate or a label.
+///
+/// [`CodeAssembler`]: struct.CodeAssembler.html
+///
+/// ```js
+/// const { AsmMemoryOperand, AsmOperand, CodeAssembler, Mnemonic, Register } = require("iced-x86");
+///
+/// const a = new CodeAssembler(64);
+/// const rax = AsmOperand.reg(Register.RAX);
+/// const mem = new AsmMemoryOperand(Register.RCX).withIndex(Register.RDX, 8).withDisplacement(0x10n).qwordPtr();
+/// a.emit2(Mnemonic.Add, rax, mem);
+/// const bytes = a.assemble(0x1234_5678n);
+///
+/// rax.free();
+/// mem.free();
+/// a.free();
+/// ```
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub struct AsmOperand(iced_x86_rust::code_asm::AsmOperand);
+
+#[wasm_bindgen]
+impl AsmOperand {
+	/// Creates a register operand
+	///
+	/// # Arguments
+	///
+	/// * `register`: Register (a [`Register`] enum value)
+	///
+	/// [`Register`]: enum.Register.html
+	pub fn reg(register: Register) -> AsmOperand {
+		Self(register_to_iced(register).into())
+	}
+
+	/// Creates a label operand, eg. a branch target
+	///
+	/// # Arguments
+	///
+	/// * `label`: Label created by [`CodeAssembler.createLabel()`]
+	///
+	/// [`CodeAssembler.createLabel()`]: struct.CodeAssembler.html#method.create_label
+	pub fn label(label: &CodeLabel) -> AsmOperand {
+		Self(label.0.into())
+	}
+
+	/// Creates a signed 32-bit immediate operand
+	///
+	/// # Arguments
+	///
+	/// * `value`: Immediate
+	#[wasm_bindgen(js_name = "immI32")]
+	pub fn imm_i32(value: i32) -> AsmOperand {
+		Self(value.into())
+	}
+
+	/// Creates an unsigned 32-bit immediate operand
+	///
+	/// # Arguments
+	///
+	/// * `value`: Immediate
+	#[wasm_bindgen(js_name = "immU32")]
+	pub fn imm_u32(value: u32) -> AsmOperand {
+		Self(value.into())
+	}
+
+	/// Creates a signed 64-bit immediate operand
+	///
+	/// # Arguments
+	///
+	/// * `value`: Immediate
+	#[wasm_bindgen(js_name = "immI64")]
+	pub fn imm_i64(value: i64) -> AsmOperand {
+		Self(value.into())
+	}
+
+	/// Creates an unsigned 64-bit immediate operand
+	///
+	/// # Arguments
+	///
+	/// * `value`: Immediate
+	#[wasm_bindgen(js_name = "immU64")]
+	pub fn imm_u64(value: u64) -> AsmOperand {
+		Self(value.into())
+	}
+
+	/// Adds an op mask register (`{k1}`-`{k7}`) to a register or memory operand and returns the new operand
+	///
+	/// # Throws
+	///
+	/// Throws if this is an immediate or label operand or if `opMask` isn't one of `K1`-`K7`
+	///
+	/// # Arguments
+	///
+	/// * `opMask`: Op mask register (a [`Register`] enum value)
+	///
+	/// [`Register`]: enum.Register.html
+	#[wasm_bindgen(js_name = "withOpMask")]
+	pub fn with_op_mask(&self, #[allow(non_snake_case)] opMask: Register) -> Result<AsmOperand, JsValue> {
+		Ok(Self(self.0.with_op_mask(register_to_iced(opMask)).map_err(to_js_error)?))
+	}
+
+	/// Enables zeroing-masking (`{z}`) and returns the new operand
+	///
+	/// # Throws
+	///
+	/// Throws if this isn't a register operand
+	#[wasm_bindgen(js_name = "withZeroingMasking")]
+	pub fn with_zeroing_masking(&self) -> Result<AsmOperand, JsValue> {
+		Ok(Self(self.0.with_zeroing_masking().map_err(to_js_error)?))
+	}
+
+	/// Enables suppress-all-exceptions (`{sae}`) and returns the new operand
+	///
+	/// # Throws
+	///
+	/// Throws if this isn't a register operand
+	#[wasm_bindgen(js_name = "withSuppressAllExceptions")]
+	pub fn with_suppress_all_exceptions(&self) -> Result<AsmOperand, JsValue> {
+		Ok(Self(self.0.with_suppress_all_exceptions().map_err(to_js_error)?))
+	}
+
+	/// Sets the rounding control (`{rn-sae}`, `{rd-sae}`, `{ru-sae}`, `{rz-sae}`) and returns the new operand
+	///
+	/// # Throws
+	///
+	/// Throws if this isn't a register operand
+	///
+	/// # Arguments
+	///
+	/// * `roundingControl`: Rounding control (a [`RoundingControl`] enum value)
+	///
+	/// [`RoundingControl`]: enum.RoundingControl.html
+	#[wasm_bindgen(js_name = "withRoundingControl")]
+	pub fn with_rounding_control(&self, #[allow(non_snake_case)] roundingControl: RoundingControl) -> Result<AsmOperand, JsValue> {
+		Ok(Self(self.0.with_rounding_control(rounding_control_to_iced(roundingControl)).map_err(to_js_error)?))
+	}
+
+	/// Adds a segment override to a memory operand and returns the new operand
+	///
+	/// # Throws
+	///
+	/// Throws if this isn't a memory operand or if `segment` isn't a segment register
+	///
+	/// # Arguments
+	///
+	/// * `segment`: Segment register (a [`Register`] enum value)
+	///
+	/// [`Register`]: enum.Register.html
+	#[wasm_bindgen(js_name = "withSegment")]
+	pub fn with_segment(&self, segment: Register) -> Result<AsmOperand, JsValue> {
+		Ok(Self(self.0.with_segment(register_to_iced(segment)).map_err(to_js_error)?))
+	}
+}
+
+/// Creates memory operands used by [`CodeAssembler`], eg. `qword ptr [rcx+rdx*8+10h]`.
+///
+/// Call one of the `*Ptr()`/`*Bcst()` methods to create an [`AsmOperand`].
+///
+/// [`CodeAssembler`]: struct.CodeAssembler.html
+/// [`AsmOperand`]: struct.AsmOperand.html
+///
+/// ```js
+/// const { AsmMemoryOperand, Register } = require("iced-x86");
+///
+/// // qword ptr [rcx+rdx*8+10h]
+/// const mem1 = new AsmMemoryOperand(Register.RCX).withIndex(Register.RDX, 8).withDisplacement(0x10n).qwordPtr();
+/// // dword bcst [rax]
+/// const mem2 = new AsmMemoryOperand(Register.RAX).dwordBcst();
+///
+/// mem1.free();
+/// mem2.free();
+/// ```
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub struct AsmMemoryOperand {
+	base: iced_x86_rust::Register,
+	index: iced_x86_rust::Register,
+	scale: u32,
+	displacement: i64,
+	label: Option<iced_x86_rust::code_asm::CodeLabel>,
+}
+
+#[wasm_bindgen]
+impl AsmMemoryOperand {
+	/// Constructor
+	///
+	/// # Arguments
+	///
+	/// * `base`: Base register or [`Register.None`] (a [`Register`] enum value)
+	///
+	/// [`Register`]: enum.Register.html
+	/// [`Register.None`]: enum.Register.html#variant.None
+	#[wasm_bindgen(constructor)]
+	pub fn new(base: Register) -> Self {
+		Self { base: register_to_iced(base), index: iced_x86_rust::Register::None, scale: 1, displacement: 0, label: None }
+	}
+
+	/// Creates a memory operand that references a label, eg. `lea rax,[label]`
+	///
+	/// # Arguments
+	///
+	/// * `label`: Label created by [`CodeAssembler.createLabel()`]
+	///
+	/// [`CodeAssembler.createLabel()`]: struct.CodeAssembler.html#method.create_label
+	#[wasm_bindgen(js_name = "fromLabel")]
+	pub fn from_label(label: &CodeLabel) -> Self {
+		Self { label: Some(label.0), ..Self::new(Register::None) }
+	}
+
+	/// Sets the index register and scale and returns the new memory operand
+	///
+	/// # Arguments
+	///
+	/// * `index`: Index register (a [`Register`] enum value)
+	/// * `scale`: Index register scale (1, 2, 4, or 8)
+	///
+	/// [`Register`]: enum.Register.html
+	#[wasm_bindgen(js_name = "withIndex")]
+	pub fn with_index(&self, index: Register, scale: u32) -> Self {
+		Self { index: register_to_iced(index), scale, ..*self }
+	}
+
+	/// Sets the displacement and returns the new memory operand
+	///
+	/// # Arguments
+	///
+	/// * `displacement`: Memory displacement
+	#[wasm_bindgen(js_name = "withDisplacement")]
+	pub fn with_displacement(&self, displacement: i64) -> Self {
+		Self { displacement, ..*self }
+	}
+
+	fn create(&self, size: u32, is_broadcast: bool) -> Result<AsmOperand, JsValue> {
+		let op = if let Some(label) = self.label {
+			if self.base != iced_x86_rust::Register::None || self.index != iced_x86_rust::Register::None || self.displacement != 0 {
+				return Err(js_sys::Error::new("A label memory operand can't have a base, index or displacement").into());
+			}
+			iced_x86_rust::code_asm::AsmMemoryOperand::from(label).into()
+		} else {
+			iced_x86_rust::code_asm::AsmOperand::memory(self.base, self.index, self.scale, self.displacement).map_err(to_js_error)?
+		};
+		let op = if is_broadcast { op.with_broadcast(size) } else { op.with_memory_size(size) };
+		Ok(AsmOperand(op.map_err(to_js_error)?))
+	}
+
+	/// Creates a memory operand without a size. Use it if the instruction's memory operand can only have one size.
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	pub fn ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(0, false)
+	}
+
+	/// Creates a `byte ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "bytePtr")]
+	pub fn byte_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(1, false)
+	}
+
+	/// Creates a `word ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "wordPtr")]
+	pub fn word_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(2, false)
+	}
+
+	/// Creates a `dword ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "dwordPtr")]
+	pub fn dword_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(4, false)
+	}
+
+	/// Creates a `fword ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "fwordPtr")]
+	pub fn fword_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(6, false)
+	}
+
+	/// Creates a `qword ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "qwordPtr")]
+	pub fn qword_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(8, false)
+	}
+
+	/// Creates a `tbyte ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "tbytePtr")]
+	pub fn tbyte_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(10, false)
+	}
+
+	/// Creates an `xmmword ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "xmmwordPtr")]
+	pub fn xmmword_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(16, false)
+	}
+
+	/// Creates a `ymmword ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "ymmwordPtr")]
+	pub fn ymmword_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(32, false)
+	}
+
+	/// Creates a `zmmword ptr [mem]` memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "zmmwordPtr")]
+	pub fn zmmword_ptr(&self) -> Result<AsmOperand, JsValue> {
+		self.create(64, false)
+	}
+
+	/// Creates a `word bcst [mem]` broadcast memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "wordBcst")]
+	pub fn word_bcst(&self) -> Result<AsmOperand, JsValue> {
+		self.create(2, true)
+	}
+
+	/// Creates a `dword bcst [mem]` broadcast memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "dwordBcst")]
+	pub fn dword_bcst(&self) -> Result<AsmOperand, JsValue> {
+		self.create(4, true)
+	}
+
+	/// Creates a `qword bcst [mem]` broadcast memory operand
+	///
+	/// # Throws
+	///
+	/// Throws if the scale is invalid
+	#[wasm_bindgen(js_name = "qwordBcst")]
+	pub fn qword_bcst(&self) -> Result<AsmOperand, JsValue> {
+		self.create(8, true)
+	}
+}
+
+/// Result of [`CodeAssembler.assembleOptions()`]
+///
+/// [`CodeAssembler.assembleOptions()`]: struct.CodeAssembler.html#method.assemble_options
+#[wasm_bindgen]
+pub struct CodeAssemblerResult(iced_x86_rust::code_asm::CodeAssemblerResult);
+
+#[wasm_bindgen]
+impl CodeAssemblerResult {
+	/// Base IP of all encoded instructions
+	#[wasm_bindgen(getter)]
+	pub fn rip(&self) -> u64 {
+		self.0.inner.rip
+	}
+
+	/// The encoded instructions
+	#[wasm_bindgen(getter)]
+	#[wasm_bindgen(js_name = "codeBuffer")]
+	pub fn code_buffer(&self) -> Vec<u8> {
+		self.0.inner.code_buffer.clone()
+	}
+
+	/// Gets the address of a label
+	///
+	/// # Throws
+	///
+	/// Throws if the label is invalid or if [`BlockEncoderOptions.ReturnNewInstructionOffsets`] wasn't passed to [`CodeAssembler.assembleOptions()`]
+	///
+	/// # Arguments
+	///
+	/// * `label`: The label
+	///
+	/// [`BlockEncoderOptions.ReturnNewInstructionOffsets`]: enum.BlockEncoderOptions.html#variant.ReturnNewInstructionOffsets
+	/// [`CodeAssembler.assembleOptions()`]: struct.CodeAssembler.html#method.assemble_options
+	#[wasm_bindgen(js_name = "labelIP")]
+	pub fn label_ip(&self, label: &CodeLabel) -> Result<u64, JsValue> {
+		self.0.label_ip(&label.0).map_err(to_js_error)
+	}
+}
+
+/// Creates and encodes instructions. It's easier to use than calling `Instruction.create*()` methods.
+///
+/// The instruction is selected by the mnemonic and the [`AsmOperand`]s passed to one of the `emit*()` methods.
+/// If there are several encodings, the shortest one is used.
+///
+/// [`AsmOperand`]: struct.AsmOperand.html
+///
+/// ```js
+/// const { AsmMemoryOperand, AsmOperand, BlockEncoderOptions, CodeAssembler, Mnemonic, Register } = require("iced-x86");
+///
+/// const a = new CodeAssembler(64);
+/// const ecx = AsmOperand.reg(Register.ECX);
+/// const ten = AsmOperand.immI32(10);
+/// const loopLbl = a.createLabel();
+/// const loopOp = AsmOperand.label(loopLbl);
+///
+/// a.emit2(Mnemonic.Mov, ecx, ten);
+/// a.setLabel(loopLbl);
+/// a.lock();
+/// const mem = new AsmMemoryOperand(Register.RAX).dwordPtr();
+/// a.emit1(Mnemonic.Inc, mem);
+/// a.emit1(Mnemonic.Dec, ecx);
+/// a.emit1(Mnemonic.Jne, loopOp);
+/// a.emit0(Mnemonic.Ret);
+///
+/// const result = a.assembleOptions(0x1234_5678n, BlockEncoderOptions.ReturnNewInstructionOffsets);
+/// const bytes = result.codeBuffer;
+/// const loopIP = result.labelIP(loopLbl);
+///
+/// result.free();
+/// mem.free();
+/// loopOp.free();
+/// loopLbl.free();
+/// ten.free();
+/// ecx.free();
+/// a.free();
+/// ```
+#[wasm_bindgen]
+pub struct CodeAssembler(iced_x86_rust::code_asm::CodeAssembler);
+
+#[wasm_bindgen]
+impl CodeAssembler {
+	/// Constructor
+	///
+	/// # Throws
+	///
+	/// Throws if `bitness` is not one of 16, 32, 64.
+	///
+	/// # Arguments
+	///
+	/// * `bitness`: 16, 32, or 64
+	#[wasm_bindgen(constructor)]
+	pub fn new(bitness: u32) -> Result<CodeAssembler, JsValue> {
+		Ok(Self(iced_x86_rust::code_asm::CodeAssembler::new(bitness).map_err(to_js_error)?))
+	}
+
+	fn emit_core(&mut self, mnemonic: Mnemonic, operands: &[iced_x86_rust::code_asm::AsmOperand]) -> Result<(), JsValue> {
+		self.0.emit(mnemonic_to_iced(mnemonic), operands).map_err(to_js_error)
+	}
+
+	/// Adds an instruction with no operands
+	///
+	/// # Throws
+	///
+	/// Throws if there's no instruction with this mnemonic and operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic (a [`Mnemonic`] enum value)
+	///
+	/// [`Mnemonic`]: enum.Mnemonic.html
+	pub fn emit0(&mut self, mnemonic: Mnemonic) -> Result<(), JsValue> {
+		self.emit_core(mnemonic, &[])
+	}
+
+	/// Adds an instruction with one operand
+	///
+	/// # Throws
+	///
+	/// Throws if there's no instruction with this mnemonic and operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic (a [`Mnemonic`] enum value)
+	/// * `op0`: First operand
+	///
+	/// [`Mnemonic`]: enum.Mnemonic.html
+	pub fn emit1(&mut self, mnemonic: Mnemonic, op0: &AsmOperand) -> Result<(), JsValue> {
+		self.emit_core(mnemonic, &[op0.0])
+	}
+
+	/// Adds an instruction with two operands
+	///
+	/// # Throws
+	///
+	/// Throws if there's no instruction with this mnemonic and operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic (a [`Mnemonic`] enum value)
+	/// * `op0`: First operand
+	/// * `op1`: Second operand
+	///
+	/// [`Mnemonic`]: enum.Mnemonic.html
+	pub fn emit2(&mut self, mnemonic: Mnemonic, op0: &AsmOperand, op1: &AsmOperand) -> Result<(), JsValue> {
+		self.emit_core(mnemonic, &[op0.0, op1.0])
+	}
+
+	/// Adds an instruction with three operands
+	///
+	/// # Throws
+	///
+	/// Throws if there's no instruction with this mnemonic and operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic (a [`Mnemonic`] enum value)
+	/// * `op0`: First operand
+	/// * `op1`: Second operand
+	/// * `op2`: Third operand
+	///
+	/// [`Mnemonic`]: enum.Mnemonic.html
+	pub fn emit3(&mut self, mnemonic: Mnemonic, op0: &AsmOperand, op1: &AsmOperand, op2: &AsmOperand) -> Result<(), JsValue> {
+		self.emit_core(mnemonic, &[op0.0, op1.0, op2.0])
+	}
+
+	/// Adds an instruction with four operands
+	///
+	/// # Throws
+	///
+	/// Throws if there's no instruction with this mnemonic and operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic (a [`Mnemonic`] enum value)
+	/// * `op0`: First operand
+	/// * `op1`: Second operand
+	/// * `op2`: Third operand
+	/// * `op3`: Fourth operand
+	///
+	/// [`Mnemonic`]: enum.Mnemonic.html
+	pub fn emit4(&mut self, mnemonic: Mnemonic, op0: &AsmOperand, op1: &AsmOperand, op2: &AsmOperand, op3: &AsmOperand) -> Result<(), JsValue> {
+		self.emit_core(mnemonic, &[op0.0, op1.0, op2.0, op3.0])
+	}
+
+	/// Adds an instruction with five operands
+	///
+	/// # Throws
+	///
+	/// Throws if there's no instruction with this mnemonic and operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic (a [`Mnemonic`] enum value)
+	/// * `op0`: First operand
+	/// * `op1`: Second operand
+	/// * `op2`: Third operand
+	/// * `op3`: Fourth operand
+	/// * `op4`: Fifth operand
+	///
+	/// [`Mnemonic`]: enum.Mnemonic.html
+	pub fn emit5(
+		&mut self, mnemonic: Mnemonic, op0: &AsmOperand, op1: &AsmOperand, op2: &AsmOperand, op3: &AsmOperand, op4: &AsmOperand,
+	) -> Result<(), JsValue> {
+		self.emit_core(mnemonic, &[op0.0, op1.0, op2.0, op3.0, op4.0])
+	}
+
+	/// Adds an `XACQUIRE` prefix to the next added instruction
+	pub fn xacquire(&mut self) {
+		let _ = self.0.xacquire();
+	}
+
+	/// Adds an `XRELEASE` prefix to the next added instruction
+	pub fn xrelease(&mut self) {
+		let _ = self.0.xrelease();
+	}
+
+	/// Adds a `LOCK` prefix to the next added instruction
+	pub fn lock(&mut self) {
+		let _ = self.0.lock();
+	}
+
+	/// Adds a `REP` prefix to the next added instruction
+	pub fn rep(&mut self) {
+		let _ = self.0.rep();
+	}
+
+	/// Adds a `REPE`/`REPZ` prefix to the next added instruction
+	pub fn repe(&mut self) {
+		let _ = self.0.repe();
+	}
+
+	/// Adds a `REPNE`/`REPNZ` prefix to the next added instruction
+	pub fn repne(&mut self) {
+		let _ = self.0.repne();
+	}
+
+	/// Adds a `BND` prefix to the next added instruction
+	pub fn bnd(&mut self) {
+		let _ = self.0.bnd();
+	}
+
+	/// Adds a `NOTRACK` prefix to the next added instruction
+	pub fn notrack(&mut self) {
+		let _ = self.0.notrack();
+	}
+
+	/// Prefer `VEX` encoding if the next instruction can be `VEX` and `EVEX` encoded
+	pub fn vex(&mut self) {
+		let _ = self.0.vex();
+	}
+
+	/// Prefer `EVEX` encoding if the next instruction can be `VEX` and `EVEX` encoded
+	pub fn evex(&mut self) {
+		let _ = self.0.evex();
+	}
+
+	/// Gets the bitness (16, 32 or 64)
+	#[wasm_bindgen(getter)]
+	pub fn bitness(&self) -> u32 {
+		self.0.bitness()
+	}
+
+	/// `true` (default value) to use `VEX` encoding intead of `EVEX` encoding if we must pick one of the encodings
+	#[wasm_bindgen(getter)]
+	#[wasm_bindgen(js_name = "preferVex")]
+	pub fn prefer_vex(&self) -> bool {
+		self.0.prefer_vex()
+	}
+
+	/// `true` (default value) to use `VEX` encoding intead of `EVEX` encoding if we must pick one of the encodings
+	///
+	/// # Arguments
+	///
+	/// * `newValue`: New value
+	#[wasm_bindgen(setter)]
+	#[wasm_bindgen(js_name = "preferVex")]
+	pub fn set_prefer_vex(&mut self, #[allow(non_snake_case)] newValue: bool) {
+		self.0.set_prefer_vex(newValue)
+	}
+
+	/// `true` (default value) to create short branches, `false` to create near branches.
+	#[wasm_bindgen(getter)]
+	#[wasm_bindgen(js_name = "preferShortBranch")]
+	pub fn prefer_short_branch(&self) -> bool {
+		self.0.prefer_short_branch()
+	}
+
+	/// `true` (default value) to create short branches, `false` to create near branches.
+	///
+	/// # Arguments
+	///
+	/// * `newValue`: New value
+	#[wasm_bindgen(setter)]
+	#[wasm_bindgen(js_name = "preferShortBranch")]
+	pub fn set_prefer_short_branch(&mut self, #[allow(non_snake_case)] newValue: bool) {
+		self.0.set_prefer_short_branch(newValue)
+	}
+
+	/// Gets all added instructions and returns an array of [`Instruction`]s
+	///
+	/// [`Instruction`]: struct.Instruction.html
+	pub fn instructions(&self) -> js_sys::Array {
+		self.0.instructions().iter().map(|&i| JsValue::from(Instruction(i))).collect()
+	}
+
+	/// Takes ownership of all instructions and returns them. Instruction state is also reset (see [`reset()`])
+	///
+	/// [`reset()`]: #method.reset
+	#[wasm_bindgen(js_name = "takeInstructions")]
+	pub fn take_instructions(&mut self) -> js_sys::Array {
+		self.0.take_instructions().into_iter().map(|i| JsValue::from(Instruction(i))).collect()
+	}
+
+	/// Resets all instructions and labels so this instance can be re-used
+	pub fn reset(&mut self) {
+		self.0.reset()
+	}
+
+	/// Creates a label that can be referenced by instructions
+	#[wasm_bindgen(js_name = "createLabel")]
+	pub fn create_label(&mut self) -> CodeLabel {
+		CodeLabel(self.0.create_label())
+	}
+
+	/// Initializes the label to the next instruction
+	///
+	/// # Throws
+	///
+	/// Throws if the label wasn't created by [`createLabel()`], if this method was called multiple times for the same label, or if the next instruction already has a label
+	///
+	/// # Arguments
+	///
+	/// * `label`: Label created by [`createLabel()`]
+	///
+	/// [`createLabel()`]: #method.create_label
+	#[wasm_bindgen(js_name = "setLabel")]
+	pub fn set_label(&mut self, label: &mut CodeLabel) -> Result<(), JsValue> {
+		self.0.set_label(&mut label.0).map_err(to_js_error)
+	}
+
+	/// Creates an anonymous label that can be referenced by calling [`bwd()`] and [`fwd()`]
+	///
+	/// # Throws
+	///
+	/// Throws if the next instruction already has a label
+	///
+	/// [`bwd()`]: #method.bwd
+	/// [`fwd()`]: #method.fwd
+	#[wasm_bindgen(js_name = "anonymousLabel")]
+	pub fn anonymous_label(&mut self) -> Result<(), JsValue> {
+		self.0.anonymous_label().map_err(to_js_error)
+	}
+
+	/// Gets the previously created anonymous label created by [`anonymousLabel()`]
+	///
+	/// # Throws
+	///
+	/// Throws if no anonymous label has been created yet
+	///
+	/// [`anonymousLabel()`]: #method.anonymous_label
+	pub fn bwd(&mut self) -> Result<CodeLabel, JsValue> {
+		Ok(CodeLabel(self.0.bwd().map_err(to_js_error)?))
+	}
+
+	/// Gets the next anonymous label created by a future call to [`anonymousLabel()`]
+	///
+	/// # Throws
+	///
+	/// Throws if an error occurs
+	///
+	/// [`anonymousLabel()`]: #method.anonymous_label
+	pub fn fwd(&mut self) -> Result<CodeLabel, JsValue> {
+		Ok(CodeLabel(self.0.fwd().map_err(to_js_error)?))
+	}
+
+	/// Adds data
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix was used
+	///
+	/// # Arguments
+	///
+	/// * `data`: Data that will be written to the output
+	pub fn db(&mut self, data: &[u8]) -> Result<(), JsValue> {
+		self.0.db(data).map_err(to_js_error)
+	}
+
+	/// Adds 16-bit data
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix was used
+	///
+	/// # Arguments
+	///
+	/// * `data`: Data that will be written to the output
+	pub fn dw(&mut self, data: &[u16]) -> Result<(), JsValue> {
+		self.0.dw(data).map_err(to_js_error)
+	}
+
+	/// Adds 32-bit data
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix was used
+	///
+	/// # Arguments
+	///
+	/// * `data`: Data that will be written to the output
+	pub fn dd(&mut self, data: &[u32]) -> Result<(), JsValue> {
+		self.0.dd(data).map_err(to_js_error)
+	}
+
+	/// Adds 64-bit data
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix was used
+	///
+	/// # Arguments
+	///
+	/// * `data`: Data that will be written to the output
+	pub fn dq(&mut self, data: &[u64]) -> Result<(), JsValue> {
+		self.0.dq(data).map_err(to_js_error)
+	}
+
+	/// Adds nops, preferring long nops
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix was used
+	///
+	/// # Arguments
+	///
+	/// * `size`: Size in bytes of all nops
+	#[wasm_bindgen(js_name = "nopsWithSize")]
+	pub fn nops_with_size(&mut self, size: usize) -> Result<(), JsValue> {
+		self.0.nops_with_size(size).map_err(to_js_error)
+	}
+
+	/// Adds a zero-bytes instruction that can be used as a label
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix was used
+	#[wasm_bindgen(js_name = "zeroBytes")]
+	pub fn zero_bytes(&mut self) -> Result<(), JsValue> {
+		self.0.zero_bytes().map_err(to_js_error)
+	}
+
+	/// Adds an instruction created by the user
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix was used
+	///
+	/// # Arguments
+	///
+	/// * `instruction`: Instruction to add
+	#[wasm_bindgen(js_name = "addInstruction")]
+	pub fn add_instruction(&mut self, instruction: &Instruction) -> Result<(), JsValue> {
+		self.0.add_instruction(instruction.0).map_err(to_js_error)
+	}
+
+	/// Adds a `CALL FAR` instruction
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix is invalid
+	///
+	/// # Arguments
+	///
+	/// * `selector`: Selector/segment
+	/// * `offset`: Offset within the segment
+	#[wasm_bindgen(js_name = "callFar")]
+	pub fn call_far(&mut self, selector: u16, offset: u32) -> Result<(), JsValue> {
+		self.0.call_far(selector, offset).map_err(to_js_error)
+	}
+
+	/// Adds a `JMP FAR` instruction
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix is invalid
+	///
+	/// # Arguments
+	///
+	/// * `selector`: Selector/segment
+	/// * `offset`: Offset within the segment
+	#[wasm_bindgen(js_name = "jmpFar")]
+	pub fn jmp_far(&mut self, selector: u16, offset: u32) -> Result<(), JsValue> {
+		self.0.jmp_far(selector, offset).map_err(to_js_error)
+	}
+
+	/// Adds an `XLATB` instruction
+	///
+	/// # Throws
+	///
+	/// Throws if a prefix is invalid
+	pub fn xlatb(&mut self) -> Result<(), JsValue> {
+		self.0.xlatb().map_err(to_js_error)
+	}
+
+	/// Encodes all added instructions and returns the encoded bytes
+	///
+	/// # Throws
+	///
+	/// Throws if there's an unused label or if one or more instructions couldn't be encoded
+	///
+	/// # Arguments
+	///
+	/// * `ip`: Base address of all instructions
+	pub fn assemble(&mut self, ip: u64) -> Result<Vec<u8>, JsValue> {
+		self.0.assemble(ip).map_err(to_js_error)
+	}
+
+	/// Encodes all added instructions and returns the result
+	///
+	/// # Throws
+	///
+	/// Throws if there's an unused label or if one or more instructions couldn't be encoded
+	///
+	/// # Arguments
+	///
+	/// * `ip`: Base address of all instructions
+	/// * `options`: Encoder options ([`BlockEncoderOptions`]). Use [`BlockEncoderOptions.ReturnNewInstructionOffsets`] if you need to call [`CodeAssemblerResult.labelIP()`]
+	///
+	/// [`BlockEncoderOptions`]: enum.BlockEncoderOptions.html
+	/// [`BlockEncoderOptions.ReturnNewInstructionOffsets`]: enum.BlockEncoderOptions.html#variant.ReturnNewInstructionOffsets
+	/// [`CodeAssemblerResult.labelIP()`]: struct.CodeAssemblerResult.html#method.label_ip
+	#[wasm_bindgen(js_name = "assembleOptions")]
+	pub fn assemble_options(&mut self, ip: u64, options: u32 /*flags: BlockEncoderOptions*/) -> Result<CodeAssemblerResult, JsValue> {
+		// It's not part of the method sig so make sure it's still compiled by referencing it here
+		const _: () = assert!(BlockEncoderOptions::None as u32 == 0);
+		Ok(CodeAssemblerResult(self.0.assemble_options(ip, options).map_err(to_js_error)?))
+	}
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use wasm_bindgen::prelude::*;
+
+// GENERATOR-BEGIN: Enum
+// ⚠️This was generated by GENERATOR!🦹‍♂️
+/// Decorator
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub enum DecoratorKind {
+	/// Broadcast decorator, eg. `{1to4}`
+	Broadcast = 0,
+	/// Rounding control, eg. `{rd-sae}`
+	RoundingControl = 1,
+	/// Suppress all exceptions: `{sae}`
+	SuppressAllExceptions = 2,
+	/// Zeroing masking: `{z}`
+	ZeroingMasking = 3,
+	/// MVEX swizzle or memory up/down conversion: `{dacb}` or `{sint16}`
+	SwizzleMemConv = 4,
+	/// MVEX eviction hint: `{eh}`
+	EvictionHint = 5,
+}
+// GENERATOR-END: Enum
+
+#[allow(dead_code)]
+pub(crate) fn decorator_kind_to_iced(value: DecoratorKind) -> iced_x86_rust::DecoratorKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
+
+#[allow(dead_code)]
+pub(crate) fn iced_to_decorator_kind(value: iced_x86_rust::DecoratorKind) -> DecoratorKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
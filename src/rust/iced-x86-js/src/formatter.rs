@@ -7,12 +7,17 @@ use crate::cc::{
 	iced_to_cc_le, iced_to_cc_ne, iced_to_cc_np, iced_to_cc_p, CC_a, CC_ae, CC_b, CC_be, CC_e, CC_g, CC_ge, CC_l, CC_le, CC_ne, CC_np, CC_p,
 };
 use crate::format_mnemonic_options::FormatMnemonicOptions;
+use crate::formatter_options_provider::JsFormatterOptionsProvider;
+use crate::formatter_output::{JsFormatterOutput, TextKindArrayOutput};
 use crate::instruction::Instruction;
 use crate::memory_size_options::{iced_to_memory_size_options, memory_size_options_to_iced, MemorySizeOptions};
 #[cfg(feature = "instr_info")]
 use crate::op_access::{iced_to_op_access, OpAccess};
 #[cfg(feature = "instr_api")]
 use crate::register::{register_to_iced, Register};
+use crate::symbol_resolver::JsSymbolResolver;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use wasm_bindgen::prelude::*;
 
 // GENERATOR-BEGIN: FormatterSyntax
@@ -31,10 +36,70 @@ pub enum FormatterSyntax {
 }
 // GENERATOR-END: FormatterSyntax
 
+/// Shared by a [`Formatter`] and its symbol resolver and options provider. Errors thrown by the callbacks
+/// are saved here and re-thrown once the formatter returns.
+#[derive(Default)]
+pub(crate) struct FormatterCallbackState {
+	error: RefCell<Option<JsValue>>,
+	clear_symbol_cache: Cell<bool>,
+}
+
+impl FormatterCallbackState {
+	pub(crate) fn has_error(&self) -> bool {
+		self.error.borrow().is_some()
+	}
+
+	pub(crate) fn set_error(&self, error: JsValue) {
+		let mut current = self.error.borrow_mut();
+		if current.is_none() {
+			*current = Some(error);
+		}
+	}
+
+	pub(crate) fn take_clear_symbol_cache(&self) -> bool {
+		self.clear_symbol_cache.replace(false)
+	}
+
+	fn check_error(&self) -> Result<(), JsValue> {
+		match self.error.borrow_mut().take() {
+			Some(error) => Err(error),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Calls a symbol resolver or options provider callback. The first arguments are always `instruction`, `operand`
+/// and `instructionOperand`. The instruction is freed when the callback returns.
+pub(crate) fn call_formatter_callback(
+	callback: &js_sys::Function, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, extra_args: &[JsValue],
+) -> Result<JsValue, JsValue> {
+	let js_instruction = JsValue::from(Instruction(*instruction));
+	let args = js_sys::Array::new();
+	let _ = args.push(&js_instruction);
+	let _ = args.push(&JsValue::from(operand));
+	let _ = args.push(&instruction_operand.map_or(JsValue::UNDEFINED, JsValue::from));
+	for arg in extra_args {
+		let _ = args.push(arg);
+	}
+	let result = callback.apply(&JsValue::UNDEFINED, &args);
+	// Free the wasm memory since the instruction is only valid during the call
+	if let Ok(free) = js_sys::Reflect::get(&js_instruction, &JsValue::from_str("free")) {
+		if let Some(free) = free.dyn_ref::<js_sys::Function>() {
+			let _ = free.call0(&js_instruction);
+		}
+	}
+	result
+}
+
+pub(crate) fn get_property(object: &JsValue, name: &str) -> Result<JsValue, JsValue> {
+	js_sys::Reflect::get(object, &JsValue::from_str(name))
+}
+
 /// x86 formatter that supports GNU Assembler, Intel XED, masm and nasm syntax
 #[wasm_bindgen]
 pub struct Formatter {
 	formatter: Box<dyn iced_x86_rust::Formatter>,
+	callback_state: Rc<FormatterCallbackState>,
 }
 
 #[wasm_bindgen]
@@ -44,6 +109,20 @@ impl Formatter {
 	/// # Arguments
 	///
 	/// * `syntax`: Formatter syntax, see [`FormatterSyntax`]
+	/// * `symbolResolver`: (optional) Called to resolve symbols, eg. so `call 401230h` is shown as `call printf`.
+	///   It's called as `symbolResolver(instruction, operand, instructionOperand, address, addressSize)` where `address` is a `bigint`
+	///   and it must return `undefined`, `null`, a `string` (a [`FormatterTextKind.Label`] symbol at `address`) or a symbol object:
+	///   `{ address?: bigint, text: string | [string, FormatterTextKind][], kind?: FormatterTextKind, flags?: SymbolFlags, symbolSize?: MemorySize }`.
+	///   The results are cached by `address` and `addressSize`, see [`clearSymbolCache()`]
+	/// * `optionsProvider`: (optional) Called before each operand is formatted so it can override the options.
+	///   It's called as `optionsProvider(instruction, operand, instructionOperand, options, numberOptions)` where `options` is a
+	///   `{ branchSize, ripRelativeAddresses, memorySizeOptions }` object and `numberOptions` is a `{ prefix, suffix, digitSeparator,
+	///   digitGroupSize, numberBase, uppercaseHex, smallHexNumbersInDecimal, addLeadingZeroToHexNumbers, leadingZeros, signedNumber,
+	///   displacementLeadingZeros }` object. Modify the properties to change the options. The return value is ignored.
+	///
+	/// `operand` is the formatter operand index and `instructionOperand` is the instruction operand index or `undefined` if the formatter
+	/// added the operand. The `instruction` passed to the callbacks is freed when the callback returns so don't save it.
+	/// Errors thrown by the callbacks are re-thrown by the `format*()` methods.
 	///
 	/// # Examples
 	///
@@ -67,39 +146,147 @@ impl Formatter {
 	/// ```
 	///
 	/// [`FormatterSyntax`]: enum.FormatterSyntax.html
+	/// [`FormatterTextKind.Label`]: enum.FormatterTextKind.html
+	/// [`clearSymbolCache()`]: #method.clear_symbol_cache
 	#[wasm_bindgen(constructor)]
-	pub fn new(syntax: FormatterSyntax) -> Result<Formatter, JsValue> {
+	pub fn new(
+		syntax: FormatterSyntax, #[allow(non_snake_case)] symbolResolver: Option<js_sys::Function>,
+		#[allow(non_snake_case)] optionsProvider: Option<js_sys::Function>,
+	) -> Result<Formatter, JsValue> {
+		let callback_state = Rc::new(FormatterCallbackState::default());
+		let symbol_resolver: Option<Box<dyn iced_x86_rust::SymbolResolver>> = symbolResolver
+			.map(|callback| -> Box<dyn iced_x86_rust::SymbolResolver> { Box::new(JsSymbolResolver::new(callback, callback_state.clone())) });
+		let options_provider: Option<Box<dyn iced_x86_rust::FormatterOptionsProvider>> =
+			optionsProvider.map(|callback| -> Box<dyn iced_x86_rust::FormatterOptionsProvider> {
+				Box::new(JsFormatterOptionsProvider::new(callback, callback_state.clone()))
+			});
 		let formatter: Box<dyn iced_x86_rust::Formatter> = match syntax {
 			#[cfg(feature = "gas")]
-			FormatterSyntax::Gas => Box::new(iced_x86_rust::GasFormatter::new()),
+			FormatterSyntax::Gas => Box::new(iced_x86_rust::GasFormatter::with_options(symbol_resolver, options_provider)),
 			#[cfg(not(feature = "gas"))]
 			FormatterSyntax::Gas => return Err(js_sys::Error::new("Invalid formatter").into()),
 			#[cfg(feature = "intel")]
-			FormatterSyntax::Intel => Box::new(iced_x86_rust::IntelFormatter::new()),
+			FormatterSyntax::Intel => Box::new(iced_x86_rust::IntelFormatter::with_options(symbol_resolver, options_provider)),
 			#[cfg(not(feature = "intel"))]
 			FormatterSyntax::Intel => return Err(js_sys::Error::new("Invalid formatter").into()),
 			#[cfg(feature = "masm")]
-			FormatterSyntax::Masm => Box::new(iced_x86_rust::MasmFormatter::new()),
+			FormatterSyntax::Masm => Box::new(iced_x86_rust::MasmFormatter::with_options(symbol_resolver, options_provider)),
 			#[cfg(not(feature = "masm"))]
 			FormatterSyntax::Masm => return Err(js_sys::Error::new("Invalid formatter").into()),
 			#[cfg(feature = "nasm")]
-			FormatterSyntax::Nasm => Box::new(iced_x86_rust::NasmFormatter::new()),
+			FormatterSyntax::Nasm => Box::new(iced_x86_rust::NasmFormatter::with_options(symbol_resolver, options_provider)),
 			#[cfg(not(feature = "nasm"))]
 			FormatterSyntax::Nasm => return Err(js_sys::Error::new("Invalid formatter").into()),
 		};
-		Ok(Self { formatter })
+		Ok(Self { formatter, callback_state })
+	}
+
+	/// Clears the cached symbols returned by the symbol resolver.
+	///
+	/// Call it if the symbol resolver's symbols have changed.
+	#[wasm_bindgen(js_name = "clearSymbolCache")]
+	pub fn clear_symbol_cache(&mut self) {
+		self.callback_state.clear_symbol_cache.set(true);
 	}
 
 	/// Formats the whole instruction: prefixes, mnemonic, operands
 	///
+	/// # Throws
+	///
+	/// Throws if the symbol resolver or options provider threw
+	///
 	/// # Arguments
 	///
 	/// - `instruction`: Instruction
 	#[wasm_bindgen]
-	pub fn format(&mut self, instruction: &Instruction) -> String {
+	pub fn format(&mut self, instruction: &Instruction) -> Result<String, JsValue> {
 		let mut output = String::new();
 		self.formatter.format(&instruction.0, &mut output);
-		output
+		self.callback_state.check_error()?;
+		Ok(output)
+	}
+
+	/// Formats the whole instruction and writes the text to `output`, eg. to colorize the disassembled code.
+	///
+	/// `output` must have a `write(text, kind)` method where `kind` is a [`FormatterTextKind`].
+	/// It can also have these methods and if they're missing, `write(text, kind)` is called instead:
+	///
+	/// - `writePrefix(instruction, text, prefix)`: `prefix` is a [`PrefixKind`]
+	/// - `writeMnemonic(instruction, text)`
+	/// - `writeNumber(instruction, operand, instructionOperand, text, value, numberKind, kind)`: `value` is a `bigint` and `numberKind` is a [`NumberKind`]
+	/// - `writeDecorator(instruction, operand, instructionOperand, text, decorator)`: `decorator` is a [`DecoratorKind`]
+	/// - `writeRegister(instruction, operand, instructionOperand, text, register)`: `register` is a [`Register`]
+	///
+	/// `operand` is the formatter operand index and `instructionOperand` is the instruction operand index or `undefined` if the formatter
+	/// added the operand. The `instruction` passed to the methods is freed when the method returns so don't save it.
+	///
+	/// # Throws
+	///
+	/// Throws if `output` has no `write()` method or if any of the methods, the symbol resolver or the options provider threw
+	///
+	/// # Arguments
+	///
+	/// - `instruction`: Instruction
+	/// - `output`: Receives the formatted text
+	///
+	/// # Examples
+	///
+	/// ```js
+	/// const assert = require("assert").strict;
+	/// const { Decoder, DecoderOptions, Formatter, FormatterSyntax } = require("iced-x86");
+	///
+	/// const bytes = new Uint8Array([0x48, 0x89, 0x5C, 0x24, 0x10]);
+	/// const decoder = new Decoder(64, bytes, DecoderOptions.None);
+	/// const instr = decoder.decode();
+	///
+	/// const registers = [];
+	/// const output = {
+	///     write(text, kind) {},
+	///     writeRegister(instruction, operand, instructionOperand, text, register) {
+	///         registers.push([operand, text]);
+	///     },
+	/// };
+	/// const formatter = new Formatter(FormatterSyntax.Masm);
+	/// formatter.formatOutput(instr, output);
+	/// assert.deepEqual(registers, [[0, "rsp"], [1, "rbx"]]);
+	///
+	/// // Free wasm memory
+	/// decoder.free();
+	/// instr.free();
+	/// formatter.free();
+	/// ```
+	///
+	/// [`FormatterTextKind`]: enum.FormatterTextKind.html
+	/// [`PrefixKind`]: enum.PrefixKind.html
+	/// [`NumberKind`]: enum.NumberKind.html
+	/// [`DecoratorKind`]: enum.DecoratorKind.html
+	/// [`Register`]: enum.Register.html
+	#[wasm_bindgen(js_name = "formatOutput")]
+	pub fn format_output(&mut self, instruction: &Instruction, output: JsValue) -> Result<(), JsValue> {
+		let mut js_output = JsFormatterOutput::new(output)?;
+		self.formatter.format(&instruction.0, &mut js_output);
+		self.callback_state.check_error()?;
+		js_output.check_error()
+	}
+
+	/// Formats the whole instruction and returns an array of `[text, kind]` arrays, eg. to colorize the disassembled code.
+	/// `kind` is a [`FormatterTextKind`].
+	///
+	/// # Throws
+	///
+	/// Throws if the symbol resolver or options provider threw
+	///
+	/// # Arguments
+	///
+	/// - `instruction`: Instruction
+	///
+	/// [`FormatterTextKind`]: enum.FormatterTextKind.html
+	#[wasm_bindgen(js_name = "formatToArray")]
+	pub fn format_to_array(&mut self, instruction: &Instruction) -> Result<js_sys::Array, JsValue> {
+		let mut output = TextKindArrayOutput { array: js_sys::Array::new() };
+		self.formatter.format(&instruction.0, &mut output);
+		self.callback_state.check_error()?;
+		Ok(output.array)
 	}
 
 	/// Formats the mnemonic and any prefixes
@@ -206,7 +393,7 @@ impl Formatter {
 	///
 	/// # Throws
 	///
-	/// Throws if `operand` is invalid
+	/// Throws if `operand` is invalid or if the symbol resolver or options provider threw
 	///
 	/// # Arguments
 	///
@@ -217,9 +404,9 @@ impl Formatter {
 	#[wasm_bindgen(js_name = "formatOperand")]
 	pub fn format_operand(&mut self, instruction: &Instruction, operand: u32) -> Result<String, JsValue> {
 		let mut output = String::new();
-		self.formatter
-			.format_operand(&instruction.0, &mut output, operand)
-			.map_or_else(|error| Err(js_sys::Error::new(&format!("{error}")).into()), |_| Ok(output))
+		let result = self.formatter.format_operand(&instruction.0, &mut output, operand);
+		self.callback_state.check_error()?;
+		result.map_or_else(|error| Err(js_sys::Error::new(&format!("{error}")).into()), |_| Ok(output))
 	}
 
 	/// Formats an operand separator
@@ -236,14 +423,19 @@ impl Formatter {
 
 	/// Formats all operands
 	///
+	/// # Throws
+	///
+	/// Throws if the symbol resolver or options provider threw
+	///
 	/// # Arguments
 	///
 	/// - `instruction`: Instruction
 	#[wasm_bindgen(js_name = "formatAllOperands")]
-	pub fn format_all_operands(&mut self, instruction: &Instruction) -> String {
+	pub fn format_all_operands(&mut self, instruction: &Instruction) -> Result<String, JsValue> {
 		let mut output = String::new();
 		self.formatter.format_all_operands(&instruction.0, &mut output);
-		output
+		self.callback_state.check_error()?;
+		Ok(output)
 	}
 
 	/// Formats a register
@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::formatter::{call_formatter_callback, get_property, FormatterCallbackState};
+use iced_x86_rust::{FormatterOperandOptions, MemorySizeOptions, NumberBase, NumberFormattingOptions};
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::Mutex;
+use wasm_bindgen::prelude::*;
+
+/// Calls a JavaScript function so it can override the options of each operand
+pub(crate) struct JsFormatterOptionsProvider {
+	callback: js_sys::Function,
+	state: Rc<FormatterCallbackState>,
+}
+
+impl JsFormatterOptionsProvider {
+	pub(crate) fn new(callback: js_sys::Function, state: Rc<FormatterCallbackState>) -> Self {
+		Self { callback, state }
+	}
+
+	fn call(
+		&self, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, options: &mut FormatterOperandOptions,
+		number_options: &mut NumberFormattingOptions<'_>,
+	) -> Result<(), JsValue> {
+		let js_options = js_sys::Object::new();
+		set_property(&js_options, "branchSize", options.branch_size().into())?;
+		set_property(&js_options, "ripRelativeAddresses", options.rip_relative_addresses().into())?;
+		set_property(&js_options, "memorySizeOptions", (options.memory_size_options() as u32).into())?;
+
+		let js_number_options = js_sys::Object::new();
+		set_property(&js_number_options, "prefix", number_options.prefix.into())?;
+		set_property(&js_number_options, "suffix", number_options.suffix.into())?;
+		set_property(&js_number_options, "digitSeparator", number_options.digit_separator.into())?;
+		set_property(&js_number_options, "digitGroupSize", number_options.digit_group_size.into())?;
+		set_property(&js_number_options, "numberBase", number_base_to_u32(number_options.number_base).into())?;
+		set_property(&js_number_options, "uppercaseHex", number_options.uppercase_hex.into())?;
+		set_property(&js_number_options, "smallHexNumbersInDecimal", number_options.small_hex_numbers_in_decimal.into())?;
+		set_property(&js_number_options, "addLeadingZeroToHexNumbers", number_options.add_leading_zero_to_hex_numbers.into())?;
+		set_property(&js_number_options, "leadingZeros", number_options.leading_zeros.into())?;
+		set_property(&js_number_options, "signedNumber", number_options.signed_number.into())?;
+		set_property(&js_number_options, "displacementLeadingZeros", number_options.displacement_leading_zeros.into())?;
+
+		let _ = call_formatter_callback(
+			&self.callback,
+			instruction,
+			operand,
+			instruction_operand,
+			&[js_options.clone().into(), js_number_options.clone().into()],
+		)?;
+
+		options.set_branch_size(get_bool(&js_options, "branchSize")?);
+		options.set_rip_relative_addresses(get_bool(&js_options, "ripRelativeAddresses")?);
+		let memory_size_options = get_property(&js_options, "memorySizeOptions")?
+			.as_f64()
+			.and_then(|value| MemorySizeOptions::try_from(value as usize).ok())
+			.ok_or_else(|| JsValue::from(js_sys::Error::new("Invalid MemorySizeOptions value")))?;
+		options.set_memory_size_options(memory_size_options);
+
+		let prefix = get_string(&js_number_options, "prefix")?;
+		if number_options.prefix != prefix {
+			number_options.prefix = intern(&prefix);
+		}
+		let suffix = get_string(&js_number_options, "suffix")?;
+		if number_options.suffix != suffix {
+			number_options.suffix = intern(&suffix);
+		}
+		let digit_separator = get_string(&js_number_options, "digitSeparator")?;
+		if number_options.digit_separator != digit_separator {
+			number_options.digit_separator = intern(&digit_separator);
+		}
+		number_options.digit_group_size = get_number(&js_number_options, "digitGroupSize")? as u8;
+		number_options.number_base = match get_number(&js_number_options, "numberBase")? as u32 {
+			2 => NumberBase::Binary,
+			8 => NumberBase::Octal,
+			10 => NumberBase::Decimal,
+			16 => NumberBase::Hexadecimal,
+			_ => return Err(js_sys::Error::new("Invalid number base").into()),
+		};
+		number_options.uppercase_hex = get_bool(&js_number_options, "uppercaseHex")?;
+		number_options.small_hex_numbers_in_decimal = get_bool(&js_number_options, "smallHexNumbersInDecimal")?;
+		number_options.add_leading_zero_to_hex_numbers = get_bool(&js_number_options, "addLeadingZeroToHexNumbers")?;
+		number_options.leading_zeros = get_bool(&js_number_options, "leadingZeros")?;
+		number_options.signed_number = get_bool(&js_number_options, "signedNumber")?;
+		number_options.displacement_leading_zeros = get_bool(&js_number_options, "displacementLeadingZeros")?;
+		Ok(())
+	}
+}
+
+impl iced_x86_rust::FormatterOptionsProvider for JsFormatterOptionsProvider {
+	fn operand_options(
+		&mut self, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, options: &mut FormatterOperandOptions,
+		number_options: &mut NumberFormattingOptions<'_>,
+	) {
+		if self.state.has_error() {
+			return;
+		}
+		if let Err(error) = self.call(instruction, operand, instruction_operand, options, number_options) {
+			self.state.set_error(error);
+		}
+	}
+}
+
+fn number_base_to_u32(number_base: NumberBase) -> u32 {
+	match number_base {
+		NumberBase::Binary => 2,
+		NumberBase::Octal => 8,
+		NumberBase::Decimal => 10,
+		NumberBase::Hexadecimal => 16,
+	}
+}
+
+fn set_property(object: &js_sys::Object, name: &str, value: JsValue) -> Result<(), JsValue> {
+	let _ = js_sys::Reflect::set(object, &JsValue::from_str(name), &value)?;
+	Ok(())
+}
+
+fn get_bool(object: &JsValue, name: &str) -> Result<bool, JsValue> {
+	get_property(object, name)?.as_bool().ok_or_else(|| js_sys::TypeError::new(&format!("`{name}` must be a boolean")).into())
+}
+
+fn get_number(object: &JsValue, name: &str) -> Result<f64, JsValue> {
+	get_property(object, name)?.as_f64().ok_or_else(|| js_sys::TypeError::new(&format!("`{name}` must be a number")).into())
+}
+
+fn get_string(object: &JsValue, name: &str) -> Result<String, JsValue> {
+	get_property(object, name)?.as_string().ok_or_else(|| js_sys::TypeError::new(&format!("`{name}` must be a string")).into())
+}
+
+// The formatter borrows the strings so they must outlive it. Each unique string is only leaked once.
+fn intern(value: &str) -> &'static str {
+	static STRINGS: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+	let mut strings = STRINGS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+	let strings = strings.get_or_insert_with(HashSet::new);
+	if let Some(&s) = strings.get(value) {
+		s
+	} else {
+		let s: &'static str = Box::leak(value.to_owned().into_boxed_str());
+		let _ = strings.insert(s);
+		s
+	}
+}
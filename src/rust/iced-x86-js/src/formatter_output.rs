@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::formatter::get_property;
+use crate::instruction::Instruction;
+use iced_x86_rust::{DecoratorKind, FormatterTextKind, NumberKind, PrefixKind, Register};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// Calls the methods of a JavaScript object. Only `write()` is required, the other methods are optional and if they're
+/// missing, `write()` is called instead. The first error is saved and re-thrown by the formatter.
+pub(crate) struct JsFormatterOutput {
+	output: JsValue,
+	write: js_sys::Function,
+	write_prefix: Option<js_sys::Function>,
+	write_mnemonic: Option<js_sys::Function>,
+	write_number: Option<js_sys::Function>,
+	write_decorator: Option<js_sys::Function>,
+	write_register: Option<js_sys::Function>,
+	error: Option<JsValue>,
+}
+
+impl JsFormatterOutput {
+	pub(crate) fn new(output: JsValue) -> Result<Self, JsValue> {
+		let get_method = |name: &str| -> Result<Option<js_sys::Function>, JsValue> { Ok(get_property(&output, name)?.dyn_into().ok()) };
+		let write = get_method("write")?.ok_or_else(|| JsValue::from(js_sys::TypeError::new("`output` must have a `write()` method")))?;
+		Ok(Self {
+			write,
+			write_prefix: get_method("writePrefix")?,
+			write_mnemonic: get_method("writeMnemonic")?,
+			write_number: get_method("writeNumber")?,
+			write_decorator: get_method("writeDecorator")?,
+			write_register: get_method("writeRegister")?,
+			output,
+			error: None,
+		})
+	}
+
+	/// Returns the first error thrown by the output's methods
+	pub(crate) fn check_error(self) -> Result<(), JsValue> {
+		match self.error {
+			Some(error) => Err(error),
+			None => Ok(()),
+		}
+	}
+
+	fn write_default(&self, text: &str, kind: FormatterTextKind) -> Result<JsValue, JsValue> {
+		self.write.call2(&self.output, &JsValue::from_str(text), &JsValue::from(kind as u32))
+	}
+
+	/// Calls `method(instruction, ...args)`. The instruction is freed when the method returns.
+	fn call_method(&self, method: &js_sys::Function, instruction: &iced_x86_rust::Instruction, args: &[JsValue]) -> Result<JsValue, JsValue> {
+		let js_instruction = JsValue::from(Instruction(*instruction));
+		let js_args = js_sys::Array::new();
+		let _ = js_args.push(&js_instruction);
+		for arg in args {
+			let _ = js_args.push(arg);
+		}
+		let result = method.apply(&self.output, &js_args);
+		// Free the wasm memory since the instruction is only valid during the call
+		if let Ok(free) = get_property(&js_instruction, "free") {
+			if let Some(free) = free.dyn_ref::<js_sys::Function>() {
+				let _ = free.call0(&js_instruction);
+			}
+		}
+		result
+	}
+
+	fn set_result(&mut self, result: Result<JsValue, JsValue>) {
+		if let Err(error) = result {
+			self.error = Some(error);
+		}
+	}
+}
+
+fn operand_args(operand: u32, instruction_operand: Option<u32>, text: &str) -> [JsValue; 3] {
+	[JsValue::from(operand), instruction_operand.map_or(JsValue::UNDEFINED, JsValue::from), JsValue::from_str(text)]
+}
+
+impl iced_x86_rust::FormatterOutput for JsFormatterOutput {
+	fn write(&mut self, text: &str, kind: FormatterTextKind) {
+		if self.error.is_none() {
+			let result = self.write_default(text, kind);
+			self.set_result(result);
+		}
+	}
+
+	fn write_prefix(&mut self, instruction: &iced_x86_rust::Instruction, text: &str, prefix: PrefixKind) {
+		if self.error.is_none() {
+			let result = match &self.write_prefix {
+				Some(method) => self.call_method(method, instruction, &[JsValue::from_str(text), JsValue::from(prefix as u32)]),
+				None => self.write_default(text, FormatterTextKind::Prefix),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_mnemonic(&mut self, instruction: &iced_x86_rust::Instruction, text: &str) {
+		if self.error.is_none() {
+			let result = match &self.write_mnemonic {
+				Some(method) => self.call_method(method, instruction, &[JsValue::from_str(text)]),
+				None => self.write_default(text, FormatterTextKind::Mnemonic),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_number(
+		&mut self, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, value: u64,
+		number_kind: NumberKind, kind: FormatterTextKind,
+	) {
+		if self.error.is_none() {
+			let result = match &self.write_number {
+				Some(method) => {
+					let [operand, instruction_operand, text] = operand_args(operand, instruction_operand, text);
+					let args =
+						[operand, instruction_operand, text, JsValue::from(value), JsValue::from(number_kind as u32), JsValue::from(kind as u32)];
+					self.call_method(method, instruction, &args)
+				}
+				None => self.write_default(text, kind),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_decorator(
+		&mut self, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, decorator: DecoratorKind,
+	) {
+		if self.error.is_none() {
+			let result = match &self.write_decorator {
+				Some(method) => {
+					let [operand, instruction_operand, text] = operand_args(operand, instruction_operand, text);
+					self.call_method(method, instruction, &[operand, instruction_operand, text, JsValue::from(decorator as u32)])
+				}
+				None => self.write_default(text, FormatterTextKind::Decorator),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_register(
+		&mut self, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, register: Register,
+	) {
+		if self.error.is_none() {
+			let result = match &self.write_register {
+				Some(method) => {
+					let [operand, instruction_operand, text] = operand_args(operand, instruction_operand, text);
+					self.call_method(method, instruction, &[operand, instruction_operand, text, JsValue::from(register as u32)])
+				}
+				None => self.write_default(text, FormatterTextKind::Register),
+			};
+			self.set_result(result);
+		}
+	}
+}
+
+/// Saves all text and its kind in an array of `[text, kind]` arrays
+pub(crate) struct TextKindArrayOutput {
+	pub(crate) array: js_sys::Array,
+}
+
+impl iced_x86_rust::FormatterOutput for TextKindArrayOutput {
+	fn write(&mut self, text: &str, kind: FormatterTextKind) {
+		let _ = self.array.push(&js_sys::Array::of2(&JsValue::from_str(text), &JsValue::from(kind as u32)));
+	}
+}
@@ -0,0 +1,57 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use wasm_bindgen::prelude::*;
+
+// GENERATOR-BEGIN: Enum
+// ⚠️This was generated by GENERATOR!🦹‍♂️
+/// Formatter text kind
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub enum FormatterTextKind {
+	/// Normal text
+	Text = 0,
+	/// Assembler directive
+	Directive = 1,
+	/// Any prefix
+	Prefix = 2,
+	/// Any mnemonic
+	Mnemonic = 3,
+	/// Any keyword
+	Keyword = 4,
+	/// Any operator
+	Operator = 5,
+	/// Any punctuation
+	Punctuation = 6,
+	/// Number
+	Number = 7,
+	/// Any register
+	Register = 8,
+	/// A decorator, eg. `sae` in `{sae}`
+	Decorator = 9,
+	/// Selector value (eg. far `JMP`/`CALL`)
+	SelectorValue = 10,
+	/// Label address (eg. `JE XXXXXX`)
+	LabelAddress = 11,
+	/// Function address (eg. `CALL XXXXXX`)
+	FunctionAddress = 12,
+	/// Data symbol
+	Data = 13,
+	/// Label symbol
+	Label = 14,
+	/// Function symbol
+	Function = 15,
+}
+// GENERATOR-END: Enum
+
+#[allow(dead_code)]
+pub(crate) fn formatter_text_kind_to_iced(value: FormatterTextKind) -> iced_x86_rust::FormatterTextKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
+
+#[allow(dead_code)]
+pub(crate) fn iced_to_formatter_text_kind(value: iced_x86_rust::FormatterTextKind) -> FormatterTextKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
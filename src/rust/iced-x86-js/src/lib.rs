@@ -77,6 +77,8 @@ mod block_encoder_options;
 mod cc;
 #[cfg(any(feature = "instr_api", all(feature = "encoder", feature = "op_code_info"), feature = "instr_create"))]
 mod code;
+#[cfg(feature = "code_asm")]
+mod code_asm;
 #[cfg(all(feature = "encoder", feature = "op_code_info"))]
 mod code_ext;
 #[cfg(feature = "instr_api")]
@@ -94,6 +96,8 @@ mod decoder;
 mod decoder_error;
 #[cfg(feature = "decoder")]
 mod decoder_options;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod decorator_kind;
 #[cfg(feature = "encoder")]
 mod encoder;
 #[cfg(any(feature = "instr_info", all(feature = "encoder", feature = "op_code_info")))]
@@ -108,6 +112,12 @@ mod flow_control;
 mod format_mnemonic_options;
 #[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
 mod formatter;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod formatter_options_provider;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod formatter_output;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod formatter_text_kind;
 #[cfg(feature = "instr_info")]
 mod info;
 mod instruction;
@@ -121,7 +131,7 @@ mod memory_size;
 mod memory_size_ext;
 #[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
 mod memory_size_options;
-#[cfg(any(feature = "instr_api", all(feature = "encoder", feature = "op_code_info")))]
+#[cfg(any(feature = "instr_api", all(feature = "encoder", feature = "op_code_info"), feature = "code_asm"))]
 mod mnemonic;
 #[cfg(feature = "mvex")]
 mod mvex_cvt_fn;
@@ -131,6 +141,8 @@ mod mvex_eh_bit;
 mod mvex_rm_conv;
 #[cfg(feature = "mvex")]
 mod mvex_tt_lut;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod number_kind;
 #[cfg(feature = "instr_info")]
 mod op_access;
 #[cfg(all(feature = "encoder", feature = "op_code_info"))]
@@ -141,7 +153,9 @@ mod op_code_operand_kind;
 mod op_code_table_kind;
 #[cfg(feature = "instr_api")]
 mod op_kind;
-#[cfg(any(feature = "instr_api", feature = "instr_info", feature = "instr_create"))]
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod prefix_kind;
+#[cfg(any(feature = "instr_api", feature = "instr_info", feature = "instr_create", feature = "code_asm"))]
 mod register;
 #[cfg(feature = "instr_info")]
 mod register_ext;
@@ -149,8 +163,12 @@ mod register_ext;
 mod rep_prefix_kind;
 #[cfg(feature = "instr_info")]
 mod rflags_bits;
-#[cfg(feature = "instr_api")]
+#[cfg(any(feature = "instr_api", feature = "code_asm"))]
 mod rounding_control;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod symbol_flags;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+mod symbol_resolver;
 #[cfg(all(feature = "encoder", feature = "op_code_info"))]
 mod tuple_type;
 
@@ -162,6 +180,8 @@ pub use block_encoder_options::*;
 pub use cc::*;
 #[cfg(any(feature = "instr_api", all(feature = "encoder", feature = "op_code_info"), feature = "instr_create"))]
 pub use code::*;
+#[cfg(feature = "code_asm")]
+pub use code_asm::*;
 #[cfg(all(feature = "encoder", feature = "op_code_info"))]
 pub use code_ext::*;
 #[cfg(feature = "instr_api")]
@@ -179,6 +199,8 @@ pub use decoder::*;
 pub use decoder_error::*;
 #[cfg(feature = "decoder")]
 pub use decoder_options::*;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+pub use decorator_kind::*;
 #[cfg(feature = "encoder")]
 pub use encoder::*;
 #[cfg(any(feature = "instr_info", all(feature = "encoder", feature = "op_code_info")))]
@@ -191,6 +213,8 @@ pub use flow_control::*;
 pub use format_mnemonic_options::*;
 #[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
 pub use formatter::*;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+pub use formatter_text_kind::*;
 #[cfg(feature = "instr_info")]
 pub use info::*;
 pub use instruction::*;
@@ -204,7 +228,7 @@ pub use memory_size::*;
 pub use memory_size_ext::*;
 #[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
 pub use memory_size_options::*;
-#[cfg(any(feature = "instr_api", all(feature = "encoder", feature = "op_code_info")))]
+#[cfg(any(feature = "instr_api", all(feature = "encoder", feature = "op_code_info"), feature = "code_asm"))]
 pub use mnemonic::*;
 #[cfg(feature = "mvex")]
 pub use mvex_cvt_fn::*;
@@ -214,6 +238,8 @@ pub use mvex_eh_bit::*;
 pub use mvex_rm_conv::*;
 #[cfg(feature = "mvex")]
 pub use mvex_tt_lut::*;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+pub use number_kind::*;
 #[cfg(feature = "instr_info")]
 pub use op_access::*;
 #[cfg(all(feature = "encoder", feature = "op_code_info"))]
@@ -224,7 +250,9 @@ pub use op_code_operand_kind::*;
 pub use op_code_table_kind::*;
 #[cfg(feature = "instr_api")]
 pub use op_kind::*;
-#[cfg(any(feature = "instr_api", feature = "instr_info", feature = "instr_create"))]
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+pub use prefix_kind::*;
+#[cfg(any(feature = "instr_api", feature = "instr_info", feature = "instr_create", feature = "code_asm"))]
 pub use register::*;
 #[cfg(feature = "instr_info")]
 pub use register_ext::*;
@@ -232,8 +260,10 @@ pub use register_ext::*;
 pub use rep_prefix_kind::*;
 #[cfg(feature = "instr_info")]
 pub use rflags_bits::*;
-#[cfg(feature = "instr_api")]
+#[cfg(any(feature = "instr_api", feature = "code_asm"))]
 pub use rounding_control::*;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+pub use symbol_flags::*;
 #[cfg(all(feature = "encoder", feature = "op_code_info"))]
 pub use tuple_type::*;
 use wasm_bindgen::prelude::*;
@@ -247,6 +277,7 @@ use wasm_bindgen::prelude::*;
 /// 0x04 | `XOP`
 /// 0x08 | `3DNow!`
 /// 0x10 | `MVEX`
+/// 0x20 | `CodeAssembler`
 #[wasm_bindgen(js_name = "getIcedFeatures")]
 pub fn get_iced_features() -> u32 {
 	#[allow(unused_mut)]
@@ -271,5 +302,9 @@ pub fn get_iced_features() -> u32 {
 	{
 		flags |= 0x10;
 	}
+	#[cfg(feature = "code_asm")]
+	{
+		flags |= 0x20;
+	}
 	flags
 }
@@ -1908,6 +1908,12 @@ pub enum Mnemonic {
 }
 // GENERATOR-END: Enum
 
+#[allow(dead_code)]
+pub(crate) fn mnemonic_to_iced(value: Mnemonic) -> iced_x86_rust::Mnemonic {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u16) }
+}
+
 #[allow(dead_code)]
 pub(crate) fn iced_to_mnemonic(value: iced_x86_rust::Mnemonic) -> Mnemonic {
 	// SAFETY: the enums are exactly identical
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use wasm_bindgen::prelude::*;
+
+// GENERATOR-BEGIN: Enum
+// ⚠️This was generated by GENERATOR!🦹‍♂️
+/// Number kind
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+#[allow(missing_docs)]
+pub enum NumberKind {
+	Int8 = 0,
+	UInt8 = 1,
+	Int16 = 2,
+	UInt16 = 3,
+	Int32 = 4,
+	UInt32 = 5,
+	Int64 = 6,
+	UInt64 = 7,
+}
+// GENERATOR-END: Enum
+
+#[allow(dead_code)]
+pub(crate) fn number_kind_to_iced(value: NumberKind) -> iced_x86_rust::NumberKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
+
+#[allow(dead_code)]
+pub(crate) fn iced_to_number_kind(value: iced_x86_rust::NumberKind) -> NumberKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
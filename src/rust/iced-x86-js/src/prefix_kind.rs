@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use wasm_bindgen::prelude::*;
+
+// GENERATOR-BEGIN: Enum
+// ⚠️This was generated by GENERATOR!🦹‍♂️
+/// Prefix
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+#[allow(missing_docs)]
+pub enum PrefixKind {
+	ES = 0,
+	CS = 1,
+	SS = 2,
+	DS = 3,
+	FS = 4,
+	GS = 5,
+	Lock = 6,
+	Rep = 7,
+	Repe = 8,
+	Repne = 9,
+	OperandSize = 10,
+	AddressSize = 11,
+	HintNotTaken = 12,
+	HintTaken = 13,
+	Bnd = 14,
+	Notrack = 15,
+	Xacquire = 16,
+	Xrelease = 17,
+}
+// GENERATOR-END: Enum
+
+#[allow(dead_code)]
+pub(crate) fn prefix_kind_to_iced(value: PrefixKind) -> iced_x86_rust::PrefixKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
+
+#[allow(dead_code)]
+pub(crate) fn iced_to_prefix_kind(value: iced_x86_rust::PrefixKind) -> PrefixKind {
+	// SAFETY: the enums are exactly identical
+	unsafe { std::mem::transmute(value as u8) }
+}
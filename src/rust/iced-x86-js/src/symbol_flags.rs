@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use wasm_bindgen::prelude::*;
+
+// GENERATOR-BEGIN: Enum
+// ⚠️This was generated by GENERATOR!🦹‍♂️
+/// Symbol flags
+#[wasm_bindgen]
+#[derive(Copy, Clone)]
+pub enum SymbolFlags {
+	/// No bit is set
+	None = 0x0000_0000,
+	/// It's a symbol relative to a register, eg. a struct offset `[ebx+some_struct.field1]`. If this is cleared, it's the address of a symbol.
+	Relative = 0x0000_0001,
+	/// It's a signed symbol and it should be displayed as `-symbol` or `reg-symbol` instead of `symbol` or `reg+symbol`
+	Signed = 0x0000_0002,
+}
+// GENERATOR-END: Enum
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::formatter::{call_formatter_callback, get_property, FormatterCallbackState};
+use iced_x86_rust::{FormatterTextKind, MemorySize, SymResTextInfo, SymResTextPart, SymbolFlags};
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+struct OwnedSymbol {
+	address: u64,
+	parts: Vec<SymResTextPart<'static>>,
+	flags: u32,
+	symbol_size: Option<MemorySize>,
+}
+
+impl OwnedSymbol {
+	fn to_symbol_result(&self) -> iced_x86_rust::SymbolResult<'_> {
+		iced_x86_rust::SymbolResult {
+			address: self.address,
+			text: SymResTextInfo::with_vec(&self.parts),
+			flags: self.flags,
+			symbol_size: self.symbol_size,
+		}
+	}
+}
+
+/// Calls a JavaScript function to resolve symbols. The results are cached by address and address size.
+pub(crate) struct JsSymbolResolver {
+	callback: js_sys::Function,
+	state: Rc<FormatterCallbackState>,
+	cache: HashMap<(u64, u32), Option<OwnedSymbol>>,
+}
+
+impl JsSymbolResolver {
+	pub(crate) fn new(callback: js_sys::Function, state: Rc<FormatterCallbackState>) -> Self {
+		Self { callback, state, cache: HashMap::new() }
+	}
+
+	fn call(
+		&self, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, address: u64, address_size: u32,
+	) -> Result<Option<OwnedSymbol>, JsValue> {
+		let result = call_formatter_callback(
+			&self.callback,
+			instruction,
+			operand,
+			instruction_operand,
+			&[JsValue::from(address), JsValue::from(address_size)],
+		)?;
+		if result.is_null_or_undefined() {
+			Ok(None)
+		} else if let Some(text) = result.as_string() {
+			Ok(Some(OwnedSymbol {
+				address,
+				parts: vec![SymResTextPart::with_string(text, FormatterTextKind::Label)],
+				flags: SymbolFlags::NONE,
+				symbol_size: None,
+			}))
+		} else if result.is_object() {
+			Ok(Some(to_owned_symbol(&result, address)?))
+		} else {
+			Err(js_sys::TypeError::new("The symbol resolver must return undefined, null, a string or a symbol object").into())
+		}
+	}
+}
+
+fn to_formatter_text_kind(value: &JsValue) -> Result<FormatterTextKind, JsValue> {
+	value
+		.as_f64()
+		.and_then(|value| FormatterTextKind::try_from(value as usize).ok())
+		.ok_or_else(|| js_sys::Error::new("Invalid FormatterTextKind value").into())
+}
+
+fn to_owned_symbol(symbol: &JsValue, default_address: u64) -> Result<OwnedSymbol, JsValue> {
+	let address = get_property(symbol, "address")?;
+	let address = if address.is_undefined() {
+		default_address
+	} else {
+		u64::try_from(address).map_err(|_| JsValue::from(js_sys::TypeError::new("`address` must be a bigint")))?
+	};
+
+	let text = get_property(symbol, "text")?;
+	let parts = if let Some(text) = text.as_string() {
+		let kind = get_property(symbol, "kind")?;
+		let kind = if kind.is_undefined() { FormatterTextKind::Label } else { to_formatter_text_kind(&kind)? };
+		vec![SymResTextPart::with_string(text, kind)]
+	} else if js_sys::Array::is_array(&text) {
+		let text = js_sys::Array::from(&text);
+		let mut parts = Vec::with_capacity(text.length() as usize);
+		for part in text.iter() {
+			let part = js_sys::Array::from(&part);
+			let text =
+				part.get(0).as_string().ok_or_else(|| JsValue::from(js_sys::TypeError::new("Expected a [string, FormatterTextKind] array")))?;
+			parts.push(SymResTextPart::with_string(text, to_formatter_text_kind(&part.get(1))?));
+		}
+		parts
+	} else {
+		return Err(js_sys::TypeError::new("`text` must be a string or an array of [string, FormatterTextKind] arrays").into());
+	};
+
+	let flags = get_property(symbol, "flags")?;
+	let flags = if flags.is_undefined() {
+		SymbolFlags::NONE
+	} else {
+		flags.as_f64().ok_or_else(|| JsValue::from(js_sys::TypeError::new("`flags` must be a SymbolFlags value")))? as u32
+	};
+
+	let symbol_size = get_property(symbol, "symbolSize")?;
+	let symbol_size = if symbol_size.is_null_or_undefined() {
+		None
+	} else {
+		let symbol_size = symbol_size
+			.as_f64()
+			.and_then(|value| MemorySize::try_from(value as usize).ok())
+			.ok_or_else(|| JsValue::from(js_sys::Error::new("Invalid MemorySize value")))?;
+		Some(symbol_size)
+	};
+
+	Ok(OwnedSymbol { address, parts, flags, symbol_size })
+}
+
+impl iced_x86_rust::SymbolResolver for JsSymbolResolver {
+	fn symbol(
+		&mut self, instruction: &iced_x86_rust::Instruction, operand: u32, instruction_operand: Option<u32>, address: u64, address_size: u32,
+	) -> Option<iced_x86_rust::SymbolResult<'_>> {
+		if self.state.take_clear_symbol_cache() {
+			self.cache.clear();
+		}
+		if self.state.has_error() {
+			return None;
+		}
+		let key = (address, address_size);
+		if !self.cache.contains_key(&key) {
+			match self.call(instruction, operand, instruction_operand, address, address_size) {
+				Ok(symbol) => {
+					let _ = self.cache.insert(key, symbol);
+				}
+				Err(error) => {
+					self.state.set_error(error);
+					return None;
+				}
+			}
+		}
+		self.cache.get(&key).and_then(|symbol| symbol.as_ref().map(OwnedSymbol::to_symbol_result))
+	}
+}
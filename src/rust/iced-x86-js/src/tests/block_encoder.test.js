@@ -54,6 +54,21 @@ test("BlockEncoder: 64-bit", () => {
 	blockEncoder.free();
 });
 
+test("BlockEncoder: JCC erratum", () => {
+	const decoder = new Decoder(64, new Uint8Array([0xC3]), DecoderOptions.None);
+	decoder.ip = 0x101Fn;
+	const instr = decoder.decode();
+	const blockEncoder = new BlockEncoder(64, BlockEncoderOptions.JccErratum);
+
+	blockEncoder.add(instr);
+	const newCode = blockEncoder.encode(0x101Fn);
+	expect(newCode).toStrictEqual(new Uint8Array([0x90, 0xC3]));
+
+	decoder.free();
+	instr.free();
+	blockEncoder.free();
+});
+
 // Make sure it's not an enum arg in the Rust code since it's a flags enum. It must be a u32 in the method sig.
 test("BlockEncoder flags", () => {
 	const blockEncoder1 = new BlockEncoder(64, BlockEncoderOptions.DontFixBranches |
@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+const {
+	AsmMemoryOperand, AsmOperand, BlockEncoderOptions, Code, CodeAssembler, Instruction, Mnemonic, Register,
+	RoundingControl,
+} = require("iced-x86");
+
+test("Creating a CodeAssembler with an invalid bitness throws", () => {
+	expect(() => new CodeAssembler(0)).toThrow();
+	expect(() => new CodeAssembler(63)).toThrow();
+	expect(() => new CodeAssembler(128)).toThrow();
+});
+
+test("CodeAssembler: bitness", () => {
+	for (const bitness of [16, 32, 64]) {
+		const a = new CodeAssembler(bitness);
+		expect(a.bitness).toBe(bitness);
+		a.free();
+	}
+});
+
+test("CodeAssembler: assemble", () => {
+	const a = new CodeAssembler(64);
+	const rax = AsmOperand.reg(Register.RAX);
+	const eax = AsmOperand.reg(Register.EAX);
+	const r15 = AsmOperand.reg(Register.R15);
+	const mem1 = new AsmMemoryOperand(Register.RAX).withIndex(Register.R12, 8).withDisplacement(-0x10n).qwordPtr();
+	const mem2 = new AsmMemoryOperand(Register.RSP).withDisplacement(8n).dwordPtr();
+	const imm1 = AsmOperand.immI32(0x1234);
+	const imm2 = AsmOperand.immI32(1);
+
+	a.emit1(Mnemonic.Push, r15);
+	a.emit2(Mnemonic.Add, rax, r15);
+	a.emit2(Mnemonic.Mov, rax, mem1);
+	a.emit2(Mnemonic.Mov, mem2, imm1);
+	a.emit2(Mnemonic.And, eax, imm2);
+	a.emit0(Mnemonic.Ret);
+	const bytes = a.assemble(0x12345678n);
+	expect(bytes).toStrictEqual(new Uint8Array([
+		0x41, 0x57, 0x4C, 0x01, 0xF8, 0x4A, 0x8B, 0x44, 0xE0, 0xF0, 0xC7, 0x44, 0x24, 0x08, 0x34, 0x12,
+		0x00, 0x00, 0x83, 0xE0, 0x01, 0xC3,
+	]));
+
+	a.free();
+	rax.free();
+	eax.free();
+	r15.free();
+	mem1.free();
+	mem2.free();
+	imm1.free();
+	imm2.free();
+});
+
+test("CodeAssembler: instructions", () => {
+	const a = new CodeAssembler(64);
+	const rax = AsmOperand.reg(Register.RAX);
+	const rcx = AsmOperand.reg(Register.RCX);
+	const eax = AsmOperand.reg(Register.EAX);
+	const imm1 = AsmOperand.immU32(0xFFFFFFFF);
+	const imm2 = AsmOperand.immI32(-1);
+
+	a.emit2(Mnemonic.Add, rax, rcx);
+	a.emit2(Mnemonic.Mov, eax, imm1);
+	a.emit2(Mnemonic.Add, rax, imm2);
+	const instrs = a.instructions();
+	expect(instrs.length).toBe(3);
+	expect(instrs[0].code).toBe(Code.Add_rm64_r64);
+	expect(instrs[1].code).toBe(Code.Mov_r32_imm32);
+	expect(instrs[2].code).toBe(Code.Add_rm64_imm8);
+	expect(instrs[1].immediate32).toBe(0xFFFFFFFF);
+
+	const taken = a.takeInstructions();
+	expect(taken.length).toBe(3);
+	expect(taken[0].equals(instrs[0])).toBe(true);
+	const empty = a.instructions();
+	expect(empty.length).toBe(0);
+
+	a.free();
+	rax.free();
+	rcx.free();
+	eax.free();
+	imm1.free();
+	imm2.free();
+	instrs.forEach(a => a.free());
+	taken.forEach(a => a.free());
+});
+
+test("CodeAssembler: invalid operands throw", () => {
+	const a = new CodeAssembler(64);
+	const rax = AsmOperand.reg(Register.RAX);
+	const ecx = AsmOperand.reg(Register.ECX);
+	const imm = AsmOperand.immI32(1);
+	const mem = new AsmMemoryOperand(Register.RAX).ptr();
+
+	expect(() => a.emit2(Mnemonic.Mov, rax, ecx)).toThrow();
+	expect(() => a.emit2(Mnemonic.Add, imm, rax)).toThrow();
+	// Ambiguous memory operand size
+	expect(() => a.emit1(Mnemonic.Inc, mem)).toThrow();
+	expect(() => new AsmMemoryOperand(Register.RAX).withIndex(Register.RCX, 3).qwordPtr()).toThrow();
+	expect(() => imm.withSegment(Register.FS)).toThrow();
+	expect(() => rax.withSegment(Register.FS)).toThrow();
+
+	a.free();
+	rax.free();
+	ecx.free();
+	imm.free();
+	mem.free();
+});
+
+test("CodeAssembler: segment override", () => {
+	const a = new CodeAssembler(64);
+	const rax = AsmOperand.reg(Register.RAX);
+	const mem = new AsmMemoryOperand(Register.RCX).qwordPtr();
+	const fsMem = mem.withSegment(Register.FS);
+	const gsMem = mem.withSegment(Register.GS);
+
+	a.emit2(Mnemonic.Mov, rax, fsMem);
+	a.emit2(Mnemonic.Mov, rax, gsMem);
+	expect(a.assemble(0n)).toStrictEqual(new Uint8Array([0x64, 0x48, 0x8B, 0x01, 0x65, 0x48, 0x8B, 0x01]));
+
+	a.free();
+	rax.free();
+	mem.free();
+	fsMem.free();
+	gsMem.free();
+});
+
+test("CodeAssembler: AVX-512", () => {
+	const a = new CodeAssembler(64);
+	const zmm1 = AsmOperand.reg(Register.ZMM1);
+	const zmm1k1z = zmm1.withOpMask(Register.K1).withZeroingMasking();
+	const zmm2 = AsmOperand.reg(Register.ZMM2);
+	const zmm3 = AsmOperand.reg(Register.ZMM3);
+	const zmm3rd = zmm3.withRoundingControl(RoundingControl.RoundDown);
+	const xmm1 = AsmOperand.reg(Register.XMM1);
+	const xmm2sae = AsmOperand.reg(Register.XMM2).withSuppressAllExceptions();
+	const bcst = new AsmMemoryOperand(Register.RAX).dwordBcst();
+	const mem = new AsmMemoryOperand(Register.RAX).zmmwordPtr().withOpMask(Register.K2);
+
+	a.emit3(Mnemonic.Vaddps, zmm1k1z, zmm2, bcst);
+	a.emit3(Mnemonic.Vaddps, zmm1, zmm2, zmm3rd);
+	a.emit2(Mnemonic.Vucomiss, xmm1, xmm2sae);
+	a.emit2(Mnemonic.Vmovups, mem, zmm3);
+	const instrs = a.instructions();
+	expect(instrs[0].opMask).toBe(Register.K1);
+	expect(instrs[0].zeroingMasking).toBe(true);
+	expect(instrs[0].isBroadcast).toBe(true);
+	expect(instrs[1].roundingControl).toBe(RoundingControl.RoundDown);
+	expect(instrs[2].suppressAllExceptions).toBe(true);
+	expect(instrs[3].opMask).toBe(Register.K2);
+	expect(a.assemble(0n)).toStrictEqual(new Uint8Array([
+		0x62, 0xF1, 0x6C, 0xD9, 0x58, 0x08, 0x62, 0xF1, 0x6C, 0x38, 0x58, 0xCB, 0x62, 0xF1, 0x7C, 0x18,
+		0x2E, 0xCA, 0x62, 0xF1, 0x7C, 0x4A, 0x11, 0x18,
+	]));
+
+	a.free();
+	[zmm1, zmm1k1z, zmm2, zmm3, zmm3rd, xmm1, xmm2sae, bcst, mem].forEach(a => a.free());
+	instrs.forEach(a => a.free());
+});
+
+test("CodeAssembler: prefixes", () => {
+	const a = new CodeAssembler(64);
+	const mem = new AsmMemoryOperand(Register.RAX).dwordPtr();
+	const ecx = AsmOperand.reg(Register.ECX);
+	const xmm1 = AsmOperand.reg(Register.XMM1);
+	const xmm2 = AsmOperand.reg(Register.XMM2);
+	const xmm3 = AsmOperand.reg(Register.XMM3);
+
+	a.lock();
+	a.emit2(Mnemonic.Add, mem, ecx);
+	a.rep();
+	a.emit0(Mnemonic.Stosb);
+	a.repne();
+	a.emit0(Mnemonic.Scasb);
+	a.xacquire();
+	a.lock();
+	a.emit2(Mnemonic.Add, mem, ecx);
+	a.evex();
+	a.emit3(Mnemonic.Vaddps, xmm1, xmm2, xmm3);
+	a.emit3(Mnemonic.Vaddps, xmm1, xmm2, xmm3);
+	expect(a.assemble(0n)).toStrictEqual(new Uint8Array([
+		0xF0, 0x01, 0x08, 0xF3, 0xAA, 0xF2, 0xAE, 0xF0, 0xF2, 0x01, 0x08, 0x62, 0xF1, 0x6C, 0x08, 0x58,
+		0xCB, 0xC5, 0xE8, 0x58, 0xCB,
+	]));
+
+	a.free();
+	[mem, ecx, xmm1, xmm2, xmm3].forEach(a => a.free());
+});
+
+test("CodeAssembler: options", () => {
+	const a = new CodeAssembler(64);
+
+	expect(a.preferVex).toBe(true);
+	a.preferVex = false;
+	expect(a.preferVex).toBe(false);
+
+	expect(a.preferShortBranch).toBe(true);
+	a.preferShortBranch = false;
+	expect(a.preferShortBranch).toBe(false);
+
+	a.free();
+});
+
+test("CodeAssembler: labels", () => {
+	const a = new CodeAssembler(64);
+	const ecx = AsmOperand.reg(Register.ECX);
+	const ten = AsmOperand.immI32(10);
+	const loopLbl = a.createLabel();
+	const afterLoop = a.createLabel();
+	const loopOp = AsmOperand.label(loopLbl);
+	const afterLoopOp = AsmOperand.label(afterLoop);
+
+	a.emit2(Mnemonic.Mov, ecx, ten);
+	a.setLabel(loopLbl);
+	a.emit1(Mnemonic.Dec, ecx);
+	a.emit1(Mnemonic.Jp, afterLoopOp);
+	a.emit1(Mnemonic.Jne, loopOp);
+	a.setLabel(afterLoop);
+	a.emit0(Mnemonic.Ret);
+	expect(() => a.setLabel(loopLbl)).toThrow();
+	expect(a.assemble(0x12345678n)).toStrictEqual(new Uint8Array([
+		0xB9, 0x0A, 0x00, 0x00, 0x00, 0xFF, 0xC9, 0x7A, 0x02, 0x75, 0xFA, 0xC3,
+	]));
+
+	a.free();
+	[ecx, ten, loopLbl, afterLoop, loopOp, afterLoopOp].forEach(a => a.free());
+});
+
+test("CodeAssembler: anonymous labels", () => {
+	const a = new CodeAssembler(64);
+	const rax = AsmOperand.reg(Register.RAX);
+
+	expect(() => a.bwd()).toThrow();
+	a.anonymousLabel();
+	expect(() => a.anonymousLabel()).toThrow();
+	a.emit1(Mnemonic.Inc, rax);
+	const bwd = a.bwd();
+	const bwdOp = AsmOperand.label(bwd);
+	a.emit1(Mnemonic.Jne, bwdOp);
+	const fwd = a.fwd();
+	const fwdOp = AsmOperand.label(fwd);
+	a.emit1(Mnemonic.Jmp, fwdOp);
+	a.emit0(Mnemonic.Nop);
+	a.anonymousLabel();
+	a.emit0(Mnemonic.Ret);
+	expect(a.assemble(0n)).toStrictEqual(new Uint8Array([0x48, 0xFF, 0xC0, 0x75, 0xFB, 0xEB, 0x01, 0x90, 0xC3]));
+
+	a.free();
+	[rax, bwd, bwdOp, fwd, fwdOp].forEach(a => a.free());
+});
+
+test("CodeAssembler: label IP", () => {
+	const a = new CodeAssembler(64);
+	const skipData = a.createLabel();
+	const data = a.createLabel();
+	const skipDataOp = AsmOperand.label(skipData);
+	const rax = AsmOperand.reg(Register.RAX);
+	const dataMem = AsmMemoryOperand.fromLabel(data).ptr();
+
+	a.emit1(Mnemonic.Jmp, skipDataOp);
+	a.setLabel(data);
+	a.db(new Uint8Array([0x90, 0xCC, 0xF1, 0x90]));
+	a.setLabel(skipData);
+	a.emit2(Mnemonic.Lea, rax, dataMem);
+	const result = a.assembleOptions(0x12345678n, BlockEncoderOptions.ReturnNewInstructionOffsets);
+	expect(result.rip).toBe(0x12345678n);
+	expect(result.codeBuffer).toStrictEqual(new Uint8Array([
+		0xEB, 0x04, 0x90, 0xCC, 0xF1, 0x90, 0x48, 0x8D, 0x05, 0xF5, 0xFF, 0xFF, 0xFF,
+	]));
+	expect(result.labelIP(data)).toBe(0x1234567An);
+	expect(result.labelIP(skipData)).toBe(0x1234567En);
+	expect(() => AsmMemoryOperand.fromLabel(data).withDisplacement(1n).ptr()).toThrow();
+
+	a.free();
+	result.free();
+	[skipData, data, skipDataOp, rax, dataMem].forEach(a => a.free());
+});
+
+test("CodeAssembler: labelIP() requires ReturnNewInstructionOffsets", () => {
+	const a = new CodeAssembler(64);
+	const label = a.createLabel();
+	a.setLabel(label);
+	a.emit0(Mnemonic.Nop);
+	const result = a.assembleOptions(0n, BlockEncoderOptions.None);
+	expect(() => result.labelIP(label)).toThrow();
+
+	a.free();
+	label.free();
+	result.free();
+});
+
+test("CodeAssembler: data", () => {
+	const a = new CodeAssembler(64);
+	a.db(new Uint8Array([0x01, 0x02]));
+	a.dw(new Uint16Array([0x1234, 0xFFFF]));
+	a.dd(new Uint32Array([0x12345678, 0xFFFFFFFE]));
+	a.dq(new BigUint64Array([0x123456789ABCDEF0n, 0xFFFFFFFFFFFFFFFDn]));
+	expect(a.assemble(0n)).toStrictEqual(new Uint8Array([
+		0x01, 0x02, 0x34, 0x12, 0xFF, 0xFF, 0x78, 0x56, 0x34, 0x12, 0xFE, 0xFF, 0xFF, 0xFF, 0xF0, 0xDE,
+		0xBC, 0x9A, 0x78, 0x56, 0x34, 0x12, 0xFD, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF,
+	]));
+
+	a.lock();
+	expect(() => a.db(new Uint8Array([0x90]))).toThrow();
+
+	a.free();
+});
+
+test("CodeAssembler: addInstruction() and misc", () => {
+	const a = new CodeAssembler(64);
+	const instr = Instruction.createRegReg(Code.Add_r64_rm64, Register.RAX, Register.RCX);
+	a.addInstruction(instr);
+	a.nopsWithSize(3);
+	a.zeroBytes();
+	a.xlatb();
+	expect(a.assemble(0n)).toStrictEqual(new Uint8Array([0x48, 0x03, 0xC1, 0x0F, 0x1F, 0x00, 0xD7]));
+
+	a.reset();
+	a.emit0(Mnemonic.Int3);
+	expect(a.assemble(0n)).toStrictEqual(new Uint8Array([0xCC]));
+
+	a.free();
+	instr.free();
+});
+
+test("CodeAssembler: far branches", () => {
+	const a32 = new CodeAssembler(32);
+	a32.callFar(0x1234, 0x56789ABC);
+	a32.jmpFar(0x1234, 0x56789ABC);
+	expect(a32.assemble(0n)).toStrictEqual(new Uint8Array([
+		0x9A, 0xBC, 0x9A, 0x78, 0x56, 0x34, 0x12, 0xEA, 0xBC, 0x9A, 0x78, 0x56, 0x34, 0x12,
+	]));
+
+	const a64 = new CodeAssembler(64);
+	a64.callFar(0x1234, 0x56789ABC);
+	expect(() => a64.assemble(0n)).toThrow();
+
+	a32.free();
+	a64.free();
+});
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+const {
+	Code, Decoder, DecoderOptions, Formatter, FormatterSyntax, FormatterTextKind, MemorySize, MemorySizeOptions, SymbolFlags
+} = require("iced-x86");
+
+test("Symbol resolver returns a string", () => {
+	const resolver = (instruction, operand, instructionOperand, address, addressSize) => {
+		expect(instruction.code).toBe(Code.Call_rel32_64);
+		expect(operand).toBe(0);
+		expect(instructionOperand).toBe(0);
+		expect(addressSize).toBe(8);
+		if (address === 0x5AA55AA5n)
+			return "printf";
+		return undefined;
+	};
+	const bytes = new Uint8Array([0xE8, 0x00, 0x00, 0x00, 0x00, 0xE8, 0x00, 0x00, 0x00, 0x00]);
+	const decoder = new Decoder(64, bytes, DecoderOptions.None);
+	decoder.ip = 0x5AA55AA0n;
+	const formatter = new Formatter(FormatterSyntax.Masm, resolver);
+	const instr = decoder.decode();
+	const instr2 = decoder.decode();
+
+	expect(formatter.format(instr)).toBe("call printf");
+	expect(formatter.format(instr2)).toBe("call 000000005AA55AAAh");
+
+	instr.free();
+	instr2.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Symbol resolver returns a symbol object", () => {
+	const resolver = (instruction, operand, instructionOperand, address, addressSize) => {
+		if (address === 0x1234n)
+			return { text: "abc", kind: FormatterTextKind.Data, symbolSize: MemorySize.UInt32 };
+		return { address: 0x5AA55AA0n, text: [["my", FormatterTextKind.Function], ["_func", FormatterTextKind.Data]], flags: SymbolFlags.None };
+	};
+	const bytes = new Uint8Array([0xE8, 0x00, 0x00, 0x00, 0x00, 0x8B, 0x04, 0x25, 0x34, 0x12, 0x00, 0x00]);
+	const decoder = new Decoder(64, bytes, DecoderOptions.None);
+	decoder.ip = 0x5AA55AA0n;
+	const formatter = new Formatter(FormatterSyntax.Masm, resolver);
+	const instr = decoder.decode();
+	const instr2 = decoder.decode();
+
+	expect(formatter.format(instr)).toBe("call my_func+5");
+	expect(formatter.format(instr2)).toBe("mov eax,[abc]");
+
+	instr.free();
+	instr2.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Symbol resolver results are cached", () => {
+	const calls = [];
+	const resolver = (instruction, operand, instructionOperand, address, addressSize) => {
+		calls.push(address);
+		return "sym";
+	};
+	const bytes = new Uint8Array([0xE8, 0x00, 0x00, 0x00, 0x00]);
+	const decoder = new Decoder(64, bytes, DecoderOptions.None);
+	decoder.ip = 0x5AA55AA0n;
+	const formatter = new Formatter(FormatterSyntax.Nasm, resolver);
+	const instr = decoder.decode();
+
+	expect(formatter.format(instr)).toBe("call sym");
+	expect(formatter.format(instr)).toBe("call sym");
+	expect(calls).toEqual([0x5AA55AA5n]);
+	formatter.clearSymbolCache();
+	expect(formatter.format(instr)).toBe("call sym");
+	expect(calls).toEqual([0x5AA55AA5n, 0x5AA55AA5n]);
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Symbol resolver throws", () => {
+	const resolver = () => {
+		throw new Error("abc");
+	};
+	const bytes = new Uint8Array([0xE8, 0x00, 0x00, 0x00, 0x00]);
+	const decoder = new Decoder(64, bytes, DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Nasm, resolver);
+	const invalidFormatter = new Formatter(FormatterSyntax.Nasm, () => 123);
+	const instr = decoder.decode();
+
+	expect(() => formatter.format(instr)).toThrow("abc");
+	expect(() => formatter.formatAllOperands(instr)).toThrow("abc");
+	expect(() => invalidFormatter.format(instr)).toThrow();
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+	invalidFormatter.free();
+});
+
+test("Options provider", () => {
+	const provider = (instruction, operand, instructionOperand, options, numberOptions) => {
+		expect(options.memorySizeOptions).toBe(MemorySizeOptions.Default);
+		expect(numberOptions.numberBase).toBe(16);
+		if (operand === 0)
+			options.memorySizeOptions = MemorySizeOptions.Always;
+		if (operand === 1) {
+			numberOptions.prefix = "$";
+			numberOptions.suffix = "";
+			numberOptions.digitSeparator = "_";
+			numberOptions.digitGroupSize = 2;
+		}
+	};
+	const bytes = new Uint8Array([0x81, 0x00, 0x78, 0x56, 0x34, 0x12]);
+	const decoder = new Decoder(64, bytes, DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Masm, undefined, provider);
+	const instr = decoder.decode();
+
+	expect(formatter.format(instr)).toBe("add dword ptr [rax],$12_34_56_78");
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Options provider throws", () => {
+	const provider = () => {
+		throw new Error("abc");
+	};
+	const bytes = new Uint8Array([0x48, 0x83, 0xC0, 0x10]);
+	const decoder = new Decoder(64, bytes, DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Masm, undefined, provider);
+	const invalidFormatter = new Formatter(FormatterSyntax.Masm, undefined, (instruction, operand, instructionOperand, options, numberOptions) => {
+		numberOptions.numberBase = 3;
+	});
+	const instr = decoder.decode();
+
+	expect(() => formatter.format(instr)).toThrow("abc");
+	expect(() => formatter.formatOperand(instr, 1)).toThrow("abc");
+	expect(() => invalidFormatter.format(instr)).toThrow();
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+	invalidFormatter.free();
+});
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+const {
+	DecoratorKind, Decoder, DecoderOptions, Formatter, FormatterSyntax, FormatterTextKind, NumberKind, PrefixKind, Register
+} = require("iced-x86");
+
+class AllMethodsOutput {
+	constructor() {
+		this.parts = [];
+		this.calls = [];
+	}
+	write(text, kind) {
+		this.parts.push([text, kind]);
+	}
+	writePrefix(instruction, text, prefix) {
+		this.calls.push(["prefix", text, prefix]);
+	}
+	writeMnemonic(instruction, text) {
+		this.calls.push(["mnemonic", text]);
+	}
+	writeNumber(instruction, operand, instructionOperand, text, value, numberKind, kind) {
+		this.calls.push(["number", operand, instructionOperand, text, value, numberKind, kind]);
+	}
+	writeDecorator(instruction, operand, instructionOperand, text, decorator) {
+		this.calls.push(["decorator", operand, instructionOperand, text, decorator]);
+	}
+	writeRegister(instruction, operand, instructionOperand, text, register) {
+		this.calls.push(["register", operand, instructionOperand, text, register]);
+	}
+}
+
+test("Format to array", () => {
+	const decoder = new Decoder(64, new Uint8Array([0xF0, 0x48, 0x83, 0x00, 0x10]), DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Masm);
+	const instr = decoder.decode();
+
+	const parts = formatter.formatToArray(instr);
+	expect(parts.map(a => a[0]).join("")).toBe(formatter.format(instr));
+	expect(parts).toStrictEqual([
+		["lock", FormatterTextKind.Prefix],
+		[" ", FormatterTextKind.Text],
+		["add", FormatterTextKind.Mnemonic],
+		[" ", FormatterTextKind.Text],
+		["qword", FormatterTextKind.Keyword],
+		[" ", FormatterTextKind.Text],
+		["ptr", FormatterTextKind.Keyword],
+		[" ", FormatterTextKind.Text],
+		["[", FormatterTextKind.Punctuation],
+		["rax", FormatterTextKind.Register],
+		["]", FormatterTextKind.Punctuation],
+		[",", FormatterTextKind.Punctuation],
+		["10h", FormatterTextKind.Number],
+	]);
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Format output with only a write() method", () => {
+	const decoder = new Decoder(64, new Uint8Array([0xF0, 0x48, 0x83, 0x00, 0x10]), DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Masm);
+	const instr = decoder.decode();
+
+	const parts = [];
+	formatter.formatOutput(instr, { write: (text, kind) => parts.push([text, kind]) });
+	expect(parts).toStrictEqual(formatter.formatToArray(instr));
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Format output with all methods", () => {
+	const decoder = new Decoder(64, new Uint8Array([0xF0, 0x48, 0x83, 0x00, 0x10]), DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Masm);
+	const instr = decoder.decode();
+
+	const output = new AllMethodsOutput();
+	formatter.formatOutput(instr, output);
+	expect(output.calls).toStrictEqual([
+		["prefix", "lock", PrefixKind.Lock],
+		["mnemonic", "add"],
+		["register", 0, 0, "rax", Register.RAX],
+		["number", 1, 1, "10h", 0x10n, NumberKind.UInt64, FormatterTextKind.Number],
+	]);
+	expect(output.parts).not.toContainEqual(["lock", FormatterTextKind.Prefix]);
+	expect(output.parts).toContainEqual(["ptr", FormatterTextKind.Keyword]);
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Format output decorators", () => {
+	const decoder = new Decoder(64, new Uint8Array([0x62, 0xF1, 0x7C, 0x9B, 0x58, 0xC2]), DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Masm);
+	const instr = decoder.decode();
+
+	const output = new AllMethodsOutput();
+	formatter.formatOutput(instr, output);
+	expect(output.calls).toContainEqual(["decorator", 0, 0, "z", DecoratorKind.ZeroingMasking]);
+	expect(output.calls).toContainEqual(["decorator", 2, 2, "rn-sae", DecoratorKind.RoundingControl]);
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+});
+
+test("Format output throws", () => {
+	const decoder = new Decoder(64, new Uint8Array([0x48, 0x83, 0xC0, 0x10]), DecoderOptions.None);
+	const formatter = new Formatter(FormatterSyntax.Masm);
+	const instr = decoder.decode();
+
+	expect(() => formatter.formatOutput(instr, { write: () => { throw new Error("abc"); } })).toThrow("abc");
+	expect(() => formatter.formatOutput(instr, {})).toThrow(TypeError);
+
+	instr.free();
+	decoder.free();
+	formatter.free();
+});
@@ -15,4 +15,6 @@ return {
 	ReturnNewInstructionOffsets = 0x00000004,
 	---The `BlockEncoder` should return `ConstantOffsets`
 	ReturnConstantOffsets = 0x00000008,
+	---Mitigates the Intel JCC erratum (Skylake-family CPUs). `NOP`s are added before branches (`Jcc`, `JMP`, `CALL`, `RET`, etc) and macro-fused pairs (eg. `CMP`/`TEST` + `Jcc`) so they don't cross or end on a 32-byte boundary. The padding depends on the base IP of the block so the code must be stored at that address (or at an address with the same low 5 bits).
+	JccErratum = 0x00000010,
 }
@@ -0,0 +1,20 @@
+-- SPDX-License-Identifier: MIT
+-- Copyright (C) 2018-present iced project and contributors
+
+-- ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+---Decorator
+return {
+	---Broadcast decorator, eg. `{1to4}`
+	Broadcast = 0,
+	---Rounding control, eg. `{rd-sae}`
+	RoundingControl = 1,
+	---Suppress all exceptions: `{sae}`
+	SuppressAllExceptions = 2,
+	---Zeroing masking: `{z}`
+	ZeroingMasking = 3,
+	---MVEX swizzle or memory up/down conversion: `{dacb}` or `{sint16}`
+	SwizzleMemConv = 4,
+	---MVEX eviction hint: `{eh}`
+	EvictionHint = 5,
+}
@@ -0,0 +1,40 @@
+-- SPDX-License-Identifier: MIT
+-- Copyright (C) 2018-present iced project and contributors
+
+-- ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+---Formatter text kind
+return {
+	---Normal text
+	Text = 0,
+	---Assembler directive
+	Directive = 1,
+	---Any prefix
+	Prefix = 2,
+	---Any mnemonic
+	Mnemonic = 3,
+	---Any keyword
+	Keyword = 4,
+	---Any operator
+	Operator = 5,
+	---Any punctuation
+	Punctuation = 6,
+	---Number
+	Number = 7,
+	---Any register
+	Register = 8,
+	---A decorator, eg. `sae` in `{sae}`
+	Decorator = 9,
+	---Selector value (eg. far `JMP`/`CALL`)
+	SelectorValue = 10,
+	---Label address (eg. `JE XXXXXX`)
+	LabelAddress = 11,
+	---Function address (eg. `CALL XXXXXX`)
+	FunctionAddress = 12,
+	---Data symbol
+	Data = 13,
+	---Label symbol
+	Label = 14,
+	---Function symbol
+	Function = 15,
+}
@@ -0,0 +1,16 @@
+-- SPDX-License-Identifier: MIT
+-- Copyright (C) 2018-present iced project and contributors
+
+-- ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+---Number kind
+return {
+	Int8 = 0,
+	UInt8 = 1,
+	Int16 = 2,
+	UInt16 = 3,
+	Int32 = 4,
+	UInt32 = 5,
+	Int64 = 6,
+	UInt64 = 7,
+}
@@ -0,0 +1,26 @@
+-- SPDX-License-Identifier: MIT
+-- Copyright (C) 2018-present iced project and contributors
+
+-- ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+---Prefix
+return {
+	ES = 0,
+	CS = 1,
+	SS = 2,
+	DS = 3,
+	FS = 4,
+	GS = 5,
+	Lock = 6,
+	Rep = 7,
+	Repe = 8,
+	Repne = 9,
+	OperandSize = 10,
+	AddressSize = 11,
+	HintNotTaken = 12,
+	HintTaken = 13,
+	Bnd = 14,
+	Notrack = 15,
+	Xacquire = 16,
+	Xrelease = 17,
+}
@@ -0,0 +1,14 @@
+-- SPDX-License-Identifier: MIT
+-- Copyright (C) 2018-present iced project and contributors
+
+-- ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+---Symbol flags
+return {
+	---No bit is set
+	None = 0x00000000,
+	---It's a symbol relative to a register, eg. a struct offset `[ebx+some_struct.field1]`. If this is cleared, it's the address of a symbol.
+	Relative = 0x00000001,
+	---It's a signed symbol and it should be displayed as `-symbol` or `reg-symbol` instead of `symbol` or `reg+symbol`
+	Signed = 0x00000002,
+}
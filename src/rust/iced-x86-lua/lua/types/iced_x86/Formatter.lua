@@ -13,7 +13,24 @@ local Formatter = {}
 
 ---Creates a new x86 formatter that supports GNU Assembler, Intel XED, masm and nasm syntax
 ---
+---The optional symbol resolver is called with `(instruction, operand, instruction_operand, address, address_size)` and
+---should return `nil` if there's no symbol, a string (the symbol) or a table:
+---`{ address = integer?, text = string | { { string, integer }, ... }, kind = integer?, flags = integer?, symbol_size = integer? }`
+---where `kind` is a `FormatterTextKind` enum value (default `Label`), `flags` a `SymbolFlags` value and `symbol_size` a `MemorySize`
+---enum value. The results are cached, see `Formatter:clear_symbol_cache()`.
+---
+---The optional options provider is called with `(instruction, operand, instruction_operand, options, number_options)` and can
+---modify the fields of the two tables:
+---- `options`: `branch_size`, `rip_relative_addresses`, `memory_size_options`
+---- `number_options`: `prefix`, `suffix`, `digit_separator`, `digit_group_size`, `number_base` (2, 8, 10 or 16), `uppercase_hex`,
+---  `small_hex_numbers_in_decimal`, `add_leading_zero_to_hex_numbers`, `leading_zeros`, `signed_number`, `displacement_leading_zeros`
+---
+---`instruction_operand` is `nil` if the operand isn't an instruction operand. Errors raised by the functions are re-raised by the
+---formatter method that called them.
+---
 ---@param syntax integer #Formatter syntax (A `FormatterSyntax` enum value)
+---@param symbol_resolver? fun(instruction: Instruction, operand: integer, instruction_operand: integer?, address: integer, address_size: integer): string|table|nil #(default = `nil`) Symbol resolver
+---@param options_provider? fun(instruction: Instruction, operand: integer, instruction_operand: integer?, options: table, number_options: table) #(default = `nil`) Options provider
 ---@return Formatter
 ---
 ---# Examples
@@ -32,7 +49,7 @@ local Formatter = {}
 ---local disasm = formatter:format(instr)
 ---assert(disasm == "VCVTNE2PS2BF16 zmm2{k5}{z},zmm6,dword bcst [rax+4]")
 ---```
-function Formatter.new(syntax) end
+function Formatter.new(syntax, symbol_resolver, options_provider) end
 
 ---Formats the whole instruction: prefixes, mnemonic, operands
 ---
@@ -40,6 +57,61 @@ function Formatter.new(syntax) end
 ---@return string #The formatted string
 function Formatter:format(instruction) end
 
+---Formats the whole instruction and calls the methods of `output`, eg. to colorize the disassembled code
+---
+---`output` must have a `write(self, text, kind)` method where `kind` is a `FormatterTextKind` enum value. These methods
+---are optional and if they're missing, `write()` is called instead:
+---- `write_prefix(self, instruction, text, prefix)` (`prefix` is a `PrefixKind` enum value)
+---- `write_mnemonic(self, instruction, text)`
+---- `write_number(self, instruction, operand, instruction_operand, text, value, number_kind, kind)` (`number_kind` is a `NumberKind` enum value)
+---- `write_decorator(self, instruction, operand, instruction_operand, text, decorator)` (`decorator` is a `DecoratorKind` enum value)
+---- `write_register(self, instruction, operand, instruction_operand, text, register)` (`register` is a `Register` enum value)
+---
+---`instruction_operand` is `nil` if the operand isn't an instruction operand. Errors raised by the methods are re-raised.
+---
+---@param instruction Instruction #Instruction to format
+---@param output table #Output object with a `write()` method and optional `write_prefix()`, `write_mnemonic()`, `write_number()`, `write_decorator()` and `write_register()` methods
+---
+---# Examples
+---
+---```lua
+---local Decoder = require("iced_x86.Decoder")
+---local Formatter = require("iced_x86.Formatter")
+---local FormatterSyntax = require("iced_x86.FormatterSyntax")
+---
+---local instr = Decoder.new(64, "\072\137\092\036\016"):decode()
+---local output = { registers = {} }
+---function output:write(text, kind) end
+---function output:write_register(instruction, operand, instruction_operand, text, register)
+---    table.insert(self.registers, text)
+---end
+---
+---Formatter.new(FormatterSyntax.Masm):format_output(instr, output)
+---assert(output.registers[1] == "rsp" and output.registers[2] == "rbx")
+---```
+function Formatter:format_output(instruction, output) end
+
+---Formats the whole instruction and returns an array of `{ text, kind }` tables, eg. to colorize the disassembled code
+---
+---`kind` is a `FormatterTextKind` enum value.
+---
+---@param instruction Instruction #Instruction to format
+---@return table #Array of `{ text, kind }` tables
+---
+---# Examples
+---
+---```lua
+---local Decoder = require("iced_x86.Decoder")
+---local Formatter = require("iced_x86.Formatter")
+---local FormatterSyntax = require("iced_x86.FormatterSyntax")
+---local FormatterTextKind = require("iced_x86.FormatterTextKind")
+---
+---local instr = Decoder.new(64, "\072\137\092\036\016"):decode()
+---local parts = Formatter.new(FormatterSyntax.Masm):format_to_table(instr)
+---assert(parts[1][1] == "mov" and parts[1][2] == FormatterTextKind.Mnemonic)
+---```
+function Formatter:format_to_table(instruction) end
+
 ---Formats the mnemonic and any prefixes
 ---
 ---@param instruction Instruction #Instruction to format
@@ -97,6 +169,9 @@ function Formatter:format_operand_separator(instruction) end
 ---@return string #The formatted string
 function Formatter:format_all_operands(instruction) end
 
+---Clears the symbol resolver's cache. Call it if the symbols have changed.
+function Formatter:clear_symbol_cache() end
+
 ---Formats a register
 ---
 ---@param register integer #Register (A `Register` enum value)
@@ -17,7 +17,9 @@ check_requirements() {
 
 build() {
 	lua_ver=$("$1" -v 2>&1)
-	if echo "$lua_ver" | grep 'Lua 5\.1\.' > /dev/null; then
+	if echo "$lua_ver" | grep 'LuaJIT 2\.1\.' > /dev/null; then
+		lua_feat=luajit
+	elif echo "$lua_ver" | grep 'Lua 5\.1\.' > /dev/null; then
 		lua_feat=lua5_1
 	elif echo "$lua_ver" | grep 'Lua 5\.2\.' > /dev/null; then
 		lua_feat=lua5_2
@@ -5,10 +5,13 @@ use crate::enum_utils::{
 	to_cc_a, to_cc_ae, to_cc_b, to_cc_be, to_cc_e, to_cc_g, to_cc_ge, to_cc_l, to_cc_le, to_cc_ne, to_cc_np, to_cc_p, to_memory_size_options,
 	to_register,
 };
+use crate::fmt_cb::{FormatterCallbacks, LuaFormatterOptionsProvider, LuaSymbolResolver};
+use crate::fmt_output::{LuaFormatterOutput, TextKindTableOutput};
 use crate::instr::Instruction;
 use loona::lua_api::lua_CFunction;
 use loona::prelude::*;
 use std::ptr;
+use std::rc::Rc;
 
 // GENERATOR-BEGIN: FormatterSyntax
 // ⚠️This was generated by GENERATOR!🦹‍♂️
@@ -34,6 +37,7 @@ lua_impl_userdata! { Formatter }
 struct Formatter {
 	output: String,
 	inner: Box<dyn iced_x86::Formatter>,
+	callbacks: Option<Rc<FormatterCallbacks>>,
 }
 
 impl Formatter {
@@ -47,6 +51,18 @@ impl Formatter {
 		}
 	}
 
+	fn enter(&self, lua: &Lua<'_>) {
+		if let Some(callbacks) = &self.callbacks {
+			callbacks.enter(lua);
+		}
+	}
+
+	unsafe fn leave(&self, lua: &Lua<'_>) {
+		if let Some(callbacks) = &self.callbacks {
+			unsafe { callbacks.leave(lua) };
+		}
+	}
+
 	unsafe fn init_metatable(lua: &Lua<'_>) {
 		unsafe {
 			lua.push("__index");
@@ -77,7 +93,24 @@ impl Formatter {
 lua_pub_methods! { static FORMATTER_EXPORTS =>
 	/// Creates a new x86 formatter that supports GNU Assembler, Intel XED, masm and nasm syntax
 	///
+	/// The optional symbol resolver is called with `(instruction, operand, instruction_operand, address, address_size)` and
+	/// should return `nil` if there's no symbol, a string (the symbol) or a table:
+	/// `{ address = integer?, text = string | { { string, integer }, ... }, kind = integer?, flags = integer?, symbol_size = integer? }`
+	/// where `kind` is a `FormatterTextKind` enum value (default `Label`), `flags` a `SymbolFlags` value and `symbol_size` a `MemorySize`
+	/// enum value. The results are cached, see `Formatter:clear_symbol_cache()`.
+	///
+	/// The optional options provider is called with `(instruction, operand, instruction_operand, options, number_options)` and can
+	/// modify the fields of the two tables:
+	/// - `options`: `branch_size`, `rip_relative_addresses`, `memory_size_options`
+	/// - `number_options`: `prefix`, `suffix`, `digit_separator`, `digit_group_size`, `number_base` (2, 8, 10 or 16), `uppercase_hex`,
+	///   `small_hex_numbers_in_decimal`, `add_leading_zero_to_hex_numbers`, `leading_zeros`, `signed_number`, `displacement_leading_zeros`
+	///
+	/// `instruction_operand` is `nil` if the operand isn't an instruction operand. Errors raised by the functions are re-raised by the
+	/// formatter method that called them.
+	///
 	/// @param syntax integer # Formatter syntax (A `FormatterSyntax` enum value)
+	/// @param symbol_resolver? fun(instruction: Instruction, operand: integer, instruction_operand: integer?, address: integer, address_size: integer): string|table|nil # (default = `nil`) Symbol resolver
+	/// @param options_provider? fun(instruction: Instruction, operand: integer, instruction_operand: integer?, options: table, number_options: table) # (default = `nil`) Options provider
 	/// @return Formatter
 	///
 	/// # Examples
@@ -96,18 +129,31 @@ lua_pub_methods! { static FORMATTER_EXPORTS =>
 	/// assert(disasm == "VCVTNE2PS2BF16 zmm2{k5}{z},zmm6,dword bcst [rax+4]")
 	/// ```
 	unsafe fn new(lua, syntax: u32) -> 1 {
+		let (has_symbol_resolver, has_options_provider) = unsafe { (lua.is_function(2), lua.is_function(3)) };
+		let bad_args = unsafe { (!has_symbol_resolver && !lua.is_none_or_nil(2)) || (!has_options_provider && !lua.is_none_or_nil(3)) };
+		if bad_args || (syntax != FormatterSyntax::Gas as u32 && syntax != FormatterSyntax::Intel as u32 && syntax != FormatterSyntax::Masm as u32 && syntax != FormatterSyntax::Nasm as u32) {
+			let msg = if bad_args { "Expected a function or nil" } else { "Invalid formatter syntax" };
+			unsafe { lua.throw_error(LuaError::MessageStr(msg)) }
+		}
+		let callbacks = if has_symbol_resolver || has_options_provider { Some(unsafe { FormatterCallbacks::new(lua, 2, 3) }) } else { None };
+		let symbol_resolver: Option<Box<dyn iced_x86::SymbolResolver>> = match &callbacks {
+			Some(callbacks) if has_symbol_resolver => Some(Box::new(LuaSymbolResolver::new(callbacks.clone()))),
+			_ => None,
+		};
+		let options_provider: Option<Box<dyn iced_x86::FormatterOptionsProvider>> = match &callbacks {
+			Some(callbacks) if has_options_provider => Some(Box::new(LuaFormatterOptionsProvider::new(callbacks.clone()))),
+			_ => None,
+		};
 		let formatter: Box<dyn iced_x86::Formatter> = if syntax == FormatterSyntax::Gas as u32 {
-			Box::new(iced_x86::GasFormatter::new())
+			Box::new(iced_x86::GasFormatter::with_options(symbol_resolver, options_provider))
 		} else if syntax == FormatterSyntax::Intel as u32 {
-			Box::new(iced_x86::IntelFormatter::new())
+			Box::new(iced_x86::IntelFormatter::with_options(symbol_resolver, options_provider))
 		} else if syntax == FormatterSyntax::Masm as u32 {
-			Box::new(iced_x86::MasmFormatter::new())
-		} else if syntax == FormatterSyntax::Nasm as u32 {
-			Box::new(iced_x86::NasmFormatter::new())
+			Box::new(iced_x86::MasmFormatter::with_options(symbol_resolver, options_provider))
 		} else {
-			unsafe { lua.throw_error(LuaError::MessageStr("Invalid formatter syntax")) }
+			Box::new(iced_x86::NasmFormatter::with_options(symbol_resolver, options_provider))
 		};
-		let formatter = Formatter { output: String::new(), inner: formatter };
+		let formatter = Formatter { output: String::new(), inner: formatter, callbacks };
 		unsafe { let _ = Formatter::push_new(lua, formatter); }
 	}
 
@@ -117,10 +163,76 @@ lua_pub_methods! { static FORMATTER_EXPORTS =>
 	/// @return string # The formatted string
 	unsafe fn format(lua, this: &mut Formatter, instruction: &Instruction) -> 1 {
 		this.output.clear();
+		this.enter(lua);
 		this.inner.format(&instruction.inner, &mut this.output);
+		unsafe { this.leave(lua); }
 		unsafe { lua.push(&this.output); }
 	}
 
+	/// Formats the whole instruction and calls the methods of `output`, eg. to colorize the disassembled code
+	///
+	/// `output` must have a `write(self, text, kind)` method where `kind` is a `FormatterTextKind` enum value. These methods
+	/// are optional and if they're missing, `write()` is called instead:
+	/// - `write_prefix(self, instruction, text, prefix)` (`prefix` is a `PrefixKind` enum value)
+	/// - `write_mnemonic(self, instruction, text)`
+	/// - `write_number(self, instruction, operand, instruction_operand, text, value, number_kind, kind)` (`number_kind` is a `NumberKind` enum value)
+	/// - `write_decorator(self, instruction, operand, instruction_operand, text, decorator)` (`decorator` is a `DecoratorKind` enum value)
+	/// - `write_register(self, instruction, operand, instruction_operand, text, register)` (`register` is a `Register` enum value)
+	///
+	/// `instruction_operand` is `nil` if the operand isn't an instruction operand. Errors raised by the methods are re-raised.
+	///
+	/// @param instruction Instruction # Instruction to format
+	/// @param output table # Output object with a `write()` method and optional `write_prefix()`, `write_mnemonic()`, `write_number()`, `write_decorator()` and `write_register()` methods
+	///
+	/// # Examples
+	/// ```lua
+	/// local Decoder = require("iced_x86.Decoder")
+	/// local Formatter = require("iced_x86.Formatter")
+	/// local FormatterSyntax = require("iced_x86.FormatterSyntax")
+	///
+	/// local instr = Decoder.new(64, "\072\137\092\036\016"):decode()
+	/// local output = { registers = {} }
+	/// function output:write(text, kind) end
+	/// function output:write_register(instruction, operand, instruction_operand, text, register)
+	///     table.insert(self.registers, text)
+	/// end
+	///
+	/// Formatter.new(FormatterSyntax.Masm):format_output(instr, output)
+	/// assert(output.registers[1] == "rsp" and output.registers[2] == "rbx")
+	/// ```
+	unsafe fn format_output(lua, this: &mut Formatter, instruction: &Instruction) -> 0 {
+		let mut output = unsafe { LuaFormatterOutput::new(lua, 3) };
+		this.enter(lua);
+		this.inner.format(&instruction.inner, &mut output);
+		unsafe { this.leave(lua); }
+		unsafe { output.check_error(); }
+	}
+
+	/// Formats the whole instruction and returns an array of `{ text, kind }` tables, eg. to colorize the disassembled code
+	///
+	/// `kind` is a `FormatterTextKind` enum value.
+	///
+	/// @param instruction Instruction # Instruction to format
+	/// @return table # Array of `{ text, kind }` tables
+	///
+	/// # Examples
+	/// ```lua
+	/// local Decoder = require("iced_x86.Decoder")
+	/// local Formatter = require("iced_x86.Formatter")
+	/// local FormatterSyntax = require("iced_x86.FormatterSyntax")
+	/// local FormatterTextKind = require("iced_x86.FormatterTextKind")
+	///
+	/// local instr = Decoder.new(64, "\072\137\092\036\016"):decode()
+	/// local parts = Formatter.new(FormatterSyntax.Masm):format_to_table(instr)
+	/// assert(parts[1][1] == "mov" and parts[1][2] == FormatterTextKind.Mnemonic)
+	/// ```
+	unsafe fn format_to_table(lua, this: &mut Formatter, instruction: &Instruction) -> 1 {
+		let mut output = unsafe { TextKindTableOutput::new(lua) };
+		this.enter(lua);
+		this.inner.format(&instruction.inner, &mut output);
+		unsafe { this.leave(lua); }
+	}
+
 	/// Formats the mnemonic and any prefixes
 	///
 	/// @param instruction Instruction # Instruction to format
@@ -188,7 +300,10 @@ lua_pub_methods! { static FORMATTER_EXPORTS =>
 	/// @return string # The formatted string
 	unsafe fn format_operand(lua, this: &mut Formatter, instruction: &Instruction, operand: u32) -> 1 {
 		this.output.clear();
-		match this.inner.format_operand(&instruction.inner, &mut this.output, operand) {
+		this.enter(lua);
+		let result = this.inner.format_operand(&instruction.inner, &mut this.output, operand);
+		unsafe { this.leave(lua); }
+		match result {
 			Ok(()) => unsafe { lua.push(&this.output) },
 			Err(e) => unsafe { lua.throw_error(e) },
 		}
@@ -210,10 +325,19 @@ lua_pub_methods! { static FORMATTER_EXPORTS =>
 	/// @return string # The formatted string
 	unsafe fn format_all_operands(lua, this: &mut Formatter, instruction: &Instruction) -> 1 {
 		this.output.clear();
+		this.enter(lua);
 		this.inner.format_all_operands(&instruction.inner, &mut this.output);
+		unsafe { this.leave(lua); }
 		unsafe { lua.push(&this.output); }
 	}
 
+	/// Clears the symbol resolver's cache. Call it if the symbols have changed.
+	unsafe fn clear_symbol_cache(lua, this: &Formatter) -> 0 {
+		if let Some(callbacks) = &this.callbacks {
+			callbacks.clear_symbol_cache();
+		}
+	}
+
 	/// Formats a register
 	///
 	/// @param register integer # Register (A `Register` enum value)
@@ -1203,6 +1327,9 @@ lua_methods! {
 	unsafe fn formatter_dtor(lua) -> 0 {
 		unsafe {
 			let fmt: *mut Formatter = lua.get_user_data_mut(1);
+			if let Some(callbacks) = &(*fmt).callbacks {
+				callbacks.free(lua);
+			}
 			ptr::drop_in_place(fmt);
 		}
 	}
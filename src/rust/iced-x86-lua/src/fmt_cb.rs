@@ -0,0 +1,480 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::instr::Instruction;
+use iced_x86::{
+	FormatterOperandOptions, FormatterTextKind, MemorySize, MemorySizeOptions, NumberBase, NumberFormattingOptions, SymResTextInfo, SymResTextPart,
+	SymbolFlags,
+};
+use loona::lua_api::{lua_State, LUA_REGISTRYINDEX};
+use loona::prelude::*;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::ffi::c_void;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::{ptr, str};
+
+const SYMBOL_RESOLVER: &str = "symbol_resolver";
+const OPTIONS_PROVIDER: &str = "options_provider";
+const ERROR: &str = "error";
+
+/// Shared by a `Formatter` and its symbol resolver and options provider.
+///
+/// The Lua functions are stored in a table in the registry. The key is the address of this struct.
+/// The callbacks can only be called while the formatter is formatting, see `enter()` and `leave()`.
+/// Errors raised by the Lua functions are caught by `pcall`, saved in the table and re-raised by `leave()`.
+/// Nothing here may raise a Lua error since that would `longjmp` over the formatter's stack frames.
+pub(crate) struct FormatterCallbacks {
+	lua: Cell<lua_State>,
+	has_error: Cell<bool>,
+	clear_symbol_cache: Cell<bool>,
+}
+
+impl FormatterCallbacks {
+	/// Creates the callbacks and saves the functions at stack indexes `symbol_resolver` and `options_provider` (if they're functions)
+	pub(crate) unsafe fn new(lua: &Lua<'_>, symbol_resolver: i32, options_provider: i32) -> Rc<Self> {
+		let callbacks = Rc::new(Self { lua: Cell::new(ptr::null_mut()), has_error: Cell::new(false), clear_symbol_cache: Cell::new(false) });
+		unsafe {
+			lua.push_light_user_data(callbacks.registry_key());
+			lua.create_table(0, 3);
+			if lua.is_function(symbol_resolver) {
+				lua.push(SYMBOL_RESOLVER);
+				lua.push_value(symbol_resolver);
+				lua.raw_set(-3);
+			}
+			if lua.is_function(options_provider) {
+				lua.push(OPTIONS_PROVIDER);
+				lua.push_value(options_provider);
+				lua.raw_set(-3);
+			}
+			lua.raw_set(LUA_REGISTRYINDEX);
+		}
+		callbacks
+	}
+
+	fn registry_key(&self) -> *const c_void {
+		let p: *const Self = self;
+		p.cast()
+	}
+
+	/// Removes the functions from the registry. Called by the formatter's `__gc` method.
+	pub(crate) unsafe fn free(&self, lua: &Lua<'_>) {
+		unsafe {
+			lua.push_light_user_data(self.registry_key());
+			lua.push_nil();
+			lua.raw_set(LUA_REGISTRYINDEX);
+		}
+	}
+
+	/// Must be called before the formatter formats anything
+	pub(crate) fn enter(&self, lua: &Lua<'_>) {
+		self.lua.set(lua.state());
+	}
+
+	/// Must be called after the formatter has returned. Raises the first error raised by a callback, if any.
+	pub(crate) unsafe fn leave(&self, lua: &Lua<'_>) {
+		self.lua.set(ptr::null_mut());
+		if self.has_error.replace(false) {
+			unsafe {
+				self.push_table(lua);
+				lua.push(ERROR);
+				lua.raw_get(-2);
+				lua.push(ERROR);
+				lua.push_nil();
+				lua.raw_set(-4);
+				lua.remove(-2);
+				lua.error();
+			}
+		}
+	}
+
+	pub(crate) fn clear_symbol_cache(&self) {
+		self.clear_symbol_cache.set(true);
+	}
+
+	unsafe fn push_table(&self, lua: &Lua<'_>) {
+		unsafe {
+			lua.push_light_user_data(self.registry_key());
+			lua.raw_get(LUA_REGISTRYINDEX);
+		}
+	}
+
+	/// Saves the error at the top of the stack (if it's the first error) and pops it
+	unsafe fn set_error(&self, lua: &Lua<'_>) {
+		unsafe {
+			if !self.has_error.replace(true) {
+				self.push_table(lua);
+				lua.push(ERROR);
+				lua.push_value(-3);
+				lua.raw_set(-3);
+				lua.pop(1);
+			}
+			lua.pop(1);
+		}
+	}
+
+	unsafe fn set_error_msg(&self, lua: &Lua<'_>, msg: &str) {
+		unsafe {
+			lua.push(msg);
+			self.set_error(lua);
+		}
+	}
+
+	/// Calls a Lua function with args `(instruction, operand, instruction_operand, <extra_args>)`. The extra args
+	/// are copies of the values at the stack indexes in `extra_args`. If it returns `true`, the single return value
+	/// is at the top of the stack and must be popped by the caller.
+	unsafe fn call(
+		&self, lua: &Lua<'_>, name: &str, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, extra_args: &[i32],
+	) -> bool {
+		unsafe {
+			if !lua.check_stack(extra_args.len() as i32 + 5) {
+				self.has_error.set(true);
+				return false;
+			}
+			self.push_table(lua);
+			lua.push(name);
+			lua.raw_get(-2);
+			lua.remove(-2);
+			let _ = Instruction::init_and_push(lua, &Instruction { inner: *instruction });
+			lua.push(operand);
+			lua.push(instruction_operand);
+			for &index in extra_args {
+				lua.push_value(index);
+			}
+			if lua.pcall(extra_args.len() as i32 + 3, 1, 0) != 0 {
+				self.set_error(lua);
+				false
+			} else {
+				true
+			}
+		}
+	}
+
+	/// Returns the Lua state if the formatter is formatting and no callback has raised an error
+	fn lua_state(&self) -> Option<lua_State> {
+		let state = self.lua.get();
+		if state.is_null() || self.has_error.get() {
+			None
+		} else {
+			Some(state)
+		}
+	}
+}
+
+struct OwnedSymbol {
+	address: u64,
+	parts: Vec<SymResTextPart<'static>>,
+	flags: u32,
+	symbol_size: Option<MemorySize>,
+}
+
+impl OwnedSymbol {
+	fn to_symbol_result(&self) -> iced_x86::SymbolResult<'_> {
+		iced_x86::SymbolResult {
+			address: self.address,
+			text: SymResTextInfo::with_vec(&self.parts),
+			flags: self.flags,
+			symbol_size: self.symbol_size,
+		}
+	}
+}
+
+/// Calls a Lua function to resolve symbols. The results are cached by address and address size.
+pub(crate) struct LuaSymbolResolver {
+	callbacks: Rc<FormatterCallbacks>,
+	cache: HashMap<(u64, u32), Option<OwnedSymbol>>,
+}
+
+impl LuaSymbolResolver {
+	pub(crate) fn new(callbacks: Rc<FormatterCallbacks>) -> Self {
+		Self { callbacks, cache: HashMap::new() }
+	}
+
+	unsafe fn call(
+		&self, lua: &Lua<'_>, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, address: u64, address_size: u32,
+	) -> Result<Option<OwnedSymbol>, &'static str> {
+		unsafe {
+			let top = lua.get_top();
+			lua.push(address);
+			lua.push(address_size);
+			if !self.callbacks.call(lua, SYMBOL_RESOLVER, instruction, operand, instruction_operand, &[top + 1, top + 2]) {
+				lua.set_top(top);
+				return Ok(None);
+			}
+			let result = if lua.is_none_or_nil(-1) {
+				Ok(None)
+			} else if lua.type_(-1) == loona::lua_api::LUA_TSTRING {
+				to_string(lua, -1).map(|text| Some(OwnedSymbol::new_label(address, text)))
+			} else if lua.is_table(-1) {
+				to_owned_symbol(lua, top + 3, address).map(Some)
+			} else {
+				Err("The symbol resolver must return nil, a string or a table")
+			};
+			lua.set_top(top);
+			result
+		}
+	}
+}
+
+impl OwnedSymbol {
+	fn new_label(address: u64, text: String) -> Self {
+		Self { address, parts: vec![SymResTextPart::with_string(text, FormatterTextKind::Label)], flags: SymbolFlags::NONE, symbol_size: None }
+	}
+}
+
+unsafe fn to_string(lua: &Lua<'_>, index: i32) -> Result<String, &'static str> {
+	unsafe {
+		match lua.to_l_string(index).map(str::from_utf8) {
+			Some(Ok(s)) => Ok(s.to_owned()),
+			_ => Err("Expected a UTF-8 string"),
+		}
+	}
+}
+
+/// Pushes `table[name]`. `table` must be an absolute stack index.
+unsafe fn push_field(lua: &Lua<'_>, table: i32, name: &str) {
+	unsafe {
+		lua.push(name);
+		lua.raw_get(table);
+	}
+}
+
+unsafe fn to_formatter_text_kind(lua: &Lua<'_>, index: i32) -> Result<FormatterTextKind, &'static str> {
+	unsafe { lua.try_get_usize(index).ok().and_then(|value| FormatterTextKind::try_from(value).ok()).ok_or("Invalid FormatterTextKind value") }
+}
+
+/// Converts a `{ address = ?, text = ..., kind = ?, flags = ?, symbol_size = ? }` table to a symbol. `table` must be an absolute stack index.
+unsafe fn to_owned_symbol(lua: &Lua<'_>, table: i32, default_address: u64) -> Result<OwnedSymbol, &'static str> {
+	unsafe {
+		let top = lua.get_top();
+		let result = (|| {
+			push_field(lua, table, "address");
+			let address = if lua.is_nil(-1) { default_address } else { lua.try_get_u64(-1).map_err(|_| "`address` must be an integer")? };
+
+			push_field(lua, table, "text");
+			let text = lua.get_top();
+			let parts = if lua.type_(text) == loona::lua_api::LUA_TSTRING {
+				push_field(lua, table, "kind");
+				let kind = if lua.is_nil(-1) { FormatterTextKind::Label } else { to_formatter_text_kind(lua, -1)? };
+				vec![SymResTextPart::with_string(to_string(lua, text)?, kind)]
+			} else if lua.is_table(text) {
+				let mut parts = Vec::new();
+				for i in 1.. {
+					lua.raw_get_i(text, i);
+					if lua.is_nil(-1) {
+						break;
+					}
+					if !lua.is_table(-1) {
+						return Err("`text` must be a string or an array of { text, kind } tables");
+					}
+					let part = lua.get_top();
+					lua.raw_get_i(part, 1);
+					lua.raw_get_i(part, 2);
+					parts.push(SymResTextPart::with_string(to_string(lua, -2)?, to_formatter_text_kind(lua, -1)?));
+					lua.set_top(text);
+				}
+				parts
+			} else {
+				return Err("`text` must be a string or an array of { text, kind } tables");
+			};
+
+			push_field(lua, table, "flags");
+			let flags = if lua.is_nil(-1) { SymbolFlags::NONE } else { lua.try_get_u32(-1).map_err(|_| "`flags` must be a SymbolFlags value")? };
+
+			push_field(lua, table, "symbol_size");
+			let symbol_size = if lua.is_nil(-1) {
+				None
+			} else {
+				Some(lua.try_get_usize(-1).ok().and_then(|value| MemorySize::try_from(value).ok()).ok_or("Invalid MemorySize value")?)
+			};
+
+			Ok(OwnedSymbol { address, parts, flags, symbol_size })
+		})();
+		lua.set_top(top);
+		result
+	}
+}
+
+impl iced_x86::SymbolResolver for LuaSymbolResolver {
+	fn symbol(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, address: u64, address_size: u32,
+	) -> Option<iced_x86::SymbolResult<'_>> {
+		if self.callbacks.clear_symbol_cache.replace(false) {
+			self.cache.clear();
+		}
+		let key = (address, address_size);
+		if !self.cache.contains_key(&key) {
+			let state = self.callbacks.lua_state()?;
+			let lua = unsafe { Lua::new(&state) };
+			match unsafe { self.call(&lua, instruction, operand, instruction_operand, address, address_size) } {
+				Ok(symbol) => {
+					// Don't cache the result if the function raised an error
+					if self.callbacks.has_error.get() {
+						return None;
+					}
+					let _ = self.cache.insert(key, symbol);
+				}
+				Err(msg) => {
+					unsafe { self.callbacks.set_error_msg(&lua, msg) };
+					return None;
+				}
+			}
+		}
+		self.cache.get(&key).and_then(|symbol| symbol.as_ref().map(OwnedSymbol::to_symbol_result))
+	}
+}
+
+/// Calls a Lua function so it can override the options of each operand
+pub(crate) struct LuaFormatterOptionsProvider {
+	callbacks: Rc<FormatterCallbacks>,
+}
+
+impl LuaFormatterOptionsProvider {
+	pub(crate) fn new(callbacks: Rc<FormatterCallbacks>) -> Self {
+		Self { callbacks }
+	}
+
+	unsafe fn call(
+		&self, lua: &Lua<'_>, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>,
+		options: &mut FormatterOperandOptions, number_options: &mut NumberFormattingOptions<'_>,
+	) -> Result<(), &'static str> {
+		unsafe {
+			let top = lua.get_top();
+			let options_table = top + 1;
+			lua.create_table(0, 3);
+			set_field(lua, options_table, "branch_size", options.branch_size());
+			set_field(lua, options_table, "rip_relative_addresses", options.rip_relative_addresses());
+			set_field(lua, options_table, "memory_size_options", options.memory_size_options() as u32);
+
+			let number_options_table = top + 2;
+			lua.create_table(0, 11);
+			set_field(lua, number_options_table, "prefix", number_options.prefix);
+			set_field(lua, number_options_table, "suffix", number_options.suffix);
+			set_field(lua, number_options_table, "digit_separator", number_options.digit_separator);
+			set_field(lua, number_options_table, "digit_group_size", number_options.digit_group_size);
+			set_field(lua, number_options_table, "number_base", number_base_to_u32(number_options.number_base));
+			set_field(lua, number_options_table, "uppercase_hex", number_options.uppercase_hex);
+			set_field(lua, number_options_table, "small_hex_numbers_in_decimal", number_options.small_hex_numbers_in_decimal);
+			set_field(lua, number_options_table, "add_leading_zero_to_hex_numbers", number_options.add_leading_zero_to_hex_numbers);
+			set_field(lua, number_options_table, "leading_zeros", number_options.leading_zeros);
+			set_field(lua, number_options_table, "signed_number", number_options.signed_number);
+			set_field(lua, number_options_table, "displacement_leading_zeros", number_options.displacement_leading_zeros);
+
+			if !self.callbacks.call(lua, OPTIONS_PROVIDER, instruction, operand, instruction_operand, &[options_table, number_options_table]) {
+				lua.set_top(top);
+				return Ok(());
+			}
+			let result = (|| {
+				options.set_branch_size(get_bool(lua, options_table, "branch_size")?);
+				options.set_rip_relative_addresses(get_bool(lua, options_table, "rip_relative_addresses")?);
+				push_field(lua, options_table, "memory_size_options");
+				let memory_size_options =
+					lua.try_get_usize(-1).ok().and_then(|value| MemorySizeOptions::try_from(value).ok()).ok_or("Invalid MemorySizeOptions value")?;
+				options.set_memory_size_options(memory_size_options);
+
+				let prefix = get_string(lua, number_options_table, "prefix")?;
+				if number_options.prefix != prefix {
+					number_options.prefix = intern(&prefix);
+				}
+				let suffix = get_string(lua, number_options_table, "suffix")?;
+				if number_options.suffix != suffix {
+					number_options.suffix = intern(&suffix);
+				}
+				let digit_separator = get_string(lua, number_options_table, "digit_separator")?;
+				if number_options.digit_separator != digit_separator {
+					number_options.digit_separator = intern(&digit_separator);
+				}
+				push_field(lua, number_options_table, "digit_group_size");
+				number_options.digit_group_size = lua.try_get_u8(-1).map_err(|_| "`digit_group_size` must be an integer")?;
+				push_field(lua, number_options_table, "number_base");
+				number_options.number_base = match lua.try_get_u32(-1) {
+					Ok(2) => NumberBase::Binary,
+					Ok(8) => NumberBase::Octal,
+					Ok(10) => NumberBase::Decimal,
+					Ok(16) => NumberBase::Hexadecimal,
+					_ => return Err("Invalid number base"),
+				};
+				number_options.uppercase_hex = get_bool(lua, number_options_table, "uppercase_hex")?;
+				number_options.small_hex_numbers_in_decimal = get_bool(lua, number_options_table, "small_hex_numbers_in_decimal")?;
+				number_options.add_leading_zero_to_hex_numbers = get_bool(lua, number_options_table, "add_leading_zero_to_hex_numbers")?;
+				number_options.leading_zeros = get_bool(lua, number_options_table, "leading_zeros")?;
+				number_options.signed_number = get_bool(lua, number_options_table, "signed_number")?;
+				number_options.displacement_leading_zeros = get_bool(lua, number_options_table, "displacement_leading_zeros")?;
+				Ok(())
+			})();
+			lua.set_top(top);
+			result
+		}
+	}
+}
+
+impl iced_x86::FormatterOptionsProvider for LuaFormatterOptionsProvider {
+	fn operand_options(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, options: &mut FormatterOperandOptions,
+		number_options: &mut NumberFormattingOptions<'_>,
+	) {
+		let state = match self.callbacks.lua_state() {
+			Some(state) => state,
+			None => return,
+		};
+		let lua = unsafe { Lua::new(&state) };
+		if let Err(msg) = unsafe { self.call(&lua, instruction, operand, instruction_operand, options, number_options) } {
+			unsafe { self.callbacks.set_error_msg(&lua, msg) };
+		}
+	}
+}
+
+fn number_base_to_u32(number_base: NumberBase) -> u32 {
+	match number_base {
+		NumberBase::Binary => 2,
+		NumberBase::Octal => 8,
+		NumberBase::Decimal => 10,
+		NumberBase::Hexadecimal => 16,
+	}
+}
+
+/// Sets `table[name] = value`. `table` must be an absolute stack index.
+unsafe fn set_field<T: ToLua>(lua: &Lua<'_>, table: i32, name: &str, value: T) {
+	unsafe {
+		lua.push(name);
+		lua.push(value);
+		lua.raw_set(table);
+	}
+}
+
+unsafe fn get_bool(lua: &Lua<'_>, table: i32, name: &'static str) -> Result<bool, &'static str> {
+	unsafe {
+		push_field(lua, table, name);
+		if lua.is_boolean(-1) {
+			Ok(lua.to_boolean(-1))
+		} else {
+			Err("Expected a boolean option")
+		}
+	}
+}
+
+unsafe fn get_string(lua: &Lua<'_>, table: i32, name: &'static str) -> Result<String, &'static str> {
+	unsafe {
+		push_field(lua, table, name);
+		if lua.type_(-1) == loona::lua_api::LUA_TSTRING {
+			to_string(lua, -1)
+		} else {
+			Err("Expected a string option")
+		}
+	}
+}
+
+// The formatter borrows the strings so they must outlive it. Each unique string is only leaked once.
+fn intern(value: &str) -> &'static str {
+	static STRINGS: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+	let mut strings = STRINGS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+	let strings = strings.get_or_insert_with(HashSet::new);
+	if let Some(&s) = strings.get(value) {
+		s
+	} else {
+		let s: &'static str = Box::leak(value.to_owned().into_boxed_str());
+		let _ = strings.insert(s);
+		s
+	}
+}
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::instr::Instruction;
+use iced_x86::{DecoratorKind, FormatterTextKind, NumberKind, PrefixKind, Register};
+use loona::lua_api::lua_GetIType;
+use loona::prelude::*;
+
+// Offsets of the methods (and the saved error) relative to `LuaFormatterOutput::base`
+const WRITE: i32 = 0;
+const WRITE_PREFIX: i32 = 1;
+const WRITE_MNEMONIC: i32 = 2;
+const WRITE_NUMBER: i32 = 3;
+const WRITE_DECORATOR: i32 = 4;
+const WRITE_REGISTER: i32 = 5;
+const ERROR: i32 = 6;
+
+/// Calls the methods of a Lua table. Only `write()` is required, the other methods are optional and if they're
+/// missing, `write()` is called instead.
+///
+/// The methods are read before formatting and are saved on the stack. Errors raised by the methods are caught
+/// by `pcall`, the first one is saved on the stack and re-raised by `check_error()`. Nothing here may raise a
+/// Lua error since that would `longjmp` over the formatter's stack frames.
+pub(crate) struct LuaFormatterOutput<'a, 'lua> {
+	lua: &'a Lua<'lua>,
+	output: i32,
+	base: i32,
+	has_error: bool,
+}
+
+impl<'a, 'lua> LuaFormatterOutput<'a, 'lua> {
+	/// Reads the methods of the output object at absolute stack index `output` and pushes them on the stack.
+	/// Raises an error if there's no `write()` method.
+	pub(crate) unsafe fn new(lua: &'a Lua<'lua>, output: i32) -> Self {
+		unsafe {
+			if !lua.is_table(output) && !lua.is_user_data(output) {
+				lua.throw_error_msg("Expected a table with a `write()` method");
+			}
+			let base = lua.get_top() + 1;
+			lua.get_field(output, loona::cstr!("write"));
+			if !lua.is_function(-1) {
+				lua.throw_error_msg("`output` must have a `write()` method");
+			}
+			lua.get_field(output, loona::cstr!("write_prefix"));
+			lua.get_field(output, loona::cstr!("write_mnemonic"));
+			lua.get_field(output, loona::cstr!("write_number"));
+			lua.get_field(output, loona::cstr!("write_decorator"));
+			lua.get_field(output, loona::cstr!("write_register"));
+			lua.push_nil();
+			Self { lua, output, base, has_error: false }
+		}
+	}
+
+	/// Raises the first error raised by the output's methods, if any, and pops everything pushed by `new()`
+	pub(crate) unsafe fn check_error(self) {
+		unsafe {
+			if self.has_error {
+				self.lua.push_value(self.base + ERROR);
+				self.lua.error();
+			}
+			self.lua.set_top(self.base - 1);
+		}
+	}
+
+	fn has_method(&self, method: i32) -> bool {
+		unsafe { self.lua.is_function(self.base + method) }
+	}
+
+	/// Calls `output:method(<args>)`. `push_args` pushes the args and returns the number of pushed values.
+	unsafe fn call(&mut self, method: i32, push_args: impl FnOnce(&Lua<'lua>) -> i32) {
+		if self.has_error {
+			return;
+		}
+		unsafe {
+			let lua = self.lua;
+			if !lua.check_stack(10) {
+				lua.push("Stack overflow");
+				self.set_error();
+				return;
+			}
+			lua.push_value(self.base + method);
+			lua.push_value(self.output);
+			let nargs = push_args(lua);
+			if lua.pcall(nargs + 1, 0, 0) != 0 {
+				self.set_error();
+			}
+		}
+	}
+
+	/// Saves the error at the top of the stack and pops it
+	unsafe fn set_error(&mut self) {
+		self.has_error = true;
+		unsafe { self.lua.replace(self.base + ERROR) };
+	}
+
+	unsafe fn write_default(&mut self, text: &str, kind: FormatterTextKind) {
+		unsafe {
+			self.call(WRITE, |lua| {
+				lua.push(text);
+				lua.push(kind as u32);
+				2
+			});
+		}
+	}
+
+	/// Pushes `(instruction, operand, instruction_operand, text)`
+	unsafe fn push_operand_args(lua: &Lua<'lua>, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str) {
+		unsafe {
+			let _ = Instruction::init_and_push(lua, &Instruction { inner: *instruction });
+			lua.push(operand);
+			lua.push(instruction_operand);
+			lua.push(text);
+		}
+	}
+}
+
+impl iced_x86::FormatterOutput for LuaFormatterOutput<'_, '_> {
+	fn write(&mut self, text: &str, kind: FormatterTextKind) {
+		unsafe { self.write_default(text, kind) };
+	}
+
+	fn write_prefix(&mut self, instruction: &iced_x86::Instruction, text: &str, prefix: PrefixKind) {
+		if !self.has_method(WRITE_PREFIX) {
+			unsafe { self.write_default(text, FormatterTextKind::Prefix) };
+			return;
+		}
+		unsafe {
+			self.call(WRITE_PREFIX, |lua| {
+				let _ = Instruction::init_and_push(lua, &Instruction { inner: *instruction });
+				lua.push(text);
+				lua.push(prefix as u32);
+				3
+			});
+		}
+	}
+
+	fn write_mnemonic(&mut self, instruction: &iced_x86::Instruction, text: &str) {
+		if !self.has_method(WRITE_MNEMONIC) {
+			unsafe { self.write_default(text, FormatterTextKind::Mnemonic) };
+			return;
+		}
+		unsafe {
+			self.call(WRITE_MNEMONIC, |lua| {
+				let _ = Instruction::init_and_push(lua, &Instruction { inner: *instruction });
+				lua.push(text);
+				2
+			});
+		}
+	}
+
+	fn write_number(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, value: u64,
+		number_kind: NumberKind, kind: FormatterTextKind,
+	) {
+		if !self.has_method(WRITE_NUMBER) {
+			unsafe { self.write_default(text, kind) };
+			return;
+		}
+		unsafe {
+			self.call(WRITE_NUMBER, |lua| {
+				Self::push_operand_args(lua, instruction, operand, instruction_operand, text);
+				lua.push(value);
+				lua.push(number_kind as u32);
+				lua.push(kind as u32);
+				7
+			});
+		}
+	}
+
+	fn write_decorator(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, decorator: DecoratorKind,
+	) {
+		if !self.has_method(WRITE_DECORATOR) {
+			unsafe { self.write_default(text, FormatterTextKind::Decorator) };
+			return;
+		}
+		unsafe {
+			self.call(WRITE_DECORATOR, |lua| {
+				Self::push_operand_args(lua, instruction, operand, instruction_operand, text);
+				lua.push(decorator as u32);
+				5
+			});
+		}
+	}
+
+	fn write_register(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, register: Register,
+	) {
+		if !self.has_method(WRITE_REGISTER) {
+			unsafe { self.write_default(text, FormatterTextKind::Register) };
+			return;
+		}
+		unsafe {
+			self.call(WRITE_REGISTER, |lua| {
+				Self::push_operand_args(lua, instruction, operand, instruction_operand, text);
+				lua.push(register as u32);
+				5
+			});
+		}
+	}
+}
+
+/// Saves all text and its kind in an array of `{ text, kind }` tables
+pub(crate) struct TextKindTableOutput<'a, 'lua> {
+	lua: &'a Lua<'lua>,
+	table: i32,
+	len: lua_GetIType,
+}
+
+impl<'a, 'lua> TextKindTableOutput<'a, 'lua> {
+	/// Pushes a new empty table. It's the result and is left on the stack.
+	pub(crate) unsafe fn new(lua: &'a Lua<'lua>) -> Self {
+		unsafe {
+			lua.new_table();
+			Self { lua, table: lua.get_top(), len: 0 }
+		}
+	}
+}
+
+impl iced_x86::FormatterOutput for TextKindTableOutput<'_, '_> {
+	fn write(&mut self, text: &str, kind: FormatterTextKind) {
+		unsafe {
+			let lua = self.lua;
+			lua.create_table(2, 0);
+			lua.push(text);
+			lua.raw_set_i(-2, 1);
+			lua.push(kind as u32);
+			lua.raw_set_i(-2, 2);
+			self.len += 1;
+			lua.raw_set_i(self.table, self.len);
+		}
+	}
+}
@@ -74,6 +74,8 @@ mod dec;
 mod enc;
 mod enum_utils;
 mod fmt;
+mod fmt_cb;
+mod fmt_output;
 mod fpui;
 mod grp_idx;
 mod info;
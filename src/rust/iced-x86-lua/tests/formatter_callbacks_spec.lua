@@ -0,0 +1,134 @@
+-- SPDX-License-Identifier: MIT
+-- Copyright (C) 2018-present iced project and contributors
+
+describe("Formatter callbacks", function()
+	local Code = require("iced_x86.Code")
+	local Decoder = require("iced_x86.Decoder")
+	local Formatter = require("iced_x86.Formatter")
+	local FormatterSyntax = require("iced_x86.FormatterSyntax")
+	local FormatterTextKind = require("iced_x86.FormatterTextKind")
+	local MemorySize = require("iced_x86.MemorySize")
+	local MemorySizeOptions = require("iced_x86.MemorySizeOptions")
+	local SymbolFlags = require("iced_x86.SymbolFlags")
+
+	it("invalid args", function()
+		assert.has_error(function()
+			Formatter.new(FormatterSyntax.Masm, 123)
+		end)
+		assert.has_error(function()
+			Formatter.new(FormatterSyntax.Masm, nil, "abc")
+		end)
+	end)
+
+	it("symbol resolver returns a string", function()
+		local function resolver(instruction, operand, instruction_operand, address, address_size)
+			assert.equals(Code.Call_rel32_64, instruction:code())
+			assert.equals(0, operand)
+			assert.equals(0, instruction_operand)
+			assert.equals(8, address_size)
+			if address == 0x5AA55AA5 then
+				return "printf"
+			end
+			return nil
+		end
+
+		local decoder = Decoder.new(64, "\232\000\000\000\000\232\000\000\000\000", nil, 0x5AA55AA0)
+		local formatter = Formatter.new(FormatterSyntax.Masm, resolver)
+		assert.equals("call printf", formatter:format(decoder:decode()))
+		assert.equals("call 000000005AA55AAAh", formatter:format(decoder:decode()))
+	end)
+
+	it("symbol resolver returns a table", function()
+		local function resolver(instruction, operand, instruction_operand, address, address_size)
+			if address == 0x1234 then
+				return { text = "abc", kind = FormatterTextKind.Data, symbol_size = MemorySize.UInt32 }
+			end
+			return {
+				address = 0x5AA55AA0,
+				text = { { "my", FormatterTextKind.Function }, { "_func", FormatterTextKind.Data } },
+				flags = SymbolFlags.None,
+			}
+		end
+
+		local decoder = Decoder.new(64, "\232\000\000\000\000\139\004\037\052\018\000\000", nil, 0x5AA55AA0)
+		local formatter = Formatter.new(FormatterSyntax.Masm, resolver)
+		assert.equals("call my_func+5", formatter:format(decoder:decode()))
+		assert.equals("mov eax,[abc]", formatter:format(decoder:decode()))
+	end)
+
+	it("symbol resolver results are cached", function()
+		local calls = 0
+		local function resolver()
+			calls = calls + 1
+			return "sym"
+		end
+
+		local instr = Decoder.new(64, "\232\000\000\000\000", nil, 0x5AA55AA0):decode()
+		local formatter = Formatter.new(FormatterSyntax.Nasm, resolver)
+		assert.equals("call sym", formatter:format(instr))
+		assert.equals("call sym", formatter:format(instr))
+		assert.equals(1, calls)
+		formatter:clear_symbol_cache()
+		assert.equals("call sym", formatter:format(instr))
+		assert.equals(2, calls)
+	end)
+
+	it("symbol resolver raises an error", function()
+		local instr = Decoder.new(64, "\232\000\000\000\000"):decode()
+		local formatter = Formatter.new(FormatterSyntax.Nasm, function()
+			error("abc")
+		end)
+		local invalid_formatter = Formatter.new(FormatterSyntax.Nasm, function()
+			return 123
+		end)
+
+		assert.has_error(function()
+			formatter:format(instr)
+		end)
+		assert.has_error(function()
+			formatter:format_all_operands(instr)
+		end)
+		assert.has_error(function()
+			invalid_formatter:format(instr)
+		end)
+	end)
+
+	it("options provider", function()
+		local function provider(instruction, operand, instruction_operand, options, number_options)
+			assert.equals(MemorySizeOptions.Default, options.memory_size_options)
+			assert.equals(16, number_options.number_base)
+			if operand == 0 then
+				options.memory_size_options = MemorySizeOptions.Always
+			elseif operand == 1 then
+				number_options.prefix = "$"
+				number_options.suffix = ""
+				number_options.digit_separator = "_"
+				number_options.digit_group_size = 2
+			end
+		end
+
+		local instr = Decoder.new(64, "\129\000\120\086\052\018"):decode()
+		local formatter = Formatter.new(FormatterSyntax.Masm, nil, provider)
+		assert.equals("add dword ptr [rax],$12_34_56_78", formatter:format(instr))
+	end)
+
+	it("options provider raises an error", function()
+		local instr = Decoder.new(64, "\072\131\192\016"):decode()
+		local formatter = Formatter.new(FormatterSyntax.Masm, nil, function()
+			error("abc")
+		end)
+		local invalid_formatter = Formatter.new(FormatterSyntax.Masm, nil, function(_, _, _, _, number_options)
+			number_options.number_base = 3
+		end)
+
+		assert.has_error(function()
+			formatter:format(instr)
+		end)
+		assert.has_error(function()
+			formatter:format_operand(instr, 1)
+		end)
+		assert.has_error(function()
+			invalid_formatter:format(instr)
+		end)
+	end)
+end)
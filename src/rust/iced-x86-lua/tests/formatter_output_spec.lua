@@ -0,0 +1,122 @@
+-- SPDX-License-Identifier: MIT
+-- Copyright (C) 2018-present iced project and contributors
+
+describe("Formatter output", function()
+	local DecoratorKind = require("iced_x86.DecoratorKind")
+	local Decoder = require("iced_x86.Decoder")
+	local Formatter = require("iced_x86.Formatter")
+	local FormatterSyntax = require("iced_x86.FormatterSyntax")
+	local FormatterTextKind = require("iced_x86.FormatterTextKind")
+	local NumberKind = require("iced_x86.NumberKind")
+	local PrefixKind = require("iced_x86.PrefixKind")
+	local Register = require("iced_x86.Register")
+
+	local function new_output(all_methods)
+		local output = { parts = {}, calls = {} }
+		function output:write(text, kind)
+			table.insert(self.parts, { text, kind })
+		end
+		if all_methods then
+			function output:write_prefix(instruction, text, prefix)
+				table.insert(self.calls, { "prefix", text, prefix })
+			end
+			function output:write_mnemonic(instruction, text)
+				table.insert(self.calls, { "mnemonic", text })
+			end
+			function output:write_number(instruction, operand, instruction_operand, text, value, number_kind, kind)
+				table.insert(self.calls, { "number", operand, instruction_operand, text, value, number_kind, kind })
+			end
+			function output:write_decorator(instruction, operand, instruction_operand, text, decorator)
+				table.insert(self.calls, { "decorator", operand, instruction_operand, text, decorator })
+			end
+			function output:write_register(instruction, operand, instruction_operand, text, register)
+				table.insert(self.calls, { "register", operand, instruction_operand, text, register })
+			end
+		end
+		return output
+	end
+
+	it("format to table", function()
+		local instr = Decoder.new(64, "\240\072\131\000\016"):decode()
+		local formatter = Formatter.new(FormatterSyntax.Masm)
+		local parts = formatter:format_to_table(instr)
+		local text = ""
+		for _, part in ipairs(parts) do
+			text = text .. part[1]
+		end
+		assert.equals(formatter:format(instr), text)
+		assert.are.same({
+			{ "lock", FormatterTextKind.Prefix },
+			{ " ", FormatterTextKind.Text },
+			{ "add", FormatterTextKind.Mnemonic },
+			{ " ", FormatterTextKind.Text },
+			{ "qword", FormatterTextKind.Keyword },
+			{ " ", FormatterTextKind.Text },
+			{ "ptr", FormatterTextKind.Keyword },
+			{ " ", FormatterTextKind.Text },
+			{ "[", FormatterTextKind.Punctuation },
+			{ "rax", FormatterTextKind.Register },
+			{ "]", FormatterTextKind.Punctuation },
+			{ ",", FormatterTextKind.Punctuation },
+			{ "10h", FormatterTextKind.Number },
+		}, parts)
+	end)
+
+	it("format output with only a write() method", function()
+		local instr = Decoder.new(64, "\240\072\131\000\016"):decode()
+		local formatter = Formatter.new(FormatterSyntax.Masm)
+		local output = new_output(false)
+		formatter:format_output(instr, output)
+		assert.are.same(formatter:format_to_table(instr), output.parts)
+	end)
+
+	it("format output with all methods", function()
+		local instr = Decoder.new(64, "\240\072\131\000\016"):decode()
+		local output = new_output(true)
+		Formatter.new(FormatterSyntax.Masm):format_output(instr, output)
+		assert.are.same({
+			{ "prefix", "lock", PrefixKind.Lock },
+			{ "mnemonic", "add" },
+			{ "register", 0, 0, "rax", Register.RAX },
+			{ "number", 1, 1, "10h", 0x10, NumberKind.UInt64, FormatterTextKind.Number },
+		}, output.calls)
+		for _, part in ipairs(output.parts) do
+			assert.are_not.equals(FormatterTextKind.Prefix, part[2])
+		end
+	end)
+
+	it("format output decorators", function()
+		local instr = Decoder.new(64, "\098\241\124\155\088\194"):decode()
+		local output = new_output(true)
+		Formatter.new(FormatterSyntax.Masm):format_output(instr, output)
+		local function has_call(expected)
+			for _, call in ipairs(output.calls) do
+				if call[1] == expected[1] and call[2] == expected[2] and call[3] == expected[3] and call[4] == expected[4] and call[5] == expected[5] then
+					return true
+				end
+			end
+			return false
+		end
+		assert.is_true(has_call({ "decorator", 0, 0, "z", DecoratorKind.ZeroingMasking }))
+		assert.is_true(has_call({ "decorator", 2, 2, "rn-sae", DecoratorKind.RoundingControl }))
+	end)
+
+	it("format output raises an error", function()
+		local instr = Decoder.new(64, "\072\131\192\016"):decode()
+		local formatter = Formatter.new(FormatterSyntax.Masm)
+		local output = {}
+		function output:write(text, kind)
+			error("abc")
+		end
+
+		assert.has_error(function()
+			formatter:format_output(instr, output)
+		end)
+		assert.has_error(function()
+			formatter:format_output(instr, {})
+		end)
+		assert.has_error(function()
+			formatter:format_output(instr, 123)
+		end)
+	end)
+end)
@@ -0,0 +1,572 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::Error;
+use crate::parser::{Instr, Memory, Operand, OperandKind, Prefix, Program, Statement};
+use crate::tables::Tables;
+use iced_x86::{
+	BlockEncoder, BlockEncoderOptions, Code, Encoder, Instruction, InstructionBlock, OpCodeInfo, OpCodeOperandKind, OpKind, Register, RoundingControl,
+};
+use proc_macro::Span;
+use std::collections::HashMap;
+
+/// Creates [`Instruction`]s from parsed instructions using the [`OpCodeInfo`] operand kinds
+///
+/// [`Instruction`]: https://docs.rs/iced-x86/1.21.0/iced_x86/struct.Instruction.html
+/// [`OpCodeInfo`]: https://docs.rs/iced-x86/1.21.0/iced_x86/struct.OpCodeInfo.html
+pub(crate) struct Matcher<'a> {
+	tables: &'a Tables,
+	bitness: u32,
+	/// Closures can jump to absolute addresses, a `[u8; N]` is position independent and can't
+	allow_absolute_branches: bool,
+}
+
+impl<'a> Matcher<'a> {
+	pub(crate) fn new(tables: &'a Tables, bitness: u32, allow_absolute_branches: bool) -> Self {
+		Self { tables, bitness, allow_absolute_branches }
+	}
+
+	/// Creates the instruction with the shortest encoding. Labels are branch targets or RIP-relative
+	/// addresses, `label_target` returns their address.
+	pub(crate) fn create_instruction(&self, instr: &Instr, label_target: &dyn Fn(&str) -> u64) -> Result<Instruction, Error> {
+		let mnemonic = match self.tables.mnemonic(&instr.mnemonic.name) {
+			Some(mnemonic) => mnemonic,
+			None => return Err(Error::new(instr.mnemonic.span, format!("Unknown mnemonic `{}`", instr.mnemonic.name))),
+		};
+		let codes: Vec<Code> = self.tables.codes(mnemonic).iter().copied().filter(|&code| self.is_valid_mode(code.op_code())).collect();
+		if codes.is_empty() {
+			return Err(instr.error(format!("`{}` can't be used in {}-bit code", instr.mnemonic.name, self.bitness)));
+		}
+		if instr.operands.iter().filter(|operand| operand.op_mask != Register::None).count() > 1 {
+			return Err(instr.error("Only one opmask register can be used".into()));
+		}
+
+		let mut matched = Vec::new();
+		for &code in &codes {
+			if let Some(instruction) = self.match_operands(code, instr, label_target) {
+				matched.push(instruction);
+			}
+		}
+		if matched.is_empty() {
+			return Err(instr.error(format!("Invalid operands for `{}`", instr.mnemonic.name)));
+		}
+		matched.retain(|instruction| Self::is_valid_state(instruction.code().op_code(), instr));
+		if matched.is_empty() {
+			return Err(instr.error(format!(
+				"A prefix, opmask, `{{z}}`, broadcast or rounding operand can't be used with this `{}` instruction",
+				instr.mnemonic.name
+			)));
+		}
+
+		let mut encoder = Encoder::new(self.bitness);
+		let mut encoded = Vec::with_capacity(matched.len());
+		let mut first_error = None;
+		for instruction in matched {
+			match encoder.encode(&instruction, 0) {
+				Ok(len) => encoded.push((instruction, len)),
+				Err(error) => {
+					if first_error.is_none() {
+						first_error = Some(error);
+					}
+				}
+			}
+		}
+		if encoded.is_empty() {
+			let error = first_error.map_or_else(String::new, |error| error.to_string());
+			return Err(instr.error(format!("Can't encode `{}`: {}", instr.mnemonic.name, error)));
+		}
+
+		let memory = instr.operands.iter().find_map(|operand| match &operand.kind {
+			OperandKind::Memory(memory) => Some(memory),
+			_ => None,
+		});
+		if let Some(memory) = memory {
+			if memory.size.is_none() {
+				let size = memory_size(encoded[0].0.code().op_code(), false);
+				if encoded.iter().any(|(instruction, _)| memory_size(instruction.code().op_code(), false) != size) {
+					return Err(instr.error("The memory operand size is ambiguous, add a size hint, eg. `dword ptr [rax]`".into()));
+				}
+			}
+		}
+
+		// Branches to labels use the longest form, the block encoder will use the short form if possible
+		if instr.operands.iter().any(|operand| matches!(operand.kind, OperandKind::Label(_))) {
+			let max_size = encoded.iter().map(|(instruction, _)| branch_size(instruction.code().op_code())).max().unwrap_or_default();
+			encoded.retain(|(instruction, _)| branch_size(instruction.code().op_code()) == max_size);
+		}
+
+		// If no operand selects the operand size (eg. `push 1`), don't use a form with an operand size prefix
+		if encoded.iter().any(|(instruction, _)| !self.needs_operand_size_prefix(instruction.code().op_code())) {
+			encoded.retain(|(instruction, _)| !self.needs_operand_size_prefix(instruction.code().op_code()));
+		}
+
+		let mut best: Option<(Instruction, usize)> = None;
+		for (instruction, len) in encoded {
+			if best.as_ref().map_or(true, |&(_, best_len)| len < best_len) {
+				best = Some((instruction, len));
+			}
+		}
+		match best {
+			Some((instruction, _)) => Ok(instruction),
+			None => Err(instr.error(format!("Can't encode `{}`", instr.mnemonic.name))),
+		}
+	}
+
+	fn needs_operand_size_prefix(&self, op_code: &OpCodeInfo) -> bool {
+		match op_code.operand_size() {
+			16 => self.bitness != 16,
+			32 => self.bitness == 16,
+			_ => false,
+		}
+	}
+
+	fn is_valid_mode(&self, op_code: &OpCodeInfo) -> bool {
+		match self.bitness {
+			16 => op_code.mode16(),
+			32 => op_code.mode32(),
+			_ => op_code.mode64(),
+		}
+	}
+
+	fn match_operands(&self, code: Code, instr: &Instr, label_target: &dyn Fn(&str) -> u64) -> Option<Instruction> {
+		let op_kinds = code.op_code().op_kinds();
+		// String instructions have implicit operands, eg. `lodsb` == `lodsb al, byte ptr [rsi]`
+		let has_implicit_operands = op_kinds.iter().copied().any(is_string_operand);
+		let is_implicit = |op_kind: OpCodeOperandKind| has_implicit_operands && (is_string_operand(op_kind) || is_string_register(op_kind));
+		if op_kinds.iter().filter(|&&op_kind| !is_implicit(op_kind)).count() != instr.operands.len() {
+			return None;
+		}
+
+		let mut instruction = Instruction::default();
+		instruction.set_code(code);
+		let mut operands = instr.operands.iter();
+		for (i, &op_kind) in op_kinds.iter().enumerate() {
+			let i = i as u32;
+			if is_implicit(op_kind) {
+				self.set_implicit_operand(&mut instruction, i, op_kind);
+			} else if !self.set_operand(&mut instruction, i, op_kind, operands.next()?, label_target) {
+				return None;
+			}
+		}
+		Some(instruction)
+	}
+
+	fn set_implicit_operand(&self, instruction: &mut Instruction, operand: u32, op_kind: OpCodeOperandKind) {
+		let op_kinds = match op_kind {
+			OpCodeOperandKind::seg_rSI => [OpKind::MemorySegSI, OpKind::MemorySegESI, OpKind::MemorySegRSI],
+			OpCodeOperandKind::es_rDI => [OpKind::MemoryESDI, OpKind::MemoryESEDI, OpKind::MemoryESRDI],
+			OpCodeOperandKind::seg_rDI => [OpKind::MemorySegDI, OpKind::MemorySegEDI, OpKind::MemorySegRDI],
+			OpCodeOperandKind::seg_rBX_al => {
+				instruction.set_op_kind(operand, OpKind::Memory);
+				instruction.set_memory_base(match self.bitness {
+					16 => Register::BX,
+					32 => Register::EBX,
+					_ => Register::RBX,
+				});
+				instruction.set_memory_index(Register::AL);
+				instruction.set_memory_index_scale(1);
+				return;
+			}
+			_ => {
+				instruction.set_op_kind(operand, OpKind::Register);
+				instruction.set_op_register(operand, fixed_register(op_kind));
+				return;
+			}
+		};
+		instruction.set_op_kind(
+			operand,
+			match self.bitness {
+				16 => op_kinds[0],
+				32 => op_kinds[1],
+				_ => op_kinds[2],
+			},
+		);
+	}
+
+	fn set_operand(
+		&self, instruction: &mut Instruction, operand: u32, op_kind: OpCodeOperandKind, value: &Operand, label_target: &dyn Fn(&str) -> u64,
+	) -> bool {
+		match &value.kind {
+			&OperandKind::Register(register) => {
+				if !is_register_operand(op_kind, register) {
+					return false;
+				}
+				instruction.set_op_kind(operand, OpKind::Register);
+				instruction.set_op_register(operand, register);
+			}
+			OperandKind::Memory(memory) => {
+				if !is_memory_operand(op_kind, memory) {
+					return false;
+				}
+				instruction.set_op_kind(operand, OpKind::Memory);
+				self.set_memory(instruction, memory, label_target);
+			}
+			&OperandKind::Immediate(value) => {
+				if is_branch_operand(op_kind) {
+					if !self.allow_absolute_branches || value < 0 || value > u64::MAX as i128 {
+						return false;
+					}
+					return self.set_branch_target(instruction, operand, op_kind, value as u64);
+				}
+				return set_immediate(instruction, operand, op_kind, value);
+			}
+			OperandKind::Label(name) => {
+				if !is_branch_operand(op_kind) {
+					return false;
+				}
+				return self.set_branch_target(instruction, operand, op_kind, label_target(&name.name));
+			}
+			OperandKind::Runtime(_) => return false,
+		}
+		true
+	}
+
+	fn set_memory(&self, instruction: &mut Instruction, memory: &Memory, label_target: &dyn Fn(&str) -> u64) {
+		instruction.set_memory_base(memory.base);
+		instruction.set_memory_index(memory.index);
+		instruction.set_memory_index_scale(memory.scale);
+		instruction.set_segment_prefix(memory.segment);
+		if let Some(label) = &memory.label {
+			instruction.set_memory_displacement64(label_target(&label.name));
+			instruction.set_memory_displ_size(1);
+		} else {
+			instruction.set_memory_displacement64(memory.displacement as u64);
+			// Same as `code_asm`: displacement-only operands use the largest displacement
+			let displ_size =
+				if memory.base == Register::None && memory.index == Register::None { self.bitness / 8 } else { (memory.displacement != 0) as u32 };
+			instruction.set_memory_displ_size(displ_size);
+		}
+		if let Some(size) = memory.size {
+			instruction.set_is_broadcast(size.is_broadcast);
+		}
+	}
+
+	fn set_branch_target(&self, instruction: &mut Instruction, operand: u32, op_kind: OpCodeOperandKind, target: u64) -> bool {
+		let size = match op_kind {
+			OpCodeOperandKind::br16_1 | OpCodeOperandKind::br16_2 => 16,
+			OpCodeOperandKind::br32_1 | OpCodeOperandKind::br32_4 => 32,
+			OpCodeOperandKind::br64_1 | OpCodeOperandKind::br64_4 => 64,
+			_ => self.bitness,
+		};
+		// Eg. `jne rel16` can be used in 64-bit code but it truncates the target address
+		if size != self.bitness {
+			return false;
+		}
+		match size {
+			16 if target <= u16::MAX as u64 => {
+				instruction.set_op_kind(operand, OpKind::NearBranch16);
+				instruction.set_near_branch16(target as u16);
+			}
+			32 if target <= u32::MAX as u64 => {
+				instruction.set_op_kind(operand, OpKind::NearBranch32);
+				instruction.set_near_branch32(target as u32);
+			}
+			64 => {
+				instruction.set_op_kind(operand, OpKind::NearBranch64);
+				instruction.set_near_branch64(target);
+			}
+			_ => return false,
+		}
+		true
+	}
+
+	fn is_valid_state(op_code: &OpCodeInfo, instr: &Instr) -> bool {
+		for &prefix in &instr.prefixes {
+			let is_valid = match prefix {
+				Prefix::Lock => op_code.can_use_lock_prefix(),
+				Prefix::Rep | Prefix::Repe => op_code.can_use_rep_prefix(),
+				Prefix::Repne => op_code.can_use_repne_prefix(),
+				Prefix::Xacquire => op_code.can_use_xacquire_prefix(),
+				Prefix::Xrelease => op_code.can_use_xrelease_prefix(),
+				Prefix::Notrack => op_code.can_use_notrack_prefix(),
+			};
+			if !is_valid {
+				return false;
+			}
+		}
+		for operand in &instr.operands {
+			if operand.op_mask != Register::None && !op_code.can_use_op_mask_register() {
+				return false;
+			}
+			if operand.zeroing_masking && !op_code.can_use_zeroing_masking() {
+				return false;
+			}
+			if let OperandKind::Memory(memory) = &operand.kind {
+				if let Some(size) = memory.size {
+					if size.is_broadcast && !op_code.can_broadcast() {
+						return false;
+					}
+					let code_size = memory_size(op_code, size.is_broadcast);
+					if code_size != 0 && code_size != size.size {
+						return false;
+					}
+				}
+			}
+		}
+		if op_code.require_op_mask_register() && instr.operands.iter().all(|operand| operand.op_mask == Register::None) {
+			return false;
+		}
+		match instr.rounding {
+			Some(rounding) if rounding.rounding_control() == RoundingControl::None => op_code.can_suppress_all_exceptions(),
+			Some(_) => op_code.can_use_rounding_control(),
+			None => true,
+		}
+	}
+}
+
+/// Sets the prefixes and decorators that aren't part of the operands
+pub(crate) fn set_state(instruction: &mut Instruction, instr: &Instr) {
+	for &prefix in &instr.prefixes {
+		match prefix {
+			Prefix::Lock => instruction.set_has_lock_prefix(true),
+			Prefix::Rep | Prefix::Repe => instruction.set_has_repe_prefix(true),
+			Prefix::Repne => instruction.set_has_repne_prefix(true),
+			Prefix::Xacquire => instruction.set_has_xacquire_prefix(true),
+			Prefix::Xrelease => instruction.set_has_xrelease_prefix(true),
+			Prefix::Notrack => instruction.set_segment_prefix(Register::DS),
+		}
+	}
+	for operand in &instr.operands {
+		if operand.op_mask != Register::None {
+			instruction.set_op_mask(operand.op_mask);
+		}
+		if operand.zeroing_masking {
+			instruction.set_zeroing_masking(true);
+		}
+	}
+	match instr.rounding {
+		Some(rounding) if rounding.rounding_control() == RoundingControl::None => instruction.set_suppress_all_exceptions(true),
+		Some(rounding) => instruction.set_rounding_control(rounding.rounding_control()),
+		None => {}
+	}
+}
+
+/// Assembles code without runtime operands. All branches and label references are relative so the code
+/// can be copied to any address.
+pub(crate) fn assemble(program: &Program, tables: &Tables) -> Result<Vec<u8>, Error> {
+	// Labels before the same instruction get the same id (the instruction's IP)
+	let mut labels: HashMap<&str, u64> = HashMap::new();
+	let mut next_id = 1;
+	let mut current_id = None;
+	for statement in &program.statements {
+		match statement {
+			Statement::Label(name) => {
+				let id = *current_id.get_or_insert_with(|| {
+					next_id += 1;
+					next_id - 1
+				});
+				if labels.insert(&name.name, id).is_some() {
+					return Err(Error::new(name.span, format!("Duplicate label `{}`", name.name)));
+				}
+			}
+			Statement::Instruction(_) => current_id = None,
+		}
+	}
+	for statement in &program.statements {
+		if let Statement::Instruction(instr) = statement {
+			for operand in &instr.operands {
+				let label = match &operand.kind {
+					OperandKind::Label(name) => name,
+					OperandKind::Memory(Memory { label: Some(name), .. }) => name,
+					_ => continue,
+				};
+				if !labels.contains_key(label.name.as_str()) {
+					return Err(Error::new(label.span, format!("Undefined label `{}`", label.name)));
+				}
+			}
+		}
+	}
+
+	let matcher = Matcher::new(tables, program.bitness, false);
+	let label_target = |name: &str| labels.get(name).copied().unwrap_or_default();
+	let mut instructions = Vec::new();
+	let mut current_id = None;
+	for statement in &program.statements {
+		match statement {
+			Statement::Label(name) => current_id = Some(label_target(&name.name)),
+			Statement::Instruction(instr) => {
+				let mut instruction = matcher.create_instruction(instr, &label_target)?;
+				set_state(&mut instruction, instr);
+				if let Some(id) = current_id.take() {
+					instruction.set_ip(id);
+				}
+				instructions.push(instruction);
+			}
+		}
+	}
+	if let Some(id) = current_id {
+		let mut instruction = Instruction::with(Code::Zero_bytes);
+		instruction.set_ip(id);
+		instructions.push(instruction);
+	}
+
+	match BlockEncoder::encode(program.bitness, InstructionBlock::new(&instructions, 0), BlockEncoderOptions::NONE) {
+		Ok(result) => Ok(result.code_buffer),
+		Err(error) => Err(Error::new(Span::call_site(), format!("Couldn't encode the instructions: {}", error))),
+	}
+}
+
+fn memory_size(op_code: &OpCodeInfo, is_broadcast: bool) -> usize {
+	if is_broadcast {
+		op_code.broadcast_memory_size().size()
+	} else {
+		op_code.memory_size().size()
+	}
+}
+
+/// 0 = not a branch, 1 = short branch, 2 = near branch
+fn branch_size(op_code: &OpCodeInfo) -> u32 {
+	op_code
+		.op_kinds()
+		.iter()
+		.map(|&op_kind| match op_kind {
+			OpCodeOperandKind::br16_1 | OpCodeOperandKind::br32_1 | OpCodeOperandKind::br64_1 => 1,
+			OpCodeOperandKind::br16_2
+			| OpCodeOperandKind::br32_4
+			| OpCodeOperandKind::br64_4
+			| OpCodeOperandKind::xbegin_2
+			| OpCodeOperandKind::xbegin_4 => 2,
+			_ => 0,
+		})
+		.max()
+		.unwrap_or_default()
+}
+
+fn is_string_operand(op_kind: OpCodeOperandKind) -> bool {
+	matches!(op_kind, OpCodeOperandKind::seg_rSI | OpCodeOperandKind::es_rDI | OpCodeOperandKind::seg_rDI | OpCodeOperandKind::seg_rBX_al)
+}
+
+fn is_string_register(op_kind: OpCodeOperandKind) -> bool {
+	matches!(op_kind, OpCodeOperandKind::al | OpCodeOperandKind::ax | OpCodeOperandKind::eax | OpCodeOperandKind::rax | OpCodeOperandKind::dx)
+}
+
+fn fixed_register(op_kind: OpCodeOperandKind) -> Register {
+	match op_kind {
+		OpCodeOperandKind::es => Register::ES,
+		OpCodeOperandKind::cs => Register::CS,
+		OpCodeOperandKind::ss => Register::SS,
+		OpCodeOperandKind::ds => Register::DS,
+		OpCodeOperandKind::fs => Register::FS,
+		OpCodeOperandKind::gs => Register::GS,
+		OpCodeOperandKind::al => Register::AL,
+		OpCodeOperandKind::cl => Register::CL,
+		OpCodeOperandKind::ax => Register::AX,
+		OpCodeOperandKind::dx => Register::DX,
+		OpCodeOperandKind::eax => Register::EAX,
+		OpCodeOperandKind::rax => Register::RAX,
+		OpCodeOperandKind::st0 => Register::ST0,
+		_ => Register::None,
+	}
+}
+
+fn is_branch_operand(op_kind: OpCodeOperandKind) -> bool {
+	matches!(
+		op_kind,
+		OpCodeOperandKind::br16_1
+			| OpCodeOperandKind::br32_1
+			| OpCodeOperandKind::br64_1
+			| OpCodeOperandKind::br16_2
+			| OpCodeOperandKind::br32_4
+			| OpCodeOperandKind::br64_4
+			| OpCodeOperandKind::xbegin_2
+			| OpCodeOperandKind::xbegin_4
+	)
+}
+
+#[rustfmt::skip]
+fn is_register_operand(op_kind: OpCodeOperandKind, register: Register) -> bool {
+	match op_kind {
+		OpCodeOperandKind::r8_or_mem | OpCodeOperandKind::r8_reg | OpCodeOperandKind::r8_opcode => register.is_gpr8(),
+		OpCodeOperandKind::r16_or_mem | OpCodeOperandKind::r16_reg | OpCodeOperandKind::r16_reg_mem | OpCodeOperandKind::r16_rm | OpCodeOperandKind::r16_opcode => register.is_gpr16(),
+		OpCodeOperandKind::r32_or_mem | OpCodeOperandKind::r32_or_mem_mpx | OpCodeOperandKind::r32_reg | OpCodeOperandKind::r32_reg_mem | OpCodeOperandKind::r32_rm | OpCodeOperandKind::r32_opcode | OpCodeOperandKind::r32_vvvv => register.is_gpr32(),
+		OpCodeOperandKind::r64_or_mem | OpCodeOperandKind::r64_or_mem_mpx | OpCodeOperandKind::r64_reg | OpCodeOperandKind::r64_reg_mem | OpCodeOperandKind::r64_rm | OpCodeOperandKind::r64_opcode | OpCodeOperandKind::r64_vvvv => register.is_gpr64(),
+		OpCodeOperandKind::seg_reg => register.is_segment_register(),
+		OpCodeOperandKind::k_or_mem | OpCodeOperandKind::k_reg | OpCodeOperandKind::kp1_reg | OpCodeOperandKind::k_rm | OpCodeOperandKind::k_vvvv => register.is_k(),
+		OpCodeOperandKind::mm_or_mem | OpCodeOperandKind::mm_reg | OpCodeOperandKind::mm_rm => register.is_mm(),
+		OpCodeOperandKind::xmm_or_mem | OpCodeOperandKind::xmm_reg | OpCodeOperandKind::xmm_rm | OpCodeOperandKind::xmm_vvvv | OpCodeOperandKind::xmmp3_vvvv | OpCodeOperandKind::xmm_is4 | OpCodeOperandKind::xmm_is5 => register.is_xmm(),
+		OpCodeOperandKind::ymm_or_mem | OpCodeOperandKind::ymm_reg | OpCodeOperandKind::ymm_rm | OpCodeOperandKind::ymm_vvvv | OpCodeOperandKind::ymm_is4 | OpCodeOperandKind::ymm_is5 => register.is_ymm(),
+		OpCodeOperandKind::zmm_or_mem | OpCodeOperandKind::zmm_reg | OpCodeOperandKind::zmm_rm | OpCodeOperandKind::zmm_vvvv | OpCodeOperandKind::zmmp3_vvvv => register.is_zmm(),
+		OpCodeOperandKind::bnd_or_mem_mpx | OpCodeOperandKind::bnd_reg => register.is_bnd(),
+		OpCodeOperandKind::cr_reg => register.is_cr(),
+		OpCodeOperandKind::dr_reg => register.is_dr(),
+		OpCodeOperandKind::tr_reg => register.is_tr(),
+		OpCodeOperandKind::sti_opcode => register.is_st(),
+		OpCodeOperandKind::tmm_reg | OpCodeOperandKind::tmm_rm | OpCodeOperandKind::tmm_vvvv => register.is_tmm(),
+		_ => {
+			let fixed = fixed_register(op_kind);
+			fixed != Register::None && fixed == register
+		}
+	}
+}
+
+#[rustfmt::skip]
+fn is_memory_operand(op_kind: OpCodeOperandKind, memory: &Memory) -> bool {
+	match op_kind {
+		OpCodeOperandKind::mem | OpCodeOperandKind::mem_mpx | OpCodeOperandKind::mem_mib | OpCodeOperandKind::sibmem
+		| OpCodeOperandKind::r8_or_mem | OpCodeOperandKind::r16_or_mem | OpCodeOperandKind::r32_or_mem | OpCodeOperandKind::r32_or_mem_mpx
+		| OpCodeOperandKind::r64_or_mem | OpCodeOperandKind::r64_or_mem_mpx | OpCodeOperandKind::mm_or_mem | OpCodeOperandKind::xmm_or_mem
+		| OpCodeOperandKind::ymm_or_mem | OpCodeOperandKind::zmm_or_mem | OpCodeOperandKind::bnd_or_mem_mpx | OpCodeOperandKind::k_or_mem => !memory.index.is_vector_register(),
+		OpCodeOperandKind::mem_vsib32x | OpCodeOperandKind::mem_vsib64x => memory.index.is_xmm(),
+		OpCodeOperandKind::mem_vsib32y | OpCodeOperandKind::mem_vsib64y => memory.index.is_ymm(),
+		OpCodeOperandKind::mem_vsib32z | OpCodeOperandKind::mem_vsib64z => memory.index.is_zmm(),
+		_ => false,
+	}
+}
+
+/// Sets the immediate if it fits in the operand. Negative values and values > `i64::MAX` are sign extended.
+fn set_immediate(instruction: &mut Instruction, operand: u32, op_kind: OpCodeOperandKind, value: i128) -> bool {
+	// `u64` values are stored as `i64` values, eg. `0xFFFF_FFFF_FFFF_FFFF` == `-1`
+	let value = value as u64 as i64;
+	let fits = |min: i64, max: i64| min <= value && value <= max;
+	match op_kind {
+		OpCodeOperandKind::imm8 | OpCodeOperandKind::imm8_const_1 | OpCodeOperandKind::imm4_m2z => {
+			let is_valid = match op_kind {
+				OpCodeOperandKind::imm8_const_1 => value == 1,
+				OpCodeOperandKind::imm4_m2z => fits(0, 15),
+				_ => fits(i8::MIN as i64, u8::MAX as i64),
+			};
+			if !is_valid {
+				return false;
+			}
+			// `enter` and `extrq` have two immediates
+			let is_second = (0..operand).any(|i| matches!(instruction.op_kind(i), OpKind::Immediate8 | OpKind::Immediate16));
+			if is_second {
+				instruction.set_op_kind(operand, OpKind::Immediate8_2nd);
+				instruction.set_immediate8_2nd(value as u8);
+			} else {
+				instruction.set_op_kind(operand, OpKind::Immediate8);
+				instruction.set_immediate8(value as u8);
+			}
+		}
+		OpCodeOperandKind::imm16 if fits(i16::MIN as i64, u16::MAX as i64) => {
+			instruction.set_op_kind(operand, OpKind::Immediate16);
+			instruction.set_immediate16(value as u16);
+		}
+		OpCodeOperandKind::imm32 if fits(i32::MIN as i64, u32::MAX as i64) => {
+			instruction.set_op_kind(operand, OpKind::Immediate32);
+			instruction.set_immediate32(value as u32);
+		}
+		OpCodeOperandKind::imm64 => {
+			instruction.set_op_kind(operand, OpKind::Immediate64);
+			instruction.set_immediate64(value as u64);
+		}
+		OpCodeOperandKind::imm8sex16 if fits(i8::MIN as i64, i8::MAX as i64) || fits(0xFF80, 0xFFFF) => {
+			instruction.set_op_kind(operand, OpKind::Immediate8to16);
+			instruction.set_immediate8to16(value as i8 as i16);
+		}
+		OpCodeOperandKind::imm8sex32 if fits(i8::MIN as i64, i8::MAX as i64) || fits(0xFFFF_FF80, 0xFFFF_FFFF) => {
+			instruction.set_op_kind(operand, OpKind::Immediate8to32);
+			instruction.set_immediate8to32(value as i8 as i32);
+		}
+		OpCodeOperandKind::imm8sex64 if fits(i8::MIN as i64, i8::MAX as i64) => {
+			instruction.set_op_kind(operand, OpKind::Immediate8to64);
+			instruction.set_immediate8to64(value);
+		}
+		OpCodeOperandKind::imm32sex64 if fits(i32::MIN as i64, i32::MAX as i64) => {
+			instruction.set_op_kind(operand, OpKind::Immediate32to64);
+			instruction.set_immediate32to64(value);
+		}
+		_ => return false,
+	}
+	true
+}
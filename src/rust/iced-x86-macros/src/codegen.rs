@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::assembler::Matcher;
+use crate::error::Error;
+use crate::parser::{Instr, Memory, Operand, OperandKind, Program, Rounding, Statement};
+use crate::tables::{register_name, Tables};
+use iced_x86::{OpKind, Register};
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+use std::collections::HashMap;
+
+// `CodeAssembler` methods with an operand count suffix. The method without a suffix has a different number of operands.
+static NUMBERED_METHODS: &[&str] = &[
+	"cmpsd_3",
+	"extrq_3",
+	"fadd_2",
+	"fcom_2",
+	"fcomp_2",
+	"fdiv_2",
+	"fdivr_2",
+	"fmul_2",
+	"fsub_2",
+	"fsubr_2",
+	"imul_2",
+	"imul_3",
+	"insertq_4",
+	"movsd_2",
+	"nop_1",
+	"ret_1",
+	"retf_1",
+	"ud0_2",
+	"vgatherdpd_3",
+	"vgatherdps_3",
+	"vgatherqpd_3",
+	"vgatherqps_3",
+	"vmovhpd_3",
+	"vmovhps_3",
+	"vmovlpd_3",
+	"vmovlps_3",
+	"vmovsd_3",
+	"vmovsh_3",
+	"vmovss_3",
+	"vpgatherdd_3",
+	"vpgatherdq_3",
+	"vpgatherqd_3",
+	"vpgatherqq_3",
+];
+// Mnemonics that are Rust keywords
+static KEYWORD_METHODS: &[&str] = &["in", "loop"];
+
+/// Generates a closure that adds the instructions to a `CodeAssembler`:
+///
+/// ```text
+/// move |a: &mut ::iced_x86::code_asm::CodeAssembler| -> ::core::result::Result<(), ::iced_x86::IcedError> {
+///     ::core::assert_eq!(a.bitness(), 64, "...");
+///     let mut label = a.create_label();
+///     a.set_label(&mut label)?;
+///     a.mov(::iced_x86::code_asm::rax, ::iced_x86::code_asm::qword_ptr(::iced_x86::code_asm::rdi + 8))?;
+///     ::core::result::Result::Ok(())
+/// }
+/// ```
+pub(crate) fn closure(program: &Program, tables: &Tables) -> Result<TokenStream, Error> {
+	let generator = Generator { matcher: Matcher::new(tables, program.bitness, true), tables };
+	// The assembler and labels aren't visible to the user's `{expr}` operands
+	let asm = Ident::new("a", Span::mixed_site());
+
+	// Labels before the same instruction are the same label
+	let mut labels: HashMap<&str, Ident> = HashMap::new();
+	let mut label_vars: Vec<Ident> = Vec::new();
+	let mut current_label: Option<Ident> = None;
+	for statement in &program.statements {
+		match statement {
+			Statement::Label(name) => {
+				let var = current_label
+					.get_or_insert_with(|| {
+						let var = Ident::new(&format!("label_{}", label_vars.len()), Span::mixed_site());
+						label_vars.push(var.clone());
+						var
+					})
+					.clone();
+				if labels.insert(&name.name, var).is_some() {
+					return Err(Error::new(name.span, format!("Duplicate label `{}`", name.name)));
+				}
+			}
+			Statement::Instruction(_) => current_label = None,
+		}
+	}
+
+	let mut body = TokenStream::new();
+	body.extend(path(&["core", "assert_eq"], Span::call_site()));
+	body.extend([
+		punct('!'),
+		group(Delimiter::Parenthesis, {
+			let mut args = method_call(&asm, "bitness", TokenStream::new(), Span::call_site());
+			args.extend([
+				punct(','),
+				TokenTree::Literal(Literal::u32_unsuffixed(program.bitness)),
+				punct(','),
+				TokenTree::Literal(Literal::string(&format!("The code was written for a {}-bit CodeAssembler", program.bitness))),
+			]);
+			args
+		}),
+	]);
+	body.extend([punct(';')]);
+	for var in &label_vars {
+		body.extend([ident("let"), ident("mut"), TokenTree::Ident(var.clone()), punct('=')]);
+		body.extend(method_call(&asm, "create_label", TokenStream::new(), Span::call_site()));
+		body.extend([punct(';')]);
+	}
+
+	let mut pending_label = None;
+	for statement in &program.statements {
+		match statement {
+			Statement::Label(name) => {
+				if let Some(var) = labels.get(name.name.as_str()) {
+					if pending_label.as_ref().map_or(true, |pending: &Ident| pending.to_string() != var.to_string()) {
+						let mut arg = TokenStream::new();
+						arg.extend([punct('&'), ident("mut"), TokenTree::Ident(var.clone())]);
+						body.extend(method_call(&asm, "set_label", arg, name.span));
+						body.extend([punct('?'), punct(';')]);
+						pending_label = Some(var.clone());
+					}
+				}
+			}
+			Statement::Instruction(instr) => {
+				body.extend(generator.instruction(&asm, instr, &labels)?);
+				pending_label = None;
+			}
+		}
+	}
+	if pending_label.is_some() {
+		body.extend(method_call(&asm, "zero_bytes", TokenStream::new(), Span::call_site()));
+		body.extend([punct('?'), punct(';')]);
+	}
+	body.extend(path(&["core", "result", "Result", "Ok"], Span::call_site()));
+	body.extend([group(Delimiter::Parenthesis, group(Delimiter::Parenthesis, TokenStream::new()).into())]);
+
+	let mut closure = TokenStream::new();
+	closure.extend([ident("move"), punct('|'), TokenTree::Ident(asm), punct(':'), punct('&'), ident("mut")]);
+	closure.extend(path(&["iced_x86", "code_asm", "CodeAssembler"], Span::call_site()));
+	closure.extend([punct('|'), TokenTree::Punct(Punct::new('-', Spacing::Joint)), punct('>')]);
+	closure.extend(path(&["core", "result", "Result"], Span::call_site()));
+	closure.extend([punct('<'), group(Delimiter::Parenthesis, TokenStream::new()), punct(',')]);
+	closure.extend(path(&["iced_x86", "IcedError"], Span::call_site()));
+	closure.extend([punct('>'), group(Delimiter::Brace, body)]);
+	Ok(closure)
+}
+
+struct Generator<'a> {
+	matcher: Matcher<'a>,
+	tables: &'a Tables,
+}
+
+impl Generator<'_> {
+	/// Generates `a.prefix().method(op0, op1)?;`
+	fn instruction(&self, asm: &Ident, instr: &Instr, labels: &HashMap<&str, Ident>) -> Result<TokenStream, Error> {
+		for operand in &instr.operands {
+			let label = match &operand.kind {
+				OperandKind::Label(name) => name,
+				OperandKind::Memory(Memory { label: Some(name), .. }) => name,
+				_ => continue,
+			};
+			if !labels.contains_key(label.name.as_str()) {
+				return Err(Error::new(label.span, format!("Undefined label `{}`", label.name)));
+			}
+		}
+
+		// Verify everything that's known at compile time. Unknown mnemonics could be `CodeAssembler`
+		// pseudo instructions (eg. `cmpeqps`) so the compiler verifies them.
+		let mut is_branch = false;
+		if !instr.has_runtime_operands() && self.tables.mnemonic(&instr.mnemonic.name).is_some() {
+			let instruction = self.matcher.create_instruction(instr, &|_| 0)?;
+			is_branch = matches!(instruction.op0_kind(), OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64);
+		}
+		if let Some(operand) = instr.operands.iter().find(|operand| operand.op_mask != Register::None) {
+			if !matches!(operand.kind, OperandKind::Register(_) | OperandKind::Memory(_) | OperandKind::Runtime(_)) {
+				return Err(Error::with_range(operand.start, operand.end, "Only registers and memory operands can have an opmask register".into()));
+			}
+		}
+
+		let span = instr.mnemonic.span;
+		let mut args = TokenStream::new();
+		for (i, operand) in instr.operands.iter().enumerate() {
+			if i != 0 {
+				args.extend([punct(',')]);
+			}
+			let is_last = i + 1 == instr.operands.len();
+			args.extend(Self::operand(operand, labels, is_branch && i == 0, if is_last { instr.rounding.map(Rounding::method) } else { None })?);
+		}
+		if instr.rounding.is_some() && instr.operands.last().map_or(true, |operand| matches!(operand.kind, OperandKind::Memory(_))) {
+			return Err(instr.error("The rounding operand must follow a register operand".into()));
+		}
+
+		let mut result = TokenStream::new();
+		result.extend([TokenTree::Ident(asm.clone())]);
+		for &prefix in &instr.prefixes {
+			result.extend([punct('.'), TokenTree::Ident(Ident::new(prefix.method(), span)), group(Delimiter::Parenthesis, TokenStream::new())]);
+		}
+		let method = method_name(&instr.mnemonic.name, instr.operands.len());
+		result.extend([punct('.'), TokenTree::Ident(Ident::new(&method, span)), group(Delimiter::Parenthesis, args)]);
+		result.extend([punct('?'), punct(';')]);
+		Ok(respan(result, span))
+	}
+
+	fn operand(operand: &Operand, labels: &HashMap<&str, Ident>, is_branch: bool, rounding: Option<&str>) -> Result<TokenStream, Error> {
+		let span = operand.start;
+		let mut result = match &operand.kind {
+			&OperandKind::Register(register) => path(&["iced_x86", "code_asm", &register_name(register)], span),
+			&OperandKind::Immediate(value) => integer(value, if is_branch { Some("u64") } else { None }, span),
+			OperandKind::Label(name) => labels.get(name.name.as_str()).cloned().map(TokenTree::Ident).into_iter().collect(),
+			OperandKind::Runtime(tokens) => expression(tokens),
+			OperandKind::Memory(memory) => Self::memory(memory, labels, span),
+		};
+		if let Some(method) = rounding {
+			if !matches!(operand.kind, OperandKind::Memory(_)) {
+				result = method_call(&group(Delimiter::Parenthesis, result), method, TokenStream::new(), span);
+			}
+		}
+		if operand.op_mask != Register::None {
+			result = method_call(&group(Delimiter::Parenthesis, result), &register_name(operand.op_mask), TokenStream::new(), span);
+		}
+		if operand.zeroing_masking {
+			result = method_call(&group(Delimiter::Parenthesis, result), "z", TokenStream::new(), span);
+		}
+		Ok(result)
+	}
+
+	/// Generates `size_ptr(base + index * scale + displ + (expr)).segment()`
+	fn memory(memory: &Memory, labels: &HashMap<&str, Ident>, span: Span) -> TokenStream {
+		fn add(expr: &mut TokenStream, tokens: TokenStream, is_negative: bool) {
+			if !expr.is_empty() {
+				expr.extend([punct(if is_negative { '-' } else { '+' })]);
+			} else if is_negative {
+				expr.extend([punct('-')]);
+			}
+			expr.extend(tokens);
+		}
+		let mut expr = TokenStream::new();
+		if let Some(label) = &memory.label {
+			if let Some(var) = labels.get(label.name.as_str()) {
+				expr.extend([TokenTree::Ident(var.clone())]);
+			}
+		} else {
+			if memory.base != Register::None {
+				add(&mut expr, path(&["iced_x86", "code_asm", &register_name(memory.base)], span), false);
+			}
+			if memory.index != Register::None {
+				let mut tokens = path(&["iced_x86", "code_asm", &register_name(memory.index)], span);
+				tokens.extend([punct('*'), TokenTree::Literal(Literal::u32_unsuffixed(memory.scale))]);
+				add(&mut expr, tokens, false);
+			}
+			if memory.displacement != 0 || (expr.is_empty() && memory.runtime.is_empty()) {
+				add(&mut expr, integer(memory.displacement as i128, None, span), false);
+			}
+			for (is_negative, tokens) in &memory.runtime {
+				add(&mut expr, expression(tokens), *is_negative);
+			}
+		}
+
+		let function = match memory.size {
+			Some(size) if size.is_broadcast => format!("{}_bcst", size.name),
+			Some(size) => format!("{}_ptr", size.name),
+			None => "ptr".into(),
+		};
+		let mut result = path(&["iced_x86", "code_asm", &function], span);
+		result.extend([group(Delimiter::Parenthesis, expr)]);
+		if memory.segment != Register::None {
+			result.extend([
+				punct('.'),
+				TokenTree::Ident(Ident::new(&register_name(memory.segment), span)),
+				group(Delimiter::Parenthesis, TokenStream::new()),
+			]);
+		}
+		result
+	}
+}
+
+fn method_name(mnemonic: &str, op_count: usize) -> String {
+	let numbered = format!("{}_{}", mnemonic, op_count);
+	if NUMBERED_METHODS.contains(&numbered.as_str()) {
+		numbered
+	} else if KEYWORD_METHODS.contains(&mnemonic) {
+		format!("{}_", mnemonic)
+	} else {
+		mnemonic.into()
+	}
+}
+
+/// Integers that fit in an `i32` don't get a suffix so the compiler can pick the `CodeAssembler` overload
+fn integer(value: i128, suffix: Option<&str>, span: Span) -> TokenStream {
+	if value == i64::MIN as i128 {
+		return path(&["core", "primitive", "i64", "MIN"], span);
+	}
+	let magnitude = value.unsigned_abs() as u64;
+	let mut literal = match suffix {
+		Some(_) => Literal::u64_suffixed(magnitude),
+		None if i32::MIN as i128 <= value && value <= i32::MAX as i128 => Literal::u64_unsuffixed(magnitude),
+		None if 0 <= value && value <= u32::MAX as i128 => Literal::u32_suffixed(magnitude as u32),
+		None if value <= i64::MAX as i128 => Literal::i64_suffixed(magnitude as i64),
+		None => Literal::u64_suffixed(magnitude),
+	};
+	literal.set_span(span);
+	if value < 0 {
+		let mut minus = Punct::new('-', Spacing::Alone);
+		minus.set_span(span);
+		group(Delimiter::Parenthesis, [TokenTree::Punct(minus), TokenTree::Literal(literal)].into_iter().collect()).into()
+	} else {
+		TokenTree::Literal(literal).into()
+	}
+}
+
+/// Adds parentheses if it's not a single token so operator precedence doesn't change
+fn expression(tokens: &TokenStream) -> TokenStream {
+	if tokens.clone().into_iter().count() == 1 {
+		tokens.clone()
+	} else {
+		group(Delimiter::Parenthesis, tokens.clone()).into()
+	}
+}
+
+fn method_call(receiver: &dyn ToTokens, method: &str, args: TokenStream, span: Span) -> TokenStream {
+	let mut result = receiver.to_tokens();
+	result.extend([punct('.'), TokenTree::Ident(Ident::new(method, span)), group(Delimiter::Parenthesis, args)]);
+	result
+}
+
+trait ToTokens {
+	fn to_tokens(&self) -> TokenStream;
+}
+
+impl ToTokens for Ident {
+	fn to_tokens(&self) -> TokenStream {
+		TokenTree::Ident(self.clone()).into()
+	}
+}
+
+impl ToTokens for TokenTree {
+	fn to_tokens(&self) -> TokenStream {
+		self.clone().into()
+	}
+}
+
+/// `::a::b::c`
+fn path(parts: &[&str], span: Span) -> TokenStream {
+	let mut result = TokenStream::new();
+	for part in parts {
+		result.extend([TokenTree::Punct(Punct::new(':', Spacing::Joint)), TokenTree::Punct(Punct::new(':', Spacing::Alone))]);
+		result.extend([TokenTree::Ident(Ident::new(part, span))]);
+	}
+	result
+}
+
+fn ident(name: &str) -> TokenTree {
+	TokenTree::Ident(Ident::new(name, Span::call_site()))
+}
+
+fn punct(ch: char) -> TokenTree {
+	TokenTree::Punct(Punct::new(ch, Spacing::Alone))
+}
+
+fn group(delimiter: Delimiter, tokens: TokenStream) -> TokenTree {
+	TokenTree::Group(Group::new(delimiter, tokens))
+}
+
+/// Errors in the generated code (eg. invalid operand types) are reported at the instruction. The user's
+/// `{expr}` tokens and the hygienic assembler/label identifiers keep their spans.
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+	tokens
+		.into_iter()
+		.map(|token| match token {
+			TokenTree::Group(group) => {
+				let mut new_group = Group::new(group.delimiter(), group.stream());
+				new_group.set_span(span);
+				TokenTree::Group(new_group)
+			}
+			TokenTree::Punct(mut punct) => {
+				punct.set_span(span);
+				TokenTree::Punct(punct)
+			}
+			TokenTree::Literal(mut literal) => {
+				literal.set_span(span);
+				TokenTree::Literal(literal)
+			}
+			TokenTree::Ident(ident) => TokenTree::Ident(ident),
+		})
+		.collect()
+}
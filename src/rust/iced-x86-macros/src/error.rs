@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// An error that's reported as a `compile_error!()` at the span of the bad tokens
+pub(crate) struct Error {
+	start: Span,
+	end: Span,
+	message: String,
+}
+
+impl Error {
+	pub(crate) fn new(span: Span, message: String) -> Self {
+		Self { start: span, end: span, message }
+	}
+
+	/// `Span::join()` isn't stable so the error covers everything from the first token to the last token
+	pub(crate) fn with_range(start: Span, end: Span, message: String) -> Self {
+		Self { start, end, message }
+	}
+
+	pub(crate) fn to_compile_error(&self) -> TokenStream {
+		let mut message = Group::new(Delimiter::Brace, TokenTree::Literal(Literal::string(&self.message)).into());
+		message.set_span(self.end);
+		let mut colon1 = Punct::new(':', Spacing::Joint);
+		colon1.set_span(self.start);
+		let mut colon2 = Punct::new(':', Spacing::Alone);
+		colon2.set_span(self.start);
+		let mut colon3 = Punct::new(':', Spacing::Joint);
+		colon3.set_span(self.start);
+		let mut colon4 = Punct::new(':', Spacing::Alone);
+		colon4.set_span(self.start);
+		let mut bang = Punct::new('!', Spacing::Alone);
+		bang.set_span(self.start);
+		[
+			TokenTree::Punct(colon1),
+			TokenTree::Punct(colon2),
+			TokenTree::Ident(Ident::new("core", self.start)),
+			TokenTree::Punct(colon3),
+			TokenTree::Punct(colon4),
+			TokenTree::Ident(Ident::new("compile_error", self.start)),
+			TokenTree::Punct(bang),
+			TokenTree::Group(message),
+		]
+		.into_iter()
+		.collect()
+	}
+}
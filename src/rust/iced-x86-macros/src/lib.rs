@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+#![doc = include_str!("../README.md")]
+#![doc(html_logo_url = "https://raw.githubusercontent.com/icedland/iced/master/logo.png")]
+#![allow(unknown_lints)]
+#![warn(absolute_paths_not_starting_with_crate)]
+#![warn(anonymous_parameters)]
+#![warn(elided_lifetimes_in_paths)]
+#![warn(explicit_outlives_requirements)]
+#![warn(keyword_idents)]
+#![warn(meta_variable_misuse)]
+#![warn(missing_docs)]
+#![warn(non_ascii_idents)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_extern_crates)]
+#![warn(unused_import_braces)]
+#![warn(unused_lifetimes)]
+#![warn(unused_must_use)]
+#![warn(unused_results)]
+#![allow(clippy::cast_lossless)]
+#![allow(clippy::manual_range_contains)]
+#![allow(clippy::uninlined_format_args)] // Nope
+#![warn(clippy::cloned_instead_of_copied)]
+#![warn(clippy::dbg_macro)]
+#![warn(clippy::default_trait_access)]
+#![warn(clippy::explicit_into_iter_loop)]
+#![warn(clippy::explicit_iter_loop)]
+#![warn(clippy::get_unwrap)]
+#![warn(clippy::match_bool)]
+#![warn(clippy::needless_borrow)]
+#![warn(clippy::print_stderr)]
+#![warn(clippy::print_stdout)]
+#![warn(clippy::redundant_closure_for_method_calls)]
+#![warn(clippy::todo)]
+#![warn(clippy::unimplemented)]
+#![warn(clippy::unused_self)]
+#![warn(clippy::used_underscore_binding)]
+#![cfg_attr(not(test), warn(clippy::expect_used))]
+#![cfg_attr(not(test), warn(clippy::unwrap_used))]
+
+mod assembler;
+mod codegen;
+mod error;
+mod parser;
+mod tables;
+
+use crate::tables::Tables;
+use proc_macro::{Delimiter, Group, Literal, Punct, Spacing, TokenStream, TokenTree};
+
+/// Assembles x86/x64 instructions at compile time
+///
+/// The first token is the bitness (16, 32 or 64) followed by `;`. Instructions are separated by `;` and use
+/// Intel syntax, eg. `mov rax, qword ptr [rdi + rcx*8 + 0x10]`. Labels (`name:`) can be used as branch
+/// targets and in RIP-relative memory operands (`[rip + name]`).
+///
+/// If all operands are known at compile time, the macro expands to a `[u8; N]` array with the encoded
+/// instructions. The code is position independent, branches and label references are relative.
+///
+/// If an operand is a Rust expression (`{expr}`), the macro expands to a closure that adds the instructions
+/// to an `iced_x86::code_asm::CodeAssembler` (`FnOnce(&mut CodeAssembler) -> Result<(), IcedError>`).
+/// `iced-x86` must be a dependency with the `code_asm` feature. The expressions are passed to the
+/// `CodeAssembler` methods, eg. an integer, a register (`rcx`), memory (`qword_ptr(rax)`) or a `CodeLabel`.
+///
+/// Invalid instructions and operands are compile errors.
+///
+/// Supported syntax:
+///
+/// - Registers: `al`, `r8b`, `st(1)`, `xmm0`, `k1`, ...
+/// - Immediates: `123`, `-1`, `0x1234`
+/// - Memory: `[rax]`, `fs:[rax + rcx*4 - 8]`, `dword ptr [rax]`, `dword bcst [rax]`, `[rip + label]`,
+///   `[rax + {expr}]`
+/// - Prefixes: `lock`, `rep`, `repe`, `repz`, `repne`, `repnz`, `xacquire`, `xrelease`, `notrack`
+/// - Decorators: `zmm0{k1}{z}` and a final rounding operand `{sae}`, `{rn-sae}`, `{rd-sae}`, `{ru-sae}`, `{rz-sae}`
+/// - Condition code aliases, eg. `jz`, `cmovnae`, `setc`
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86_macros::iced_asm;
+///
+/// const CODE: [u8; 9] = iced_asm!(64;
+///     mov rax, [rdi + 8];
+///     add rax, 1;
+///     ret
+/// );
+/// assert_eq!(CODE, [0x48, 0x8B, 0x47, 0x08, 0x48, 0x83, 0xC0, 0x01, 0xC3]);
+///
+/// // Loops and RIP-relative data
+/// let code = iced_asm!(64;
+///     mov ecx, 10;
+/// again:
+///     add eax, dword ptr [rip + data];
+///     dec ecx;
+///     jnz again;
+///     ret;
+/// data:
+///     nop
+/// );
+/// assert_eq!(code, [
+///     0xB9, 0x0A, 0x00, 0x00, 0x00, // mov ecx,0Ah
+///     0x03, 0x05, 0x05, 0x00, 0x00, 0x00, // add eax,[rip+5]
+///     0xFF, 0xC9, // dec ecx
+///     0x75, 0xF6, // jne short again
+///     0xC3, // ret
+///     0x90, // nop
+/// ]);
+/// ```
+///
+/// Runtime operands:
+///
+/// ```
+/// use iced_x86::code_asm::*;
+/// use iced_x86_macros::iced_asm;
+///
+/// # fn main() -> Result<(), IcedError> {
+/// let offset = 0x10;
+/// let mut a = CodeAssembler::new(64)?;
+/// let mut done = a.create_label();
+/// let add_offset = iced_asm!(64;
+///     mov rax, [rdi + {offset}];
+///     test rax, rax;
+///     jz {done};
+///     add rax, {offset};
+/// );
+/// add_offset(&mut a)?;
+/// a.set_label(&mut done)?;
+/// a.ret()?;
+/// let bytes = a.assemble(0x1234_5678)?;
+/// assert_eq!(bytes, [0x48, 0x8B, 0x47, 0x10, 0x48, 0x85, 0xC0, 0x74, 0x06, 0x48, 0x05, 0x10, 0x00, 0x00, 0x00, 0xC3]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Errors are reported at the invalid instruction or operand:
+///
+/// ```compile_fail
+/// use iced_x86_macros::iced_asm;
+///
+/// // Invalid operands for `mov`
+/// let code = iced_asm!(64; mov eax, bl);
+/// ```
+///
+/// ```compile_fail
+/// use iced_x86_macros::iced_asm;
+///
+/// // The memory operand size is ambiguous
+/// let code = iced_asm!(64; inc [rax]);
+/// ```
+#[proc_macro]
+pub fn iced_asm(input: TokenStream) -> TokenStream {
+	let tables = Tables::new();
+	let result = parser::parse(input, &tables).and_then(|program| {
+		if program.has_runtime_operands() {
+			codegen::closure(&program, &tables)
+		} else {
+			assembler::assemble(&program, &tables).map(|bytes| byte_array(&bytes))
+		}
+	});
+	match result {
+		Ok(tokens) => tokens,
+		Err(error) => error.to_compile_error(),
+	}
+}
+
+/// `[0x90u8, 0xC3u8]` or `[0u8; 0]`
+fn byte_array(bytes: &[u8]) -> TokenStream {
+	let mut array = TokenStream::new();
+	if bytes.is_empty() {
+		array.extend([
+			TokenTree::Literal(Literal::u8_suffixed(0)),
+			TokenTree::Punct(Punct::new(';', Spacing::Alone)),
+			TokenTree::Literal(Literal::usize_unsuffixed(0)),
+		]);
+	}
+	for &b in bytes {
+		array.extend([TokenTree::Literal(Literal::u8_suffixed(b)), TokenTree::Punct(Punct::new(',', Spacing::Alone))]);
+	}
+	TokenTree::Group(Group::new(Delimiter::Bracket, array)).into()
+}
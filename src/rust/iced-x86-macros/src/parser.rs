@@ -0,0 +1,556 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::error::Error;
+use crate::tables::Tables;
+use iced_x86::{Register, RoundingControl};
+use proc_macro::{Delimiter, Group, Literal, Spacing, Span, TokenStream, TokenTree};
+
+pub(crate) struct Program {
+	pub(crate) bitness: u32,
+	pub(crate) statements: Vec<Statement>,
+}
+
+impl Program {
+	/// Checks if any operand must be evaluated at runtime (`{expr}`)
+	pub(crate) fn has_runtime_operands(&self) -> bool {
+		self.statements.iter().any(|statement| match statement {
+			Statement::Label(_) => false,
+			Statement::Instruction(instr) => instr.has_runtime_operands(),
+		})
+	}
+}
+
+pub(crate) enum Statement {
+	Label(Name),
+	Instruction(Instr),
+}
+
+#[derive(Clone)]
+pub(crate) struct Name {
+	pub(crate) name: String,
+	pub(crate) span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Prefix {
+	Lock,
+	Rep,
+	Repe,
+	Repne,
+	Xacquire,
+	Xrelease,
+	Notrack,
+}
+
+static PREFIXES: &[(&str, Prefix)] = &[
+	("lock", Prefix::Lock),
+	("rep", Prefix::Rep),
+	("repe", Prefix::Repe),
+	("repz", Prefix::Repe),
+	("repne", Prefix::Repne),
+	("repnz", Prefix::Repne),
+	("xacquire", Prefix::Xacquire),
+	("xrelease", Prefix::Xrelease),
+	("notrack", Prefix::Notrack),
+];
+
+impl Prefix {
+	/// Gets the `CodeAssembler` method
+	pub(crate) fn method(self) -> &'static str {
+		match self {
+			Prefix::Lock => "lock",
+			Prefix::Rep => "rep",
+			Prefix::Repe => "repe",
+			Prefix::Repne => "repne",
+			Prefix::Xacquire => "xacquire",
+			Prefix::Xrelease => "xrelease",
+			Prefix::Notrack => "notrack",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rounding {
+	Sae,
+	RnSae,
+	RdSae,
+	RuSae,
+	RzSae,
+}
+
+static ROUNDING: &[(&str, Rounding)] =
+	&[("sae", Rounding::Sae), ("rn", Rounding::RnSae), ("rd", Rounding::RdSae), ("ru", Rounding::RuSae), ("rz", Rounding::RzSae)];
+
+impl Rounding {
+	pub(crate) fn rounding_control(self) -> RoundingControl {
+		match self {
+			Rounding::Sae => RoundingControl::None,
+			Rounding::RnSae => RoundingControl::RoundToNearest,
+			Rounding::RdSae => RoundingControl::RoundDown,
+			Rounding::RuSae => RoundingControl::RoundUp,
+			Rounding::RzSae => RoundingControl::RoundTowardZero,
+		}
+	}
+
+	/// Gets the register method, eg. `zmm1.rn_sae()`
+	pub(crate) fn method(self) -> &'static str {
+		match self {
+			Rounding::Sae => "sae",
+			Rounding::RnSae => "rn_sae",
+			Rounding::RdSae => "rd_sae",
+			Rounding::RuSae => "ru_sae",
+			Rounding::RzSae => "rz_sae",
+		}
+	}
+}
+
+pub(crate) struct Instr {
+	pub(crate) prefixes: Vec<Prefix>,
+	/// Lower case mnemonic
+	pub(crate) mnemonic: Name,
+	pub(crate) operands: Vec<Operand>,
+	pub(crate) rounding: Option<Rounding>,
+	pub(crate) start: Span,
+	pub(crate) end: Span,
+}
+
+impl Instr {
+	pub(crate) fn has_runtime_operands(&self) -> bool {
+		self.operands.iter().any(|operand| match &operand.kind {
+			OperandKind::Runtime(_) => true,
+			OperandKind::Memory(memory) => !memory.runtime.is_empty(),
+			OperandKind::Register(_) | OperandKind::Immediate(_) | OperandKind::Label(_) => false,
+		})
+	}
+
+	pub(crate) fn error(&self, message: String) -> Error {
+		Error::with_range(self.start, self.end, message)
+	}
+}
+
+pub(crate) struct Operand {
+	pub(crate) kind: OperandKind,
+	pub(crate) op_mask: Register,
+	pub(crate) zeroing_masking: bool,
+	pub(crate) start: Span,
+	pub(crate) end: Span,
+}
+
+pub(crate) enum OperandKind {
+	Register(Register),
+	/// An `i64` or a `u64`
+	Immediate(i128),
+	/// A branch target
+	Label(Name),
+	Memory(Memory),
+	/// `{expr}`, only supported by the generated closures
+	Runtime(TokenStream),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SizeHint {
+	/// Prefix of the `code_asm` function, eg. `dword` (`dword_ptr()`)
+	pub(crate) name: &'static str,
+	pub(crate) size: usize,
+	pub(crate) is_broadcast: bool,
+}
+
+static SIZE_HINTS: &[(&str, usize)] = &[
+	("byte", 1),
+	("word", 2),
+	("dword", 4),
+	("fword", 6),
+	("qword", 8),
+	("mmword", 8),
+	("tbyte", 10),
+	("tword", 10),
+	("oword", 16),
+	("xmmword", 16),
+	("ymmword", 32),
+	("zmmword", 64),
+];
+static BROADCAST_SIZE_HINTS: &[&str] = &["word", "dword", "qword"];
+
+pub(crate) struct Memory {
+	pub(crate) size: Option<SizeHint>,
+	pub(crate) segment: Register,
+	pub(crate) base: Register,
+	pub(crate) index: Register,
+	pub(crate) scale: u32,
+	pub(crate) displacement: i64,
+	/// RIP-relative label reference
+	pub(crate) label: Option<Name>,
+	/// `{expr}` terms and if they're subtracted
+	pub(crate) runtime: Vec<(bool, TokenStream)>,
+}
+
+enum ParsedOperand {
+	Operand(Operand),
+	Rounding(Rounding),
+}
+
+/// Parses `bitness; [label:] [prefix] mnemonic operands; ...`
+pub(crate) fn parse(input: TokenStream, tables: &Tables) -> Result<Program, Error> {
+	let tokens: Vec<TokenTree> = input.into_iter().collect();
+	let mut statements = split(&tokens, ';').into_iter();
+	let bitness = match statements.next() {
+		Some([TokenTree::Literal(literal)]) => match parse_integer(literal)? {
+			16 => 16,
+			32 => 32,
+			64 => 64,
+			_ => return Err(Error::new(literal.span(), "Bitness must be 16, 32 or 64".into())),
+		},
+		Some([token, ..]) => return Err(Error::new(token.span(), "Expected the bitness (16, 32 or 64), eg. `iced_asm!(64; nop)`".into())),
+		_ => return Err(Error::new(Span::call_site(), "Expected the bitness (16, 32 or 64), eg. `iced_asm!(64; nop)`".into())),
+	};
+
+	let parser = Parser { tables, bitness };
+	let mut program = Program { bitness, statements: Vec::new() };
+	for tokens in statements {
+		parser.parse_statement(tokens, &mut program.statements)?;
+	}
+	Ok(program)
+}
+
+struct Parser<'a> {
+	tables: &'a Tables,
+	bitness: u32,
+}
+
+impl Parser<'_> {
+	fn parse_statement(&self, mut tokens: &[TokenTree], statements: &mut Vec<Statement>) -> Result<(), Error> {
+		while let [TokenTree::Ident(name), TokenTree::Punct(colon), ..] = tokens {
+			if colon.as_char() != ':' || colon.spacing() != Spacing::Alone {
+				break;
+			}
+			if self.tables.register(&name.to_string()).is_some() {
+				return Err(Error::new(name.span(), format!("`{}` is a register and can't be used as a label", name)));
+			}
+			statements.push(Statement::Label(Name { name: name.to_string(), span: name.span() }));
+			tokens = &tokens[2..];
+		}
+		let (first, last) = match (tokens.first(), tokens.last()) {
+			(Some(first), Some(last)) => (first.span(), last.span()),
+			_ => return Ok(()),
+		};
+
+		let mut prefixes = Vec::new();
+		while let [TokenTree::Ident(ident), TokenTree::Ident(_), ..] = tokens {
+			let name = ident.to_string().to_lowercase();
+			match PREFIXES.iter().find(|&&(prefix, _)| prefix == name) {
+				Some(&(_, prefix)) => prefixes.push(prefix),
+				None => break,
+			}
+			tokens = &tokens[1..];
+		}
+		let mnemonic = match tokens.first() {
+			Some(TokenTree::Ident(ident)) => Name { name: ident.to_string().to_lowercase(), span: ident.span() },
+			Some(token) => return Err(Error::new(token.span(), "Expected a mnemonic".into())),
+			None => return Err(Error::with_range(first, last, "Expected a mnemonic".into())),
+		};
+
+		let mut operands = Vec::new();
+		let mut rounding = None;
+		if tokens.len() > 1 {
+			for tokens in split(&tokens[1..], ',') {
+				if rounding.is_some() {
+					return Err(Error::new(tokens.first().map_or(last, TokenTree::span), "The rounding operand must be the last operand".into()));
+				}
+				match tokens {
+					[] => return Err(Error::with_range(first, last, "Missing operand".into())),
+					_ => match self.parse_operand(tokens)? {
+						ParsedOperand::Operand(operand) => operands.push(operand),
+						ParsedOperand::Rounding(value) => rounding = Some(value),
+					},
+				}
+			}
+		}
+
+		statements.push(Statement::Instruction(Instr { prefixes, mnemonic, operands, rounding, start: first, end: last }));
+		Ok(())
+	}
+
+	fn parse_operand(&self, mut tokens: &[TokenTree]) -> Result<ParsedOperand, Error> {
+		let start = tokens[0].span();
+		let end = tokens[tokens.len() - 1].span();
+		if let [TokenTree::Group(group)] = tokens {
+			if let Some(rounding) = parse_rounding(group) {
+				return Ok(ParsedOperand::Rounding(rounding));
+			}
+		}
+
+		// Decorators: `{k1}` and `{z}`
+		let mut op_mask = Register::None;
+		let mut zeroing_masking = false;
+		while let [rest @ .., TokenTree::Group(group)] = tokens {
+			if rest.is_empty() || group.delimiter() != Delimiter::Brace {
+				break;
+			}
+			let decorator = match group.stream().into_iter().collect::<Vec<_>>().as_slice() {
+				[TokenTree::Ident(ident)] => ident.to_string().to_lowercase(),
+				_ => String::new(),
+			};
+			if decorator == "z" && !zeroing_masking {
+				zeroing_masking = true;
+			} else if op_mask == Register::None && decorator.len() == 2 && decorator.starts_with('k') && "1234567".contains(&decorator[1..]) {
+				op_mask = self.tables.register(&decorator).unwrap_or_default();
+			} else {
+				return Err(Error::new(group.span(), "Expected an opmask register (`{k1}`-`{k7}`) or `{z}`".into()));
+			}
+			tokens = rest;
+		}
+
+		let kind = match tokens {
+			[TokenTree::Group(group)] if group.delimiter() == Delimiter::Brace => OperandKind::Runtime(group.stream()),
+			[TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+				OperandKind::Memory(self.parse_memory(group, Register::None, None)?)
+			}
+			[TokenTree::Ident(segment), TokenTree::Punct(colon), TokenTree::Group(group)]
+				if colon.as_char() == ':' && group.delimiter() == Delimiter::Bracket =>
+			{
+				let segment = self.parse_segment(&segment.to_string(), segment.span())?;
+				OperandKind::Memory(self.parse_memory(group, segment, None)?)
+			}
+			[TokenTree::Ident(size), TokenTree::Ident(ptr), rest @ ..] => {
+				let size_name = size.to_string().to_lowercase();
+				let ptr_name = ptr.to_string().to_lowercase();
+				let is_broadcast = match ptr_name.as_str() {
+					"ptr" => false,
+					"bcst" => true,
+					_ => return Err(Error::new(ptr.span(), "Expected `ptr` or `bcst`".into())),
+				};
+				let size = match SIZE_HINTS.iter().find(|&&(name, _)| name == size_name) {
+					Some(&(name, size)) if !is_broadcast || BROADCAST_SIZE_HINTS.contains(&name) => SizeHint { name, size, is_broadcast },
+					_ => return Err(Error::new(size.span(), format!("Invalid memory size: `{} {}`", size_name, ptr_name))),
+				};
+				match rest {
+					[TokenTree::Group(group)] if group.delimiter() == Delimiter::Bracket => {
+						OperandKind::Memory(self.parse_memory(group, Register::None, Some(size))?)
+					}
+					[TokenTree::Ident(segment), TokenTree::Punct(colon), TokenTree::Group(group)]
+						if colon.as_char() == ':' && group.delimiter() == Delimiter::Bracket =>
+					{
+						let segment = self.parse_segment(&segment.to_string(), segment.span())?;
+						OperandKind::Memory(self.parse_memory(group, segment, Some(size))?)
+					}
+					_ => return Err(Error::with_range(start, end, "Expected a memory operand, eg. `[rax]`".into())),
+				}
+			}
+			[TokenTree::Punct(minus), TokenTree::Literal(literal)] if minus.as_char() == '-' => {
+				let value = -(parse_integer(literal)? as i128);
+				if value < i64::MIN as i128 {
+					return Err(Error::with_range(start, end, "The immediate doesn't fit in 64 bits".into()));
+				}
+				OperandKind::Immediate(value)
+			}
+			[TokenTree::Literal(literal)] => OperandKind::Immediate(parse_integer(literal)? as i128),
+			[TokenTree::Ident(ident), TokenTree::Group(group)] if group.delimiter() == Delimiter::Parenthesis && ident.to_string() == "st" => {
+				let index = match group.stream().into_iter().collect::<Vec<_>>().as_slice() {
+					[TokenTree::Literal(literal)] => parse_integer(literal)?,
+					_ => 8,
+				};
+				if index >= 8 {
+					return Err(Error::new(group.span(), "Expected `st(0)`-`st(7)`".into()));
+				}
+				OperandKind::Register(Register::ST0 + index as u32)
+			}
+			[TokenTree::Ident(ident)] => {
+				let name = ident.to_string();
+				match self.tables.register(&name) {
+					Some(register) if register.is_ip() => {
+						return Err(Error::new(ident.span(), format!("`{}` can only be used in memory operands", name)))
+					}
+					Some(register) => OperandKind::Register(register),
+					None => OperandKind::Label(Name { name, span: ident.span() }),
+				}
+			}
+			_ => return Err(Error::with_range(start, end, "Invalid operand".into())),
+		};
+
+		if let OperandKind::Memory(memory) = &kind {
+			if zeroing_masking {
+				return Err(Error::with_range(start, end, "`{z}` can't be used with a memory operand".into()));
+			}
+			if memory.label.is_some() && self.bitness != 64 {
+				return Err(Error::with_range(start, end, "Label memory operands can only be used in 64-bit code".into()));
+			}
+		}
+		Ok(ParsedOperand::Operand(Operand { kind, op_mask, zeroing_masking, start, end }))
+	}
+
+	fn parse_segment(&self, name: &str, span: Span) -> Result<Register, Error> {
+		match self.tables.register(name) {
+			Some(register) if register.is_segment_register() => Ok(register),
+			_ => Err(Error::new(span, format!("Expected a segment register: `{}`", name))),
+		}
+	}
+
+	fn parse_memory(&self, group: &Group, mut segment: Register, size: Option<SizeHint>) -> Result<Memory, Error> {
+		let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+		let mut tokens = tokens.as_slice();
+		if let [TokenTree::Ident(ident), TokenTree::Punct(colon), rest @ ..] = tokens {
+			if colon.as_char() == ':' {
+				if segment != Register::None {
+					return Err(Error::new(ident.span(), "Duplicate segment override".into()));
+				}
+				segment = self.parse_segment(&ident.to_string(), ident.span())?;
+				tokens = rest;
+			}
+		}
+
+		let mut memory =
+			Memory { size, segment, base: Register::None, index: Register::None, scale: 1, displacement: 0, label: None, runtime: Vec::new() };
+		let mut displacement: i128 = 0;
+		let mut is_rip_relative = false;
+		let mut terms = Vec::new();
+		let mut term_start = 0;
+		let mut is_negative = false;
+		for (i, token) in tokens.iter().enumerate() {
+			if let TokenTree::Punct(punct) = token {
+				if punct.as_char() == '+' || punct.as_char() == '-' {
+					if i == 0 && punct.as_char() == '-' {
+						is_negative = true;
+					} else {
+						terms.push((is_negative, &tokens[term_start..i]));
+						is_negative = punct.as_char() == '-';
+					}
+					term_start = i + 1;
+				}
+			}
+		}
+		terms.push((is_negative, &tokens[term_start..]));
+
+		for (is_negative, term) in terms {
+			match term {
+				[] => return Err(Error::new(group.span(), "Invalid memory operand".into())),
+				[TokenTree::Literal(literal)] => {
+					let value = parse_integer(literal)? as i128;
+					displacement += if is_negative { -value } else { value };
+				}
+				[TokenTree::Group(runtime)] if runtime.delimiter() == Delimiter::Brace => memory.runtime.push((is_negative, runtime.stream())),
+				[TokenTree::Ident(ident)] => {
+					let name = ident.to_string();
+					match self.tables.register(&name) {
+						Some(_) if is_negative => return Err(Error::new(ident.span(), "Registers can't be subtracted".into())),
+						Some(register) if register.is_ip() => {
+							if memory.base != Register::None || is_rip_relative {
+								return Err(Error::new(ident.span(), "Invalid base register".into()));
+							}
+							is_rip_relative = true;
+						}
+						Some(register) if register.is_vector_register() || memory.base != Register::None => {
+							if memory.index != Register::None {
+								return Err(Error::new(ident.span(), "Too many registers".into()));
+							}
+							memory.index = register;
+						}
+						Some(register) => memory.base = register,
+						None if is_negative || memory.label.is_some() => return Err(Error::new(ident.span(), "Invalid label reference".into())),
+						None => memory.label = Some(Name { name, span: ident.span() }),
+					}
+				}
+				[TokenTree::Ident(ident), TokenTree::Punct(star), TokenTree::Literal(literal)]
+				| [TokenTree::Literal(literal), TokenTree::Punct(star), TokenTree::Ident(ident)]
+					if star.as_char() == '*' =>
+				{
+					let register = match self.tables.register(&ident.to_string()) {
+						Some(register) if !register.is_ip() => register,
+						_ => return Err(Error::new(ident.span(), "Expected an index register".into())),
+					};
+					if is_negative {
+						return Err(Error::new(ident.span(), "Registers can't be subtracted".into()));
+					}
+					if memory.index != Register::None {
+						// `[rcx + rdx*4]` when `rcx` was parsed as the index
+						if memory.base != Register::None || memory.index.is_vector_register() {
+							return Err(Error::new(ident.span(), "Too many registers".into()));
+						}
+						memory.base = memory.index;
+					}
+					memory.index = register;
+					memory.scale = match parse_integer(literal)? {
+						1 => 1,
+						2 => 2,
+						4 => 4,
+						8 => 8,
+						_ => return Err(Error::new(literal.span(), "Scale must be 1, 2, 4 or 8".into())),
+					};
+				}
+				_ => {
+					let (first, last) = (&term[0], &term[term.len() - 1]);
+					return Err(Error::with_range(first.span(), last.span(), "Invalid memory operand term".into()));
+				}
+			}
+		}
+
+		if memory.label.is_some() {
+			if memory.base != Register::None || memory.index != Register::None || displacement != 0 || !memory.runtime.is_empty() {
+				return Err(Error::new(group.span(), "A label memory operand can only use `rip` and the label, eg. `[rip + data]`".into()));
+			}
+			memory.base = Register::RIP;
+		} else if is_rip_relative {
+			return Err(Error::new(group.span(), "`rip` can only be used with a label, eg. `[rip + data]`".into()));
+		}
+		memory.displacement = if i64::MIN as i128 <= displacement && displacement <= u64::MAX as i128 {
+			displacement as i64
+		} else {
+			return Err(Error::new(group.span(), "The displacement doesn't fit in 64 bits".into()));
+		};
+		Ok(memory)
+	}
+}
+
+fn parse_rounding(group: &Group) -> Option<Rounding> {
+	if group.delimiter() != Delimiter::Brace {
+		return None;
+	}
+	let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+	let name = match tokens.as_slice() {
+		[TokenTree::Ident(ident)] if ident.to_string() == "sae" => "sae".into(),
+		[TokenTree::Ident(ident), TokenTree::Punct(minus), TokenTree::Ident(sae)] if minus.as_char() == '-' && sae.to_string() == "sae" => {
+			ident.to_string()
+		}
+		_ => return None,
+	};
+	ROUNDING.iter().find(|&&(n, _)| n == name).map(|&(_, rounding)| rounding)
+}
+
+/// Splits the tokens at all `separator`s. Groups are single tokens so only top level separators are used.
+fn split(tokens: &[TokenTree], separator: char) -> Vec<&[TokenTree]> {
+	let mut result = Vec::new();
+	let mut start = 0;
+	for (i, token) in tokens.iter().enumerate() {
+		if let TokenTree::Punct(punct) = token {
+			if punct.as_char() == separator {
+				result.push(&tokens[start..i]);
+				start = i + 1;
+			}
+		}
+	}
+	// Allow a trailing separator
+	if start < tokens.len() || result.is_empty() {
+		result.push(&tokens[start..]);
+	}
+	result
+}
+
+/// Parses an integer literal. Any type suffix (eg. `u64`) is ignored.
+fn parse_integer(literal: &Literal) -> Result<u64, Error> {
+	let text = literal.to_string();
+	let (digits, radix) = if let Some(digits) = text.strip_prefix("0x") {
+		(digits, 16)
+	} else if let Some(digits) = text.strip_prefix("0o") {
+		(digits, 8)
+	} else if let Some(digits) = text.strip_prefix("0b") {
+		(digits, 2)
+	} else {
+		(text.as_str(), 10)
+	};
+	let digits = match digits.find(['i', 'u']) {
+		Some(index) => &digits[..index],
+		None => digits,
+	};
+	let digits: String = digits.chars().filter(|&c| c != '_').collect();
+	u64::from_str_radix(&digits, radix).map_err(|_| Error::new(literal.span(), format!("Expected a 64-bit integer: `{}`", text)))
+}
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use iced_x86::{Code, Mnemonic, Register};
+use std::collections::HashMap;
+
+// Condition code aliases that aren't `Mnemonic`s, eg. `jz` == `je`
+static CC_ALIASES: &[(&str, &str)] = &[
+	("z", "e"),
+	("nz", "ne"),
+	("c", "b"),
+	("nae", "b"),
+	("nb", "ae"),
+	("nc", "ae"),
+	("na", "be"),
+	("nbe", "a"),
+	("ng", "le"),
+	("nge", "l"),
+	("nl", "ge"),
+	("nle", "g"),
+	("pe", "p"),
+	("po", "np"),
+];
+static CC_PREFIXES: &[&str] = &["j", "cmov", "set", "loop"];
+
+pub(crate) struct Tables {
+	registers: HashMap<String, Register>,
+	mnemonics: HashMap<String, Mnemonic>,
+	codes: Vec<Vec<Code>>,
+}
+
+impl Tables {
+	pub(crate) fn new() -> Self {
+		let mut registers = HashMap::new();
+		for register in Register::values() {
+			if register == Register::None || register.is_ip() || register > Register::TMM7 {
+				continue;
+			}
+			let _ = registers.insert(register_name(register), register);
+		}
+		let _ = registers.insert("rip".into(), Register::RIP);
+		let _ = registers.insert("eip".into(), Register::EIP);
+		let _ = registers.insert("st".into(), Register::ST0);
+
+		let mut mnemonics = HashMap::new();
+		for mnemonic in Mnemonic::values() {
+			if mnemonic != Mnemonic::INVALID {
+				let _ = mnemonics.insert(format!("{:?}", mnemonic).to_lowercase(), mnemonic);
+			}
+		}
+
+		let mut codes = vec![Vec::new(); Mnemonic::values().len()];
+		for code in Code::values() {
+			if code.op_code().is_instruction() {
+				codes[code.mnemonic() as usize].push(code);
+			}
+		}
+
+		Self { registers, mnemonics, codes }
+	}
+
+	pub(crate) fn register(&self, name: &str) -> Option<Register> {
+		self.registers.get(&name.to_lowercase()).copied()
+	}
+
+	pub(crate) fn mnemonic(&self, name: &str) -> Option<Mnemonic> {
+		if let Some(&mnemonic) = self.mnemonics.get(name) {
+			return Some(mnemonic);
+		}
+		for &prefix in CC_PREFIXES {
+			if let Some(cc) = name.strip_prefix(prefix) {
+				if let Some(&(_, real_cc)) = CC_ALIASES.iter().find(|&&(alias, _)| alias == cc) {
+					let mut real_name = String::from(prefix);
+					real_name.push_str(real_cc);
+					return self.mnemonics.get(&real_name).copied();
+				}
+			}
+		}
+		None
+	}
+
+	/// Gets all instructions with this mnemonic, including the ones that can't be used in the current mode
+	pub(crate) fn codes(&self, mnemonic: Mnemonic) -> &[Code] {
+		&self.codes[mnemonic as usize]
+	}
+}
+
+/// Gets the name of the register in the `iced_x86::code_asm` module, eg. `r8b`
+pub(crate) fn register_name(register: Register) -> String {
+	let name = format!("{:?}", register).to_lowercase();
+	if Register::R8L <= register && register <= Register::R15L {
+		let mut name = name;
+		let _ = name.pop();
+		name.push('b');
+		name
+	} else {
+		name
+	}
+}
@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use iced_x86::code_asm::*;
+use iced_x86::{Decoder, DecoderOptions, Formatter, IntelFormatter};
+use iced_x86_macros::iced_asm;
+
+fn disasm(bitness: u32, ip: u64, code: &[u8]) -> Vec<String> {
+	let mut decoder = Decoder::with_ip(bitness, code, ip, DecoderOptions::NONE);
+	let mut formatter = IntelFormatter::new();
+	formatter.options_mut().set_space_after_operand_separator(true);
+	formatter.options_mut().set_hex_prefix("0x");
+	formatter.options_mut().set_hex_suffix("");
+	formatter.options_mut().set_uppercase_hex(false);
+	let mut result = Vec::new();
+	for instruction in &mut decoder {
+		assert!(!instruction.is_invalid());
+		let mut output = String::new();
+		formatter.format(&instruction, &mut output);
+		result.push(output);
+	}
+	result
+}
+
+fn assemble(bitness: u32, f: impl FnOnce(&mut CodeAssembler) -> Result<(), IcedError>) -> Vec<u8> {
+	let mut a = CodeAssembler::new(bitness).unwrap();
+	f(&mut a).unwrap();
+	a.assemble(0).unwrap()
+}
+
+#[test]
+fn const_code() {
+	const CODE: [u8; 9] = iced_asm!(64; mov rax, [rdi + 8]; add rax, 1; ret);
+	assert_eq!(CODE, [0x48, 0x8B, 0x47, 0x08, 0x48, 0x83, 0xC0, 0x01, 0xC3]);
+	let code: [u8; 0] = iced_asm!(64;);
+	assert_eq!(code, []);
+}
+
+#[test]
+fn immediates_use_the_shortest_encoding() {
+	let code = iced_asm!(64;
+		add eax, 1;
+		add eax, 0x1234;
+		add al, 1;
+		mov rax, -1;
+		mov rax, 0xFFFF_FFFF;
+		mov rax, 0x1122_3344_5566_7788;
+		mov rax, 0xFFFF_FFFF_FFFF_FFFF;
+		mov eax, 0xFFFF_FFFF;
+		shl eax, 1;
+		shl eax, 3;
+		push 1;
+		push -0x1000;
+		enter 8, 1;
+		and ax, 0xFFF0;
+	);
+	assert_eq!(code[0..3], [0x83, 0xC0, 0x01]);
+	assert_eq!(
+		disasm(64, 0, &code),
+		[
+			"add eax, 1",
+			"add eax, 0x1234",
+			"add al, 1",
+			"mov rax, 0xffffffffffffffff",
+			"mov rax, 0xffffffff",
+			"mov rax, 0x1122334455667788",
+			"mov rax, 0xffffffffffffffff",
+			"mov eax, 0xffffffff",
+			"shl eax, 1",
+			"shl eax, 3",
+			"push 1",
+			"push 0xfffffffffffff000",
+			"enter 8, 1",
+			"and ax, 0xfff0",
+		]
+	);
+	assert_eq!(code.len(), 3 + 5 + 2 + 7 + 10 + 10 + 7 + 5 + 2 + 3 + 2 + 5 + 4 + 4);
+}
+
+#[test]
+fn memory_operands() {
+	let code = iced_asm!(64;
+		mov eax, fs:[rax + rcx*4 - 8];
+		mov eax, [fs:rax];
+		mov qword ptr [rsp + 0x10], 5;
+		movzx eax, byte ptr [rdi];
+		movzx eax, word ptr [4*rdx + r9];
+		lea rax, [rdi + rsi + 0x1000];
+		mov eax, [0x1234];
+		inc dword ptr [rbx];
+		fld qword ptr [rax];
+		vmovups ymm1, [rsi];
+		vgatherdps xmm0, [rax + xmm1*4], xmm2;
+	);
+	assert_eq!(
+		disasm(64, 0, &code),
+		[
+			"mov eax, fs:[rax+rcx*4-8]",
+			"mov eax, fs:[rax]",
+			"mov qword ptr [rsp+0x10], 5",
+			"movzx eax, byte ptr [rdi]",
+			"movzx eax, word ptr [r9+rdx*4]",
+			"lea rax, [rdi+rsi+0x1000]",
+			"mov eax, [0x1234]",
+			"inc dword ptr [rbx]",
+			"fld st, qword ptr [rax]",
+			"vmovups ymm1, [rsi]",
+			"vgatherdps xmm0, [rax+xmm1*4], xmm2",
+		]
+	);
+}
+
+#[test]
+fn other_instructions() {
+	let code = iced_asm!(64;
+		rep movsb;
+		repne scasb;
+		lodsq;
+		stosd;
+		xlatb;
+		lock add dword ptr [rax], 1;
+		xchg [rax], ecx;
+		lock cmpxchg [rdx], r8;
+		fadd st(0), st(3);
+		fxch st(0), st(1);
+		cmovz eax, ecx;
+		setc al;
+		in al, dx;
+		out 0x80, al;
+		imul eax, ecx, 10;
+		int3;
+		ud2;
+	);
+	assert_eq!(
+		disasm(64, 0, &code),
+		[
+			"rep movsb [rdi], [rsi]",
+			"repne scasb [rdi]",
+			"lodsq [rsi]",
+			"stosd [rdi]",
+			"xlat [rbx]",
+			"lock add dword ptr [rax], 1",
+			"xchg [rax], ecx",
+			"lock cmpxchg [rdx], r8",
+			"fadd st, st(3)",
+			"fxch",
+			"cmove eax, ecx",
+			"setb al",
+			"in al, dx",
+			"out 0x80, al",
+			"imul eax, ecx, 0xa",
+			"int3",
+			"ud2",
+		]
+	);
+	assert_eq!(code[0..2], [0xF3, 0xA4]);
+	assert_eq!(iced_asm!(64; fxch st(0), st(1); fld qword ptr [rax]), [0xD9, 0xC9, 0xDD, 0x00]);
+}
+
+#[test]
+fn evex_decorators() {
+	let code = iced_asm!(64;
+		vaddps zmm0{k1}{z}, zmm1, zmm2;
+		vaddps zmm0, zmm1, dword bcst [rax];
+		vaddps zmm0, zmm1, zmm2, {rn-sae};
+		vmaxps zmm0, zmm1, zmm2, {sae};
+		vmovups [rax]{k2}, zmm3;
+		vaddps xmm20, xmm1, xmm2;
+		vpcmpeqd k1{k2}, zmm0, zmm1;
+	);
+	assert_eq!(
+		disasm(64, 0, &code),
+		[
+			"vaddps zmm0{k1}{z}, zmm1, zmm2",
+			"vaddps zmm0, zmm1, [rax]{1to16}",
+			"vaddps zmm0{rne-sae}, zmm1, zmm2",
+			"vmaxps zmm0{sae}, zmm1, zmm2",
+			"vmovups [rax]{k2}, zmm3",
+			"vaddps xmm20, xmm1, xmm2",
+			"vpcmpeqd k1{k2}, zmm0, zmm1",
+		]
+	);
+	// VEX is used if possible
+	assert_eq!(iced_asm!(64; vaddps xmm0, xmm1, xmm2), [0xC5, 0xF0, 0x58, 0xC2]);
+}
+
+#[test]
+fn labels() {
+	let code = iced_asm!(64;
+		xor eax, eax;
+	again:
+	again2:
+		add eax, dword ptr [rip + data];
+		cmp eax, 100;
+		jb again2;
+		jmp done;
+		nop;
+		call again;
+	done:
+		ret;
+	data:
+		int3;
+	end:
+	);
+	assert_eq!(
+		disasm(64, 0x1000, &code),
+		[
+			"xor eax, eax",
+			"add eax, [0x1016]",
+			"cmp eax, 0x64",
+			"jb short 0x0000000000001002",
+			"jmp short 0x0000000000001015",
+			"nop",
+			"call 0x0000000000001002",
+			"ret",
+			"int3",
+		]
+	);
+	// Branches that can't be short
+	let code = iced_asm!(64; jmp skip; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2;
+		vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2;
+		vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2;
+		vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2;
+		vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2;
+		vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2; vaddps zmm0{k1}{z}, zmm1, zmm2;
+		skip: ret);
+	assert_eq!(code[0..5], [0xE9, 0x8A, 0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn other_bitness() {
+	let code = iced_asm!(16; mov ax, 1; push es; jmp l; l: lodsb; ret);
+	assert_eq!(disasm(16, 0, &code), ["mov ax, 1", "push es", "jmp short 6", "lodsb [si]", "ret"]);
+	let code = iced_asm!(32; push ebp; mov ebp, esp; mov eax, [ebp + 8]; inc eax; pop ebp; ret);
+	assert_eq!(disasm(32, 0, &code), ["push ebp", "mov ebp, esp", "mov eax, [ebp+8]", "inc eax", "pop ebp", "ret"]);
+}
+
+#[test]
+fn closure_code() {
+	let offset = 8;
+	let reg = rcx;
+	let value = 0x1234_5678_9ABC_u64;
+	let f = iced_asm!(64;
+		mov rax, [rdi + {offset}];
+		mov {reg}, {value};
+		add qword ptr [rdi + {offset} + 8 - 8], {reg};
+	top:
+		lock xadd [rax + rcx*4 + 0x10], edx;
+		vaddps zmm0{k1}{z}, zmm1, [rax + {offset}];
+		dec eax;
+		jnz top;
+		ret;
+	);
+	let code = assemble(64, f);
+	assert_eq!(
+		disasm(64, 0, &code),
+		[
+			"mov rax, [rdi+8]",
+			"mov rcx, 0x123456789abc",
+			"add [rdi+8], rcx",
+			"lock xadd [rax+rcx*4+0x10], edx",
+			"vaddps zmm0{k1}{z}, zmm1, [rax+8]",
+			"dec eax",
+			"jne short 0x0000000000000012",
+			"ret",
+		]
+	);
+}
+
+#[test]
+fn closure_labels() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut func = a.create_label();
+	let f = iced_asm!(64;
+		call {func};
+		lea rax, [rip + data];
+		mov ecx, {3};
+		jmp {func};
+	data:
+	);
+	f(&mut a).unwrap();
+	a.set_label(&mut func).unwrap();
+	a.ret().unwrap();
+	let code = a.assemble(0x1000).unwrap();
+	assert_eq!(disasm(64, 0x1000, &code), ["call 0x0000000000001013", "lea rax, [0x1013]", "mov ecx, 3", "jmp short 0x0000000000001013", "ret"]);
+}
+
+#[test]
+fn closure_and_array_are_identical() {
+	let bytes = iced_asm!(64;
+		push rbx;
+		mov rbx, rdi;
+	next:
+		movzx eax, byte ptr [rbx];
+		test al, al;
+		jz done;
+		inc rbx;
+		jmp next;
+	done:
+		sub rbx, rdi;
+		mov rax, rbx;
+		pop rbx;
+		ret;
+	);
+	let rdi_reg = rdi;
+	let f = iced_asm!(64;
+		push rbx;
+		mov rbx, {rdi_reg};
+	next:
+		movzx eax, byte ptr [rbx];
+		test al, al;
+		jz done;
+		inc rbx;
+		jmp next;
+	done:
+		sub rbx, rdi;
+		mov rax, rbx;
+		pop rbx;
+		ret;
+	);
+	assert_eq!(assemble(64, f), bytes);
+}
+
+#[test]
+#[should_panic]
+fn closure_bitness_must_match() {
+	let value = 1;
+	let f = iced_asm!(32; mov eax, {value});
+	let _ = assemble(64, f);
+}
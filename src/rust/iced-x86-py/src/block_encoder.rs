@@ -18,6 +18,7 @@ use pyo3::types::PyBytes;
 /// Args:
 ///     bitness (int): 16, 32 or 64
 ///     fix_branches (bool): (default = ``True``) Fix branches (eg. convert short to near branches if the target is too far away)
+///     jcc_erratum (bool): (default = ``False``) Mitigate the Intel JCC erratum by adding ``NOP`` instructions so branches and macro-fused pairs (eg. ``CMP``/``TEST`` + ``Jcc``) don't cross or end on a 32-byte boundary. The code must be stored at the base IP passed to :class:`BlockEncoder.encode` (or at an address with the same low 5 bits)
 ///
 /// Raises:
 ///     ValueError: If `bitness` is invalid
@@ -58,10 +59,13 @@ pub(crate) struct BlockEncoder {
 #[pymethods]
 impl BlockEncoder {
 	#[new]
-	#[pyo3(text_signature = "(bitness, fix_branches = true)")]
-	#[pyo3(signature = (bitness, fix_branches = true))]
-	fn new(bitness: u32, fix_branches: bool) -> PyResult<BlockEncoder> {
-		let options = if fix_branches { iced_x86::BlockEncoderOptions::NONE } else { iced_x86::BlockEncoderOptions::DONT_FIX_BRANCHES };
+	#[pyo3(text_signature = "(bitness, fix_branches = true, jcc_erratum = false)")]
+	#[pyo3(signature = (bitness, fix_branches = true, jcc_erratum = false))]
+	fn new(bitness: u32, fix_branches: bool, jcc_erratum: bool) -> PyResult<BlockEncoder> {
+		let mut options = if fix_branches { iced_x86::BlockEncoderOptions::NONE } else { iced_x86::BlockEncoderOptions::DONT_FIX_BRANCHES };
+		if jcc_erratum {
+			options |= iced_x86::BlockEncoderOptions::JCC_ERRATUM;
+		}
 		match bitness {
 			16 | 32 | 64 => Ok(BlockEncoder { instructions: Vec::new(), bitness, options }),
 			_ => Err(PyValueError::new_err("bitness must be 16, 32 or 64")),
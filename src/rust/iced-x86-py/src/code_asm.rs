@@ -0,0 +1,1288 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::enum_utils::{to_mnemonic, to_register};
+use crate::instruction::Instruction;
+use crate::utils::{get_temporary_byte_array_ref, to_value_error};
+use core::hash::{Hash, Hasher};
+use iced_x86::code_asm::AsmOperand;
+use iced_x86::{Mnemonic, Register, RoundingControl};
+use pyo3::exceptions::{PyAttributeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyCFunction, PyDict, PyTuple};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+#[derive(FromPyObject)]
+pub(crate) enum Integer {
+	Unsigned(u64),
+	Signed(i64),
+}
+
+impl Integer {
+	fn to_i64(&self) -> i64 {
+		match *self {
+			Integer::Unsigned(value) => value as i64,
+			Integer::Signed(value) => value,
+		}
+	}
+
+	fn to_data_value(&self, bits: u32) -> PyResult<u64> {
+		let valid = match *self {
+			Integer::Unsigned(value) => bits == 64 || value >> bits == 0,
+			Integer::Signed(value) => bits == 64 || (value >> (bits - 1)) == -1,
+		};
+		if valid {
+			Ok(self.to_i64() as u64)
+		} else {
+			Err(PyValueError::new_err(format!("Value doesn't fit in {bits} bits")))
+		}
+	}
+}
+
+#[derive(FromPyObject)]
+pub(crate) enum MemoryArg {
+	Register(AsmRegister),
+	Memory(AsmMemoryOperand),
+	Label(CodeLabel),
+	Displacement(Integer),
+}
+
+impl MemoryArg {
+	fn into_memory(self) -> AsmMemoryOperand {
+		match self {
+			MemoryArg::Register(register) => AsmMemoryOperand::from_base(register.register),
+			MemoryArg::Memory(mem) => mem,
+			MemoryArg::Label(label) => AsmMemoryOperand { label: Some(label.label), ..AsmMemoryOperand::default() },
+			MemoryArg::Displacement(displ) => AsmMemoryOperand { displ: displ.to_i64(), ..AsmMemoryOperand::default() },
+		}
+	}
+}
+
+#[derive(FromPyObject)]
+enum OperandArg {
+	Register(AsmRegister),
+	Memory(AsmMemoryOperand),
+	Label(CodeLabel),
+	Immediate(Integer),
+}
+
+impl OperandArg {
+	fn to_operand(&self) -> PyResult<AsmOperand> {
+		match self {
+			OperandArg::Register(register) => register.to_operand(),
+			OperandArg::Memory(mem) => mem.to_operand(),
+			OperandArg::Label(label) => Ok(label.label.into()),
+			OperandArg::Immediate(Integer::Unsigned(value)) => Ok((*value).into()),
+			OperandArg::Immediate(Integer::Signed(value)) => Ok((*value).into()),
+		}
+	}
+}
+
+/// A register used by :class:`CodeAssembler`, eg. ``rax``, ``xmm1`` or ``k1``.
+///
+/// All registers are defined in the ``iced_x86.code_asm`` module. Registers can be used
+/// to create memory operands, eg. ``qword_ptr(rax + rcx*8 + 0x10)``.
+///
+/// Args:
+///     `register` (:class:`Register`): The register
+///
+/// Examples:
+///
+/// .. testcode::
+///
+///     from iced_x86 import *
+///     from iced_x86.code_asm import *
+///
+///     a = CodeAssembler(64)
+///     a.vaddpd(zmm1.k3.z, zmm2, zmm3.rz_sae)
+///     a.mov(rax, qword_ptr(rcx + rdx*8 - 0x10))
+#[pyclass(module = "iced_x86._iced_x86_py", from_py_object)]
+#[derive(Copy, Clone)]
+pub(crate) struct AsmRegister {
+	register: Register,
+	op_mask: Register,
+	zeroing_masking: bool,
+	suppress_all_exceptions: bool,
+	rounding_control: RoundingControl,
+}
+
+impl AsmRegister {
+	fn with_op_mask(&self, op_mask: Register) -> Self {
+		Self { op_mask, ..*self }
+	}
+
+	fn with_rounding_control(&self, rounding_control: RoundingControl) -> Self {
+		Self { rounding_control, ..*self }
+	}
+
+	fn to_operand(&self) -> PyResult<AsmOperand> {
+		let mut op = AsmOperand::from(self.register);
+		if self.op_mask != Register::None {
+			op = op.with_op_mask(self.op_mask).map_err(to_value_error)?;
+		}
+		if self.zeroing_masking {
+			op = op.with_zeroing_masking().map_err(to_value_error)?;
+		}
+		if self.suppress_all_exceptions {
+			op = op.with_suppress_all_exceptions().map_err(to_value_error)?;
+		}
+		op.with_rounding_control(self.rounding_control).map_err(to_value_error)
+	}
+}
+
+#[pymethods]
+impl AsmRegister {
+	#[new]
+	#[pyo3(text_signature = "(register)")]
+	fn new(register: u32) -> PyResult<Self> {
+		Ok(Self {
+			register: to_register(register)?,
+			op_mask: Register::None,
+			zeroing_masking: false,
+			suppress_all_exceptions: false,
+			rounding_control: RoundingControl::None,
+		})
+	}
+
+	/// :class:`Register`: Gets the register
+	#[getter]
+	fn register(&self) -> u32 {
+		self.register as u32
+	}
+
+	/// AsmRegister: Adds an op mask register ``{k1}``
+	#[getter]
+	fn k1(&self) -> Self {
+		self.with_op_mask(Register::K1)
+	}
+
+	/// AsmRegister: Adds an op mask register ``{k2}``
+	#[getter]
+	fn k2(&self) -> Self {
+		self.with_op_mask(Register::K2)
+	}
+
+	/// AsmRegister: Adds an op mask register ``{k3}``
+	#[getter]
+	fn k3(&self) -> Self {
+		self.with_op_mask(Register::K3)
+	}
+
+	/// AsmRegister: Adds an op mask register ``{k4}``
+	#[getter]
+	fn k4(&self) -> Self {
+		self.with_op_mask(Register::K4)
+	}
+
+	/// AsmRegister: Adds an op mask register ``{k5}``
+	#[getter]
+	fn k5(&self) -> Self {
+		self.with_op_mask(Register::K5)
+	}
+
+	/// AsmRegister: Adds an op mask register ``{k6}``
+	#[getter]
+	fn k6(&self) -> Self {
+		self.with_op_mask(Register::K6)
+	}
+
+	/// AsmRegister: Adds an op mask register ``{k7}``
+	#[getter]
+	fn k7(&self) -> Self {
+		self.with_op_mask(Register::K7)
+	}
+
+	/// AsmRegister: Enables zeroing-masking ``{z}``
+	#[getter]
+	fn z(&self) -> Self {
+		Self { zeroing_masking: true, ..*self }
+	}
+
+	/// AsmRegister: Enables suppress-all-exceptions ``{sae}``
+	#[getter]
+	fn sae(&self) -> Self {
+		Self { suppress_all_exceptions: true, ..*self }
+	}
+
+	/// AsmRegister: Round to nearest (even) ``{rn-sae}``
+	#[getter]
+	fn rn_sae(&self) -> Self {
+		self.with_rounding_control(RoundingControl::RoundToNearest)
+	}
+
+	/// AsmRegister: Round down (toward -inf) ``{rd-sae}``
+	#[getter]
+	fn rd_sae(&self) -> Self {
+		self.with_rounding_control(RoundingControl::RoundDown)
+	}
+
+	/// AsmRegister: Round up (toward +inf) ``{ru-sae}``
+	#[getter]
+	fn ru_sae(&self) -> Self {
+		self.with_rounding_control(RoundingControl::RoundUp)
+	}
+
+	/// AsmRegister: Round toward zero (truncate) ``{rz-sae}``
+	#[getter]
+	fn rz_sae(&self) -> Self {
+		self.with_rounding_control(RoundingControl::RoundTowardZero)
+	}
+
+	fn __add__(&self, other: MemoryArg) -> PyResult<AsmMemoryOperand> {
+		AsmMemoryOperand::from_base(self.register).add(other.into_memory())
+	}
+
+	fn __radd__(&self, other: Integer) -> PyResult<AsmMemoryOperand> {
+		self.__add__(MemoryArg::Displacement(other))
+	}
+
+	fn __sub__(&self, other: Integer) -> AsmMemoryOperand {
+		AsmMemoryOperand { displ: other.to_i64().wrapping_neg(), ..AsmMemoryOperand::from_base(self.register) }
+	}
+
+	fn __mul__(&self, scale: u32) -> PyResult<AsmMemoryOperand> {
+		match scale {
+			1 | 2 | 4 | 8 => Ok(AsmMemoryOperand { index: self.register, scale, ..AsmMemoryOperand::default() }),
+			_ => Err(PyValueError::new_err("Invalid scale, must be 1, 2, 4 or 8")),
+		}
+	}
+
+	fn __rmul__(&self, scale: u32) -> PyResult<AsmMemoryOperand> {
+		self.__mul__(scale)
+	}
+
+	fn __eq__(&self, other: &Self) -> bool {
+		self.register == other.register
+			&& self.op_mask == other.op_mask
+			&& self.zeroing_masking == other.zeroing_masking
+			&& self.suppress_all_exceptions == other.suppress_all_exceptions
+			&& self.rounding_control == other.rounding_control
+	}
+
+	fn __ne__(&self, other: &Self) -> bool {
+		!self.__eq__(other)
+	}
+
+	fn __hash__(&self) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		(self.register, self.op_mask, self.zeroing_masking, self.suppress_all_exceptions, self.rounding_control).hash(&mut hasher);
+		hasher.finish()
+	}
+}
+
+/// A memory operand used by :class:`CodeAssembler`
+///
+/// It's created by adding registers and integers (eg. ``rax + rcx*8 + 0x10``) or by calling one of
+/// the ``*_ptr()``/``*_bcst()`` functions in the ``iced_x86.code_asm`` module, eg. ``qword_ptr(rax + rcx*8 + 0x10)``.
+///
+/// Examples:
+///
+/// .. testcode::
+///
+///     from iced_x86 import *
+///     from iced_x86.code_asm import *
+///
+///     a = CodeAssembler(64)
+///     a.mov(rax, qword_ptr(rcx + rdx*8 - 0x10).fs)
+///     a.vaddps(zmm1.k1, zmm2, dword_bcst(rax))
+#[pyclass(module = "iced_x86._iced_x86_py", from_py_object)]
+#[derive(Copy, Clone, Default)]
+pub(crate) struct AsmMemoryOperand {
+	base: Register,
+	index: Register,
+	scale: u32,
+	displ: i64,
+	size: u32,
+	is_broadcast: bool,
+	segment: Register,
+	op_mask: Register,
+	label: Option<iced_x86::code_asm::CodeLabel>,
+}
+
+impl AsmMemoryOperand {
+	fn from_base(base: Register) -> Self {
+		Self { base, ..Self::default() }
+	}
+
+	fn add(self, other: Self) -> PyResult<Self> {
+		if self.label.is_some() || other.label.is_some() {
+			return Err(PyValueError::new_err("A label memory operand can't have a base, index or displacement"));
+		}
+		let (base, index, scale) = match (self.base, self.index, other.base, other.index) {
+			(base, index, Register::None, Register::None) => (base, index, self.scale),
+			(Register::None, Register::None, base, index) => (base, index, other.scale),
+			(base, Register::None, Register::None, index) => (base, index, other.scale),
+			(Register::None, index, base, Register::None) => (base, index, self.scale),
+			(base, Register::None, index, Register::None) => (base, index, 1),
+			_ => return Err(PyValueError::new_err("A memory operand can only have one base and one index register")),
+		};
+		Ok(Self {
+			base,
+			index,
+			scale,
+			displ: self.displ.wrapping_add(other.displ),
+			size: if self.size != 0 { self.size } else { other.size },
+			is_broadcast: self.is_broadcast | other.is_broadcast,
+			segment: if self.segment != Register::None { self.segment } else { other.segment },
+			op_mask: if self.op_mask != Register::None { self.op_mask } else { other.op_mask },
+			label: None,
+		})
+	}
+
+	fn with_size(mut self, size: u32, is_broadcast: bool) -> Self {
+		self.size = size;
+		self.is_broadcast = is_broadcast;
+		self
+	}
+
+	fn to_operand(&self) -> PyResult<AsmOperand> {
+		let op = if let Some(label) = self.label {
+			AsmOperand::from(iced_x86::code_asm::AsmMemoryOperand::from(label))
+		} else {
+			AsmOperand::memory(self.base, self.index, if self.scale == 0 { 1 } else { self.scale }, self.displ).map_err(to_value_error)?
+		};
+		let mut op = if self.is_broadcast { op.with_broadcast(self.size) } else { op.with_memory_size(self.size) }.map_err(to_value_error)?;
+		if self.segment != Register::None {
+			op = op.with_segment(self.segment).map_err(to_value_error)?;
+		}
+		if self.op_mask != Register::None {
+			op = op.with_op_mask(self.op_mask).map_err(to_value_error)?;
+		}
+		Ok(op)
+	}
+}
+
+#[pymethods]
+impl AsmMemoryOperand {
+	/// :class:`Register`: Gets the base register or :class:`Register.NONE`
+	#[getter]
+	fn base(&self) -> u32 {
+		self.base as u32
+	}
+
+	/// :class:`Register`: Gets the index register or :class:`Register.NONE`
+	#[getter]
+	fn index(&self) -> u32 {
+		self.index as u32
+	}
+
+	/// int: Gets the index register scale (1, 2, 4 or 8)
+	#[getter]
+	fn scale(&self) -> u32 {
+		if self.scale == 0 {
+			1
+		} else {
+			self.scale
+		}
+	}
+
+	/// int: (``i64``) Gets the displacement
+	#[getter]
+	fn displ(&self) -> i64 {
+		self.displ
+	}
+
+	/// int: Gets the size of the memory operand in bytes or 0 if it's unknown. If it's a broadcast memory operand, it's the size of an element.
+	#[getter]
+	fn size(&self) -> u32 {
+		self.size
+	}
+
+	/// bool: ``True`` if it's a broadcast memory operand
+	#[getter]
+	fn is_broadcast(&self) -> bool {
+		self.is_broadcast
+	}
+
+	/// :class:`Register`: Gets the segment override or :class:`Register.NONE`
+	#[getter]
+	fn segment(&self) -> u32 {
+		self.segment as u32
+	}
+
+	/// AsmMemoryOperand: Adds an ``es`` segment override
+	#[getter]
+	fn es(&self) -> Self {
+		Self { segment: Register::ES, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds a ``cs`` segment override
+	#[getter]
+	fn cs(&self) -> Self {
+		Self { segment: Register::CS, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an ``ss`` segment override
+	#[getter]
+	fn ss(&self) -> Self {
+		Self { segment: Register::SS, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds a ``ds`` segment override
+	#[getter]
+	fn ds(&self) -> Self {
+		Self { segment: Register::DS, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an ``fs`` segment override
+	#[getter]
+	fn fs(&self) -> Self {
+		Self { segment: Register::FS, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds a ``gs`` segment override
+	#[getter]
+	fn gs(&self) -> Self {
+		Self { segment: Register::GS, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an op mask register ``{k1}``
+	#[getter]
+	fn k1(&self) -> Self {
+		Self { op_mask: Register::K1, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an op mask register ``{k2}``
+	#[getter]
+	fn k2(&self) -> Self {
+		Self { op_mask: Register::K2, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an op mask register ``{k3}``
+	#[getter]
+	fn k3(&self) -> Self {
+		Self { op_mask: Register::K3, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an op mask register ``{k4}``
+	#[getter]
+	fn k4(&self) -> Self {
+		Self { op_mask: Register::K4, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an op mask register ``{k5}``
+	#[getter]
+	fn k5(&self) -> Self {
+		Self { op_mask: Register::K5, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an op mask register ``{k6}``
+	#[getter]
+	fn k6(&self) -> Self {
+		Self { op_mask: Register::K6, ..*self }
+	}
+
+	/// AsmMemoryOperand: Adds an op mask register ``{k7}``
+	#[getter]
+	fn k7(&self) -> Self {
+		Self { op_mask: Register::K7, ..*self }
+	}
+
+	fn __add__(&self, other: MemoryArg) -> PyResult<Self> {
+		self.add(other.into_memory())
+	}
+
+	fn __radd__(&self, other: Integer) -> PyResult<Self> {
+		self.__add__(MemoryArg::Displacement(other))
+	}
+
+	fn __sub__(&self, other: Integer) -> PyResult<Self> {
+		self.add(Self { displ: other.to_i64().wrapping_neg(), ..Self::default() })
+	}
+}
+
+/// A label created by :class:`CodeAssembler.create_label`
+///
+/// It's passed to :class:`CodeAssembler.set_label` and can be used as a branch target or
+/// as a memory operand, eg. ``qword_ptr(label)``
+#[pyclass(module = "iced_x86._iced_x86_py", from_py_object)]
+#[derive(Copy, Clone)]
+pub(crate) struct CodeLabel {
+	label: iced_x86::code_asm::CodeLabel,
+}
+
+#[pymethods]
+impl CodeLabel {
+	fn __eq__(&self, other: &Self) -> bool {
+		self.label == other.label
+	}
+
+	fn __ne__(&self, other: &Self) -> bool {
+		self.label != other.label
+	}
+
+	fn __hash__(&self) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		self.label.hash(&mut hasher);
+		hasher.finish()
+	}
+}
+
+/// Result of :class:`CodeAssembler.assemble_options`
+#[pyclass(module = "iced_x86._iced_x86_py")]
+pub(crate) struct CodeAssemblerResult {
+	result: iced_x86::code_asm::CodeAssemblerResult,
+}
+
+#[pymethods]
+impl CodeAssemblerResult {
+	/// int: (``u64``) Base IP of all encoded instructions
+	#[getter]
+	fn rip(&self) -> u64 {
+		self.result.inner.rip
+	}
+
+	/// bytes: The encoded instructions
+	#[getter]
+	fn code_buffer<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+		PyBytes::new(py, &self.result.inner.code_buffer)
+	}
+
+	/// Gets the address of a label
+	///
+	/// Args:
+	///     `label` (CodeLabel): The label
+	///
+	/// Returns:
+	///     int: (``u64``) The address of the label
+	///
+	/// Raises:
+	///     ValueError: If the label is invalid or if it wasn't passed to :class:`CodeAssembler.set_label`
+	#[pyo3(text_signature = "($self, label)")]
+	fn label_ip(&self, label: &CodeLabel) -> PyResult<u64> {
+		self.result.label_ip(&label.label).map_err(to_value_error)
+	}
+}
+
+/// Creates and encodes instructions. It's easier to use than calling ``Instruction.create*()`` methods.
+///
+/// Instructions are added by calling methods with the same name as the mnemonic, eg. ``a.mov(rax, rcx)``.
+/// Reserved words get a ``_`` suffix, eg. ``a.and_(eax, 1)``. All registers and the ``*_ptr()`` memory
+/// operand functions are defined in the ``iced_x86.code_asm`` module.
+///
+/// Args:
+///     `bitness` (int): 16, 32 or 64
+///
+/// Raises:
+///     ValueError: If `bitness` is invalid
+///
+/// Examples:
+///
+/// .. testcode::
+///
+///     from iced_x86 import *
+///     from iced_x86.code_asm import *
+///
+///     a = CodeAssembler(64)
+///
+///     a.push(r15)
+///     a.add(rax, r15)
+///
+///     # If the memory operand can only have one size, ptr() can be used
+///     a.mov(rax, ptr(rax + r12*8 - 0x10))
+///     # ... or use qword_ptr(), dword_bcst() etc
+///     a.mov(rax, qword_ptr(rax + r12*8 - 0x10))
+///
+///     # Labels
+///     loop_lbl1 = a.create_label()
+///     after_loop1 = a.create_label()
+///     a.mov(ecx, 10)
+///     a.set_label(loop_lbl1)
+///     # If needed, a zero-bytes instruction can be used as a label but this is optional
+///     a.zero_bytes()
+///     a.dec(ecx)
+///     a.jp(after_loop1)
+///     a.jne(loop_lbl1)
+///     a.set_label(after_loop1)
+///
+///     # It's possible to reference labels with RIP-relative addressing
+///     skip_data = a.create_label()
+///     data = a.create_label()
+///     a.jmp(skip_data)
+///     a.set_label(data)
+///     a.db(b"\x90\xCC\xF1\x90")
+///     a.set_label(skip_data)
+///     a.lea(rax, ptr(data))
+///
+///     # AVX512 opmasks, {z}, {sae}, {er} and broadcasting are also supported
+///     a.vsqrtps(zmm16.k2.z, dword_bcst(rcx))
+///     a.vsqrtps(zmm1.k2.z, zmm23.rd_sae)
+///
+///     # Add an instruction created by Instruction.create*() methods
+///     a.add_instruction(Instruction.create_reg_reg(Code.ADD_R64_RM64, Register.RAX, Register.RCX))
+///
+///     # Anonymous labels
+///     a.anonymous_label()
+///     a.inc(rax)
+///     a.jne(a.bwd())
+///     a.jmp(a.fwd())
+///     a.nop()
+///     a.anonymous_label()
+///     a.ret()
+///
+///     # Encode all added instructions
+///     encoded = a.assemble(0x1234_5678)
+#[pyclass(module = "iced_x86._iced_x86_py")]
+pub(crate) struct CodeAssembler {
+	asm: iced_x86::code_asm::CodeAssembler,
+}
+
+fn mnemonic_from_name(name: &str) -> Option<Mnemonic> {
+	static MNEMONICS: OnceLock<HashMap<String, Mnemonic>> = OnceLock::new();
+	let map = MNEMONICS
+		.get_or_init(|| iced_x86::Mnemonic::values().filter(|&m| m != Mnemonic::INVALID).map(|m| (format!("{m:?}").to_lowercase(), m)).collect());
+	map.get(name.strip_suffix('_').unwrap_or(name)).copied()
+}
+
+impl CodeAssembler {
+	fn emit_args(&mut self, mnemonic: Mnemonic, args: &Bound<'_, PyTuple>) -> PyResult<()> {
+		let mut operands = Vec::with_capacity(args.len());
+		for arg in args.iter() {
+			operands.push(arg.extract::<OperandArg>()?.to_operand()?);
+		}
+		self.asm.emit(mnemonic, &operands).map_err(to_value_error)
+	}
+
+	fn data<T>(
+		&mut self, data: Vec<Integer>, bits: u32, add: fn(&mut iced_x86::code_asm::CodeAssembler, &[T]) -> Result<(), iced_x86::IcedError>,
+	) -> PyResult<()>
+	where
+		T: TryFrom<u64>,
+	{
+		let mut values = Vec::with_capacity(data.len());
+		for value in &data {
+			let value = value.to_data_value(bits)? & (u64::MAX >> (64 - bits));
+			values.push(T::try_from(value).map_err(|_| PyValueError::new_err("Invalid value"))?);
+		}
+		add(&mut self.asm, &values).map_err(to_value_error)
+	}
+}
+
+#[pymethods]
+impl CodeAssembler {
+	#[new]
+	#[pyo3(text_signature = "(bitness)")]
+	fn new(bitness: u32) -> PyResult<Self> {
+		Ok(Self { asm: iced_x86::code_asm::CodeAssembler::new(bitness).map_err(to_value_error)? })
+	}
+
+	fn __getattr__<'py>(slf: &Bound<'py, Self>, name: &str) -> PyResult<Bound<'py, PyCFunction>> {
+		let mnemonic =
+			mnemonic_from_name(name).ok_or_else(|| PyAttributeError::new_err(format!("'CodeAssembler' object has no attribute '{name}'")))?;
+		let asm: Py<Self> = slf.clone().unbind();
+		PyCFunction::new_closure(slf.py(), None, None, move |args: &Bound<'_, PyTuple>, _kwargs: Option<&Bound<'_, PyDict>>| {
+			asm.borrow_mut(args.py()).emit_args(mnemonic, args)
+		})
+	}
+
+	/// Adds an instruction
+	///
+	/// This is called by all ``a.<mnemonic>(...)`` methods, eg. ``a.add(rax, rcx)`` is the same as
+	/// ``a.emit(Mnemonic.ADD, rax, rcx)``. The instruction is selected by the mnemonic and the operands.
+	/// If there are several encodings, the shortest one is used.
+	///
+	/// Args:
+	///     `mnemonic` (:class:`Mnemonic`): Mnemonic
+	///     `operands` (Union[AsmRegister, AsmMemoryOperand, CodeLabel, int]): Operands
+	///
+	/// Raises:
+	///     ValueError: If the operands are invalid or if no instruction can be created
+	#[pyo3(text_signature = "($self, mnemonic, *operands)")]
+	#[pyo3(signature = (mnemonic, *operands))]
+	fn emit(&mut self, mnemonic: u32, operands: &Bound<'_, PyTuple>) -> PyResult<()> {
+		self.emit_args(to_mnemonic(mnemonic)?, operands)
+	}
+
+	/// int: Gets the bitness (16, 32 or 64)
+	#[getter]
+	fn bitness(&self) -> u32 {
+		self.asm.bitness()
+	}
+
+	/// bool: ``True`` (default value) to use VEX encoding intead of EVEX encoding if we must pick one of the encodings
+	#[getter]
+	fn prefer_vex(&self) -> bool {
+		self.asm.prefer_vex()
+	}
+
+	#[setter]
+	fn set_prefer_vex(&mut self, new_value: bool) {
+		self.asm.set_prefer_vex(new_value)
+	}
+
+	/// bool: ``True`` (default value) to create short branches, ``False`` to create near branches.
+	#[getter]
+	fn prefer_short_branch(&self) -> bool {
+		self.asm.prefer_short_branch()
+	}
+
+	#[setter]
+	fn set_prefer_short_branch(&mut self, new_value: bool) {
+		self.asm.set_prefer_short_branch(new_value)
+	}
+
+	/// Adds an ``XACQUIRE`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn xacquire(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.xacquire();
+		slf
+	}
+
+	/// Adds an ``XRELEASE`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn xrelease(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.xrelease();
+		slf
+	}
+
+	/// Adds a ``LOCK`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn lock(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.lock();
+		slf
+	}
+
+	/// Adds a ``REP`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn rep(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.rep();
+		slf
+	}
+
+	/// Adds a ``REPE``/``REPZ`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn repe(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.repe();
+		slf
+	}
+
+	/// Adds a ``REPE``/``REPZ`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn repz(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.repz();
+		slf
+	}
+
+	/// Adds a ``REPNE``/``REPNZ`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn repne(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.repne();
+		slf
+	}
+
+	/// Adds a ``REPNE``/``REPNZ`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn repnz(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.repnz();
+		slf
+	}
+
+	/// Adds a ``BND`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn bnd(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.bnd();
+		slf
+	}
+
+	/// Adds a ``NOTRACK`` prefix to the next added instruction
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn notrack(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.notrack();
+		slf
+	}
+
+	/// Prefer ``VEX`` encoding if the next instruction can be ``VEX`` and ``EVEX`` encoded
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn vex(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.vex();
+		slf
+	}
+
+	/// Prefer ``EVEX`` encoding if the next instruction can be ``VEX`` and ``EVEX`` encoded
+	///
+	/// Returns:
+	///     CodeAssembler: This instance
+	#[pyo3(text_signature = "($self)")]
+	fn evex(mut slf: PyRefMut<'_, Self>) -> PyRefMut<'_, Self> {
+		let _ = slf.asm.evex();
+		slf
+	}
+
+	/// Gets all added instructions
+	///
+	/// Returns:
+	///     List[Instruction]: All added instructions
+	#[pyo3(text_signature = "($self)")]
+	fn instructions(&self) -> Vec<Instruction> {
+		self.asm.instructions().iter().map(|&instr| Instruction { instr }).collect()
+	}
+
+	/// Takes ownership of all instructions and returns them. Instruction state is also reset (see :class:`CodeAssembler.reset`)
+	///
+	/// Returns:
+	///     List[Instruction]: All added instructions
+	#[pyo3(text_signature = "($self)")]
+	fn take_instructions(&mut self) -> Vec<Instruction> {
+		self.asm.take_instructions().into_iter().map(|instr| Instruction { instr }).collect()
+	}
+
+	/// Resets all instructions and labels so this instance can be re-used
+	#[pyo3(text_signature = "($self)")]
+	fn reset(&mut self) {
+		self.asm.reset()
+	}
+
+	/// Creates a label that can be referenced by instructions
+	///
+	/// Returns:
+	///     CodeLabel: A new label
+	#[pyo3(text_signature = "($self)")]
+	fn create_label(&mut self) -> CodeLabel {
+		CodeLabel { label: self.asm.create_label() }
+	}
+
+	/// Initializes the label to the next instruction
+	///
+	/// Args:
+	///     `label` (CodeLabel): Label created by :class:`CodeAssembler.create_label`
+	///
+	/// Raises:
+	///     ValueError: If the label wasn't created by :class:`CodeAssembler.create_label`, if this method was called multiple times for the same label, or if the next instruction already has a label
+	#[pyo3(text_signature = "($self, label)")]
+	fn set_label(&mut self, label: &Bound<'_, CodeLabel>) -> PyResult<()> {
+		self.asm.set_label(&mut label.borrow_mut().label).map_err(to_value_error)
+	}
+
+	/// Creates an anonymous label that can be referenced by calling :class:`CodeAssembler.bwd` and :class:`CodeAssembler.fwd`
+	///
+	/// Raises:
+	///     ValueError: If the next instruction already has a label
+	#[pyo3(text_signature = "($self)")]
+	fn anonymous_label(&mut self) -> PyResult<()> {
+		self.asm.anonymous_label().map_err(to_value_error)
+	}
+
+	/// Gets the previously created anonymous label created by :class:`CodeAssembler.anonymous_label`
+	///
+	/// Returns:
+	///     CodeLabel: The label
+	///
+	/// Raises:
+	///     ValueError: If no anonymous label has been created yet
+	#[pyo3(text_signature = "($self)")]
+	fn bwd(&mut self) -> PyResult<CodeLabel> {
+		Ok(CodeLabel { label: self.asm.bwd().map_err(to_value_error)? })
+	}
+
+	/// Gets the next anonymous label created by a future call to :class:`CodeAssembler.anonymous_label`
+	///
+	/// Returns:
+	///     CodeLabel: The label
+	///
+	/// Raises:
+	///     ValueError: If an error occurs
+	#[pyo3(text_signature = "($self)")]
+	fn fwd(&mut self) -> PyResult<CodeLabel> {
+		Ok(CodeLabel { label: self.asm.fwd().map_err(to_value_error)? })
+	}
+
+	/// Adds data
+	///
+	/// Args:
+	///     `data` (bytes, bytearray): Data that will be written to the output
+	///
+	/// Raises:
+	///     ValueError: If a prefix was used
+	#[pyo3(text_signature = "($self, data)")]
+	fn db(&mut self, data: &Bound<'_, PyAny>) -> PyResult<()> {
+		let data = unsafe { get_temporary_byte_array_ref(data)? };
+		self.asm.db(data).map_err(to_value_error)
+	}
+
+	/// Adds 16-bit data
+	///
+	/// Args:
+	///     `data` (List[int]): (``u16`` or ``i16``) Data that will be written to the output
+	///
+	/// Raises:
+	///     ValueError: If a prefix was used or if a value doesn't fit in 16 bits
+	#[pyo3(text_signature = "($self, data)")]
+	fn dw(&mut self, data: Vec<Integer>) -> PyResult<()> {
+		self.data(data, 16, iced_x86::code_asm::CodeAssembler::dw)
+	}
+
+	/// Adds 32-bit data
+	///
+	/// Args:
+	///     `data` (List[int]): (``u32`` or ``i32``) Data that will be written to the output
+	///
+	/// Raises:
+	///     ValueError: If a prefix was used or if a value doesn't fit in 32 bits
+	#[pyo3(text_signature = "($self, data)")]
+	fn dd(&mut self, data: Vec<Integer>) -> PyResult<()> {
+		self.data(data, 32, iced_x86::code_asm::CodeAssembler::dd)
+	}
+
+	/// Adds 64-bit data
+	///
+	/// Args:
+	///     `data` (List[int]): (``u64`` or ``i64``) Data that will be written to the output
+	///
+	/// Raises:
+	///     ValueError: If a prefix was used
+	#[pyo3(text_signature = "($self, data)")]
+	fn dq(&mut self, data: Vec<Integer>) -> PyResult<()> {
+		self.data(data, 64, iced_x86::code_asm::CodeAssembler::dq)
+	}
+
+	/// Adds nops, preferring long nops
+	///
+	/// Args:
+	///     `size` (int): Size in bytes of all nops
+	///
+	/// Raises:
+	///     ValueError: If a prefix was used
+	#[pyo3(text_signature = "($self, size)")]
+	fn nops_with_size(&mut self, size: usize) -> PyResult<()> {
+		self.asm.nops_with_size(size).map_err(to_value_error)
+	}
+
+	/// Adds a zero-bytes instruction that can be used as a label
+	///
+	/// Raises:
+	///     ValueError: If a prefix was used
+	#[pyo3(text_signature = "($self)")]
+	fn zero_bytes(&mut self) -> PyResult<()> {
+		self.asm.zero_bytes().map_err(to_value_error)
+	}
+
+	/// Adds an instruction created by the user
+	///
+	/// Args:
+	///     `instruction` (Instruction): Instruction to add
+	///
+	/// Raises:
+	///     ValueError: If a prefix was used
+	#[pyo3(text_signature = "($self, instruction)")]
+	fn add_instruction(&mut self, instruction: &Instruction) -> PyResult<()> {
+		self.asm.add_instruction(instruction.instr).map_err(to_value_error)
+	}
+
+	/// Adds a ``CALL FAR`` instruction
+	///
+	/// Args:
+	///     `selector` (int): (``u16``) Selector/segment
+	///     `offset` (int): (``u32``) Offset within the segment
+	///
+	/// Raises:
+	///     ValueError: If it's 64-bit code
+	#[pyo3(text_signature = "($self, selector, offset)")]
+	fn call_far(&mut self, selector: u16, offset: u32) -> PyResult<()> {
+		self.asm.call_far(selector, offset).map_err(to_value_error)
+	}
+
+	/// Adds a ``JMP FAR`` instruction
+	///
+	/// Args:
+	///     `selector` (int): (``u16``) Selector/segment
+	///     `offset` (int): (``u32``) Offset within the segment
+	///
+	/// Raises:
+	///     ValueError: If it's 64-bit code
+	#[pyo3(text_signature = "($self, selector, offset)")]
+	fn jmp_far(&mut self, selector: u16, offset: u32) -> PyResult<()> {
+		self.asm.jmp_far(selector, offset).map_err(to_value_error)
+	}
+
+	/// Adds an ``XLATB`` instruction
+	///
+	/// Raises:
+	///     ValueError: If a prefix is invalid
+	#[pyo3(text_signature = "($self)")]
+	fn xlatb(&mut self) -> PyResult<()> {
+		self.asm.xlatb().map_err(to_value_error)
+	}
+
+	/// Encodes all added instructions and returns the result
+	///
+	/// Args:
+	///     `ip` (int): (``u64``) Base address of all instructions
+	///
+	/// Returns:
+	///     bytes: The encoded instructions
+	///
+	/// Raises:
+	///     ValueError: If there's an unused label or if one or more instructions couldn't be encoded
+	#[pyo3(text_signature = "($self, ip)")]
+	fn assemble<'py>(&mut self, py: Python<'py>, ip: u64) -> PyResult<Bound<'py, PyBytes>> {
+		Ok(PyBytes::new(py, &self.asm.assemble(ip).map_err(to_value_error)?))
+	}
+
+	/// Encodes all added instructions and returns the result. The result can be used to get the address of labels.
+	///
+	/// Args:
+	///     `ip` (int): (``u64``) Base address of all instructions
+	///     `fix_branches` (bool): (default = ``True``) Fix branches (eg. convert short to near branches if the target is too far away)
+	///
+	/// Returns:
+	///     CodeAssemblerResult: The result
+	///
+	/// Raises:
+	///     ValueError: If there's an unused label or if one or more instructions couldn't be encoded
+	#[pyo3(text_signature = "($self, ip, fix_branches = True)")]
+	#[pyo3(signature = (ip, fix_branches = true))]
+	fn assemble_options(&mut self, ip: u64, fix_branches: bool) -> PyResult<CodeAssemblerResult> {
+		let mut options = iced_x86::BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS;
+		if !fix_branches {
+			options |= iced_x86::BlockEncoderOptions::DONT_FIX_BRANCHES;
+		}
+		Ok(CodeAssemblerResult { result: self.asm.assemble_options(ip, options).map_err(to_value_error)? })
+	}
+}
+
+fn create_memory(mem: MemoryArg, size: u32, is_broadcast: bool) -> AsmMemoryOperand {
+	mem.into_memory().with_size(size, is_broadcast)
+}
+
+/// Creates a memory operand without a size. Use it if the instruction's memory operand can only have one size.
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 0, false)
+}
+
+/// Creates a ``byte ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn byte_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 1, false)
+}
+
+/// Creates a ``word ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn word_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 2, false)
+}
+
+/// Creates a ``dword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn dword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 4, false)
+}
+
+/// Creates a ``qword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn qword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 8, false)
+}
+
+/// Creates a ``mmword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn mmword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 8, false)
+}
+
+/// Creates a ``tbyte ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn tbyte_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 10, false)
+}
+
+/// Creates a ``tword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn tword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 10, false)
+}
+
+/// Creates a ``fword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn fword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 6, false)
+}
+
+/// Creates an ``oword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn oword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 16, false)
+}
+
+/// Creates an ``xmmword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn xmmword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 16, false)
+}
+
+/// Creates a ``ymmword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn ymmword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 32, false)
+}
+
+/// Creates a ``zmmword ptr [mem]`` memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn zmmword_ptr(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 64, false)
+}
+
+/// Creates a ``word bcst [mem]`` broadcast memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn word_bcst(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 2, true)
+}
+
+/// Creates a ``dword bcst [mem]`` broadcast memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn dword_bcst(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 4, true)
+}
+
+/// Creates a ``qword bcst [mem]`` broadcast memory operand
+///
+/// Args:
+///     `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. ``rax + rcx*8 + 0x10``
+///
+/// Returns:
+///     AsmMemoryOperand: A memory operand
+#[pyfunction]
+#[pyo3(text_signature = "(mem)")]
+pub(crate) fn qword_bcst(mem: MemoryArg) -> AsmMemoryOperand {
+	create_memory(mem, 8, true)
+}
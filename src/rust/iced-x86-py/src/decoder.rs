@@ -3,6 +3,7 @@
 
 use crate::constant_offsets::ConstantOffsets;
 use crate::instruction::Instruction;
+use crate::instruction_batch::InstructionBatch;
 use crate::utils::to_value_error;
 use core::slice;
 use pyo3::exceptions::PyTypeError;
@@ -13,7 +14,7 @@ use pyo3::PyTraverseError;
 
 enum DecoderDataRef {
 	None,
-	Vec(#[allow(dead_code)] Vec<u8>),
+	Vec(Vec<u8>),
 	#[allow(dead_code)]
 	PyObj(Py<PyAny>),
 }
@@ -102,7 +103,18 @@ pub(crate) struct Decoder {
 	// * If the decoder ctor was called with a `bytearray` object, data_ref is Vec(copy of `bytearray` data)
 	//   and the decoder holds a reference to this copied data.
 	data_ref: DecoderDataRef,
-	decoder: iced_x86::Decoder<'static>,
+	pub(crate) decoder: iced_x86::Decoder<'static>,
+	pub(crate) options: u32,
+}
+
+impl Decoder {
+	/// Gets the data passed to the constructor or `None` if it's not available
+	pub(crate) fn data(&self) -> Option<&[u8]> {
+		match &self.data_ref {
+			DecoderDataRef::Vec(data) => Some(data),
+			DecoderDataRef::None | DecoderDataRef::PyObj(_) => None,
+		}
+	}
 }
 
 #[pymethods]
@@ -130,7 +142,7 @@ impl Decoder {
 		};
 
 		let decoder = iced_x86::Decoder::try_with_ip(bitness, decoder_data, ip, options).map_err(to_value_error)?;
-		Ok(Decoder { data_ref, decoder })
+		Ok(Decoder { data_ref, decoder, options })
 	}
 
 	/// int: (``u64``) The current ``IP``/``EIP``/``RIP`` value, see also :class:`Decoder.position`
@@ -342,6 +354,39 @@ impl Decoder {
 		self.decoder.decode_out(&mut instruction.instr)
 	}
 
+	/// Decodes many instructions and saves them in a contiguous buffer, see :class:`InstructionBatch`.
+	///
+	/// This is much faster than creating one :class:`Instruction` per decoded instruction, eg. if you need to
+	/// decode a big file and feed the result to NumPy.
+	///
+	/// Args:
+	///     count (int, None): (``usize``) (default = ``None``) Max number of instructions to decode or ``None`` to decode all remaining instructions
+	///
+	/// Returns:
+	///     InstructionBatch: All decoded instructions
+	///
+	/// Examples:
+	///
+	/// .. testcode::
+	///
+	///     from iced_x86 import *
+	///
+	///     data = b"\x48\x89\x5C\x24\x10\x55\xE8\x00\x00\x00\x00"
+	///     decoder = Decoder(64, data, ip=0x1234_5678)
+	///
+	///     batch = decoder.decode_batch(2)
+	///     assert len(batch) == 2
+	///     assert len(batch.data) == 2 * InstructionBatch.RECORD_SIZE
+	///     assert batch[0].code == Code.MOV_RM64_R64
+	///     # The decoder's position was updated
+	///     assert decoder.position == 6
+	///     assert len(decoder.decode_batch()) == 1
+	#[pyo3(text_signature = "($self, count = None)")]
+	#[pyo3(signature = (count = None))]
+	fn decode_batch(slf: &Bound<'_, Self>, count: Option<usize>) -> PyResult<InstructionBatch> {
+		InstructionBatch::decode(slf, count)
+	}
+
 	/// Gets the offsets of the constants (memory displacement and immediate) in the decoded instruction.
 	///
 	/// The caller can check if there are any relocations at those addresses.
@@ -17,8 +17,11 @@ mk_to_enum! {to_register, iced_x86::Register, "Register"}
 mk_to_enum! {to_rounding_control, iced_x86::RoundingControl, "RoundingControl"}
 mk_to_enum! {to_code_size, iced_x86::CodeSize, "CodeSize"}
 mk_to_enum! {to_code, iced_x86::Code, "Code"}
+mk_to_enum! {to_mnemonic, iced_x86::Mnemonic, "Mnemonic"}
 mk_to_enum! {to_op_kind, iced_x86::OpKind, "OpKind"}
 mk_to_enum! {to_memory_size_options, iced_x86::MemorySizeOptions, "MemorySizeOptions"}
+mk_to_enum! {to_number_base, iced_x86::NumberBase, "NumberBase"}
+mk_to_enum! {to_formatter_text_kind, iced_x86::FormatterTextKind, "FormatterTextKind"}
 mk_to_enum! {to_cc_b, iced_x86::CC_b, "CC_b"}
 mk_to_enum! {to_cc_ae, iced_x86::CC_ae, "CC_ae"}
 mk_to_enum! {to_cc_e, iced_x86::CC_e, "CC_e"}
@@ -5,10 +5,15 @@ use crate::enum_utils::{
 	to_cc_a, to_cc_ae, to_cc_b, to_cc_be, to_cc_e, to_cc_g, to_cc_ge, to_cc_l, to_cc_le, to_cc_ne, to_cc_np, to_cc_p, to_memory_size_options,
 	to_register,
 };
+use crate::formatter_options_provider::PyFormatterOptionsProvider;
+use crate::formatter_output::{PyFormatterOutput, TextKindListOutput};
 use crate::instruction::Instruction;
+use crate::symbol_resolver::PySymbolResolver;
 use crate::utils::to_value_error;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 
 // GENERATOR-BEGIN: FormatterSyntax
 // ⚠️This was generated by GENERATOR!🦹‍♂️
@@ -26,10 +31,52 @@ pub(crate) enum FormatterSyntax {
 }
 // GENERATOR-END: FormatterSyntax
 
+/// Shared by a :class:`Formatter` and its symbol resolver and options provider. Exceptions raised by the callables
+/// are saved here and re-raised once the formatter returns.
+#[derive(Default)]
+pub(crate) struct FormatterCallbackState {
+	error: RefCell<Option<PyErr>>,
+	clear_symbol_cache: Cell<bool>,
+}
+
+impl FormatterCallbackState {
+	pub(crate) fn has_error(&self) -> bool {
+		self.error.borrow().is_some()
+	}
+
+	pub(crate) fn set_error(&self, error: PyErr) {
+		let mut current = self.error.borrow_mut();
+		if current.is_none() {
+			*current = Some(error);
+		}
+	}
+
+	pub(crate) fn take_clear_symbol_cache(&self) -> bool {
+		self.clear_symbol_cache.replace(false)
+	}
+
+	fn check_error(&self) -> PyResult<()> {
+		match self.error.borrow_mut().take() {
+			Some(error) => Err(error),
+			None => Ok(()),
+		}
+	}
+}
+
 /// x86 formatter that supports GNU Assembler, Intel XED, masm and nasm syntax
 ///
 /// Args:
 ///     `syntax` (:class:`FormatterSyntax`): Formatter syntax
+///     `symbol_resolver` (Callable, None): (default = ``None``) Called to resolve symbols, eg. so ``call 401230h`` is shown as ``call printf``.
+///         It's called as ``symbol_resolver(instruction, operand, instruction_operand, address, address_size)`` and must return ``None``,
+///         a ``str`` (a :class:`FormatterTextKind.LABEL` symbol at `address`) or a :class:`SymbolResult`.
+///         The results are cached by `address` and `address_size`, see :class:`Formatter.clear_symbol_cache`
+///     `options_provider` (Callable, None): (default = ``None``) Called before each operand is formatted so it can override the options.
+///         It's called as ``options_provider(instruction, operand, instruction_operand, options, number_options)`` where `options` is a
+///         :class:`FormatterOperandOptions` and `number_options` is a :class:`NumberFormattingOptions`. The return value is ignored.
+///
+/// `operand` is the formatter operand index and `instruction_operand` is the instruction operand index or ``None`` if the formatter added the operand.
+/// Exceptions raised by the callables are re-raised by the ``format*()`` methods.
 ///
 /// Examples:
 ///
@@ -49,6 +96,7 @@ pub(crate) enum FormatterSyntax {
 pub(crate) struct Formatter {
 	fmt_output: String,
 	formatter: Box<dyn iced_x86::Formatter>,
+	callback_state: Rc<FormatterCallbackState>,
 }
 
 unsafe impl Send for Formatter {}
@@ -57,21 +105,37 @@ unsafe impl Sync for Formatter {}
 #[pymethods]
 impl Formatter {
 	#[new]
-	#[pyo3(text_signature = "(syntax)")]
-	fn new(syntax: u32) -> PyResult<Self> {
+	#[pyo3(text_signature = "(syntax, symbol_resolver = None, options_provider = None)")]
+	#[pyo3(signature = (syntax, symbol_resolver = None, options_provider = None))]
+	fn new(syntax: u32, symbol_resolver: Option<Py<PyAny>>, options_provider: Option<Py<PyAny>>) -> PyResult<Self> {
+		let callback_state = Rc::new(FormatterCallbackState::default());
+		let symbol_resolver: Option<Box<dyn iced_x86::SymbolResolver>> = symbol_resolver
+			.map(|callback| -> Box<dyn iced_x86::SymbolResolver> { Box::new(PySymbolResolver::new(callback, callback_state.clone())) });
+		let options_provider: Option<Box<dyn iced_x86::FormatterOptionsProvider>> =
+			options_provider.map(|callback| -> Box<dyn iced_x86::FormatterOptionsProvider> {
+				Box::new(PyFormatterOptionsProvider::new(callback, callback_state.clone()))
+			});
 		let formatter: Box<dyn iced_x86::Formatter> = if syntax == FormatterSyntax::Gas as u32 {
-			Box::new(iced_x86::GasFormatter::new())
+			Box::new(iced_x86::GasFormatter::with_options(symbol_resolver, options_provider))
 		} else if syntax == FormatterSyntax::Intel as u32 {
-			Box::new(iced_x86::IntelFormatter::new())
+			Box::new(iced_x86::IntelFormatter::with_options(symbol_resolver, options_provider))
 		} else if syntax == FormatterSyntax::Masm as u32 {
-			Box::new(iced_x86::MasmFormatter::new())
+			Box::new(iced_x86::MasmFormatter::with_options(symbol_resolver, options_provider))
 		} else if syntax == FormatterSyntax::Nasm as u32 {
-			Box::new(iced_x86::NasmFormatter::new())
+			Box::new(iced_x86::NasmFormatter::with_options(symbol_resolver, options_provider))
 		} else {
 			return Err(PyValueError::new_err("Invalid formatter syntax"));
 		};
 
-		Ok(Formatter { fmt_output: String::new(), formatter })
+		Ok(Formatter { fmt_output: String::new(), formatter, callback_state })
+	}
+
+	/// Clears the cached symbols returned by the symbol resolver.
+	///
+	/// Call it if the symbol resolver's symbols have changed.
+	#[pyo3(text_signature = "($self)")]
+	fn clear_symbol_cache(&mut self) {
+		self.callback_state.clear_symbol_cache.set(true);
 	}
 
 	/// Formats the whole instruction: prefixes, mnemonic, operands
@@ -82,10 +146,85 @@ impl Formatter {
 	/// Returns:
 	///     str: The formatted string
 	#[pyo3(text_signature = "($self, instruction)")]
-	fn format(&mut self, instruction: &Instruction) -> &str {
+	fn format(&mut self, instruction: &Instruction) -> PyResult<&str> {
 		self.fmt_output.clear();
 		self.formatter.format(&instruction.instr, &mut self.fmt_output);
-		&self.fmt_output
+		self.callback_state.check_error()?;
+		Ok(&self.fmt_output)
+	}
+
+	/// Formats the whole instruction and writes the text to `output`, eg. to colorize the disassembled code.
+	///
+	/// `output` must have a ``write(text, kind)`` method where `kind` is a :class:`FormatterTextKind`.
+	/// It can also have these methods and if they're missing, ``write(text, kind)`` is called instead:
+	///
+	/// - ``write_prefix(instruction, text, prefix)``: `prefix` is a :class:`PrefixKind`
+	/// - ``write_mnemonic(instruction, text)``
+	/// - ``write_number(instruction, operand, instruction_operand, text, value, number_kind, kind)``: `number_kind` is a :class:`NumberKind`
+	/// - ``write_decorator(instruction, operand, instruction_operand, text, decorator)``: `decorator` is a :class:`DecoratorKind`
+	/// - ``write_register(instruction, operand, instruction_operand, text, register)``: `register` is a :class:`Register`
+	///
+	/// `operand` is the formatter operand index and `instruction_operand` is the instruction operand index or ``None`` if the formatter added the operand.
+	///
+	/// Args:
+	///     `instruction` (Instruction): Instruction to format
+	///     `output` (Any): Receives the formatted text
+	///
+	/// Examples:
+	///
+	/// .. testcode::
+	///
+	///     from iced_x86 import *
+	///
+	///     class Output:
+	///         def __init__(self):
+	///             self.registers = []
+	///         def write(self, text, kind):
+	///             pass
+	///         def write_register(self, instruction, operand, instruction_operand, text, register):
+	///             self.registers.append((operand, text))
+	///
+	///     instr = Decoder(64, b"\x48\x89\x5C\x24\x10").decode()
+	///     output = Output()
+	///     Formatter(FormatterSyntax.MASM).format_output(instr, output)
+	///     assert output.registers == [(0, "rsp"), (1, "rbx")]
+	#[pyo3(text_signature = "($self, instruction, output)")]
+	fn format_output(&mut self, instruction: &Instruction, output: &Bound<'_, PyAny>) -> PyResult<()> {
+		let mut py_output = PyFormatterOutput::new(output)?;
+		self.formatter.format(&instruction.instr, &mut py_output);
+		self.callback_state.check_error()?;
+		py_output.check_error()
+	}
+
+	/// Formats the whole instruction and returns all text and its kind, eg. to colorize the disassembled code.
+	///
+	/// Args:
+	///     `instruction` (Instruction): Instruction to format
+	///
+	/// Returns:
+	///     List[Tuple[str, :class:`FormatterTextKind`]]: All text and its kind
+	///
+	/// Examples:
+	///
+	/// .. testcode::
+	///
+	///     from iced_x86 import *
+	///
+	///     instr = Decoder(64, b"\x48\x83\xC0\x10").decode()
+	///     parts = Formatter(FormatterSyntax.MASM).format_to_list(instr)
+	///     assert parts == [
+	///         ("add", FormatterTextKind.MNEMONIC),
+	///         (" ", FormatterTextKind.TEXT),
+	///         ("rax", FormatterTextKind.REGISTER),
+	///         (",", FormatterTextKind.PUNCTUATION),
+	///         ("10h", FormatterTextKind.NUMBER),
+	///     ]
+	#[pyo3(text_signature = "($self, instruction)")]
+	fn format_to_list(&mut self, instruction: &Instruction) -> PyResult<Vec<(String, u32)>> {
+		let mut output = TextKindListOutput::default();
+		self.formatter.format(&instruction.instr, &mut output);
+		self.callback_state.check_error()?;
+		Ok(output.list)
 	}
 
 	/// Formats the mnemonic and any prefixes
@@ -190,6 +329,7 @@ impl Formatter {
 	fn format_operand(&mut self, instruction: &Instruction, operand: u32) -> PyResult<&str> {
 		self.fmt_output.clear();
 		self.formatter.format_operand(&instruction.instr, &mut self.fmt_output, operand).map_err(to_value_error)?;
+		self.callback_state.check_error()?;
 		Ok(&self.fmt_output)
 	}
 
@@ -215,10 +355,11 @@ impl Formatter {
 	/// Returns:
 	///     str: The formatted string
 	#[pyo3(text_signature = "($self, instruction)")]
-	fn format_all_operands(&mut self, instruction: &Instruction) -> &str {
+	fn format_all_operands(&mut self, instruction: &Instruction) -> PyResult<&str> {
 		self.fmt_output.clear();
 		self.formatter.format_all_operands(&instruction.instr, &mut self.fmt_output);
-		&self.fmt_output
+		self.callback_state.check_error()?;
+		Ok(&self.fmt_output)
 	}
 
 	/// Formats a register
@@ -0,0 +1,286 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::enum_utils::{to_memory_size_options, to_number_base};
+use crate::formatter::FormatterCallbackState;
+use crate::instruction::Instruction;
+use pyo3::prelude::*;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::Mutex;
+
+/// Operand options passed to an options provider, see :class:`Formatter`
+#[pyclass(module = "iced_x86._iced_x86_py")]
+pub(crate) struct FormatterOperandOptions {
+	options: iced_x86::FormatterOperandOptions,
+}
+
+#[pymethods]
+impl FormatterOperandOptions {
+	/// bool: Show branch size (eg. ``SHORT``, ``NEAR PTR``)
+	#[getter]
+	fn branch_size(&self) -> bool {
+		self.options.branch_size()
+	}
+
+	#[setter]
+	fn set_branch_size(&mut self, new_value: bool) {
+		self.options.set_branch_size(new_value);
+	}
+
+	/// bool: If ``True``, show ``RIP`` relative addresses as ``[rip+12345678h]``, else show the linear address eg. ``[1029384756AFBECDh]``
+	#[getter]
+	fn rip_relative_addresses(&self) -> bool {
+		self.options.rip_relative_addresses()
+	}
+
+	#[setter]
+	fn set_rip_relative_addresses(&mut self, new_value: bool) {
+		self.options.set_rip_relative_addresses(new_value);
+	}
+
+	/// :class:`MemorySizeOptions`: Memory size options
+	#[getter]
+	fn memory_size_options(&self) -> u32 {
+		self.options.memory_size_options() as u32
+	}
+
+	#[setter]
+	fn set_memory_size_options(&mut self, new_value: u32) -> PyResult<()> {
+		self.options.set_memory_size_options(to_memory_size_options(new_value)?);
+		Ok(())
+	}
+}
+
+/// Number formatting options passed to an options provider, see :class:`Formatter`
+#[pyclass(module = "iced_x86._iced_x86_py")]
+pub(crate) struct NumberFormattingOptions {
+	prefix: String,
+	suffix: String,
+	digit_separator: String,
+	digit_group_size: u8,
+	number_base: iced_x86::NumberBase,
+	uppercase_hex: bool,
+	small_hex_numbers_in_decimal: bool,
+	add_leading_zero_to_hex_numbers: bool,
+	leading_zeros: bool,
+	signed_number: bool,
+	displacement_leading_zeros: bool,
+}
+
+impl NumberFormattingOptions {
+	fn new(options: &iced_x86::NumberFormattingOptions<'_>) -> Self {
+		Self {
+			prefix: options.prefix.to_owned(),
+			suffix: options.suffix.to_owned(),
+			digit_separator: options.digit_separator.to_owned(),
+			digit_group_size: options.digit_group_size,
+			number_base: options.number_base,
+			uppercase_hex: options.uppercase_hex,
+			small_hex_numbers_in_decimal: options.small_hex_numbers_in_decimal,
+			add_leading_zero_to_hex_numbers: options.add_leading_zero_to_hex_numbers,
+			leading_zeros: options.leading_zeros,
+			signed_number: options.signed_number,
+			displacement_leading_zeros: options.displacement_leading_zeros,
+		}
+	}
+
+	fn copy_to(&self, options: &mut iced_x86::NumberFormattingOptions<'_>) {
+		if options.prefix != self.prefix {
+			options.prefix = intern(&self.prefix);
+		}
+		if options.suffix != self.suffix {
+			options.suffix = intern(&self.suffix);
+		}
+		if options.digit_separator != self.digit_separator {
+			options.digit_separator = intern(&self.digit_separator);
+		}
+		options.digit_group_size = self.digit_group_size;
+		options.number_base = self.number_base;
+		options.uppercase_hex = self.uppercase_hex;
+		options.small_hex_numbers_in_decimal = self.small_hex_numbers_in_decimal;
+		options.add_leading_zero_to_hex_numbers = self.add_leading_zero_to_hex_numbers;
+		options.leading_zeros = self.leading_zeros;
+		options.signed_number = self.signed_number;
+		options.displacement_leading_zeros = self.displacement_leading_zeros;
+	}
+}
+
+// The formatter borrows the strings so they must outlive it. Each unique string is only leaked once.
+fn intern(value: &str) -> &'static str {
+	static STRINGS: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+	let mut strings = STRINGS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+	let strings = strings.get_or_insert_with(HashSet::new);
+	if let Some(&s) = strings.get(value) {
+		s
+	} else {
+		let s: &'static str = Box::leak(value.to_owned().into_boxed_str());
+		let _ = strings.insert(s);
+		s
+	}
+}
+
+#[pymethods]
+impl NumberFormattingOptions {
+	/// str: Number prefix or an empty string
+	#[getter]
+	fn prefix(&self) -> &str {
+		&self.prefix
+	}
+
+	#[setter]
+	fn set_prefix(&mut self, new_value: String) {
+		self.prefix = new_value;
+	}
+
+	/// str: Number suffix or an empty string
+	#[getter]
+	fn suffix(&self) -> &str {
+		&self.suffix
+	}
+
+	#[setter]
+	fn set_suffix(&mut self, new_value: String) {
+		self.suffix = new_value;
+	}
+
+	/// str: Digit separator or an empty string to not use a digit separator
+	#[getter]
+	fn digit_separator(&self) -> &str {
+		&self.digit_separator
+	}
+
+	#[setter]
+	fn set_digit_separator(&mut self, new_value: String) {
+		self.digit_separator = new_value;
+	}
+
+	/// int: (``u8``) Size of a digit group or 0 to not use a digit separator
+	#[getter]
+	fn digit_group_size(&self) -> u8 {
+		self.digit_group_size
+	}
+
+	#[setter]
+	fn set_digit_group_size(&mut self, new_value: u8) {
+		self.digit_group_size = new_value;
+	}
+
+	/// :class:`NumberBase`: Number base
+	#[getter]
+	fn number_base(&self) -> u32 {
+		self.number_base as u32
+	}
+
+	#[setter]
+	fn set_number_base(&mut self, new_value: u32) -> PyResult<()> {
+		self.number_base = to_number_base(new_value)?;
+		Ok(())
+	}
+
+	/// bool: Use uppercase hex digits
+	#[getter]
+	fn uppercase_hex(&self) -> bool {
+		self.uppercase_hex
+	}
+
+	#[setter]
+	fn set_uppercase_hex(&mut self, new_value: bool) {
+		self.uppercase_hex = new_value;
+	}
+
+	/// bool: Small hex numbers (-9 .. 9) are shown in decimal
+	#[getter]
+	fn small_hex_numbers_in_decimal(&self) -> bool {
+		self.small_hex_numbers_in_decimal
+	}
+
+	#[setter]
+	fn set_small_hex_numbers_in_decimal(&mut self, new_value: bool) {
+		self.small_hex_numbers_in_decimal = new_value;
+	}
+
+	/// bool: Add a leading zero to hex numbers if there's no prefix and the number starts with hex digits ``A-F``
+	#[getter]
+	fn add_leading_zero_to_hex_numbers(&self) -> bool {
+		self.add_leading_zero_to_hex_numbers
+	}
+
+	#[setter]
+	fn set_add_leading_zero_to_hex_numbers(&mut self, new_value: bool) {
+		self.add_leading_zero_to_hex_numbers = new_value;
+	}
+
+	/// bool: If ``True``, add leading zeros to numbers, eg. ``1h`` vs ``00000001h``
+	#[getter]
+	fn leading_zeros(&self) -> bool {
+		self.leading_zeros
+	}
+
+	#[setter]
+	fn set_leading_zeros(&mut self, new_value: bool) {
+		self.leading_zeros = new_value;
+	}
+
+	/// bool: If ``True``, the number is signed, and if ``False`` it's an unsigned number
+	#[getter]
+	fn signed_number(&self) -> bool {
+		self.signed_number
+	}
+
+	#[setter]
+	fn set_signed_number(&mut self, new_value: bool) {
+		self.signed_number = new_value;
+	}
+
+	/// bool: Add leading zeros to displacements
+	#[getter]
+	fn displacement_leading_zeros(&self) -> bool {
+		self.displacement_leading_zeros
+	}
+
+	#[setter]
+	fn set_displacement_leading_zeros(&mut self, new_value: bool) {
+		self.displacement_leading_zeros = new_value;
+	}
+}
+
+/// Calls a Python callable so it can override the options of each operand
+pub(crate) struct PyFormatterOptionsProvider {
+	callback: Py<PyAny>,
+	state: Rc<FormatterCallbackState>,
+}
+
+impl PyFormatterOptionsProvider {
+	pub(crate) fn new(callback: Py<PyAny>, state: Rc<FormatterCallbackState>) -> Self {
+		Self { callback, state }
+	}
+
+	fn call(
+		&self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, options: &mut iced_x86::FormatterOperandOptions,
+		number_options: &mut iced_x86::NumberFormattingOptions<'_>,
+	) -> PyResult<()> {
+		Python::attach(|py| {
+			let py_options = Bound::new(py, FormatterOperandOptions { options: *options })?;
+			let py_number_options = Bound::new(py, NumberFormattingOptions::new(number_options))?;
+			let _ = self.callback.call1(py, (Instruction { instr: *instruction }, operand, instruction_operand, &py_options, &py_number_options))?;
+			*options = py_options.borrow().options;
+			py_number_options.borrow().copy_to(number_options);
+			Ok(())
+		})
+	}
+}
+
+impl iced_x86::FormatterOptionsProvider for PyFormatterOptionsProvider {
+	fn operand_options(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>,
+		options: &mut iced_x86::FormatterOperandOptions, number_options: &mut iced_x86::NumberFormattingOptions<'_>,
+	) {
+		if self.state.has_error() {
+			return;
+		}
+		if let Err(e) = self.call(instruction, operand, instruction_operand, options, number_options) {
+			self.state.set_error(e);
+		}
+	}
+}
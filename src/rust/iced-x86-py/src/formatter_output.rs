@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::instruction::Instruction;
+use iced_x86::{DecoratorKind, FormatterTextKind, NumberKind, PrefixKind, Register};
+use pyo3::prelude::*;
+
+/// Calls the methods of a Python object. Only ``write()`` is required, the other methods are optional and if they're
+/// missing, ``write()`` is called instead. The first exception is saved and re-raised by the formatter.
+pub(crate) struct PyFormatterOutput<'py> {
+	write: Bound<'py, PyAny>,
+	write_prefix: Option<Bound<'py, PyAny>>,
+	write_mnemonic: Option<Bound<'py, PyAny>>,
+	write_number: Option<Bound<'py, PyAny>>,
+	write_decorator: Option<Bound<'py, PyAny>>,
+	write_register: Option<Bound<'py, PyAny>>,
+	error: Option<PyErr>,
+}
+
+impl<'py> PyFormatterOutput<'py> {
+	pub(crate) fn new(output: &Bound<'py, PyAny>) -> PyResult<Self> {
+		let get_method = |name: &str| -> PyResult<Option<Bound<'py, PyAny>>> {
+			if output.hasattr(name)? {
+				Ok(Some(output.getattr(name)?))
+			} else {
+				Ok(None)
+			}
+		};
+		Ok(Self {
+			write: output.getattr("write")?,
+			write_prefix: get_method("write_prefix")?,
+			write_mnemonic: get_method("write_mnemonic")?,
+			write_number: get_method("write_number")?,
+			write_decorator: get_method("write_decorator")?,
+			write_register: get_method("write_register")?,
+			error: None,
+		})
+	}
+
+	/// Returns the first exception raised by the output's methods
+	pub(crate) fn check_error(self) -> PyResult<()> {
+		match self.error {
+			Some(error) => Err(error),
+			None => Ok(()),
+		}
+	}
+
+	fn write_default(&self, text: &str, kind: FormatterTextKind) -> PyResult<Bound<'py, PyAny>> {
+		self.write.call1((text, kind as u32))
+	}
+
+	fn set_result(&mut self, result: PyResult<Bound<'py, PyAny>>) {
+		if let Err(error) = result {
+			self.error = Some(error);
+		}
+	}
+}
+
+impl iced_x86::FormatterOutput for PyFormatterOutput<'_> {
+	fn write(&mut self, text: &str, kind: FormatterTextKind) {
+		if self.error.is_none() {
+			let result = self.write_default(text, kind);
+			self.set_result(result);
+		}
+	}
+
+	fn write_prefix(&mut self, instruction: &iced_x86::Instruction, text: &str, prefix: PrefixKind) {
+		if self.error.is_none() {
+			let result = match &self.write_prefix {
+				Some(method) => method.call1((Instruction { instr: *instruction }, text, prefix as u32)),
+				None => self.write_default(text, FormatterTextKind::Prefix),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_mnemonic(&mut self, instruction: &iced_x86::Instruction, text: &str) {
+		if self.error.is_none() {
+			let result = match &self.write_mnemonic {
+				Some(method) => method.call1((Instruction { instr: *instruction }, text)),
+				None => self.write_default(text, FormatterTextKind::Mnemonic),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_number(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, value: u64,
+		number_kind: NumberKind, kind: FormatterTextKind,
+	) {
+		if self.error.is_none() {
+			let result = match &self.write_number {
+				Some(method) => {
+					method.call1((Instruction { instr: *instruction }, operand, instruction_operand, text, value, number_kind as u32, kind as u32))
+				}
+				None => self.write_default(text, kind),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_decorator(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, decorator: DecoratorKind,
+	) {
+		if self.error.is_none() {
+			let result = match &self.write_decorator {
+				Some(method) => method.call1((Instruction { instr: *instruction }, operand, instruction_operand, text, decorator as u32)),
+				None => self.write_default(text, FormatterTextKind::Decorator),
+			};
+			self.set_result(result);
+		}
+	}
+
+	fn write_register(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, text: &str, register: Register,
+	) {
+		if self.error.is_none() {
+			let result = match &self.write_register {
+				Some(method) => method.call1((Instruction { instr: *instruction }, operand, instruction_operand, text, register as u32)),
+				None => self.write_default(text, FormatterTextKind::Register),
+			};
+			self.set_result(result);
+		}
+	}
+}
+
+/// Saves all text and its kind in a list
+#[derive(Default)]
+pub(crate) struct TextKindListOutput {
+	pub(crate) list: Vec<(String, u32)>,
+}
+
+impl iced_x86::FormatterOutput for TextKindListOutput {
+	fn write(&mut self, text: &str, kind: FormatterTextKind) {
+		self.list.push((text.to_owned(), kind as u32));
+	}
+}
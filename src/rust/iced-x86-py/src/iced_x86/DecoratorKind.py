@@ -0,0 +1,43 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+# ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+# pylint: disable=invalid-name
+# pylint: disable=line-too-long
+# pylint: disable=too-many-lines
+
+"""
+Decorator
+"""
+
+import typing
+if typing.TYPE_CHECKING:
+	from ._iced_x86_py import DecoratorKind
+else:
+	DecoratorKind = int
+
+BROADCAST: DecoratorKind = 0 # type: ignore
+"""
+Broadcast decorator, eg. ``{1to4}``
+"""
+ROUNDING_CONTROL: DecoratorKind = 1 # type: ignore
+"""
+Rounding control, eg. ``{rd-sae}``
+"""
+SUPPRESS_ALL_EXCEPTIONS: DecoratorKind = 2 # type: ignore
+"""
+Suppress all exceptions: ``{sae}``
+"""
+ZEROING_MASKING: DecoratorKind = 3 # type: ignore
+"""
+Zeroing masking: ``{z}``
+"""
+SWIZZLE_MEM_CONV: DecoratorKind = 4 # type: ignore
+"""
+MVEX swizzle or memory up/down conversion: ``{dacb}`` or ``{sint16}``
+"""
+EVICTION_HINT: DecoratorKind = 5 # type: ignore
+"""
+MVEX eviction hint: ``{eh}``
+"""
@@ -0,0 +1,83 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+# ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+# pylint: disable=invalid-name
+# pylint: disable=line-too-long
+# pylint: disable=too-many-lines
+
+"""
+Formatter text kind
+"""
+
+import typing
+if typing.TYPE_CHECKING:
+	from ._iced_x86_py import FormatterTextKind
+else:
+	FormatterTextKind = int
+
+TEXT: FormatterTextKind = 0 # type: ignore
+"""
+Normal text
+"""
+DIRECTIVE: FormatterTextKind = 1 # type: ignore
+"""
+Assembler directive
+"""
+PREFIX: FormatterTextKind = 2 # type: ignore
+"""
+Any prefix
+"""
+MNEMONIC: FormatterTextKind = 3 # type: ignore
+"""
+Any mnemonic
+"""
+KEYWORD: FormatterTextKind = 4 # type: ignore
+"""
+Any keyword
+"""
+OPERATOR: FormatterTextKind = 5 # type: ignore
+"""
+Any operator
+"""
+PUNCTUATION: FormatterTextKind = 6 # type: ignore
+"""
+Any punctuation
+"""
+NUMBER: FormatterTextKind = 7 # type: ignore
+"""
+Number
+"""
+REGISTER: FormatterTextKind = 8 # type: ignore
+"""
+Any register
+"""
+DECORATOR: FormatterTextKind = 9 # type: ignore
+"""
+A decorator, eg. ``sae`` in ``{sae}``
+"""
+SELECTOR_VALUE: FormatterTextKind = 10 # type: ignore
+"""
+Selector value (eg. far ``JMP``/``CALL``)
+"""
+LABEL_ADDRESS: FormatterTextKind = 11 # type: ignore
+"""
+Label address (eg. ``JE XXXXXX``)
+"""
+FUNCTION_ADDRESS: FormatterTextKind = 12 # type: ignore
+"""
+Function address (eg. ``CALL XXXXXX``)
+"""
+DATA: FormatterTextKind = 13 # type: ignore
+"""
+Data symbol
+"""
+LABEL: FormatterTextKind = 14 # type: ignore
+"""
+Label symbol
+"""
+FUNCTION: FormatterTextKind = 15 # type: ignore
+"""
+Function symbol
+"""
@@ -0,0 +1,35 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+# ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+# pylint: disable=invalid-name
+# pylint: disable=line-too-long
+# pylint: disable=too-many-lines
+
+"""
+Number base
+"""
+
+import typing
+if typing.TYPE_CHECKING:
+	from ._iced_x86_py import NumberBase
+else:
+	NumberBase = int
+
+HEXADECIMAL: NumberBase = 0 # type: ignore
+"""
+Hex numbers (base 16)
+"""
+DECIMAL: NumberBase = 1 # type: ignore
+"""
+Decimal numbers (base 10)
+"""
+OCTAL: NumberBase = 2 # type: ignore
+"""
+Octal numbers (base 8)
+"""
+BINARY: NumberBase = 3 # type: ignore
+"""
+Binary numbers (base 2)
+"""
@@ -0,0 +1,51 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+# ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+# pylint: disable=invalid-name
+# pylint: disable=line-too-long
+# pylint: disable=too-many-lines
+
+"""
+Number kind
+"""
+
+import typing
+if typing.TYPE_CHECKING:
+	from ._iced_x86_py import NumberKind
+else:
+	NumberKind = int
+
+INT8: NumberKind = 0 # type: ignore
+"""
+<no docs>
+"""
+UINT8: NumberKind = 1 # type: ignore
+"""
+<no docs>
+"""
+INT16: NumberKind = 2 # type: ignore
+"""
+<no docs>
+"""
+UINT16: NumberKind = 3 # type: ignore
+"""
+<no docs>
+"""
+INT32: NumberKind = 4 # type: ignore
+"""
+<no docs>
+"""
+UINT32: NumberKind = 5 # type: ignore
+"""
+<no docs>
+"""
+INT64: NumberKind = 6 # type: ignore
+"""
+<no docs>
+"""
+UINT64: NumberKind = 7 # type: ignore
+"""
+<no docs>
+"""
@@ -0,0 +1,91 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+# ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+# pylint: disable=invalid-name
+# pylint: disable=line-too-long
+# pylint: disable=too-many-lines
+
+"""
+Prefix
+"""
+
+import typing
+if typing.TYPE_CHECKING:
+	from ._iced_x86_py import PrefixKind
+else:
+	PrefixKind = int
+
+ES: PrefixKind = 0 # type: ignore
+"""
+<no docs>
+"""
+CS: PrefixKind = 1 # type: ignore
+"""
+<no docs>
+"""
+SS: PrefixKind = 2 # type: ignore
+"""
+<no docs>
+"""
+DS: PrefixKind = 3 # type: ignore
+"""
+<no docs>
+"""
+FS: PrefixKind = 4 # type: ignore
+"""
+<no docs>
+"""
+GS: PrefixKind = 5 # type: ignore
+"""
+<no docs>
+"""
+LOCK: PrefixKind = 6 # type: ignore
+"""
+<no docs>
+"""
+REP: PrefixKind = 7 # type: ignore
+"""
+<no docs>
+"""
+REPE: PrefixKind = 8 # type: ignore
+"""
+<no docs>
+"""
+REPNE: PrefixKind = 9 # type: ignore
+"""
+<no docs>
+"""
+OPERAND_SIZE: PrefixKind = 10 # type: ignore
+"""
+<no docs>
+"""
+ADDRESS_SIZE: PrefixKind = 11 # type: ignore
+"""
+<no docs>
+"""
+HINT_NOT_TAKEN: PrefixKind = 12 # type: ignore
+"""
+<no docs>
+"""
+HINT_TAKEN: PrefixKind = 13 # type: ignore
+"""
+<no docs>
+"""
+BND: PrefixKind = 14 # type: ignore
+"""
+<no docs>
+"""
+NOTRACK: PrefixKind = 15 # type: ignore
+"""
+<no docs>
+"""
+XACQUIRE: PrefixKind = 16 # type: ignore
+"""
+<no docs>
+"""
+XRELEASE: PrefixKind = 17 # type: ignore
+"""
+<no docs>
+"""
@@ -0,0 +1,31 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+# ⚠️This file was generated by GENERATOR!🦹‍♂️
+
+# pylint: disable=invalid-name
+# pylint: disable=line-too-long
+# pylint: disable=too-many-lines
+
+"""
+Symbol flags
+"""
+
+import typing
+if typing.TYPE_CHECKING:
+	from ._iced_x86_py import SymbolFlags
+else:
+	SymbolFlags = int
+
+NONE: SymbolFlags = 0x0000_0000 # type: ignore
+"""
+No bit is set
+"""
+RELATIVE: SymbolFlags = 0x0000_0001 # type: ignore
+"""
+It's a symbol relative to a register, eg. a struct offset ``[ebx+some_struct.field1]``. If this is cleared, it's the address of a symbol.
+"""
+SIGNED: SymbolFlags = 0x0000_0002 # type: ignore
+"""
+It's a signed symbol and it should be displayed as ``-symbol`` or ``reg-symbol`` instead of ``symbol`` or ``reg+symbol``
+"""
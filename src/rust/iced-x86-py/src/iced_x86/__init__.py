@@ -12,22 +12,31 @@ iced-x86 is a blazing fast and correct x86/x64 disassembler, assembler and instr
 """
 
 import typing
+from ._iced_x86_py import AsmMemoryOperand # pylint: disable=import-self
+from ._iced_x86_py import AsmRegister # pylint: disable=import-self
 from ._iced_x86_py import BlockEncoder # pylint: disable=import-self
+from ._iced_x86_py import CodeAssembler # pylint: disable=import-self
+from ._iced_x86_py import CodeAssemblerResult # pylint: disable=import-self
+from ._iced_x86_py import CodeLabel # pylint: disable=import-self
 from ._iced_x86_py import ConstantOffsets # pylint: disable=import-self
 from ._iced_x86_py import Decoder # pylint: disable=import-self
 from ._iced_x86_py import Encoder # pylint: disable=import-self
 from ._iced_x86_py import FastFormatter # pylint: disable=import-self
 from ._iced_x86_py import Formatter # pylint: disable=import-self
+from ._iced_x86_py import FormatterOperandOptions # pylint: disable=import-self
 from ._iced_x86_py import FpuStackIncrementInfo # pylint: disable=import-self
 from ._iced_x86_py import Instruction # pylint: disable=import-self
+from ._iced_x86_py import InstructionBatch # pylint: disable=import-self
 from ._iced_x86_py import InstructionInfo # pylint: disable=import-self
 from ._iced_x86_py import InstructionInfoFactory # pylint: disable=import-self
 from ._iced_x86_py import MemoryOperand # pylint: disable=import-self
 from ._iced_x86_py import MemorySizeExt # pylint: disable=import-self
 from ._iced_x86_py import MemorySizeInfo # pylint: disable=import-self
+from ._iced_x86_py import NumberFormattingOptions # pylint: disable=import-self
 from ._iced_x86_py import OpCodeInfo # pylint: disable=import-self
 from ._iced_x86_py import RegisterExt # pylint: disable=import-self
 from ._iced_x86_py import RegisterInfo # pylint: disable=import-self
+from ._iced_x86_py import SymbolResult # pylint: disable=import-self
 from ._iced_x86_py import UsedMemory # pylint: disable=import-self
 from ._iced_x86_py import UsedRegister # pylint: disable=import-self
 from . import CC_a
@@ -48,10 +57,12 @@ from . import ConditionCode
 from . import CpuidFeature
 from . import DecoderError
 from . import DecoderOptions
+from . import DecoratorKind
 from . import EncodingKind
 from . import FlowControl
 from . import FormatMnemonicOptions
 from . import FormatterSyntax
+from . import FormatterTextKind
 from . import MandatoryPrefix
 from . import MemorySize
 from . import MemorySizeOptions
@@ -60,14 +71,18 @@ from . import MvexConvFn
 from . import MvexEHBit
 from . import MvexRegMemConv
 from . import MvexTupleTypeLutKind
+from . import NumberBase
+from . import NumberKind
 from . import OpAccess
 from . import OpCodeOperandKind
 from . import OpCodeTableKind
 from . import OpKind
+from . import PrefixKind
 from . import Register
 from . import RepPrefixKind
 from . import RflagsBits
 from . import RoundingControl
+from . import SymbolFlags
 from . import TupleType
 
 if typing.TYPE_CHECKING:
@@ -90,10 +105,12 @@ if typing.TYPE_CHECKING:
 	CpuidFeature_ = _iced_x86_py.CpuidFeature
 	DecoderError_ = _iced_x86_py.DecoderError
 	DecoderOptions_ = _iced_x86_py.DecoderOptions
+	DecoratorKind_ = _iced_x86_py.DecoratorKind
 	EncodingKind_ = _iced_x86_py.EncodingKind
 	FlowControl_ = _iced_x86_py.FlowControl
 	FormatMnemonicOptions_ = _iced_x86_py.FormatMnemonicOptions
 	FormatterSyntax_ = _iced_x86_py.FormatterSyntax
+	FormatterTextKind_ = _iced_x86_py.FormatterTextKind
 	MandatoryPrefix_ = _iced_x86_py.MandatoryPrefix
 	MemorySize_ = _iced_x86_py.MemorySize
 	MemorySizeOptions_ = _iced_x86_py.MemorySizeOptions
@@ -102,14 +119,18 @@ if typing.TYPE_CHECKING:
 	MvexEHBit_ = _iced_x86_py.MvexEHBit
 	MvexRegMemConv_ = _iced_x86_py.MvexRegMemConv
 	MvexTupleTypeLutKind_ = _iced_x86_py.MvexTupleTypeLutKind
+	NumberBase_ = _iced_x86_py.NumberBase
+	NumberKind_ = _iced_x86_py.NumberKind
 	OpAccess_ = _iced_x86_py.OpAccess
 	OpCodeOperandKind_ = _iced_x86_py.OpCodeOperandKind
 	OpCodeTableKind_ = _iced_x86_py.OpCodeTableKind
 	OpKind_ = _iced_x86_py.OpKind
+	PrefixKind_ = _iced_x86_py.PrefixKind
 	Register_ = _iced_x86_py.Register
 	RepPrefixKind_ = _iced_x86_py.RepPrefixKind
 	RflagsBits_ = _iced_x86_py.RflagsBits
 	RoundingControl_ = _iced_x86_py.RoundingControl
+	SymbolFlags_ = _iced_x86_py.SymbolFlags
 	TupleType_ = _iced_x86_py.TupleType
 else:
 	CC_a_ = int
@@ -130,10 +151,12 @@ else:
 	CpuidFeature_ = int
 	DecoderError_ = int
 	DecoderOptions_ = int
+	DecoratorKind_ = int
 	EncodingKind_ = int
 	FlowControl_ = int
 	FormatMnemonicOptions_ = int
 	FormatterSyntax_ = int
+	FormatterTextKind_ = int
 	MandatoryPrefix_ = int
 	MemorySize_ = int
 	MemorySizeOptions_ = int
@@ -142,17 +165,23 @@ else:
 	MvexEHBit_ = int
 	MvexRegMemConv_ = int
 	MvexTupleTypeLutKind_ = int
+	NumberBase_ = int
+	NumberKind_ = int
 	OpAccess_ = int
 	OpCodeOperandKind_ = int
 	OpCodeTableKind_ = int
 	OpKind_ = int
+	PrefixKind_ = int
 	Register_ = int
 	RepPrefixKind_ = int
 	RflagsBits_ = int
 	RoundingControl_ = int
+	SymbolFlags_ = int
 	TupleType_ = int
 
 __all__ = [
+	"AsmMemoryOperand",
+	"AsmRegister",
 	"BlockEncoder",
 	"CC_a",
 	"CC_ae",
@@ -167,6 +196,9 @@ __all__ = [
 	"CC_np",
 	"CC_p",
 	"Code",
+	"CodeAssembler",
+	"CodeAssemblerResult",
+	"CodeLabel",
 	"CodeSize",
 	"ConditionCode",
 	"ConstantOffsets",
@@ -174,15 +206,19 @@ __all__ = [
 	"Decoder",
 	"DecoderError",
 	"DecoderOptions",
+	"DecoratorKind",
 	"Encoder",
 	"EncodingKind",
 	"FastFormatter",
 	"FlowControl",
 	"FormatMnemonicOptions",
 	"Formatter",
+	"FormatterOperandOptions",
 	"FormatterSyntax",
+	"FormatterTextKind",
 	"FpuStackIncrementInfo",
 	"Instruction",
+	"InstructionBatch",
 	"InstructionInfo",
 	"InstructionInfoFactory",
 	"MandatoryPrefix",
@@ -196,17 +232,23 @@ __all__ = [
 	"MvexEHBit",
 	"MvexRegMemConv",
 	"MvexTupleTypeLutKind",
+	"NumberBase",
+	"NumberFormattingOptions",
+	"NumberKind",
 	"OpAccess",
 	"OpCodeInfo",
 	"OpCodeOperandKind",
 	"OpCodeTableKind",
 	"OpKind",
+	"PrefixKind",
 	"Register",
 	"RegisterExt",
 	"RegisterInfo",
 	"RepPrefixKind",
 	"RflagsBits",
 	"RoundingControl",
+	"SymbolFlags",
+	"SymbolResult",
 	"TupleType",
 	"UsedMemory",
 	"UsedRegister",
@@ -228,10 +270,12 @@ __all__ = [
 	"CpuidFeature_",
 	"DecoderError_",
 	"DecoderOptions_",
+	"DecoratorKind_",
 	"EncodingKind_",
 	"FlowControl_",
 	"FormatMnemonicOptions_",
 	"FormatterSyntax_",
+	"FormatterTextKind_",
 	"MandatoryPrefix_",
 	"MemorySize_",
 	"MemorySizeOptions_",
@@ -240,13 +284,17 @@ __all__ = [
 	"MvexEHBit_",
 	"MvexRegMemConv_",
 	"MvexTupleTypeLutKind_",
+	"NumberBase_",
+	"NumberKind_",
 	"OpAccess_",
 	"OpCodeOperandKind_",
 	"OpCodeTableKind_",
 	"OpKind_",
+	"PrefixKind_",
 	"Register_",
 	"RepPrefixKind_",
 	"RflagsBits_",
 	"RoundingControl_",
+	"SymbolFlags_",
 	"TupleType_",
 ]
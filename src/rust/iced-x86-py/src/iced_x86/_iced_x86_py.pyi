@@ -7,7 +7,7 @@
 
 from collections.abc import Iterator
 from enum import IntEnum, IntFlag
-from typing import Any, List, Optional, Union
+from typing import Any, Callable, Dict, List, Optional, Tuple, Union
 
 class CC_a(IntEnum):
 	A = 0
@@ -63,6 +63,9 @@ class DecoderError(IntEnum):
 class DecoderOptions(IntFlag):
 	NONE = 0x0000_0000
 	...
+class DecoratorKind(IntEnum):
+	BROADCAST = 0
+	...
 class EncodingKind(IntEnum):
 	LEGACY = 0
 	...
@@ -75,6 +78,9 @@ class FormatMnemonicOptions(IntFlag):
 class FormatterSyntax(IntEnum):
 	GAS = 0
 	...
+class FormatterTextKind(IntEnum):
+	TEXT = 0
+	...
 class MandatoryPrefix(IntEnum):
 	NONE = 0
 	...
@@ -99,6 +105,12 @@ class MvexRegMemConv(IntEnum):
 class MvexTupleTypeLutKind(IntEnum):
 	INT32 = 0
 	...
+class NumberBase(IntEnum):
+	HEXADECIMAL = 0
+	...
+class NumberKind(IntEnum):
+	INT8 = 0
+	...
 class OpAccess(IntEnum):
 	NONE = 0
 	...
@@ -111,6 +123,9 @@ class OpCodeTableKind(IntEnum):
 class OpKind(IntEnum):
 	REGISTER = 0
 	...
+class PrefixKind(IntEnum):
+	ES = 0
+	...
 class Register(IntEnum):
 	NONE = 0
 	...
@@ -123,10 +138,203 @@ class RflagsBits(IntFlag):
 class RoundingControl(IntEnum):
 	NONE = 0
 	...
+class SymbolFlags(IntFlag):
+	NONE = 0x0000_0000
+	...
 class TupleType(IntEnum):
 	N1 = 0
 	...
 
+class AsmMemoryOperand:
+	"""
+	A memory operand used by `CodeAssembler`
+
+	It's created by adding registers and integers (eg. `rax + rcx*8 + 0x10`) or by calling one of
+	the `*_ptr()`/`*_bcst()` functions in the `iced_x86.code_asm` module, eg. `qword_ptr(rax + rcx*8 + 0x10)`.
+
+	### Examples:
+
+	```python
+	from iced_x86 import *
+	from iced_x86.code_asm import *
+
+	a = CodeAssembler(64)
+	a.mov(rax, qword_ptr(rcx + rdx*8 - 0x10).fs)
+	a.vaddps(zmm1.k1, zmm2, dword_bcst(rax))
+	```
+	"""
+	@property
+	def base(self) -> Register:
+		"""`Register`: Gets the base register or `Register.NONE`"""
+		...
+	@property
+	def index(self) -> Register:
+		"""`Register`: Gets the index register or `Register.NONE`"""
+		...
+	@property
+	def scale(self) -> int:
+		"""int: Gets the index register scale (1, 2, 4 or 8)"""
+		...
+	@property
+	def displ(self) -> int:
+		"""int: (`i64`) Gets the displacement"""
+		...
+	@property
+	def size(self) -> int:
+		"""int: Gets the size of the memory operand in bytes or 0 if it's unknown. If it's a broadcast memory operand, it's the size of an element."""
+		...
+	@property
+	def is_broadcast(self) -> bool:
+		"""bool: `True` if it's a broadcast memory operand"""
+		...
+	@property
+	def segment(self) -> Register:
+		"""`Register`: Gets the segment override or `Register.NONE`"""
+		...
+	@property
+	def es(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an `es` segment override"""
+		...
+	@property
+	def cs(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds a `cs` segment override"""
+		...
+	@property
+	def ss(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an `ss` segment override"""
+		...
+	@property
+	def ds(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds a `ds` segment override"""
+		...
+	@property
+	def fs(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an `fs` segment override"""
+		...
+	@property
+	def gs(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds a `gs` segment override"""
+		...
+	@property
+	def k1(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an op mask register `{k1}`"""
+		...
+	@property
+	def k2(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an op mask register `{k2}`"""
+		...
+	@property
+	def k3(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an op mask register `{k3}`"""
+		...
+	@property
+	def k4(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an op mask register `{k4}`"""
+		...
+	@property
+	def k5(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an op mask register `{k5}`"""
+		...
+	@property
+	def k6(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an op mask register `{k6}`"""
+		...
+	@property
+	def k7(self) -> AsmMemoryOperand:
+		"""AsmMemoryOperand: Adds an op mask register `{k7}`"""
+		...
+	def __add__(self, other: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand: ...
+	def __radd__(self, other: int) -> AsmMemoryOperand: ...
+	def __sub__(self, other: int) -> AsmMemoryOperand: ...
+
+class AsmRegister:
+	"""
+	A register used by `CodeAssembler`, eg. `rax`, `xmm1` or `k1`.
+
+	All registers are defined in the `iced_x86.code_asm` module. Registers can be used
+	to create memory operands, eg. `qword_ptr(rax + rcx*8 + 0x10)`.
+
+	### Args:
+
+	- `register` (`Register`): The register
+
+	### Examples:
+
+	```python
+	from iced_x86 import *
+	from iced_x86.code_asm import *
+
+	a = CodeAssembler(64)
+	a.vaddpd(zmm1.k3.z, zmm2, zmm3.rz_sae)
+	a.mov(rax, qword_ptr(rcx + rdx*8 - 0x10))
+	```
+	"""
+	def __init__(self, register: Register) -> None: ...
+	@property
+	def register(self) -> Register:
+		"""`Register`: Gets the register"""
+		...
+	@property
+	def k1(self) -> AsmRegister:
+		"""AsmRegister: Adds an op mask register `{k1}`"""
+		...
+	@property
+	def k2(self) -> AsmRegister:
+		"""AsmRegister: Adds an op mask register `{k2}`"""
+		...
+	@property
+	def k3(self) -> AsmRegister:
+		"""AsmRegister: Adds an op mask register `{k3}`"""
+		...
+	@property
+	def k4(self) -> AsmRegister:
+		"""AsmRegister: Adds an op mask register `{k4}`"""
+		...
+	@property
+	def k5(self) -> AsmRegister:
+		"""AsmRegister: Adds an op mask register `{k5}`"""
+		...
+	@property
+	def k6(self) -> AsmRegister:
+		"""AsmRegister: Adds an op mask register `{k6}`"""
+		...
+	@property
+	def k7(self) -> AsmRegister:
+		"""AsmRegister: Adds an op mask register `{k7}`"""
+		...
+	@property
+	def z(self) -> AsmRegister:
+		"""AsmRegister: Enables zeroing-masking `{z}`"""
+		...
+	@property
+	def sae(self) -> AsmRegister:
+		"""AsmRegister: Enables suppress-all-exceptions `{sae}`"""
+		...
+	@property
+	def rn_sae(self) -> AsmRegister:
+		"""AsmRegister: Round to nearest (even) `{rn-sae}`"""
+		...
+	@property
+	def rd_sae(self) -> AsmRegister:
+		"""AsmRegister: Round down (toward -inf) `{rd-sae}`"""
+		...
+	@property
+	def ru_sae(self) -> AsmRegister:
+		"""AsmRegister: Round up (toward +inf) `{ru-sae}`"""
+		...
+	@property
+	def rz_sae(self) -> AsmRegister:
+		"""AsmRegister: Round toward zero (truncate) `{rz-sae}`"""
+		...
+	def __add__(self, other: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand: ...
+	def __radd__(self, other: int) -> AsmMemoryOperand: ...
+	def __sub__(self, other: int) -> AsmMemoryOperand: ...
+	def __mul__(self, scale: int) -> AsmMemoryOperand: ...
+	def __rmul__(self, scale: int) -> AsmMemoryOperand: ...
+	def __eq__(self, other: Any) -> bool: ...
+	def __ne__(self, other: Any) -> bool: ...
+	def __hash__(self) -> int: ...
+
 class BlockEncoder:
 	"""
 	Encodes instructions
@@ -141,6 +349,7 @@ class BlockEncoder:
 
 	- `bitness` (int): 16, 32 or 64
 	- `fix_branches` (bool): (default = `True`) Fix branches (eg. convert short to near branches if the target is too far away)
+	- `jcc_erratum` (bool): (default = `False`) Mitigate the Intel JCC erratum by adding `NOP` instructions so branches and macro-fused pairs (eg. `CMP`/`TEST` + `Jcc`) don't cross or end on a 32-byte boundary. The code must be stored at the base IP passed to `BlockEncoder.encode` (or at an address with the same low 5 bits)
 
 	### Raises:
 
@@ -173,7 +382,7 @@ class BlockEncoder:
 	assert data == raw_data
 	```
 	"""
-	def __init__(self, bitness: int, fix_branches: bool = True) -> None: ...
+	def __init__(self, bitness: int, fix_branches: bool = True, jcc_erratum: bool = False) -> None: ...
 	def add(self, instruction: Instruction) -> None:
 		"""
 		Adds an instruction that will be encoded when `BlockEncoder.encode` is called.
@@ -213,6 +422,500 @@ class BlockEncoder:
 		"""
 		...
 
+class CodeAssembler:
+	"""
+	Creates and encodes instructions. It's easier to use than calling `Instruction.create*()` methods.
+
+	Instructions are added by calling methods with the same name as the mnemonic, eg. `a.mov(rax, rcx)`.
+	Reserved words get a `_` suffix, eg. `a.and_(eax, 1)`. All registers and the `*_ptr()` memory
+	operand functions are defined in the `iced_x86.code_asm` module.
+
+	### Args:
+
+	- `bitness` (int): 16, 32 or 64
+
+	### Raises:
+
+	- ValueError: If `bitness` is invalid
+
+	### Examples:
+
+	```python
+	from iced_x86 import *
+	from iced_x86.code_asm import *
+
+	a = CodeAssembler(64)
+
+	a.push(r15)
+	a.add(rax, r15)
+
+	# If the memory operand can only have one size, ptr() can be used
+	a.mov(rax, ptr(rax + r12*8 - 0x10))
+	# ... or use qword_ptr(), dword_bcst() etc
+	a.mov(rax, qword_ptr(rax + r12*8 - 0x10))
+
+	# Labels
+	loop_lbl1 = a.create_label()
+	after_loop1 = a.create_label()
+	a.mov(ecx, 10)
+	a.set_label(loop_lbl1)
+	# If needed, a zero-bytes instruction can be used as a label but this is optional
+	a.zero_bytes()
+	a.dec(ecx)
+	a.jp(after_loop1)
+	a.jne(loop_lbl1)
+	a.set_label(after_loop1)
+
+	# It's possible to reference labels with RIP-relative addressing
+	skip_data = a.create_label()
+	data = a.create_label()
+	a.jmp(skip_data)
+	a.set_label(data)
+	a.db(b"\\x90\\xCC\\xF1\\x90")
+	a.set_label(skip_data)
+	a.lea(rax, ptr(data))
+
+	# AVX512 opmasks, {z}, {sae}, {er} and broadcasting are also supported
+	a.vsqrtps(zmm16.k2.z, dword_bcst(rcx))
+	a.vsqrtps(zmm1.k2.z, zmm23.rd_sae)
+
+	# Add an instruction created by Instruction.create*() methods
+	a.add_instruction(Instruction.create_reg_reg(Code.ADD_R64_RM64, Register.RAX, Register.RCX))
+
+	# Anonymous labels
+	a.anonymous_label()
+	a.inc(rax)
+	a.jne(a.bwd())
+	a.jmp(a.fwd())
+	a.nop()
+	a.anonymous_label()
+	a.ret()
+
+	# Encode all added instructions
+	encoded = a.assemble(0x1234_5678)
+	```
+	"""
+	def __init__(self, bitness: int) -> None: ...
+	def __getattr__(self, name: str) -> Callable[..., None]: ...
+	def emit(self, mnemonic: Mnemonic, *operands: Union[AsmRegister, AsmMemoryOperand, CodeLabel, int]) -> None:
+		"""
+		Adds an instruction
+
+		This is called by all `a.<mnemonic>(...)` methods, eg. `a.add(rax, rcx)` is the same as
+		`a.emit(Mnemonic.ADD, rax, rcx)`. The instruction is selected by the mnemonic and the operands.
+		If there are several encodings, the shortest one is used.
+
+		### Args:
+
+		- `mnemonic` (`Mnemonic`): Mnemonic
+		- `operands` (Union[AsmRegister, AsmMemoryOperand, CodeLabel, int]): Operands
+
+		### Raises:
+
+		- ValueError: If the operands are invalid or if no instruction can be created
+		"""
+		...
+	@property
+	def bitness(self) -> int:
+		"""int: Gets the bitness (16, 32 or 64)"""
+		...
+	@property
+	def prefer_vex(self) -> bool:
+		"""bool: `True` (default value) to use VEX encoding intead of EVEX encoding if we must pick one of the encodings"""
+		...
+	@prefer_vex.setter
+	def prefer_vex(self, new_value: bool) -> None: ...
+	@property
+	def prefer_short_branch(self) -> bool:
+		"""bool: `True` (default value) to create short branches, `False` to create near branches."""
+		...
+	@prefer_short_branch.setter
+	def prefer_short_branch(self, new_value: bool) -> None: ...
+	def xacquire(self) -> CodeAssembler:
+		"""
+		Adds an `XACQUIRE` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def xrelease(self) -> CodeAssembler:
+		"""
+		Adds an `XRELEASE` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def lock(self) -> CodeAssembler:
+		"""
+		Adds a `LOCK` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def rep(self) -> CodeAssembler:
+		"""
+		Adds a `REP` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def repe(self) -> CodeAssembler:
+		"""
+		Adds a `REPE`/`REPZ` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def repz(self) -> CodeAssembler:
+		"""
+		Adds a `REPE`/`REPZ` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def repne(self) -> CodeAssembler:
+		"""
+		Adds a `REPNE`/`REPNZ` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def repnz(self) -> CodeAssembler:
+		"""
+		Adds a `REPNE`/`REPNZ` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def bnd(self) -> CodeAssembler:
+		"""
+		Adds a `BND` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def notrack(self) -> CodeAssembler:
+		"""
+		Adds a `NOTRACK` prefix to the next added instruction
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def vex(self) -> CodeAssembler:
+		"""
+		Prefer `VEX` encoding if the next instruction can be `VEX` and `EVEX` encoded
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def evex(self) -> CodeAssembler:
+		"""
+		Prefer `EVEX` encoding if the next instruction can be `VEX` and `EVEX` encoded
+
+		### Returns:
+
+		- CodeAssembler: This instance
+		"""
+		...
+	def instructions(self) -> List[Instruction]:
+		"""
+		Gets all added instructions
+
+		### Returns:
+
+		- List[Instruction]: All added instructions
+		"""
+		...
+	def take_instructions(self) -> List[Instruction]:
+		"""
+		Takes ownership of all instructions and returns them. Instruction state is also reset (see `CodeAssembler.reset`)
+
+		### Returns:
+
+		- List[Instruction]: All added instructions
+		"""
+		...
+	def reset(self) -> None:
+		"""Resets all instructions and labels so this instance can be re-used"""
+		...
+	def create_label(self) -> CodeLabel:
+		"""
+		Creates a label that can be referenced by instructions
+
+		### Returns:
+
+		- CodeLabel: A new label
+		"""
+		...
+	def set_label(self, label: CodeLabel) -> None:
+		"""
+		Initializes the label to the next instruction
+
+		### Args:
+
+		- `label` (CodeLabel): Label created by `CodeAssembler.create_label`
+
+		### Raises:
+
+		- ValueError: If the label wasn't created by `CodeAssembler.create_label`, if this method was called multiple times for the same label, or if the next instruction already has a label
+		"""
+		...
+	def anonymous_label(self) -> None:
+		"""
+		Creates an anonymous label that can be referenced by calling `CodeAssembler.bwd` and `CodeAssembler.fwd`
+
+		### Raises:
+
+		- ValueError: If the next instruction already has a label
+		"""
+		...
+	def bwd(self) -> CodeLabel:
+		"""
+		Gets the previously created anonymous label created by `CodeAssembler.anonymous_label`
+
+		### Returns:
+
+		- CodeLabel: The label
+
+		### Raises:
+
+		- ValueError: If no anonymous label has been created yet
+		"""
+		...
+	def fwd(self) -> CodeLabel:
+		"""
+		Gets the next anonymous label created by a future call to `CodeAssembler.anonymous_label`
+
+		### Returns:
+
+		- CodeLabel: The label
+
+		### Raises:
+
+		- ValueError: If an error occurs
+		"""
+		...
+	def db(self, data: Union[bytes, bytearray]) -> None:
+		"""
+		Adds data
+
+		### Args:
+
+		- `data` (bytes, bytearray): Data that will be written to the output
+
+		### Raises:
+
+		- ValueError: If a prefix was used
+		"""
+		...
+	def dw(self, data: List[int]) -> None:
+		"""
+		Adds 16-bit data
+
+		### Args:
+
+		- `data` (List[int]): (`u16` or `i16`) Data that will be written to the output
+
+		### Raises:
+
+		- ValueError: If a prefix was used or if a value doesn't fit in 16 bits
+		"""
+		...
+	def dd(self, data: List[int]) -> None:
+		"""
+		Adds 32-bit data
+
+		### Args:
+
+		- `data` (List[int]): (`u32` or `i32`) Data that will be written to the output
+
+		### Raises:
+
+		- ValueError: If a prefix was used or if a value doesn't fit in 32 bits
+		"""
+		...
+	def dq(self, data: List[int]) -> None:
+		"""
+		Adds 64-bit data
+
+		### Args:
+
+		- `data` (List[int]): (`u64` or `i64`) Data that will be written to the output
+
+		### Raises:
+
+		- ValueError: If a prefix was used
+		"""
+		...
+	def nops_with_size(self, size: int) -> None:
+		"""
+		Adds nops, preferring long nops
+
+		### Args:
+
+		- `size` (int): Size in bytes of all nops
+
+		### Raises:
+
+		- ValueError: If a prefix was used
+		"""
+		...
+	def zero_bytes(self) -> None:
+		"""
+		Adds a zero-bytes instruction that can be used as a label
+
+		### Raises:
+
+		- ValueError: If a prefix was used
+		"""
+		...
+	def add_instruction(self, instruction: Instruction) -> None:
+		"""
+		Adds an instruction created by the user
+
+		### Args:
+
+		- `instruction` (Instruction): Instruction to add
+
+		### Raises:
+
+		- ValueError: If a prefix was used
+		"""
+		...
+	def call_far(self, selector: int, offset: int) -> None:
+		"""
+		Adds a `CALL FAR` instruction
+
+		### Args:
+
+		- `selector` (int): (`u16`) Selector/segment
+		- `offset` (int): (`u32`) Offset within the segment
+
+		### Raises:
+
+		- ValueError: If it's 64-bit code
+		"""
+		...
+	def jmp_far(self, selector: int, offset: int) -> None:
+		"""
+		Adds a `JMP FAR` instruction
+
+		### Args:
+
+		- `selector` (int): (`u16`) Selector/segment
+		- `offset` (int): (`u32`) Offset within the segment
+
+		### Raises:
+
+		- ValueError: If it's 64-bit code
+		"""
+		...
+	def xlatb(self) -> None:
+		"""
+		Adds an `XLATB` instruction
+
+		### Raises:
+
+		- ValueError: If a prefix is invalid
+		"""
+		...
+	def assemble(self, ip: int) -> bytes:
+		"""
+		Encodes all added instructions and returns the result
+
+		### Args:
+
+		- `ip` (int): (`u64`) Base address of all instructions
+
+		### Returns:
+
+		- bytes: The encoded instructions
+
+		### Raises:
+
+		- ValueError: If there's an unused label or if one or more instructions couldn't be encoded
+		"""
+		...
+	def assemble_options(self, ip: int, fix_branches: bool = True) -> CodeAssemblerResult:
+		"""
+		Encodes all added instructions and returns the result. The result can be used to get the address of labels.
+
+		### Args:
+
+		- `ip` (int): (`u64`) Base address of all instructions
+		- `fix_branches` (bool): (default = `True`) Fix branches (eg. convert short to near branches if the target is too far away)
+
+		### Returns:
+
+		- CodeAssemblerResult: The result
+
+		### Raises:
+
+		- ValueError: If there's an unused label or if one or more instructions couldn't be encoded
+		"""
+		...
+
+class CodeAssemblerResult:
+	"""Result of `CodeAssembler.assemble_options`"""
+	@property
+	def rip(self) -> int:
+		"""int: (`u64`) Base IP of all encoded instructions"""
+		...
+	@property
+	def code_buffer(self) -> bytes:
+		"""bytes: The encoded instructions"""
+		...
+	def label_ip(self, label: CodeLabel) -> int:
+		"""
+		Gets the address of a label
+
+		### Args:
+
+		- `label` (CodeLabel): The label
+
+		### Returns:
+
+		- int: (`u64`) The address of the label
+
+		### Raises:
+
+		- ValueError: If the label is invalid or if it wasn't passed to `CodeAssembler.set_label`
+		"""
+		...
+
+class CodeLabel:
+	"""
+	A label created by `CodeAssembler.create_label`
+
+	It's passed to `CodeAssembler.set_label` and can be used as a branch target or
+	as a memory operand, eg. `qword_ptr(label)`
+	"""
+	def __eq__(self, other: Any) -> bool: ...
+	def __ne__(self, other: Any) -> bool: ...
+	def __hash__(self) -> int: ...
+
 class ConstantOffsets:
 	"""
 	Contains the offsets of the displacement and immediate.
@@ -586,6 +1289,39 @@ class Decoder:
 		```
 		"""
 		...
+	def decode_batch(self, count: Optional[int] = None) -> InstructionBatch:
+		"""
+		Decodes many instructions and saves them in a contiguous buffer, see `InstructionBatch`.
+
+		This is much faster than creating one `Instruction` per decoded instruction, eg. if you need to
+		decode a big file and feed the result to NumPy.
+
+		### Args:
+
+		- `count` (int, None): (`usize`) (default = `None`) Max number of instructions to decode or `None` to decode all remaining instructions
+
+		### Returns:
+
+		- `InstructionBatch`: All decoded instructions
+
+		### Examples:
+
+		```python
+		from iced_x86 import *
+
+		data = b"\\x48\\x89\\x5C\\x24\\x10\\x55\\xE8\\x00\\x00\\x00\\x00"
+		decoder = Decoder(64, data, ip=0x1234_5678)
+
+		batch = decoder.decode_batch(2)
+		assert len(batch) == 2
+		assert len(batch.data) == 2 * InstructionBatch.RECORD_SIZE
+		assert batch[0].code == Code.MOV_RM64_R64
+		# The decoder's position was updated
+		assert decoder.position == 6
+		assert len(decoder.decode_batch()) == 1
+		```
+		"""
+		...
 	def get_constant_offsets(self, instruction: Instruction) -> ConstantOffsets:
 		"""
 		Gets the offsets of the constants (memory displacement and immediate) in the decoded instruction.
@@ -980,26 +1716,96 @@ class Formatter:
 	### Args:
 
 	- `syntax` (`FormatterSyntax`): Formatter syntax
+	- `symbol_resolver` (Callable, None): (default = `None`) Called to resolve symbols, eg. so `call 401230h` is shown as `call printf`.
+	It's called as `symbol_resolver(instruction, operand, instruction_operand, address, address_size)` and must return `None`,
+	a `str` (a `FormatterTextKind.LABEL` symbol at `address`) or a `SymbolResult`.
+	The results are cached by `address` and `address_size`, see `Formatter.clear_symbol_cache`
+	- `options_provider` (Callable, None): (default = `None`) Called before each operand is formatted so it can override the options.
+	It's called as `options_provider(instruction, operand, instruction_operand, options, number_options)` where `options` is a
+	`FormatterOperandOptions` and `number_options` is a `NumberFormattingOptions`. The return value is ignored.
+
+	`operand` is the formatter operand index and `instruction_operand` is the instruction operand index or `None` if the formatter added the operand.
+	Exceptions raised by the callables are re-raised by the `format*()` methods.
+
+	### Examples:
+
+	```python
+	from iced_x86 import *
+
+	data = b"\\x62\\xF2\\x4F\\xDD\\x72\\x50\\x01"
+	decoder = Decoder(64, data)
+	instr = decoder.decode()
+
+	formatter = Formatter(FormatterSyntax.MASM)
+	formatter.uppercase_mnemonics = True
+	disasm = formatter.format(instr)
+	assert disasm == "VCVTNE2PS2BF16 zmm2{k5}{z},zmm6,dword bcst [rax+4]"
+	```
+	"""
+	def __init__(self, syntax: FormatterSyntax, symbol_resolver: Optional[Callable[[Instruction, int, Optional[int], int, int], Union[None, str, SymbolResult]]] = None, options_provider: Optional[Callable[[Instruction, int, Optional[int], FormatterOperandOptions, NumberFormattingOptions], Any]] = None) -> None: ...
+	def clear_symbol_cache(self) -> None:
+		"""
+		Clears the cached symbols returned by the symbol resolver.
+
+		Call it if the symbol resolver's symbols have changed.
+		"""
+		...
+	def format(self, instruction: Instruction) -> str:
+		"""
+		Formats the whole instruction: prefixes, mnemonic, operands
+
+		### Args:
+
+		- `instruction` (Instruction): Instruction to format
+
+		### Returns:
+
+		- str: The formatted string
+		"""
+		...
+	def format_output(self, instruction: Instruction, output: Any) -> None:
+		"""
+		Formats the whole instruction and writes the text to `output`, eg. to colorize the disassembled code.
+
+		`output` must have a `write(text, kind)` method where `kind` is a `FormatterTextKind`.
+		It can also have these methods and if they're missing, `write(text, kind)` is called instead:
+
+		- `write_prefix(instruction, text, prefix)`: `prefix` is a `PrefixKind`
+		- `write_mnemonic(instruction, text)`
+		- `write_number(instruction, operand, instruction_operand, text, value, number_kind, kind)`: `number_kind` is a `NumberKind`
+		- `write_decorator(instruction, operand, instruction_operand, text, decorator)`: `decorator` is a `DecoratorKind`
+		- `write_register(instruction, operand, instruction_operand, text, register)`: `register` is a `Register`
+
+		`operand` is the formatter operand index and `instruction_operand` is the instruction operand index or `None` if the formatter added the operand.
+
+		### Args:
+
+		- `instruction` (Instruction): Instruction to format
+		- `output` (Any): Receives the formatted text
 
-	### Examples:
+		### Examples:
 
-	```python
-	from iced_x86 import *
+		```python
+		from iced_x86 import *
 
-	data = b"\\x62\\xF2\\x4F\\xDD\\x72\\x50\\x01"
-	decoder = Decoder(64, data)
-	instr = decoder.decode()
+		class Output:
+		    def __init__(self):
+		        self.registers = []
+		    def write(self, text, kind):
+		        pass
+		    def write_register(self, instruction, operand, instruction_operand, text, register):
+		        self.registers.append((operand, text))
 
-	formatter = Formatter(FormatterSyntax.MASM)
-	formatter.uppercase_mnemonics = True
-	disasm = formatter.format(instr)
-	assert disasm == "VCVTNE2PS2BF16 zmm2{k5}{z},zmm6,dword bcst [rax+4]"
-	```
-	"""
-	def __init__(self, syntax: FormatterSyntax) -> None: ...
-	def format(self, instruction: Instruction) -> str:
+		instr = Decoder(64, b"\\x48\\x89\\x5C\\x24\\x10").decode()
+		output = Output()
+		Formatter(FormatterSyntax.MASM).format_output(instr, output)
+		assert output.registers == [(0, "rsp"), (1, "rbx")]
+		```
 		"""
-		Formats the whole instruction: prefixes, mnemonic, operands
+		...
+	def format_to_list(self, instruction: Instruction) -> List[Tuple[str, FormatterTextKind]]:
+		"""
+		Formats the whole instruction and returns all text and its kind, eg. to colorize the disassembled code.
 
 		### Args:
 
@@ -1007,7 +1813,23 @@ class Formatter:
 
 		### Returns:
 
-		- str: The formatted string
+		- List[Tuple[str, `FormatterTextKind`]]: All text and its kind
+
+		### Examples:
+
+		```python
+		from iced_x86 import *
+
+		instr = Decoder(64, b"\\x48\\x83\\xC0\\x10").decode()
+		parts = Formatter(FormatterSyntax.MASM).format_to_list(instr)
+		assert parts == [
+		    ("add", FormatterTextKind.MNEMONIC),
+		    (" ", FormatterTextKind.TEXT),
+		    ("rax", FormatterTextKind.REGISTER),
+		    (",", FormatterTextKind.PUNCTUATION),
+		    ("10h", FormatterTextKind.NUMBER),
+		]
+		```
 		"""
 		...
 	def format_mnemonic(self, instruction: Instruction, options: FormatMnemonicOptions = FormatMnemonicOptions.NONE) -> str:
@@ -2116,6 +2938,27 @@ class Formatter:
 	@cc_g.setter
 	def cc_g(self, new_value: CC_g) -> None: ...
 
+class FormatterOperandOptions:
+	"""Operand options passed to an options provider, see `Formatter`"""
+	@property
+	def branch_size(self) -> bool:
+		"""bool: Show branch size (eg. `SHORT`, `NEAR PTR`)"""
+		...
+	@branch_size.setter
+	def branch_size(self, new_value: bool) -> None: ...
+	@property
+	def rip_relative_addresses(self) -> bool:
+		"""bool: If `True`, show `RIP` relative addresses as `[rip+12345678h]`, else show the linear address eg. `[1029384756AFBECDh]`"""
+		...
+	@rip_relative_addresses.setter
+	def rip_relative_addresses(self, new_value: bool) -> None: ...
+	@property
+	def memory_size_options(self) -> MemorySizeOptions:
+		"""`MemorySizeOptions`: Memory size options"""
+		...
+	@memory_size_options.setter
+	def memory_size_options(self, new_value: MemorySizeOptions) -> None: ...
+
 class FpuStackIncrementInfo:
 	"""
 	Contains the FPU `TOP` increment, whether it's conditional and whether the instruction writes to `TOP`
@@ -6746,6 +7589,68 @@ class Instruction:
 	def __bool__(self) -> bool: ...
 	def __len__(self) -> int: ...
 
+class InstructionBatch:
+	"""
+	Instructions decoded by `Decoder.decode_batch`, stored in a contiguous buffer of fixed size records.
+
+	The records are written directly to a `bytearray` which is exposed by `InstructionBatch.data` so no
+	`Instruction` objects are created unless you index the batch, eg. `batch[5]`. That re-decodes the
+	instruction from the decoder's data.
+
+	Each record is `InstructionBatch.RECORD_SIZE` bytes and has these fields (native endian):
+
+	- `ip` (`u64`): `RIP` value
+	- `position` (`u64`): Index of the instruction in the data passed to the `Decoder` constructor
+	- `immediate` (`u64`): Value of the first immediate operand or `0` if there's none
+	- `memory_displacement` (`u64`): `Instruction.memory_displacement` or `0` if there's no memory operand
+	- `code` (`u16`): `Code` enum value
+	- `mnemonic` (`u16`): `Mnemonic` enum value
+	- `op_registers` (`u16[5]`): `Register` enum value of each operand or `Register.NONE`
+	- `memory_base` (`u16`): `Register` enum value
+	- `memory_index` (`u16`): `Register` enum value
+	- `len` (`u8`): Length of the instruction
+	- `op_count` (`u8`): Number of operands
+	- `op_kinds` (`u8[5]`): `OpKind` enum value of each operand or `0` if it's not used
+	- `memory_index_scale` (`u8`): 1, 2, 4 or 8
+	- `flow_control` (`u8`): `FlowControl` enum value
+
+	### Examples:
+
+	```python
+	from iced_x86 import *
+	import struct
+
+	data = b"\\x48\\x89\\x5C\\x24\\x10\\x55\\xE8\\x00\\x00\\x00\\x00"
+	batch = Decoder(64, data, ip=0x1234_5678).decode_batch()
+	assert len(batch) == 3
+
+	# With NumPy: records = numpy.frombuffer(batch.data, dtype=numpy.dtype(InstructionBatch.RECORD_DTYPE))
+	records = list(struct.iter_unpack(InstructionBatch.RECORD_FORMAT, batch.data))
+	ip, position, immediate, displ, code, mnemonic = records[1][:6]
+	assert (ip, position, code, mnemonic) == (0x1234_567D, 5, Code.PUSH_R64, Mnemonic.PUSH)
+
+	# Lazily create an Instruction
+	assert batch[2].flow_control == FlowControl.CALL
+	assert batch[-1].near_branch_target == 0x1234_5683
+	```
+	"""
+	RECORD_SIZE: int
+	"""int: Size of each record in bytes"""
+	RECORD_FORMAT: str
+	"""str: Record format that can be passed to `struct.unpack()` and `struct.iter_unpack()`"""
+	RECORD_DTYPE: Dict[str, Any]
+	"""dict: Record format that can be passed to `numpy.dtype()`"""
+	@property
+	def data(self) -> memoryview:
+		"""
+		memoryview: A read-only view of all records. Its length is `len(batch) * InstructionBatch.RECORD_SIZE`.
+
+		Nothing is copied, eg. `numpy.frombuffer(batch.data, dtype=numpy.dtype(InstructionBatch.RECORD_DTYPE))`.
+		"""
+		...
+	def __len__(self) -> int: ...
+	def __getitem__(self, index: int) -> Instruction: ...
+
 class InstructionInfo:
 	"""Contains accessed registers and memory locations"""
 	def used_registers(self) -> List[UsedRegister]:
@@ -7399,6 +8304,75 @@ class MemorySizeInfo:
 		"""
 		...
 
+class NumberFormattingOptions:
+	"""Number formatting options passed to an options provider, see `Formatter`"""
+	@property
+	def prefix(self) -> str:
+		"""str: Number prefix or an empty string"""
+		...
+	@prefix.setter
+	def prefix(self, new_value: str) -> None: ...
+	@property
+	def suffix(self) -> str:
+		"""str: Number suffix or an empty string"""
+		...
+	@suffix.setter
+	def suffix(self, new_value: str) -> None: ...
+	@property
+	def digit_separator(self) -> str:
+		"""str: Digit separator or an empty string to not use a digit separator"""
+		...
+	@digit_separator.setter
+	def digit_separator(self, new_value: str) -> None: ...
+	@property
+	def digit_group_size(self) -> int:
+		"""int: (`u8`) Size of a digit group or 0 to not use a digit separator"""
+		...
+	@digit_group_size.setter
+	def digit_group_size(self, new_value: int) -> None: ...
+	@property
+	def number_base(self) -> NumberBase:
+		"""`NumberBase`: Number base"""
+		...
+	@number_base.setter
+	def number_base(self, new_value: NumberBase) -> None: ...
+	@property
+	def uppercase_hex(self) -> bool:
+		"""bool: Use uppercase hex digits"""
+		...
+	@uppercase_hex.setter
+	def uppercase_hex(self, new_value: bool) -> None: ...
+	@property
+	def small_hex_numbers_in_decimal(self) -> bool:
+		"""bool: Small hex numbers (-9 .. 9) are shown in decimal"""
+		...
+	@small_hex_numbers_in_decimal.setter
+	def small_hex_numbers_in_decimal(self, new_value: bool) -> None: ...
+	@property
+	def add_leading_zero_to_hex_numbers(self) -> bool:
+		"""bool: Add a leading zero to hex numbers if there's no prefix and the number starts with hex digits `A-F`"""
+		...
+	@add_leading_zero_to_hex_numbers.setter
+	def add_leading_zero_to_hex_numbers(self, new_value: bool) -> None: ...
+	@property
+	def leading_zeros(self) -> bool:
+		"""bool: If `True`, add leading zeros to numbers, eg. `1h` vs `00000001h`"""
+		...
+	@leading_zeros.setter
+	def leading_zeros(self, new_value: bool) -> None: ...
+	@property
+	def signed_number(self) -> bool:
+		"""bool: If `True`, the number is signed, and if `False` it's an unsigned number"""
+		...
+	@signed_number.setter
+	def signed_number(self, new_value: bool) -> None: ...
+	@property
+	def displacement_leading_zeros(self) -> bool:
+		"""bool: Add leading zeros to displacements"""
+		...
+	@displacement_leading_zeros.setter
+	def displacement_leading_zeros(self, new_value: bool) -> None: ...
+
 class OpCodeInfo:
 	"""
 	Opcode info, returned by `Instruction.op_code` or created by the constructor
@@ -8895,6 +9869,55 @@ class RegisterInfo:
 		"""
 		...
 
+class SymbolResult:
+	"""
+	Symbol returned by a symbol resolver passed to `Formatter`
+
+	### Args:
+
+	- `address` (int): (`u64`) The address of the symbol. The formatter shows `symbol+(address_to_format-address)` if the addresses differ
+	- `text` (str, List[Tuple[str, `FormatterTextKind`]]): Symbol text or a list of text parts and their kinds
+	- `kind` (`FormatterTextKind`): (default = `FormatterTextKind.LABEL`) Text kind. Only used if `text` is a `str`
+	- `flags` (`SymbolFlags`): (default = `SymbolFlags.NONE`) Symbol flags
+	- `symbol_size` (`MemorySize`, None): (default = `None`) Symbol size or `None`
+
+	### Raises:
+
+	- ValueError: If one of the enum values is invalid
+
+	### Examples:
+
+	```python
+	from iced_x86 import *
+
+	def resolver(instruction, operand, instruction_operand, address, address_size):
+	    if address == 0x5AA55AA5:
+	        return SymbolResult(address, [("my", FormatterTextKind.FUNCTION), ("_func", FormatterTextKind.DATA)])
+	    return None
+
+	decoder = Decoder(64, b"\\xE8\\x00\\x00\\x00\\x00", ip=0x5AA55AA0)
+	formatter = Formatter(FormatterSyntax.MASM, symbol_resolver=resolver)
+	assert formatter.format(decoder.decode()) == "call my_func"
+	```
+	"""
+	def __init__(self, address: int, text: Union[str, List[Tuple[str, FormatterTextKind]]], kind: FormatterTextKind = FormatterTextKind.LABEL, flags: SymbolFlags = SymbolFlags.NONE, symbol_size: Optional[MemorySize] = None) -> None: ...
+	@property
+	def address(self) -> int:
+		"""int: (`u64`) The address of the symbol"""
+		...
+	@property
+	def text(self) -> List[Tuple[str, FormatterTextKind]]:
+		"""List[Tuple[str, `FormatterTextKind`]]: All text parts and their kinds"""
+		...
+	@property
+	def flags(self) -> SymbolFlags:
+		"""`SymbolFlags`: Symbol flags"""
+		...
+	@property
+	def symbol_size(self) -> Optional[MemorySize]:
+		"""`MemorySize`, None: Symbol size or `None`"""
+		...
+
 class UsedMemory:
 	"""A memory location used by an instruction"""
 	@property
@@ -9024,3 +10047,212 @@ class UsedRegister:
 	def __eq__(self, other: Any) -> bool: ...
 	def __ne__(self, other: Any) -> bool: ...
 	def __hash__(self) -> int: ...
+
+def ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a memory operand without a size. Use it if the instruction's memory operand can only have one size.
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def byte_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `byte ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def word_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `word ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def dword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `dword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def qword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `qword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def mmword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `mmword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def tbyte_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `tbyte ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def tword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `tword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def fword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `fword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def oword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates an `oword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def xmmword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates an `xmmword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def ymmword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `ymmword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def zmmword_ptr(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `zmmword ptr [mem]` memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def word_bcst(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `word bcst [mem]` broadcast memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def dword_bcst(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `dword bcst [mem]` broadcast memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
+def qword_bcst(mem: Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]) -> AsmMemoryOperand:
+	"""
+	Creates a `qword bcst [mem]` broadcast memory operand
+
+	### Args:
+
+	- `mem` (Union[AsmMemoryOperand, AsmRegister, CodeLabel, int]): Memory operand, eg. `rax + rcx*8 + 0x10`
+
+	### Returns:
+
+	- AsmMemoryOperand: A memory operand
+	"""
+	...
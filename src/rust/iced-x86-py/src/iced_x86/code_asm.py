@@ -0,0 +1,556 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+# pylint: disable=invalid-name
+# pylint: disable=line-too-long
+
+"""
+Registers and memory operand functions used by :class:`CodeAssembler`
+
+Examples:
+
+.. testcode::
+
+    from iced_x86 import *
+    from iced_x86.code_asm import *
+
+    a = CodeAssembler(64)
+    a.mov(rax, qword_ptr(rcx + rdx*8 + 0x10))
+    encoded = a.assemble(0x1234_5678)
+"""
+
+from . import Register as _Register
+from ._iced_x86_py import ptr # pylint: disable=import-self
+from ._iced_x86_py import byte_ptr # pylint: disable=import-self
+from ._iced_x86_py import word_ptr # pylint: disable=import-self
+from ._iced_x86_py import dword_ptr # pylint: disable=import-self
+from ._iced_x86_py import qword_ptr # pylint: disable=import-self
+from ._iced_x86_py import mmword_ptr # pylint: disable=import-self
+from ._iced_x86_py import tbyte_ptr # pylint: disable=import-self
+from ._iced_x86_py import tword_ptr # pylint: disable=import-self
+from ._iced_x86_py import fword_ptr # pylint: disable=import-self
+from ._iced_x86_py import oword_ptr # pylint: disable=import-self
+from ._iced_x86_py import xmmword_ptr # pylint: disable=import-self
+from ._iced_x86_py import ymmword_ptr # pylint: disable=import-self
+from ._iced_x86_py import zmmword_ptr # pylint: disable=import-self
+from ._iced_x86_py import word_bcst # pylint: disable=import-self
+from ._iced_x86_py import dword_bcst # pylint: disable=import-self
+from ._iced_x86_py import qword_bcst # pylint: disable=import-self
+from ._iced_x86_py import AsmMemoryOperand, AsmRegister, CodeAssembler, CodeAssemblerResult, CodeLabel # pylint: disable=import-self
+
+al = AsmRegister(_Register.AL)
+cl = AsmRegister(_Register.CL)
+dl = AsmRegister(_Register.DL)
+bl = AsmRegister(_Register.BL)
+ah = AsmRegister(_Register.AH)
+ch = AsmRegister(_Register.CH)
+dh = AsmRegister(_Register.DH)
+bh = AsmRegister(_Register.BH)
+spl = AsmRegister(_Register.SPL)
+bpl = AsmRegister(_Register.BPL)
+sil = AsmRegister(_Register.SIL)
+dil = AsmRegister(_Register.DIL)
+r8b = AsmRegister(_Register.R8L)
+r9b = AsmRegister(_Register.R9L)
+r10b = AsmRegister(_Register.R10L)
+r11b = AsmRegister(_Register.R11L)
+r12b = AsmRegister(_Register.R12L)
+r13b = AsmRegister(_Register.R13L)
+r14b = AsmRegister(_Register.R14L)
+r15b = AsmRegister(_Register.R15L)
+ax = AsmRegister(_Register.AX)
+cx = AsmRegister(_Register.CX)
+dx = AsmRegister(_Register.DX)
+bx = AsmRegister(_Register.BX)
+sp = AsmRegister(_Register.SP)
+bp = AsmRegister(_Register.BP)
+si = AsmRegister(_Register.SI)
+di = AsmRegister(_Register.DI)
+r8w = AsmRegister(_Register.R8W)
+r9w = AsmRegister(_Register.R9W)
+r10w = AsmRegister(_Register.R10W)
+r11w = AsmRegister(_Register.R11W)
+r12w = AsmRegister(_Register.R12W)
+r13w = AsmRegister(_Register.R13W)
+r14w = AsmRegister(_Register.R14W)
+r15w = AsmRegister(_Register.R15W)
+eax = AsmRegister(_Register.EAX)
+ecx = AsmRegister(_Register.ECX)
+edx = AsmRegister(_Register.EDX)
+ebx = AsmRegister(_Register.EBX)
+esp = AsmRegister(_Register.ESP)
+ebp = AsmRegister(_Register.EBP)
+esi = AsmRegister(_Register.ESI)
+edi = AsmRegister(_Register.EDI)
+r8d = AsmRegister(_Register.R8D)
+r9d = AsmRegister(_Register.R9D)
+r10d = AsmRegister(_Register.R10D)
+r11d = AsmRegister(_Register.R11D)
+r12d = AsmRegister(_Register.R12D)
+r13d = AsmRegister(_Register.R13D)
+r14d = AsmRegister(_Register.R14D)
+r15d = AsmRegister(_Register.R15D)
+rax = AsmRegister(_Register.RAX)
+rcx = AsmRegister(_Register.RCX)
+rdx = AsmRegister(_Register.RDX)
+rbx = AsmRegister(_Register.RBX)
+rsp = AsmRegister(_Register.RSP)
+rbp = AsmRegister(_Register.RBP)
+rsi = AsmRegister(_Register.RSI)
+rdi = AsmRegister(_Register.RDI)
+r8 = AsmRegister(_Register.R8)
+r9 = AsmRegister(_Register.R9)
+r10 = AsmRegister(_Register.R10)
+r11 = AsmRegister(_Register.R11)
+r12 = AsmRegister(_Register.R12)
+r13 = AsmRegister(_Register.R13)
+r14 = AsmRegister(_Register.R14)
+r15 = AsmRegister(_Register.R15)
+es = AsmRegister(_Register.ES)
+cs = AsmRegister(_Register.CS)
+ss = AsmRegister(_Register.SS)
+ds = AsmRegister(_Register.DS)
+fs = AsmRegister(_Register.FS)
+gs = AsmRegister(_Register.GS)
+st0 = AsmRegister(_Register.ST0)
+st1 = AsmRegister(_Register.ST1)
+st2 = AsmRegister(_Register.ST2)
+st3 = AsmRegister(_Register.ST3)
+st4 = AsmRegister(_Register.ST4)
+st5 = AsmRegister(_Register.ST5)
+st6 = AsmRegister(_Register.ST6)
+st7 = AsmRegister(_Register.ST7)
+cr0 = AsmRegister(_Register.CR0)
+cr1 = AsmRegister(_Register.CR1)
+cr2 = AsmRegister(_Register.CR2)
+cr3 = AsmRegister(_Register.CR3)
+cr4 = AsmRegister(_Register.CR4)
+cr5 = AsmRegister(_Register.CR5)
+cr6 = AsmRegister(_Register.CR6)
+cr7 = AsmRegister(_Register.CR7)
+cr8 = AsmRegister(_Register.CR8)
+cr9 = AsmRegister(_Register.CR9)
+cr10 = AsmRegister(_Register.CR10)
+cr11 = AsmRegister(_Register.CR11)
+cr12 = AsmRegister(_Register.CR12)
+cr13 = AsmRegister(_Register.CR13)
+cr14 = AsmRegister(_Register.CR14)
+cr15 = AsmRegister(_Register.CR15)
+dr0 = AsmRegister(_Register.DR0)
+dr1 = AsmRegister(_Register.DR1)
+dr2 = AsmRegister(_Register.DR2)
+dr3 = AsmRegister(_Register.DR3)
+dr4 = AsmRegister(_Register.DR4)
+dr5 = AsmRegister(_Register.DR5)
+dr6 = AsmRegister(_Register.DR6)
+dr7 = AsmRegister(_Register.DR7)
+dr8 = AsmRegister(_Register.DR8)
+dr9 = AsmRegister(_Register.DR9)
+dr10 = AsmRegister(_Register.DR10)
+dr11 = AsmRegister(_Register.DR11)
+dr12 = AsmRegister(_Register.DR12)
+dr13 = AsmRegister(_Register.DR13)
+dr14 = AsmRegister(_Register.DR14)
+dr15 = AsmRegister(_Register.DR15)
+tr0 = AsmRegister(_Register.TR0)
+tr1 = AsmRegister(_Register.TR1)
+tr2 = AsmRegister(_Register.TR2)
+tr3 = AsmRegister(_Register.TR3)
+tr4 = AsmRegister(_Register.TR4)
+tr5 = AsmRegister(_Register.TR5)
+tr6 = AsmRegister(_Register.TR6)
+tr7 = AsmRegister(_Register.TR7)
+bnd0 = AsmRegister(_Register.BND0)
+bnd1 = AsmRegister(_Register.BND1)
+bnd2 = AsmRegister(_Register.BND2)
+bnd3 = AsmRegister(_Register.BND3)
+k0 = AsmRegister(_Register.K0)
+k1 = AsmRegister(_Register.K1)
+k2 = AsmRegister(_Register.K2)
+k3 = AsmRegister(_Register.K3)
+k4 = AsmRegister(_Register.K4)
+k5 = AsmRegister(_Register.K5)
+k6 = AsmRegister(_Register.K6)
+k7 = AsmRegister(_Register.K7)
+mm0 = AsmRegister(_Register.MM0)
+mm1 = AsmRegister(_Register.MM1)
+mm2 = AsmRegister(_Register.MM2)
+mm3 = AsmRegister(_Register.MM3)
+mm4 = AsmRegister(_Register.MM4)
+mm5 = AsmRegister(_Register.MM5)
+mm6 = AsmRegister(_Register.MM6)
+mm7 = AsmRegister(_Register.MM7)
+xmm0 = AsmRegister(_Register.XMM0)
+xmm1 = AsmRegister(_Register.XMM1)
+xmm2 = AsmRegister(_Register.XMM2)
+xmm3 = AsmRegister(_Register.XMM3)
+xmm4 = AsmRegister(_Register.XMM4)
+xmm5 = AsmRegister(_Register.XMM5)
+xmm6 = AsmRegister(_Register.XMM6)
+xmm7 = AsmRegister(_Register.XMM7)
+xmm8 = AsmRegister(_Register.XMM8)
+xmm9 = AsmRegister(_Register.XMM9)
+xmm10 = AsmRegister(_Register.XMM10)
+xmm11 = AsmRegister(_Register.XMM11)
+xmm12 = AsmRegister(_Register.XMM12)
+xmm13 = AsmRegister(_Register.XMM13)
+xmm14 = AsmRegister(_Register.XMM14)
+xmm15 = AsmRegister(_Register.XMM15)
+xmm16 = AsmRegister(_Register.XMM16)
+xmm17 = AsmRegister(_Register.XMM17)
+xmm18 = AsmRegister(_Register.XMM18)
+xmm19 = AsmRegister(_Register.XMM19)
+xmm20 = AsmRegister(_Register.XMM20)
+xmm21 = AsmRegister(_Register.XMM21)
+xmm22 = AsmRegister(_Register.XMM22)
+xmm23 = AsmRegister(_Register.XMM23)
+xmm24 = AsmRegister(_Register.XMM24)
+xmm25 = AsmRegister(_Register.XMM25)
+xmm26 = AsmRegister(_Register.XMM26)
+xmm27 = AsmRegister(_Register.XMM27)
+xmm28 = AsmRegister(_Register.XMM28)
+xmm29 = AsmRegister(_Register.XMM29)
+xmm30 = AsmRegister(_Register.XMM30)
+xmm31 = AsmRegister(_Register.XMM31)
+ymm0 = AsmRegister(_Register.YMM0)
+ymm1 = AsmRegister(_Register.YMM1)
+ymm2 = AsmRegister(_Register.YMM2)
+ymm3 = AsmRegister(_Register.YMM3)
+ymm4 = AsmRegister(_Register.YMM4)
+ymm5 = AsmRegister(_Register.YMM5)
+ymm6 = AsmRegister(_Register.YMM6)
+ymm7 = AsmRegister(_Register.YMM7)
+ymm8 = AsmRegister(_Register.YMM8)
+ymm9 = AsmRegister(_Register.YMM9)
+ymm10 = AsmRegister(_Register.YMM10)
+ymm11 = AsmRegister(_Register.YMM11)
+ymm12 = AsmRegister(_Register.YMM12)
+ymm13 = AsmRegister(_Register.YMM13)
+ymm14 = AsmRegister(_Register.YMM14)
+ymm15 = AsmRegister(_Register.YMM15)
+ymm16 = AsmRegister(_Register.YMM16)
+ymm17 = AsmRegister(_Register.YMM17)
+ymm18 = AsmRegister(_Register.YMM18)
+ymm19 = AsmRegister(_Register.YMM19)
+ymm20 = AsmRegister(_Register.YMM20)
+ymm21 = AsmRegister(_Register.YMM21)
+ymm22 = AsmRegister(_Register.YMM22)
+ymm23 = AsmRegister(_Register.YMM23)
+ymm24 = AsmRegister(_Register.YMM24)
+ymm25 = AsmRegister(_Register.YMM25)
+ymm26 = AsmRegister(_Register.YMM26)
+ymm27 = AsmRegister(_Register.YMM27)
+ymm28 = AsmRegister(_Register.YMM28)
+ymm29 = AsmRegister(_Register.YMM29)
+ymm30 = AsmRegister(_Register.YMM30)
+ymm31 = AsmRegister(_Register.YMM31)
+zmm0 = AsmRegister(_Register.ZMM0)
+zmm1 = AsmRegister(_Register.ZMM1)
+zmm2 = AsmRegister(_Register.ZMM2)
+zmm3 = AsmRegister(_Register.ZMM3)
+zmm4 = AsmRegister(_Register.ZMM4)
+zmm5 = AsmRegister(_Register.ZMM5)
+zmm6 = AsmRegister(_Register.ZMM6)
+zmm7 = AsmRegister(_Register.ZMM7)
+zmm8 = AsmRegister(_Register.ZMM8)
+zmm9 = AsmRegister(_Register.ZMM9)
+zmm10 = AsmRegister(_Register.ZMM10)
+zmm11 = AsmRegister(_Register.ZMM11)
+zmm12 = AsmRegister(_Register.ZMM12)
+zmm13 = AsmRegister(_Register.ZMM13)
+zmm14 = AsmRegister(_Register.ZMM14)
+zmm15 = AsmRegister(_Register.ZMM15)
+zmm16 = AsmRegister(_Register.ZMM16)
+zmm17 = AsmRegister(_Register.ZMM17)
+zmm18 = AsmRegister(_Register.ZMM18)
+zmm19 = AsmRegister(_Register.ZMM19)
+zmm20 = AsmRegister(_Register.ZMM20)
+zmm21 = AsmRegister(_Register.ZMM21)
+zmm22 = AsmRegister(_Register.ZMM22)
+zmm23 = AsmRegister(_Register.ZMM23)
+zmm24 = AsmRegister(_Register.ZMM24)
+zmm25 = AsmRegister(_Register.ZMM25)
+zmm26 = AsmRegister(_Register.ZMM26)
+zmm27 = AsmRegister(_Register.ZMM27)
+zmm28 = AsmRegister(_Register.ZMM28)
+zmm29 = AsmRegister(_Register.ZMM29)
+zmm30 = AsmRegister(_Register.ZMM30)
+zmm31 = AsmRegister(_Register.ZMM31)
+tmm0 = AsmRegister(_Register.TMM0)
+tmm1 = AsmRegister(_Register.TMM1)
+tmm2 = AsmRegister(_Register.TMM2)
+tmm3 = AsmRegister(_Register.TMM3)
+tmm4 = AsmRegister(_Register.TMM4)
+tmm5 = AsmRegister(_Register.TMM5)
+tmm6 = AsmRegister(_Register.TMM6)
+tmm7 = AsmRegister(_Register.TMM7)
+
+__all__ = [
+	"AsmMemoryOperand",
+	"AsmRegister",
+	"CodeAssembler",
+	"CodeAssemblerResult",
+	"CodeLabel",
+	"ptr",
+	"byte_ptr",
+	"word_ptr",
+	"dword_ptr",
+	"qword_ptr",
+	"mmword_ptr",
+	"tbyte_ptr",
+	"tword_ptr",
+	"fword_ptr",
+	"oword_ptr",
+	"xmmword_ptr",
+	"ymmword_ptr",
+	"zmmword_ptr",
+	"word_bcst",
+	"dword_bcst",
+	"qword_bcst",
+	"al",
+	"cl",
+	"dl",
+	"bl",
+	"ah",
+	"ch",
+	"dh",
+	"bh",
+	"spl",
+	"bpl",
+	"sil",
+	"dil",
+	"r8b",
+	"r9b",
+	"r10b",
+	"r11b",
+	"r12b",
+	"r13b",
+	"r14b",
+	"r15b",
+	"ax",
+	"cx",
+	"dx",
+	"bx",
+	"sp",
+	"bp",
+	"si",
+	"di",
+	"r8w",
+	"r9w",
+	"r10w",
+	"r11w",
+	"r12w",
+	"r13w",
+	"r14w",
+	"r15w",
+	"eax",
+	"ecx",
+	"edx",
+	"ebx",
+	"esp",
+	"ebp",
+	"esi",
+	"edi",
+	"r8d",
+	"r9d",
+	"r10d",
+	"r11d",
+	"r12d",
+	"r13d",
+	"r14d",
+	"r15d",
+	"rax",
+	"rcx",
+	"rdx",
+	"rbx",
+	"rsp",
+	"rbp",
+	"rsi",
+	"rdi",
+	"r8",
+	"r9",
+	"r10",
+	"r11",
+	"r12",
+	"r13",
+	"r14",
+	"r15",
+	"es",
+	"cs",
+	"ss",
+	"ds",
+	"fs",
+	"gs",
+	"st0",
+	"st1",
+	"st2",
+	"st3",
+	"st4",
+	"st5",
+	"st6",
+	"st7",
+	"cr0",
+	"cr1",
+	"cr2",
+	"cr3",
+	"cr4",
+	"cr5",
+	"cr6",
+	"cr7",
+	"cr8",
+	"cr9",
+	"cr10",
+	"cr11",
+	"cr12",
+	"cr13",
+	"cr14",
+	"cr15",
+	"dr0",
+	"dr1",
+	"dr2",
+	"dr3",
+	"dr4",
+	"dr5",
+	"dr6",
+	"dr7",
+	"dr8",
+	"dr9",
+	"dr10",
+	"dr11",
+	"dr12",
+	"dr13",
+	"dr14",
+	"dr15",
+	"tr0",
+	"tr1",
+	"tr2",
+	"tr3",
+	"tr4",
+	"tr5",
+	"tr6",
+	"tr7",
+	"bnd0",
+	"bnd1",
+	"bnd2",
+	"bnd3",
+	"k0",
+	"k1",
+	"k2",
+	"k3",
+	"k4",
+	"k5",
+	"k6",
+	"k7",
+	"mm0",
+	"mm1",
+	"mm2",
+	"mm3",
+	"mm4",
+	"mm5",
+	"mm6",
+	"mm7",
+	"xmm0",
+	"xmm1",
+	"xmm2",
+	"xmm3",
+	"xmm4",
+	"xmm5",
+	"xmm6",
+	"xmm7",
+	"xmm8",
+	"xmm9",
+	"xmm10",
+	"xmm11",
+	"xmm12",
+	"xmm13",
+	"xmm14",
+	"xmm15",
+	"xmm16",
+	"xmm17",
+	"xmm18",
+	"xmm19",
+	"xmm20",
+	"xmm21",
+	"xmm22",
+	"xmm23",
+	"xmm24",
+	"xmm25",
+	"xmm26",
+	"xmm27",
+	"xmm28",
+	"xmm29",
+	"xmm30",
+	"xmm31",
+	"ymm0",
+	"ymm1",
+	"ymm2",
+	"ymm3",
+	"ymm4",
+	"ymm5",
+	"ymm6",
+	"ymm7",
+	"ymm8",
+	"ymm9",
+	"ymm10",
+	"ymm11",
+	"ymm12",
+	"ymm13",
+	"ymm14",
+	"ymm15",
+	"ymm16",
+	"ymm17",
+	"ymm18",
+	"ymm19",
+	"ymm20",
+	"ymm21",
+	"ymm22",
+	"ymm23",
+	"ymm24",
+	"ymm25",
+	"ymm26",
+	"ymm27",
+	"ymm28",
+	"ymm29",
+	"ymm30",
+	"ymm31",
+	"zmm0",
+	"zmm1",
+	"zmm2",
+	"zmm3",
+	"zmm4",
+	"zmm5",
+	"zmm6",
+	"zmm7",
+	"zmm8",
+	"zmm9",
+	"zmm10",
+	"zmm11",
+	"zmm12",
+	"zmm13",
+	"zmm14",
+	"zmm15",
+	"zmm16",
+	"zmm17",
+	"zmm18",
+	"zmm19",
+	"zmm20",
+	"zmm21",
+	"zmm22",
+	"zmm23",
+	"zmm24",
+	"zmm25",
+	"zmm26",
+	"zmm27",
+	"zmm28",
+	"zmm29",
+	"zmm30",
+	"zmm31",
+	"tmm0",
+	"tmm1",
+	"tmm2",
+	"tmm3",
+	"tmm4",
+	"tmm5",
+	"tmm6",
+	"tmm7",
+]
@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::decoder::Decoder;
+use crate::instruction::Instruction;
+use crate::utils::to_value_error;
+use core::{mem, ptr};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::gc::PyVisit;
+use pyo3::prelude::*;
+use pyo3::types::{PyByteArray, PyDict, PyMemoryView};
+use pyo3::PyTraverseError;
+
+/// One decoded instruction. The layout must match `RECORD_FORMAT` and `record_dtype()`.
+#[repr(C)]
+#[derive(Copy, Clone, Default)]
+struct InstructionRecord {
+	ip: u64,
+	position: u64,
+	immediate: u64,
+	memory_displacement: u64,
+	code: u16,
+	mnemonic: u16,
+	op_registers: [u16; 5],
+	memory_base: u16,
+	memory_index: u16,
+	len: u8,
+	op_count: u8,
+	op_kinds: [u8; 5],
+	memory_index_scale: u8,
+	flow_control: u8,
+	padding: [u8; 5],
+}
+
+const RECORD_SIZE: usize = mem::size_of::<InstructionRecord>();
+const _: () = assert!(RECORD_SIZE == 64);
+
+// (name, numpy format, offset)
+const RECORD_FIELDS: [(&str, &str, usize); 14] = [
+	("ip", "u8", 0),
+	("position", "u8", 8),
+	("immediate", "u8", 16),
+	("memory_displacement", "u8", 24),
+	("code", "u2", 32),
+	("mnemonic", "u2", 34),
+	("op_registers", "(5,)u2", 36),
+	("memory_base", "u2", 46),
+	("memory_index", "u2", 48),
+	("len", "u1", 50),
+	("op_count", "u1", 51),
+	("op_kinds", "(5,)u1", 52),
+	("memory_index_scale", "u1", 57),
+	("flow_control", "u1", 58),
+];
+
+impl InstructionRecord {
+	fn new(instr: &iced_x86::Instruction, position: usize) -> Self {
+		let mut record = InstructionRecord {
+			ip: instr.ip(),
+			position: position as u64,
+			immediate: 0,
+			memory_displacement: 0,
+			code: instr.code() as u16,
+			mnemonic: instr.mnemonic() as u16,
+			op_registers: [0; 5],
+			memory_base: instr.memory_base() as u16,
+			memory_index: instr.memory_index() as u16,
+			len: instr.len() as u8,
+			op_count: instr.op_count() as u8,
+			op_kinds: [0; 5],
+			memory_index_scale: instr.memory_index_scale() as u8,
+			flow_control: instr.flow_control() as u8,
+			padding: [0; 5],
+		};
+		let mut has_immediate = false;
+		for i in 0..instr.op_count() {
+			record.op_kinds[i as usize] = instr.op_kind(i) as u8;
+			record.op_registers[i as usize] = instr.op_register(i) as u16;
+			if instr.op_kind(i) == iced_x86::OpKind::Memory {
+				record.memory_displacement = instr.memory_displacement64();
+			}
+			if !has_immediate {
+				if let Ok(immediate) = instr.try_immediate(i) {
+					record.immediate = immediate;
+					has_immediate = true;
+				}
+			}
+		}
+		record
+	}
+}
+
+/// Instructions decoded by :class:`Decoder.decode_batch`, stored in a contiguous buffer of fixed size records.
+///
+/// The records are written directly to a ``bytearray`` which is exposed by :class:`InstructionBatch.data` so no
+/// :class:`Instruction` objects are created unless you index the batch, eg. ``batch[5]``. That re-decodes the
+/// instruction from the decoder's data.
+///
+/// Each record is :class:`InstructionBatch.RECORD_SIZE` bytes and has these fields (native endian):
+///
+/// - ``ip`` (``u64``): ``RIP`` value
+/// - ``position`` (``u64``): Index of the instruction in the data passed to the :class:`Decoder` constructor
+/// - ``immediate`` (``u64``): Value of the first immediate operand or ``0`` if there's none
+/// - ``memory_displacement`` (``u64``): :class:`Instruction.memory_displacement` or ``0`` if there's no memory operand
+/// - ``code`` (``u16``): :class:`Code` enum value
+/// - ``mnemonic`` (``u16``): :class:`Mnemonic` enum value
+/// - ``op_registers`` (``u16[5]``): :class:`Register` enum value of each operand or :class:`Register.NONE`
+/// - ``memory_base`` (``u16``): :class:`Register` enum value
+/// - ``memory_index`` (``u16``): :class:`Register` enum value
+/// - ``len`` (``u8``): Length of the instruction
+/// - ``op_count`` (``u8``): Number of operands
+/// - ``op_kinds`` (``u8[5]``): :class:`OpKind` enum value of each operand or ``0`` if it's not used
+/// - ``memory_index_scale`` (``u8``): 1, 2, 4 or 8
+/// - ``flow_control`` (``u8``): :class:`FlowControl` enum value
+///
+/// Examples:
+///
+/// .. testcode::
+///
+///     from iced_x86 import *
+///     import struct
+///
+///     data = b"\x48\x89\x5C\x24\x10\x55\xE8\x00\x00\x00\x00"
+///     batch = Decoder(64, data, ip=0x1234_5678).decode_batch()
+///     assert len(batch) == 3
+///
+///     # With NumPy: records = numpy.frombuffer(batch.data, dtype=numpy.dtype(InstructionBatch.RECORD_DTYPE))
+///     records = list(struct.iter_unpack(InstructionBatch.RECORD_FORMAT, batch.data))
+///     ip, position, immediate, displ, code, mnemonic = records[1][:6]
+///     assert (ip, position, code, mnemonic) == (0x1234_567D, 5, Code.PUSH_R64, Mnemonic.PUSH)
+///
+///     # Lazily create an Instruction
+///     assert batch[2].flow_control == FlowControl.CALL
+///     assert batch[-1].near_branch_target == 0x1234_5683
+#[pyclass(module = "iced_x86._iced_x86_py")]
+pub(crate) struct InstructionBatch {
+	decoder: Option<Py<Decoder>>,
+	bitness: u32,
+	options: u32,
+	records: Py<PyByteArray>,
+	len: usize,
+}
+
+impl InstructionBatch {
+	/// Decodes at most `count` instructions (or all remaining instructions if it's `None`)
+	pub(crate) fn decode(decoder_obj: &Bound<'_, Decoder>, count: Option<usize>) -> PyResult<Self> {
+		let py = decoder_obj.py();
+		let mut decoder = decoder_obj.try_borrow_mut()?;
+		let (bitness, options) = (decoder.decoder.bitness(), decoder.options);
+		let remaining = decoder.decoder.max_position() - decoder.decoder.position();
+		let max_count = count.map_or(remaining, |count| count.min(remaining));
+
+		// Most instructions are longer than 1 byte so don't allocate too much if we decode everything
+		let mut capacity = if count.is_some() { max_count } else { remaining / 4 + 1 };
+		let records = PyByteArray::new(py, &[]);
+		records.resize(capacity.checked_mul(RECORD_SIZE).ok_or_else(|| PyValueError::new_err("Too many instructions"))?)?;
+
+		let mut len = 0;
+		let mut instr = iced_x86::Instruction::default();
+		while len < max_count && decoder.decoder.can_decode() {
+			let position = decoder.decoder.position();
+			decoder.decoder.decode_out(&mut instr);
+			if len == capacity {
+				capacity = (capacity * 2).min(max_count);
+				records.resize(capacity * RECORD_SIZE)?;
+			}
+			let record = InstructionRecord::new(&instr, position);
+			// SAFETY: `len < capacity` and nothing else has a reference to the bytearray
+			unsafe { ptr::write_unaligned(records.data().add(len * RECORD_SIZE).cast::<InstructionRecord>(), record) };
+			len += 1;
+		}
+		records.resize(len * RECORD_SIZE)?;
+		drop(decoder);
+
+		Ok(InstructionBatch { decoder: Some(decoder_obj.clone().unbind()), bitness, options, records: records.unbind(), len })
+	}
+
+	fn record(&self, py: Python<'_>, index: usize) -> InstructionRecord {
+		let records = self.records.bind(py);
+		if (index + 1) * RECORD_SIZE <= records.len() {
+			// SAFETY: it's within the bytearray's bounds
+			unsafe { ptr::read_unaligned(records.data().add(index * RECORD_SIZE).cast::<InstructionRecord>()) }
+		} else {
+			InstructionRecord::default()
+		}
+	}
+}
+
+#[pymethods]
+impl InstructionBatch {
+	/// int: Size of each record in bytes
+	#[classattr]
+	const RECORD_SIZE: usize = RECORD_SIZE;
+
+	/// str: Record format that can be passed to ``struct.unpack()`` and ``struct.iter_unpack()``
+	#[classattr]
+	const RECORD_FORMAT: &'static str = "=QQQQHH5HHHBB5BBB5x";
+
+	/// dict: Record format that can be passed to ``numpy.dtype()``
+	#[classattr]
+	#[pyo3(name = "RECORD_DTYPE")]
+	fn record_dtype(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+		let dict = PyDict::new(py);
+		dict.set_item("names", RECORD_FIELDS.iter().map(|&(name, _, _)| name).collect::<Vec<_>>())?;
+		dict.set_item("formats", RECORD_FIELDS.iter().map(|&(_, format, _)| format).collect::<Vec<_>>())?;
+		dict.set_item("offsets", RECORD_FIELDS.iter().map(|&(_, _, offset)| offset).collect::<Vec<_>>())?;
+		dict.set_item("itemsize", RECORD_SIZE)?;
+		Ok(dict)
+	}
+
+	/// memoryview: A read-only view of all records. Its length is ``len(batch) * InstructionBatch.RECORD_SIZE``.
+	///
+	/// Nothing is copied, eg. ``numpy.frombuffer(batch.data, dtype=numpy.dtype(InstructionBatch.RECORD_DTYPE))``.
+	#[getter]
+	fn data<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+		PyMemoryView::from(self.records.bind(py).as_any())?.call_method0("toreadonly")
+	}
+
+	fn __len__(&self) -> usize {
+		self.len
+	}
+
+	/// Re-decodes the instruction at ``index``
+	fn __getitem__(&self, py: Python<'_>, index: isize) -> PyResult<Instruction> {
+		let index = if index < 0 { index + self.len as isize } else { index };
+		if index < 0 || index as usize >= self.len {
+			return Err(PyIndexError::new_err("Index out of range"));
+		}
+		let record = self.record(py, index as usize);
+		let decoder = match &self.decoder {
+			Some(decoder) => decoder.bind(py).try_borrow()?,
+			None => return Err(PyValueError::new_err("The decoder has been freed")),
+		};
+		let data = decoder.data().and_then(|data| data.get(record.position as usize..));
+		let data = data.ok_or_else(|| PyValueError::new_err("The decoder's data is not available"))?;
+		let mut instr_decoder = iced_x86::Decoder::try_with_ip(self.bitness, data, record.ip, self.options).map_err(to_value_error)?;
+		Ok(Instruction { instr: instr_decoder.decode() })
+	}
+
+	fn __traverse__(&self, visit: PyVisit<'_>) -> Result<(), PyTraverseError> {
+		if let Some(decoder) = &self.decoder {
+			visit.call(decoder)?
+		}
+		Ok(())
+	}
+
+	fn __clear__(&mut self) {
+		self.decoder = None;
+	}
+}
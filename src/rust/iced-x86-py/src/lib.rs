@@ -71,30 +71,38 @@
 #![warn(clippy::zero_sized_map_values)]
 
 mod block_encoder;
+mod code_asm;
 mod constant_offsets;
 mod decoder;
 mod encoder;
 mod enum_utils;
 mod fast_formatter;
 mod formatter;
+mod formatter_options_provider;
+mod formatter_output;
 mod info;
 mod instruction;
+mod instruction_batch;
 mod memory_operand;
 mod memory_size_ext;
 mod memory_size_info;
 mod op_code_info;
 mod register_ext;
 mod register_info;
+mod symbol_resolver;
 mod utils;
 
 use block_encoder::BlockEncoder;
+use code_asm::{AsmMemoryOperand, AsmRegister, CodeAssembler, CodeAssemblerResult, CodeLabel};
 use constant_offsets::ConstantOffsets;
 use decoder::Decoder;
 use encoder::Encoder;
 use fast_formatter::FastFormatter;
 use formatter::Formatter;
+use formatter_options_provider::{FormatterOperandOptions, NumberFormattingOptions};
 use info::{InstructionInfo, InstructionInfoFactory, UsedMemory, UsedRegister};
 use instruction::{FpuStackIncrementInfo, Instruction};
+use instruction_batch::InstructionBatch;
 use memory_operand::MemoryOperand;
 use memory_size_ext::MemorySizeExt;
 use memory_size_info::MemorySizeInfo;
@@ -102,6 +110,7 @@ use op_code_info::OpCodeInfo;
 use pyo3::prelude::*;
 use register_ext::RegisterExt;
 use register_info::RegisterInfo;
+use symbol_resolver::SymbolResult;
 
 // GENERATOR-BEGIN: EnumClassDefs
 // ⚠️This was generated by GENERATOR!🦹‍♂️
@@ -180,6 +189,10 @@ struct DecoderOptions {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
 #[allow(non_camel_case_types)]
+struct DecoratorKind {}
+/// DO NOT USE
+#[pyclass(module = "iced_x86._iced_x86_py")]
+#[allow(non_camel_case_types)]
 struct EncodingKind {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
@@ -196,6 +209,10 @@ struct FormatterSyntax {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
 #[allow(non_camel_case_types)]
+struct FormatterTextKind {}
+/// DO NOT USE
+#[pyclass(module = "iced_x86._iced_x86_py")]
+#[allow(non_camel_case_types)]
 struct MandatoryPrefix {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
@@ -228,6 +245,14 @@ struct MvexTupleTypeLutKind {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
 #[allow(non_camel_case_types)]
+struct NumberBase {}
+/// DO NOT USE
+#[pyclass(module = "iced_x86._iced_x86_py")]
+#[allow(non_camel_case_types)]
+struct NumberKind {}
+/// DO NOT USE
+#[pyclass(module = "iced_x86._iced_x86_py")]
+#[allow(non_camel_case_types)]
 struct OpAccess {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
@@ -244,6 +269,10 @@ struct OpKind {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
 #[allow(non_camel_case_types)]
+struct PrefixKind {}
+/// DO NOT USE
+#[pyclass(module = "iced_x86._iced_x86_py")]
+#[allow(non_camel_case_types)]
 struct Register {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
@@ -260,6 +289,10 @@ struct RoundingControl {}
 /// DO NOT USE
 #[pyclass(module = "iced_x86._iced_x86_py")]
 #[allow(non_camel_case_types)]
+struct SymbolFlags {}
+/// DO NOT USE
+#[pyclass(module = "iced_x86._iced_x86_py")]
+#[allow(non_camel_case_types)]
 struct TupleType {}
 // GENERATOR-END: EnumClassDefs
 
@@ -267,6 +300,8 @@ struct TupleType {}
 fn _iced_x86_py(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
 	// GENERATOR-BEGIN: ClassExport
 	// ⚠️This was generated by GENERATOR!🦹‍♂️
+	m.add_class::<AsmMemoryOperand>()?;
+	m.add_class::<AsmRegister>()?;
 	m.add_class::<BlockEncoder>()?;
 	m.add_class::<CC_a>()?;
 	m.add_class::<CC_ae>()?;
@@ -281,6 +316,9 @@ fn _iced_x86_py(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
 	m.add_class::<CC_np>()?;
 	m.add_class::<CC_p>()?;
 	m.add_class::<Code>()?;
+	m.add_class::<CodeAssembler>()?;
+	m.add_class::<CodeAssemblerResult>()?;
+	m.add_class::<CodeLabel>()?;
 	m.add_class::<CodeSize>()?;
 	m.add_class::<ConditionCode>()?;
 	m.add_class::<ConstantOffsets>()?;
@@ -288,15 +326,19 @@ fn _iced_x86_py(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
 	m.add_class::<Decoder>()?;
 	m.add_class::<DecoderError>()?;
 	m.add_class::<DecoderOptions>()?;
+	m.add_class::<DecoratorKind>()?;
 	m.add_class::<Encoder>()?;
 	m.add_class::<EncodingKind>()?;
 	m.add_class::<FastFormatter>()?;
 	m.add_class::<FlowControl>()?;
 	m.add_class::<FormatMnemonicOptions>()?;
 	m.add_class::<Formatter>()?;
+	m.add_class::<FormatterOperandOptions>()?;
 	m.add_class::<FormatterSyntax>()?;
+	m.add_class::<FormatterTextKind>()?;
 	m.add_class::<FpuStackIncrementInfo>()?;
 	m.add_class::<Instruction>()?;
+	m.add_class::<InstructionBatch>()?;
 	m.add_class::<InstructionInfo>()?;
 	m.add_class::<InstructionInfoFactory>()?;
 	m.add_class::<MandatoryPrefix>()?;
@@ -310,21 +352,44 @@ fn _iced_x86_py(_py: Python<'_>, m: &Bound<'_, PyModule>) -> PyResult<()> {
 	m.add_class::<MvexEHBit>()?;
 	m.add_class::<MvexRegMemConv>()?;
 	m.add_class::<MvexTupleTypeLutKind>()?;
+	m.add_class::<NumberBase>()?;
+	m.add_class::<NumberFormattingOptions>()?;
+	m.add_class::<NumberKind>()?;
 	m.add_class::<OpAccess>()?;
 	m.add_class::<OpCodeInfo>()?;
 	m.add_class::<OpCodeOperandKind>()?;
 	m.add_class::<OpCodeTableKind>()?;
 	m.add_class::<OpKind>()?;
+	m.add_class::<PrefixKind>()?;
 	m.add_class::<Register>()?;
 	m.add_class::<RegisterExt>()?;
 	m.add_class::<RegisterInfo>()?;
 	m.add_class::<RepPrefixKind>()?;
 	m.add_class::<RflagsBits>()?;
 	m.add_class::<RoundingControl>()?;
+	m.add_class::<SymbolFlags>()?;
+	m.add_class::<SymbolResult>()?;
 	m.add_class::<TupleType>()?;
 	m.add_class::<UsedMemory>()?;
 	m.add_class::<UsedRegister>()?;
 	// GENERATOR-END: ClassExport
 
+	m.add_function(wrap_pyfunction!(code_asm::ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::byte_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::word_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::dword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::qword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::mmword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::tbyte_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::tword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::fword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::oword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::xmmword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::ymmword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::zmmword_ptr, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::word_bcst, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::dword_bcst, m)?)?;
+	m.add_function(wrap_pyfunction!(code_asm::qword_bcst, m)?)?;
+
 	Ok(())
 }
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::enum_utils::{to_formatter_text_kind, to_memory_size};
+use crate::formatter::FormatterCallbackState;
+use crate::instruction::Instruction;
+use iced_x86::{FormatterTextKind, MemorySize, SymResString, SymResTextInfo, SymResTextPart};
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+#[derive(FromPyObject)]
+enum SymbolText {
+	Str(String),
+	Parts(Vec<(String, u32)>),
+}
+
+/// Symbol returned by a symbol resolver passed to :class:`Formatter`
+///
+/// Args:
+///     `address` (int): (``u64``) The address of the symbol. The formatter shows ``symbol+(address_to_format-address)`` if the addresses differ
+///     `text` (str, List[Tuple[str, :class:`FormatterTextKind`]]): Symbol text or a list of text parts and their kinds
+///     `kind` (:class:`FormatterTextKind`): (default = :class:`FormatterTextKind.LABEL`) Text kind. Only used if `text` is a ``str``
+///     `flags` (:class:`SymbolFlags`): (default = :class:`SymbolFlags.NONE`) Symbol flags
+///     `symbol_size` (:class:`MemorySize`, None): (default = ``None``) Symbol size or ``None``
+///
+/// Raises:
+///     ValueError: If one of the enum values is invalid
+///
+/// Examples:
+///
+/// .. testcode::
+///
+///     from iced_x86 import *
+///
+///     def resolver(instruction, operand, instruction_operand, address, address_size):
+///         if address == 0x5AA55AA5:
+///             return SymbolResult(address, [("my", FormatterTextKind.FUNCTION), ("_func", FormatterTextKind.DATA)])
+///         return None
+///
+///     decoder = Decoder(64, b"\xE8\x00\x00\x00\x00", ip=0x5AA55AA0)
+///     formatter = Formatter(FormatterSyntax.MASM, symbol_resolver=resolver)
+///     assert formatter.format(decoder.decode()) == "call my_func"
+#[pyclass(module = "iced_x86._iced_x86_py", from_py_object)]
+#[derive(Clone)]
+pub(crate) struct SymbolResult {
+	symbol: OwnedSymbol,
+}
+
+#[pymethods]
+impl SymbolResult {
+	#[new]
+	#[pyo3(text_signature = "(address, text, kind = 14, flags = 0, symbol_size = None)")]
+	#[pyo3(signature = (address, text, kind = 14, flags = 0, symbol_size = None))]
+	fn new(address: u64, text: SymbolText, kind: u32, flags: u32, symbol_size: Option<u32>) -> PyResult<Self> {
+		// #[pyo3(signature = (...))] line assumption
+		const _: () = assert!(FormatterTextKind::Label as u32 == 14);
+		const _: () = assert!(iced_x86::SymbolFlags::NONE == 0);
+
+		let parts = match text {
+			SymbolText::Str(text) => vec![SymResTextPart::with_string(text, to_formatter_text_kind(kind)?)],
+			SymbolText::Parts(parts) => {
+				let mut result = Vec::with_capacity(parts.len());
+				for (text, kind) in parts {
+					result.push(SymResTextPart::with_string(text, to_formatter_text_kind(kind)?));
+				}
+				result
+			}
+		};
+		let symbol_size = if let Some(symbol_size) = symbol_size { Some(to_memory_size(symbol_size)?) } else { None };
+		Ok(SymbolResult { symbol: OwnedSymbol { address, parts, flags, symbol_size } })
+	}
+
+	/// int: (``u64``) The address of the symbol
+	#[getter]
+	fn address(&self) -> u64 {
+		self.symbol.address
+	}
+
+	/// List[Tuple[str, :class:`FormatterTextKind`]]: All text parts and their kinds
+	#[getter]
+	fn text(&self) -> Vec<(String, u32)> {
+		self.symbol
+			.parts
+			.iter()
+			.map(|part| {
+				let text = match &part.text {
+					SymResString::Str(text) => (*text).to_owned(),
+					SymResString::String(text) => text.clone(),
+				};
+				(text, part.color as u32)
+			})
+			.collect()
+	}
+
+	/// :class:`SymbolFlags`: Symbol flags
+	#[getter]
+	fn flags(&self) -> u32 {
+		self.symbol.flags
+	}
+
+	/// :class:`MemorySize`, None: Symbol size or ``None``
+	#[getter]
+	fn symbol_size(&self) -> Option<u32> {
+		self.symbol.symbol_size.map(|v| v as u32)
+	}
+}
+
+#[derive(Clone)]
+struct OwnedSymbol {
+	address: u64,
+	parts: Vec<SymResTextPart<'static>>,
+	flags: u32,
+	symbol_size: Option<MemorySize>,
+}
+
+impl OwnedSymbol {
+	fn to_symbol_result(&self) -> iced_x86::SymbolResult<'_> {
+		iced_x86::SymbolResult {
+			address: self.address,
+			text: SymResTextInfo::with_vec(&self.parts),
+			flags: self.flags,
+			symbol_size: self.symbol_size,
+		}
+	}
+}
+
+/// Calls a Python callable to resolve symbols. The results are cached by address and address size.
+pub(crate) struct PySymbolResolver {
+	callback: Py<PyAny>,
+	state: Rc<FormatterCallbackState>,
+	cache: HashMap<(u64, u32), Option<OwnedSymbol>>,
+}
+
+impl PySymbolResolver {
+	pub(crate) fn new(callback: Py<PyAny>, state: Rc<FormatterCallbackState>) -> Self {
+		Self { callback, state, cache: HashMap::new() }
+	}
+
+	fn call(
+		&self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, address: u64, address_size: u32,
+	) -> PyResult<Option<OwnedSymbol>> {
+		Python::attach(|py| {
+			let result = self.callback.call1(py, (Instruction { instr: *instruction }, operand, instruction_operand, address, address_size))?;
+			let result = result.bind(py);
+			if result.is_none() {
+				Ok(None)
+			} else if let Ok(text) = result.extract::<String>() {
+				Ok(Some(OwnedSymbol {
+					address,
+					parts: vec![SymResTextPart::with_string(text, FormatterTextKind::Label)],
+					flags: iced_x86::SymbolFlags::NONE,
+					symbol_size: None,
+				}))
+			} else if let Ok(symbol) = result.extract::<SymbolResult>() {
+				Ok(Some(symbol.symbol))
+			} else {
+				Err(PyTypeError::new_err("The symbol resolver must return None, a str or a SymbolResult"))
+			}
+		})
+	}
+}
+
+impl iced_x86::SymbolResolver for PySymbolResolver {
+	fn symbol(
+		&mut self, instruction: &iced_x86::Instruction, operand: u32, instruction_operand: Option<u32>, address: u64, address_size: u32,
+	) -> Option<iced_x86::SymbolResult<'_>> {
+		if self.state.take_clear_symbol_cache() {
+			self.cache.clear();
+		}
+		if self.state.has_error() {
+			return None;
+		}
+		let key = (address, address_size);
+		if !self.cache.contains_key(&key) {
+			match self.call(instruction, operand, instruction_operand, address, address_size) {
+				Ok(symbol) => {
+					let _ = self.cache.insert(key, symbol);
+				}
+				Err(e) => {
+					self.state.set_error(e);
+					return None;
+				}
+			}
+		}
+		self.cache.get(&key).and_then(|symbol| symbol.as_ref().map(OwnedSymbol::to_symbol_result))
+	}
+}
@@ -35,6 +35,17 @@ def test_fix_branches_defaults_to_true() -> None:
 	encoded_data = encoder.encode(new_rip)
 	assert len(encoded_data) > 2
 
+@pytest.mark.parametrize("jcc_erratum", [False, True])
+def test_jcc_erratum_arg(jcc_erratum: bool) -> None:
+	decoder = Decoder(64, b"\xC3", ip=0x101F)
+	encoder = BlockEncoder(64, jcc_erratum=jcc_erratum)
+	encoder.add(decoder.decode())
+	encoded_data = encoder.encode(0x101F)
+	if jcc_erratum:
+		assert encoded_data == b"\x90\xC3"
+	else:
+		assert encoded_data == b"\xC3"
+
 def test_encode() -> None:
 	decoder = Decoder(64, b"\xF3\x90\x90\x48\x09\xCE\x48\x09\xCE\x90\xF3\x90\x48\x09\xCE", ip=0x1234_5678_9ABC_DEF0)
 	new_rip = 0xFEDC_BA98_7654_3210
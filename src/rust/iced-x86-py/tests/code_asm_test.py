@@ -0,0 +1,271 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+import pytest
+from iced_x86 import *
+from iced_x86.code_asm import *
+
+@pytest.mark.parametrize("bitness", [16, 32, 64, 0, 15, 128])
+def test_invalid_bitness(bitness: int) -> None:
+	if bitness in (16, 32, 64):
+		assert CodeAssembler(bitness).bitness == bitness
+	else:
+		with pytest.raises(ValueError):
+			CodeAssembler(bitness)
+
+def test_assemble() -> None:
+	a = CodeAssembler(64)
+	a.push(r15)
+	a.add(rax, r15)
+	a.mov(rax, qword_ptr(rax + r12*8 - 0x10))
+	a.mov(dword_ptr(rsp + 8), 0x1234)
+	a.and_(eax, 1)
+	a.ret()
+	assert a.assemble(0x1234_5678) == b"\x41\x57\x4C\x01\xF8\x4A\x8B\x44\xE0\xF0\xC7\x44\x24\x08\x34\x12\x00\x00\x83\xE0\x01\xC3"
+
+def test_instructions() -> None:
+	a = CodeAssembler(64)
+	a.add(rax, rcx)
+	a.mov(eax, 0xFFFF_FFFF)
+	a.add(rax, -1)
+	instrs = a.instructions()
+	assert [instr.code for instr in instrs] == [Code.ADD_RM64_R64, Code.MOV_R32_IMM32, Code.ADD_RM64_IMM8]
+	assert instrs[0].op0_register == Register.RAX
+	assert instrs[0].op1_register == Register.RCX
+	assert instrs[1].immediate32 == 0xFFFF_FFFF
+	assert instrs[2].immediate8to64 == -1
+	assert a.take_instructions() == instrs
+	assert a.instructions() == []
+
+def test_emit() -> None:
+	a = CodeAssembler(64)
+	a.emit(Mnemonic.ADD, rax, qword_ptr(rcx + 0x10))
+	a.emit(Mnemonic.NOP)
+	assert a.assemble(0) == b"\x48\x03\x41\x10\x90"
+
+@pytest.mark.parametrize("mem, base, index, scale, displ", [
+	(rax + 0x10, Register.RAX, Register.NONE, 1, 0x10),
+	(0x10 + rax, Register.RAX, Register.NONE, 1, 0x10),
+	(rax - 0x10, Register.RAX, Register.NONE, 1, -0x10),
+	(rax + rcx, Register.RAX, Register.RCX, 1, 0),
+	(rax + rcx*8, Register.RAX, Register.RCX, 8, 0),
+	(rcx*4 + rax, Register.RAX, Register.RCX, 4, 0),
+	(2*rcx + rax - 1, Register.RAX, Register.RCX, 2, -1),
+	(rax + rcx*8 + 0x10, Register.RAX, Register.RCX, 8, 0x10),
+	(rax + xmm1*4 - 0x10, Register.RAX, Register.XMM1, 4, -0x10),
+	(qword_ptr(0x1234), Register.NONE, Register.NONE, 1, 0x1234),
+])
+def test_memory_operand(mem: AsmMemoryOperand, base: Register_, index: Register_, scale: int, displ: int) -> None:
+	if not isinstance(mem, AsmMemoryOperand):
+		mem = ptr(mem)
+	assert mem.base == base
+	assert mem.index == index
+	assert mem.scale == scale
+	assert mem.displ == displ
+
+def test_memory_operand_size() -> None:
+	assert ptr(rax).size == 0
+	assert byte_ptr(rax).size == 1
+	assert word_ptr(rax).size == 2
+	assert dword_ptr(rax).size == 4
+	assert fword_ptr(rax).size == 6
+	assert qword_ptr(rax).size == 8
+	assert mmword_ptr(rax).size == 8
+	assert tbyte_ptr(rax).size == 10
+	assert tword_ptr(rax).size == 10
+	assert oword_ptr(rax).size == 16
+	assert xmmword_ptr(rax).size == 16
+	assert ymmword_ptr(rax).size == 32
+	assert zmmword_ptr(rax).size == 64
+	assert not qword_ptr(rax).is_broadcast
+	assert word_bcst(rax).size == 2 and word_bcst(rax).is_broadcast
+	assert dword_bcst(rax).size == 4 and dword_bcst(rax).is_broadcast
+	assert qword_bcst(rax).size == 8 and qword_bcst(rax).is_broadcast
+	assert qword_ptr(rax).fs.segment == Register.FS
+	assert qword_ptr(rax).segment == Register.NONE
+
+def test_invalid_memory_operand() -> None:
+	with pytest.raises(ValueError):
+		rax * 3
+	with pytest.raises(ValueError):
+		rax + rcx + rdx
+	with pytest.raises(ValueError):
+		(rax + rcx*2) + (rdx + rbx*2)
+	a = CodeAssembler(64)
+	label = a.create_label()
+	with pytest.raises(ValueError):
+		ptr(label) + 1
+
+def test_ambiguous_memory_operand_size() -> None:
+	a = CodeAssembler(64)
+	with pytest.raises(ValueError):
+		a.inc(ptr(rax))
+	a.inc(dword_ptr(rax))
+	a.movups(xmm1, ptr(rax))
+	assert a.assemble(0) == b"\xFF\x00\x0F\x10\x08"
+
+def test_invalid_operands() -> None:
+	a = CodeAssembler(64)
+	with pytest.raises(ValueError):
+		a.mov(rax, ecx)
+	with pytest.raises(ValueError):
+		a.add(1, rax)
+	with pytest.raises(AttributeError):
+		a.not_a_mnemonic(rax)
+
+def test_segment_override() -> None:
+	a = CodeAssembler(64)
+	a.mov(rax, qword_ptr(rcx).fs)
+	a.mov(rax, qword_ptr(rcx).gs)
+	assert a.assemble(0) == b"\x64\x48\x8B\x01\x65\x48\x8B\x01"
+
+def test_avx512() -> None:
+	a = CodeAssembler(64)
+	a.vaddps(zmm1.k1.z, zmm2, dword_bcst(rax))
+	a.vaddps(zmm1, zmm2, zmm3.rd_sae)
+	a.vucomiss(xmm1, xmm2.sae)
+	a.vmovups(zmmword_ptr(rax).k2, zmm3)
+	instrs = a.instructions()
+	assert instrs[0].op_mask == Register.K1
+	assert instrs[0].zeroing_masking
+	assert instrs[0].is_broadcast
+	assert instrs[1].rounding_control == RoundingControl.ROUND_DOWN
+	assert instrs[2].suppress_all_exceptions
+	assert instrs[3].op_mask == Register.K2
+	assert a.assemble(0) == b"\x62\xF1\x6C\xD9\x58\x08\x62\xF1\x6C\x38\x58\xCB\x62\xF1\x7C\x18\x2E\xCA\x62\xF1\x7C\x4A\x11\x18"
+
+def test_prefixes() -> None:
+	a = CodeAssembler(64)
+	a.lock().add(dword_ptr(rax), ecx)
+	a.rep().stosb()
+	a.repne().scasb()
+	a.xacquire().lock().add(dword_ptr(rax), ecx)
+	a.evex().vaddps(xmm1, xmm2, xmm3)
+	a.vaddps(xmm1, xmm2, xmm3)
+	assert a.assemble(0) == b"\xF0\x01\x08\xF3\xAA\xF2\xAE\xF0\xF2\x01\x08\x62\xF1\x6C\x08\x58\xCB\xC5\xE8\x58\xCB"
+
+def test_prefer_vex() -> None:
+	a = CodeAssembler(64)
+	assert a.prefer_vex
+	a.prefer_vex = False
+	assert not a.prefer_vex
+	a.vaddps(xmm1, xmm2, xmm3)
+	a.vex().vaddps(xmm1, xmm2, xmm3)
+	assert a.assemble(0) == b"\x62\xF1\x6C\x08\x58\xCB\xC5\xE8\x58\xCB"
+
+@pytest.mark.parametrize("prefer_short_branch, jp_code, jne_code", [
+	(True, Code.JP_REL8_64, Code.JNE_REL8_64),
+	(False, Code.JP_REL32_64, Code.JNE_REL32_64),
+])
+def test_labels(prefer_short_branch: bool, jp_code: Code_, jne_code: Code_) -> None:
+	a = CodeAssembler(64)
+	assert a.prefer_short_branch
+	a.prefer_short_branch = prefer_short_branch
+	loop_lbl = a.create_label()
+	after_loop = a.create_label()
+	a.mov(ecx, 10)
+	a.set_label(loop_lbl)
+	a.dec(ecx)
+	a.jp(after_loop)
+	a.jne(loop_lbl)
+	a.set_label(after_loop)
+	a.ret()
+	instrs = a.instructions()
+	assert instrs[2].code == jp_code
+	assert instrs[3].code == jne_code
+	# Near branches are converted to short branches if possible
+	assert a.assemble(0x1234_5678) == b"\xB9\x0A\x00\x00\x00\xFF\xC9\x7A\x02\x75\xFA\xC3"
+
+def test_anonymous_labels() -> None:
+	a = CodeAssembler(64)
+	a.anonymous_label()
+	a.inc(rax)
+	a.jne(a.bwd())
+	a.jmp(a.fwd())
+	a.nop()
+	a.anonymous_label()
+	a.ret()
+	assert a.assemble(0) == b"\x48\xFF\xC0\x75\xFB\xEB\x01\x90\xC3"
+
+def test_label_errors() -> None:
+	a = CodeAssembler(64)
+	label = a.create_label()
+	a.set_label(label)
+	with pytest.raises(ValueError):
+		a.set_label(label)
+	a.nop()
+	with pytest.raises(ValueError):
+		a.bwd()
+	a.anonymous_label()
+	with pytest.raises(ValueError):
+		a.anonymous_label()
+
+def test_label_ip() -> None:
+	a = CodeAssembler(64)
+	skip_data = a.create_label()
+	data = a.create_label()
+	a.jmp(skip_data)
+	a.set_label(data)
+	a.db(b"\x90\xCC\xF1\x90")
+	a.set_label(skip_data)
+	a.lea(rax, ptr(data))
+	result = a.assemble_options(0x1234_5678)
+	assert result.rip == 0x1234_5678
+	assert result.code_buffer == b"\xEB\x04\x90\xCC\xF1\x90\x48\x8D\x05\xF5\xFF\xFF\xFF"
+	assert result.label_ip(data) == 0x1234_567A
+	assert result.label_ip(skip_data) == 0x1234_567E
+	assert data != skip_data
+	assert data == data
+	assert len({data, skip_data, data}) == 2
+
+def test_data() -> None:
+	a = CodeAssembler(64)
+	a.db(bytearray(b"\x01\x02"))
+	a.dw([0x1234, -1])
+	a.dd([0x1234_5678, -2])
+	a.dq([0x1234_5678_9ABC_DEF0, -3])
+	assert a.assemble(0) == b"\x01\x02\x34\x12\xFF\xFF\x78\x56\x34\x12\xFE\xFF\xFF\xFF\xF0\xDE\xBC\x9A\x78\x56\x34\x12\xFD\xFF\xFF\xFF\xFF\xFF\xFF\xFF"
+
+@pytest.mark.parametrize("bits, value", [
+	(16, 0x1_0000),
+	(16, -0x8001),
+	(32, 0x1_0000_0000),
+	(32, -0x8000_0001),
+])
+def test_data_out_of_range(bits: int, value: int) -> None:
+	a = CodeAssembler(64)
+	with pytest.raises(ValueError):
+		if bits == 16:
+			a.dw([value])
+		else:
+			a.dd([value])
+
+def test_data_with_prefix_fails() -> None:
+	a = CodeAssembler(64)
+	with pytest.raises(ValueError):
+		a.lock().db(b"\x90")
+
+def test_add_instruction() -> None:
+	a = CodeAssembler(64)
+	a.add_instruction(Instruction.create_reg_reg(Code.ADD_R64_RM64, Register.RAX, Register.RCX))
+	a.nops_with_size(3)
+	a.zero_bytes()
+	a.xlatb()
+	assert a.assemble(0) == b"\x48\x03\xC1\x0F\x1F\x00\xD7"
+
+def test_far_branches() -> None:
+	a = CodeAssembler(32)
+	a.call_far(0x1234, 0x5678_9ABC)
+	a.jmp_far(0x1234, 0x5678_9ABC)
+	assert a.assemble(0) == b"\x9A\xBC\x9A\x78\x56\x34\x12\xEA\xBC\x9A\x78\x56\x34\x12"
+	a = CodeAssembler(64)
+	a.call_far(0x1234, 0x5678_9ABC)
+	with pytest.raises(ValueError):
+		a.assemble(0)
+
+def test_reset() -> None:
+	a = CodeAssembler(64)
+	a.nop()
+	a.reset()
+	a.int3()
+	assert a.assemble(0) == b"\xCC"
@@ -0,0 +1,136 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+from typing import List, Optional
+import pytest
+from iced_x86 import *
+
+def test_symbol_resolver_str() -> None:
+	def resolver(instruction: Instruction, operand: int, instruction_operand: Optional[int], address: int, address_size: int) -> Optional[str]:
+		assert instruction.code == Code.CALL_REL32_64
+		assert operand == 0
+		assert instruction_operand == 0
+		assert address_size == 8
+		if address == 0x5AA55AA5:
+			return "printf"
+		return None
+
+	decoder = Decoder(64, b"\xE8\x00\x00\x00\x00\xE8\x00\x00\x00\x00", ip=0x5AA55AA0)
+	formatter = Formatter(FormatterSyntax.MASM, symbol_resolver=resolver)
+	assert formatter.format(decoder.decode()) == "call printf"
+	assert formatter.format(decoder.decode()) == "call 000000005AA55AAAh"
+
+def test_symbol_resolver_symbol_result() -> None:
+	def resolver(instruction: Instruction, operand: int, instruction_operand: Optional[int], address: int, address_size: int) -> SymbolResult:
+		return SymbolResult(0x5AA55AA0, [("my", FormatterTextKind.FUNCTION), ("_func", FormatterTextKind.DATA)])
+
+	decoder = Decoder(64, b"\xE8\x00\x00\x00\x00", ip=0x5AA55AA0)
+	formatter = Formatter(FormatterSyntax.MASM, symbol_resolver=resolver)
+	assert formatter.format(decoder.decode()) == "call my_func+5"
+
+def test_symbol_result_getters() -> None:
+	symbol = SymbolResult(0x1234, "abc", FormatterTextKind.FUNCTION, SymbolFlags.RELATIVE | SymbolFlags.SIGNED, MemorySize.UINT32)
+	assert symbol.address == 0x1234
+	assert symbol.text == [("abc", FormatterTextKind.FUNCTION)]
+	assert symbol.flags == SymbolFlags.RELATIVE | SymbolFlags.SIGNED
+	assert symbol.symbol_size == MemorySize.UINT32
+
+	symbol = SymbolResult(0x1234, [("a", FormatterTextKind.DATA), ("b", FormatterTextKind.LABEL)])
+	assert symbol.text == [("a", FormatterTextKind.DATA), ("b", FormatterTextKind.LABEL)]
+	assert symbol.flags == SymbolFlags.NONE
+	assert symbol.symbol_size is None
+
+def test_symbol_result_invalid_args() -> None:
+	with pytest.raises(ValueError):
+		SymbolResult(0, "abc", 0x12345) # type: ignore
+	with pytest.raises(ValueError):
+		SymbolResult(0, [("abc", 0x12345)]) # type: ignore
+	with pytest.raises(ValueError):
+		SymbolResult(0, "abc", symbol_size=0x12345) # type: ignore
+
+def test_symbol_resolver_cache() -> None:
+	calls: List[int] = []
+	def resolver(instruction: Instruction, operand: int, instruction_operand: Optional[int], address: int, address_size: int) -> str:
+		calls.append(address)
+		return "sym"
+
+	instr = Decoder(64, b"\xE8\x00\x00\x00\x00", ip=0x5AA55AA0).decode()
+	formatter = Formatter(FormatterSyntax.NASM, symbol_resolver=resolver)
+	assert formatter.format(instr) == "call sym"
+	assert formatter.format(instr) == "call sym"
+	assert calls == [0x5AA55AA5]
+	formatter.clear_symbol_cache()
+	assert formatter.format(instr) == "call sym"
+	assert calls == [0x5AA55AA5, 0x5AA55AA5]
+
+def test_symbol_resolver_raises() -> None:
+	def resolver(instruction: Instruction, operand: int, instruction_operand: Optional[int], address: int, address_size: int) -> str:
+		raise KeyError("abc")
+
+	instr = Decoder(64, b"\xE8\x00\x00\x00\x00", ip=0x5AA55AA0).decode()
+	formatter = Formatter(FormatterSyntax.NASM, symbol_resolver=resolver)
+	with pytest.raises(KeyError):
+		formatter.format(instr)
+	with pytest.raises(KeyError):
+		formatter.format_all_operands(instr)
+
+def test_symbol_resolver_invalid_return_value() -> None:
+	def resolver(instruction: Instruction, operand: int, instruction_operand: Optional[int], address: int, address_size: int) -> int:
+		return 123
+
+	instr = Decoder(64, b"\xE8\x00\x00\x00\x00", ip=0x5AA55AA0).decode()
+	formatter = Formatter(FormatterSyntax.NASM, symbol_resolver=resolver) # type: ignore
+	with pytest.raises(TypeError):
+		formatter.format(instr)
+
+def test_options_provider() -> None:
+	def provider(instruction: Instruction, operand: int, instruction_operand: Optional[int], options: FormatterOperandOptions, number_options: NumberFormattingOptions) -> None:
+		if operand == 1:
+			number_options.number_base = NumberBase.DECIMAL
+			number_options.suffix = ""
+
+	instr = Decoder(64, b"\x48\x83\xC0\x10").decode()
+	formatter = Formatter(FormatterSyntax.MASM, options_provider=provider)
+	assert formatter.format(instr) == "add rax,16"
+
+def test_options_provider_strings() -> None:
+	def provider(instruction: Instruction, operand: int, instruction_operand: Optional[int], options: FormatterOperandOptions, number_options: NumberFormattingOptions) -> None:
+		assert number_options.number_base == NumberBase.HEXADECIMAL
+		number_options.prefix = "$"
+		number_options.suffix = ""
+		number_options.digit_separator = "_"
+		number_options.digit_group_size = 2
+
+	instr = Decoder(64, b"\x48\x05\x78\x56\x34\x12").decode()
+	formatter = Formatter(FormatterSyntax.MASM, options_provider=provider)
+	assert formatter.format(instr) == "add rax,$12_34_56_78"
+
+def test_options_provider_memory_size_options() -> None:
+	def provider(instruction: Instruction, operand: int, instruction_operand: Optional[int], options: FormatterOperandOptions, number_options: NumberFormattingOptions) -> None:
+		assert options.memory_size_options == MemorySizeOptions.DEFAULT
+		options.memory_size_options = MemorySizeOptions.ALWAYS
+
+	instr = Decoder(64, b"\x8B\x00").decode()
+	assert Formatter(FormatterSyntax.MASM).format(instr) == "mov eax,[rax]"
+	formatter = Formatter(FormatterSyntax.MASM, options_provider=provider)
+	assert formatter.format(instr) == "mov eax,dword ptr [rax]"
+
+def test_options_provider_raises() -> None:
+	def provider(instruction: Instruction, operand: int, instruction_operand: Optional[int], options: FormatterOperandOptions, number_options: NumberFormattingOptions) -> None:
+		raise KeyError("abc")
+
+	instr = Decoder(64, b"\x48\x83\xC0\x10").decode()
+	formatter = Formatter(FormatterSyntax.MASM, options_provider=provider)
+	with pytest.raises(KeyError):
+		formatter.format(instr)
+	with pytest.raises(KeyError):
+		formatter.format_operand(instr, 1)
+
+def test_options_provider_invalid_enum_value() -> None:
+	def provider(instruction: Instruction, operand: int, instruction_operand: Optional[int], options: FormatterOperandOptions, number_options: NumberFormattingOptions) -> None:
+		number_options.number_base = 0x12345 # type: ignore
+
+	instr = Decoder(64, b"\x48\x83\xC0\x10").decode()
+	formatter = Formatter(FormatterSyntax.MASM, options_provider=provider)
+	with pytest.raises(ValueError):
+		formatter.format(instr)
@@ -0,0 +1,93 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+from typing import Any, List, Optional, Tuple
+import pytest
+from iced_x86 import *
+
+class WriteOnlyOutput:
+	def __init__(self) -> None:
+		self.parts: List[Tuple[str, FormatterTextKind_]] = []
+
+	def write(self, text: str, kind: FormatterTextKind_) -> None:
+		self.parts.append((text, kind))
+
+class AllMethodsOutput(WriteOnlyOutput):
+	def __init__(self) -> None:
+		super().__init__()
+		self.calls: List[Tuple[Any, ...]] = []
+
+	def write_prefix(self, instruction: Instruction, text: str, prefix: PrefixKind_) -> None:
+		self.calls.append(("prefix", text, prefix))
+
+	def write_mnemonic(self, instruction: Instruction, text: str) -> None:
+		self.calls.append(("mnemonic", text))
+
+	def write_number(self, instruction: Instruction, operand: int, instruction_operand: Optional[int], text: str, value: int, number_kind: NumberKind_, kind: FormatterTextKind_) -> None:
+		self.calls.append(("number", operand, instruction_operand, text, value, number_kind, kind))
+
+	def write_decorator(self, instruction: Instruction, operand: int, instruction_operand: Optional[int], text: str, decorator: DecoratorKind_) -> None:
+		self.calls.append(("decorator", operand, instruction_operand, text, decorator))
+
+	def write_register(self, instruction: Instruction, operand: int, instruction_operand: Optional[int], text: str, register: Register_) -> None:
+		self.calls.append(("register", operand, instruction_operand, text, register))
+
+def test_format_to_list() -> None:
+	instr = Decoder(64, b"\xF0\x48\x83\x00\x10").decode()
+	formatter = Formatter(FormatterSyntax.MASM)
+	parts = formatter.format_to_list(instr)
+	assert "".join(text for text, _ in parts) == formatter.format(instr)
+	assert parts == [
+		("lock", FormatterTextKind.PREFIX),
+		(" ", FormatterTextKind.TEXT),
+		("add", FormatterTextKind.MNEMONIC),
+		(" ", FormatterTextKind.TEXT),
+		("qword", FormatterTextKind.KEYWORD),
+		(" ", FormatterTextKind.TEXT),
+		("ptr", FormatterTextKind.KEYWORD),
+		(" ", FormatterTextKind.TEXT),
+		("[", FormatterTextKind.PUNCTUATION),
+		("rax", FormatterTextKind.REGISTER),
+		("]", FormatterTextKind.PUNCTUATION),
+		(",", FormatterTextKind.PUNCTUATION),
+		("10h", FormatterTextKind.NUMBER),
+	]
+
+def test_format_output_write_only() -> None:
+	instr = Decoder(64, b"\xF0\x48\x83\x00\x10").decode()
+	formatter = Formatter(FormatterSyntax.MASM)
+	output = WriteOnlyOutput()
+	formatter.format_output(instr, output)
+	assert output.parts == formatter.format_to_list(instr)
+
+def test_format_output_all_methods() -> None:
+	instr = Decoder(64, b"\xF0\x48\x83\x00\x10").decode()
+	output = AllMethodsOutput()
+	Formatter(FormatterSyntax.MASM).format_output(instr, output)
+	assert output.calls == [
+		("prefix", "lock", PrefixKind.LOCK),
+		("mnemonic", "add"),
+		("register", 0, 0, "rax", Register.RAX),
+		("number", 1, 1, "10h", 0x10, NumberKind.UINT64, FormatterTextKind.NUMBER),
+	]
+	assert ("lock", FormatterTextKind.PREFIX) not in output.parts
+	assert ("ptr", FormatterTextKind.KEYWORD) in output.parts
+
+def test_format_output_decorator() -> None:
+	instr = Decoder(64, b"\x62\xF1\x7C\x9B\x58\xC2").decode()
+	output = AllMethodsOutput()
+	Formatter(FormatterSyntax.MASM).format_output(instr, output)
+	assert ("decorator", 0, 0, "z", DecoratorKind.ZEROING_MASKING) in output.calls
+	assert ("decorator", 2, 2, "rn-sae", DecoratorKind.ROUNDING_CONTROL) in output.calls
+
+def test_format_output_raises() -> None:
+	class Output:
+		def write(self, text: str, kind: FormatterTextKind_) -> None:
+			raise KeyError("abc")
+
+	instr = Decoder(64, b"\x48\x83\xC0\x10").decode()
+	formatter = Formatter(FormatterSyntax.MASM)
+	with pytest.raises(KeyError):
+		formatter.format_output(instr, Output())
+	with pytest.raises(AttributeError):
+		formatter.format_output(instr, 123)
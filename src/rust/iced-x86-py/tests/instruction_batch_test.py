@@ -0,0 +1,90 @@
+# SPDX-License-Identifier: MIT
+# Copyright (C) 2018-present iced project and contributors
+
+import struct
+import pytest
+from iced_x86 import *
+
+# mov [rsp+10h],rbx / push rbp / call 12345683h / add dword ptr [rax+rcx*4-10h],12345678h / nop
+DATA = b"\x48\x89\x5C\x24\x10\x55\xE8\x00\x00\x00\x00\x81\x44\x88\xF0\x78\x56\x34\x12\x90"
+IP = 0x1234_5678
+
+def test_record_format() -> None:
+	assert struct.calcsize(InstructionBatch.RECORD_FORMAT) == InstructionBatch.RECORD_SIZE
+	dtype = InstructionBatch.RECORD_DTYPE
+	assert dtype["itemsize"] == InstructionBatch.RECORD_SIZE
+	assert len(dtype["names"]) == len(dtype["formats"]) == len(dtype["offsets"])
+
+def test_decode_all() -> None:
+	decoder = Decoder(64, DATA, ip=IP)
+	instrs = list(Decoder(64, DATA, ip=IP))
+	batch = decoder.decode_batch()
+	assert not decoder.can_decode
+	assert len(batch) == len(instrs)
+	assert len(batch.data) == len(instrs) * InstructionBatch.RECORD_SIZE
+	assert batch.data.readonly
+
+	records = list(struct.iter_unpack(InstructionBatch.RECORD_FORMAT, batch.data))
+	position = 0
+	for instr, record, i in zip(instrs, records, range(len(instrs))):
+		ip, rec_position, immediate, displ, code, mnemonic = record[0:6]
+		op_registers = record[6:11]
+		memory_base, memory_index, length, op_count = record[11:15]
+		op_kinds = record[15:20]
+		memory_index_scale, flow_control = record[20:22]
+
+		assert ip == instr.ip
+		assert rec_position == position
+		assert code == instr.code
+		assert mnemonic == instr.mnemonic
+		assert memory_base == instr.memory_base
+		assert memory_index == instr.memory_index
+		assert length == instr.len
+		assert op_count == instr.op_count
+		assert memory_index_scale == instr.memory_index_scale
+		assert flow_control == instr.flow_control
+		for op in range(5):
+			if op < instr.op_count:
+				assert op_kinds[op] == instr.op_kind(op)
+				assert op_registers[op] == instr.op_register(op)
+			else:
+				assert op_kinds[op] == 0
+				assert op_registers[op] == Register.NONE
+		assert batch[i] == instr
+		position += instr.len
+
+	assert records[3][2] == 0x1234_5678
+	assert records[3][3] == 0xFFFF_FFFF_FFFF_FFF0
+	assert records[2][2] == 0
+	assert records[2][3] == 0
+
+def test_decode_count() -> None:
+	decoder = Decoder(64, DATA, ip=IP)
+	batch = decoder.decode_batch(2)
+	assert len(batch) == 2
+	assert decoder.position == 6
+	assert decoder.ip == IP + 6
+	assert batch[1].code == Code.PUSH_R64
+	batch = decoder.decode_batch(100)
+	assert len(batch) == 3
+	assert batch[0].code == Code.CALL_REL32_64
+	assert batch[0].near_branch_target == 0x1234_5683
+	assert len(decoder.decode_batch()) == 0
+	assert len(decoder.decode_batch(0)) == 0
+
+def test_index() -> None:
+	batch = Decoder(64, DATA, ip=IP).decode_batch()
+	assert batch[-1].code == Code.NOPD
+	assert batch[-5].code == Code.MOV_RM64_R64
+	with pytest.raises(IndexError):
+		batch[5]
+	with pytest.raises(IndexError):
+		batch[-6]
+	assert [instr.code for instr in batch] == [instr.code for instr in Decoder(64, DATA, ip=IP)]
+
+def test_invalid_instructions() -> None:
+	data = b"\x90\xF3\x0F"
+	batch = Decoder(64, data).decode_batch()
+	assert len(batch) == 2
+	assert batch[1].code == Code.INVALID
+	assert batch[1].len == 2
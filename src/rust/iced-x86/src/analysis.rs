@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+//! Static analysis of decoded instructions
+//!
+//! This requires the `analysis` feature to use (not enabled by default). Add it to your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.iced-x86]
+//! version = "1.21.0"
+//! features = ["analysis"]
+//! ```
+
+mod cfg;
+mod function;
+mod gadget;
+mod ibt;
+mod isa_report;
+mod jump_table;
+mod pattern;
+#[cfg(test)]
+mod tests;
+
+pub use crate::analysis::cfg::*;
+pub use crate::analysis::function::*;
+pub use crate::analysis::gadget::*;
+pub use crate::analysis::ibt::*;
+pub use crate::analysis::isa_report::*;
+pub use crate::analysis::jump_table::*;
+pub use crate::analysis::pattern::*;
@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::{Code, FlowControl, Instruction, OpKind};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Kind of a control flow [`Edge`]
+///
+/// [`Edge`]: struct.Edge.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum EdgeKind {
+	/// Execution continues at the next instruction (eg. after a `CALL` or a not-taken `Jcc`)
+	Fallthrough,
+	/// Unconditional `JMP`
+	Branch,
+	/// Taken conditional branch (`Jcc`, `LOOP`, `JRCXZ`, `XBEGIN`)
+	ConditionalBranch,
+}
+
+/// An edge from a [`BasicBlock`] to another address
+///
+/// [`BasicBlock`]: struct.BasicBlock.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct Edge {
+	/// Target address. It's not necessarily the start of a block in the graph, eg. a branch outside of the decoded code.
+	pub target: u64,
+	/// Kind of edge
+	pub kind: EdgeKind,
+}
+
+/// A basic block in a [`ControlFlowGraph`]
+///
+/// [`ControlFlowGraph`]: struct.ControlFlowGraph.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct BasicBlock {
+	/// Address of the first instruction
+	pub start: u64,
+	/// Address of the instruction following the last instruction
+	pub end: u64,
+	/// Indexes of the instructions in the slice passed to [`ControlFlowGraph::new()`]
+	///
+	/// [`ControlFlowGraph::new()`]: struct.ControlFlowGraph.html#method.new
+	pub instructions: Range<usize>,
+	/// Flow control of the last instruction
+	pub flow_control: FlowControl,
+	/// All direct successors. Indirect branches, returns and far branches have no successors.
+	pub successors: Vec<Edge>,
+}
+
+/// Control flow graph of decoded instructions
+///
+/// A new block starts at the first instruction, at every direct branch target, after every instruction
+/// that changes the flow control (including `CALL`) and after gaps in the code. Call targets don't start a new block but
+/// are available in [`call_targets()`].
+///
+/// [`call_targets()`]: #method.call_targets
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+/// use iced_x86::*;
+///
+/// // 0x1000: test ecx,ecx
+/// // 0x1002: je short 1007h
+/// // 0x1004: inc eax
+/// // 0x1006: ret
+/// // 0x1007: xor eax,eax
+/// // 0x1009: ret
+/// let bytes = b"\x85\xC9\x74\x03\xFF\xC0\xC3\x31\xC0\xC3";
+/// let instrs: Vec<Instruction> = Decoder::with_ip(64, bytes, 0x1000, DecoderOptions::NONE).into_iter().collect();
+/// let cfg = ControlFlowGraph::new(&instrs);
+///
+/// let starts: Vec<u64> = cfg.blocks().iter().map(|b| b.start).collect();
+/// assert_eq!(starts, [0x1000, 0x1004, 0x1007]);
+/// let first = &cfg.blocks()[0];
+/// assert_eq!(first.instructions, 0..2);
+/// let targets: Vec<u64> = first.successors.iter().map(|e| e.target).collect();
+/// assert_eq!(targets, [0x1007, 0x1004]);
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct ControlFlowGraph {
+	blocks: Vec<BasicBlock>,
+	call_targets: Vec<u64>,
+}
+
+impl ControlFlowGraph {
+	/// Creates the control flow graph
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Instructions sorted by IP, eg. all instructions returned by a [`Decoder`]
+	///
+	/// [`Decoder`]: ../struct.Decoder.html
+	#[must_use]
+	#[inline]
+	pub fn new(instructions: &[Instruction]) -> Self {
+		Self::with_leaders(instructions, &[])
+	}
+
+	/// Creates the control flow graph and starts a new block at each address in `leaders`, eg. known function starts.
+	/// Addresses that aren't the address of an instruction are ignored.
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Instructions sorted by IP, eg. all instructions returned by a [`Decoder`]
+	/// * `leaders`: Extra block start addresses
+	///
+	/// [`Decoder`]: ../struct.Decoder.html
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn with_leaders(instructions: &[Instruction], leaders: &[u64]) -> Self {
+		let mut is_leader = vec![false; instructions.len()];
+		let mut call_targets = Vec::new();
+		let index_of = |address: u64| instructions.binary_search_by_key(&address, Instruction::ip).ok();
+
+		if let Some(first) = is_leader.first_mut() {
+			*first = true;
+		}
+		for &address in leaders {
+			if let Some(index) = index_of(address) {
+				is_leader[index] = true;
+			}
+		}
+		for (i, instruction) in instructions.iter().enumerate() {
+			let ends_block = instruction.is_invalid() || instruction.flow_control() != FlowControl::Next;
+			let has_gap = instructions.get(i + 1).map_or(false, |next| next.ip() != instruction.next_ip());
+			if (ends_block || has_gap) && i + 1 < instructions.len() {
+				is_leader[i + 1] = true;
+			}
+			if let Some(target) = near_branch_target(instruction) {
+				if instruction.flow_control() == FlowControl::Call {
+					call_targets.push(target);
+				} else if let Some(index) = index_of(target) {
+					is_leader[index] = true;
+				}
+			}
+		}
+		call_targets.sort_unstable();
+		call_targets.dedup();
+
+		let mut blocks = Vec::new();
+		let mut start = 0;
+		for end in 1..=instructions.len() {
+			if end < instructions.len() && !is_leader[end] {
+				continue;
+			}
+			let last = &instructions[end - 1];
+			blocks.push(BasicBlock {
+				start: instructions[start].ip(),
+				end: last.next_ip(),
+				instructions: start..end,
+				flow_control: last.flow_control(),
+				successors: successors(last),
+			});
+			start = end;
+		}
+
+		Self { blocks, call_targets }
+	}
+
+	/// Gets all blocks, sorted by address
+	#[must_use]
+	#[inline]
+	pub fn blocks(&self) -> &[BasicBlock] {
+		&self.blocks
+	}
+
+	/// Gets the index of the block that starts at `address`
+	///
+	/// # Arguments
+	///
+	/// * `address`: Address of the first instruction in the block
+	#[must_use]
+	#[inline]
+	pub fn block_index(&self, address: u64) -> Option<usize> {
+		self.blocks.binary_search_by_key(&address, |block| block.start).ok()
+	}
+
+	/// Gets the index of the block that contains the instruction at `address`
+	///
+	/// # Arguments
+	///
+	/// * `address`: Address
+	#[must_use]
+	#[inline]
+	pub fn block_index_containing(&self, address: u64) -> Option<usize> {
+		let index = match self.blocks.binary_search_by_key(&address, |block| block.start) {
+			Ok(index) => index,
+			Err(0) => return None,
+			Err(index) => index - 1,
+		};
+		if address < self.blocks[index].end {
+			Some(index)
+		} else {
+			None
+		}
+	}
+
+	/// Gets all unique targets of direct `CALL` instructions, sorted by address. They can be outside of the decoded code.
+	#[must_use]
+	#[inline]
+	pub fn call_targets(&self) -> &[u64] {
+		&self.call_targets
+	}
+}
+
+pub(super) fn near_branch_target(instruction: &Instruction) -> Option<u64> {
+	match instruction.op0_kind() {
+		OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 => Some(instruction.near_branch_target()),
+		_ => None,
+	}
+}
+
+// `true` if the next instruction can be executed after `instruction`
+pub(super) fn has_fallthrough(instruction: &Instruction) -> bool {
+	if instruction.is_invalid() {
+		return false;
+	}
+	match instruction.flow_control() {
+		FlowControl::Next | FlowControl::ConditionalBranch | FlowControl::Call | FlowControl::IndirectCall | FlowControl::XbeginXabortXend => true,
+		FlowControl::Interrupt => instruction.code() != Code::Int3,
+		FlowControl::UnconditionalBranch | FlowControl::IndirectBranch | FlowControl::Return | FlowControl::Exception => false,
+	}
+}
+
+fn successors(instruction: &Instruction) -> Vec<Edge> {
+	let mut edges = Vec::new();
+	let target_kind = match instruction.flow_control() {
+		FlowControl::UnconditionalBranch => Some(EdgeKind::Branch),
+		FlowControl::ConditionalBranch | FlowControl::XbeginXabortXend => Some(EdgeKind::ConditionalBranch),
+		_ => None,
+	};
+	if let (Some(kind), Some(target)) = (target_kind, near_branch_target(instruction)) {
+		edges.push(Edge { target, kind });
+	}
+	if has_fallthrough(instruction) {
+		edges.push(Edge { target: instruction.next_ip(), kind: EdgeKind::Fallthrough });
+	}
+	edges
+}
@@ -0,0 +1,424 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::analysis::cfg::{has_fallthrough, near_branch_target, ControlFlowGraph, EdgeKind};
+use crate::analysis::pattern::{InstrPattern, MemoryPattern, OperandPattern, PatternSequence, RegisterClass};
+use crate::iced_error::IcedError;
+use crate::{Code, Decoder, DecoderOptions, FlowControl, Instruction, Mnemonic, Register};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+/// Reasons why an address was detected as the start of a function, see [`FunctionBoundary::sources`]
+///
+/// [`FunctionBoundary::sources`]: struct.FunctionBoundary.html#structfield.sources
+#[allow(missing_copy_implementations)]
+#[allow(missing_debug_implementations)]
+pub struct FunctionSources;
+impl FunctionSources {
+	/// No sources
+	pub const NONE: u32 = 0x0000_0000;
+	/// It was added by [`FunctionFinder::add_entry_point()`]
+	///
+	/// [`FunctionFinder::add_entry_point()`]: struct.FunctionFinder.html#method.add_entry_point
+	pub const ENTRY_POINT: u32 = 0x0000_0001;
+	/// It was added by [`FunctionFinder::add_unwind_function()`]
+	///
+	/// [`FunctionFinder::add_unwind_function()`]: struct.FunctionFinder.html#method.add_unwind_function
+	pub const UNWIND: u32 = 0x0000_0002;
+	/// It's the target of a direct `CALL`
+	pub const CALL_TARGET: u32 = 0x0000_0004;
+	/// It's the target of a tail call (a `JMP` to an address before the start of the function containing the `JMP`)
+	pub const TAIL_CALL_TARGET: u32 = 0x0000_0008;
+	/// It starts with a known prologue, eg. `PUSH RBP` + `MOV RBP,RSP`
+	pub const PROLOGUE: u32 = 0x0000_0010;
+	/// It starts with `ENDBR64` or `ENDBR32`
+	pub const ENDBR: u32 = 0x0000_0020;
+	/// It's aligned and follows alignment padding (`INT3` or `NOP`) after an instruction that doesn't continue to the next instruction (eg. `RET`)
+	pub const PADDING: u32 = 0x0000_0040;
+}
+
+/// How likely it is that a [`FunctionBoundary`] is a real function
+///
+/// [`FunctionBoundary`]: struct.FunctionBoundary.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum Confidence {
+	/// It's only aligned and follows padding or it only starts with `ENDBR64`/`ENDBR32` (which is also used by indirect jump targets)
+	Low,
+	/// It only starts with a prologue or it's only a tail call target
+	Medium,
+	/// It's an entry point, it's in the unwind data, it's a call target or there are at least two weak signals
+	High,
+}
+
+/// A function found by [`FunctionFinder`]
+///
+/// [`FunctionFinder`]: struct.FunctionFinder.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct FunctionBoundary {
+	/// Address of the first instruction
+	pub start: u64,
+	/// End address (exclusive). It's the end of the last block reachable from [`start`] or the end address
+	/// of the unwind data if it's known.
+	///
+	/// [`start`]: #structfield.start
+	pub end: u64,
+	/// Confidence
+	pub confidence: Confidence,
+	/// Why it was detected, a combination of [`FunctionSources`] flags
+	///
+	/// [`FunctionSources`]: struct.FunctionSources.html
+	pub sources: u32,
+}
+
+/// Finds functions in code using heuristics
+///
+/// The code is decoded linearly and function starts are detected from entry points and unwind data
+/// (if provided by the caller), `CALL` targets, prologues, `ENDBR64`/`ENDBR32` and alignment padding.
+/// The end of each function is found by walking the [`ControlFlowGraph`] from its start until
+/// returns, indirect branches and tail calls (a `JMP` to another function or to an address outside of the code).
+///
+/// [`ControlFlowGraph`]: struct.ControlFlowGraph.html
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+///
+/// // 0x1000: call 1010h
+/// // 0x1005: ret
+/// // 0x1006: int3 (x10)
+/// // 0x1010: push rbp
+/// // 0x1011: mov rbp,rsp
+/// // 0x1014: xor eax,eax
+/// // 0x1016: pop rbp
+/// // 0x1017: ret
+/// let bytes = b"\xE8\x0B\x00\x00\x00\xC3\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\xCC\x55\x48\x89\xE5\x31\xC0\x5D\xC3";
+/// let mut finder = FunctionFinder::new(64).unwrap();
+/// finder.add_entry_point(0x1000);
+/// let functions = finder.find(bytes, 0x1000);
+///
+/// assert_eq!(functions.len(), 2);
+/// assert_eq!((functions[0].start, functions[0].end), (0x1000, 0x1006));
+/// assert_eq!((functions[1].start, functions[1].end), (0x1010, 0x1018));
+/// assert_eq!(functions[1].confidence, Confidence::High);
+/// assert_eq!(functions[1].sources, FunctionSources::CALL_TARGET | FunctionSources::PROLOGUE | FunctionSources::PADDING);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FunctionFinder {
+	bitness: u32,
+	decoder_options: u32,
+	alignment: u32,
+	entry_points: Vec<u64>,
+	unwind_functions: Vec<(u64, u64)>,
+}
+
+impl FunctionFinder {
+	/// Creates a new instance
+	///
+	/// # Errors
+	///
+	/// Fails if `bitness` is not one of 16, 32, 64.
+	///
+	/// # Arguments
+	///
+	/// * `bitness`: 16, 32 or 64
+	#[inline]
+	pub fn new(bitness: u32) -> Result<Self, IcedError> {
+		match bitness {
+			16 | 32 | 64 => {}
+			_ => return Err(IcedError::new("Invalid bitness")),
+		}
+		Ok(Self { bitness, decoder_options: DecoderOptions::NONE, alignment: 16, entry_points: Vec::new(), unwind_functions: Vec::new() })
+	}
+
+	/// Gets the bitness (16, 32 or 64)
+	#[must_use]
+	#[inline]
+	pub fn bitness(&self) -> u32 {
+		self.bitness
+	}
+
+	/// Gets the [`DecoderOptions`] passed to the decoder (default: [`DecoderOptions::NONE`])
+	///
+	/// [`DecoderOptions`]: ../struct.DecoderOptions.html
+	/// [`DecoderOptions::NONE`]: ../struct.DecoderOptions.html#associatedconstant.NONE
+	#[must_use]
+	#[inline]
+	pub fn decoder_options(&self) -> u32 {
+		self.decoder_options
+	}
+
+	/// Sets the [`DecoderOptions`] passed to the decoder (default: [`DecoderOptions::NONE`])
+	///
+	/// [`DecoderOptions`]: ../struct.DecoderOptions.html
+	/// [`DecoderOptions::NONE`]: ../struct.DecoderOptions.html#associatedconstant.NONE
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_decoder_options(&mut self, new_value: u32) {
+		self.decoder_options = new_value;
+	}
+
+	/// Gets the alignment of functions that follow padding (default: 16)
+	#[must_use]
+	#[inline]
+	pub fn alignment(&self) -> u32 {
+		self.alignment
+	}
+
+	/// Sets the alignment of functions that follow padding (default: 16). `0` and `1` mean that any address is accepted.
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_alignment(&mut self, new_value: u32) {
+		self.alignment = new_value;
+	}
+
+	/// Adds a known function start, eg. the entry point or an exported function
+	///
+	/// # Arguments
+	///
+	/// * `address`: Address of the function
+	#[inline]
+	pub fn add_entry_point(&mut self, address: u64) {
+		self.entry_points.push(address);
+	}
+
+	/// Adds a function from the unwind data, eg. a `RUNTIME_FUNCTION` in a PE file's exception directory or an FDE in `.eh_frame`
+	///
+	/// # Arguments
+	///
+	/// * `start`: Start address of the function
+	/// * `end`: End address (exclusive) of the function
+	#[inline]
+	pub fn add_unwind_function(&mut self, start: u64, end: u64) {
+		self.unwind_functions.push((start, end));
+	}
+
+	/// Finds all functions in `code`. The result is sorted by address.
+	///
+	/// # Arguments
+	///
+	/// * `code`: Code to search
+	/// * `ip`: Address of `code[0]`
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn find(&self, code: &[u8], ip: u64) -> Vec<FunctionBoundary> {
+		let code_end = ip.wrapping_add(code.len() as u64);
+		let in_code = |address: u64| address.wrapping_sub(ip) < code.len() as u64;
+		let instructions = self.decode(code, ip);
+		let index_of = |address: u64| instructions.binary_search_by_key(&address, Instruction::ip).ok();
+
+		// Function start -> FunctionSources
+		let mut candidates: BTreeMap<u64, u32> = BTreeMap::new();
+		let mut add_candidate = |address: u64, source: u32| *candidates.entry(address).or_default() |= source;
+		for &address in &self.entry_points {
+			add_candidate(address, FunctionSources::ENTRY_POINT);
+		}
+		for &(start, _) in &self.unwind_functions {
+			add_candidate(start, FunctionSources::UNWIND);
+		}
+		let prologues = self.prologue_patterns();
+		let mut prologue_end = 0;
+		for (i, instruction) in instructions.iter().enumerate() {
+			if instruction.flow_control() == FlowControl::Call {
+				if let Some(target) = near_branch_target(instruction) {
+					add_candidate(target, FunctionSources::CALL_TARGET);
+				}
+			}
+			if matches!(instruction.code(), Code::Endbr64 | Code::Endbr32) {
+				add_candidate(instruction.ip(), FunctionSources::ENDBR);
+			}
+			if self.follows_padding(&instructions, i) {
+				add_candidate(instruction.ip(), FunctionSources::PADDING);
+			}
+			// Don't report `push rbp` if the prologue starts with a `push rbx` at the previous instruction
+			if i >= prologue_end {
+				if let Some(m) = prologues.iter().filter_map(|p| p.match_at(&instructions, i)).max_by_key(|m| m.end) {
+					prologue_end = m.end;
+					add_candidate(instruction.ip(), FunctionSources::PROLOGUE);
+				}
+			}
+		}
+		candidates.retain(|&address, _| in_code(address) && index_of(address).is_some());
+
+		let leaders: Vec<u64> = candidates.keys().copied().collect();
+		let cfg = ControlFlowGraph::with_leaders(&instructions, &leaders);
+
+		// Remove candidates with low confidence that are reachable from another function (eg. an aligned loop
+		// after a `JMP`) and add tail call targets until nothing changes
+		let mut walker = Walker::new(cfg.blocks().len());
+		loop {
+			let stops: BTreeSet<u64> = candidates.iter().filter(|&(_, &sources)| confidence(sources) > Confidence::Low).map(|(&a, _)| a).collect();
+			let mut reached: BTreeSet<u64> = BTreeSet::new();
+			let mut tail_call_targets = Vec::new();
+			for &start in &stops {
+				let _ = walker.walk(&cfg, start, &stops, &mut tail_call_targets, |block_start| {
+					let _ = reached.insert(block_start);
+				});
+			}
+			let count = candidates.len();
+			candidates.retain(|&address, &mut sources| confidence(sources) > Confidence::Low || !reached.contains(&address));
+			let mut changed = candidates.len() != count;
+			for target in tail_call_targets {
+				let sources = candidates.entry(target).or_default();
+				if (*sources & FunctionSources::TAIL_CALL_TARGET) == 0 {
+					*sources |= FunctionSources::TAIL_CALL_TARGET;
+					changed = true;
+				}
+			}
+			if !changed {
+				break;
+			}
+		}
+
+		let stops: BTreeSet<u64> = candidates.keys().copied().collect();
+		let mut functions = Vec::with_capacity(candidates.len());
+		for (&start, &sources) in &candidates {
+			let end = match self.unwind_functions.iter().find(|&&(unwind_start, _)| unwind_start == start) {
+				Some(&(_, unwind_end)) => unwind_end,
+				None => walker.walk(&cfg, start, &stops, &mut Vec::new(), |_| {}).unwrap_or(start),
+			};
+			functions.push(FunctionBoundary { start, end: end.min(code_end), confidence: confidence(sources), sources });
+		}
+		functions
+	}
+
+	// Decodes the code linearly, resyncing at all known function starts
+	fn decode(&self, code: &[u8], ip: u64) -> Vec<Instruction> {
+		let mut anchors: Vec<u64> = self.entry_points.iter().chain(self.unwind_functions.iter().map(|(start, _)| start)).copied().collect();
+		anchors.sort_unstable();
+		anchors.dedup();
+		let mut anchors = anchors.into_iter().filter(|&address| address.wrapping_sub(ip) < code.len() as u64).peekable();
+
+		let mut instructions = Vec::new();
+		let mut decoder = Decoder::with_ip(self.bitness, code, ip, self.decoder_options);
+		let mut instruction = Instruction::default();
+		while decoder.can_decode() {
+			let current_ip = decoder.ip();
+			while anchors.peek().map_or(false, |&address| address <= current_ip) {
+				let _ = anchors.next();
+			}
+			decoder.decode_out(&mut instruction);
+			if let Some(&anchor) = anchors.peek() {
+				if anchor < instruction.next_ip() && decoder.set_position((anchor - ip) as usize).is_ok() {
+					decoder.set_ip(anchor);
+					continue;
+				}
+			}
+			instructions.push(instruction);
+		}
+		instructions
+	}
+
+	// `true` if it's an aligned instruction that follows padding after an instruction without fallthrough
+	fn follows_padding(&self, instructions: &[Instruction], index: usize) -> bool {
+		let instruction = &instructions[index];
+		if is_padding(instruction) || (self.alignment > 1 && instruction.ip() % self.alignment as u64 != 0) {
+			return false;
+		}
+		let mut i = index;
+		while i > 0 && is_padding(&instructions[i - 1]) && instructions[i - 1].next_ip() == instructions[i].ip() {
+			i -= 1;
+		}
+		i != index && i > 0 && instructions[i - 1].next_ip() == instructions[i].ip() && !has_fallthrough(&instructions[i - 1])
+	}
+
+	fn prologue_patterns(&self) -> Vec<PatternSequence> {
+		let (sp, bp, gpr) = match self.bitness {
+			16 => (Register::SP, Register::BP, RegisterClass::Gpr16),
+			32 => (Register::ESP, Register::EBP, RegisterClass::Gpr32),
+			_ => (Register::RSP, Register::RBP, RegisterClass::Gpr64),
+		};
+		let endbr = || InstrPattern::with_mnemonics(&[Mnemonic::Endbr64, Mnemonic::Endbr32]);
+		let push_gpr = || InstrPattern::new(Mnemonic::Push).operand(gpr);
+		let sub_sp = || InstrPattern::new(Mnemonic::Sub).operand(sp).operand(OperandPattern::immediate());
+		vec![
+			// push rbp / mov rbp,rsp (optionally starting with the hot patch `mov edi,edi`)
+			PatternSequence::new()
+				.optional(endbr())
+				.optional(InstrPattern::new(Mnemonic::Mov).operand(Register::EDI).operand(Register::EDI))
+				.then(InstrPattern::new(Mnemonic::Push).operand(bp))
+				.then(InstrPattern::new(Mnemonic::Mov).operand(bp).operand(sp)),
+			// push rbx / push rsi / sub rsp,20h
+			PatternSequence::new().optional(endbr()).then(push_gpr()).optional(push_gpr()).optional(push_gpr()).optional(push_gpr()).then(sub_sp()),
+			// mov [rsp+8],rbx / ... / sub rsp,20h (Win64 home space)
+			PatternSequence::new()
+				.optional(endbr())
+				.then(InstrPattern::new(Mnemonic::Mov).operand(MemoryPattern::new().base(sp).displacement(8, 0x20)).operand(gpr))
+				.gap(0, 4)
+				.then(sub_sp()),
+		]
+	}
+}
+
+fn confidence(sources: u32) -> Confidence {
+	const STRONG: u32 = FunctionSources::ENTRY_POINT | FunctionSources::UNWIND | FunctionSources::CALL_TARGET;
+	if (sources & STRONG) != 0 || sources.count_ones() >= 2 {
+		Confidence::High
+	} else if sources == FunctionSources::PADDING || sources == FunctionSources::ENDBR {
+		Confidence::Low
+	} else {
+		Confidence::Medium
+	}
+}
+
+fn is_padding(instruction: &Instruction) -> bool {
+	matches!(instruction.code(), Code::Int3 | Code::Nopw | Code::Nopd | Code::Nopq | Code::Nop_rm16 | Code::Nop_rm32 | Code::Nop_rm64)
+}
+
+struct Walker {
+	visited: Vec<bool>,
+	stack: Vec<usize>,
+}
+
+impl Walker {
+	fn new(block_count: usize) -> Self {
+		Self { visited: vec![false; block_count], stack: Vec::new() }
+	}
+
+	// Visits all blocks reachable from `start` without entering other functions in `stops` and returns the end
+	// address of the last block. Branches to `stops` or outside the code aren't followed. A `JMP` to code before
+	// `start` is a tail call and its target is added to `tail_call_targets`. Other `JMP`s are followed
+	// since they're usually part of the function.
+	fn walk(
+		&mut self, cfg: &ControlFlowGraph, start: u64, stops: &BTreeSet<u64>, tail_call_targets: &mut Vec<u64>, mut on_block: impl FnMut(u64),
+	) -> Option<u64> {
+		let blocks = cfg.blocks();
+		let first = cfg.block_index(start)?;
+		self.visited.iter_mut().for_each(|v| *v = false);
+		self.stack.clear();
+		self.visited[first] = true;
+		self.stack.push(first);
+		let mut end = start;
+		while let Some(index) = self.stack.pop() {
+			let block = &blocks[index];
+			end = end.max(block.end);
+			if index != first {
+				on_block(block.start);
+			}
+			for edge in &block.successors {
+				let target_index = match cfg.block_index(edge.target) {
+					Some(target_index) => target_index,
+					None => continue,
+				};
+				if self.visited[target_index] || stops.contains(&edge.target) {
+					continue;
+				}
+				if edge.kind == EdgeKind::Branch && edge.target < start {
+					tail_call_targets.push(edge.target);
+					continue;
+				}
+				self.visited[target_index] = true;
+				self.stack.push(target_index);
+			}
+		}
+		Some(end)
+	}
+}
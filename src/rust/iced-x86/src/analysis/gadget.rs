@@ -0,0 +1,454 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::iced_error::IcedError;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+use crate::Formatter;
+use crate::{Code, Decoder, DecoderOptions, FlowControl, Instruction, InstructionInfoFactory, Mnemonic, OpAccess, OpKind, Register};
+use alloc::collections::BTreeMap;
+#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Gadget terminators searched for by [`GadgetFinder`]
+///
+/// [`GadgetFinder`]: struct.GadgetFinder.html
+#[allow(missing_copy_implementations)]
+#[allow(missing_debug_implementations)]
+pub struct GadgetKinds;
+impl GadgetKinds {
+	/// No gadgets are searched for
+	pub const NONE: u32 = 0x0000_0000;
+	/// `RET`, `RET imm16` (ROP)
+	pub const RETURN: u32 = 0x0000_0001;
+	/// `JMP reg`, `JMP [mem]` (JOP)
+	pub const INDIRECT_JUMP: u32 = 0x0000_0002;
+	/// `CALL reg`, `CALL [mem]` (COP)
+	pub const INDIRECT_CALL: u32 = 0x0000_0004;
+	/// `SYSCALL`, `SYSENTER`, `INT 80h`
+	pub const SYSCALL: u32 = 0x0000_0008;
+	/// All gadget kinds
+	pub const ALL: u32 = GadgetKinds::RETURN | GadgetKinds::INDIRECT_JUMP | GadgetKinds::INDIRECT_CALL | GadgetKinds::SYSCALL;
+}
+
+/// Kind of the last instruction of a [`Gadget`]
+///
+/// [`Gadget`]: struct.Gadget.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum GadgetKind {
+	/// Ends in a `RET` instruction
+	Return,
+	/// Ends in an indirect `JMP` instruction
+	IndirectJump,
+	/// Ends in an indirect `CALL` instruction
+	IndirectCall,
+	/// Ends in a `SYSCALL`, `SYSENTER` or `INT 80h` instruction
+	Syscall,
+}
+
+/// Summary of what a [`Gadget`] does, created from [`InstructionInfo`]
+///
+/// [`Gadget`]: struct.Gadget.html
+/// [`InstructionInfo`]: ../struct.InstructionInfo.html
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct GadgetEffects {
+	/// Registers loaded from the stack by `POP reg` instructions, in the order they're popped
+	pub popped_registers: Vec<Register>,
+	/// All written registers (full registers, eg. `RAX` if `AL` is written), excluding the stack pointer and `RIP`/`EIP`
+	pub clobbered_registers: Vec<Register>,
+	/// Number of bytes the stack pointer is incremented by, including the terminator (eg. `RET` adds 8 in 64-bit mode)
+	pub stack_delta: i64,
+	/// `true` if the stack pointer is overwritten with a new value (eg. `XCHG RSP,RAX`, `LEAVE`, `POP RSP`).
+	/// [`stack_delta`] doesn't include the change of that instruction, eg. it's 8 for `POP RSP` + `RET` in 64-bit mode.
+	///
+	/// [`stack_delta`]: #structfield.stack_delta
+	pub stack_pivot: bool,
+	/// `true` if a non-stack memory location is read, ie. a location that isn't addressed by the stack pointer
+	pub reads_memory: bool,
+	/// `true` if a non-stack memory location is written, ie. a location that isn't addressed by the stack pointer
+	pub writes_memory: bool,
+}
+
+/// A unique gadget found by [`GadgetFinder`]
+///
+/// [`GadgetFinder`]: struct.GadgetFinder.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct Gadget {
+	/// Kind of the last instruction
+	pub kind: GadgetKind,
+	/// All addresses of this gadget, sorted in ascending order. Gadgets with identical bytes are only reported once.
+	pub addresses: Vec<u64>,
+	/// Gadget bytes
+	pub bytes: Vec<u8>,
+	/// All instructions, decoded at the first address. The last instruction is the terminator.
+	pub instructions: Vec<Instruction>,
+	/// What the gadget does
+	pub effects: GadgetEffects,
+}
+
+impl Gadget {
+	/// Gets the first address of the gadget
+	#[must_use]
+	#[inline]
+	pub fn address(&self) -> u64 {
+		self.addresses.first().copied().unwrap_or_default()
+	}
+
+	/// Formats all instructions, eg. `pop rdi ; ret`
+	///
+	/// # Arguments
+	///
+	/// * `formatter`: Formatter
+	#[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm"))]
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn format(&self, formatter: &mut dyn Formatter) -> String {
+		let mut output = String::new();
+		for (i, instruction) in self.instructions.iter().enumerate() {
+			if i != 0 {
+				output.push_str(" ; ");
+			}
+			formatter.format(instruction, &mut output);
+		}
+		output
+	}
+}
+
+/// Finds ROP/JOP/COP gadgets by decoding backwards from every gadget terminator (`RET`, `JMP reg`, `CALL reg`, `SYSCALL`).
+///
+/// Every offset in the code is decoded and if it's a terminator, all byte offsets before it are tried as start
+/// offsets. A start offset is valid if it decodes to a chain of valid instructions without control flow
+/// (see [`FlowControl::Next`]) that ends exactly at the terminator.
+///
+/// [`FlowControl::Next`]: ../enum.FlowControl.html#variant.Next
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+/// use iced_x86::*;
+///
+/// // 0x1000: pop rdi
+/// // 0x1001: ret
+/// // 0x1002: mov rax,0C35Fh
+/// let bytes = b"\x5F\xC3\x48\xC7\xC0\x5F\xC3\x00\x00";
+/// let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+///
+/// let mut formatter = NasmFormatter::new();
+/// let texts: Vec<String> = gadgets.iter().map(|g| g.format(&mut formatter)).collect();
+/// assert_eq!(texts, ["pop rdi ; ret", "ret"]);
+///
+/// // The gadget is also found inside the `mov` instruction
+/// let gadget = &gadgets[0];
+/// assert_eq!(gadget.addresses, [0x1000, 0x1005]);
+/// assert_eq!(gadget.kind, GadgetKind::Return);
+/// assert_eq!(gadget.effects.popped_registers, [Register::RDI]);
+/// assert_eq!(gadget.effects.stack_delta, 16);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct GadgetFinder {
+	bitness: u32,
+	decoder_options: u32,
+	kinds: u32,
+	max_instructions: usize,
+	max_bytes: usize,
+}
+
+impl GadgetFinder {
+	/// Creates a new instance that finds all gadget kinds with at most 6 instructions and at most 30 bytes
+	///
+	/// # Errors
+	///
+	/// Fails if `bitness` is not one of 16, 32, 64.
+	///
+	/// # Arguments
+	///
+	/// * `bitness`: 16, 32 or 64
+	#[inline]
+	pub fn new(bitness: u32) -> Result<Self, IcedError> {
+		match bitness {
+			16 | 32 | 64 => {}
+			_ => return Err(IcedError::new("Invalid bitness")),
+		}
+		Ok(Self { bitness, decoder_options: DecoderOptions::NONE, kinds: GadgetKinds::ALL, max_instructions: 6, max_bytes: 30 })
+	}
+
+	/// Gets the bitness (16, 32 or 64)
+	#[must_use]
+	#[inline]
+	pub fn bitness(&self) -> u32 {
+		self.bitness
+	}
+
+	/// Gets the [`GadgetKinds`] flags (default: [`GadgetKinds::ALL`])
+	///
+	/// [`GadgetKinds`]: struct.GadgetKinds.html
+	/// [`GadgetKinds::ALL`]: struct.GadgetKinds.html#associatedconstant.ALL
+	#[must_use]
+	#[inline]
+	pub fn kinds(&self) -> u32 {
+		self.kinds
+	}
+
+	/// Sets the [`GadgetKinds`] flags (default: [`GadgetKinds::ALL`])
+	///
+	/// [`GadgetKinds`]: struct.GadgetKinds.html
+	/// [`GadgetKinds::ALL`]: struct.GadgetKinds.html#associatedconstant.ALL
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_kinds(&mut self, new_value: u32) {
+		self.kinds = new_value;
+	}
+
+	/// Gets the [`DecoderOptions`] passed to the decoder (default: [`DecoderOptions::NONE`])
+	///
+	/// [`DecoderOptions`]: ../struct.DecoderOptions.html
+	/// [`DecoderOptions::NONE`]: ../struct.DecoderOptions.html#associatedconstant.NONE
+	#[must_use]
+	#[inline]
+	pub fn decoder_options(&self) -> u32 {
+		self.decoder_options
+	}
+
+	/// Sets the [`DecoderOptions`] passed to the decoder (default: [`DecoderOptions::NONE`])
+	///
+	/// [`DecoderOptions`]: ../struct.DecoderOptions.html
+	/// [`DecoderOptions::NONE`]: ../struct.DecoderOptions.html#associatedconstant.NONE
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_decoder_options(&mut self, new_value: u32) {
+		self.decoder_options = new_value;
+	}
+
+	/// Gets the max number of instructions in a gadget, including the terminator (default: 6)
+	#[must_use]
+	#[inline]
+	pub fn max_instructions(&self) -> usize {
+		self.max_instructions
+	}
+
+	/// Sets the max number of instructions in a gadget, including the terminator (default: 6)
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_max_instructions(&mut self, new_value: usize) {
+		self.max_instructions = new_value;
+	}
+
+	/// Gets the max number of bytes in a gadget, including the terminator (default: 30)
+	#[must_use]
+	#[inline]
+	pub fn max_bytes(&self) -> usize {
+		self.max_bytes
+	}
+
+	/// Sets the max number of bytes in a gadget, including the terminator (default: 30)
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_max_bytes(&mut self, new_value: usize) {
+		self.max_bytes = new_value;
+	}
+
+	/// Finds all unique gadgets in `code`. The result is sorted by address.
+	///
+	/// # Arguments
+	///
+	/// * `code`: Code to search
+	/// * `ip`: Address of `code[0]`
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn find(&self, code: &[u8], ip: u64) -> Vec<Gadget> {
+		let mut gadgets: Vec<Gadget> = Vec::new();
+		// Gadget bytes -> index in `gadgets`
+		let mut unique: BTreeMap<&[u8], usize> = BTreeMap::new();
+		let mut info_factory = InstructionInfoFactory::new();
+		let mut instructions = Vec::with_capacity(self.max_instructions);
+		if self.max_instructions == 0 {
+			return gadgets;
+		}
+
+		for term_offset in 0..code.len() {
+			let term_ip = ip.wrapping_add(term_offset as u64);
+			let mut decoder = Decoder::with_ip(self.bitness, &code[term_offset..], term_ip, self.decoder_options);
+			let terminator = decoder.decode();
+			let kind = match self.terminator_kind(&terminator) {
+				Some(kind) => kind,
+				None => continue,
+			};
+			let term_end = term_offset + terminator.len();
+			if terminator.len() > self.max_bytes {
+				continue;
+			}
+
+			let min_offset = term_end.saturating_sub(self.max_bytes);
+			for start in (min_offset..=term_offset).rev() {
+				if !self.decode_chain(code, ip, start, term_offset, &terminator, &mut instructions) {
+					continue;
+				}
+				let bytes = &code[start..term_end];
+				let address = ip.wrapping_add(start as u64);
+				if let Some(&index) = unique.get(bytes) {
+					gadgets[index].addresses.push(address);
+				} else {
+					let effects = Self::effects(&instructions, &mut info_factory);
+					let _ = unique.insert(bytes, gadgets.len());
+					gadgets.push(Gadget { kind, addresses: vec![address], bytes: bytes.to_vec(), instructions: instructions.clone(), effects });
+				}
+			}
+		}
+
+		for gadget in &mut gadgets {
+			gadget.addresses.sort_unstable();
+		}
+		gadgets.sort_by(|a, b| a.address().cmp(&b.address()).then_with(|| a.bytes.len().cmp(&b.bytes.len())));
+		gadgets
+	}
+
+	fn terminator_kind(&self, instruction: &Instruction) -> Option<GadgetKind> {
+		let (kind, flag) = match instruction.flow_control() {
+			FlowControl::Return => (GadgetKind::Return, GadgetKinds::RETURN),
+			FlowControl::IndirectBranch => (GadgetKind::IndirectJump, GadgetKinds::INDIRECT_JUMP),
+			FlowControl::IndirectCall => (GadgetKind::IndirectCall, GadgetKinds::INDIRECT_CALL),
+			FlowControl::Call | FlowControl::Interrupt => match instruction.code() {
+				Code::Syscall | Code::Sysenter => (GadgetKind::Syscall, GadgetKinds::SYSCALL),
+				Code::Int_imm8 if instruction.immediate8() == 0x80 => (GadgetKind::Syscall, GadgetKinds::SYSCALL),
+				_ => return None,
+			},
+			_ => return None,
+		};
+		if (self.kinds & flag) != 0 {
+			Some(kind)
+		} else {
+			None
+		}
+	}
+
+	// Decodes code[start..] and returns true if it's a valid chain that ends at the terminator
+	fn decode_chain(
+		&self, code: &[u8], ip: u64, start: usize, term_offset: usize, terminator: &Instruction, instructions: &mut Vec<Instruction>,
+	) -> bool {
+		instructions.clear();
+		let mut decoder = Decoder::with_ip(self.bitness, &code[start..term_offset], ip.wrapping_add(start as u64), self.decoder_options);
+		let mut offset = start;
+		while offset < term_offset {
+			if instructions.len() + 1 >= self.max_instructions {
+				return false;
+			}
+			let instruction = decoder.decode();
+			if instruction.is_invalid() || instruction.flow_control() != FlowControl::Next {
+				return false;
+			}
+			offset += instruction.len();
+			instructions.push(instruction);
+		}
+		// The decoder only sees the bytes up to the terminator so the last instruction can't overlap it
+		debug_assert_eq!(offset, term_offset);
+		instructions.push(*terminator);
+		true
+	}
+
+	fn effects(instructions: &[Instruction], info_factory: &mut InstructionInfoFactory) -> GadgetEffects {
+		let mut effects = GadgetEffects::default();
+		for instruction in instructions {
+			let info = info_factory.info(instruction);
+
+			let mut writes_sp = false;
+			for used_reg in info.used_registers() {
+				if !is_write(used_reg.access()) {
+					continue;
+				}
+				let register = used_reg.register().full_register();
+				if register == Register::RSP || register == Register::ESP || register == Register::SP {
+					writes_sp = true;
+				} else if register.is_gpr() || register.is_vector_register() || register.is_k() || register.is_mm() || register.is_segment_register()
+				{
+					if !effects.clobbered_registers.contains(&register) {
+						effects.clobbered_registers.push(register);
+					}
+				}
+			}
+
+			// Implicit memory operands (eg. `MOVSB`) are also checked, but not stack accesses (eg. `POP`, `MOV RAX,[RSP+8]`)
+			for used_mem in info.used_memory() {
+				if is_stack_pointer(used_mem.base()) {
+					continue;
+				}
+				match used_mem.access() {
+					OpAccess::Read | OpAccess::CondRead => effects.reads_memory = true,
+					OpAccess::Write | OpAccess::CondWrite => effects.writes_memory = true,
+					OpAccess::ReadWrite | OpAccess::ReadCondWrite => {
+						effects.reads_memory = true;
+						effects.writes_memory = true;
+					}
+					_ => {}
+				}
+			}
+
+			if instruction.mnemonic() == Mnemonic::Pop && instruction.op_count() == 1 && instruction.op0_kind() == OpKind::Register {
+				effects.popped_registers.push(instruction.op0_register());
+			}
+
+			let explicit_sp = (0..instruction.op_count())
+				.any(|i| instruction.op_kind(i) == OpKind::Register && is_stack_pointer(instruction.op_register(i).full_register()));
+			if let Some(delta) = stack_adjustment(instruction) {
+				effects.stack_delta += delta;
+			} else if writes_sp && (explicit_sp || instruction.stack_pointer_increment() == 0 || instruction.mnemonic() == Mnemonic::Leave) {
+				// The new stack pointer isn't known so this instruction's change (eg. `POP RSP`'s +8) isn't part of the delta
+				effects.stack_pivot = true;
+				continue;
+			}
+			effects.stack_delta += instruction.stack_pointer_increment() as i64;
+		}
+		effects
+	}
+}
+
+#[inline]
+pub(super) fn is_write(access: OpAccess) -> bool {
+	matches!(access, OpAccess::Write | OpAccess::CondWrite | OpAccess::ReadWrite | OpAccess::ReadCondWrite)
+}
+
+#[inline]
+fn is_stack_pointer(register: Register) -> bool {
+	register == Register::RSP || register == Register::ESP || register == Register::SP
+}
+
+// `ADD rsp,imm`, `SUB rsp,imm` and `LEA rsp,[rsp+imm]` move the stack pointer by a known amount
+fn stack_adjustment(instruction: &Instruction) -> Option<i64> {
+	if instruction.op_count() != 2 || instruction.op0_kind() != OpKind::Register || !is_stack_pointer(instruction.op0_register()) {
+		return None;
+	}
+	match instruction.mnemonic() {
+		Mnemonic::Add | Mnemonic::Sub => {
+			let imm = instruction.try_immediate(1).ok()? as i64;
+			Some(if instruction.mnemonic() == Mnemonic::Add { imm } else { imm.wrapping_neg() })
+		}
+		Mnemonic::Lea => {
+			if instruction.op1_kind() == OpKind::Memory && is_stack_pointer(instruction.memory_base()) && instruction.memory_index() == Register::None
+			{
+				Some(match instruction.op0_register() {
+					Register::RSP => instruction.memory_displacement64() as i64,
+					Register::ESP => instruction.memory_displacement32() as i32 as i64,
+					_ => instruction.memory_displacement32() as i16 as i64,
+				})
+			} else {
+				None
+			}
+		}
+		_ => None,
+	}
+}
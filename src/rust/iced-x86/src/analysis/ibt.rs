@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::analysis::jump_table::JumpTableResolver;
+use crate::iced_error::IcedError;
+use crate::{Code, Instruction, Mnemonic, OpKind, Register};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// Reasons why an address is a possible indirect branch target, see [`IndirectTarget::sources`]
+///
+/// [`IndirectTarget::sources`]: struct.IndirectTarget.html#structfield.sources
+#[allow(missing_copy_implementations)]
+#[allow(missing_debug_implementations)]
+pub struct IndirectTargetSources;
+impl IndirectTargetSources {
+	/// No sources
+	pub const NONE: u32 = 0x0000_0000;
+	/// Its address is taken by an instruction, eg. `LEA RAX,[RIP+func]`, `MOV ECX,OFFSET func` or `PUSH OFFSET func`
+	pub const ADDRESS_TAKEN: u32 = 0x0000_0001;
+	/// A pointer in a data section (see [`IbtAuditor::add_data_section()`]) points to it
+	///
+	/// [`IbtAuditor::add_data_section()`]: struct.IbtAuditor.html#method.add_data_section
+	pub const DATA_POINTER: u32 = 0x0000_0002;
+	/// It was added by [`IbtAuditor::add_export()`]
+	///
+	/// [`IbtAuditor::add_export()`]: struct.IbtAuditor.html#method.add_export
+	pub const EXPORT: u32 = 0x0000_0004;
+	/// It's a jump table target of a `JMP` without a `NOTRACK` prefix
+	pub const JUMP_TABLE: u32 = 0x0000_0008;
+}
+
+/// A possible indirect branch target found by [`IbtAuditor`]
+///
+/// [`IbtAuditor`]: struct.IbtAuditor.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct IndirectTarget {
+	/// Address of the target
+	pub address: u64,
+	/// Why it's a target, a combination of [`IndirectTargetSources`] flags
+	///
+	/// [`IndirectTargetSources`]: struct.IndirectTargetSources.html
+	pub sources: u32,
+	/// `true` if the instruction at the target is `ENDBR64` (64-bit code) or `ENDBR32` (32-bit code). It's `false` if
+	/// the target isn't the start of an instruction.
+	pub has_endbr: bool,
+}
+
+/// Result of [`IbtAuditor::audit()`]
+///
+/// [`IbtAuditor::audit()`]: struct.IbtAuditor.html#method.audit
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct IbtReport {
+	/// All possible indirect branch targets in the code, sorted by address
+	pub targets: Vec<IndirectTarget>,
+	/// Addresses of all `CALL`/`JMP` instructions with a `NOTRACK` prefix. Their targets don't need an `ENDBR64`/`ENDBR32`.
+	pub notrack_branches: Vec<u64>,
+}
+
+impl IbtReport {
+	/// Gets all targets that don't start with an `ENDBR64`/`ENDBR32` instruction. They'd cause a `#CP` exception if
+	/// CET indirect branch tracking is enabled.
+	#[inline]
+	pub fn missing_endbr(&self) -> impl Iterator<Item = &IndirectTarget> {
+		self.targets.iter().filter(|target| !target.has_endbr)
+	}
+}
+
+/// Finds possible indirect branch targets that don't start with an `ENDBR64`/`ENDBR32` instruction (CET indirect branch tracking)
+///
+/// Only targets in the audited code are checked. The targets are found by looking for:
+///
+/// - Address-taken code, eg. `LEA RAX,[RIP+func]` or `MOV ECX,OFFSET func`
+/// - Pointers in data sections, eg. vtables and function pointer tables (see [`add_data_section()`])
+/// - Exported functions (see [`add_export()`])
+/// - Jump table targets (see [`JumpTableResolver`]) unless the `JMP` has a `NOTRACK` prefix
+///
+/// Any code address is reported even if it's not used by an indirect branch so it's best used to find code that
+/// needs to be reviewed.
+///
+/// [`add_data_section()`]: #method.add_data_section
+/// [`add_export()`]: #method.add_export
+/// [`JumpTableResolver`]: struct.JumpTableResolver.html
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+/// use iced_x86::*;
+///
+/// // 0x1000: lea rax,[1011h]
+/// // 0x1007: lea rcx,[1020h]
+/// // 0x100E: notrack jmp rax
+/// // 0x1011: int3 (xF)
+/// // 0x1020: endbr64
+/// // 0x1024: ret
+/// let mut bytes = b"\x48\x8D\x05\x0A\x00\x00\x00\x48\x8D\x0D\x12\x00\x00\x00\x3E\xFF\xE0".to_vec();
+/// bytes.resize(0x20, 0xCC);
+/// bytes.extend_from_slice(b"\xF3\x0F\x1E\xFA\xC3");
+/// let instructions: Vec<_> = Decoder::with_ip(64, &bytes, 0x1000, DecoderOptions::NONE).into_iter().collect();
+///
+/// let auditor = IbtAuditor::new(64, &bytes, 0x1000).unwrap();
+/// let report = auditor.audit(&instructions);
+/// assert_eq!(report.targets.len(), 2);
+/// let missing: Vec<u64> = report.missing_endbr().map(|target| target.address).collect();
+/// assert_eq!(missing, [0x1011]);
+/// assert_eq!(report.notrack_branches, [0x100E]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct IbtAuditor<'a> {
+	bitness: u32,
+	image: &'a [u8],
+	image_address: u64,
+	data_sections: Vec<(u64, u64)>,
+	exports: Vec<u64>,
+}
+
+impl<'a> IbtAuditor<'a> {
+	/// Creates a new instance
+	///
+	/// # Errors
+	///
+	/// Fails if `bitness` is not one of 32, 64.
+	///
+	/// # Arguments
+	///
+	/// * `bitness`: 32 or 64
+	/// * `image`: Memory containing the code and data, eg. the mapped image. It's used to read data sections and jump tables.
+	/// * `image_address`: Address of `image[0]`
+	#[inline]
+	pub fn new(bitness: u32, image: &'a [u8], image_address: u64) -> Result<Self, IcedError> {
+		match bitness {
+			32 | 64 => {}
+			_ => return Err(IcedError::new("Invalid bitness")),
+		}
+		Ok(Self { bitness, image, image_address, data_sections: Vec::new(), exports: Vec::new() })
+	}
+
+	/// Gets the bitness (32 or 64)
+	#[must_use]
+	#[inline]
+	pub fn bitness(&self) -> u32 {
+		self.bitness
+	}
+
+	/// Adds a data section that is searched for pointers to code, eg. `.data`, `.rodata` or `.data.rel.ro`.
+	/// Only aligned 4-byte (32-bit code) or 8-byte (64-bit code) values are checked.
+	///
+	/// # Arguments
+	///
+	/// * `address`: Address of the section. It must be in the image.
+	/// * `size`: Size of the section in bytes
+	#[inline]
+	pub fn add_data_section(&mut self, address: u64, size: u64) {
+		self.data_sections.push((address, size));
+	}
+
+	/// Adds an exported function, eg. from the export directory of a PE file or the dynamic symbol table of an ELF file
+	///
+	/// # Arguments
+	///
+	/// * `address`: Address of the function
+	#[inline]
+	pub fn add_export(&mut self, address: u64) {
+		self.exports.push(address);
+	}
+
+	/// Finds all possible indirect branch targets in `instructions` and all `NOTRACK` branches
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Instructions sorted by IP, eg. all instructions returned by a [`Decoder`]
+	///
+	/// [`Decoder`]: ../struct.Decoder.html
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn audit(&self, instructions: &[Instruction]) -> IbtReport {
+		let (code_start, code_end) = match (instructions.first(), instructions.last()) {
+			(Some(first), Some(last)) => (first.ip(), last.next_ip()),
+			_ => return IbtReport::default(),
+		};
+		let in_code = |address: u64| code_start <= address && address < code_end;
+		let mut candidates: BTreeMap<u64, u32> = BTreeMap::new();
+		let mut add = |address: u64, source: u32| {
+			if in_code(address) {
+				*candidates.entry(address).or_default() |= source;
+			}
+		};
+
+		for instruction in instructions {
+			if let Some(address) = taken_address(instruction) {
+				add(address, IndirectTargetSources::ADDRESS_TAKEN);
+			}
+		}
+
+		let pointer_size = self.bitness as u64 / 8;
+		for &(address, size) in &self.data_sections {
+			let aligned = address.wrapping_add(pointer_size - 1) & !(pointer_size - 1);
+			let end = address.saturating_add(size);
+			let mut pointer_address = aligned;
+			while pointer_address.saturating_add(pointer_size) <= end {
+				if let Some(pointer) = self.read_pointer(pointer_address) {
+					add(pointer, IndirectTargetSources::DATA_POINTER);
+				}
+				pointer_address += pointer_size;
+			}
+		}
+
+		for &address in &self.exports {
+			add(address, IndirectTargetSources::EXPORT);
+		}
+
+		for table in JumpTableResolver::new(self.image, self.image_address).resolve_all(instructions) {
+			let is_notrack = find(instructions, table.jump_address).map_or(false, Instruction::has_notrack_prefix);
+			if !is_notrack {
+				for &target in &table.targets {
+					add(target, IndirectTargetSources::JUMP_TABLE);
+				}
+			}
+		}
+
+		let endbr = if self.bitness == 64 { Code::Endbr64 } else { Code::Endbr32 };
+		let targets = candidates
+			.into_iter()
+			.map(|(address, sources)| IndirectTarget {
+				address,
+				sources,
+				has_endbr: find(instructions, address).map_or(false, |instruction| instruction.code() == endbr),
+			})
+			.collect();
+		let notrack_branches = instructions.iter().filter(|instruction| instruction.has_notrack_prefix()).map(Instruction::ip).collect();
+		IbtReport { targets, notrack_branches }
+	}
+
+	fn read_pointer(&self, address: u64) -> Option<u64> {
+		let offset = usize::try_from(address.wrapping_sub(self.image_address)).ok()?;
+		let bytes = self.image.get(offset..offset.checked_add(self.bitness as usize / 8)?)?;
+		Some(bytes.iter().rev().fold(0, |value, &b| (value << 8) | b as u64))
+	}
+}
+
+fn find(instructions: &[Instruction], address: u64) -> Option<&Instruction> {
+	instructions.binary_search_by_key(&address, Instruction::ip).ok().map(|index| &instructions[index])
+}
+
+// Gets the code address taken by the instruction if any, eg. `LEA RAX,[RIP+func]`
+fn taken_address(instruction: &Instruction) -> Option<u64> {
+	match instruction.mnemonic() {
+		Mnemonic::Lea => {
+			if instruction.is_ip_rel_memory_operand() {
+				Some(instruction.ip_rel_memory_address())
+			} else if instruction.memory_base() == Register::None && instruction.memory_index() == Register::None {
+				Some(instruction.memory_displacement64())
+			} else {
+				None
+			}
+		}
+		Mnemonic::Mov | Mnemonic::Push => (0..instruction.op_count()).find_map(|operand| match instruction.op_kind(operand) {
+			OpKind::Immediate32 | OpKind::Immediate64 | OpKind::Immediate32to64 => Some(instruction.immediate(operand)),
+			_ => None,
+		}),
+		_ => None,
+	}
+}
@@ -0,0 +1,366 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::{Code, CpuFeatureProfile, CpuidFeature, EncodingKind, Instruction, OpKind};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Features that must be enabled by the OS before instructions using them can be executed, see
+/// [`CpuFeatureReport::os_features`]
+///
+/// [`CpuFeatureReport::os_features`]: struct.CpuFeatureReport.html#structfield.os_features
+#[allow(missing_copy_implementations)]
+#[allow(missing_debug_implementations)]
+pub struct OsFeatures;
+impl OsFeatures {
+	/// No OS support is needed
+	pub const NONE: u32 = 0x0000_0000;
+	/// `XMM`/`YMM` state must be enabled in `XCR0` (AVX and other VEX/XOP encoded vector instructions)
+	pub const AVX_STATE: u32 = 0x0000_0001;
+	/// Opmask and `ZMM` state must be enabled in `XCR0` (AVX-512)
+	pub const AVX512_STATE: u32 = 0x0000_0002;
+	/// Tile config and tile data state must be enabled in `XCR0` (AMX). Linux also requires the process to request
+	/// permission with `arch_prctl(ARCH_REQ_XCOMP_PERM)`.
+	pub const AMX_TILE_STATE: u32 = 0x0000_0004;
+	/// `BNDREGS`/`BNDCSR` state must be enabled in `XCR0` (MPX)
+	pub const MPX_STATE: u32 = 0x0000_0008;
+	/// Protection keys must be enabled (`CR4.PKE`)
+	pub const PKU: u32 = 0x0000_0010;
+	/// `RDFSBASE` and friends must be enabled (`CR4.FSGSBASE`)
+	pub const FSGSBASE: u32 = 0x0000_0020;
+	/// User mode shadow stacks must be enabled (CET)
+	pub const SHADOW_STACK: u32 = 0x0000_0040;
+	/// User interrupts must be enabled (`CR4.UINTR`)
+	pub const UINTR: u32 = 0x0000_0080;
+	/// Key Locker must be enabled (`CR4.KL`)
+	pub const KEY_LOCKER: u32 = 0x0000_0100;
+}
+
+/// An `x86-64` micro-architecture level as defined by the x86-64 psABI
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum IsaLevel {
+	/// `x86-64` (baseline): `CMOV`, `CX8`, `FPU`, `FXSR`, `MMX`, `SSE`, `SSE2`, `SYSCALL`, ...
+	V1,
+	/// `x86-64-v2`: `CMPXCHG16B`, `POPCNT`, `SSE3`, `SSSE3`, `SSE4.1`, `SSE4.2`
+	V2,
+	/// `x86-64-v3`: `AVX`, `AVX2`, `BMI1`, `BMI2`, `F16C`, `FMA`, `LZCNT`, `MOVBE`, `XSAVE`
+	V3,
+	/// `x86-64-v4`: `AVX512F`, `AVX512BW`, `AVX512CD`, `AVX512DQ`, `AVX512VL`
+	V4,
+}
+
+impl IsaLevel {
+	/// Gets the name, eg. `x86-64-v2`. It can be passed to [`CpuFeatureProfile::from_name()`] and to GCC/Clang's `-march=`.
+	///
+	/// [`CpuFeatureProfile::from_name()`]: ../struct.CpuFeatureProfile.html#method.from_name
+	#[must_use]
+	#[inline]
+	pub const fn name(self) -> &'static str {
+		match self {
+			IsaLevel::V1 => "x86-64",
+			IsaLevel::V2 => "x86-64-v2",
+			IsaLevel::V3 => "x86-64-v3",
+			IsaLevel::V4 => "x86-64-v4",
+		}
+	}
+
+	/// Gets all features supported by CPUs of this level
+	#[must_use]
+	#[inline]
+	pub fn profile(self) -> CpuFeatureProfile {
+		match self {
+			IsaLevel::V1 => CpuFeatureProfile::x86_64_v1(),
+			IsaLevel::V2 => CpuFeatureProfile::x86_64_v2(),
+			IsaLevel::V3 => CpuFeatureProfile::x86_64_v3(),
+			IsaLevel::V4 => CpuFeatureProfile::x86_64_v4(),
+		}
+	}
+}
+
+impl Default for IsaLevel {
+	#[inline]
+	fn default() -> Self {
+		IsaLevel::V1
+	}
+}
+
+impl fmt::Display for IsaLevel {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// How often a CPU feature is used, see [`CpuFeatureReport`]
+///
+/// [`CpuFeatureReport`]: struct.CpuFeatureReport.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct CpuFeatureUsage {
+	/// The feature
+	pub feature: CpuidFeature,
+	/// Number of instructions that require it
+	pub count: usize,
+	/// Address of the first instruction that requires it
+	pub first_ip: u64,
+}
+
+/// CPU features required by decoded instructions
+///
+/// The result can be serialized if the `serde` feature is enabled. Human readable formats (eg. JSON) get the
+/// feature names, other formats get the [`CpuidFeature`] values.
+///
+/// [`CpuidFeature`]: ../enum.CpuidFeature.html
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+/// use iced_x86::*;
+///
+/// // popcnt eax,ecx
+/// // vpaddd ymm1,ymm2,ymm3
+/// // ret
+/// let bytes = b"\xF3\x0F\xB8\xC1\xC5\xED\xFE\xCB\xC3";
+/// let instrs: Vec<Instruction> = Decoder::with_ip(64, bytes, 0x1000, DecoderOptions::NONE).into_iter().collect();
+/// let report = CpuFeatureReport::new(&instrs);
+///
+/// assert_eq!(report.min_level, IsaLevel::V3);
+/// assert!(!report.is_supported_by(&CpuFeatureProfile::x86_64_v2()));
+/// let usage = report.usage(CpuidFeature::AVX2).unwrap();
+/// assert_eq!((usage.count, usage.first_ip), (1, 0x1004));
+/// assert_eq!(report.os_features, OsFeatures::AVX_STATE);
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct CpuFeatureReport {
+	/// All required features, sorted by [`CpuidFeature`] value
+	///
+	/// [`CpuidFeature`]: ../enum.CpuidFeature.html
+	pub features: Vec<CpuFeatureUsage>,
+	/// Number of valid instructions
+	pub instruction_count: usize,
+	/// Number of invalid instructions. They're ignored.
+	pub invalid_count: usize,
+	/// Lowest `x86-64` level that supports all required features that are part of some level. Features that
+	/// aren't part of any level (eg. `AES`) are in [`extra_features`].
+	///
+	/// [`extra_features`]: #structfield.extra_features
+	pub min_level: IsaLevel,
+	/// Required features that aren't part of any `x86-64` level, eg. `AES`, `SHA` or 32-bit only features
+	pub extra_features: Vec<CpuidFeature>,
+	/// Features that must be enabled by the OS, see [`OsFeatures`]
+	///
+	/// [`OsFeatures`]: struct.OsFeatures.html
+	pub os_features: u32,
+}
+
+impl CpuFeatureReport {
+	/// Creates a report of the features required by `instructions`
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Instructions, eg. all instructions returned by a [`Decoder`]
+	///
+	/// [`Decoder`]: ../struct.Decoder.html
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn new(instructions: &[Instruction]) -> Self {
+		let mut report = Self::default();
+		let mut index_of: Vec<Option<usize>> = vec![None; CpuidFeature::values().len()];
+		for instruction in instructions {
+			if instruction.code() == Code::INVALID {
+				report.invalid_count += 1;
+				continue;
+			}
+			report.instruction_count += 1;
+			report.os_features |= get_os_features(instruction);
+			for &feature in instruction.cpuid_features() {
+				match index_of[feature as usize] {
+					Some(index) => report.features[index].count += 1,
+					None => {
+						index_of[feature as usize] = Some(report.features.len());
+						report.features.push(CpuFeatureUsage { feature, count: 1, first_ip: instruction.ip() });
+					}
+				}
+			}
+		}
+		report.features.sort_unstable_by_key(|usage| usage.feature);
+
+		let levels = [IsaLevel::V1, IsaLevel::V2, IsaLevel::V3, IsaLevel::V4];
+		let profiles = levels.map(IsaLevel::profile);
+		for usage in &report.features {
+			match profiles.iter().position(|profile| profile.contains(usage.feature)) {
+				Some(index) => report.min_level = report.min_level.max(levels[index]),
+				None => report.extra_features.push(usage.feature),
+			}
+		}
+		report
+	}
+
+	/// Gets the usage of a feature or `None` if it's not required
+	///
+	/// # Arguments
+	///
+	/// * `feature`: Feature
+	#[must_use]
+	#[inline]
+	pub fn usage(&self, feature: CpuidFeature) -> Option<&CpuFeatureUsage> {
+		self.features.binary_search_by_key(&feature, |usage| usage.feature).ok().map(|index| &self.features[index])
+	}
+
+	/// Checks if a CPU with the features in `profile` can execute all instructions
+	///
+	/// # Arguments
+	///
+	/// * `profile`: Supported features, eg. [`IsaLevel::profile()`]
+	///
+	/// [`IsaLevel::profile()`]: enum.IsaLevel.html#method.profile
+	#[must_use]
+	#[inline]
+	pub fn is_supported_by(&self, profile: &CpuFeatureProfile) -> bool {
+		self.unsupported_features(profile).next().is_none()
+	}
+
+	/// Gets all required features that aren't in `profile`
+	///
+	/// # Arguments
+	///
+	/// * `profile`: Supported features, eg. [`IsaLevel::profile()`]
+	///
+	/// [`IsaLevel::profile()`]: enum.IsaLevel.html#method.profile
+	#[inline]
+	pub fn unsupported_features<'a>(&'a self, profile: &'a CpuFeatureProfile) -> impl Iterator<Item = &'a CpuFeatureUsage> + 'a {
+		self.features.iter().filter(move |usage| !profile.contains(usage.feature))
+	}
+}
+
+fn get_os_features(instruction: &Instruction) -> u32 {
+	let mut flags = OsFeatures::NONE;
+	for &feature in instruction.cpuid_features() {
+		flags |= match feature {
+			// VZEROUPPER/VZEROALL have no operands so they're not detected below
+			CpuidFeature::AVX => OsFeatures::AVX_STATE,
+			CpuidFeature::AVX512F
+			| CpuidFeature::AVX512BW
+			| CpuidFeature::AVX512CD
+			| CpuidFeature::AVX512DQ
+			| CpuidFeature::AVX512ER
+			| CpuidFeature::AVX512PF
+			| CpuidFeature::AVX512VL
+			| CpuidFeature::AVX512_4FMAPS
+			| CpuidFeature::AVX512_4VNNIW
+			| CpuidFeature::AVX512_BF16
+			| CpuidFeature::AVX512_BITALG
+			| CpuidFeature::AVX512_FP16
+			| CpuidFeature::AVX512_IFMA
+			| CpuidFeature::AVX512_VBMI
+			| CpuidFeature::AVX512_VBMI2
+			| CpuidFeature::AVX512_VNNI
+			| CpuidFeature::AVX512_VP2INTERSECT
+			| CpuidFeature::AVX512_VPOPCNTDQ => OsFeatures::AVX_STATE | OsFeatures::AVX512_STATE,
+			CpuidFeature::AMX_TILE | CpuidFeature::AMX_INT8 | CpuidFeature::AMX_BF16 | CpuidFeature::AMX_FP16 | CpuidFeature::AMX_COMPLEX => {
+				OsFeatures::AMX_TILE_STATE
+			}
+			CpuidFeature::MPX => OsFeatures::MPX_STATE,
+			CpuidFeature::PKU => OsFeatures::PKU,
+			CpuidFeature::FSGSBASE => OsFeatures::FSGSBASE,
+			CpuidFeature::CET_SS => OsFeatures::SHADOW_STACK,
+			CpuidFeature::UINTR => OsFeatures::UINTR,
+			CpuidFeature::KL | CpuidFeature::AESKLE | CpuidFeature::WIDE_KL => OsFeatures::KEY_LOCKER,
+			_ => OsFeatures::NONE,
+		};
+	}
+	// Only VEX/XOP instructions that use vector registers need AVX state, eg. BMI1/BMI2 instructions don't
+	match instruction.encoding() {
+		EncodingKind::EVEX => flags |= OsFeatures::AVX_STATE | OsFeatures::AVX512_STATE,
+		EncodingKind::VEX | EncodingKind::XOP => {
+			let uses_vector_reg = (0..instruction.op_count())
+				.any(|i| instruction.op_kind(i) == OpKind::Register && instruction.op_register(i).is_vector_register())
+				|| instruction.memory_index().is_vector_register();
+			if uses_vector_reg {
+				flags |= OsFeatures::AVX_STATE;
+			}
+		}
+		_ => {}
+	}
+	flags
+}
+
+#[cfg(feature = "serde")]
+const _: () = {
+	use serde::ser::SerializeStruct;
+	use serde::{Serialize, Serializer};
+
+	struct FeatureName(CpuidFeature);
+	impl Serialize for FeatureName {
+		#[inline]
+		fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where
+			S: Serializer,
+		{
+			if serializer.is_human_readable() {
+				serializer.collect_str(&format_args!("{:?}", self.0))
+			} else {
+				self.0.serialize(serializer)
+			}
+		}
+	}
+
+	impl Serialize for IsaLevel {
+		#[inline]
+		fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where
+			S: Serializer,
+		{
+			if serializer.is_human_readable() {
+				serializer.serialize_str(self.name())
+			} else {
+				serializer.serialize_u8(*self as u8)
+			}
+		}
+	}
+
+	impl Serialize for CpuFeatureUsage {
+		#[inline]
+		fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where
+			S: Serializer,
+		{
+			let mut serde_state = serializer.serialize_struct("CpuFeatureUsage", 3)?;
+			serde_state.serialize_field("feature", &FeatureName(self.feature))?;
+			serde_state.serialize_field("count", &self.count)?;
+			serde_state.serialize_field("first_ip", &self.first_ip)?;
+			serde_state.end()
+		}
+	}
+
+	struct FeatureNames<'a>(&'a [CpuidFeature]);
+	impl Serialize for FeatureNames<'_> {
+		#[inline]
+		fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where
+			S: Serializer,
+		{
+			serializer.collect_seq(self.0.iter().map(|&feature| FeatureName(feature)))
+		}
+	}
+
+	impl Serialize for CpuFeatureReport {
+		#[inline]
+		fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+		where
+			S: Serializer,
+		{
+			let mut serde_state = serializer.serialize_struct("CpuFeatureReport", 6)?;
+			serde_state.serialize_field("features", self.features.as_slice())?;
+			serde_state.serialize_field("instruction_count", &self.instruction_count)?;
+			serde_state.serialize_field("invalid_count", &self.invalid_count)?;
+			serde_state.serialize_field("min_level", &self.min_level)?;
+			serde_state.serialize_field("extra_features", &FeatureNames(&self.extra_features))?;
+			serde_state.serialize_field("os_features", &self.os_features)?;
+			serde_state.end()
+		}
+	}
+};
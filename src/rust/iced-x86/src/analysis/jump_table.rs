@@ -0,0 +1,373 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::analysis::cfg::near_branch_target;
+use crate::analysis::gadget::is_write;
+use crate::{CodeSize, FlowControl, Instruction, InstructionInfoFactory, Mnemonic, OpKind, Register};
+use alloc::vec::Vec;
+use core::cmp;
+
+// Max number of instructions before the indirect branch that are checked
+const MAX_BACKTRACK: usize = 32;
+// Max number of targets allocated before the table has been read
+const MAX_INITIAL_CAPACITY: u64 = 255;
+
+/// How the targets are stored in a [`JumpTable`]
+///
+/// [`JumpTable`]: struct.JumpTable.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum JumpTableKind {
+	/// Each entry is the target address, eg. `jmp qword ptr [rax*8+402000h]`
+	Absolute,
+	/// Each entry is added to [`JumpTable::base`], eg. `movsxd rax,dword ptr [rdx+rax*4]` + `add rax,rdx` + `jmp rax`.
+	/// Entries loaded with `MOVSXD` are sign extended, other entries are zero extended.
+	///
+	/// [`JumpTable::base`]: struct.JumpTable.html#structfield.base
+	Relative,
+}
+
+/// A jump table used by an indirect `JMP`, see [`JumpTableResolver`]
+///
+/// [`JumpTableResolver`]: struct.JumpTableResolver.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct JumpTable {
+	/// Address of the indirect `JMP` instruction
+	pub jump_address: u64,
+	/// Kind of table
+	pub kind: JumpTableKind,
+	/// Address of the first entry
+	pub table_address: u64,
+	/// Size of each entry in bytes (2, 4 or 8)
+	pub entry_size: u32,
+	/// Value added to each entry if it's a [`JumpTableKind::Relative`] table, else 0
+	///
+	/// [`JumpTableKind::Relative`]: enum.JumpTableKind.html#variant.Relative
+	pub base: u64,
+	/// Index register in the instruction that reads the table
+	pub index_register: Register,
+	/// The bounds check limits the index to `0..=max_index`
+	pub max_index: u64,
+	/// Target of the bounds check branch (eg. `JA`) if any
+	pub default_target: Option<u64>,
+	/// All targets, one per index. It can contain duplicates.
+	pub targets: Vec<u64>,
+}
+
+/// Recovers switch jump tables used by indirect `JMP` instructions
+///
+/// The instructions before the `JMP` are checked for common compiler idioms:
+///
+/// - `cmp idx,N` + `ja default` + `jmp [table+idx*8]`
+/// - `lea base,[rip+table]` + `movsxd off,[base+idx*4]` + `add off,base` + `jmp off`
+/// - `mov off,[base+idx*4+table_rva]` + `add off,base` + `jmp off` (`base` is the image base)
+///
+/// The index must be bounded by a `CMP` followed by a `JA` or `JAE` or by an `AND` with an immediate.
+/// The table entries are read from the image passed to the constructor.
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+/// use iced_x86::*;
+///
+/// // 0x1000: cmp eax,2
+/// // 0x1003: ja short 1020h
+/// // 0x1005: jmp qword ptr [rax*8+2000h]
+/// let bytes = b"\x83\xF8\x02\x77\x1B\xFF\x24\xC5\x00\x20\x00\x00";
+/// let instrs: Vec<Instruction> = Decoder::with_ip(64, bytes, 0x1000, DecoderOptions::NONE).into_iter().collect();
+///
+/// // The table at 0x2000
+/// let mut image = vec![0; 0x1018];
+/// image[0x1000..0x1008].copy_from_slice(&0x1100u64.to_le_bytes());
+/// image[0x1008..0x1010].copy_from_slice(&0x1200u64.to_le_bytes());
+/// image[0x1010..0x1018].copy_from_slice(&0x1100u64.to_le_bytes());
+///
+/// let resolver = JumpTableResolver::new(&image, 0x1000);
+/// let table = resolver.resolve(&instrs, 2).unwrap();
+/// assert_eq!(table.kind, JumpTableKind::Absolute);
+/// assert_eq!(table.table_address, 0x2000);
+/// assert_eq!(table.index_register, Register::RAX);
+/// assert_eq!(table.max_index, 2);
+/// assert_eq!(table.default_target, Some(0x1020));
+/// assert_eq!(table.targets, [0x1100, 0x1200, 0x1100]);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct JumpTableResolver<'a> {
+	image: &'a [u8],
+	image_address: u64,
+	max_entries: u64,
+}
+
+struct TableLoad {
+	address: u64,
+	entry_size: u32,
+	signed: bool,
+	index_register: Register,
+	position: usize,
+}
+
+impl<'a> JumpTableResolver<'a> {
+	/// Creates a new instance that reads tables with at most 1024 entries
+	///
+	/// # Arguments
+	///
+	/// * `image`: Memory containing the jump tables, eg. the mapped image
+	/// * `image_address`: Address of `image[0]`
+	#[must_use]
+	#[inline]
+	pub fn new(image: &'a [u8], image_address: u64) -> Self {
+		Self { image, image_address, max_entries: 1024 }
+	}
+
+	/// Gets the max number of entries in a table (default: 1024). Larger tables are ignored.
+	#[must_use]
+	#[inline]
+	pub fn max_entries(&self) -> u64 {
+		self.max_entries
+	}
+
+	/// Sets the max number of entries in a table (default: 1024). Larger tables are ignored.
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_max_entries(&mut self, new_value: u64) {
+		self.max_entries = new_value;
+	}
+
+	/// Resolves the jump table used by `instructions[index]`. `None` is returned if it's not an indirect `JMP`,
+	/// if no known idiom was found or if the table isn't in the image.
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Instructions sorted by IP, eg. all instructions returned by a [`Decoder`]
+	/// * `index`: Index of the indirect `JMP` instruction
+	///
+	/// [`Decoder`]: ../struct.Decoder.html
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn resolve(&self, instructions: &[Instruction], index: usize) -> Option<JumpTable> {
+		self.resolve_core(instructions, index, &mut InstructionInfoFactory::new())
+	}
+
+	/// Resolves the jump tables of all indirect `JMP` instructions, see [`resolve()`]
+	///
+	/// [`resolve()`]: #method.resolve
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Instructions sorted by IP, eg. all instructions returned by a [`Decoder`]
+	///
+	/// [`Decoder`]: ../struct.Decoder.html
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn resolve_all(&self, instructions: &[Instruction]) -> Vec<JumpTable> {
+		let mut info_factory = InstructionInfoFactory::new();
+		(0..instructions.len()).filter_map(|index| self.resolve_core(instructions, index, &mut info_factory)).collect()
+	}
+
+	fn resolve_core(&self, instructions: &[Instruction], index: usize, info_factory: &mut InstructionInfoFactory) -> Option<JumpTable> {
+		let jump = instructions.get(index)?;
+		if jump.mnemonic() != Mnemonic::Jmp || jump.flow_control() != FlowControl::IndirectBranch {
+			return None;
+		}
+
+		let (kind, base, load) = match jump.op0_kind() {
+			OpKind::Memory => (JumpTableKind::Absolute, 0, table_load(instructions, index, jump, false, info_factory)?),
+			OpKind::Register => {
+				let register = jump.op0_register().full_register();
+				let def = find_def(instructions, index, register, info_factory)?;
+				let instruction = &instructions[def];
+				if instruction.mnemonic() == Mnemonic::Add
+					&& instruction.op0_kind() == OpKind::Register
+					&& instruction.op1_kind() == OpKind::Register
+					&& instruction.op0_register().full_register() == register
+				{
+					// `add reg,other`: one of them is the loaded entry and the other one is the base
+					let other = instruction.op1_register().full_register();
+					if let Some(base) = constant_value(instructions, def, other, info_factory) {
+						(JumpTableKind::Relative, base, loaded_entry(instructions, def, register, info_factory)?)
+					} else {
+						let base = constant_value(instructions, def, register, info_factory)?;
+						(JumpTableKind::Relative, base, loaded_entry(instructions, def, other, info_factory)?)
+					}
+				} else {
+					(JumpTableKind::Absolute, 0, loaded_entry(instructions, index, register, info_factory)?)
+				}
+			}
+			_ => return None,
+		};
+
+		let (max_index, default_target) = find_bound(instructions, load.position, load.index_register, info_factory)?;
+		if max_index >= self.max_entries {
+			return None;
+		}
+		let target_mask = match jump.code_size() {
+			CodeSize::Code16 => 0xFFFF,
+			CodeSize::Code32 => 0xFFFF_FFFF,
+			CodeSize::Code64 | CodeSize::Unknown => u64::MAX,
+		};
+		// The bound can be huge (eg. `cmp rax,-1` if max_entries is big) and the table is validated while it's read
+		let mut targets = Vec::with_capacity(cmp::min(max_index, MAX_INITIAL_CAPACITY) as usize + 1);
+		for i in 0..=max_index {
+			let entry_address = load.address.wrapping_add(i.wrapping_mul(load.entry_size as u64));
+			let value = self.read(entry_address, load.entry_size, load.signed)?;
+			targets.push(base.wrapping_add(value) & target_mask);
+		}
+
+		Some(JumpTable {
+			jump_address: jump.ip(),
+			kind,
+			table_address: load.address,
+			entry_size: load.entry_size,
+			base,
+			index_register: load.index_register,
+			max_index,
+			default_target,
+			targets,
+		})
+	}
+
+	fn read(&self, address: u64, size: u32, signed: bool) -> Option<u64> {
+		let offset = usize::try_from(address.checked_sub(self.image_address)?).ok()?;
+		let bytes = self.image.get(offset..offset.checked_add(size as usize)?)?;
+		let value = bytes.iter().rev().fold(0u64, |value, &b| (value << 8) | b as u64);
+		let shift = 64 - size * 8;
+		Some(if signed { (((value << shift) as i64) >> shift) as u64 } else { value })
+	}
+}
+
+// Finds the closest instruction before `before` that writes `register` (a full register)
+fn find_def(instructions: &[Instruction], before: usize, register: Register, info_factory: &mut InstructionInfoFactory) -> Option<usize> {
+	for i in (before.saturating_sub(MAX_BACKTRACK)..before).rev() {
+		let instruction = &instructions[i];
+		let info = info_factory.info(instruction);
+		if info.used_registers().iter().any(|used_reg| is_write(used_reg.access()) && used_reg.register().full_register() == register) {
+			return Some(i);
+		}
+		if !matches!(instruction.flow_control(), FlowControl::Next | FlowControl::ConditionalBranch) {
+			break;
+		}
+	}
+	None
+}
+
+// Gets the value of `register` if it was initialized with a constant (`lea reg,[rip+X]`, `mov reg,imm`)
+fn constant_value(instructions: &[Instruction], before: usize, register: Register, info_factory: &mut InstructionInfoFactory) -> Option<u64> {
+	let instruction = &instructions[find_def(instructions, before, register, info_factory)?];
+	if instruction.op0_kind() != OpKind::Register || instruction.op0_register().full_register() != register {
+		return None;
+	}
+	let mask = value_mask(instruction.op0_register().size());
+	match (instruction.mnemonic(), instruction.op1_kind()) {
+		(Mnemonic::Lea, OpKind::Memory) if instruction.is_ip_rel_memory_operand() => Some(instruction.ip_rel_memory_address() & mask),
+		(Mnemonic::Lea, OpKind::Memory) if instruction.memory_base() == Register::None && instruction.memory_index() == Register::None => {
+			Some(instruction.memory_displacement64() & mask)
+		}
+		(Mnemonic::Mov, op_kind) if is_immediate(op_kind) => Some(instruction.immediate(1) & mask),
+		_ => None,
+	}
+}
+
+// Finds the `mov reg,[table+idx*N]` or `movsxd reg,[table+idx*4]` instruction that initialized `register`
+fn loaded_entry(instructions: &[Instruction], before: usize, register: Register, info_factory: &mut InstructionInfoFactory) -> Option<TableLoad> {
+	let def = find_def(instructions, before, register, info_factory)?;
+	let instruction = &instructions[def];
+	let signed = match instruction.mnemonic() {
+		Mnemonic::Mov | Mnemonic::Movzx => false,
+		Mnemonic::Movsxd | Mnemonic::Movsx => true,
+		_ => return None,
+	};
+	if instruction.op0_kind() != OpKind::Register || instruction.op0_register().full_register() != register {
+		return None;
+	}
+	table_load(instructions, def, instruction, signed, info_factory)
+}
+
+// Gets the table address from the memory operand of `instruction`, eg. `[rdx+rax*4]` or `[rax*8+402000h]`
+fn table_load(
+	instructions: &[Instruction], position: usize, instruction: &Instruction, signed: bool, info_factory: &mut InstructionInfoFactory,
+) -> Option<TableLoad> {
+	if !instruction.op_kinds().any(|op_kind| op_kind == OpKind::Memory) {
+		return None;
+	}
+	let index_register = instruction.memory_index();
+	let base_register = instruction.memory_base();
+	let entry_size = instruction.memory_size().size() as u32;
+	if !index_register.is_gpr() || instruction.memory_index_scale() != entry_size || !matches!(entry_size, 2 | 4 | 8) {
+		return None;
+	}
+	let base = if base_register == Register::None { 0 } else { constant_value(instructions, position, base_register.full_register(), info_factory)? };
+	let address_mask = value_mask(if index_register.is_gpr64() {
+		8
+	} else if index_register.is_gpr32() {
+		4
+	} else {
+		2
+	});
+	let address = base.wrapping_add(instruction.memory_displacement64()) & address_mask;
+	Some(TableLoad { address, entry_size, signed, index_register, position })
+}
+
+// Finds the bounds check of the index register, eg. `cmp eax,N` + `ja default` or `and eax,N`
+fn find_bound(
+	instructions: &[Instruction], before: usize, index_register: Register, info_factory: &mut InstructionInfoFactory,
+) -> Option<(u64, Option<u64>)> {
+	let mut register = index_register.full_register();
+	for i in (before.saturating_sub(MAX_BACKTRACK)..before).rev() {
+		let instruction = &instructions[i];
+		let has_immediate = instruction.op_count() == 2 && is_immediate(instruction.op1_kind());
+		let is_index = instruction.op0_kind() == OpKind::Register && instruction.op0_register().full_register() == register;
+		if instruction.mnemonic() == Mnemonic::Cmp && is_index && has_immediate {
+			let limit = instruction.immediate(1) & value_mask(instruction.op0_register().size());
+			let jcc = instructions.get(i + 1)?;
+			return match jcc.mnemonic() {
+				Mnemonic::Ja => Some((limit, near_branch_target(jcc))),
+				Mnemonic::Jae if limit != 0 => Some((limit - 1, near_branch_target(jcc))),
+				_ => None,
+			};
+		}
+		let info = info_factory.info(instruction);
+		if info.used_registers().iter().any(|used_reg| is_write(used_reg.access()) && used_reg.register().full_register() == register) {
+			match instruction.mnemonic() {
+				// The index was copied from another register
+				Mnemonic::Mov | Mnemonic::Movzx | Mnemonic::Movsxd if is_index && instruction.op1_kind() == OpKind::Register => {
+					register = instruction.op1_register().full_register()
+				}
+				Mnemonic::And if is_index && has_immediate => {
+					return Some((instruction.immediate(1) & value_mask(instruction.op0_register().size()), None));
+				}
+				_ => return None,
+			}
+		}
+		if !matches!(instruction.flow_control(), FlowControl::Next | FlowControl::ConditionalBranch) {
+			break;
+		}
+	}
+	None
+}
+
+fn is_immediate(op_kind: OpKind) -> bool {
+	matches!(
+		op_kind,
+		OpKind::Immediate8
+			| OpKind::Immediate16
+			| OpKind::Immediate32
+			| OpKind::Immediate64
+			| OpKind::Immediate8to16
+			| OpKind::Immediate8to32
+			| OpKind::Immediate8to64
+			| OpKind::Immediate32to64
+	)
+}
+
+fn value_mask(size: usize) -> u64 {
+	if size >= 8 {
+		u64::MAX
+	} else {
+		(1u64 << (size * 8)) - 1
+	}
+}
@@ -0,0 +1,997 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::{CodeSize, Instruction, MemorySize, Mnemonic, OpKind, Register};
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A class of registers that can be matched by a [`RegisterPattern`]
+///
+/// [`RegisterPattern`]: enum.RegisterPattern.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum RegisterClass {
+	/// 8-bit general purpose registers (`AL`-`R15L`, `AH`-`BH`)
+	Gpr8,
+	/// 16-bit general purpose registers (`AX`-`R15W`)
+	Gpr16,
+	/// 32-bit general purpose registers (`EAX`-`R15D`)
+	Gpr32,
+	/// 64-bit general purpose registers (`RAX`-`R15`)
+	Gpr64,
+	/// Any general purpose register (8, 16, 32 or 64-bit)
+	Gpr,
+	/// Segment registers (`ES`, `CS`, `SS`, `DS`, `FS`, `GS`)
+	Segment,
+	/// `XMM` registers
+	Xmm,
+	/// `YMM` registers
+	Ymm,
+	/// `ZMM` registers
+	Zmm,
+	/// Any vector register (`XMM`, `YMM` or `ZMM`)
+	Vector,
+	/// Opmask registers (`K0`-`K7`)
+	K,
+	/// `MM` registers
+	Mm,
+	/// `ST(i)` registers
+	St,
+	/// Control registers
+	Cr,
+	/// Debug registers
+	Dr,
+	/// Test registers
+	Tr,
+	/// `BND` registers
+	Bnd,
+	/// Tile registers
+	Tmm,
+}
+
+impl RegisterClass {
+	/// Checks if `register` is part of this class
+	///
+	/// # Arguments
+	///
+	/// * `register`: Register
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::analysis::RegisterClass;
+	/// use iced_x86::Register;
+	///
+	/// assert!(RegisterClass::Gpr64.contains(Register::R8));
+	/// assert!(!RegisterClass::Gpr64.contains(Register::R8D));
+	/// assert!(RegisterClass::Vector.contains(Register::YMM3));
+	/// ```
+	#[must_use]
+	#[inline]
+	pub fn contains(self, register: Register) -> bool {
+		match self {
+			RegisterClass::Gpr8 => register.is_gpr8(),
+			RegisterClass::Gpr16 => register.is_gpr16(),
+			RegisterClass::Gpr32 => register.is_gpr32(),
+			RegisterClass::Gpr64 => register.is_gpr64(),
+			RegisterClass::Gpr => register.is_gpr(),
+			RegisterClass::Segment => register.is_segment_register(),
+			RegisterClass::Xmm => register.is_xmm(),
+			RegisterClass::Ymm => register.is_ymm(),
+			RegisterClass::Zmm => register.is_zmm(),
+			RegisterClass::Vector => register.is_vector_register(),
+			RegisterClass::K => register.is_k(),
+			RegisterClass::Mm => register.is_mm(),
+			RegisterClass::St => register.is_st(),
+			RegisterClass::Cr => register.is_cr(),
+			RegisterClass::Dr => register.is_dr(),
+			RegisterClass::Tr => register.is_tr(),
+			RegisterClass::Bnd => register.is_bnd(),
+			RegisterClass::Tmm => register.is_tmm(),
+		}
+	}
+}
+
+/// Matches a register operand or a memory operand's base/index register
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum RegisterPattern {
+	/// Any register but not [`Register::None`]
+	///
+	/// [`Register::None`]: ../enum.Register.html#variant.None
+	Any,
+	/// Only matches [`Register::None`], eg. a memory operand without an index register
+	///
+	/// [`Register::None`]: ../enum.Register.html#variant.None
+	None,
+	/// Only matches this register
+	Exact(Register),
+	/// Any register in this class
+	Class(RegisterClass),
+	/// Any of these registers
+	OneOf(Vec<Register>),
+}
+
+impl RegisterPattern {
+	/// Checks if `register` matches this pattern
+	///
+	/// # Arguments
+	///
+	/// * `register`: Register
+	#[must_use]
+	#[inline]
+	pub fn matches(&self, register: Register) -> bool {
+		match self {
+			RegisterPattern::Any => register != Register::None,
+			RegisterPattern::None => register == Register::None,
+			RegisterPattern::Exact(reg) => *reg == register,
+			RegisterPattern::Class(class) => class.contains(register),
+			RegisterPattern::OneOf(regs) => regs.contains(&register),
+		}
+	}
+}
+
+impl From<Register> for RegisterPattern {
+	#[inline]
+	fn from(register: Register) -> Self {
+		RegisterPattern::Exact(register)
+	}
+}
+
+impl From<RegisterClass> for RegisterPattern {
+	#[inline]
+	fn from(class: RegisterClass) -> Self {
+		RegisterPattern::Class(class)
+	}
+}
+
+/// A value captured by a pattern, see [`PatternCaptures`]
+///
+/// [`PatternCaptures`]: struct.PatternCaptures.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum CapturedValue {
+	/// A register operand or a memory operand's base/index register
+	Register(Register),
+	/// An immediate operand, see [`Instruction::immediate()`]
+	///
+	/// [`Instruction::immediate()`]: ../struct.Instruction.html#method.immediate
+	Immediate(u64),
+	/// A memory operand's displacement. If it's a `RIP`/`EIP` relative memory operand, this is the absolute address.
+	Displacement(u64),
+	/// A near branch target
+	BranchTarget(u64),
+}
+
+/// Values captured by a matched pattern. Each capture has a name and if the same name is used more than
+/// once in a pattern, all values must be identical or the pattern doesn't match.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct PatternCaptures {
+	captures: Vec<(String, CapturedValue)>,
+}
+
+impl PatternCaptures {
+	/// Gets a captured value
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the capture
+	#[must_use]
+	#[inline]
+	pub fn get(&self, name: &str) -> Option<CapturedValue> {
+		self.captures.iter().find(|(n, _)| n == name).map(|(_, value)| *value)
+	}
+
+	/// Gets a captured register or `None` if it doesn't exist or if it's not a register
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the capture
+	#[must_use]
+	#[inline]
+	pub fn register(&self, name: &str) -> Option<Register> {
+		match self.get(name) {
+			Some(CapturedValue::Register(register)) => Some(register),
+			_ => None,
+		}
+	}
+
+	/// Gets a captured immediate, displacement or branch target or `None` if it doesn't exist or if it's a register
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the capture
+	#[must_use]
+	#[inline]
+	pub fn value(&self, name: &str) -> Option<u64> {
+		match self.get(name) {
+			Some(CapturedValue::Immediate(value)) | Some(CapturedValue::Displacement(value)) | Some(CapturedValue::BranchTarget(value)) => {
+				Some(value)
+			}
+			_ => None,
+		}
+	}
+
+	/// Gets all captures in the order they were captured
+	#[inline]
+	pub fn iter(&self) -> impl Iterator<Item = (&str, CapturedValue)> {
+		self.captures.iter().map(|(name, value)| (name.as_str(), *value))
+	}
+
+	/// Gets the number of captures
+	#[must_use]
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.captures.len()
+	}
+
+	/// `true` if nothing was captured
+	#[must_use]
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.captures.is_empty()
+	}
+
+	// Adds the value or verifies that it's identical to an already captured value
+	fn bind(&mut self, name: &Option<String>, value: CapturedValue) -> bool {
+		let name = match name {
+			Some(name) => name,
+			None => return true,
+		};
+		match self.get(name) {
+			Some(prev) => prev == value,
+			None => {
+				self.captures.push((name.clone(), value));
+				true
+			}
+		}
+	}
+}
+
+/// Matches one operand of an instruction
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum OperandPattern {
+	/// Any operand
+	Any,
+	/// A register operand
+	Register {
+		/// Register constraint
+		register: RegisterPattern,
+		/// Name of the capture or `None`
+		capture: Option<String>,
+	},
+	/// An immediate operand. The immediate is sign extended from its size and compared as a signed 64-bit value,
+	/// eg. the 8-bit immediate `0xFF` is `-1`. The captured value isn't sign extended, see [`Instruction::immediate()`]
+	///
+	/// [`Instruction::immediate()`]: ../struct.Instruction.html#method.immediate
+	Immediate {
+		/// Min value (inclusive)
+		min: i64,
+		/// Max value (inclusive)
+		max: i64,
+		/// Name of the capture or `None`
+		capture: Option<String>,
+	},
+	/// A near branch operand
+	BranchTarget {
+		/// Name of the capture or `None`
+		capture: Option<String>,
+	},
+	/// A memory operand
+	Memory(MemoryPattern),
+}
+
+impl OperandPattern {
+	/// Matches any operand
+	#[must_use]
+	#[inline]
+	pub const fn any() -> Self {
+		OperandPattern::Any
+	}
+
+	/// Matches any register operand
+	#[must_use]
+	#[inline]
+	pub const fn any_register() -> Self {
+		OperandPattern::Register { register: RegisterPattern::Any, capture: None }
+	}
+
+	/// Matches a register operand
+	///
+	/// # Arguments
+	///
+	/// * `register`: A register, a register class or a [`RegisterPattern`]
+	///
+	/// [`RegisterPattern`]: enum.RegisterPattern.html
+	#[must_use]
+	#[inline]
+	pub fn register<T: Into<RegisterPattern>>(register: T) -> Self {
+		OperandPattern::Register { register: register.into(), capture: None }
+	}
+
+	/// Matches any immediate operand
+	#[must_use]
+	#[inline]
+	pub const fn immediate() -> Self {
+		OperandPattern::Immediate { min: i64::MIN, max: i64::MAX, capture: None }
+	}
+
+	/// Matches an immediate operand in the range `min..=max`
+	///
+	/// # Arguments
+	///
+	/// * `min`: Min value (inclusive)
+	/// * `max`: Max value (inclusive)
+	#[must_use]
+	#[inline]
+	pub const fn immediate_range(min: i64, max: i64) -> Self {
+		OperandPattern::Immediate { min, max, capture: None }
+	}
+
+	/// Matches any near branch operand
+	#[must_use]
+	#[inline]
+	pub const fn branch_target() -> Self {
+		OperandPattern::BranchTarget { capture: None }
+	}
+
+	/// Matches a memory operand
+	///
+	/// # Arguments
+	///
+	/// * `memory`: Memory operand constraints
+	#[must_use]
+	#[inline]
+	pub const fn memory(memory: MemoryPattern) -> Self {
+		OperandPattern::Memory(memory)
+	}
+
+	/// Captures the register, immediate or branch target. If it's a memory operand, it's ignored,
+	/// use the capture methods of [`MemoryPattern`] instead.
+	///
+	/// [`MemoryPattern`]: struct.MemoryPattern.html
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the capture
+	#[must_use]
+	#[inline]
+	pub fn capture<S: Into<String>>(mut self, name: S) -> Self {
+		match &mut self {
+			OperandPattern::Register { capture, .. } | OperandPattern::Immediate { capture, .. } | OperandPattern::BranchTarget { capture } => {
+				*capture = Some(name.into())
+			}
+			OperandPattern::Any | OperandPattern::Memory(_) => {}
+		}
+		self
+	}
+
+	fn match_operand(&self, instruction: &Instruction, operand: u32, captures: &mut PatternCaptures) -> bool {
+		let op_kind = instruction.op_kind(operand);
+		match self {
+			OperandPattern::Any => true,
+			OperandPattern::Register { register, capture } => {
+				if op_kind != OpKind::Register {
+					return false;
+				}
+				let reg = instruction.op_register(operand);
+				register.matches(reg) && captures.bind(capture, CapturedValue::Register(reg))
+			}
+			OperandPattern::Immediate { min, max, capture } => {
+				let value = match instruction.try_immediate(operand) {
+					Ok(value) => value,
+					Err(_) => return false,
+				};
+				let signed = match op_kind {
+					OpKind::Immediate8 | OpKind::Immediate8_2nd => value as i8 as i64,
+					OpKind::Immediate16 => value as i16 as i64,
+					OpKind::Immediate32 => value as i32 as i64,
+					_ => value as i64,
+				};
+				*min <= signed && signed <= *max && captures.bind(capture, CapturedValue::Immediate(value))
+			}
+			OperandPattern::BranchTarget { capture } => match op_kind {
+				OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 => {
+					captures.bind(capture, CapturedValue::BranchTarget(instruction.near_branch_target()))
+				}
+				_ => false,
+			},
+			OperandPattern::Memory(memory) => op_kind == OpKind::Memory && memory.match_memory(instruction, captures),
+		}
+	}
+}
+
+impl From<Register> for OperandPattern {
+	#[inline]
+	fn from(register: Register) -> Self {
+		OperandPattern::register(register)
+	}
+}
+
+impl From<RegisterClass> for OperandPattern {
+	#[inline]
+	fn from(class: RegisterClass) -> Self {
+		OperandPattern::register(class)
+	}
+}
+
+impl From<MemoryPattern> for OperandPattern {
+	#[inline]
+	fn from(memory: MemoryPattern) -> Self {
+		OperandPattern::Memory(memory)
+	}
+}
+
+/// Matches a memory operand, eg. `[any+disp]`. All fields are unconstrained by default.
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+///
+/// // [reg64+disp] where disp is in the range 0-0xFF, eg. [rcx+10h]
+/// let _ = MemoryPattern::new()
+///     .base(RegisterClass::Gpr64)
+///     .index(RegisterPattern::None)
+///     .displacement(0, 0xFF)
+///     .capture_displacement("offset");
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct MemoryPattern {
+	base: Option<RegisterPattern>,
+	index: Option<RegisterPattern>,
+	scale: Option<u32>,
+	displacement: Option<(i64, i64)>,
+	size: Option<MemorySize>,
+	segment: Option<Register>,
+	base_capture: Option<String>,
+	index_capture: Option<String>,
+	displacement_capture: Option<String>,
+}
+
+impl MemoryPattern {
+	/// Creates a pattern that matches any memory operand
+	#[must_use]
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the base register constraint. Use [`RegisterPattern::None`] to match memory operands without a base register.
+	///
+	/// [`RegisterPattern::None`]: enum.RegisterPattern.html#variant.None
+	///
+	/// # Arguments
+	///
+	/// * `base`: Base register constraint
+	#[must_use]
+	#[inline]
+	pub fn base<T: Into<RegisterPattern>>(mut self, base: T) -> Self {
+		self.base = Some(base.into());
+		self
+	}
+
+	/// Sets the index register constraint. Use [`RegisterPattern::None`] to match memory operands without an index register.
+	///
+	/// [`RegisterPattern::None`]: enum.RegisterPattern.html#variant.None
+	///
+	/// # Arguments
+	///
+	/// * `index`: Index register constraint
+	#[must_use]
+	#[inline]
+	pub fn index<T: Into<RegisterPattern>>(mut self, index: T) -> Self {
+		self.index = Some(index.into());
+		self
+	}
+
+	/// Sets the index scale (1, 2, 4 or 8)
+	///
+	/// # Arguments
+	///
+	/// * `scale`: Index scale
+	#[must_use]
+	#[inline]
+	pub fn scale(mut self, scale: u32) -> Self {
+		self.scale = Some(scale);
+		self
+	}
+
+	/// Sets the displacement range. The displacement is compared as a signed value unless it's a
+	/// `RIP`/`EIP` relative memory operand in which case the absolute address is compared.
+	///
+	/// # Arguments
+	///
+	/// * `min`: Min value (inclusive)
+	/// * `max`: Max value (inclusive)
+	#[must_use]
+	#[inline]
+	pub fn displacement(mut self, min: i64, max: i64) -> Self {
+		self.displacement = Some((min, max));
+		self
+	}
+
+	/// Sets the memory size constraint
+	///
+	/// # Arguments
+	///
+	/// * `size`: Memory size
+	#[must_use]
+	#[inline]
+	pub fn size(mut self, size: MemorySize) -> Self {
+		self.size = Some(size);
+		self
+	}
+
+	/// Sets the effective segment register constraint, see [`Instruction::memory_segment()`]
+	///
+	/// [`Instruction::memory_segment()`]: ../struct.Instruction.html#method.memory_segment
+	///
+	/// # Arguments
+	///
+	/// * `segment`: Segment register
+	#[must_use]
+	#[inline]
+	pub fn segment(mut self, segment: Register) -> Self {
+		self.segment = Some(segment);
+		self
+	}
+
+	/// Captures the base register
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the capture
+	#[must_use]
+	#[inline]
+	pub fn capture_base<S: Into<String>>(mut self, name: S) -> Self {
+		self.base_capture = Some(name.into());
+		self
+	}
+
+	/// Captures the index register
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the capture
+	#[must_use]
+	#[inline]
+	pub fn capture_index<S: Into<String>>(mut self, name: S) -> Self {
+		self.index_capture = Some(name.into());
+		self
+	}
+
+	/// Captures the displacement (or the absolute address if it's `RIP`/`EIP` relative)
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the capture
+	#[must_use]
+	#[inline]
+	pub fn capture_displacement<S: Into<String>>(mut self, name: S) -> Self {
+		self.displacement_capture = Some(name.into());
+		self
+	}
+
+	fn match_memory(&self, instruction: &Instruction, captures: &mut PatternCaptures) -> bool {
+		let base = instruction.memory_base();
+		let index = instruction.memory_index();
+		if let Some(ref pattern) = self.base {
+			if !pattern.matches(base) {
+				return false;
+			}
+		}
+		if let Some(ref pattern) = self.index {
+			if !pattern.matches(index) {
+				return false;
+			}
+		}
+		if let Some(scale) = self.scale {
+			if instruction.memory_index_scale() != scale {
+				return false;
+			}
+		}
+		if let Some(size) = self.size {
+			if instruction.memory_size() != size {
+				return false;
+			}
+		}
+		if let Some(segment) = self.segment {
+			if instruction.memory_segment() != segment {
+				return false;
+			}
+		}
+		let displacement = if instruction.is_ip_rel_memory_operand() {
+			instruction.ip_rel_memory_address()
+		} else {
+			signed_displacement(instruction, base, index) as u64
+		};
+		if let Some((min, max)) = self.displacement {
+			let value = displacement as i64;
+			if value < min || value > max {
+				return false;
+			}
+		}
+		(base == Register::None || captures.bind(&self.base_capture, CapturedValue::Register(base)))
+			&& (index == Register::None || captures.bind(&self.index_capture, CapturedValue::Register(index)))
+			&& captures.bind(&self.displacement_capture, CapturedValue::Displacement(displacement))
+	}
+}
+
+// Sign extends the displacement using the memory operand's address size
+fn signed_displacement(instruction: &Instruction, base: Register, index: Register) -> i64 {
+	let addr_reg = if base != Register::None { base } else { index };
+	let is_64 = if addr_reg.is_gpr64() {
+		true
+	} else if addr_reg.is_gpr32() || addr_reg.is_gpr16() {
+		false
+	} else {
+		// No base/index reg or it's a vsib index reg
+		instruction.code_size() == CodeSize::Code64
+	};
+	if is_64 {
+		instruction.memory_displacement64() as i64
+	} else if addr_reg.is_gpr16() {
+		instruction.memory_displacement32() as i16 as i64
+	} else {
+		instruction.memory_displacement32() as i32 as i64
+	}
+}
+
+/// Matches one instruction: a mnemonic and optional operand constraints
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+/// use iced_x86::*;
+///
+/// // mov rax,[rip+...]
+/// let bytes = b"\x48\x8B\x05\x10\x00\x00\x00";
+/// let instr = Decoder::with_ip(64, bytes, 0x1000, DecoderOptions::NONE).decode();
+///
+/// let pattern = InstrPattern::new(Mnemonic::Mov)
+///     .operand(OperandPattern::register(RegisterClass::Gpr64).capture("dst"))
+///     .operand(MemoryPattern::new().base(Register::RIP).capture_displacement("addr"));
+/// let captures = pattern.match_instruction(&instr).unwrap();
+/// assert_eq!(captures.register("dst"), Some(Register::RAX));
+/// assert_eq!(captures.value("addr"), Some(0x1017));
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct InstrPattern {
+	mnemonics: Vec<Mnemonic>,
+	operands: Option<Vec<OperandPattern>>,
+}
+
+impl InstrPattern {
+	/// Creates a pattern that matches instructions with this mnemonic and any operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic
+	#[must_use]
+	#[inline]
+	pub fn new(mnemonic: Mnemonic) -> Self {
+		Self { mnemonics: vec![mnemonic], operands: None }
+	}
+
+	/// Creates a pattern that matches instructions with any of these mnemonics and any operands
+	///
+	/// # Arguments
+	///
+	/// * `mnemonics`: Mnemonics
+	#[must_use]
+	#[inline]
+	pub fn with_mnemonics(mnemonics: &[Mnemonic]) -> Self {
+		Self { mnemonics: mnemonics.to_vec(), operands: None }
+	}
+
+	/// Creates a pattern that matches any instruction
+	#[must_use]
+	#[inline]
+	pub fn any() -> Self {
+		Self::default()
+	}
+
+	/// Adds an operand constraint. Once at least one operand has been added, the instruction must
+	/// have exactly as many operands as there are operand constraints.
+	///
+	/// # Arguments
+	///
+	/// * `operand`: Operand constraint
+	#[must_use]
+	#[inline]
+	pub fn operand<T: Into<OperandPattern>>(mut self, operand: T) -> Self {
+		self.operands.get_or_insert_with(Vec::new).push(operand.into());
+		self
+	}
+
+	/// The instruction must not have any operands
+	#[must_use]
+	#[inline]
+	pub fn no_operands(mut self) -> Self {
+		self.operands = Some(Vec::new());
+		self
+	}
+
+	/// Checks if the instruction matches this pattern
+	///
+	/// # Arguments
+	///
+	/// * `instruction`: Instruction
+	#[must_use]
+	#[inline]
+	pub fn matches(&self, instruction: &Instruction) -> bool {
+		self.match_instruction(instruction).is_some()
+	}
+
+	/// Matches the instruction and returns all captured values or `None` if it doesn't match
+	///
+	/// # Arguments
+	///
+	/// * `instruction`: Instruction
+	#[must_use]
+	#[inline]
+	pub fn match_instruction(&self, instruction: &Instruction) -> Option<PatternCaptures> {
+		let mut captures = PatternCaptures::default();
+		if self.match_into(instruction, &mut captures) {
+			Some(captures)
+		} else {
+			None
+		}
+	}
+
+	// On failure, `captures` could contain new captures and the caller must restore it
+	fn match_into(&self, instruction: &Instruction, captures: &mut PatternCaptures) -> bool {
+		if !self.mnemonics.is_empty() && !self.mnemonics.contains(&instruction.mnemonic()) {
+			return false;
+		}
+		if let Some(ref operands) = self.operands {
+			if operands.len() != instruction.op_count() as usize {
+				return false;
+			}
+			for (i, operand) in operands.iter().enumerate() {
+				if !operand.match_operand(instruction, i as u32, captures) {
+					return false;
+				}
+			}
+		}
+		true
+	}
+}
+
+/// An item in a [`PatternSequence`]
+///
+/// [`PatternSequence`]: struct.PatternSequence.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum PatternItem {
+	/// One instruction that must match
+	Instruction(InstrPattern),
+	/// One instruction that is matched if present
+	Optional(InstrPattern),
+	/// `min..=max` instructions that are ignored
+	Gap {
+		/// Min number of instructions (inclusive)
+		min: usize,
+		/// Max number of instructions (inclusive)
+		max: usize,
+	},
+}
+
+/// A match returned by [`PatternSequence`]
+///
+/// [`PatternSequence`]: struct.PatternSequence.html
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct PatternMatch {
+	/// Index of the first matched instruction
+	pub start: usize,
+	/// Index of the instruction following the last matched instruction
+	pub end: usize,
+	/// IP of the first matched instruction
+	pub ip: u64,
+	/// IP of the instruction following the last matched instruction
+	pub end_ip: u64,
+	/// All captured values
+	pub captures: PatternCaptures,
+}
+
+/// A sequence of instruction patterns that can contain optional instructions and gaps
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::analysis::*;
+/// use iced_x86::*;
+///
+/// // push rbp
+/// // mov rbp,rsp
+/// // push rbx
+/// // sub rsp,20h
+/// let bytes = b"\x55\x48\x89\xE5\x53\x48\x83\xEC\x20";
+/// let instrs: Vec<Instruction> = Decoder::with_ip(64, bytes, 0x1000, DecoderOptions::NONE).into_iter().collect();
+///
+/// let prologue = PatternSequence::new()
+///     .then(InstrPattern::new(Mnemonic::Push).operand(Register::RBP))
+///     .then(InstrPattern::new(Mnemonic::Mov).operand(Register::RBP).operand(Register::RSP))
+///     .gap(0, 4)
+///     .then(InstrPattern::new(Mnemonic::Sub).operand(Register::RSP).operand(OperandPattern::immediate().capture("frame")));
+/// let m = prologue.find(&instrs).unwrap();
+/// assert_eq!((m.start, m.end), (0, 4));
+/// assert_eq!(m.ip, 0x1000);
+/// assert_eq!(m.end_ip, 0x1009);
+/// assert_eq!(m.captures.value("frame"), Some(0x20));
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct PatternSequence {
+	items: Vec<PatternItem>,
+}
+
+impl PatternSequence {
+	/// Creates an empty sequence
+	#[must_use]
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds an instruction that must match
+	///
+	/// # Arguments
+	///
+	/// * `pattern`: Instruction pattern
+	#[must_use]
+	#[inline]
+	pub fn then(mut self, pattern: InstrPattern) -> Self {
+		self.items.push(PatternItem::Instruction(pattern));
+		self
+	}
+
+	/// Adds an optional instruction
+	///
+	/// # Arguments
+	///
+	/// * `pattern`: Instruction pattern
+	#[must_use]
+	#[inline]
+	pub fn optional(mut self, pattern: InstrPattern) -> Self {
+		self.items.push(PatternItem::Optional(pattern));
+		self
+	}
+
+	/// Adds a gap of `min..=max` instructions that are ignored. The shortest gap is tried first.
+	///
+	/// # Arguments
+	///
+	/// * `min`: Min number of instructions (inclusive)
+	/// * `max`: Max number of instructions (inclusive)
+	#[must_use]
+	#[inline]
+	pub fn gap(mut self, min: usize, max: usize) -> Self {
+		self.items.push(PatternItem::Gap { min, max });
+		self
+	}
+
+	/// Gets all items
+	#[must_use]
+	#[inline]
+	pub fn items(&self) -> &[PatternItem] {
+		&self.items
+	}
+
+	/// Matches the sequence starting at `instructions[index]`. Empty matches are ignored.
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: All instructions
+	/// * `index`: Index of the first instruction
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn match_at(&self, instructions: &[Instruction], index: usize) -> Option<PatternMatch> {
+		if index >= instructions.len() {
+			return None;
+		}
+		let mut captures = PatternCaptures::default();
+		let end = self.match_items(0, instructions, index, &mut captures, &mut BTreeMap::new())?;
+		if end == index {
+			return None;
+		}
+		Some(PatternMatch { start: index, end, ip: instructions[index].ip(), end_ip: instructions[end - 1].next_ip(), captures })
+	}
+
+	/// Finds the first match
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: All instructions
+	#[must_use]
+	#[inline]
+	pub fn find(&self, instructions: &[Instruction]) -> Option<PatternMatch> {
+		(0..instructions.len()).find_map(|index| self.match_at(instructions, index))
+	}
+
+	/// Finds all non-overlapping matches
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: All instructions
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn find_all(&self, instructions: &[Instruction]) -> Vec<PatternMatch> {
+		let mut matches = Vec::new();
+		let mut index = 0;
+		while index < instructions.len() {
+			if let Some(m) = self.match_at(instructions, index) {
+				index = m.end;
+				matches.push(m);
+			} else {
+				index += 1;
+			}
+		}
+		matches
+	}
+
+	// Returns the end index if the remaining items match. `failed` contains all (item index, position, captures)
+	// states that didn't match. Several gaps can reach the same state in many ways so they're only tried once.
+	fn match_items(
+		&self, item_index: usize, instructions: &[Instruction], pos: usize, captures: &mut PatternCaptures,
+		failed: &mut BTreeMap<(usize, usize), Vec<PatternCaptures>>,
+	) -> Option<usize> {
+		let item = match self.items.get(item_index) {
+			Some(item) => item,
+			None => return Some(pos),
+		};
+		let saved_len = captures.len();
+		match item {
+			PatternItem::Instruction(pattern) => {
+				if let Some(instruction) = instructions.get(pos) {
+					if pattern.match_into(instruction, captures) {
+						if let Some(end) = self.match_items(item_index + 1, instructions, pos + 1, captures, failed) {
+							return Some(end);
+						}
+					}
+				}
+				captures.captures.truncate(saved_len);
+				None
+			}
+			PatternItem::Optional(pattern) => {
+				if let Some(instruction) = instructions.get(pos) {
+					if pattern.match_into(instruction, captures) {
+						if let Some(end) = self.match_items(item_index + 1, instructions, pos + 1, captures, failed) {
+							return Some(end);
+						}
+					}
+				}
+				captures.captures.truncate(saved_len);
+				self.match_items(item_index + 1, instructions, pos, captures, failed)
+			}
+			&PatternItem::Gap { min, max } => {
+				let remaining = instructions.len() - pos;
+				if min > remaining {
+					return None;
+				}
+				for skip in min..=max.min(remaining) {
+					let key = (item_index + 1, pos + skip);
+					if failed.get(&key).map_or(false, |states| states.contains(captures)) {
+						continue;
+					}
+					if let Some(end) = self.match_items(item_index + 1, instructions, pos + skip, captures, failed) {
+						return Some(end);
+					}
+					failed.entry(key).or_default().push(captures.clone());
+				}
+				None
+			}
+		}
+	}
+}
+
+impl From<InstrPattern> for PatternSequence {
+	#[inline]
+	fn from(pattern: InstrPattern) -> Self {
+		PatternSequence::new().then(pattern)
+	}
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use super::decode;
+use crate::analysis::*;
+use crate::FlowControl;
+
+#[test]
+fn empty() {
+	let cfg = ControlFlowGraph::new(&[]);
+	assert!(cfg.blocks().is_empty());
+	assert!(cfg.call_targets().is_empty());
+	assert_eq!(cfg.block_index(0), None);
+	assert_eq!(cfg.block_index_containing(0), None);
+}
+
+#[test]
+fn loop_and_call() {
+	// 0x1000: xor ecx,ecx
+	// 0x1002: call 1020h
+	// 0x1007: inc ecx
+	// 0x1009: cmp ecx,0Ah
+	// 0x100C: jb short 1002h
+	// 0x100E: jmp qword ptr [rax]
+	let instrs = decode(64, 0x1000, b"\x31\xC9\xE8\x19\x00\x00\x00\xFF\xC1\x83\xF9\x0A\x72\xF4\xFF\x20");
+	let cfg = ControlFlowGraph::new(&instrs);
+	let blocks = cfg.blocks();
+	assert_eq!(blocks.len(), 4);
+
+	assert_eq!((blocks[0].start, blocks[0].end), (0x1000, 0x1002));
+	assert_eq!(blocks[0].flow_control, FlowControl::Next);
+	assert_eq!(blocks[0].successors, [Edge { target: 0x1002, kind: EdgeKind::Fallthrough }]);
+
+	assert_eq!((blocks[1].start, blocks[1].end), (0x1002, 0x1007));
+	assert_eq!(blocks[1].flow_control, FlowControl::Call);
+	assert_eq!(blocks[1].successors, [Edge { target: 0x1007, kind: EdgeKind::Fallthrough }]);
+
+	assert_eq!((blocks[2].start, blocks[2].end), (0x1007, 0x100E));
+	assert_eq!(blocks[2].instructions, 2..5);
+	assert_eq!(blocks[2].flow_control, FlowControl::ConditionalBranch);
+	assert_eq!(
+		blocks[2].successors,
+		[Edge { target: 0x1002, kind: EdgeKind::ConditionalBranch }, Edge { target: 0x100E, kind: EdgeKind::Fallthrough }]
+	);
+
+	assert_eq!(blocks[3].flow_control, FlowControl::IndirectBranch);
+	assert!(blocks[3].successors.is_empty());
+
+	assert_eq!(cfg.call_targets(), [0x1020]);
+	assert_eq!(cfg.block_index(0x1002), Some(1));
+	assert_eq!(cfg.block_index(0x1009), None);
+	assert_eq!(cfg.block_index_containing(0x1009), Some(2));
+	assert_eq!(cfg.block_index_containing(0x1010), None);
+}
+
+#[test]
+fn extra_leaders() {
+	// 0x1000: nop
+	// 0x1001: nop
+	// 0x1002: ret
+	let instrs = decode(64, 0x1000, b"\x90\x90\xC3");
+	let cfg = ControlFlowGraph::with_leaders(&instrs, &[0x1001, 0x1005]);
+	let starts: Vec<u64> = cfg.blocks().iter().map(|b| b.start).collect();
+	assert_eq!(starts, [0x1000, 0x1001]);
+	assert_eq!(cfg.blocks()[0].successors, [Edge { target: 0x1001, kind: EdgeKind::Fallthrough }]);
+	assert_eq!(cfg.blocks()[1].flow_control, FlowControl::Return);
+}
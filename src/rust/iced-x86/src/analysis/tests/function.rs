@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::analysis::*;
+
+fn ranges(functions: &[FunctionBoundary]) -> Vec<(u64, u64)> {
+	functions.iter().map(|f| (f.start, f.end)).collect()
+}
+
+#[test]
+fn invalid_bitness() {
+	assert!(FunctionFinder::new(0).is_err());
+	assert!(FunctionFinder::new(8).is_err());
+}
+
+#[test]
+fn default_options() {
+	let finder = FunctionFinder::new(32).unwrap();
+	assert_eq!(finder.bitness(), 32);
+	assert_eq!(finder.alignment(), 16);
+	assert_eq!(finder.decoder_options(), 0);
+}
+
+#[test]
+fn call_target_and_tail_call() {
+	// 0x1000: call 1008h
+	// 0x1005: ret
+	// 0x1006: int3
+	// 0x1007: int3
+	// 0x1008: mov eax,1
+	// 0x100D: jmp short 1000h
+	let bytes = b"\xE8\x03\x00\x00\x00\xC3\xCC\xCC\xB8\x01\x00\x00\x00\xEB\xF1";
+	let functions = FunctionFinder::new(64).unwrap().find(bytes, 0x1000);
+	assert_eq!(ranges(&functions), [(0x1000, 0x1006), (0x1008, 0x100F)]);
+	assert_eq!(functions[0].sources, FunctionSources::TAIL_CALL_TARGET);
+	assert_eq!(functions[0].confidence, Confidence::Medium);
+	assert_eq!(functions[1].sources, FunctionSources::CALL_TARGET);
+	assert_eq!(functions[1].confidence, Confidence::High);
+}
+
+#[test]
+fn endbr_and_prologue() {
+	// 0x1000: endbr64
+	// 0x1004: push rbx
+	// 0x1005: sub rsp,20h
+	// 0x1009: add rsp,20h
+	// 0x100D: pop rbx
+	// 0x100E: ret
+	let bytes = b"\xF3\x0F\x1E\xFA\x53\x48\x83\xEC\x20\x48\x83\xC4\x20\x5B\xC3";
+	let functions = FunctionFinder::new(64).unwrap().find(bytes, 0x1000);
+	assert_eq!(ranges(&functions), [(0x1000, 0x100F)]);
+	assert_eq!(functions[0].sources, FunctionSources::ENDBR | FunctionSources::PROLOGUE);
+	assert_eq!(functions[0].confidence, Confidence::High);
+}
+
+#[test]
+fn padding_inside_function_is_ignored() {
+	// 0x1000: push ebp
+	// 0x1001: mov ebp,esp
+	// 0x1003: jmp short 1010h
+	// 0x1005: nop (x11)
+	// 0x1010: dec ecx
+	// 0x1011: jne short 1010h
+	// 0x1013: pop ebp
+	// 0x1014: ret
+	// 0x1015: int3 (x11)
+	// 0x1020: xor eax,eax
+	// 0x1022: ret
+	let mut bytes = b"\x55\x89\xE5\xEB\x0B".to_vec();
+	bytes.extend_from_slice(&[0x90; 11]);
+	bytes.extend_from_slice(b"\x49\x75\xFD\x5D\xC3");
+	bytes.extend_from_slice(&[0xCC; 11]);
+	bytes.extend_from_slice(b"\x31\xC0\xC3");
+	let functions = FunctionFinder::new(32).unwrap().find(&bytes, 0x1000);
+	assert_eq!(ranges(&functions), [(0x1000, 0x1015), (0x1020, 0x1023)]);
+	assert_eq!(functions[0].confidence, Confidence::Medium);
+	assert_eq!(functions[1].sources, FunctionSources::PADDING);
+	assert_eq!(functions[1].confidence, Confidence::Low);
+}
+
+#[test]
+fn unwind_data() {
+	// 0x1000: xor eax,eax
+	// 0x1002: ret
+	// 0x1003: mov eax,1 (only decoded because of the unwind data)
+	// 0x1008: ret
+	let bytes = b"\x31\xC0\xC3\xB8\x01\x00\x00\x00\xC3";
+	let mut finder = FunctionFinder::new(64).unwrap();
+	finder.add_entry_point(0x1000);
+	finder.add_unwind_function(0x1003, 0x1010);
+	let functions = finder.find(bytes, 0x1000);
+	assert_eq!(ranges(&functions), [(0x1000, 0x1003), (0x1003, 0x1009)]);
+	assert_eq!(functions[1].sources, FunctionSources::UNWIND);
+	assert_eq!(functions[1].confidence, Confidence::High);
+}
+
+#[test]
+fn resync_at_entry_point() {
+	// 0x1000: db 0B8h (mov eax,imm32 if decoded linearly)
+	// 0x1001: xor eax,eax
+	// 0x1003: ret
+	let bytes = b"\xB8\x31\xC0\xC3";
+	let mut finder = FunctionFinder::new(64).unwrap();
+	finder.add_entry_point(0x1001);
+	let functions = finder.find(bytes, 0x1000);
+	assert_eq!(ranges(&functions), [(0x1001, 0x1004)]);
+	assert_eq!(functions[0].sources, FunctionSources::ENTRY_POINT);
+}
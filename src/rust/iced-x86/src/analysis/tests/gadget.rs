@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::analysis::*;
+use crate::Register;
+
+#[test]
+fn invalid_bitness() {
+	assert!(GadgetFinder::new(0).is_err());
+	assert!(GadgetFinder::new(128).is_err());
+}
+
+#[test]
+fn default_options() {
+	let finder = GadgetFinder::new(64).unwrap();
+	assert_eq!(finder.bitness(), 64);
+	assert_eq!(finder.kinds(), GadgetKinds::ALL);
+	assert_eq!(finder.max_instructions(), 6);
+	assert_eq!(finder.max_bytes(), 30);
+}
+
+#[test]
+fn find_ret_gadgets() {
+	// pop rdi
+	// pop rsi
+	// ret
+	// add rsp,18h
+	// ret
+	let bytes = b"\x5F\x5E\xC3\x48\x83\xC4\x18\xC3";
+	let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+	let g = gadgets.iter().find(|g| g.address() == 0x1000).unwrap();
+	assert_eq!(g.kind, GadgetKind::Return);
+	assert_eq!(g.instructions.len(), 3);
+	assert_eq!(g.effects.popped_registers, [Register::RDI, Register::RSI]);
+	assert_eq!(g.effects.clobbered_registers, [Register::RDI, Register::RSI]);
+	assert_eq!(g.effects.stack_delta, 24);
+	assert!(!g.effects.stack_pivot);
+	assert!(!g.effects.reads_memory);
+	assert!(!g.effects.writes_memory);
+
+	let g = gadgets.iter().find(|g| g.address() == 0x1003).unwrap();
+	assert_eq!(g.bytes, b"\x48\x83\xC4\x18\xC3");
+	assert_eq!(g.effects.stack_delta, 0x18 + 8);
+	assert!(!g.effects.stack_pivot);
+
+	let g = gadgets.iter().find(|g| g.bytes == b"\xC3").unwrap();
+	assert_eq!(g.addresses, [0x1002, 0x1007]);
+
+	for w in gadgets.windows(2) {
+		assert!(w[0].address() <= w[1].address());
+	}
+}
+
+#[test]
+fn find_jop_cop_syscall_gadgets() {
+	// mov rax,[rcx]
+	// jmp rax
+	// call qword ptr [rbx]
+	// syscall
+	let bytes = b"\x48\x8B\x01\xFF\xE0\xFF\x13\x0F\x05";
+	let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+	let g = gadgets.iter().find(|g| g.address() == 0x1000).unwrap();
+	assert_eq!(g.kind, GadgetKind::IndirectJump);
+	assert_eq!(g.effects.clobbered_registers, [Register::RAX]);
+	assert!(g.effects.reads_memory);
+	assert!(!g.effects.writes_memory);
+	assert!(gadgets.iter().any(|g| g.kind == GadgetKind::IndirectCall && g.address() == 0x1005));
+	assert!(gadgets.iter().any(|g| g.kind == GadgetKind::Syscall && g.address() == 0x1007));
+
+	let mut finder = GadgetFinder::new(64).unwrap();
+	finder.set_kinds(GadgetKinds::SYSCALL);
+	let gadgets = finder.find(bytes, 0x1000);
+	assert!(gadgets.iter().all(|g| g.kind == GadgetKind::Syscall));
+	finder.set_kinds(GadgetKinds::NONE);
+	assert!(finder.find(bytes, 0x1000).is_empty());
+}
+
+#[test]
+fn memory_effects() {
+	// mov rax,[rsp+8]
+	// ret
+	// movsb
+	// ret
+	// mov [rcx],eax
+	// ret
+	let bytes = b"\x48\x8B\x44\x24\x08\xC3\xA4\xC3\x89\x01\xC3";
+	let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+	let g = gadgets.iter().find(|g| g.address() == 0x1000).unwrap();
+	assert!(!g.effects.reads_memory);
+	assert!(!g.effects.writes_memory);
+	let g = gadgets.iter().find(|g| g.address() == 0x1006).unwrap();
+	assert!(g.effects.reads_memory);
+	assert!(g.effects.writes_memory);
+	let g = gadgets.iter().find(|g| g.address() == 0x1008).unwrap();
+	assert!(!g.effects.reads_memory);
+	assert!(g.effects.writes_memory);
+}
+
+#[test]
+fn int80_gadget_32() {
+	// pop ebx
+	// int 80h
+	// int 3
+	let bytes = b"\x5B\xCD\x80\xCC";
+	let gadgets = GadgetFinder::new(32).unwrap().find(bytes, 0x1000);
+	let g = gadgets.iter().find(|g| g.address() == 0x1000).unwrap();
+	assert_eq!(g.kind, GadgetKind::Syscall);
+	assert_eq!(g.effects.popped_registers, [Register::EBX]);
+	assert_eq!(g.effects.stack_delta, 4);
+	assert_eq!(gadgets.len(), 2);
+}
+
+#[test]
+fn stack_pivot() {
+	// xchg rsp,rax
+	// ret
+	// leave
+	// ret
+	let bytes = b"\x48\x94\xC3\xC9\xC3";
+	let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+	assert!(gadgets.iter().find(|g| g.address() == 0x1000).unwrap().effects.stack_pivot);
+	assert!(gadgets.iter().find(|g| g.address() == 0x1003).unwrap().effects.stack_pivot);
+	assert!(!gadgets.iter().find(|g| g.address() == 0x1002).unwrap().effects.stack_pivot);
+}
+
+#[test]
+fn stack_pivot_delta() {
+	// pop rsp
+	// ret
+	let bytes = b"\x5C\xC3";
+	let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+	let g = gadgets.iter().find(|g| g.address() == 0x1000).unwrap();
+	assert!(g.effects.stack_pivot);
+	// Only the RET
+	assert_eq!(g.effects.stack_delta, 8);
+
+	// leave
+	// ret
+	let bytes = b"\xC9\xC3";
+	let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+	let g = gadgets.iter().find(|g| g.address() == 0x1000).unwrap();
+	assert!(g.effects.stack_pivot);
+	assert_eq!(g.effects.stack_delta, 8);
+}
+
+#[test]
+fn chain_stops_at_control_flow_and_limits() {
+	// jmp short $+2
+	// pop rdi
+	// ret
+	let bytes = b"\xEB\x00\x5F\xC3";
+	let gadgets = GadgetFinder::new(64).unwrap().find(bytes, 0x1000);
+	assert!(gadgets.iter().all(|g| g.address() >= 0x1001));
+
+	// 5x nop
+	// ret
+	let bytes = b"\x90\x90\x90\x90\x90\xC3";
+	let mut finder = GadgetFinder::new(64).unwrap();
+	finder.set_max_instructions(3);
+	let gadgets = finder.find(bytes, 0x1000);
+	assert_eq!(gadgets.iter().map(Gadget::address).min(), Some(0x1003));
+	finder.set_max_instructions(10);
+	finder.set_max_bytes(2);
+	let gadgets = finder.find(bytes, 0x1000);
+	assert_eq!(gadgets.iter().map(Gadget::address).min(), Some(0x1004));
+	finder.set_max_instructions(0);
+	assert!(finder.find(bytes, 0x1000).is_empty());
+}
+
+#[test]
+#[cfg(feature = "nasm")]
+fn format_gadget() {
+	use crate::NasmFormatter;
+	// pop rdi
+	// ret
+	let gadgets = GadgetFinder::new(64).unwrap().find(b"\x5F\xC3", 0x1000);
+	let mut formatter = NasmFormatter::new();
+	assert_eq!(gadgets[0].format(&mut formatter), "pop rdi ; ret");
+	assert_eq!(gadgets[1].format(&mut formatter), "ret");
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use super::decode;
+use crate::analysis::*;
+use alloc::vec::Vec;
+
+fn missing(report: &IbtReport) -> Vec<u64> {
+	report.missing_endbr().map(|target| target.address).collect()
+}
+
+#[test]
+fn invalid_bitness() {
+	assert!(IbtAuditor::new(16, &[], 0).is_err());
+	assert!(IbtAuditor::new(0, &[], 0).is_err());
+	assert_eq!(IbtAuditor::new(32, &[], 0).unwrap().bitness(), 32);
+}
+
+#[test]
+fn empty() {
+	let report = IbtAuditor::new(64, &[], 0).unwrap().audit(&[]);
+	assert!(report.targets.is_empty());
+	assert!(report.notrack_branches.is_empty());
+}
+
+#[test]
+fn address_taken_32() {
+	// 0x1000: push 1010h
+	// 0x1005: mov ecx,1014h
+	// 0x100A: lea edx,[1018h]
+	// 0x1010: endbr32
+	// 0x1014: ret
+	// 0x1015: int3 (x3)
+	// 0x1018: ret
+	let bytes = b"\x68\x10\x10\x00\x00\xB9\x14\x10\x00\x00\x8D\x15\x18\x10\x00\x00\xF3\x0F\x1E\xFB\xC3\xCC\xCC\xCC\xC3";
+	let instrs = decode(32, 0x1000, bytes);
+	let report = IbtAuditor::new(32, bytes, 0x1000).unwrap().audit(&instrs);
+	assert_eq!(
+		report.targets,
+		[
+			IndirectTarget { address: 0x1010, sources: IndirectTargetSources::ADDRESS_TAKEN, has_endbr: true },
+			IndirectTarget { address: 0x1014, sources: IndirectTargetSources::ADDRESS_TAKEN, has_endbr: false },
+			IndirectTarget { address: 0x1018, sources: IndirectTargetSources::ADDRESS_TAKEN, has_endbr: false },
+		]
+	);
+	assert_eq!(missing(&report), [0x1014, 0x1018]);
+}
+
+#[test]
+fn endbr32_in_64_bit_code_is_missing() {
+	// 0x1000: lea rax,[1007h]
+	// 0x1007: endbr32
+	let bytes = b"\x48\x8D\x05\x00\x00\x00\x00\xF3\x0F\x1E\xFB";
+	let instrs = decode(64, 0x1000, bytes);
+	let report = IbtAuditor::new(64, bytes, 0x1000).unwrap().audit(&instrs);
+	assert_eq!(missing(&report), [0x1007]);
+}
+
+#[test]
+fn addresses_outside_of_code_are_ignored() {
+	// 0x1000: lea rax,[2000h]
+	// 0x1007: mov ecx,12345678h
+	// 0x100C: ret
+	let bytes = b"\x48\x8D\x05\xF9\x0F\x00\x00\xB9\x78\x56\x34\x12\xC3";
+	let instrs = decode(64, 0x1000, bytes);
+	let report = IbtAuditor::new(64, bytes, 0x1000).unwrap().audit(&instrs);
+	assert!(report.targets.is_empty());
+}
+
+#[test]
+fn data_pointers_and_exports() {
+	// 0x1000: endbr64
+	// 0x1004: ret
+	// 0x1005: ret
+	// 0x1006: ret
+	// 0x1007: int3
+	// 0x1008: data: 0x1000, 0x1005, 0x1006 (unaligned, ignored)
+	let mut image = b"\xF3\x0F\x1E\xFA\xC3\xC3\xC3\xCC".to_vec();
+	image.extend_from_slice(&0x1000u64.to_le_bytes());
+	image.extend_from_slice(&0x1005u64.to_le_bytes());
+	image.push(0);
+	image.extend_from_slice(&0x1006u64.to_le_bytes());
+	let instrs = decode(64, 0x1000, &image[..8]);
+	let mut auditor = IbtAuditor::new(64, &image, 0x1000).unwrap();
+	auditor.add_data_section(0x1008, image.len() as u64 - 8);
+	auditor.add_export(0x1000);
+	auditor.add_export(0x1004);
+	let report = auditor.audit(&instrs);
+	assert_eq!(
+		report.targets,
+		[
+			IndirectTarget { address: 0x1000, sources: IndirectTargetSources::DATA_POINTER | IndirectTargetSources::EXPORT, has_endbr: true },
+			IndirectTarget { address: 0x1004, sources: IndirectTargetSources::EXPORT, has_endbr: false },
+			IndirectTarget { address: 0x1005, sources: IndirectTargetSources::DATA_POINTER, has_endbr: false },
+		]
+	);
+}
+
+#[test]
+fn target_inside_instruction() {
+	// 0x1000: lea rax,[1002h]
+	let bytes = b"\x48\x8D\x05\xFB\xFF\xFF\xFF";
+	let instrs = decode(64, 0x1000, bytes);
+	let report = IbtAuditor::new(64, bytes, 0x1000).unwrap().audit(&instrs);
+	assert_eq!(missing(&report), [0x1002]);
+}
+
+#[test]
+fn jump_table() {
+	// 0x1000: cmp eax,1
+	// 0x1003: ja short 1010h
+	// 0x1005: jmp qword ptr [rax*8+1100h]
+	// 0x100C: int3 (x4)
+	// 0x1010: ret
+	// 0x1011: endbr64
+	// 0x1015: ret
+	let mut code = b"\x83\xF8\x01\x77\x0B\xFF\x24\xC5\x00\x11\x00\x00\xCC\xCC\xCC\xCC\xC3\xF3\x0F\x1E\xFA\xC3".to_vec();
+	let mut image = code.clone();
+	image.resize(0x110, 0);
+	image[0x100..0x108].copy_from_slice(&0x1010u64.to_le_bytes());
+	image[0x108..0x110].copy_from_slice(&0x1011u64.to_le_bytes());
+	let instrs = decode(64, 0x1000, &code);
+	let report = IbtAuditor::new(64, &image, 0x1000).unwrap().audit(&instrs);
+	assert_eq!(missing(&report), [0x1010]);
+	assert!(report.targets.iter().all(|target| target.sources == IndirectTargetSources::JUMP_TABLE));
+	assert!(report.notrack_branches.is_empty());
+
+	// notrack jmp qword ptr [rax*8+1100h]
+	code.insert(5, 0x3E);
+	code[4] += 1;
+	image[..code.len()].copy_from_slice(&code);
+	let instrs = decode(64, 0x1000, &code);
+	let report = IbtAuditor::new(64, &image, 0x1000).unwrap().audit(&instrs);
+	assert!(report.targets.is_empty());
+	assert_eq!(report.notrack_branches, [0x1005]);
+}
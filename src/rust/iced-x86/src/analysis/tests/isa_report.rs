@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::analysis::tests::decode;
+use crate::analysis::*;
+use crate::{CpuFeatureProfile, CpuidFeature};
+
+#[test]
+fn empty() {
+	let report = CpuFeatureReport::new(&[]);
+	assert!(report.features.is_empty());
+	assert_eq!(report.instruction_count, 0);
+	assert_eq!(report.min_level, IsaLevel::V1);
+	assert_eq!(report.os_features, OsFeatures::NONE);
+}
+
+#[test]
+fn baseline() {
+	// push rbp
+	// mov rbp,rsp
+	// movaps xmm0,xmm1
+	// pop rbp
+	// ret
+	let instrs = decode(64, 0x1000, b"\x55\x48\x89\xE5\x0F\x28\xC1\x5D\xC3");
+	let report = CpuFeatureReport::new(&instrs);
+	assert_eq!(report.instruction_count, 5);
+	assert_eq!(report.min_level, IsaLevel::V1);
+	assert!(report.extra_features.is_empty());
+	assert!(report.is_supported_by(&IsaLevel::V1.profile()));
+	let sse = report.usage(CpuidFeature::SSE).unwrap();
+	assert_eq!((sse.count, sse.first_ip), (1, 0x1004));
+	assert_eq!(report.os_features, OsFeatures::NONE);
+}
+
+#[test]
+fn levels() {
+	// popcnt eax,ecx
+	let v2 = decode(64, 0x1000, b"\xF3\x0F\xB8\xC1");
+	assert_eq!(CpuFeatureReport::new(&v2).min_level, IsaLevel::V2);
+	// andn eax,ebx,ecx
+	let v3 = decode(64, 0x1000, b"\xC4\xE2\x60\xF2\xC1");
+	let report = CpuFeatureReport::new(&v3);
+	assert_eq!(report.min_level, IsaLevel::V3);
+	// BMI1 doesn't need OS support
+	assert_eq!(report.os_features, OsFeatures::NONE);
+	// vpaddd zmm1,zmm2,zmm3
+	let v4 = decode(64, 0x1000, b"\x62\xF1\x6D\x48\xFE\xCB");
+	let report = CpuFeatureReport::new(&v4);
+	assert_eq!(report.min_level, IsaLevel::V4);
+	assert_eq!(report.os_features, OsFeatures::AVX_STATE | OsFeatures::AVX512_STATE);
+	assert_eq!(IsaLevel::V4.to_string(), "x86-64-v4");
+	assert_eq!(CpuFeatureProfile::from_name(IsaLevel::V2.name()), Some(IsaLevel::V2.profile()));
+}
+
+#[test]
+fn unsupported_features() {
+	// vpaddd ymm1,ymm2,ymm3
+	// aesenc xmm1,xmm2
+	// vpaddd ymm1,ymm2,ymm3
+	// vzeroupper
+	let instrs = decode(64, 0x1000, b"\xC5\xED\xFE\xCB\x66\x0F\x38\xDC\xCA\xC5\xED\xFE\xCB\xC5\xF8\x77");
+	let report = CpuFeatureReport::new(&instrs);
+	assert_eq!(report.min_level, IsaLevel::V3);
+	assert_eq!(report.extra_features, [CpuidFeature::AES]);
+	let avx2 = report.usage(CpuidFeature::AVX2).unwrap();
+	assert_eq!((avx2.count, avx2.first_ip), (2, 0x1000));
+	assert_eq!(report.usage(CpuidFeature::AVX).unwrap().first_ip, 0x100D);
+	let v2 = CpuFeatureProfile::x86_64_v2();
+	let unsupported: Vec<CpuidFeature> = report.unsupported_features(&v2).map(|usage| usage.feature).collect();
+	assert_eq!(unsupported, [CpuidFeature::AES, CpuidFeature::AVX, CpuidFeature::AVX2]);
+	assert!(report.is_supported_by(&CpuFeatureProfile::from_name("haswell").unwrap()));
+	assert_eq!(report.os_features, OsFeatures::AVX_STATE);
+}
+
+#[test]
+fn os_features() {
+	// tilezero tmm0
+	// rdpkru
+	// rdfsbase rax
+	let instrs = decode(64, 0x1000, b"\xC4\xE2\x7B\x49\xC0\x0F\x01\xEE\xF3\x48\x0F\xAE\xC0");
+	let report = CpuFeatureReport::new(&instrs);
+	assert_eq!(report.os_features, OsFeatures::AMX_TILE_STATE | OsFeatures::PKU | OsFeatures::FSGSBASE);
+	assert_eq!(report.extra_features, [CpuidFeature::FSGSBASE, CpuidFeature::PKU, CpuidFeature::AMX_TILE]);
+}
+
+#[test]
+fn invalid_instructions_are_ignored() {
+	let instrs = decode(64, 0x1000, b"\xF0\x01\xCE\x90");
+	let report = CpuFeatureReport::new(&instrs);
+	assert_eq!(report.instruction_count, 1);
+	assert_eq!(report.invalid_count, 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize() {
+	// andn eax,ebx,ecx
+	let instrs = decode(64, 0x1000, b"\xC4\xE2\x60\xF2\xC1");
+	let json = serde_json::to_string(&CpuFeatureReport::new(&instrs)).unwrap();
+	assert_eq!(
+		json,
+		r#"{"features":[{"feature":"BMI1","count":1,"first_ip":4096}],"instruction_count":1,"invalid_count":0,"min_level":"x86-64-v3","extra_features":[],"os_features":0}"#
+	);
+	let bin = bincode::serialize(&CpuFeatureReport::new(&instrs)).unwrap();
+	assert!(!bin.is_empty());
+}
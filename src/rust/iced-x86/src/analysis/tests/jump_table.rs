@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use super::decode;
+use crate::analysis::*;
+use crate::Register;
+
+#[test]
+fn not_a_jump_table() {
+	// 0x1000: jmp rax
+	// 0x1002: jmp qword ptr [rax]
+	// 0x1005: ret
+	let instrs = decode(64, 0x1000, b"\xFF\xE0\xFF\x20\xC3");
+	let resolver = JumpTableResolver::new(&[], 0);
+	assert!(resolver.resolve_all(&instrs).is_empty());
+	assert!(resolver.resolve(&instrs, 10).is_none());
+}
+
+#[test]
+fn absolute_32() {
+	// 0x1000: mov eax,[esp+4]
+	// 0x1004: cmp eax,1
+	// 0x1007: ja short 1050h
+	// 0x1009: jmp dword ptr [eax*4+1100h]
+	let instrs = decode(32, 0x1000, b"\x8B\x44\x24\x04\x83\xF8\x01\x77\x47\xFF\x24\x85\x00\x11\x00\x00");
+	let mut image = [0u8; 0x108];
+	image[0x100..0x104].copy_from_slice(&0x1020u32.to_le_bytes());
+	image[0x104..0x108].copy_from_slice(&0x1030u32.to_le_bytes());
+	let tables = JumpTableResolver::new(&image, 0x1000).resolve_all(&instrs);
+	assert_eq!(tables.len(), 1);
+	let table = &tables[0];
+	assert_eq!(table.jump_address, 0x1009);
+	assert_eq!(table.kind, JumpTableKind::Absolute);
+	assert_eq!(table.table_address, 0x1100);
+	assert_eq!(table.entry_size, 4);
+	assert_eq!(table.base, 0);
+	assert_eq!(table.index_register, Register::EAX);
+	assert_eq!(table.max_index, 1);
+	assert_eq!(table.default_target, Some(0x1050));
+	assert_eq!(table.targets, [0x1020, 0x1030]);
+}
+
+#[test]
+fn rip_relative_64() {
+	// 0x1000: cmp edi,3
+	// 0x1003: jae short 1040h
+	// 0x1005: mov eax,edi
+	// 0x1007: lea rdx,[1100h]
+	// 0x100E: movsxd rax,dword ptr [rdx+rax*4]
+	// 0x1012: add rax,rdx
+	// 0x1015: jmp rax
+	let instrs = decode(64, 0x1000, b"\x83\xFF\x03\x73\x3B\x89\xF8\x48\x8D\x15\xF2\x00\x00\x00\x48\x63\x04\x82\x48\x01\xD0\xFF\xE0");
+	let mut image = [0u8; 0x10C];
+	image[0x100..0x104].copy_from_slice(&(-0xE0i32).to_le_bytes());
+	image[0x104..0x108].copy_from_slice(&(-0xD0i32).to_le_bytes());
+	image[0x108..0x10C].copy_from_slice(&0x10i32.to_le_bytes());
+	let table = JumpTableResolver::new(&image, 0x1000).resolve(&instrs, 6).unwrap();
+	assert_eq!(table.kind, JumpTableKind::Relative);
+	assert_eq!(table.table_address, 0x1100);
+	assert_eq!(table.entry_size, 4);
+	assert_eq!(table.base, 0x1100);
+	assert_eq!(table.index_register, Register::RAX);
+	assert_eq!(table.max_index, 2);
+	assert_eq!(table.default_target, Some(0x1040));
+	assert_eq!(table.targets, [0x1020, 0x1030, 0x1110]);
+}
+
+#[test]
+fn image_base_relative_64() {
+	// 0x1000: and ecx,3
+	// 0x1003: lea r8,[0]
+	// 0x100A: mov eax,[r8+rcx*4+1100h]
+	// 0x1012: add rax,r8
+	// 0x1015: jmp rax
+	let instrs = decode(64, 0x1000, b"\x83\xE1\x03\x4C\x8D\x05\xF6\xEF\xFF\xFF\x41\x8B\x84\x88\x00\x11\x00\x00\x4C\x01\xC0\xFF\xE0");
+	let mut image = [0u8; 0x1110];
+	for (i, rva) in [0x1020u32, 0x1030, 0x1040, 0x1050].iter().enumerate() {
+		image[0x1100 + i * 4..0x1104 + i * 4].copy_from_slice(&rva.to_le_bytes());
+	}
+	let table = JumpTableResolver::new(&image, 0).resolve(&instrs, 4).unwrap();
+	assert_eq!(table.kind, JumpTableKind::Relative);
+	assert_eq!(table.table_address, 0x1100);
+	assert_eq!(table.base, 0);
+	assert_eq!(table.index_register, Register::RCX);
+	assert_eq!(table.max_index, 3);
+	assert_eq!(table.default_target, None);
+	assert_eq!(table.targets, [0x1020, 0x1030, 0x1040, 0x1050]);
+}
+
+#[test]
+fn table_outside_image_or_too_big() {
+	// 0x1000: cmp eax,2
+	// 0x1003: ja short 1020h
+	// 0x1005: jmp qword ptr [rax*8+2000h]
+	let instrs = decode(64, 0x1000, b"\x83\xF8\x02\x77\x1B\xFF\x24\xC5\x00\x20\x00\x00");
+	let image = [0u8; 0x18];
+	assert!(JumpTableResolver::new(&image, 0x2001).resolve(&instrs, 2).is_none());
+	assert!(JumpTableResolver::new(&image[..0x17], 0x2000).resolve(&instrs, 2).is_none());
+	let mut resolver = JumpTableResolver::new(&image, 0x2000);
+	assert_eq!(resolver.max_entries(), 1024);
+	assert!(resolver.resolve(&instrs, 2).is_some());
+	resolver.set_max_entries(2);
+	assert!(resolver.resolve(&instrs, 2).is_none());
+}
+
+#[test]
+fn no_bounds_check() {
+	// 0x1000: jmp qword ptr [rax*8+2000h]
+	let instrs = decode(64, 0x1000, b"\xFF\x24\xC5\x00\x20\x00\x00");
+	let image = [0u8; 0x100];
+	assert!(JumpTableResolver::new(&image, 0x2000).resolve(&instrs, 0).is_none());
+}
+
+#[test]
+fn huge_bound() {
+	let image = [0u8; 0x100];
+	let mut resolver = JumpTableResolver::new(&image, 0x2000);
+	resolver.set_max_entries(u64::MAX);
+
+	// 0x1000: cmp rax,0FFFFFFFFFFFFFFFFh
+	// 0x1004: ja short 1020h
+	// 0x1006: jmp qword ptr [rax*8+2000h]
+	let instrs = decode(64, 0x1000, b"\x48\x83\xF8\xFF\x77\x1A\xFF\x24\xC5\x00\x20\x00\x00");
+	assert!(resolver.resolve(&instrs, 2).is_none());
+
+	// 0x1000: cmp eax,0FFFFFFFFh
+	// 0x1003: ja short 1020h
+	// 0x1005: jmp qword ptr [rax*8+2000h]
+	let instrs = decode(64, 0x1000, b"\x83\xF8\xFF\x77\x1B\xFF\x24\xC5\x00\x20\x00\x00");
+	assert!(resolver.resolve(&instrs, 2).is_none());
+}
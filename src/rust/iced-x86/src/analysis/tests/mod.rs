@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+mod cfg;
+mod function;
+mod gadget;
+mod ibt;
+mod isa_report;
+mod jump_table;
+mod pattern;
+
+use crate::{Decoder, DecoderOptions, Instruction};
+use alloc::vec::Vec;
+
+fn decode(bitness: u32, ip: u64, bytes: &[u8]) -> Vec<Instruction> {
+	Decoder::with_ip(bitness, bytes, ip, DecoderOptions::NONE).into_iter().collect()
+}
@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::analysis::tests::decode;
+use crate::analysis::*;
+use crate::{MemorySize, Mnemonic, Register};
+
+#[test]
+fn register_class() {
+	assert!(RegisterClass::Gpr.contains(Register::AH));
+	assert!(RegisterClass::Gpr32.contains(Register::R15D));
+	assert!(!RegisterClass::Gpr32.contains(Register::R15));
+	assert!(RegisterClass::Segment.contains(Register::FS));
+	assert!(RegisterClass::K.contains(Register::K7));
+	assert!(!RegisterClass::Vector.contains(Register::None));
+}
+
+#[test]
+fn register_pattern() {
+	assert!(RegisterPattern::Any.matches(Register::EAX));
+	assert!(!RegisterPattern::Any.matches(Register::None));
+	assert!(RegisterPattern::None.matches(Register::None));
+	assert!(!RegisterPattern::None.matches(Register::EAX));
+	assert!(RegisterPattern::OneOf(vec![Register::RAX, Register::RCX]).matches(Register::RCX));
+	assert!(!RegisterPattern::OneOf(vec![Register::RAX, Register::RCX]).matches(Register::RDX));
+}
+
+#[test]
+fn match_operands() {
+	// mov eax,12345678h
+	// mov rax,[rcx+rdx*8+10h]
+	// mov [rbx-8],ecx
+	let instrs = decode(64, 0x1000, b"\xB8\x78\x56\x34\x12\x48\x8B\x44\xD1\x10\x89\x4B\xF8");
+
+	let mov_imm = InstrPattern::new(Mnemonic::Mov).operand(RegisterClass::Gpr32).operand(OperandPattern::immediate_range(0, 0x1234_5678));
+	assert!(mov_imm.matches(&instrs[0]));
+	let mov_imm = InstrPattern::new(Mnemonic::Mov).operand(RegisterClass::Gpr32).operand(OperandPattern::immediate_range(0, 0x1234_5677));
+	assert!(!mov_imm.matches(&instrs[0]));
+	let mov_imm = InstrPattern::new(Mnemonic::Mov).operand(RegisterClass::Gpr64).operand(OperandPattern::immediate());
+	assert!(!mov_imm.matches(&instrs[0]));
+
+	let load = InstrPattern::new(Mnemonic::Mov).operand(OperandPattern::any_register().capture("dst")).operand(
+		MemoryPattern::new()
+			.base(RegisterClass::Gpr64)
+			.index(RegisterPattern::Any)
+			.scale(8)
+			.size(MemorySize::UInt64)
+			.capture_base("base")
+			.capture_index("index")
+			.capture_displacement("disp"),
+	);
+	let captures = load.match_instruction(&instrs[1]).unwrap();
+	assert_eq!(captures.len(), 4);
+	assert_eq!(captures.register("dst"), Some(Register::RAX));
+	assert_eq!(captures.register("base"), Some(Register::RCX));
+	assert_eq!(captures.register("index"), Some(Register::RDX));
+	assert_eq!(captures.value("disp"), Some(0x10));
+	assert_eq!(captures.value("dst"), None);
+	assert!(!load.matches(&instrs[0]));
+	assert!(!load.matches(&instrs[2]));
+
+	let store = InstrPattern::new(Mnemonic::Mov)
+		.operand(MemoryPattern::new().index(RegisterPattern::None).displacement(-8, -8).capture_displacement("disp"))
+		.operand(OperandPattern::any());
+	let captures = store.match_instruction(&instrs[2]).unwrap();
+	assert_eq!(captures.value("disp"), Some(-8i64 as u64));
+	assert!(!store.matches(&instrs[1]));
+}
+
+#[test]
+fn immediate_is_sign_extended() {
+	// cmp al,0FFh
+	// add rax,0FFFFFFFFFFFFFFFFh
+	// mov ax,0FFFFh
+	// mov eax,0FFFFFFFFh
+	let instrs = decode(64, 0x1000, b"\x3C\xFF\x48\x83\xC0\xFF\x66\xB8\xFF\xFF\xB8\xFF\xFF\xFF\xFF");
+	let minus_one = InstrPattern::any().operand(OperandPattern::any_register()).operand(OperandPattern::immediate_range(-1, -1).capture("imm"));
+	for instr in &instrs {
+		assert!(minus_one.matches(instr));
+	}
+	assert_eq!(minus_one.match_instruction(&instrs[0]).unwrap().value("imm"), Some(0xFF));
+	let positive = InstrPattern::any().operand(OperandPattern::any_register()).operand(OperandPattern::immediate_range(0, i64::MAX));
+	assert!(instrs.iter().all(|instr| !positive.matches(instr)));
+}
+
+#[test]
+fn match_operand_count() {
+	// ret
+	// ret 8
+	let instrs = decode(64, 0x1000, b"\xC3\xC2\x08\x00");
+	assert!(InstrPattern::new(Mnemonic::Ret).matches(&instrs[0]));
+	assert!(InstrPattern::new(Mnemonic::Ret).matches(&instrs[1]));
+	assert!(InstrPattern::new(Mnemonic::Ret).no_operands().matches(&instrs[0]));
+	assert!(!InstrPattern::new(Mnemonic::Ret).no_operands().matches(&instrs[1]));
+	assert!(InstrPattern::with_mnemonics(&[Mnemonic::Retf, Mnemonic::Ret]).operand(OperandPattern::immediate()).matches(&instrs[1]));
+	assert!(InstrPattern::any().matches(&instrs[0]));
+}
+
+#[test]
+fn match_ip_rel_and_branch() {
+	// lea rcx,[rip+100h]
+	// call 0x2000
+	let instrs = decode(64, 0x1000, b"\x48\x8D\x0D\x00\x01\x00\x00\xE8\xF4\x0F\x00\x00");
+	let lea = InstrPattern::new(Mnemonic::Lea)
+		.operand(OperandPattern::any_register())
+		.operand(MemoryPattern::new().base(Register::RIP).displacement(0x1000, 0x2000).capture_displacement("addr"));
+	assert_eq!(lea.match_instruction(&instrs[0]).unwrap().value("addr"), Some(0x1107));
+	let call = InstrPattern::new(Mnemonic::Call).operand(OperandPattern::branch_target().capture("target"));
+	let captures = call.match_instruction(&instrs[1]).unwrap();
+	assert_eq!(captures.get("target"), Some(CapturedValue::BranchTarget(0x2000)));
+}
+
+#[test]
+fn match_32bit_displacement() {
+	// mov eax,[ebp-4]
+	let instrs = decode(32, 0x1000, b"\x8B\x45\xFC");
+	let pattern = InstrPattern::new(Mnemonic::Mov).operand(Register::EAX).operand(MemoryPattern::new().base(Register::EBP).displacement(-0x10, -1));
+	assert!(pattern.matches(&instrs[0]));
+}
+
+#[test]
+fn back_reference() {
+	// xor eax,eax
+	// xor eax,ecx
+	let instrs = decode(64, 0x1000, b"\x31\xC0\x31\xC8");
+	let zero = InstrPattern::new(Mnemonic::Xor)
+		.operand(OperandPattern::any_register().capture("reg"))
+		.operand(OperandPattern::any_register().capture("reg"));
+	assert_eq!(zero.match_instruction(&instrs[0]).unwrap().register("reg"), Some(Register::EAX));
+	assert!(!zero.matches(&instrs[1]));
+}
+
+#[test]
+fn sequence_optional_and_gap() {
+	// cmp ecx,5
+	// ja 0x1020
+	// mov eax,ecx
+	// nop
+	// jmp qword ptr [rax*8+2000h]
+	let instrs = decode(64, 0x1000, b"\x83\xF9\x05\x77\x1B\x89\xC8\x90\xFF\x24\xC5\x00\x20\x00\x00");
+	let jump_table = PatternSequence::new()
+		.then(
+			InstrPattern::new(Mnemonic::Cmp)
+				.operand(OperandPattern::any_register().capture("idx"))
+				.operand(OperandPattern::immediate().capture("max")),
+		)
+		.then(InstrPattern::new(Mnemonic::Ja).operand(OperandPattern::branch_target().capture("default")))
+		.optional(InstrPattern::new(Mnemonic::Movsxd))
+		.gap(0, 2)
+		.then(InstrPattern::new(Mnemonic::Jmp).operand(MemoryPattern::new().base(RegisterPattern::None).scale(8).capture_displacement("table")));
+	let m = jump_table.find(&instrs).unwrap();
+	assert_eq!((m.start, m.end), (0, 5));
+	assert_eq!((m.ip, m.end_ip), (0x1000, 0x100F));
+	assert_eq!(m.captures.register("idx"), Some(Register::ECX));
+	assert_eq!(m.captures.value("max"), Some(5));
+	assert_eq!(m.captures.value("default"), Some(0x1020));
+	assert_eq!(m.captures.value("table"), Some(0x2000));
+
+	let too_short_gap = PatternSequence::new().then(InstrPattern::new(Mnemonic::Ja)).gap(0, 1).then(InstrPattern::new(Mnemonic::Jmp));
+	assert!(too_short_gap.find(&instrs).is_none());
+}
+
+#[test]
+fn sequence_many_gaps() {
+	// nop (x100)
+	let instrs = decode(64, 0x1000, &[0x90; 100]);
+	let mut seq = PatternSequence::new();
+	for _ in 0..10 {
+		seq = seq.then(InstrPattern::new(Mnemonic::Nop)).gap(0, 20);
+	}
+	// Each failed state is only tried once so this doesn't take forever
+	assert!(seq.clone().then(InstrPattern::new(Mnemonic::Int3)).find(&instrs).is_none());
+	let m = seq.then(InstrPattern::new(Mnemonic::Nop)).match_at(&instrs, 0).unwrap();
+	assert_eq!((m.start, m.end), (0, 11));
+}
+
+#[test]
+fn sequence_captures_are_restored_on_backtrack() {
+	// mov eax,1
+	// mov ecx,2
+	// add ecx,ecx
+	let instrs = decode(64, 0x1000, b"\xB8\x01\x00\x00\x00\xB9\x02\x00\x00\x00\x01\xC9");
+	let seq = PatternSequence::new()
+		.optional(InstrPattern::new(Mnemonic::Mov).operand(OperandPattern::any_register().capture("r")).operand(OperandPattern::immediate()))
+		.then(
+			InstrPattern::new(Mnemonic::Add)
+				.operand(OperandPattern::any_register().capture("r"))
+				.operand(OperandPattern::any_register().capture("r")),
+		);
+	assert!(seq.match_at(&instrs, 0).is_none());
+	let m = seq.match_at(&instrs, 1).unwrap();
+	assert_eq!((m.start, m.end), (1, 3));
+	assert_eq!(m.captures.register("r"), Some(Register::ECX));
+	let m = seq.match_at(&instrs, 2).unwrap();
+	assert_eq!((m.start, m.end), (2, 3));
+}
+
+#[test]
+fn find_all_non_overlapping() {
+	// push rbp
+	// push rbp
+	// push rbp
+	let instrs = decode(64, 0x1000, b"\x55\x55\x55");
+	let seq = PatternSequence::new().then(InstrPattern::new(Mnemonic::Push)).then(InstrPattern::new(Mnemonic::Push));
+	let matches = seq.find_all(&instrs);
+	assert_eq!(matches.len(), 1);
+	assert_eq!((matches[0].start, matches[0].end), (0, 2));
+	assert!(PatternSequence::new().optional(InstrPattern::new(Mnemonic::Pop)).find(&instrs).is_none());
+	assert!(PatternSequence::from(InstrPattern::new(Mnemonic::Push)).match_at(&instrs, 3).is_none());
+}
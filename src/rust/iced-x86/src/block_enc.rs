@@ -16,6 +16,139 @@ use crate::*;
 use alloc::boxed::Box;
 use alloc::rc::Rc;
 use alloc::vec::Vec;
+use core::cmp;
+
+pub(crate) const MAX_NOP_LEN: usize = 9;
+
+// Returns a multi-byte NOP with a length of 1-MAX_NOP_LEN bytes
+pub(crate) const fn get_nop_bytes(bitness: u32, size: usize) -> &'static [u8] {
+	match size {
+		1 => &[0x90],                   // NOP
+		2 => &[0x66, 0x90],             // 66 NOP
+		3 => &[0x0F, 0x1F, 0x00],       // NOP dword ptr [eax] or NOP word ptr [bx+si]
+		4 => &[0x0F, 0x1F, 0x40, 0x00], // NOP dword ptr [eax + 00] or NOP word ptr [bx+si]
+		5 => {
+			if bitness != 16 {
+				&[0x0F, 0x1F, 0x44, 0x00, 0x00] // NOP dword ptr [eax + eax*1 + 00]
+			} else {
+				&[0x0F, 0x1F, 0x80, 0x00, 0x00] // NOP word ptr[bx + si]
+			}
+		}
+		6 => {
+			if bitness != 16 {
+				&[0x66, 0x0F, 0x1F, 0x44, 0x00, 0x00] // 66 NOP dword ptr [eax + eax*1 + 00]
+			} else {
+				&[0x66, 0x0F, 0x1F, 0x80, 0x00, 0x00] // NOP dword ptr [bx+si]
+			}
+		}
+		7 => {
+			if bitness != 16 {
+				&[0x0F, 0x1F, 0x80, 0x00, 0x00, 0x00, 0x00] // NOP dword ptr [eax + 00000000]
+			} else {
+				&[0x67, 0x66, 0x0F, 0x1F, 0x44, 0x00, 0x00] // NOP dword ptr [eax+eax]
+			}
+		}
+		8 => {
+			if bitness != 16 {
+				&[0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00] // NOP dword ptr [eax + eax*1 + 00000000]
+			} else {
+				&[0x67, 0x0F, 0x1F, 0x80, 0x00, 0x00, 0x00, 0x00] // NOP word ptr [eax]
+			}
+		}
+		9 => {
+			if bitness != 16 {
+				&[0x66, 0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00] // 66 NOP dword ptr [eax + eax*1 + 00000000]
+			} else {
+				&[0x67, 0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00] // NOP word ptr [eax+eax]
+			}
+		}
+		_ => &[],
+	}
+}
+
+const fn is_jcc(mnemonic: Mnemonic) -> bool {
+	matches!(
+		mnemonic,
+		Mnemonic::Jo
+			| Mnemonic::Jno
+			| Mnemonic::Jb
+			| Mnemonic::Jae
+			| Mnemonic::Je
+			| Mnemonic::Jne
+			| Mnemonic::Jbe
+			| Mnemonic::Ja
+			| Mnemonic::Js
+			| Mnemonic::Jns
+			| Mnemonic::Jp
+			| Mnemonic::Jnp
+			| Mnemonic::Jl
+			| Mnemonic::Jge
+			| Mnemonic::Jle
+			| Mnemonic::Jg
+	)
+}
+
+// Instructions affected by the JCC erratum: all jumps, calls and returns
+const fn is_jcc_erratum_branch(mnemonic: Mnemonic) -> bool {
+	is_jcc(mnemonic)
+		|| matches!(
+			mnemonic,
+			Mnemonic::Jmp
+				| Mnemonic::Jmpe
+				| Mnemonic::Call
+				| Mnemonic::Ret
+				| Mnemonic::Retf
+				| Mnemonic::Loop
+				| Mnemonic::Loope
+				| Mnemonic::Loopne
+				| Mnemonic::Jcxz
+				| Mnemonic::Jecxz
+				| Mnemonic::Jrcxz
+		)
+}
+
+// Checks if the CPU can macro-fuse `first` and the following `Jcc` into one uop (Sandy Bridge and later)
+fn can_macro_fuse(first: &Instruction, jcc: Mnemonic) -> bool {
+	let fuses = match first.mnemonic() {
+		Mnemonic::Test | Mnemonic::And => is_jcc(jcc),
+		Mnemonic::Cmp | Mnemonic::Add | Mnemonic::Sub => {
+			matches!(
+				jcc,
+				Mnemonic::Jb
+					| Mnemonic::Jae | Mnemonic::Je
+					| Mnemonic::Jne | Mnemonic::Jbe
+					| Mnemonic::Ja | Mnemonic::Jl
+					| Mnemonic::Jge | Mnemonic::Jle
+					| Mnemonic::Jg
+			)
+		}
+		Mnemonic::Inc | Mnemonic::Dec => matches!(jcc, Mnemonic::Je | Mnemonic::Jne | Mnemonic::Jl | Mnemonic::Jge | Mnemonic::Jle | Mnemonic::Jg),
+		_ => false,
+	};
+	// Memory + immediate operands can't be fused
+	fuses
+		&& !(first.op_kinds().any(|kind| kind == OpKind::Memory)
+			&& first.op_kinds().any(|kind| {
+				matches!(
+					kind,
+					OpKind::Immediate8
+						| OpKind::Immediate8to16
+						| OpKind::Immediate8to32
+						| OpKind::Immediate8to64
+						| OpKind::Immediate16
+						| OpKind::Immediate32
+						| OpKind::Immediate32to64
+				)
+			}))
+}
+
+// Returns the InstrBase::branch_unit value of an instruction if the JCC erratum option is enabled
+fn jcc_erratum_branch_unit(instruction: &Instruction, next: Option<&Instruction>) -> u32 {
+	match next {
+		Some(next) if can_macro_fuse(instruction, next.mnemonic()) => 2,
+		_ => is_jcc_erratum_branch(instruction.mnemonic()) as u32,
+	}
+}
 
 /// Relocation info
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -41,6 +174,32 @@ impl RelocInfo {
 	}
 }
 
+/// A directive that is encoded instead of an instruction, see [`InstructionBlock::with_directives()`]
+///
+/// [`InstructionBlock::with_directives()`]: struct.InstructionBlock.html#method.with_directives
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum BlockDirective {
+	/// Adds `NOP`s until the address is a multiple of the alignment. It must be a power of 2.
+	Align(u32),
+	/// Stores the address of the instruction with IP `target` (or `target` if there's no such instruction)
+	Address {
+		/// Size of the data in bytes: 2, 4 or 8
+		size: u32,
+		/// Target IP
+		target: u64,
+	},
+	/// Stores the address of the instruction with IP `target` minus the address of the instruction with IP `base`, eg. a PIC jump table entry
+	RelativeAddress {
+		/// Size of the data in bytes: 2, 4 or 8
+		size: u32,
+		/// Target IP
+		target: u64,
+		/// Base IP
+		base: u64,
+	},
+}
+
 /// Contains a slice of instructions that should be encoded by [`BlockEncoder`]
 ///
 /// [`BlockEncoder`]: struct.BlockEncoder.html
@@ -48,6 +207,7 @@ impl RelocInfo {
 pub struct InstructionBlock<'a> {
 	instructions: &'a [Instruction],
 	rip: u64,
+	directives: &'a [(usize, BlockDirective)],
 }
 
 impl<'a> InstructionBlock<'a> {
@@ -60,7 +220,42 @@ impl<'a> InstructionBlock<'a> {
 	#[must_use]
 	#[inline]
 	pub const fn new(instructions: &'a [Instruction], rip: u64) -> Self {
-		Self { instructions, rip }
+		Self { instructions, rip, directives: &[] }
+	}
+
+	/// Constructor. Each directive replaces the instruction at its index (usually a [`Code::Zero_bytes`] instruction).
+	/// Only the IP of the replaced instruction is used so it can still be the target of branches and other directives.
+	///
+	/// Alignment padding depends on the final address so it's calculated after all branches have been optimized.
+	/// Label addresses are calculated when the instructions are encoded.
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: All instructions
+	/// * `rip`: Base IP of all encoded instructions
+	/// * `directives`: Instruction index and directive, sorted by index
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::*;
+	///
+	/// let instructions = [
+	///     Instruction::with(Code::Int3),
+	///     Instruction::with(Code::Zero_bytes),
+	///     Instruction::with(Code::Zero_bytes),
+	/// ];
+	/// let directives = [(1, BlockDirective::Align(4)), (2, BlockDirective::Address { size: 4, target: 0x1000 })];
+	/// let block = InstructionBlock::with_directives(&instructions, 0x1000, &directives);
+	/// let result = BlockEncoder::encode(32, block, BlockEncoderOptions::NONE).unwrap();
+	/// assert_eq!(result.code_buffer, b"\xCC\x0F\x1F\x00\x00\x10\x00\x00");
+	/// ```
+	///
+	/// [`Code::Zero_bytes`]: enum.Code.html#variant.Zero_bytes
+	#[must_use]
+	#[inline]
+	pub const fn with_directives(instructions: &'a [Instruction], rip: u64, directives: &'a [(usize, BlockDirective)]) -> Self {
+		Self { instructions, rip, directives }
 	}
 }
 
@@ -116,6 +311,7 @@ struct BlockEncInt {
 	null_encoder: Encoder,
 	to_instr_index: Vec<(u64, usize)>,
 	has_multiple_zero_ip_instrs: bool,
+	has_alignment: bool,
 }
 
 impl BlockEncInt {
@@ -160,6 +356,7 @@ impl BlockEncoder {
 				null_encoder: Encoder::try_new(bitness)?,
 				to_instr_index: Vec::new(),
 				has_multiple_zero_ip_instrs: false,
+				has_alignment: false,
 			},
 		};
 
@@ -169,14 +366,32 @@ impl BlockEncoder {
 			instr_count += instructions.len();
 			let mut ip = instr_block.rip;
 			let start_index = this.all_instrs.len();
-			for instruction in instructions {
-				let mut base = InstrBase { orig_ip: instruction.ip(), size: 0, done: false };
-				let instr = InstrUtils::create(&mut this.benc, &mut base, instruction);
-				debug_assert!(base.size != 0 || instruction.code() == Code::Zero_bytes);
+			let mut directives = instr_block.directives.iter().peekable();
+			for (index, instruction) in instructions.iter().enumerate() {
+				let mut base = InstrBase { orig_ip: instruction.ip(), size: 0, alignment: 0, branch_unit: 0, padding: 0, done: false };
+				let instr = match directives.next_if(|&&(directive_index, _)| directive_index == index) {
+					Some((_, directive)) => InstrUtils::create_directive(&mut this.benc, &mut base, directive)?,
+					None => {
+						let instr = InstrUtils::create(&mut this.benc, &mut base, instruction);
+						debug_assert!(base.size != 0 || instruction.code() == Code::Zero_bytes);
+						if (options & BlockEncoderOptions::JCC_ERRATUM) != 0 {
+							// A directive isn't a Jcc so it can't be the 2nd instruction of a macro-fused pair
+							let next = match directives.peek() {
+								Some(&&(directive_index, _)) if directive_index == index + 1 => None,
+								_ => instructions.get(index + 1),
+							};
+							base.branch_unit = jcc_erratum_branch_unit(instruction, next);
+						}
+						instr
+					}
+				};
 				ip = ip.wrapping_add(base.size as u64);
 				this.all_ips.push(ip);
 				this.all_instrs.push((base, instr));
 			}
+			if directives.next().is_some() {
+				return Err(IcedError::new("Invalid directive index. The directives must be sorted by index."));
+			}
 			let end_index = this.all_instrs.len();
 			let block = Block::new(
 				this.benc.bitness,
@@ -187,8 +402,8 @@ impl BlockEncoder {
 			)?;
 			this.blocks.push((block, start_index, end_index));
 		}
-		// Optimize from low to high addresses
-		this.blocks.sort_unstable_by_key(|a| a.0.rip);
+		// Optimize from low to high addresses. It's a stable sort so blocks with the same IP keep their order.
+		this.blocks.sort_by_key(|a| a.0.rip);
 
 		this.benc.to_instr_index = Vec::with_capacity(instr_count);
 		// There must not be any instructions with the same IP, except if IP = 0 (default value)
@@ -229,11 +444,18 @@ impl BlockEncoder {
 
 		for info in &mut this.blocks {
 			let mut ip = info.0.rip;
-			for (i, (base, instr)) in this.all_instrs[info.1..info.2].iter_mut().enumerate() {
-				this.all_ips[info.1 + i] = ip;
+			for i in info.1..info.2 {
+				let padding = Self::jcc_erratum_padding(&this.all_instrs, i, None);
+				let (base, instr) = &mut this.all_instrs[i];
+				base.padding = padding;
+				ip = ip.wrapping_add(padding as u64);
+				this.all_ips[i] = ip;
 				if !base.done {
 					let (target_instr, target_ip) = instr.get_target_instr();
 					*target_instr = this.benc.get_target(base, target_ip);
+					if let Some((target_instr, target_ip)) = instr.get_target_instr2() {
+						*target_instr = this.benc.get_target(base, target_ip);
+					}
 				}
 				ip = ip.wrapping_add(base.size as u64);
 			}
@@ -242,6 +464,30 @@ impl BlockEncoder {
 		Ok(this)
 	}
 
+	// Returns the number of NOP bytes that must be added before the instruction so the branch (or macro-fused pair)
+	// doesn't cross or end on a 32-byte boundary. `ip` is the address of the first padding byte. If it's `None`, the max
+	// padding is returned. The real padding is always <= the max padding so it can only make the code shorter.
+	fn jcc_erratum_padding(all_instrs: &[(InstrBase, Box<dyn Instr>)], index: usize, ip: Option<u64>) -> u32 {
+		let base = &all_instrs[index].0;
+		let size = match base.branch_unit {
+			0 => return 0,
+			1 => base.size,
+			_ => base.size + all_instrs[index + 1].0.size,
+		};
+		match ip {
+			None => cmp::min(size, 31),
+			Some(ip) => {
+				let offset = (ip & 31) as u32;
+				// If it's 32 bytes or more, it can't be fixed
+				if size < 32 && offset + size >= 32 {
+					32 - offset
+				} else {
+					0
+				}
+			}
+		}
+	}
+
 	/// Encodes instructions. Any number of branches can be part of this block.
 	/// You can use this function to move instructions from one location to another location.
 	/// If the target of a branch is too far away, it'll be rewritten to a longer branch.
@@ -358,8 +604,20 @@ impl BlockEncoder {
 				let mut gained = 0;
 				let block_rip = info.0.rip;
 				let mut ctx = InstrContext { block: &mut info.0, all_ips: &mut self.all_ips, ip: block_rip };
-				for (i, (base, instr)) in self.all_instrs[info.1..info.2].iter_mut().enumerate() {
-					ctx.all_ips[info.1 + i] = ctx.ip;
+				for i in info.1..info.2 {
+					// The max padding depends on the size of the branch so it can only get smaller
+					let padding = Self::jcc_erratum_padding(&self.all_instrs, i, None);
+					let (base, instr) = &mut self.all_instrs[i];
+					if padding != base.padding {
+						if padding > base.padding {
+							return Err(IcedError::new("Internal error"));
+						}
+						gained += (base.padding - padding) as u64;
+						updated = true;
+						base.padding = padding;
+					}
+					ctx.ip = ctx.ip.wrapping_add(padding as u64);
+					ctx.all_ips[i] = ctx.ip;
 					// If it can't be optimized further, don't call its virtual optimize() fn for a nice speedup
 					if !base.done {
 						let old_size = base.size;
@@ -384,6 +642,25 @@ impl BlockEncoder {
 			}
 		}
 
+		// Alignment and JCC erratum padding used the max size above. The real size depends on the final address and
+		// can only make the code shorter so all branches will still be in range.
+		if self.benc.has_alignment || (self.benc.options & BlockEncoderOptions::JCC_ERRATUM) != 0 {
+			for info in &mut self.blocks {
+				let mut ip = info.0.rip;
+				for i in info.1..info.2 {
+					let padding = Self::jcc_erratum_padding(&self.all_instrs, i, Some(ip));
+					let base = &mut self.all_instrs[i].0;
+					base.padding = padding;
+					ip = ip.wrapping_add(padding as u64);
+					self.all_ips[i] = ip;
+					if base.alignment != 0 {
+						base.size = (ip.wrapping_neg() & (base.alignment as u64 - 1)) as u32;
+					}
+					ip = ip.wrapping_add(base.size as u64);
+				}
+			}
+		}
+
 		for info in &mut self.blocks {
 			let index = info.2.wrapping_sub(1);
 			if let Some((last_base, _)) = self.all_instrs.get(index) {
@@ -408,6 +685,10 @@ impl BlockEncoder {
 			let block_rip = info.0.rip;
 			let mut ctx = InstrContext { block: &mut info.0, all_ips: &mut self.all_ips, ip: block_rip };
 			for (base, instr) in &mut self.all_instrs[info.1..info.2] {
+				if base.padding != 0 {
+					ctx.block.write_nops(self.benc.bitness, base.padding);
+					ctx.ip = ctx.ip.wrapping_add(base.padding as u64);
+				}
 				let buffer_pos = ctx.block.buffer_pos();
 				let is_original_instruction = if (self.benc.options & BlockEncoderOptions::RETURN_CONSTANT_OFFSETS) != 0 {
 					let result = instr.encode(base, &mut ctx)?;
@@ -103,6 +103,17 @@ impl Block {
 		self.encoder.write_byte_internal(value);
 	}
 
+	pub(super) fn write_nops(&mut self, bitness: u32, size: u32) {
+		let mut size = size as usize;
+		while size != 0 {
+			let nop_size = if size < MAX_NOP_LEN { size } else { MAX_NOP_LEN };
+			for &b in get_nop_bytes(bitness, nop_size) {
+				self.write_byte(b as u32);
+			}
+			size -= nop_size;
+		}
+	}
+
 	pub(super) fn take_buffer(&mut self) -> Vec<u8> {
 		self.encoder.take_buffer()
 	}
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::block_enc::instr::*;
+use crate::block_enc::*;
+use crate::iced_error::IcedError;
+
+pub(super) struct AlignInstr {
+	bitness: u8,
+}
+
+impl AlignInstr {
+	pub(super) fn new(block_encoder: &mut BlockEncInt, base: &mut InstrBase, alignment: u32) -> Self {
+		base.done = true;
+		// Max size until the final address is known, see BlockEncoder::encode2()
+		base.size = alignment - 1;
+		base.alignment = if alignment > 1 { alignment } else { 0 };
+		Self { bitness: block_encoder.bitness() as u8 }
+	}
+}
+
+impl Instr for AlignInstr {
+	fn get_target_instr(&mut self) -> (&mut TargetInstr, u64) {
+		// Never called since base.done == true
+		unreachable!()
+	}
+
+	fn optimize(&mut self, _base: &mut InstrBase, _ctx: &mut InstrContext<'_>, _gained: u64) -> bool {
+		false
+	}
+
+	fn encode(&mut self, base: &mut InstrBase, ctx: &mut InstrContext<'_>) -> Result<(ConstantOffsets, bool), IcedError> {
+		ctx.block.write_nops(self.bitness as u32, base.size);
+		Ok((ConstantOffsets::default(), true))
+	}
+}
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::block_enc::instr::*;
+use crate::block_enc::*;
+use crate::iced_error::IcedError;
+
+pub(super) struct LabelAddressInstr {
+	bitness: u8,
+	size: u8,
+	target: u64,
+	target_instr: TargetInstr,
+	// Only used if it's a relative address
+	base: Option<(u64, TargetInstr)>,
+}
+
+impl LabelAddressInstr {
+	pub(super) fn new(block_encoder: &mut BlockEncInt, base: &mut InstrBase, size: u32, target: u64, base_ip: Option<u64>) -> Self {
+		// Not done until the targets have been initialized, see optimize()
+		base.done = false;
+		base.size = size;
+		Self {
+			bitness: block_encoder.bitness() as u8,
+			size: size as u8,
+			target,
+			target_instr: TargetInstr::default(),
+			base: base_ip.map(|ip| (ip, TargetInstr::default())),
+		}
+	}
+}
+
+impl Instr for LabelAddressInstr {
+	fn get_target_instr(&mut self) -> (&mut TargetInstr, u64) {
+		(&mut self.target_instr, self.target)
+	}
+
+	fn get_target_instr2(&mut self) -> Option<(&mut TargetInstr, u64)> {
+		self.base.as_mut().map(|(ip, target_instr)| (target_instr, *ip))
+	}
+
+	fn optimize(&mut self, base: &mut InstrBase, _ctx: &mut InstrContext<'_>, _gained: u64) -> bool {
+		base.done = true;
+		false
+	}
+
+	fn encode(&mut self, _base: &mut InstrBase, ctx: &mut InstrContext<'_>) -> Result<(ConstantOffsets, bool), IcedError> {
+		let target_address = self.target_instr.address(ctx);
+		let (value, fits) = if let Some((_, ref base_instr)) = self.base {
+			let value = target_address.wrapping_sub(base_instr.address(ctx));
+			let fits = match self.size {
+				2 => value as i16 as u64 == value,
+				4 => value as i32 as u64 == value,
+				_ => true,
+			};
+			(value, fits)
+		} else {
+			let fits = match self.size {
+				2 => target_address <= u16::MAX as u64,
+				4 => target_address <= u32::MAX as u64,
+				_ => true,
+			};
+			(target_address, fits)
+		};
+		if !fits {
+			return Err(IcedError::with_string(format!("Label address 0x{:X} doesn't fit in {} bytes", value, self.size)));
+		}
+
+		if self.base.is_none() && self.size == 8 && self.bitness == 64 {
+			ctx.block.add_reloc_info(RelocInfo::new(RelocKind::Offset64, ctx.ip));
+		}
+		for i in 0..self.size {
+			ctx.block.write_byte((value >> (i * 8)) as u32);
+		}
+		Ok((ConstantOffsets::default(), true))
+	}
+}
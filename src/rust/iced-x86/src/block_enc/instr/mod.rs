@@ -1,19 +1,23 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2018-present iced project and contributors
 
+mod align_instr;
 mod call_instr;
 mod ip_relmem_instr;
 mod jcc_instr;
 mod jmp_instr;
+mod label_addr_instr;
 mod simple_br_instr;
 mod simple_instr;
 mod xbegin_instr;
 
 use crate::block_enc::block::{Block, BlockData};
+use crate::block_enc::instr::align_instr::AlignInstr;
 use crate::block_enc::instr::call_instr::CallInstr;
 use crate::block_enc::instr::ip_relmem_instr::IpRelMemOpInstr;
 use crate::block_enc::instr::jcc_instr::JccInstr;
 use crate::block_enc::instr::jmp_instr::JmpInstr;
+use crate::block_enc::instr::label_addr_instr::LabelAddressInstr;
 use crate::block_enc::instr::simple_br_instr::SimpleBranchInstr;
 use crate::block_enc::instr::simple_instr::SimpleInstr;
 use crate::block_enc::instr::xbegin_instr::XbeginInstr;
@@ -44,6 +48,12 @@ pub(super) struct InstrContext<'a> {
 pub(super) struct InstrBase {
 	pub(super) size: u32,
 	pub(super) orig_ip: u64,
+	// Non-zero if it's an align directive. The size is calculated after all instructions have been optimized.
+	pub(super) alignment: u32,
+	// JCC erratum: 0 = nothing, 1 = branch, 2 = first instruction of a macro-fused pair (this instr + next instr)
+	pub(super) branch_unit: u32,
+	// Number of NOP bytes written before this instruction, see BlockEncoder::jcc_erratum_padding()
+	pub(super) padding: u32,
 	// If it can't be optimized, this will be set to true
 	pub(super) done: bool,
 }
@@ -51,6 +61,11 @@ pub(super) struct InstrBase {
 pub(super) trait Instr {
 	fn get_target_instr(&mut self) -> (&mut TargetInstr, u64);
 
+	/// Returns the 2nd target if it has one
+	fn get_target_instr2(&mut self) -> Option<(&mut TargetInstr, u64)> {
+		None
+	}
+
 	/// Returns `true` if the instruction was updated to a shorter instruction, `false` if nothing changed
 	fn optimize(&mut self, base: &mut InstrBase, ctx: &mut InstrContext<'_>, gained: u64) -> bool;
 
@@ -308,6 +323,37 @@ impl InstrUtils {
 		Box::new(SimpleInstr::new(block_encoder, base, instruction))
 	}
 
+	pub(super) fn create_directive(
+		block_encoder: &mut BlockEncInt, base: &mut InstrBase, directive: &BlockDirective,
+	) -> Result<Box<dyn Instr>, IcedError> {
+		match *directive {
+			BlockDirective::Align(alignment) => {
+				if !alignment.is_power_of_two() {
+					return Err(IcedError::new("Alignment must be a power of 2"));
+				}
+				if alignment > 1 {
+					block_encoder.has_alignment = true;
+				}
+				Ok(Box::new(AlignInstr::new(block_encoder, base, alignment)))
+			}
+			BlockDirective::Address { size, target } => {
+				Self::verify_label_address_size(size)?;
+				Ok(Box::new(LabelAddressInstr::new(block_encoder, base, size, target, None)))
+			}
+			BlockDirective::RelativeAddress { size, target, base: base_ip } => {
+				Self::verify_label_address_size(size)?;
+				Ok(Box::new(LabelAddressInstr::new(block_encoder, base, size, target, Some(base_ip))))
+			}
+		}
+	}
+
+	fn verify_label_address_size(size: u32) -> Result<(), IcedError> {
+		match size {
+			2 | 4 | 8 => Ok(()),
+			_ => Err(IcedError::new("Invalid address size, must be 2, 4 or 8")),
+		}
+	}
+
 	fn encode_branch_to_pointer_data(
 		block: &mut Block, is_call: bool, ip: u64, pointer_data: Rc<RefCell<BlockData>>, min_size: u32,
 	) -> Result<u32, IcedError> {
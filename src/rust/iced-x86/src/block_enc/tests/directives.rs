@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::block_enc::tests::*;
+
+fn with_ip(mut instruction: Instruction, ip: u64) -> Instruction {
+	instruction.set_ip(ip);
+	instruction
+}
+
+fn zero_bytes(ip: u64) -> Instruction {
+	with_ip(Instruction::with(Code::Zero_bytes), ip)
+}
+
+#[test]
+fn align_padding_grows_when_branch_is_shortened() {
+	// A near jmp would end at 0x1008 (no padding) but the short jmp ends at 0x1005 (3 bytes of padding)
+	let instructions =
+		[with_ip(Instruction::with_branch(Code::Jmp_rel32_64, 3).unwrap(), 1), zero_bytes(2), with_ip(Instruction::with(Code::Nopd), 3)];
+	let directives = [(1, BlockDirective::Align(8))];
+	let result = BlockEncoder::encode(
+		64,
+		InstructionBlock::with_directives(&instructions, 0x1003, &directives),
+		BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS,
+	)
+	.unwrap();
+	assert_eq!(result.code_buffer, b"\xEB\x03\x0F\x1F\x00\x90");
+	assert_eq!(result.new_instruction_offsets, [0, 2, 5]);
+}
+
+#[test]
+fn align_uses_long_nops() {
+	let instructions = [
+		with_ip(Instruction::with_branch(Code::Jmp_rel32_64, 4).unwrap(), 1),
+		zero_bytes(2),
+		with_ip(Instruction::with(Code::Int3), 3),
+		with_ip(Instruction::with(Code::Nopd), 4),
+	];
+	let directives = [(1, BlockDirective::Align(16))];
+	let result = BlockEncoder::encode(
+		64,
+		InstructionBlock::with_directives(&instructions, 0x1000, &directives),
+		BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS,
+	)
+	.unwrap();
+	assert_eq!(result.code_buffer, b"\xEB\x0F\x66\x0F\x1F\x84\x00\x00\x00\x00\x00\x0F\x1F\x44\x00\x00\xCC\x90");
+	assert_eq!(result.new_instruction_offsets, [0, 2, 0x10, 0x11]);
+}
+
+#[test]
+fn align_1_and_already_aligned() {
+	let instructions = [zero_bytes(1), with_ip(Instruction::with(Code::Int3), 2), zero_bytes(3), with_ip(Instruction::with(Code::Int3), 4)];
+	let directives = [(0, BlockDirective::Align(1)), (2, BlockDirective::Align(1))];
+	let result = BlockEncoder::encode(32, InstructionBlock::with_directives(&instructions, 0x1001, &directives), BlockEncoderOptions::NONE).unwrap();
+	assert_eq!(result.code_buffer, b"\xCC\xCC");
+
+	let directives = [(0, BlockDirective::Align(16))];
+	let result = BlockEncoder::encode(32, InstructionBlock::with_directives(&instructions, 0x1000, &directives), BlockEncoderOptions::NONE).unwrap();
+	assert_eq!(result.code_buffer, b"\xCC\xCC");
+}
+
+#[test]
+fn label_addresses() {
+	let instructions = [zero_bytes(1), zero_bytes(2), zero_bytes(3), zero_bytes(4), with_ip(Instruction::with(Code::Retnq), 5)];
+	let directives = [
+		(0, BlockDirective::Address { size: 8, target: 5 }),
+		(1, BlockDirective::Address { size: 4, target: 0x1234_5678 }),
+		(2, BlockDirective::RelativeAddress { size: 4, target: 5, base: 1 }),
+		(3, BlockDirective::RelativeAddress { size: 2, target: 1, base: 5 }),
+	];
+	let result = BlockEncoder::encode(
+		64,
+		InstructionBlock::with_directives(&instructions, 0x8000_0000_0000_0000, &directives),
+		BlockEncoderOptions::RETURN_RELOC_INFOS | BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS,
+	)
+	.unwrap();
+	assert_eq!(result.code_buffer, b"\x12\x00\x00\x00\x00\x00\x00\x80\x78\x56\x34\x12\x12\x00\x00\x00\xEE\xFF\xC3");
+	assert_eq!(result.reloc_infos, [RelocInfo::new(RelocKind::Offset64, 0x8000_0000_0000_0000)]);
+	assert_eq!(result.new_instruction_offsets, [0, 8, 12, 16, 18]);
+}
+
+#[test]
+fn label_address_too_big_fails() {
+	let instructions = [zero_bytes(1), with_ip(Instruction::with(Code::Retnq), 2)];
+	let directives = [(0, BlockDirective::Address { size: 4, target: 2 })];
+	assert!(
+		BlockEncoder::encode(64, InstructionBlock::with_directives(&instructions, 0x1_0000_0000, &directives), BlockEncoderOptions::NONE).is_err()
+	);
+	assert!(BlockEncoder::encode(64, InstructionBlock::with_directives(&instructions, 0xFFFF_0000, &directives), BlockEncoderOptions::NONE).is_ok());
+
+	let directives = [(0, BlockDirective::RelativeAddress { size: 2, target: 0x9100, base: 2 })];
+	assert!(BlockEncoder::encode(64, InstructionBlock::with_directives(&instructions, 0x1000, &directives), BlockEncoderOptions::NONE).is_err());
+}
+
+#[test]
+fn invalid_directives_fail() {
+	let instructions = [zero_bytes(1), zero_bytes(2)];
+	for directives in [
+		&[(0, BlockDirective::Align(0))][..],
+		&[(0, BlockDirective::Align(3))][..],
+		&[(0, BlockDirective::Address { size: 1, target: 1 })][..],
+		&[(0, BlockDirective::RelativeAddress { size: 16, target: 1, base: 2 })][..],
+		&[(2, BlockDirective::Align(4))][..],
+		&[(1, BlockDirective::Align(4)), (0, BlockDirective::Align(4))][..],
+		&[(0, BlockDirective::Align(4)), (0, BlockDirective::Align(4))][..],
+	] {
+		assert!(BlockEncoder::encode(64, InstructionBlock::with_directives(&instructions, 0x1000, directives), BlockEncoderOptions::NONE).is_err());
+	}
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::block_enc::tests::*;
+
+fn encode_jcc_erratum(bitness: u32, rip: u64, data: &[u8]) -> BlockEncoderResult {
+	let instructions = decode(bitness, rip, data, DECODER_OPTIONS);
+	BlockEncoder::encode(
+		bitness,
+		InstructionBlock::new(&instructions, rip),
+		BlockEncoderOptions::JCC_ERRATUM | BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS,
+	)
+	.unwrap()
+}
+
+#[test]
+fn jmp_ending_on_32_byte_boundary_is_padded() {
+	// jmp short 0x1020 ; nop
+	let result = encode_jcc_erratum(64, 0x101E, b"\xEB\x00\x90");
+	assert_eq!(result.code_buffer, b"\x66\x90\xEB\x00\x90");
+	assert_eq!(result.new_instruction_offsets, [2, 4]);
+}
+
+#[test]
+fn jmp_not_crossing_32_byte_boundary_is_not_padded() {
+	let result = encode_jcc_erratum(64, 0x101D, b"\xEB\x00\x90");
+	assert_eq!(result.code_buffer, b"\xEB\x00\x90");
+	assert_eq!(result.new_instruction_offsets, [0, 2]);
+}
+
+#[test]
+fn no_padding_if_option_is_disabled() {
+	let instructions = decode(64, 0x101E, b"\xEB\x00\x90", DECODER_OPTIONS);
+	let result = BlockEncoder::encode(64, InstructionBlock::new(&instructions, 0x101E), BlockEncoderOptions::NONE).unwrap();
+	assert_eq!(result.code_buffer, b"\xEB\x00\x90");
+}
+
+#[test]
+fn ret_is_padded() {
+	let result = encode_jcc_erratum(64, 0x101F, b"\xC3");
+	assert_eq!(result.code_buffer, b"\x90\xC3");
+	assert_eq!(result.new_instruction_offsets, [1]);
+}
+
+#[test]
+fn indirect_call_is_padded() {
+	// call rax
+	let result = encode_jcc_erratum(32, 0x101F, b"\xFF\xD0");
+	assert_eq!(result.code_buffer, b"\x90\xFF\xD0");
+}
+
+#[test]
+fn macro_fused_pair_is_padded_before_first_instruction() {
+	// cmp eax,ecx ; je short 0x1021 ; nop
+	let result = encode_jcc_erratum(64, 0x101D, b"\x39\xC8\x74\x00\x90");
+	assert_eq!(result.code_buffer, b"\x0F\x1F\x00\x39\xC8\x74\x00\x90");
+	assert_eq!(result.new_instruction_offsets, [3, 5, 7]);
+}
+
+#[test]
+fn mem_imm_cmp_is_not_macro_fused() {
+	// cmp dword ptr [rax],1 ; je short 0x1022 ; nop
+	let result = encode_jcc_erratum(64, 0x101D, b"\x83\x38\x01\x74\x00\x90");
+	assert_eq!(result.code_buffer, b"\x83\x38\x01\x74\x00\x90");
+	assert_eq!(result.new_instruction_offsets, [0, 3, 5]);
+}
+
+#[test]
+fn inc_jo_is_not_macro_fused() {
+	// inc eax ; jo short 0x1021 ; nop
+	let result = encode_jcc_erratum(64, 0x101D, b"\xFF\xC0\x70\x00\x90");
+	assert_eq!(result.code_buffer, b"\xFF\xC0\x90\x70\x00\x90");
+	assert_eq!(result.new_instruction_offsets, [0, 3, 5]);
+}
+
+#[test]
+fn padding_uses_optimized_branch_size() {
+	// jmp near 0x1021 ; nop. The short jmp doesn't cross the boundary.
+	let result = encode_jcc_erratum(64, 0x101C, b"\xE9\x00\x00\x00\x00\x90");
+	assert_eq!(result.code_buffer, b"\xEB\x00\x90");
+	assert_eq!(result.new_instruction_offsets, [0, 2]);
+}
+
+#[test]
+fn padding_moves_branch_targets() {
+	// 0x101C: jmp short 0x1020
+	// 0x101E: jne short 0x101C
+	// 0x1020: nop
+	let result = encode_jcc_erratum(64, 0x101C, b"\xEB\x02\x75\xFC\x90");
+	// jmp short 0x1022 ; 2-byte nop ; jne short 0x101C ; nop
+	assert_eq!(result.code_buffer, b"\xEB\x04\x66\x90\x75\xFA\x90");
+	assert_eq!(result.new_instruction_offsets, [0, 4, 6]);
+}
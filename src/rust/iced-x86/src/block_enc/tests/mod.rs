@@ -12,10 +12,12 @@ mod br8_64;
 mod call_16;
 mod call_32;
 mod call_64;
+mod directives;
 mod ip_rel_64;
 mod jcc_16;
 mod jcc_32;
 mod jcc_64;
+mod jcc_erratum;
 #[cfg(feature = "mvex")]
 mod jkcc_64;
 mod jmp_16;
@@ -15,23 +15,45 @@
 //!
 //! [`CodeAssembler`]: struct.CodeAssembler.html
 
+#[cfg(feature = "abi")]
+mod abi;
 pub mod asm_traits;
+#[cfg(any(feature = "abi", feature = "reg_alloc"))]
+mod calling_convention;
 mod code_asm_methods;
+#[cfg(feature = "op_code_info")]
+mod emit;
 mod fn_asm_impl;
 mod fn_asm_pub;
 mod mem;
+#[cfg(feature = "object_writer")]
+pub mod object;
 mod op_state;
+#[cfg(feature = "op_code_info")]
+mod operand;
 mod reg;
+#[cfg(feature = "reg_alloc")]
+mod reg_alloc;
 pub mod registers;
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "abi")]
+pub use crate::code_asm::abi::{CallArgument, Frame, FrameOptions};
+#[cfg(any(feature = "abi", feature = "reg_alloc"))]
+pub use crate::code_asm::calling_convention::CallingConvention;
 pub use crate::code_asm::mem::*;
+#[cfg(feature = "op_code_info")]
+pub use crate::code_asm::operand::*;
 pub use crate::code_asm::reg::*;
+#[cfg(feature = "reg_alloc")]
+pub use crate::code_asm::reg_alloc::RegisterAllocatorOptions;
 pub use crate::code_asm::registers::*;
 pub use crate::IcedError;
-use crate::{BlockEncoderResult, Instruction};
+use crate::{BlockDirective, BlockEncoderResult, Instruction};
+use alloc::string::String;
 use alloc::vec::Vec;
+use core::fmt;
 use core::hash::{Hash, Hasher};
 
 struct PrefixFlags;
@@ -144,7 +166,8 @@ impl CodeAssemblerOptions {
 #[allow(missing_debug_implementations)]
 pub struct CodeAssembler {
 	bitness: u32,
-	instructions: Vec<Instruction>,
+	sections: Vec<Section>,
+	current_section: usize,
 	current_label_id: u64,
 	current_label: CodeLabel,
 	current_anon_label: CodeLabel,
@@ -152,6 +175,80 @@ pub struct CodeAssembler {
 	defined_anon_label: bool,
 	prefix_flags: u8,
 	options: u8,
+	#[cfg(feature = "reg_alloc")]
+	register_allocator: reg_alloc::RegisterAllocator,
+}
+
+struct Section {
+	name: String,
+	kind: SectionKind,
+	alignment: u32,
+	ip: Option<u64>,
+	instructions: Vec<Instruction>,
+	directives: Vec<(usize, BlockDirective)>,
+}
+
+impl Section {
+	#[must_use]
+	#[inline]
+	fn new(name: String, kind: SectionKind, alignment: u32) -> Self {
+		Self { name, kind, alignment, ip: None, instructions: Vec::new(), directives: Vec::new() }
+	}
+}
+
+/// The kind of data stored in a section created by [`CodeAssembler::create_section()`]
+///
+/// [`CodeAssembler::create_section()`]: struct.CodeAssembler.html#method.create_section
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum SectionKind {
+	/// Executable code
+	Code,
+	/// Read-only data, eg. constants and jump tables
+	ReadOnlyData,
+	/// Writable data
+	Data,
+}
+
+impl Default for SectionKind {
+	#[inline]
+	fn default() -> Self {
+		SectionKind::Code
+	}
+}
+
+impl fmt::Display for SectionKind {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			SectionKind::Code => "code",
+			SectionKind::ReadOnlyData => "rodata",
+			SectionKind::Data => "data",
+		})
+	}
+}
+
+/// A section created by [`CodeAssembler`]. All instructions are added to the current section, see [`CodeAssembler::set_section()`]
+///
+/// [`CodeAssembler`]: struct.CodeAssembler.html
+/// [`CodeAssembler::set_section()`]: struct.CodeAssembler.html#method.set_section
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct CodeSection {
+	index: usize,
+}
+
+impl CodeSection {
+	#[must_use]
+	#[inline]
+	pub(crate) fn new(index: usize) -> Self {
+		Self { index }
+	}
+
+	#[must_use]
+	#[inline]
+	pub(crate) fn index(&self) -> usize {
+		self.index
+	}
 }
 
 /// A label created by [`CodeAssembler`]
@@ -161,6 +258,7 @@ pub struct CodeAssembler {
 pub struct CodeLabel {
 	id: u64,
 	instruction_index: usize,
+	section: usize,
 }
 
 impl Eq for CodeLabel {}
@@ -183,7 +281,7 @@ impl CodeLabel {
 	#[must_use]
 	#[inline]
 	pub(crate) fn new(id: u64) -> Self {
-		Self { id, instruction_index: usize::MAX }
+		Self { id, instruction_index: usize::MAX, section: 0 }
 	}
 
 	#[must_use]
@@ -209,11 +307,60 @@ impl CodeLabel {
 #[derive(Debug)]
 #[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
 pub struct CodeAssemblerResult {
+	/// Inner `BlockEncoder` result of the `.text` section
+	pub inner: BlockEncoderResult,
+	/// All sections created by [`CodeAssembler::create_section()`] in creation order
+	///
+	/// [`CodeAssembler::create_section()`]: struct.CodeAssembler.html#method.create_section
+	pub sections: Vec<CodeSectionResult>,
+	/// Number of bytes of the spill area used by the register allocator, see [`RegisterAllocatorOptions::set_spill_area()`]
+	///
+	/// [`RegisterAllocatorOptions::set_spill_area()`]: struct.RegisterAllocatorOptions.html#method.set_spill_area
+	#[cfg(feature = "reg_alloc")]
+	pub spill_size: u32,
+}
+
+/// Result of assembling the instructions of a section created by [`CodeAssembler::create_section()`]
+///
+/// [`CodeAssembler::create_section()`]: struct.CodeAssembler.html#method.create_section
+#[derive(Debug)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct CodeSectionResult {
+	/// Name of the section
+	pub name: String,
+	/// Section kind
+	pub kind: SectionKind,
+	/// Alignment of the section
+	pub alignment: u32,
 	/// Inner `BlockEncoder` result
 	pub inner: BlockEncoderResult,
 }
 
 impl CodeAssemblerResult {
+	/// Gets the `BlockEncoder` result of a section
+	///
+	/// # Arguments
+	///
+	/// * `section`: The section, see [`CodeAssembler::text_section()`] and [`CodeAssembler::create_section()`]
+	///
+	/// [`CodeAssembler::text_section()`]: struct.CodeAssembler.html#method.text_section
+	/// [`CodeAssembler::create_section()`]: struct.CodeAssembler.html#method.create_section
+	#[must_use]
+	#[inline]
+	pub fn section(&self, section: CodeSection) -> Option<&BlockEncoderResult> {
+		self.section_result(section.index())
+	}
+
+	#[must_use]
+	#[inline]
+	fn section_result(&self, index: usize) -> Option<&BlockEncoderResult> {
+		if index == 0 {
+			Some(&self.inner)
+		} else {
+			self.sections.get(index - 1).map(|section| &section.inner)
+		}
+	}
+
 	/// Gets the address of a label
 	///
 	/// # Notes
@@ -240,7 +387,12 @@ impl CodeAssemblerResult {
 				"The label is not associated with an instruction index. It must be emitted via `CodeAssembler::set_label()`.",
 			));
 		}
-		let new_offset = if let Some(new_offset) = self.inner.new_instruction_offsets.get(label.instruction_index) {
+		let inner = if let Some(inner) = self.section_result(label.section) {
+			inner
+		} else {
+			return Err(IcedError::new("Invalid label section"));
+		};
+		let new_offset = if let Some(new_offset) = inner.new_instruction_offsets.get(label.instruction_index) {
 			*new_offset
 		} else {
 			return Err(IcedError::new(
@@ -250,7 +402,7 @@ impl CodeAssemblerResult {
 		if new_offset == u32::MAX {
 			Err(IcedError::new("The instruction was re-written to a longer instruction (eg. JE NEAR -> JE FAR) and there's no instruction offset. Consider using a `zero_bytes()` instruction as a label instead of a normal instruction or disable branch optimizations."))
 		} else {
-			Ok(self.inner.rip.wrapping_add(new_offset as u64))
+			Ok(inner.rip.wrapping_add(new_offset as u64))
 		}
 	}
 }
@@ -0,0 +1,784 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::{CallingConvention, CodeAssembler};
+use crate::{Code, Encoder, IcedError, Instruction, MemoryOperand, Register};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Options used by [`CodeAssembler::prologue()`] to create a stack frame
+///
+/// [`CodeAssembler::prologue()`]: struct.CodeAssembler.html#method.prologue
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct FrameOptions {
+	calling_convention: CallingConvention,
+	local_size: u32,
+	saved_registers: Vec<Register>,
+	frame_pointer: bool,
+	outgoing_stack_size: u32,
+	leaf: bool,
+	argument_stack_size: u32,
+}
+
+impl FrameOptions {
+	/// Creates options for a frame without locals and saved registers
+	///
+	/// # Arguments
+	///
+	/// * `calling_convention`: Calling convention of the function
+	#[must_use]
+	#[inline]
+	pub fn new(calling_convention: CallingConvention) -> Self {
+		Self {
+			calling_convention,
+			local_size: 0,
+			saved_registers: Vec::new(),
+			frame_pointer: false,
+			outgoing_stack_size: 0,
+			leaf: false,
+			argument_stack_size: 0,
+		}
+	}
+
+	/// Calling convention of the function
+	#[must_use]
+	#[inline]
+	pub fn calling_convention(&self) -> CallingConvention {
+		self.calling_convention
+	}
+
+	/// Calling convention of the function
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_calling_convention(&mut self, new_value: CallingConvention) {
+		self.calling_convention = new_value;
+	}
+
+	/// Size in bytes of the local variables, see [`Frame::locals_offset()`]. Default is 0.
+	///
+	/// [`Frame::locals_offset()`]: struct.Frame.html#method.locals_offset
+	#[must_use]
+	#[inline]
+	pub fn local_size(&self) -> u32 {
+		self.local_size
+	}
+
+	/// Size in bytes of the local variables, see [`Frame::locals_offset()`]. Default is 0.
+	///
+	/// [`Frame::locals_offset()`]: struct.Frame.html#method.locals_offset
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_local_size(&mut self, new_value: u32) {
+		self.local_size = new_value;
+	}
+
+	/// Callee-saved registers that are saved by the prologue and restored by the epilogue, see [`CallingConvention::is_callee_saved()`].
+	/// `XMM6`-`XMM15` (Microsoft x64) are saved in the frame, all other registers are pushed in this order. Default is an empty slice.
+	///
+	/// [`CallingConvention::is_callee_saved()`]: enum.CallingConvention.html#method.is_callee_saved
+	#[must_use]
+	#[inline]
+	pub fn saved_registers(&self) -> &[Register] {
+		&self.saved_registers
+	}
+
+	/// Callee-saved registers that are saved by the prologue and restored by the epilogue, see [`CallingConvention::is_callee_saved()`].
+	/// `XMM6`-`XMM15` (Microsoft x64) are saved in the frame, all other registers are pushed in this order. Default is an empty slice.
+	///
+	/// [`CallingConvention::is_callee_saved()`]: enum.CallingConvention.html#method.is_callee_saved
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_saved_registers(&mut self, new_value: &[Register]) {
+		self.saved_registers.clear();
+		self.saved_registers.extend_from_slice(new_value);
+	}
+
+	/// `true` to use `RBP`/`EBP` as a frame pointer. It's saved by the prologue and must not be in [`saved_registers()`]. Default is `false`.
+	///
+	/// [`saved_registers()`]: #method.saved_registers
+	#[must_use]
+	#[inline]
+	pub fn frame_pointer(&self) -> bool {
+		self.frame_pointer
+	}
+
+	/// `true` to use `RBP`/`EBP` as a frame pointer. It's saved by the prologue and must not be in [`saved_registers()`]. Default is `false`.
+	///
+	/// [`saved_registers()`]: #method.saved_registers
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_frame_pointer(&mut self, new_value: bool) {
+		self.frame_pointer = new_value;
+	}
+
+	/// 64-bit only: max size in bytes of the stack arguments of all calls made by the function (not including the
+	/// Microsoft x64 shadow space). 32-bit code pushes the stack arguments instead. Default is 0.
+	#[must_use]
+	#[inline]
+	pub fn outgoing_stack_size(&self) -> u32 {
+		self.outgoing_stack_size
+	}
+
+	/// 64-bit only: max size in bytes of the stack arguments of all calls made by the function (not including the
+	/// Microsoft x64 shadow space). 32-bit code pushes the stack arguments instead. Default is 0.
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_outgoing_stack_size(&mut self, new_value: u32) {
+		self.outgoing_stack_size = new_value;
+	}
+
+	/// `true` if the function doesn't call other functions. A leaf frame doesn't reserve the Microsoft x64 shadow space.
+	/// Default is `false`.
+	#[must_use]
+	#[inline]
+	pub fn leaf(&self) -> bool {
+		self.leaf
+	}
+
+	/// `true` if the function doesn't call other functions. A leaf frame doesn't reserve the Microsoft x64 shadow space.
+	/// Default is `false`.
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_leaf(&mut self, new_value: bool) {
+		self.leaf = new_value;
+	}
+
+	/// Size in bytes of the function's own stack arguments. The epilogue of a `stdcall` or `fastcall` function pops them
+	/// (`RET imm16`). Default is 0.
+	#[must_use]
+	#[inline]
+	pub fn argument_stack_size(&self) -> u32 {
+		self.argument_stack_size
+	}
+
+	/// Size in bytes of the function's own stack arguments. The epilogue of a `stdcall` or `fastcall` function pops them
+	/// (`RET imm16`). Default is 0.
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_argument_stack_size(&mut self, new_value: u32) {
+		self.argument_stack_size = new_value;
+	}
+}
+
+impl Default for FrameOptions {
+	#[inline]
+	fn default() -> Self {
+		FrameOptions::new(CallingConvention::default())
+	}
+}
+
+/// An argument passed to a function by [`CodeAssembler::call_with_args()`]
+///
+/// [`CodeAssembler::call_with_args()`]: struct.CodeAssembler.html#method.call_with_args
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum CallArgument {
+	/// An integer or a pointer in a 32-bit or 64-bit general purpose register
+	Register(Register),
+	/// An integer immediate. 32-bit code and stack arguments only support 32-bit immediates.
+	Immediate(i64),
+	/// A `float` in the low 32 bits of an `XMM` register
+	Float32(Register),
+	/// A `double` in the low 64 bits of an `XMM` register
+	Float64(Register),
+}
+
+impl CallArgument {
+	#[must_use]
+	fn is_float(self) -> bool {
+		matches!(self, CallArgument::Float32(_) | CallArgument::Float64(_))
+	}
+
+	// Size of the argument when it's pushed by 32-bit code
+	#[must_use]
+	fn stack_size32(self) -> u32 {
+		if let CallArgument::Float64(_) = self {
+			8
+		} else {
+			4
+		}
+	}
+}
+
+// Unwind operation codes (UNWIND_CODE.UnwindOp)
+const UWOP_PUSH_NONVOL: u16 = 0;
+const UWOP_ALLOC_LARGE: u16 = 1;
+const UWOP_ALLOC_SMALL: u16 = 2;
+const UWOP_SET_FPREG: u16 = 3;
+const UWOP_SAVE_XMM128: u16 = 8;
+const UWOP_SAVE_XMM128_FAR: u16 = 9;
+
+/// A stack frame created by [`CodeAssembler::prologue()`]. Pass it to [`CodeAssembler::epilogue()`] and
+/// [`CodeAssembler::call_with_args()`].
+///
+/// The stack pointer is 16-byte aligned after the prologue. The stack looks like this (higher addresses first):
+///
+/// - the function's stack arguments, see [`stack_arguments_offset()`]
+/// - the return address
+/// - pushed registers (the frame pointer is pushed first)
+/// - padding and saved `XMM` registers
+/// - local variables, see [`locals_offset()`]
+/// - 64-bit: the stack arguments of called functions and the Microsoft x64 shadow space
+///
+/// [`CodeAssembler::prologue()`]: struct.CodeAssembler.html#method.prologue
+/// [`CodeAssembler::epilogue()`]: struct.CodeAssembler.html#method.epilogue
+/// [`CodeAssembler::call_with_args()`]: struct.CodeAssembler.html#method.call_with_args
+/// [`stack_arguments_offset()`]: #method.stack_arguments_offset
+/// [`locals_offset()`]: #method.locals_offset
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Frame {
+	calling_convention: CallingConvention,
+	pushed: Vec<Register>,
+	saved_xmm: Vec<(Register, u32)>,
+	frame_pointer: bool,
+	leaf: bool,
+	allocation: u32,
+	locals_offset: u32,
+	outgoing_stack_size: u32,
+	argument_stack_size: u32,
+	prologue_size: u32,
+	// Microsoft x64 unwind codes in reverse prologue order
+	unwind_codes: Vec<u16>,
+}
+
+impl Frame {
+	/// Gets the calling convention
+	#[must_use]
+	#[inline]
+	pub fn calling_convention(&self) -> CallingConvention {
+		self.calling_convention
+	}
+
+	/// Offset of the local variables relative to the stack pointer
+	#[must_use]
+	#[inline]
+	pub fn locals_offset(&self) -> u32 {
+		self.locals_offset
+	}
+
+	/// Offset of the function's first stack argument relative to the stack pointer. The Microsoft x64 shadow space is
+	/// skipped, it's at `stack_arguments_offset() - 32`.
+	#[must_use]
+	#[inline]
+	pub fn stack_arguments_offset(&self) -> u32 {
+		let word = self.calling_convention.bitness() / 8;
+		self.size() + word + self.calling_convention.shadow_space()
+	}
+
+	/// Size in bytes of the frame, not including the return address. It's the number of bytes the prologue subtracted
+	/// from the stack pointer.
+	#[must_use]
+	#[inline]
+	pub fn size(&self) -> u32 {
+		let word = self.calling_convention.bitness() / 8;
+		self.allocation + word * self.pushed.len() as u32
+	}
+
+	/// Size in bytes of the prologue
+	#[must_use]
+	#[inline]
+	pub fn prologue_size(&self) -> u32 {
+		self.prologue_size
+	}
+
+	/// Creates the Microsoft x64 `UNWIND_INFO` of the function. The prologue must be at the start of the function.
+	///
+	/// It should be stored in a read-only data section, 4-byte aligned, and be referenced by the function's `RUNTIME_FUNCTION`.
+	/// No exception handler is used (`Flags` = 0).
+	///
+	/// # Errors
+	///
+	/// Fails if it's not a [`CallingConvention::Microsoft64`] frame or if the prologue is too big
+	///
+	/// [`CallingConvention::Microsoft64`]: enum.CallingConvention.html#variant.Microsoft64
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn unwind_info(&self) -> Result<Vec<u8>, IcedError> {
+		if self.calling_convention != CallingConvention::Microsoft64 {
+			return Err(IcedError::new("Unwind info is only used by the Microsoft x64 calling convention"));
+		}
+		if self.prologue_size > u8::MAX as u32 || self.unwind_codes.len() > u8::MAX as usize {
+			return Err(IcedError::new("The prologue is too big"));
+		}
+		let mut info = Vec::with_capacity(4 + (self.unwind_codes.len() + 1) / 2 * 4);
+		// Version = 1, Flags = 0
+		info.push(1);
+		info.push(self.prologue_size as u8);
+		info.push(self.unwind_codes.len() as u8);
+		// FrameRegister, FrameOffset = 0
+		info.push(if self.frame_pointer { Register::RBP.number() as u8 } else { 0 });
+		for &code in &self.unwind_codes {
+			info.extend_from_slice(&code.to_le_bytes());
+		}
+		// The array is padded to an even number of slots
+		if self.unwind_codes.len() % 2 != 0 {
+			info.extend_from_slice(&[0, 0]);
+		}
+		Ok(info)
+	}
+
+	#[must_use]
+	fn stack_pointer(&self) -> Register {
+		if self.calling_convention.bitness() == 64 {
+			Register::RSP
+		} else {
+			Register::ESP
+		}
+	}
+
+	#[must_use]
+	fn frame_register(&self) -> Register {
+		if self.calling_convention.bitness() == 64 {
+			Register::RBP
+		} else {
+			Register::EBP
+		}
+	}
+}
+
+// Adds instructions and counts the number of bytes
+struct Emitter {
+	encoder: Encoder,
+	size: u32,
+}
+
+impl Emitter {
+	fn new(bitness: u32) -> Result<Self, IcedError> {
+		Ok(Self { encoder: Encoder::try_new(bitness)?, size: 0 })
+	}
+
+	// Returns the number of bytes emitted so far
+	fn emit(&mut self, a: &mut CodeAssembler, instruction: Instruction) -> Result<u32, IcedError> {
+		self.size += self.encoder.encode(&instruction, 0)? as u32;
+		let _ = self.encoder.take_buffer();
+		a.add_instr(instruction)?;
+		Ok(self.size)
+	}
+}
+
+#[must_use]
+fn unwind_code(offset: u32, op: u16, info: u16) -> u16 {
+	offset as u16 | (op << 8) | (info << 12)
+}
+
+fn add_sub(bitness: u32, add: bool, register: Register, value: u32) -> Result<Instruction, IcedError> {
+	let code = match (bitness, add, value <= i8::MAX as u32) {
+		(64, true, true) => Code::Add_rm64_imm8,
+		(64, true, false) => Code::Add_rm64_imm32,
+		(64, false, true) => Code::Sub_rm64_imm8,
+		(64, false, false) => Code::Sub_rm64_imm32,
+		(_, true, true) => Code::Add_rm32_imm8,
+		(_, true, false) => Code::Add_rm32_imm32,
+		(_, false, true) => Code::Sub_rm32_imm8,
+		(_, false, false) => Code::Sub_rm32_imm32,
+	};
+	Instruction::with2(code, register, value)
+}
+
+#[must_use]
+fn mov_code(register: Register) -> Code {
+	if register.is_gpr64() {
+		Code::Mov_rm64_r64
+	} else {
+		Code::Mov_rm32_r32
+	}
+}
+
+// No displacement is used if it's 0
+#[must_use]
+fn memory(base: Register, displacement: i64) -> MemoryOperand {
+	if displacement == 0 {
+		MemoryOperand::with_base(base)
+	} else {
+		MemoryOperand::with_base_displ(base, displacement)
+	}
+}
+
+fn checked_size(value: u64) -> Result<u32, IcedError> {
+	if value > i32::MAX as u64 {
+		Err(IcedError::new("The frame is too big"))
+	} else {
+		Ok(value as u32)
+	}
+}
+
+// Returns the register with the same size as `like`
+#[must_use]
+fn same_size(register: Register, like: Register) -> Register {
+	let number = register.number();
+	let base = if like.is_gpr64() {
+		Register::RAX
+	} else if like.is_gpr32() {
+		Register::EAX
+	} else {
+		Register::XMM0
+	};
+	Register::try_from(base as usize + number).unwrap_or_default()
+}
+
+pub(crate) fn prologue(a: &mut CodeAssembler, options: &FrameOptions) -> Result<Frame, IcedError> {
+	let calling_convention = options.calling_convention;
+	let bitness = calling_convention.bitness();
+	if a.bitness() != bitness {
+		return Err(IcedError::with_string(format!("The {} calling convention can't be used in {}-bit code", calling_convention, a.bitness())));
+	}
+	let word = bitness / 8;
+	let mut frame = Frame {
+		calling_convention,
+		pushed: Vec::new(),
+		saved_xmm: Vec::new(),
+		frame_pointer: options.frame_pointer,
+		leaf: options.leaf,
+		allocation: 0,
+		locals_offset: 0,
+		outgoing_stack_size: 0,
+		argument_stack_size: options.argument_stack_size,
+		prologue_size: 0,
+		unwind_codes: Vec::new(),
+	};
+	if options.frame_pointer {
+		frame.pushed.push(frame.frame_register());
+	}
+	let mut saved_xmm = Vec::new();
+	for &register in &options.saved_registers {
+		if !calling_convention.is_callee_saved(register) {
+			return Err(IcedError::with_string(format!(
+				"{:?} isn't a callee-saved register in the {} calling convention",
+				register, calling_convention
+			)));
+		}
+		if frame.pushed.contains(&register) || saved_xmm.contains(&register) {
+			return Err(IcedError::with_string(format!("{:?} is already saved", register)));
+		}
+		if register.is_xmm() {
+			saved_xmm.push(register);
+		} else {
+			frame.pushed.push(register);
+		}
+	}
+	if !calling_convention.callee_pops_arguments() && options.argument_stack_size != 0 && bitness == 32 {
+		return Err(IcedError::with_string(format!("The caller pops the stack arguments in the {} calling convention", calling_convention)));
+	}
+
+	// The stack pointer is 16-byte aligned before the CALL instruction pushed the return address
+	let shadow_space = if options.leaf { 0 } else { calling_convention.shadow_space() };
+	let outgoing_stack_size = if bitness == 64 { (options.outgoing_stack_size as u64 + 7) & !7 } else { 0 };
+	let locals_offset = shadow_space as u64 + outgoing_stack_size;
+	let mut allocation = locals_offset + options.local_size as u64;
+	if !saved_xmm.is_empty() {
+		allocation = (allocation + 15) & !15;
+	}
+	let xmm_offset = allocation;
+	allocation += 16 * saved_xmm.len() as u64;
+	let pushed_size = word as u64 * (frame.pushed.len() as u64 + 1);
+	allocation += (16 - (pushed_size + allocation) % 16) % 16;
+	frame.allocation = checked_size(allocation)?;
+	frame.locals_offset = checked_size(locals_offset)?;
+	frame.outgoing_stack_size = checked_size(outgoing_stack_size)?;
+	frame.saved_xmm = saved_xmm.iter().enumerate().map(|(i, &register)| (register, xmm_offset as u32 + 16 * i as u32)).collect();
+
+	let stack_pointer = frame.stack_pointer();
+	let mut emitter = Emitter::new(bitness)?;
+	let push = if bitness == 64 { Code::Push_r64 } else { Code::Push_r32 };
+	if calling_convention == CallingConvention::Microsoft64 {
+		// Each code is followed by its extra slots
+		let mut codes: Vec<Vec<u16>> = Vec::new();
+		for &register in &frame.pushed {
+			let offset = emitter.emit(a, Instruction::with1(push, register)?)?;
+			codes.push(vec![unwind_code(offset, UWOP_PUSH_NONVOL, register.number() as u16)]);
+		}
+		if frame.allocation != 0 {
+			let offset = emitter.emit(a, add_sub(bitness, false, stack_pointer, frame.allocation)?)?;
+			codes.push(if frame.allocation <= 128 {
+				vec![unwind_code(offset, UWOP_ALLOC_SMALL, (frame.allocation / 8 - 1) as u16)]
+			} else if frame.allocation <= 0x7FFF8 {
+				vec![unwind_code(offset, UWOP_ALLOC_LARGE, 0), (frame.allocation / 8) as u16]
+			} else {
+				vec![unwind_code(offset, UWOP_ALLOC_LARGE, 1), frame.allocation as u16, (frame.allocation >> 16) as u16]
+			});
+		}
+		if frame.frame_pointer {
+			let offset = emitter.emit(a, Instruction::with2(Code::Mov_rm64_r64, Register::RBP, Register::RSP)?)?;
+			codes.push(vec![unwind_code(offset, UWOP_SET_FPREG, 0)]);
+		}
+		for &(register, displacement) in &frame.saved_xmm {
+			let memory = memory(Register::RSP, displacement as i64);
+			let offset = emitter.emit(a, Instruction::with2(Code::Movaps_xmmm128_xmm, memory, register)?)?;
+			let number = (register.number()) as u16;
+			codes.push(if displacement / 16 <= u16::MAX as u32 {
+				vec![unwind_code(offset, UWOP_SAVE_XMM128, number), (displacement / 16) as u16]
+			} else {
+				vec![unwind_code(offset, UWOP_SAVE_XMM128_FAR, number), displacement as u16, (displacement >> 16) as u16]
+			});
+		}
+		frame.unwind_codes = codes.into_iter().rev().flatten().collect();
+	} else {
+		for (i, &register) in frame.pushed.iter().enumerate() {
+			let _ = emitter.emit(a, Instruction::with1(push, register)?)?;
+			if i == 0 && frame.frame_pointer {
+				let _ = emitter.emit(a, Instruction::with2(mov_code(stack_pointer), frame.frame_register(), stack_pointer)?)?;
+			}
+		}
+		if frame.allocation != 0 {
+			let _ = emitter.emit(a, add_sub(bitness, false, stack_pointer, frame.allocation)?)?;
+		}
+	}
+	frame.prologue_size = emitter.size;
+	Ok(frame)
+}
+
+pub(crate) fn epilogue(a: &mut CodeAssembler, frame: &Frame) -> Result<(), IcedError> {
+	let bitness = frame.calling_convention.bitness();
+	if a.bitness() != bitness {
+		return Err(IcedError::new("The frame was created by a CodeAssembler with another bitness"));
+	}
+	let word = bitness / 8;
+	let stack_pointer = frame.stack_pointer();
+	let frame_register = frame.frame_register();
+	let lea = if bitness == 64 { Code::Lea_r64_m } else { Code::Lea_r32_m };
+	if frame.calling_convention == CallingConvention::Microsoft64 {
+		// The frame pointer is the stack pointer after the prologue. The epilogue must be `LEA RSP, [RBP+X]` or `ADD RSP, X`
+		// followed by POPs and a RET.
+		let base = if frame.frame_pointer { frame_register } else { stack_pointer };
+		for &(register, displacement) in &frame.saved_xmm {
+			let memory = memory(base, displacement as i64);
+			a.add_instr(Instruction::with2(Code::Movaps_xmm_xmmm128, register, memory)?)?;
+		}
+		if frame.frame_pointer {
+			let memory = memory(frame_register, frame.allocation as i64);
+			a.add_instr(Instruction::with2(lea, stack_pointer, memory)?)?;
+		} else if frame.allocation != 0 {
+			a.add_instr(add_sub(bitness, true, stack_pointer, frame.allocation)?)?;
+		}
+	} else if frame.frame_pointer {
+		// The frame pointer points to the saved frame pointer
+		let others = frame.pushed.len() as u32 - 1;
+		if others == 0 {
+			a.add_instr(Instruction::with2(mov_code(stack_pointer), stack_pointer, frame_register)?)?;
+		} else {
+			let memory = memory(frame_register, -((word * others) as i64));
+			a.add_instr(Instruction::with2(lea, stack_pointer, memory)?)?;
+		}
+	} else if frame.allocation != 0 {
+		a.add_instr(add_sub(bitness, true, stack_pointer, frame.allocation)?)?;
+	}
+	let pop = if bitness == 64 { Code::Pop_r64 } else { Code::Pop_r32 };
+	for &register in frame.pushed.iter().rev() {
+		a.add_instr(Instruction::with1(pop, register)?)?;
+	}
+	if frame.calling_convention.callee_pops_arguments() && frame.argument_stack_size != 0 {
+		if frame.argument_stack_size > u16::MAX as u32 {
+			return Err(IcedError::new("Too many stack arguments"));
+		}
+		a.add_instr(Instruction::with1(Code::Retnd_imm16, frame.argument_stack_size)?)
+	} else {
+		a.add_instr(Instruction::with(if bitness == 64 { Code::Retnq } else { Code::Retnd }))
+	}
+}
+
+// Moves the arguments to their registers and stack slots. Returns the number of bytes to pop after the call.
+pub(crate) fn move_arguments(
+	a: &mut CodeAssembler, frame: &Frame, calling_convention: CallingConvention, args: &[CallArgument],
+) -> Result<u32, IcedError> {
+	let bitness = calling_convention.bitness();
+	if a.bitness() != bitness || frame.calling_convention.bitness() != bitness {
+		return Err(IcedError::with_string(format!("The {} calling convention can't be used in {}-bit code", calling_convention, a.bitness())));
+	}
+	if frame.leaf {
+		return Err(IcedError::new("Functions can't be called from a leaf frame"));
+	}
+	for &arg in args {
+		let valid = match arg {
+			CallArgument::Register(register) => register.is_gpr32() || (bitness == 64 && register.is_gpr64()),
+			CallArgument::Immediate(value) => bitness == 64 || i32::try_from(value).is_ok() || u32::try_from(value).is_ok(),
+			CallArgument::Float32(register) | CallArgument::Float64(register) => register.is_xmm(),
+		};
+		if !valid {
+			return Err(IcedError::with_string(format!("Invalid argument: {:?}", arg)));
+		}
+	}
+
+	// Register arguments
+	let mut registers: Vec<(Register, CallArgument)> = Vec::new();
+	// 64-bit: stack arguments and their offsets, 32-bit: stack arguments in push order
+	let mut stack: Vec<(u32, CallArgument)> = Vec::new();
+	let scratch_xmm;
+	match calling_convention {
+		CallingConvention::SystemV64 => {
+			const GPRS: [Register; 6] = [Register::RDI, Register::RSI, Register::RDX, Register::RCX, Register::R8, Register::R9];
+			let (mut gprs, mut xmms, mut offset) = (0, 0, 0);
+			for &arg in args {
+				if arg.is_float() && xmms < 8 {
+					registers.push((Register::try_from(Register::XMM0 as usize + xmms).unwrap_or_default(), arg));
+					xmms += 1;
+				} else if !arg.is_float() && gprs < GPRS.len() {
+					registers.push((GPRS[gprs], arg));
+					gprs += 1;
+				} else {
+					stack.push((offset, arg));
+					offset += 8;
+				}
+			}
+			scratch_xmm = Register::XMM15;
+		}
+		CallingConvention::Microsoft64 => {
+			const GPRS: [Register; 4] = [Register::RCX, Register::RDX, Register::R8, Register::R9];
+			for (i, &arg) in args.iter().enumerate() {
+				if i >= GPRS.len() {
+					stack.push((32 + 8 * (i as u32 - 4), arg));
+				} else if arg.is_float() {
+					registers.push((Register::try_from(Register::XMM0 as usize + i).unwrap_or_default(), arg));
+				} else {
+					registers.push((GPRS[i], arg));
+				}
+			}
+			scratch_xmm = Register::XMM5;
+		}
+		CallingConvention::Cdecl | CallingConvention::Stdcall | CallingConvention::Fastcall => {
+			const GPRS: [Register; 2] = [Register::ECX, Register::EDX];
+			let mut gprs = 0;
+			for &arg in args {
+				if calling_convention == CallingConvention::Fastcall && !arg.is_float() && gprs < GPRS.len() {
+					registers.push((GPRS[gprs], arg));
+					gprs += 1;
+				} else {
+					stack.push((0, arg));
+				}
+			}
+			stack.reverse();
+			scratch_xmm = Register::None;
+		}
+	}
+
+	let mut pop_size = 0;
+	if bitness == 64 {
+		let needed = stack.last().map_or(0, |&(offset, _)| offset + 8);
+		if needed > frame.outgoing_stack_size + calling_convention.shadow_space() {
+			return Err(IcedError::with_string(format!(
+				"The frame has too little space for stack arguments, see `FrameOptions::set_outgoing_stack_size()`: {} bytes are needed",
+				needed - calling_convention.shadow_space()
+			)));
+		}
+		for &(offset, arg) in &stack {
+			let memory = memory(Register::RSP, offset as i64);
+			let instruction = match arg {
+				CallArgument::Register(register) => {
+					Instruction::with2(if register.is_gpr64() { Code::Mov_rm64_r64 } else { Code::Mov_rm32_r32 }, memory, register)?
+				}
+				CallArgument::Immediate(value) => {
+					let value = if let Ok(value) = i32::try_from(value) {
+						value
+					} else {
+						return Err(IcedError::new("64-bit immediate stack arguments aren't supported, use a register"));
+					};
+					Instruction::with2(Code::Mov_rm64_imm32, memory, value)?
+				}
+				CallArgument::Float32(register) => Instruction::with2(Code::Movss_xmmm32_xmm, memory, register)?,
+				CallArgument::Float64(register) => Instruction::with2(Code::Movsd_xmmm64_xmm, memory, register)?,
+			};
+			a.add_instr(instruction)?;
+		}
+	} else {
+		// The stack pointer must be 16-byte aligned at the CALL
+		let size: u32 = stack.iter().map(|&(_, arg)| arg.stack_size32()).sum();
+		let padding = (16 - size % 16) % 16;
+		if padding != 0 {
+			a.add_instr(add_sub(32, false, Register::ESP, padding)?)?;
+		}
+		for &(_, arg) in &stack {
+			match arg {
+				CallArgument::Register(register) => a.add_instr(Instruction::with1(Code::Push_r32, register)?)?,
+				CallArgument::Immediate(value) => {
+					let value = value as i32;
+					let code = if i8::try_from(value).is_ok() { Code::Pushd_imm8 } else { Code::Pushd_imm32 };
+					a.add_instr(Instruction::with1(code, value)?)?;
+				}
+				CallArgument::Float32(register) | CallArgument::Float64(register) => {
+					let (size, code) = if arg.stack_size32() == 8 { (8, Code::Movsd_xmmm64_xmm) } else { (4, Code::Movss_xmmm32_xmm) };
+					a.add_instr(add_sub(32, false, Register::ESP, size)?)?;
+					a.add_instr(Instruction::with2(code, MemoryOperand::with_base(Register::ESP), register)?)?;
+				}
+			}
+		}
+		pop_size = if calling_convention.callee_pops_arguments() { padding } else { size + padding };
+	}
+
+	// Register to register moves are parallel moves, eg. RDI and RSI could be swapped. Immediates are moved last.
+	let mut moves: Vec<(Register, Register)> = Vec::new();
+	for &(destination, arg) in &registers {
+		match arg {
+			CallArgument::Register(source) | CallArgument::Float32(source) | CallArgument::Float64(source) => {
+				if source.full_register() != destination.full_register() {
+					moves.push((same_size(destination, source), source));
+				}
+			}
+			CallArgument::Immediate(_) => {}
+		}
+	}
+	while !moves.is_empty() {
+		let free = moves.iter().position(|&(destination, _)| moves.iter().all(|&(_, source)| source.full_register() != destination.full_register()));
+		if let Some(index) = free {
+			let (destination, source) = moves.remove(index);
+			let code = if destination.is_xmm() { Code::Movaps_xmm_xmmm128 } else { mov_code(destination) };
+			a.add_instr(Instruction::with2(code, destination, source)?)?;
+		} else {
+			// Only cycles are left
+			let (destination, source) = moves[0];
+			let replacement = if destination.is_xmm() {
+				a.add_instr(Instruction::with2(Code::Movaps_xmm_xmmm128, scratch_xmm, destination)?)?;
+				scratch_xmm
+			} else {
+				let code = if bitness == 64 { Code::Xchg_rm64_r64 } else { Code::Xchg_rm32_r32 };
+				let size = if bitness == 64 { Register::RAX } else { Register::EAX };
+				a.add_instr(Instruction::with2(code, same_size(destination, size), same_size(source, size))?)?;
+				let _ = moves.remove(0);
+				source
+			};
+			for (_, other) in &mut moves {
+				if other.full_register() == destination.full_register() {
+					*other = same_size(replacement, *other);
+				}
+			}
+			moves.retain(|&(destination, source)| source.full_register() != destination.full_register());
+		}
+	}
+	for &(destination, arg) in &registers {
+		if let CallArgument::Immediate(value) = arg {
+			let instruction = if let Ok(value) = u32::try_from(value) {
+				Instruction::with2(Code::Mov_r32_imm32, same_size(destination, Register::EAX), value)?
+			} else if bitness == 32 {
+				Instruction::with2(Code::Mov_r32_imm32, destination, value as i32)?
+			} else if let Ok(value) = i32::try_from(value) {
+				Instruction::with2(Code::Mov_rm64_imm32, destination, value)?
+			} else {
+				Instruction::with2(Code::Mov_r64_imm64, destination, value)?
+			};
+			a.add_instr(instruction)?;
+		}
+	}
+	Ok(pop_size)
+}
+
+pub(crate) fn pop_arguments(a: &mut CodeAssembler, size: u32) -> Result<(), IcedError> {
+	a.add_instr(add_sub(32, true, Register::ESP, size)?)
+}
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::Register;
+use core::fmt;
+
+/// A calling convention, see [`CodeAssembler::prologue()`], [`CodeAssembler::call_with_args()`] and [`RegisterAllocatorOptions`]
+///
+/// [`CodeAssembler::prologue()`]: struct.CodeAssembler.html#method.prologue
+/// [`CodeAssembler::call_with_args()`]: struct.CodeAssembler.html#method.call_with_args
+/// [`RegisterAllocatorOptions`]: struct.RegisterAllocatorOptions.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum CallingConvention {
+	/// System V AMD64 ABI (eg. Linux, macOS, BSD)
+	SystemV64,
+	/// Microsoft x64 calling convention (Windows)
+	Microsoft64,
+	/// i386 `cdecl`: all arguments are passed on the stack and the caller pops them
+	Cdecl,
+	/// i386 `stdcall`: all arguments are passed on the stack and the callee pops them
+	Stdcall,
+	/// i386 `fastcall` (Microsoft): the first two integer arguments are passed in `ECX` and `EDX`, the other arguments are
+	/// passed on the stack and the callee pops them
+	Fastcall,
+}
+
+impl Default for CallingConvention {
+	#[inline]
+	fn default() -> Self {
+		CallingConvention::SystemV64
+	}
+}
+
+impl fmt::Display for CallingConvention {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			CallingConvention::SystemV64 => "System V AMD64",
+			CallingConvention::Microsoft64 => "Microsoft x64",
+			CallingConvention::Cdecl => "cdecl",
+			CallingConvention::Stdcall => "stdcall",
+			CallingConvention::Fastcall => "fastcall",
+		})
+	}
+}
+
+impl CallingConvention {
+	/// Gets the bitness (32 or 64) of code that uses this calling convention
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::CallingConvention;
+	///
+	/// assert_eq!(CallingConvention::Microsoft64.bitness(), 64);
+	/// assert_eq!(CallingConvention::Stdcall.bitness(), 32);
+	/// ```
+	#[must_use]
+	#[inline]
+	pub fn bitness(self) -> u32 {
+		match self {
+			CallingConvention::SystemV64 | CallingConvention::Microsoft64 => 64,
+			CallingConvention::Cdecl | CallingConvention::Stdcall | CallingConvention::Fastcall => 32,
+		}
+	}
+
+	/// Checks if a callee must preserve the register
+	///
+	/// # Arguments
+	///
+	/// * `register`: Register
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::Register;
+	/// use iced_x86::code_asm::CallingConvention;
+	///
+	/// assert!(CallingConvention::SystemV64.is_callee_saved(Register::RBX));
+	/// assert!(!CallingConvention::SystemV64.is_callee_saved(Register::RSI));
+	/// assert!(CallingConvention::Microsoft64.is_callee_saved(Register::RSI));
+	/// assert!(CallingConvention::Microsoft64.is_callee_saved(Register::XMM6));
+	/// assert!(CallingConvention::Cdecl.is_callee_saved(Register::EDI));
+	/// ```
+	#[must_use]
+	#[inline]
+	pub fn is_callee_saved(self, register: Register) -> bool {
+		match self {
+			CallingConvention::SystemV64 => {
+				matches!(register, Register::RBX | Register::RBP | Register::R12 | Register::R13 | Register::R14 | Register::R15)
+			}
+			CallingConvention::Microsoft64 => {
+				matches!(
+					register,
+					Register::RBX | Register::RBP | Register::RSI | Register::RDI | Register::R12 | Register::R13 | Register::R14 | Register::R15
+				) || (Register::XMM6 <= register && register <= Register::XMM15)
+			}
+			CallingConvention::Cdecl | CallingConvention::Stdcall | CallingConvention::Fastcall => {
+				matches!(register, Register::EBX | Register::EBP | Register::ESI | Register::EDI)
+			}
+		}
+	}
+
+	/// `true` if the callee pops its stack arguments (`stdcall` and `fastcall`)
+	#[must_use]
+	#[inline]
+	pub fn callee_pops_arguments(self) -> bool {
+		matches!(self, CallingConvention::Stdcall | CallingConvention::Fastcall)
+	}
+
+	// Size of the stack area reserved by the caller for the callee's register arguments
+	#[cfg(feature = "abi")]
+	#[must_use]
+	pub(super) fn shadow_space(self) -> u32 {
+		if self == CallingConvention::Microsoft64 {
+			32
+		} else {
+			0
+		}
+	}
+}
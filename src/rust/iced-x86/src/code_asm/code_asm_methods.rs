@@ -5,10 +5,26 @@
 // putting all generated methods (thousands of methods) ahead of non-generated fns
 // such as the constructor, prefix fns, etc.
 
+use crate::block_enc::{get_nop_bytes, MAX_NOP_LEN};
+#[cfg(feature = "abi")]
+use crate::code_asm::abi;
+#[cfg(feature = "abi")]
+use crate::code_asm::asm_traits::CodeAsmCall;
 use crate::code_asm::op_state::CodeAsmOpState;
-use crate::code_asm::{CodeAssembler, CodeAssemblerOptions, CodeAssemblerResult, CodeLabel, PrefixFlags};
+#[cfg(feature = "reg_alloc")]
+use crate::code_asm::reg_alloc::{RegisterAllocator, VirtualRegisterKind};
+#[cfg(feature = "reg_alloc")]
+use crate::code_asm::{
+	AsmRegister16, AsmRegister32, AsmRegister64, AsmRegister8, AsmRegisterK, AsmRegisterXmm, AsmRegisterYmm, AsmRegisterZmm, RegisterAllocatorOptions,
+};
+#[cfg(feature = "abi")]
+use crate::code_asm::{CallArgument, CallingConvention, Frame, FrameOptions};
+use crate::code_asm::{
+	CodeAssembler, CodeAssemblerOptions, CodeAssemblerResult, CodeLabel, CodeSection, CodeSectionResult, PrefixFlags, Section, SectionKind,
+};
 use crate::IcedError;
-use crate::{BlockEncoder, BlockEncoderOptions, Code, Instruction, InstructionBlock, MemoryOperand, Register};
+use crate::{BlockDirective, BlockEncoder, BlockEncoderOptions, BlockEncoderResult, Code, Instruction, InstructionBlock, MemoryOperand, Register};
+use alloc::string::String;
 use alloc::vec::Vec;
 
 impl CodeAssembler {
@@ -35,7 +51,8 @@ impl CodeAssembler {
 
 		Ok(Self {
 			bitness,
-			instructions: Vec::new(),
+			sections: vec![Section::new(String::from(".text"), SectionKind::Code, 1)],
+			current_section: 0,
 			current_label_id: 0,
 			current_label: CodeLabel::default(),
 			current_anon_label: CodeLabel::default(),
@@ -43,6 +60,8 @@ impl CodeAssembler {
 			defined_anon_label: false,
 			prefix_flags: PrefixFlags::NONE,
 			options: CodeAssemblerOptions::PREFER_VEX | CodeAssemblerOptions::PREFER_SHORT_BRANCH,
+			#[cfg(feature = "reg_alloc")]
+			register_allocator: RegisterAllocator::new(),
 		})
 	}
 
@@ -261,10 +280,14 @@ impl CodeAssembler {
 		self
 	}
 
-	/// Gets all added instructions, see also [`take_instructions()`] and [`assemble()`]
+	/// Gets all added instructions of the current section, see also [`take_instructions()`] and [`assemble()`]
+	///
+	/// Virtual registers (see [`create_virtual_gpr64()`]) are only allocated by [`assemble()`] so they're still
+	/// placeholder registers in the returned instructions.
 	///
 	/// [`take_instructions()`]: #method.take_instructions
 	/// [`assemble()`]: #method.assemble
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
 	///
 	/// # Examples
 	///
@@ -286,12 +309,32 @@ impl CodeAssembler {
 	#[must_use]
 	#[inline]
 	pub fn instructions(&self) -> &[Instruction] {
-		&self.instructions
+		&self.current().instructions
+	}
+
+	#[must_use]
+	#[inline]
+	fn current(&self) -> &Section {
+		&self.sections[self.current_section]
+	}
+
+	#[must_use]
+	#[inline]
+	fn current_mut(&mut self) -> &mut Section {
+		&mut self.sections[self.current_section]
 	}
 
-	/// Takes ownership of all instructions and returns them. Instruction state is also reset (see [`reset()`])
+	/// Takes ownership of all instructions of the current section and returns them. Instruction state is also reset (see [`reset()`])
+	/// unless another section still has instructions. In that case only the current section is cleared and the other sections
+	/// can be taken after calling [`set_section()`].
+	///
+	/// Directives added by [`align()`] and the `d*_label*()` methods are `zero_bytes` instructions in the returned vec.
+	/// Virtual registers (see [`create_virtual_gpr64()`]) aren't allocated.
 	///
 	/// [`reset()`]: #method.reset
+	/// [`set_section()`]: #method.set_section
+	/// [`align()`]: #method.align
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
 	///
 	/// # Examples
 	///
@@ -317,12 +360,16 @@ impl CodeAssembler {
 	#[must_use]
 	#[inline]
 	pub fn take_instructions(&mut self) -> Vec<Instruction> {
-		let instrs = core::mem::take(&mut self.instructions);
-		self.reset();
+		let section = self.current_mut();
+		let instrs = core::mem::take(&mut section.instructions);
+		section.directives.clear();
+		if self.sections.iter().all(|section| section.instructions.is_empty()) {
+			self.reset();
+		}
 		instrs
 	}
 
-	/// Resets all instructions and labels so this instance can be re-used
+	/// Resets all instructions, labels, sections and virtual registers so this instance can be re-used
 	///
 	/// # Examples
 	///
@@ -341,13 +388,18 @@ impl CodeAssembler {
 	/// ```
 	#[inline]
 	pub fn reset(&mut self) {
-		self.instructions.clear();
+		self.sections.truncate(1);
+		self.sections[0].instructions.clear();
+		self.sections[0].directives.clear();
+		self.current_section = 0;
 		self.current_label_id = 0;
 		self.current_label = CodeLabel::default();
 		self.current_anon_label = CodeLabel::default();
 		self.next_anon_label = CodeLabel::default();
 		self.defined_anon_label = false;
 		self.prefix_flags = PrefixFlags::NONE;
+		#[cfg(feature = "reg_alloc")]
+		self.register_allocator.reset();
 	}
 
 	/// Creates a label that can be referenced by instructions
@@ -398,7 +450,8 @@ impl CodeAssembler {
 		if !self.current_label.is_empty() {
 			return Err(IcedError::new("Only one label per instruction is allowed"));
 		}
-		label.instruction_index = self.instructions.len();
+		label.instruction_index = self.current().instructions.len();
+		label.section = self.current_section;
 		self.current_label = *label;
 		Ok(())
 	}
@@ -483,6 +536,147 @@ impl CodeAssembler {
 		Ok(self.next_anon_label)
 	}
 
+	/// Creates a new section. Use [`set_section()`] to add instructions and data to it.
+	///
+	/// All sections are encoded separately when calling [`assemble_options()`]. Unless [`set_section_ip()`] is called, a section
+	/// is placed after the previous section (in creation order) and its address is aligned to `alignment`. The first section is
+	/// the `.text` section, see [`text_section()`].
+	///
+	/// Labels can be referenced from any section, eg. a RIP-relative memory operand can reference a constant in another section.
+	///
+	/// # Errors
+	///
+	/// Fails if the name is empty or already used or if `alignment` isn't a power of 2
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the section, eg. `.rodata`
+	/// * `kind`: Section kind
+	/// * `alignment`: Alignment of the section, must be a power of 2
+	///
+	/// [`set_section()`]: #method.set_section
+	/// [`assemble_options()`]: #method.assemble_options
+	/// [`set_section_ip()`]: #method.set_section_ip
+	/// [`text_section()`]: #method.text_section
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::BlockEncoderOptions;
+	/// use iced_x86::code_asm::*;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// let rodata = a.create_section(".rodata", SectionKind::ReadOnlyData, 16)?;
+	/// let mut mask = a.create_label();
+	///
+	/// a.movaps(xmm0, xmmword_ptr(mask))?;
+	/// a.ret()?;
+	///
+	/// a.set_section(rodata)?;
+	/// a.set_label(&mut mask)?;
+	/// a.dq(&[0x7FFF_FFFF_7FFF_FFFF, 0x7FFF_FFFF_7FFF_FFFF])?;
+	///
+	/// let result = a.assemble_options(0x1000, BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS)?;
+	/// // movaps xmm0,[rip+9] ; ret
+	/// assert_eq!(result.inner.code_buffer, b"\x0F\x28\x05\x09\x00\x00\x00\xC3");
+	/// assert_eq!(result.sections.len(), 1);
+	/// assert_eq!(result.sections[0].name, ".rodata");
+	/// assert_eq!(result.sections[0].inner.rip, 0x1010);
+	/// assert_eq!(result.sections[0].inner.code_buffer.len(), 16);
+	/// assert_eq!(result.label_ip(&mask)?, 0x1010);
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn create_section(&mut self, name: &str, kind: SectionKind, alignment: u32) -> Result<CodeSection, IcedError> {
+		if name.is_empty() {
+			return Err(IcedError::new("Invalid section name"));
+		}
+		if self.sections.iter().any(|section| section.name == name) {
+			return Err(IcedError::with_string(format!("Section `{}` already exists", name)));
+		}
+		if !alignment.is_power_of_two() {
+			return Err(IcedError::new("Alignment must be a power of 2"));
+		}
+		self.sections.push(Section::new(String::from(name), kind, alignment));
+		Ok(CodeSection::new(self.sections.len() - 1))
+	}
+
+	/// Gets the `.text` section. It's the current section unless [`set_section()`] was called.
+	///
+	/// [`set_section()`]: #method.set_section
+	#[must_use]
+	#[inline]
+	#[allow(clippy::unused_self)]
+	pub fn text_section(&self) -> CodeSection {
+		CodeSection::new(0)
+	}
+
+	/// Gets the current section. All new instructions and data are added to this section.
+	#[must_use]
+	#[inline]
+	pub fn section(&self) -> CodeSection {
+		CodeSection::new(self.current_section)
+	}
+
+	/// Sets the current section. All new instructions and data are added to this section.
+	///
+	/// # Errors
+	///
+	/// Fails if the section is invalid or if there are unused prefixes or labels
+	///
+	/// # Arguments
+	///
+	/// * `section`: The new section, see [`text_section()`] and [`create_section()`]
+	///
+	/// [`text_section()`]: #method.text_section
+	/// [`create_section()`]: #method.create_section
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn set_section(&mut self, section: CodeSection) -> Result<(), IcedError> {
+		if section.index() >= self.sections.len() {
+			return Err(IcedError::new("Invalid section"));
+		}
+		if self.prefix_flags != 0 {
+			return Err(IcedError::new("Unused prefixes. Did you forget to add an instruction?"));
+		}
+		if !self.current_label.is_empty() {
+			return Err(IcedError::new("Unused label. Did you forget to add an instruction?"));
+		}
+		if self.defined_anon_label {
+			return Err(IcedError::new("Unused anonymous label. Did you forget to add an instruction?"));
+		}
+		self.current_section = section.index();
+		Ok(())
+	}
+
+	/// Sets the address of a section created by [`create_section()`]. By default, a section is placed after the previous section.
+	///
+	/// # Errors
+	///
+	/// Fails if the section is invalid or if it's the `.text` section. The address of the `.text` section is passed to [`assemble()`] and [`assemble_options()`].
+	///
+	/// # Arguments
+	///
+	/// * `section`: The section
+	/// * `ip`: Address of the section
+	///
+	/// [`create_section()`]: #method.create_section
+	/// [`assemble()`]: #method.assemble
+	/// [`assemble_options()`]: #method.assemble_options
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn set_section_ip(&mut self, section: CodeSection, ip: u64) -> Result<(), IcedError> {
+		if section.index() == 0 {
+			return Err(IcedError::new("The address of the `.text` section is passed to `assemble()`"));
+		}
+		if let Some(section) = self.sections.get_mut(section.index()) {
+			section.ip = Some(ip);
+			Ok(())
+		} else {
+			Err(IcedError::new("Invalid section"))
+		}
+	}
+
 	#[inline]
 	fn decl_data_verify_no_prefixes(&self) -> Result<(), IcedError> {
 		if self.prefix_flags != 0 {
@@ -895,64 +1089,188 @@ impl CodeAssembler {
 			return Err(IcedError::new("No prefixes are allowed"));
 		}
 
-		const MAX_NOP_LEN: usize = 9;
 		if size >= MAX_NOP_LEN {
-			let bytes = self.get_nop_bytes(MAX_NOP_LEN);
+			let bytes = get_nop_bytes(self.bitness(), MAX_NOP_LEN);
 			for _ in 0..size / MAX_NOP_LEN {
 				self.db(bytes)?;
 			}
 		}
 		match size % MAX_NOP_LEN {
 			0 => {}
-			remaining => self.db(self.get_nop_bytes(remaining))?,
+			remaining => self.db(get_nop_bytes(self.bitness(), remaining))?,
 		}
 
 		Ok(())
 	}
 
-	fn get_nop_bytes(&self, size: usize) -> &'static [u8] {
-		match size {
-			1 => &[0x90],                   // NOP
-			2 => &[0x66, 0x90],             // 66 NOP
-			3 => &[0x0F, 0x1F, 0x00],       // NOP dword ptr [eax] or NOP word ptr [bx+si]
-			4 => &[0x0F, 0x1F, 0x40, 0x00], // NOP dword ptr [eax + 00] or NOP word ptr [bx+si]
-			5 => {
-				if self.bitness() != 16 {
-					&[0x0F, 0x1F, 0x44, 0x00, 0x00] // NOP dword ptr [eax + eax*1 + 00]
-				} else {
-					&[0x0F, 0x1F, 0x80, 0x00, 0x00] // NOP word ptr[bx + si]
-				}
-			}
-			6 => {
-				if self.bitness() != 16 {
-					&[0x66, 0x0F, 0x1F, 0x44, 0x00, 0x00] // 66 NOP dword ptr [eax + eax*1 + 00]
-				} else {
-					&[0x66, 0x0F, 0x1F, 0x80, 0x00, 0x00] // NOP dword ptr [bx+si]
-				}
-			}
-			7 => {
-				if self.bitness() != 16 {
-					&[0x0F, 0x1F, 0x80, 0x00, 0x00, 0x00, 0x00] // NOP dword ptr [eax + 00000000]
-				} else {
-					&[0x67, 0x66, 0x0F, 0x1F, 0x44, 0x00, 0x00] // NOP dword ptr [eax+eax]
-				}
-			}
-			8 => {
-				if self.bitness() != 16 {
-					&[0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00] // NOP dword ptr [eax + eax*1 + 00000000]
-				} else {
-					&[0x67, 0x0F, 0x1F, 0x80, 0x00, 0x00, 0x00, 0x00] // NOP word ptr [eax]
-				}
-			}
-			9 => {
-				if self.bitness() != 16 {
-					&[0x66, 0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00] // 66 NOP dword ptr [eax + eax*1 + 00000000]
-				} else {
-					&[0x67, 0x0F, 0x1F, 0x84, 0x00, 0x00, 0x00, 0x00, 0x00] // NOP word ptr [eax+eax]
-				}
-			}
-			_ => unreachable!(),
+	/// Adds `NOP`s until the address of the next instruction is a multiple of `alignment`.
+	/// The padding is calculated by [`assemble()`] after all branches have been optimized.
+	///
+	/// # Errors
+	///
+	/// Fails if `alignment` isn't a power of 2 or if there are prefixes
+	///
+	/// # Arguments
+	///
+	/// * `alignment`: Alignment, a power of 2
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::*;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// a.int3()?;
+	/// a.align(8)?;
+	/// a.ret()?;
+	/// let bytes = a.assemble(0x1234_5678)?;
+	/// assert_eq!(bytes, b"\xCC\x0F\x1F\x80\x00\x00\x00\x00\xC3");
+	/// # Ok(())
+	/// # }
+	/// ```
+	///
+	/// [`assemble()`]: #method.assemble
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn align(&mut self, alignment: u32) -> Result<(), IcedError> {
+		if self.prefix_flags != 0 {
+			return Err(IcedError::new("align: No prefixes are allowed"));
 		}
+		if !alignment.is_power_of_two() {
+			return Err(IcedError::new("Alignment must be a power of 2"));
+		}
+		self.add_directive(BlockDirective::Align(alignment))
+	}
+
+	/// Adds the 32-bit address of each label
+	///
+	/// # Errors
+	///
+	/// Fails if a label is invalid or if there are prefixes. [`assemble()`] fails if an address doesn't fit in 32 bits.
+	///
+	/// # Arguments
+	///
+	/// * `labels`: The labels
+	///
+	/// [`assemble()`]: #method.assemble
+	#[inline]
+	pub fn dd_label(&mut self, labels: &[CodeLabel]) -> Result<(), IcedError> {
+		self.add_label_addresses(4, labels, None)
+	}
+
+	/// Adds the 64-bit address of each label
+	///
+	/// # Errors
+	///
+	/// Fails if a label is invalid or if there are prefixes
+	///
+	/// # Arguments
+	///
+	/// * `labels`: The labels
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::*;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// let mut target = a.create_label();
+	/// a.dq_label(&[target])?;
+	/// a.set_label(&mut target)?;
+	/// a.ret()?;
+	/// let bytes = a.assemble(0x1234_5678)?;
+	/// assert_eq!(bytes, b"\x80\x56\x34\x12\x00\x00\x00\x00\xC3");
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[inline]
+	pub fn dq_label(&mut self, labels: &[CodeLabel]) -> Result<(), IcedError> {
+		self.add_label_addresses(8, labels, None)
+	}
+
+	/// Adds the address of each label minus the address of `base` as 32-bit values, eg. a position independent jump table
+	///
+	/// # Errors
+	///
+	/// Fails if a label is invalid or if there are prefixes. [`assemble()`] fails if a value doesn't fit in 32 bits.
+	///
+	/// # Arguments
+	///
+	/// * `labels`: The labels
+	/// * `base`: The base label, eg. the start of the table
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::*;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// let mut table = a.create_label();
+	/// let mut case0 = a.create_label();
+	/// let mut case1 = a.create_label();
+	/// // ecx = case index
+	/// a.lea(rdx, ptr(table))?;
+	/// a.movsxd(rax, dword_ptr(rdx + rcx * 4))?;
+	/// a.add(rax, rdx)?;
+	/// a.jmp(rax)?;
+	/// a.align(4)?;
+	/// a.set_label(&mut table)?;
+	/// a.dd_label_rel(&[case0, case1], table)?;
+	/// a.set_label(&mut case0)?;
+	/// a.xor(eax, eax)?;
+	/// a.ret()?;
+	/// a.set_label(&mut case1)?;
+	/// a.mov(eax, 1)?;
+	/// a.ret()?;
+	/// let bytes = a.assemble(0x1234_5678)?;
+	/// assert_eq!(&bytes[16..24], b"\x08\x00\x00\x00\x0B\x00\x00\x00");
+	/// # Ok(())
+	/// # }
+	/// ```
+	///
+	/// [`assemble()`]: #method.assemble
+	#[inline]
+	pub fn dd_label_rel(&mut self, labels: &[CodeLabel], base: CodeLabel) -> Result<(), IcedError> {
+		self.add_label_addresses(4, labels, Some(base))
+	}
+
+	/// Adds the address of each label minus the address of `base` as 64-bit values
+	///
+	/// # Errors
+	///
+	/// Fails if a label is invalid or if there are prefixes
+	///
+	/// # Arguments
+	///
+	/// * `labels`: The labels
+	/// * `base`: The base label, eg. the start of the table
+	#[inline]
+	pub fn dq_label_rel(&mut self, labels: &[CodeLabel], base: CodeLabel) -> Result<(), IcedError> {
+		self.add_label_addresses(8, labels, Some(base))
+	}
+
+	fn add_label_addresses(&mut self, size: u32, labels: &[CodeLabel], base: Option<CodeLabel>) -> Result<(), IcedError> {
+		self.decl_data_verify_no_prefixes()?;
+		if labels.iter().chain(base.iter()).any(CodeLabel::is_empty) {
+			return Err(IcedError::new("Invalid label. Must be created by create_label()"));
+		}
+		for label in labels {
+			let directive = match base {
+				Some(base) => BlockDirective::RelativeAddress { size, target: label.id(), base: base.id() },
+				None => BlockDirective::Address { size, target: label.id() },
+			};
+			self.add_directive(directive)?;
+		}
+		Ok(())
+	}
+
+	fn add_directive(&mut self, directive: BlockDirective) -> Result<(), IcedError> {
+		let index = self.current().instructions.len();
+		self.add_instr(Instruction::with(Code::Zero_bytes))?;
+		self.current_mut().directives.push((index, directive));
+		Ok(())
 	}
 
 	/// Adds an instruction created by the decoder or by `Instruction::with*()` methods
@@ -1026,7 +1344,7 @@ impl CodeAssembler {
 			}
 		}
 
-		self.instructions.push(instruction);
+		self.current_mut().instructions.push(instruction);
 		self.current_label = CodeLabel::default();
 		self.defined_anon_label = false;
 		self.prefix_flags = PrefixFlags::NONE;
@@ -1037,12 +1355,16 @@ impl CodeAssembler {
 	///
 	/// # Errors
 	///
-	/// Fails if an error was detected (eg. an invalid instruction operand)
+	/// Fails if an error was detected (eg. an invalid instruction operand) or if a section created by [`create_section()`]
+	/// isn't empty. Call [`assemble_options()`] to get the bytes of all sections.
 	///
 	/// # Arguments
 	///
 	/// * `ip`: Base address of all instructions
 	///
+	/// [`create_section()`]: #method.create_section
+	/// [`assemble_options()`]: #method.assemble_options
+	///
 	/// # Examples
 	///
 	/// ```
@@ -1065,6 +1387,9 @@ impl CodeAssembler {
 	/// ```
 	#[inline]
 	pub fn assemble(&mut self, ip: u64) -> Result<Vec<u8>, IcedError> {
+		if self.sections.iter().skip(1).any(|section| !section.instructions.is_empty()) {
+			return Err(IcedError::new("There are other non-empty sections. Call `assemble_options()` to get all sections."));
+		}
 		Ok(self.assemble_options(ip, BlockEncoderOptions::NONE)?.inner.code_buffer)
 	}
 
@@ -1076,7 +1401,7 @@ impl CodeAssembler {
 	///
 	/// # Arguments
 	///
-	/// * `ip`: Base address of all instructions
+	/// * `ip`: Base address of the `.text` section
 	/// * `options`: [`BlockEncoderOptions`] flags
 	///
 	/// # Examples
@@ -1105,8 +1430,13 @@ impl CodeAssembler {
 	/// ```
 	///
 	/// [`BlockEncoderOptions`]: ../struct.BlockEncoderOptions.html
-	#[inline]
+	#[allow(clippy::missing_inline_in_public_items)]
 	pub fn assemble_options(&mut self, ip: u64, options: u32) -> Result<CodeAssemblerResult, IcedError> {
+		self.assemble_sections(ip, options, 0)
+	}
+
+	// `section_gap` is the minimum number of bytes between a section and the next section if the next section doesn't have a fixed address
+	pub(crate) fn assemble_sections(&mut self, ip: u64, options: u32, section_gap: u64) -> Result<CodeAssemblerResult, IcedError> {
 		if self.prefix_flags != 0 {
 			return Err(IcedError::new("Unused prefixes. Did you forget to add an instruction?"));
 		}
@@ -1120,9 +1450,105 @@ impl CodeAssembler {
 			return Err(IcedError::new("Unused anonymous fwd() label. Did you forget to call anonymous_label()?"));
 		}
 
-		let block = InstructionBlock::new(self.instructions(), ip);
-		let result = BlockEncoder::encode(self.bitness(), block, options)?;
-		Ok(CodeAssemblerResult { inner: result })
+		#[cfg(feature = "reg_alloc")]
+		let allocated = self
+			.sections
+			.iter()
+			.map(|section| self.register_allocator.allocate(&section.instructions, &section.directives))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		// The size of a section is only known after it's been encoded so if a section is placed after another
+		// section, re-encode everything until all sections fit. The sizes can only grow so this terminates.
+		let mut sizes = vec![0u64; self.sections.len()];
+		let results = loop {
+			let mut ips = Vec::with_capacity(self.sections.len());
+			let mut next_ip = ip;
+			for (section, &size) in self.sections.iter().zip(sizes.iter()) {
+				let section_ip = if ips.is_empty() {
+					ip
+				} else if let Some(section_ip) = section.ip {
+					section_ip
+				} else {
+					let mask = section.alignment as u64 - 1;
+					next_ip.wrapping_add(mask) & !mask
+				};
+				ips.push(section_ip);
+				next_ip = section_ip.wrapping_add(size).wrapping_add(section_gap);
+			}
+
+			let blocks: Vec<_> = self
+				.sections
+				.iter()
+				.enumerate()
+				.zip(ips.iter())
+				.map(|((index, section), &ip)| {
+					#[cfg(feature = "reg_alloc")]
+					if let Some(allocated) = &allocated[index] {
+						return InstructionBlock::with_directives(&allocated.instructions, ip, &allocated.directives);
+					}
+					#[cfg(not(feature = "reg_alloc"))]
+					let _ = index;
+					InstructionBlock::with_directives(&section.instructions, ip, &section.directives)
+				})
+				.collect();
+			// The results are sorted by address (stable sort)
+			let mut indexes: Vec<usize> = (0..ips.len()).collect();
+			indexes.sort_by_key(|&i| ips[i]);
+			let mut results: Vec<(usize, BlockEncoderResult)> =
+				indexes.into_iter().zip(BlockEncoder::encode_slice(self.bitness, &blocks, options)?).collect();
+			results.sort_unstable_by_key(|&(i, _)| i);
+
+			let mut updated = false;
+			for &(i, ref result) in &results {
+				let size = result.code_buffer.len() as u64;
+				if size > sizes[i] && self.sections.get(i + 1).map_or(false, |next| next.ip.is_none()) {
+					sizes[i] = size;
+					updated = true;
+				}
+			}
+			if !updated {
+				break results;
+			}
+		};
+
+		let mut ranges: Vec<(u64, u64, usize)> = results
+			.iter()
+			.filter(|(_, result)| !result.code_buffer.is_empty())
+			.map(|&(i, ref result)| (result.rip, result.rip.wrapping_add(result.code_buffer.len() as u64), i))
+			.collect();
+		ranges.sort_unstable();
+		for w in ranges.windows(2) {
+			if w[1].0 < w[0].1 {
+				return Err(IcedError::with_string(format!(
+					"Sections `{}` and `{}` overlap",
+					self.sections[w[0].2].name, self.sections[w[1].2].name
+				)));
+			}
+		}
+
+		#[cfg(feature = "reg_alloc")]
+		let mut results = results;
+		#[cfg(feature = "reg_alloc")]
+		for &mut (i, ref mut result) in &mut results {
+			if let Some(allocated) = &allocated[i] {
+				allocated.remap_result(result);
+			}
+		}
+		let mut results = results.into_iter().map(|(_, result)| result);
+		let inner = if let Some(inner) = results.next() { inner } else { return Err(IcedError::new("Internal error")) };
+		let sections = self
+			.sections
+			.iter()
+			.skip(1)
+			.zip(results)
+			.map(|(section, inner)| CodeSectionResult { name: section.name.clone(), kind: section.kind, alignment: section.alignment, inner })
+			.collect();
+		Ok(CodeAssemblerResult {
+			inner,
+			sections,
+			#[cfg(feature = "reg_alloc")]
+			spill_size: allocated.iter().flatten().map(|allocated| allocated.spill_size).max().unwrap_or(0),
+		})
 	}
 
 	/// Gets the bitness (16, 32 or 64)
@@ -1180,6 +1606,319 @@ impl CodeAssembler {
 		}
 	}
 
+	/// Creates a virtual 64-bit general purpose register. It can be used like any other register and [`assemble()`] assigns a
+	/// real register to it. Only 64-bit code supports virtual registers.
+	///
+	/// The register allocator uses the calling convention in [`register_allocator_options()`] to find out which registers are
+	/// clobbered by a `CALL` and which registers are read by a `CALL` (all argument registers) and a `RET` (all return value
+	/// registers written by the code). Registers you use explicitly are never clobbered, and `RSP` is never allocated.
+	/// Callee-saved registers are only allocated if they're in [`RegisterAllocatorOptions::saved_registers()`].
+	///
+	/// If there are too many live virtual registers, some of them are stored in the spill area
+	/// (see [`RegisterAllocatorOptions::set_spill_area()`]) and loaded into and stored from a free register around each
+	/// instruction that uses them. Use [`CodeAssemblerResult::spill_size`] to get the number of bytes used.
+	///
+	/// Virtual registers can't be used as a `VSIB` index register or together with `AH`, `BH`, `CH` or `DH` in the same instruction.
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`assemble()`]: #method.assemble
+	/// [`register_allocator_options()`]: #method.register_allocator_options
+	/// [`RegisterAllocatorOptions::saved_registers()`]: struct.RegisterAllocatorOptions.html#method.saved_registers
+	/// [`RegisterAllocatorOptions::set_spill_area()`]: struct.RegisterAllocatorOptions.html#method.set_spill_area
+	/// [`CodeAssemblerResult::spill_size`]: struct.CodeAssemblerResult.html#structfield.spill_size
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::*;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// let sum = a.create_virtual_gpr64()?;
+	/// let tmp = a.create_virtual_gpr64()?;
+	/// // rax = rdi + rsi * 2
+	/// a.mov(sum, rdi)?;
+	/// a.lea(tmp, ptr(rsi + rsi))?;
+	/// a.add(sum, tmp)?;
+	/// a.mov(rax, sum)?;
+	/// a.ret()?;
+	///
+	/// // mov r10,rdi / lea r11,[rsi+rsi] / add r10,r11 / mov rax,r10 / ret
+	/// let bytes = a.assemble(0x1234_5678)?;
+	/// assert_eq!(bytes, b"\x49\x89\xFA\x4C\x8D\x1C\x36\x4D\x01\xDA\x4C\x89\xD0\xC3");
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_gpr64(&mut self) -> Result<AsmRegister64, IcedError> {
+		Ok(AsmRegister64::new(self.create_virtual_register(VirtualRegisterKind::Gpr64)?))
+	}
+
+	/// Creates a virtual 32-bit general purpose register, see [`create_virtual_gpr64()`]
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_gpr32(&mut self) -> Result<AsmRegister32, IcedError> {
+		Ok(AsmRegister32::new(self.create_virtual_register(VirtualRegisterKind::Gpr32)?))
+	}
+
+	/// Creates a virtual 16-bit general purpose register, see [`create_virtual_gpr64()`]
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_gpr16(&mut self) -> Result<AsmRegister16, IcedError> {
+		Ok(AsmRegister16::new(self.create_virtual_register(VirtualRegisterKind::Gpr16)?))
+	}
+
+	/// Creates a virtual 8-bit general purpose register, see [`create_virtual_gpr64()`]
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_gpr8(&mut self) -> Result<AsmRegister8, IcedError> {
+		Ok(AsmRegister8::new(self.create_virtual_register(VirtualRegisterKind::Gpr8)?))
+	}
+
+	/// Creates a virtual `XMM` register, see [`create_virtual_gpr64()`]. Only `XMM0`-`XMM15` are allocated.
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_xmm(&mut self) -> Result<AsmRegisterXmm, IcedError> {
+		Ok(AsmRegisterXmm::new(self.create_virtual_register(VirtualRegisterKind::Xmm)?))
+	}
+
+	/// Creates a virtual `YMM` register, see [`create_virtual_gpr64()`]. Only `YMM0`-`YMM15` are allocated.
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_ymm(&mut self) -> Result<AsmRegisterYmm, IcedError> {
+		Ok(AsmRegisterYmm::new(self.create_virtual_register(VirtualRegisterKind::Ymm)?))
+	}
+
+	/// Creates a virtual `ZMM` register, see [`create_virtual_gpr64()`]. Only `ZMM0`-`ZMM15` are allocated.
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_zmm(&mut self) -> Result<AsmRegisterZmm, IcedError> {
+		Ok(AsmRegisterZmm::new(self.create_virtual_register(VirtualRegisterKind::Zmm)?))
+	}
+
+	/// Creates a virtual opmask register, see [`create_virtual_gpr64()`]
+	///
+	/// # Errors
+	///
+	/// Fails if it's not 64-bit code or if too many virtual registers have been created
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn create_virtual_k(&mut self) -> Result<AsmRegisterK, IcedError> {
+		Ok(AsmRegisterK::new(self.create_virtual_register(VirtualRegisterKind::K)?))
+	}
+
+	#[cfg(feature = "reg_alloc")]
+	fn create_virtual_register(&mut self, kind: VirtualRegisterKind) -> Result<Register, IcedError> {
+		if self.bitness != 64 {
+			return Err(IcedError::new("Virtual registers can only be used in 64-bit code"));
+		}
+		self.register_allocator.create(kind)
+	}
+
+	/// Gets the register allocator options used to allocate virtual registers, see [`create_virtual_gpr64()`]
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	#[cfg(feature = "reg_alloc")]
+	#[must_use]
+	#[inline]
+	pub fn register_allocator_options(&self) -> &RegisterAllocatorOptions {
+		self.register_allocator.options()
+	}
+
+	/// Sets the register allocator options used to allocate virtual registers, see [`create_virtual_gpr64()`]
+	///
+	/// [`create_virtual_gpr64()`]: #method.create_virtual_gpr64
+	///
+	/// # Arguments
+	///
+	/// * `options`: New options
+	#[cfg(feature = "reg_alloc")]
+	#[inline]
+	pub fn set_register_allocator_options(&mut self, options: RegisterAllocatorOptions) {
+		self.register_allocator.set_options(options);
+	}
+
+	/// Creates a stack frame. It saves the callee-saved registers, sets up the frame pointer and allocates the
+	/// stack space used by local variables, saved `XMM` registers and (64-bit only) the stack arguments of called functions
+	/// including the Microsoft x64 shadow space. The stack pointer is 16-byte aligned after the prologue.
+	///
+	/// The returned [`Frame`] is passed to [`epilogue()`] and [`call_with_args()`], and it can create the Microsoft x64
+	/// unwind info of the function (see [`Frame::unwind_info()`]).
+	///
+	/// Large frames aren't probed, call a stack probe function first if the frame is bigger than a page.
+	///
+	/// # Errors
+	///
+	/// Fails if the calling convention can't be used in this code (wrong bitness), if a saved register isn't a callee-saved
+	/// register or if it's saved twice.
+	///
+	/// # Arguments
+	///
+	/// * `options`: Frame options
+	///
+	/// [`Frame`]: struct.Frame.html
+	/// [`epilogue()`]: #method.epilogue
+	/// [`call_with_args()`]: #method.call_with_args
+	/// [`Frame::unwind_info()`]: struct.Frame.html#method.unwind_info
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::*;
+	/// use iced_x86::Register;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// let mut options = FrameOptions::new(CallingConvention::Microsoft64);
+	/// options.set_saved_registers(&[Register::RBX]);
+	/// options.set_local_size(0x20);
+	///
+	/// let frame = a.prologue(&options)?;
+	/// a.mov(qword_ptr(rsp + frame.locals_offset()), rcx)?;
+	/// a.epilogue(&frame)?;
+	///
+	/// // push rbx / sub rsp,40h / mov [rsp+20h],rcx / add rsp,40h / pop rbx / ret
+	/// let bytes = a.assemble(0x1234_5678)?;
+	/// assert_eq!(bytes, b"\x53\x48\x83\xEC\x40\x48\x89\x4C\x24\x20\x48\x83\xC4\x40\x5B\xC3");
+	/// // Version 1, prologue size, 2 unwind codes, no frame register, UWOP_ALLOC_SMALL, UWOP_PUSH_NONVOL
+	/// assert_eq!(frame.unwind_info()?, b"\x01\x05\x02\x00\x05\x72\x01\x30");
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[cfg(feature = "abi")]
+	#[inline]
+	pub fn prologue(&mut self, options: &FrameOptions) -> Result<Frame, IcedError> {
+		abi::prologue(self, options)
+	}
+
+	/// Destroys a stack frame created by [`prologue()`]: restores the saved registers and returns to the caller.
+	/// `stdcall` and `fastcall` functions also pop their stack arguments (see [`FrameOptions::argument_stack_size()`]).
+	///
+	/// The epilogue can be added more than once, eg. once per return path.
+	///
+	/// # Errors
+	///
+	/// Fails if the frame was created by code with another bitness
+	///
+	/// # Arguments
+	///
+	/// * `frame`: Frame returned by [`prologue()`]
+	///
+	/// [`prologue()`]: #method.prologue
+	/// [`FrameOptions::argument_stack_size()`]: struct.FrameOptions.html#method.argument_stack_size
+	#[cfg(feature = "abi")]
+	#[inline]
+	pub fn epilogue(&mut self, frame: &Frame) -> Result<(), IcedError> {
+		abi::epilogue(self, frame)
+	}
+
+	/// Moves the arguments to the registers and stack slots used by the calling convention and calls a function.
+	/// 32-bit code pushes the stack arguments and pops them after the call if the caller must pop them.
+	///
+	/// Register arguments are moved as if all moves happened at the same time, eg. the first two arguments can be
+	/// `RSI` and `RDI` (System V AMD64). The target must not be an argument register. Variadic System V AMD64 functions
+	/// also need the number of used vector registers in `AL`, which must be set by the caller.
+	///
+	/// Integer arguments use [`CallArgument::Register`] (32-bit or 64-bit registers) and [`CallArgument::Immediate`],
+	/// `float` and `double` arguments use [`CallArgument::Float32`] and [`CallArgument::Float64`].
+	///
+	/// # Errors
+	///
+	/// Fails if the calling convention can't be used in this code, if it's a leaf frame, if the frame has too little space
+	/// for the stack arguments (see [`FrameOptions::set_outgoing_stack_size()`]) or if an argument is invalid.
+	///
+	/// # Arguments
+	///
+	/// * `frame`: Frame returned by [`prologue()`]
+	/// * `calling_convention`: Calling convention of the called function
+	/// * `target`: Call target, eg. a label, a register or a memory operand
+	/// * `args`: Arguments
+	///
+	/// [`prologue()`]: #method.prologue
+	/// [`CallArgument::Register`]: enum.CallArgument.html#variant.Register
+	/// [`CallArgument::Immediate`]: enum.CallArgument.html#variant.Immediate
+	/// [`CallArgument::Float32`]: enum.CallArgument.html#variant.Float32
+	/// [`CallArgument::Float64`]: enum.CallArgument.html#variant.Float64
+	/// [`FrameOptions::set_outgoing_stack_size()`]: struct.FrameOptions.html#method.set_outgoing_stack_size
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::*;
+	/// use iced_x86::Register;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// let frame = a.prologue(&FrameOptions::new(CallingConvention::SystemV64))?;
+	/// // f(rsi, rdi, 5)
+	/// let args = [CallArgument::Register(Register::RSI), CallArgument::Register(Register::RDI), CallArgument::Immediate(5)];
+	/// a.call_with_args(&frame, CallingConvention::SystemV64, rax, &args)?;
+	/// a.epilogue(&frame)?;
+	///
+	/// // sub rsp,8 / xchg rdi,rsi / mov edx,5 / call rax / add rsp,8 / ret
+	/// let bytes = a.assemble(0x1234_5678)?;
+	/// assert_eq!(bytes, b"\x48\x83\xEC\x08\x48\x87\xF7\xBA\x05\x00\x00\x00\xFF\xD0\x48\x83\xC4\x08\xC3");
+	/// # Ok(())
+	/// # }
+	/// ```
+	#[cfg(feature = "abi")]
+	#[inline]
+	pub fn call_with_args<T>(
+		&mut self, frame: &Frame, calling_convention: CallingConvention, target: T, args: &[CallArgument],
+	) -> Result<(), IcedError>
+	where
+		Self: CodeAsmCall<T>,
+	{
+		let pop_size = abi::move_arguments(self, frame, calling_convention, args)?;
+		<Self as CodeAsmCall<T>>::call(self, target)?;
+		if pop_size != 0 {
+			abi::pop_arguments(self, pop_size)?;
+		}
+		Ok(())
+	}
+
 	#[inline]
 	pub(crate) fn instruction_prefer_vex(&self) -> bool {
 		if (self.prefix_flags & (PrefixFlags::PREFER_VEX | PrefixFlags::PREFER_EVEX)) != 0 {
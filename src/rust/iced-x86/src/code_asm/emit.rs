@@ -0,0 +1,466 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::op_state::{CodeAsmOpState, MemoryOperandSize};
+use crate::code_asm::operand::AsmOperandKind;
+use crate::code_asm::*;
+use crate::iced_constants::IcedConstants;
+use crate::instruction_internal;
+use crate::{Code, Encoder, EncodingKind, Instruction, MemorySize, Mnemonic, OpCodeInfo, OpCodeOperandKind, OpKind, Register, RoundingControl};
+use alloc::format;
+use alloc::vec::Vec;
+use lazy_static::lazy_static;
+
+lazy_static! {
+	// All instructions with the same mnemonic, indexed by mnemonic
+	static ref CODES_BY_MNEMONIC: Vec<Vec<Code>> = {
+		let mut codes = vec![Vec::new(); IcedConstants::MNEMONIC_ENUM_COUNT];
+		for code in Code::values() {
+			let op_code = code.op_code();
+			if op_code.is_instruction() && op_code.encoding() != EncodingKind::MVEX {
+				codes[code.mnemonic() as usize].push(code);
+			}
+		}
+		codes
+	};
+}
+
+struct Candidate {
+	instruction: Instruction,
+	op_code: &'static OpCodeInfo,
+	// Needs an operand size prefix (66h or REX.W) that isn't implied by the operands
+	size_penalty: bool,
+	length: usize,
+	is_short_branch: bool,
+	is_near_branch: bool,
+}
+
+impl CodeAssembler {
+	/// Adds an instruction created from a mnemonic and its operands.
+	///
+	/// This is mostly useful if the mnemonic and operands aren't known at compile time (eg. language
+	/// bindings or scripts). Prefer calling the instruction methods (eg. [`mov()`]) if possible.
+	///
+	/// The same rules as the instruction methods are used to select the [`Code`] value:
+	///
+	/// - The shortest encoding is used if more than one [`Code`] value matches the operands
+	/// - VEX or EVEX encoding is selected by [`vex()`], [`evex()`] and [`prefer_vex()`]
+	/// - Short or near branches are selected by [`prefer_short_branch()`]
+	/// - Implicit string instruction operands (eg. `movsb`'s operands) must not be passed in
+	///
+	/// This method is only available if the `op_code_info` feature is enabled.
+	///
+	/// # Errors
+	///
+	/// Fails if no instruction matches the operands or if the memory operand size is ambiguous
+	/// (use eg. [`AsmOperand::with_memory_size()`] or [`dword_ptr()`] to set the size)
+	///
+	/// # Arguments
+	///
+	/// * `mnemonic`: Mnemonic
+	/// * `operands`: Operands
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::code_asm::*;
+	/// use iced_x86::Mnemonic;
+	///
+	/// # fn main() -> Result<(), IcedError> {
+	/// let mut a = CodeAssembler::new(64)?;
+	/// let label = a.create_label();
+	/// a.emit(Mnemonic::Mov, &[rax.into(), qword_ptr(rcx + rdx * 8 + 0x10).into()])?;
+	/// a.emit(Mnemonic::Add, &[eax.into(), 1.into()])?;
+	/// a.emit(Mnemonic::Vaddps, &[zmm0.k1().z().into(), zmm1.into(), dword_bcst(rax).into()])?;
+	/// a.emit(Mnemonic::Jne, &[label.into()])?;
+	/// assert_eq!(a.instructions().len(), 4);
+	/// # Ok(())
+	/// # }
+	/// ```
+	///
+	/// [`mov()`]: trait.CodeAsmMov.html
+	/// [`Code`]: ../enum.Code.html
+	/// [`vex()`]: #method.vex
+	/// [`evex()`]: #method.evex
+	/// [`prefer_vex()`]: #method.prefer_vex
+	/// [`prefer_short_branch()`]: #method.prefer_short_branch
+	/// [`AsmOperand::with_memory_size()`]: struct.AsmOperand.html#method.with_memory_size
+	/// [`dword_ptr()`]: fn.dword_ptr.html
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn emit(&mut self, mnemonic: Mnemonic, operands: &[AsmOperand]) -> Result<(), IcedError> {
+		let state = operands.iter().fold(CodeAsmOpState::new(), |state, op| state.merge(op.full_state()));
+		let unsized_memory = operands.iter().any(|op| match &op.kind {
+			AsmOperandKind::Memory(mem) => mem.size() == MemoryOperandSize::None,
+			_ => false,
+		});
+
+		let mut best: Option<Candidate> = None;
+		let mut ambiguous_size = false;
+		for &code in &CODES_BY_MNEMONIC[mnemonic as usize] {
+			let candidate = if let Some(candidate) = self.create_candidate(code.op_code(), operands, state) {
+				candidate
+			} else {
+				continue;
+			};
+			best = match best {
+				None => Some(candidate),
+				Some(prev) => {
+					if unsized_memory && memory_size(prev.op_code) != memory_size(candidate.op_code) {
+						ambiguous_size = true;
+					}
+					Some(if self.is_better(&candidate, &prev) { candidate } else { prev })
+				}
+			};
+		}
+
+		match best {
+			Some(_) if ambiguous_size => {
+				Err(IcedError::with_string(format!("{}: ambiguous memory operand size, use eg. `dword_ptr()`", mnemonic_name(mnemonic))))
+			}
+			Some(candidate) => self.add_instr_with_state(candidate.instruction, state),
+			None => Err(IcedError::with_string(format!("{}: invalid operands", mnemonic_name(mnemonic)))),
+		}
+	}
+
+	fn is_better(&self, candidate: &Candidate, prev: &Candidate) -> bool {
+		let prefer_vex = self.instruction_prefer_vex();
+		let vex_evex_rank = |c: &Candidate| match c.op_code.encoding() {
+			EncodingKind::VEX => !prefer_vex as u32,
+			EncodingKind::EVEX => prefer_vex as u32,
+			_ => 0,
+		};
+		let prefer_short_branch = self.prefer_short_branch();
+		let branch_rank = |c: &Candidate| if prefer_short_branch { c.is_near_branch as u32 } else { c.is_short_branch as u32 };
+		let key = |c: &Candidate| (vex_evex_rank(c), branch_rank(c), c.size_penalty, c.length);
+		key(candidate) < key(prev)
+	}
+
+	fn create_candidate(&self, op_code: &'static OpCodeInfo, operands: &[AsmOperand], state: CodeAsmOpState) -> Option<Candidate> {
+		let bitness = self.bitness();
+		if !op_code.is_available_in_mode(bitness) {
+			return None;
+		}
+		if (state.op_mask() != Register::None && !op_code.can_use_op_mask_register())
+			|| (state.zeroing_masking() && !op_code.can_use_zeroing_masking())
+			|| (state.suppress_all_exceptions() && !op_code.can_suppress_all_exceptions())
+			|| (state.rounding_control() != RoundingControl::None && !op_code.can_use_rounding_control())
+			|| (state.is_broadcast() && !op_code.can_broadcast())
+		{
+			return None;
+		}
+
+		let op_kinds = op_code.op_kinds();
+		let has_implicit_memory = op_kinds.iter().any(|&kind| implicit_memory_op_kind(kind, bitness).is_some());
+		let mut instruction = Instruction::default();
+		instruction.set_code(op_code.code());
+		let mut is_short_branch = false;
+		let mut is_near_branch = false;
+		let mut user_operands = operands.iter();
+		for (operand, &kind) in op_kinds.iter().enumerate() {
+			let operand = operand as u32;
+			if let Some(op_kind) = implicit_memory_op_kind(kind, bitness) {
+				instruction.try_set_op_kind(operand, op_kind).ok()?;
+				if kind == OpCodeOperandKind::seg_rBX_al {
+					instruction.set_memory_base(match bitness {
+						64 => Register::RBX,
+						32 => Register::EBX,
+						_ => Register::BX,
+					});
+					instruction.set_memory_index(Register::AL);
+					instruction.set_memory_index_scale(1);
+				}
+				continue;
+			}
+			if has_implicit_memory {
+				if let Some(register) = fixed_register(kind) {
+					instruction.try_set_op_register(operand, register).ok()?;
+					continue;
+				}
+			}
+
+			let user_operand = user_operands.next()?;
+			match user_operand.kind {
+				AsmOperandKind::Register(register) => {
+					if !is_register_op_kind(kind, register) {
+						return None;
+					}
+					instruction.try_set_op_kind(operand, OpKind::Register).ok()?;
+					instruction.try_set_op_register(operand, register).ok()?;
+				}
+				AsmOperandKind::Memory(mem) => {
+					if !is_memory_op_kind(kind, &mem) || !is_valid_memory_size(op_code, &mem) {
+						return None;
+					}
+					let mem = mem.to_memory_operand(bitness);
+					instruction.try_set_op_kind(operand, OpKind::Memory).ok()?;
+					instruction.set_memory_base(mem.base);
+					instruction.set_memory_index(mem.index);
+					instruction.set_memory_index_scale(mem.scale);
+					instruction.set_memory_displ_size(mem.displ_size);
+					instruction.set_memory_displacement64(mem.displacement as u64);
+					instruction.set_is_broadcast(mem.is_broadcast);
+					instruction.set_segment_prefix(mem.segment_prefix);
+				}
+				AsmOperandKind::Immediate(value) => {
+					if is_branch_op_kind(kind) {
+						init_branch(&mut instruction, kind, operand, value as u64, bitness)?;
+					} else {
+						if !is_immediate_op_kind(kind) || (kind == OpCodeOperandKind::imm8_const_1 && value != 1) {
+							return None;
+						}
+						instruction_internal::initialize_signed_immediate(&mut instruction, operand as usize, value).ok()?;
+					}
+				}
+				AsmOperandKind::ImmediateU64(value) => {
+					if is_branch_op_kind(kind) {
+						init_branch(&mut instruction, kind, operand, value, bitness)?;
+					} else {
+						if !is_immediate_op_kind(kind) || (kind == OpCodeOperandKind::imm8_const_1 && value != 1) {
+							return None;
+						}
+						instruction_internal::initialize_unsigned_immediate(&mut instruction, operand as usize, value).ok()?;
+					}
+				}
+				AsmOperandKind::Label(label) => {
+					if label.is_empty() || !is_branch_op_kind(kind) {
+						return None;
+					}
+					init_branch(&mut instruction, kind, operand, label.id(), bitness)?;
+				}
+			}
+			match kind {
+				OpCodeOperandKind::br16_1 | OpCodeOperandKind::br32_1 | OpCodeOperandKind::br64_1 => is_short_branch = true,
+				OpCodeOperandKind::br16_2 | OpCodeOperandKind::br32_4 | OpCodeOperandKind::br64_4 => is_near_branch = true,
+				_ => {}
+			}
+		}
+		if user_operands.next().is_some() {
+			return None;
+		}
+
+		// The target could be too far away so encode the branch with a target that's always reachable.
+		// The BlockEncoder fixes the branch later if needed.
+		let mut tmp = instruction;
+		if op_kinds.iter().any(|&kind| is_branch_op_kind(kind)) {
+			tmp.set_near_branch64(0);
+		}
+		if state.op_mask() != Register::None {
+			tmp.set_op_mask(state.op_mask());
+		}
+		tmp.set_zeroing_masking(state.zeroing_masking());
+		tmp.set_suppress_all_exceptions(state.suppress_all_exceptions());
+		tmp.set_rounding_control(state.rounding_control());
+		let mut encoder = Encoder::new(bitness);
+		let length = encoder.encode(&tmp, 0).ok()?;
+
+		let size_penalty = match op_code.operand_size() {
+			0 => false,
+			operand_size => {
+				let default_size = if bitness == 64 && !(op_code.default_op_size64() || op_code.force_op_size64()) { 32 } else { bitness };
+				operand_size != default_size
+			}
+		};
+
+		Some(Candidate { instruction, op_code, size_penalty, length, is_short_branch, is_near_branch })
+	}
+}
+
+fn mnemonic_name(mnemonic: Mnemonic) -> alloc::string::String {
+	format!("{:?}", mnemonic).to_lowercase()
+}
+
+fn init_branch(instruction: &mut Instruction, kind: OpCodeOperandKind, operand: u32, target: u64, bitness: u32) -> Option<()> {
+	let op_kind = match kind {
+		// Same as Instruction::with_xbegin()
+		OpCodeOperandKind::xbegin_2 | OpCodeOperandKind::xbegin_4 => {
+			if bitness == 64 {
+				OpKind::NearBranch64
+			} else {
+				OpKind::NearBranch32
+			}
+		}
+		_ => instruction_internal::get_near_branch_op_kind(instruction.code(), operand as usize).ok()?,
+	};
+	instruction.try_set_op_kind(operand, op_kind).ok()?;
+	instruction.set_near_branch64(target);
+	Some(())
+}
+
+fn memory_size(op_code: &OpCodeInfo) -> usize {
+	op_code.memory_size().size()
+}
+
+fn is_valid_memory_size(op_code: &OpCodeInfo, mem: &AsmMemoryOperand) -> bool {
+	let size = match mem.size() {
+		MemoryOperandSize::None => return true,
+		MemoryOperandSize::Byte => 1,
+		MemoryOperandSize::Word => 2,
+		MemoryOperandSize::Dword => 4,
+		MemoryOperandSize::Qword => 8,
+		MemoryOperandSize::Tbyte => 10,
+		MemoryOperandSize::Fword => 6,
+		MemoryOperandSize::Xword => 16,
+		MemoryOperandSize::Yword => 32,
+		MemoryOperandSize::Zword => 64,
+	};
+	if mem.is_broadcast() {
+		op_code.broadcast_memory_size().size() == size
+	} else {
+		let memory_size = op_code.memory_size();
+		memory_size == MemorySize::Unknown || memory_size.size() == size
+	}
+}
+
+fn implicit_memory_op_kind(kind: OpCodeOperandKind, bitness: u32) -> Option<OpKind> {
+	Some(match (kind, bitness) {
+		(OpCodeOperandKind::seg_rSI, 64) => OpKind::MemorySegRSI,
+		(OpCodeOperandKind::seg_rSI, 32) => OpKind::MemorySegESI,
+		(OpCodeOperandKind::seg_rSI, _) => OpKind::MemorySegSI,
+		(OpCodeOperandKind::es_rDI, 64) => OpKind::MemoryESRDI,
+		(OpCodeOperandKind::es_rDI, 32) => OpKind::MemoryESEDI,
+		(OpCodeOperandKind::es_rDI, _) => OpKind::MemoryESDI,
+		(OpCodeOperandKind::seg_rDI, 64) => OpKind::MemorySegRDI,
+		(OpCodeOperandKind::seg_rDI, 32) => OpKind::MemorySegEDI,
+		(OpCodeOperandKind::seg_rDI, _) => OpKind::MemorySegDI,
+		(OpCodeOperandKind::seg_rBX_al, _) => OpKind::Memory,
+		_ => return None,
+	})
+}
+
+fn fixed_register(kind: OpCodeOperandKind) -> Option<Register> {
+	Some(match kind {
+		OpCodeOperandKind::al => Register::AL,
+		OpCodeOperandKind::ax => Register::AX,
+		OpCodeOperandKind::eax => Register::EAX,
+		OpCodeOperandKind::rax => Register::RAX,
+		OpCodeOperandKind::dx => Register::DX,
+		_ => return None,
+	})
+}
+
+fn is_register_op_kind(kind: OpCodeOperandKind, register: Register) -> bool {
+	match kind {
+		OpCodeOperandKind::r8_or_mem | OpCodeOperandKind::r8_reg | OpCodeOperandKind::r8_opcode => register.is_gpr8(),
+		OpCodeOperandKind::r16_or_mem
+		| OpCodeOperandKind::r16_reg
+		| OpCodeOperandKind::r16_reg_mem
+		| OpCodeOperandKind::r16_rm
+		| OpCodeOperandKind::r16_opcode => register.is_gpr16(),
+		OpCodeOperandKind::r32_or_mem
+		| OpCodeOperandKind::r32_or_mem_mpx
+		| OpCodeOperandKind::r32_reg
+		| OpCodeOperandKind::r32_reg_mem
+		| OpCodeOperandKind::r32_rm
+		| OpCodeOperandKind::r32_opcode
+		| OpCodeOperandKind::r32_vvvv => register.is_gpr32(),
+		OpCodeOperandKind::r64_or_mem
+		| OpCodeOperandKind::r64_or_mem_mpx
+		| OpCodeOperandKind::r64_reg
+		| OpCodeOperandKind::r64_reg_mem
+		| OpCodeOperandKind::r64_rm
+		| OpCodeOperandKind::r64_opcode
+		| OpCodeOperandKind::r64_vvvv => register.is_gpr64(),
+		OpCodeOperandKind::seg_reg => register.is_segment_register(),
+		OpCodeOperandKind::k_or_mem | OpCodeOperandKind::k_reg | OpCodeOperandKind::kp1_reg | OpCodeOperandKind::k_rm | OpCodeOperandKind::k_vvvv => {
+			register.is_k()
+		}
+		OpCodeOperandKind::mm_or_mem | OpCodeOperandKind::mm_reg | OpCodeOperandKind::mm_rm => register.is_mm(),
+		OpCodeOperandKind::xmm_or_mem
+		| OpCodeOperandKind::xmm_reg
+		| OpCodeOperandKind::xmm_rm
+		| OpCodeOperandKind::xmm_vvvv
+		| OpCodeOperandKind::xmmp3_vvvv
+		| OpCodeOperandKind::xmm_is4
+		| OpCodeOperandKind::xmm_is5 => register.is_xmm(),
+		OpCodeOperandKind::ymm_or_mem
+		| OpCodeOperandKind::ymm_reg
+		| OpCodeOperandKind::ymm_rm
+		| OpCodeOperandKind::ymm_vvvv
+		| OpCodeOperandKind::ymm_is4
+		| OpCodeOperandKind::ymm_is5 => register.is_ymm(),
+		OpCodeOperandKind::zmm_or_mem
+		| OpCodeOperandKind::zmm_reg
+		| OpCodeOperandKind::zmm_rm
+		| OpCodeOperandKind::zmm_vvvv
+		| OpCodeOperandKind::zmmp3_vvvv => register.is_zmm(),
+		OpCodeOperandKind::bnd_or_mem_mpx | OpCodeOperandKind::bnd_reg => register.is_bnd(),
+		OpCodeOperandKind::cr_reg => register.is_cr(),
+		OpCodeOperandKind::dr_reg => register.is_dr(),
+		OpCodeOperandKind::tr_reg => register.is_tr(),
+		OpCodeOperandKind::tmm_reg | OpCodeOperandKind::tmm_rm | OpCodeOperandKind::tmm_vvvv => register.is_tmm(),
+		OpCodeOperandKind::sti_opcode => register.is_st(),
+		OpCodeOperandKind::es => register == Register::ES,
+		OpCodeOperandKind::cs => register == Register::CS,
+		OpCodeOperandKind::ss => register == Register::SS,
+		OpCodeOperandKind::ds => register == Register::DS,
+		OpCodeOperandKind::fs => register == Register::FS,
+		OpCodeOperandKind::gs => register == Register::GS,
+		OpCodeOperandKind::al => register == Register::AL,
+		OpCodeOperandKind::cl => register == Register::CL,
+		OpCodeOperandKind::ax => register == Register::AX,
+		OpCodeOperandKind::dx => register == Register::DX,
+		OpCodeOperandKind::eax => register == Register::EAX,
+		OpCodeOperandKind::rax => register == Register::RAX,
+		OpCodeOperandKind::st0 => register == Register::ST0,
+		_ => false,
+	}
+}
+
+fn is_memory_op_kind(kind: OpCodeOperandKind, mem: &AsmMemoryOperand) -> bool {
+	let index = mem.index();
+	match kind {
+		OpCodeOperandKind::mem_offs => mem.is_displacement_only(),
+		OpCodeOperandKind::mem_vsib32x | OpCodeOperandKind::mem_vsib64x => index.is_xmm(),
+		OpCodeOperandKind::mem_vsib32y | OpCodeOperandKind::mem_vsib64y => index.is_ymm(),
+		OpCodeOperandKind::mem_vsib32z | OpCodeOperandKind::mem_vsib64z => index.is_zmm(),
+		OpCodeOperandKind::mem
+		| OpCodeOperandKind::mem_mpx
+		| OpCodeOperandKind::mem_mib
+		| OpCodeOperandKind::sibmem
+		| OpCodeOperandKind::r8_or_mem
+		| OpCodeOperandKind::r16_or_mem
+		| OpCodeOperandKind::r32_or_mem
+		| OpCodeOperandKind::r32_or_mem_mpx
+		| OpCodeOperandKind::r64_or_mem
+		| OpCodeOperandKind::r64_or_mem_mpx
+		| OpCodeOperandKind::mm_or_mem
+		| OpCodeOperandKind::xmm_or_mem
+		| OpCodeOperandKind::ymm_or_mem
+		| OpCodeOperandKind::zmm_or_mem
+		| OpCodeOperandKind::bnd_or_mem_mpx
+		| OpCodeOperandKind::k_or_mem => !index.is_vector_register(),
+		_ => false,
+	}
+}
+
+fn is_immediate_op_kind(kind: OpCodeOperandKind) -> bool {
+	matches!(
+		kind,
+		OpCodeOperandKind::imm4_m2z
+			| OpCodeOperandKind::imm8
+			| OpCodeOperandKind::imm8_const_1
+			| OpCodeOperandKind::imm8sex16
+			| OpCodeOperandKind::imm8sex32
+			| OpCodeOperandKind::imm8sex64
+			| OpCodeOperandKind::imm16
+			| OpCodeOperandKind::imm32
+			| OpCodeOperandKind::imm32sex64
+			| OpCodeOperandKind::imm64
+	)
+}
+
+fn is_branch_op_kind(kind: OpCodeOperandKind) -> bool {
+	matches!(
+		kind,
+		OpCodeOperandKind::br16_1
+			| OpCodeOperandKind::br32_1
+			| OpCodeOperandKind::br64_1
+			| OpCodeOperandKind::br16_2
+			| OpCodeOperandKind::br32_4
+			| OpCodeOperandKind::br64_4
+			| OpCodeOperandKind::xbegin_2
+			| OpCodeOperandKind::xbegin_4
+			| OpCodeOperandKind::brdisp_2
+			| OpCodeOperandKind::brdisp_4
+	)
+}
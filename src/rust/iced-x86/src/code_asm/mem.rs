@@ -279,6 +279,21 @@ impl AsmMemoryOperand {
 		self
 	}
 
+	#[cfg(feature = "op_code_info")]
+	#[must_use]
+	#[inline]
+	pub(crate) fn with_index(mut self, index: Register, scale: u8) -> Self {
+		self.index = index;
+		self.scale = scale;
+		self
+	}
+
+	#[cfg(feature = "op_code_info")]
+	#[inline]
+	pub(crate) fn state_mut(&mut self) -> &mut CodeAsmOpState {
+		&mut self.state
+	}
+
 	#[must_use]
 	#[inline]
 	pub(crate) fn is_displacement_only(&self) -> bool {
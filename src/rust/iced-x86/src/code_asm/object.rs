@@ -0,0 +1,419 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+//! Creates relocatable object files (ELF64 `.o` and COFF `.obj`) that can be linked with code compiled by other compilers.
+//!
+//! This requires the `object_writer` feature to use (not enabled by default).
+//!
+//! See [`ObjectWriter`] docs for usage.
+//!
+//! [`ObjectWriter`]: struct.ObjectWriter.html
+
+mod coff;
+mod elf;
+
+use crate::code_asm::{CodeAssembler, CodeLabel, SectionKind};
+use crate::{BlockDirective, BlockEncoderOptions, BlockEncoderResult, IcedError, OpKind};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+// All sections are assembled at this address and extern symbols are placed at their label ids (small numbers) so
+// all branches to extern symbols are near branches with 32-bit displacements.
+const BASE_ADDRESS: u64 = 0x4000_0000;
+// Number of bytes between two sections. It's greater than the range of a short branch so all branches to other
+// sections are near branches with 32-bit displacements.
+const SECTION_GAP: u64 = 0x100;
+const MAX_LABEL_ID: u64 = 0x1000_0000;
+
+/// Object file format
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum ObjectFormat {
+	/// ELF64 x86-64 relocatable object file (`.o`), eg. Linux and BSD
+	Elf,
+	/// COFF x64 object file (`.obj`), eg. Windows
+	Coff,
+}
+
+impl Default for ObjectFormat {
+	#[inline]
+	fn default() -> Self {
+		ObjectFormat::Elf
+	}
+}
+
+impl fmt::Display for ObjectFormat {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			ObjectFormat::Elf => "ELF",
+			ObjectFormat::Coff => "COFF",
+		})
+	}
+}
+
+/// Writes the code and data of a [`CodeAssembler`] to a relocatable object file.
+///
+/// Each [`CodeAssembler`] section (see [`CodeAssembler::create_section()`]) is written to a section with the same name.
+/// Only 64-bit code is supported.
+///
+/// - Labels can be exported as global symbols, see [`add_export()`]
+/// - Extern symbols are labels created by [`add_extern()`]. They can be used as branch targets, RIP-relative memory operands
+///   and in label address data (eg. [`CodeAssembler::dq_label()`]).
+/// - References to labels in other sections are also relocated since the linker decides where the sections are placed.
+///
+/// [`CodeAssembler`]: ../struct.CodeAssembler.html
+/// [`CodeAssembler::create_section()`]: ../struct.CodeAssembler.html#method.create_section
+/// [`CodeAssembler::dq_label()`]: ../struct.CodeAssembler.html#method.dq_label
+/// [`add_export()`]: #method.add_export
+/// [`add_extern()`]: #method.add_extern
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::code_asm::*;
+/// use iced_x86::code_asm::object::*;
+///
+/// # fn main() -> Result<(), IcedError> {
+/// let mut a = CodeAssembler::new(64)?;
+/// let mut obj = ObjectWriter::new();
+/// let rodata = a.create_section(".rodata", SectionKind::ReadOnlyData, 1)?;
+/// let puts = obj.add_extern(&mut a, "puts")?;
+/// let mut hello = a.create_label();
+/// let mut main = a.create_label();
+///
+/// // int main(void) { puts("Hello, world!"); return 0; }
+/// a.set_label(&mut main)?;
+/// a.sub(rsp, 8)?;
+/// a.lea(rdi, ptr(hello))?;
+/// a.call(puts)?;
+/// a.xor(eax, eax)?;
+/// a.add(rsp, 8)?;
+/// a.ret()?;
+///
+/// a.set_section(rodata)?;
+/// a.set_label(&mut hello)?;
+/// a.db(b"Hello, world!\0")?;
+///
+/// obj.add_export("main", main)?;
+/// let bytes = obj.write(&mut a, ObjectFormat::Elf)?;
+/// assert_eq!(&bytes[0..4], b"\x7FELF");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct ObjectWriter {
+	externs: Vec<(String, CodeLabel)>,
+	exports: Vec<(String, CodeLabel)>,
+}
+
+impl ObjectWriter {
+	/// Creates a new instance
+	#[must_use]
+	#[inline]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Creates a label that references an extern symbol. It must not be passed to [`CodeAssembler::set_label()`].
+	///
+	/// # Errors
+	///
+	/// Fails if the name is invalid or if it's already been used
+	///
+	/// # Arguments
+	///
+	/// * `a`: The assembler
+	/// * `name`: Name of the symbol
+	///
+	/// [`CodeAssembler::set_label()`]: ../struct.CodeAssembler.html#method.set_label
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn add_extern(&mut self, a: &mut CodeAssembler, name: &str) -> Result<CodeLabel, IcedError> {
+		self.verify_name(name)?;
+		let label = a.create_label();
+		self.externs.push((String::from(name), label));
+		Ok(label)
+	}
+
+	/// Exports a label as a global symbol. It's a function symbol if the label is in a code section.
+	///
+	/// # Errors
+	///
+	/// Fails if the name is invalid or if it's already been used
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of the symbol
+	/// * `label`: The label, it must be set with [`CodeAssembler::set_label()`]
+	///
+	/// [`CodeAssembler::set_label()`]: ../struct.CodeAssembler.html#method.set_label
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn add_export(&mut self, name: &str, label: CodeLabel) -> Result<(), IcedError> {
+		self.verify_name(name)?;
+		if label.is_empty() {
+			return Err(IcedError::new("Invalid label. Must be created by create_label()"));
+		}
+		self.exports.push((String::from(name), label));
+		Ok(())
+	}
+
+	fn verify_name(&self, name: &str) -> Result<(), IcedError> {
+		if name.is_empty() || name.contains('\0') {
+			return Err(IcedError::new("Invalid symbol name"));
+		}
+		if self.externs.iter().chain(self.exports.iter()).any(|(other, _)| other == name) {
+			return Err(IcedError::with_string(format!("Symbol `{}` already exists", name)));
+		}
+		Ok(())
+	}
+
+	/// Assembles all instructions and writes an object file
+	///
+	/// # Errors
+	///
+	/// Fails if the code couldn't be assembled or if it can't be stored in an object file, eg. it's not 64-bit code, a section
+	/// has a fixed address (see [`CodeAssembler::set_section_ip()`]), an exported label wasn't set, or an instruction references
+	/// another section or an extern symbol without using a 32-bit displacement.
+	///
+	/// # Arguments
+	///
+	/// * `a`: The assembler
+	/// * `format`: Object file format
+	///
+	/// [`CodeAssembler::set_section_ip()`]: ../struct.CodeAssembler.html#method.set_section_ip
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn write(&self, a: &mut CodeAssembler, format: ObjectFormat) -> Result<Vec<u8>, IcedError> {
+		let file = self.create_object_file(a)?;
+		match format {
+			ObjectFormat::Elf => elf::write(file),
+			ObjectFormat::Coff => coff::write(file),
+		}
+	}
+
+	fn create_object_file(&self, a: &mut CodeAssembler) -> Result<ObjectFile<'_>, IcedError> {
+		if a.bitness() != 64 {
+			return Err(IcedError::new("Only 64-bit code can be written to an object file"));
+		}
+		if a.current_label_id >= MAX_LABEL_ID {
+			return Err(IcedError::new("Too many labels"));
+		}
+		let mut alignments = Vec::with_capacity(a.sections.len());
+		for (i, section) in a.sections.iter().enumerate() {
+			if section.ip.is_some() {
+				return Err(IcedError::with_string(format!("Section `{}` has a fixed address", section.name)));
+			}
+			let max_alignment = section
+				.directives
+				.iter()
+				.map(|&(_, directive)| if let BlockDirective::Align(alignment) = directive { alignment } else { 1 })
+				.max()
+				.unwrap_or(1);
+			// The linker only guarantees the section alignment
+			if i == 0 {
+				alignments.push(max_alignment.max(16));
+			} else if max_alignment > section.alignment {
+				return Err(IcedError::with_string(format!("Section `{}` is aligned to less than one of its align directives", section.name)));
+			} else {
+				alignments.push(section.alignment);
+			}
+		}
+
+		let result = a.assemble_sections(
+			BASE_ADDRESS,
+			BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS | BlockEncoderOptions::RETURN_CONSTANT_OFFSETS,
+			SECTION_GAP,
+		)?;
+		let results: Vec<&BlockEncoderResult> = core::iter::once(&result.inner).chain(result.sections.iter().map(|section| &section.inner)).collect();
+
+		// All labels: (id, section index, offset in section)
+		let mut labels: Vec<(u64, usize, u32)> = Vec::new();
+		for (i, (section, result)) in a.sections.iter().zip(results.iter()).enumerate() {
+			for (instruction, &offset) in section.instructions.iter().zip(result.new_instruction_offsets.iter()) {
+				let id = instruction.ip();
+				if id != 0 {
+					if offset == u32::MAX {
+						return Err(IcedError::new("A labeled instruction was re-written to a longer instruction. Consider using a `zero_bytes()` instruction as a label instead of a normal instruction."));
+					}
+					labels.push((id, i, offset));
+				}
+			}
+		}
+		labels.sort_unstable_by_key(|&(id, _, _)| id);
+		for (name, label) in &self.externs {
+			if labels.binary_search_by_key(&label.id(), |&(id, _, _)| id).is_ok() {
+				return Err(IcedError::with_string(format!("Extern symbol `{}` can't be set with set_label()", name)));
+			}
+		}
+		let resolve = |id: u64| -> Option<Target> {
+			if let Ok(index) = labels.binary_search_by_key(&id, |&(id, _, _)| id) {
+				let (_, section, offset) = labels[index];
+				Some(Target::Label(section, offset))
+			} else {
+				self.externs.iter().position(|(_, label)| label.id() == id).map(Target::Extern)
+			}
+		};
+
+		let mut sections = Vec::with_capacity(a.sections.len());
+		for (i, ((section, result), &alignment)) in a.sections.iter().zip(results.iter()).zip(alignments.iter()).enumerate() {
+			let mut data = result.code_buffer.clone();
+			let mut relocs = Vec::new();
+
+			for (j, instruction) in section.instructions.iter().enumerate() {
+				let (target, field_offset, field_size, kind) = if instruction.is_ip_rel_memory_operand() {
+					let co = &result.constant_offsets[j];
+					(instruction.ip_rel_memory_address(), co.displacement_offset(), co.displacement_size(), ObjectRelocKind::Rel32)
+				} else if (0..instruction.op_count())
+					.any(|op| matches!(instruction.op_kind(op), OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64))
+				{
+					let co = &result.constant_offsets[j];
+					(instruction.near_branch_target(), co.immediate_offset(), co.immediate_size(), ObjectRelocKind::Branch32)
+				} else {
+					continue;
+				};
+				let (target, target_address) = match resolve(target) {
+					Some(Target::Label(section, _)) if section == i => continue,
+					Some(Target::Label(section, offset)) => (ObjectRelocTarget::Section(section), results[section].rip + offset as u64),
+					Some(Target::Extern(index)) => (ObjectRelocTarget::Extern(index), self.externs[index].1.id()),
+					None => return Err(IcedError::with_string(format!("Target 0x{:X} isn't a label", target))),
+				};
+				let offset = result.new_instruction_offsets[j];
+				if offset == u32::MAX {
+					return Err(IcedError::new(
+						"An instruction that references another section or an extern symbol was re-written to a longer instruction",
+					));
+				}
+				if field_size != 4 {
+					return Err(IcedError::new("An instruction that references another section or an extern symbol must use a 32-bit displacement"));
+				}
+				let pos = offset as usize + field_offset;
+				let field = &mut data[pos..pos + 4];
+				let value = i32::from_le_bytes([field[0], field[1], field[2], field[3]]);
+				field.copy_from_slice(&[0; 4]);
+				// The CPU adds the displacement to the address of the next instruction, the linker adds it to the address of the field
+				let next_ip = target_address.wrapping_sub(value as i64 as u64);
+				let mut addend = result.rip.wrapping_add(pos as u64).wrapping_sub(next_ip) as i64;
+				if let ObjectRelocTarget::Section(section) = target {
+					addend += target_address.wrapping_sub(results[section].rip) as i64;
+				}
+				relocs.push(ObjectReloc { offset: pos as u32, kind, target, addend });
+			}
+
+			for &(index, directive) in &section.directives {
+				let offset = result.new_instruction_offsets[index] as usize;
+				match directive {
+					BlockDirective::Align(_) => {}
+					BlockDirective::Address { size, target } => {
+						let (target, addend) = match resolve(target) {
+							Some(Target::Label(section, offset)) => (ObjectRelocTarget::Section(section), offset as i64),
+							Some(Target::Extern(index)) => (ObjectRelocTarget::Extern(index), 0),
+							None => return Err(IcedError::with_string(format!("Target 0x{:X} isn't a label", target))),
+						};
+						let kind = match size {
+							4 => ObjectRelocKind::Abs32,
+							8 => ObjectRelocKind::Abs64,
+							_ => return Err(IcedError::new("Invalid label address size")),
+						};
+						for b in &mut data[offset..offset + size as usize] {
+							*b = 0;
+						}
+						relocs.push(ObjectReloc { offset: offset as u32, kind, target, addend });
+					}
+					BlockDirective::RelativeAddress { size: _, target, base } => match (resolve(target), resolve(base)) {
+						(Some(Target::Label(section1, _)), Some(Target::Label(section2, _))) if section1 == section2 => {}
+						_ => return Err(IcedError::new("Relative label addresses must reference labels in the same section")),
+					},
+				}
+			}
+
+			relocs.sort_unstable_by_key(|reloc| reloc.offset);
+			sections.push(ObjectSection { name: section.name.clone(), kind: section.kind, alignment, data, relocs });
+		}
+
+		let mut exports = Vec::with_capacity(self.exports.len());
+		for (name, label) in &self.exports {
+			match resolve(label.id()) {
+				Some(Target::Label(section, offset)) => {
+					exports.push(ObjectExport { name, section, offset, is_function: sections[section].kind == SectionKind::Code })
+				}
+				Some(Target::Extern(_)) => return Err(IcedError::with_string(format!("Exported symbol `{}` is an extern symbol", name))),
+				None => return Err(IcedError::with_string(format!("Exported symbol `{}` wasn't set with set_label()", name))),
+			}
+		}
+		let externs = self.externs.iter().map(|(name, _)| name.as_str()).collect();
+
+		Ok(ObjectFile { sections, exports, externs })
+	}
+}
+
+enum Target {
+	Label(usize, u32),
+	Extern(usize),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ObjectRelocKind {
+	// RIP-relative memory operand
+	Rel32,
+	// Near branch
+	Branch32,
+	Abs32,
+	Abs64,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum ObjectRelocTarget {
+	Section(usize),
+	Extern(usize),
+}
+
+// The field has been cleared and the value is `symbol + addend - address of field` (PC-relative) or `symbol + addend`
+struct ObjectReloc {
+	offset: u32,
+	kind: ObjectRelocKind,
+	target: ObjectRelocTarget,
+	addend: i64,
+}
+
+struct ObjectSection {
+	name: String,
+	kind: SectionKind,
+	alignment: u32,
+	data: Vec<u8>,
+	relocs: Vec<ObjectReloc>,
+}
+
+struct ObjectExport<'a> {
+	name: &'a str,
+	section: usize,
+	offset: u32,
+	is_function: bool,
+}
+
+struct ObjectFile<'a> {
+	sections: Vec<ObjectSection>,
+	exports: Vec<ObjectExport<'a>>,
+	externs: Vec<&'a str>,
+}
+
+#[inline]
+fn write_u16(buffer: &mut Vec<u8>, value: u16) {
+	buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+#[inline]
+fn write_u32(buffer: &mut Vec<u8>, value: u32) {
+	buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+#[inline]
+fn write_u64(buffer: &mut Vec<u8>, value: u64) {
+	buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+#[inline]
+fn align_buffer(buffer: &mut Vec<u8>, alignment: usize) {
+	while buffer.len() % alignment != 0 {
+		buffer.push(0);
+	}
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::object::{write_u16, write_u32, ObjectFile, ObjectRelocKind, ObjectRelocTarget};
+use crate::code_asm::SectionKind;
+use crate::IcedError;
+use alloc::vec::Vec;
+
+const FILE_HEADER_SIZE: usize = 20;
+const SECTION_HEADER_SIZE: usize = 40;
+const RELOC_SIZE: usize = 10;
+const SYMBOL_SIZE: usize = 18;
+
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x0000_0020;
+const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x0000_0040;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+const IMAGE_SCN_MEM_READ: u32 = 0x4000_0000;
+const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+const IMAGE_SCN_ALIGN_SHIFT: u32 = 20;
+const MAX_SECTION_ALIGNMENT: u32 = 0x2000;
+
+const IMAGE_REL_AMD64_ADDR64: u16 = 0x0001;
+const IMAGE_REL_AMD64_ADDR32: u16 = 0x0002;
+const IMAGE_REL_AMD64_REL32: u16 = 0x0004;
+
+const IMAGE_SYM_UNDEFINED: u16 = 0;
+const IMAGE_SYM_DTYPE_FUNCTION: u16 = 0x20;
+const IMAGE_SYM_CLASS_EXTERNAL: u8 = 2;
+const IMAGE_SYM_CLASS_STATIC: u8 = 3;
+
+// Strings that don't fit in a name field are stored in the string table. The offset includes the 4-byte size.
+fn write_name(buffer: &mut Vec<u8>, string_table: &mut Vec<u8>, name: &str, is_section: bool) {
+	let bytes = name.as_bytes();
+	if bytes.len() <= 8 {
+		buffer.extend_from_slice(bytes);
+		buffer.extend_from_slice(&[0; 8][bytes.len()..]);
+	} else {
+		let offset = string_table.len() as u32 + 4;
+		string_table.extend_from_slice(bytes);
+		string_table.push(0);
+		if is_section {
+			let mut name = [0; 8];
+			let s = format!("/{}", offset);
+			name[..s.len()].copy_from_slice(s.as_bytes());
+			buffer.extend_from_slice(&name);
+		} else {
+			write_u32(buffer, 0);
+			write_u32(buffer, offset);
+		}
+	}
+}
+
+fn write_symbol(buffer: &mut Vec<u8>, string_table: &mut Vec<u8>, name: &str, value: u32, section: u16, kind: u16, class: u8, aux_count: u8) {
+	write_name(buffer, string_table, name, false);
+	write_u32(buffer, value);
+	write_u16(buffer, section);
+	write_u16(buffer, kind);
+	buffer.push(class);
+	buffer.push(aux_count);
+}
+
+// Each section has a section symbol with one aux symbol, followed by exports and externs
+pub(super) fn write(mut file: ObjectFile<'_>) -> Result<Vec<u8>, IcedError> {
+	if file.sections.len() >= 0x7FFF {
+		return Err(IcedError::new("Too many sections"));
+	}
+	// COFF relocations don't have an addend, it's stored in the data
+	for section in &mut file.sections {
+		if section.relocs.len() > 0xFFFF {
+			return Err(IcedError::with_string(format!("Section `{}` has too many relocations", section.name)));
+		}
+		if section.alignment > MAX_SECTION_ALIGNMENT {
+			return Err(IcedError::with_string(format!("Section `{}` has an alignment > 0x{:X}", section.name, MAX_SECTION_ALIGNMENT)));
+		}
+		for reloc in &section.relocs {
+			let offset = reloc.offset as usize;
+			match reloc.kind {
+				ObjectRelocKind::Rel32 | ObjectRelocKind::Branch32 => {
+					// REL32 is relative to the end of the 4-byte field
+					let addend = reloc.addend + 4;
+					if addend < i32::MIN as i64 || addend > i32::MAX as i64 {
+						return Err(IcedError::new("Relocation addend doesn't fit in 32 bits"));
+					}
+					section.data[offset..offset + 4].copy_from_slice(&(addend as i32).to_le_bytes());
+				}
+				ObjectRelocKind::Abs32 => {
+					if reloc.addend < 0 || reloc.addend > u32::MAX as i64 {
+						return Err(IcedError::new("Relocation addend doesn't fit in 32 bits"));
+					}
+					section.data[offset..offset + 4].copy_from_slice(&(reloc.addend as u32).to_le_bytes());
+				}
+				ObjectRelocKind::Abs64 => section.data[offset..offset + 8].copy_from_slice(&reloc.addend.to_le_bytes()),
+			}
+		}
+	}
+
+	let section_sym_index = |section: usize| section as u32 * 2;
+	let first_extern = (file.sections.len() * 2 + file.exports.len()) as u32;
+	let mut buffer = Vec::new();
+	let mut string_table = Vec::new();
+	let mut data_offset = FILE_HEADER_SIZE + file.sections.len() * SECTION_HEADER_SIZE;
+	let mut section_headers = Vec::with_capacity(file.sections.len() * SECTION_HEADER_SIZE);
+	for section in &file.sections {
+		let characteristics = match section.kind {
+			SectionKind::Code => IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_MEM_READ,
+			SectionKind::ReadOnlyData => IMAGE_SCN_CNT_INITIALIZED_DATA | IMAGE_SCN_MEM_READ,
+			SectionKind::Data => IMAGE_SCN_CNT_INITIALIZED_DATA | IMAGE_SCN_MEM_READ | IMAGE_SCN_MEM_WRITE,
+		} | ((section.alignment.trailing_zeros() + 1) << IMAGE_SCN_ALIGN_SHIFT);
+		let raw_data_offset = if section.data.is_empty() { 0 } else { data_offset };
+		data_offset += section.data.len();
+		let relocs_offset = if section.relocs.is_empty() { 0 } else { data_offset };
+		data_offset += section.relocs.len() * RELOC_SIZE;
+
+		write_name(&mut section_headers, &mut string_table, &section.name, true);
+		write_u32(&mut section_headers, 0);
+		write_u32(&mut section_headers, 0);
+		write_u32(&mut section_headers, section.data.len() as u32);
+		write_u32(&mut section_headers, raw_data_offset as u32);
+		write_u32(&mut section_headers, relocs_offset as u32);
+		write_u32(&mut section_headers, 0);
+		write_u16(&mut section_headers, section.relocs.len() as u16);
+		write_u16(&mut section_headers, 0);
+		write_u32(&mut section_headers, characteristics);
+	}
+	let symbol_count = file.sections.len() * 2 + file.exports.len() + file.externs.len();
+
+	write_u16(&mut buffer, IMAGE_FILE_MACHINE_AMD64);
+	write_u16(&mut buffer, file.sections.len() as u16);
+	write_u32(&mut buffer, 0);
+	write_u32(&mut buffer, data_offset as u32);
+	write_u32(&mut buffer, symbol_count as u32);
+	write_u16(&mut buffer, 0);
+	write_u16(&mut buffer, 0);
+	debug_assert_eq!(buffer.len(), FILE_HEADER_SIZE);
+	buffer.extend_from_slice(&section_headers);
+
+	for section in &file.sections {
+		buffer.extend_from_slice(&section.data);
+		for reloc in &section.relocs {
+			let kind = match reloc.kind {
+				ObjectRelocKind::Rel32 | ObjectRelocKind::Branch32 => IMAGE_REL_AMD64_REL32,
+				ObjectRelocKind::Abs32 => IMAGE_REL_AMD64_ADDR32,
+				ObjectRelocKind::Abs64 => IMAGE_REL_AMD64_ADDR64,
+			};
+			let symbol = match reloc.target {
+				ObjectRelocTarget::Section(section) => section_sym_index(section),
+				ObjectRelocTarget::Extern(index) => first_extern + index as u32,
+			};
+			write_u32(&mut buffer, reloc.offset);
+			write_u32(&mut buffer, symbol);
+			write_u16(&mut buffer, kind);
+		}
+	}
+	debug_assert_eq!(buffer.len(), data_offset);
+
+	for (i, section) in file.sections.iter().enumerate() {
+		write_symbol(&mut buffer, &mut string_table, &section.name, 0, i as u16 + 1, 0, IMAGE_SYM_CLASS_STATIC, 1);
+		// Aux record: length, number of relocations, number of line numbers, checksum, number, selection, 3 unused bytes
+		write_u32(&mut buffer, section.data.len() as u32);
+		write_u16(&mut buffer, section.relocs.len() as u16);
+		write_u16(&mut buffer, 0);
+		write_u32(&mut buffer, 0);
+		write_u16(&mut buffer, 0);
+		buffer.extend_from_slice(&[0; 4]);
+	}
+	for export in &file.exports {
+		let kind = if export.is_function { IMAGE_SYM_DTYPE_FUNCTION } else { 0 };
+		write_symbol(&mut buffer, &mut string_table, export.name, export.offset, export.section as u16 + 1, kind, IMAGE_SYM_CLASS_EXTERNAL, 0);
+	}
+	for name in &file.externs {
+		write_symbol(&mut buffer, &mut string_table, name, 0, IMAGE_SYM_UNDEFINED, 0, IMAGE_SYM_CLASS_EXTERNAL, 0);
+	}
+	debug_assert_eq!(buffer.len(), data_offset + symbol_count * SYMBOL_SIZE);
+
+	write_u32(&mut buffer, string_table.len() as u32 + 4);
+	buffer.extend_from_slice(&string_table);
+
+	Ok(buffer)
+}
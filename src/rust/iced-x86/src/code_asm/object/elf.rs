@@ -0,0 +1,259 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::object::{align_buffer, write_u16, write_u32, write_u64, ObjectFile, ObjectRelocKind, ObjectRelocTarget};
+use crate::code_asm::SectionKind;
+use crate::IcedError;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const EHDR_SIZE: usize = 64;
+const SHDR_SIZE: usize = 64;
+const SYM_SIZE: usize = 24;
+const RELA_SIZE: usize = 24;
+
+const ET_REL: u16 = 1;
+const EM_X86_64: u16 = 62;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_RELA: u32 = 4;
+
+const SHF_WRITE: u64 = 0x01;
+const SHF_ALLOC: u64 = 0x02;
+const SHF_EXECINSTR: u64 = 0x04;
+const SHF_INFO_LINK: u64 = 0x40;
+
+const STB_LOCAL: u8 = 0;
+const STB_GLOBAL: u8 = 1;
+const STT_NOTYPE: u8 = 0;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+const STT_SECTION: u8 = 3;
+const SHN_UNDEF: u16 = 0;
+
+const R_X86_64_64: u32 = 1;
+const R_X86_64_PC32: u32 = 2;
+const R_X86_64_PLT32: u32 = 4;
+const R_X86_64_32: u32 = 10;
+
+struct SectionHeader {
+	name: u32,
+	kind: u32,
+	flags: u64,
+	offset: u64,
+	size: u64,
+	link: u32,
+	info: u32,
+	alignment: u64,
+	entry_size: u64,
+}
+
+struct StringTable {
+	data: Vec<u8>,
+}
+
+impl StringTable {
+	fn new() -> Self {
+		Self { data: vec![0] }
+	}
+
+	fn add(&mut self, name: &str) -> u32 {
+		let offset = self.data.len() as u32;
+		self.data.extend_from_slice(name.as_bytes());
+		self.data.push(0);
+		offset
+	}
+}
+
+// Section header indexes: null, the sections, their `.rela` sections, `.note.GNU-stack`, `.symtab`, `.strtab`, `.shstrtab`
+pub(super) fn write(file: ObjectFile<'_>) -> Result<Vec<u8>, IcedError> {
+	// SHN_LORESERVE = 0xFF00
+	if file.sections.len() * 2 + 5 >= 0xFF00 {
+		return Err(IcedError::new("Too many sections"));
+	}
+	let mut buffer = vec![0; EHDR_SIZE];
+	let mut headers: Vec<SectionHeader> = Vec::new();
+	let mut shstrtab = StringTable::new();
+	let mut strtab = StringTable::new();
+
+	for section in &file.sections {
+		let flags = match section.kind {
+			SectionKind::Code => SHF_ALLOC | SHF_EXECINSTR,
+			SectionKind::ReadOnlyData => SHF_ALLOC,
+			SectionKind::Data => SHF_ALLOC | SHF_WRITE,
+		};
+		align_buffer(&mut buffer, section.alignment as usize);
+		let offset = buffer.len() as u64;
+		buffer.extend_from_slice(&section.data);
+		headers.push(SectionHeader {
+			name: shstrtab.add(&section.name),
+			kind: SHT_PROGBITS,
+			flags,
+			offset,
+			size: section.data.len() as u64,
+			link: 0,
+			info: 0,
+			alignment: section.alignment as u64,
+			entry_size: 0,
+		});
+	}
+
+	// Local section symbols come first, then exports and externs
+	let section_sym_index = |section: usize| section as u64 + 1;
+	let first_global = file.sections.len() as u32 + 1;
+	let extern_sym_index = |index: usize| (first_global as usize + file.exports.len() + index) as u64;
+	let symtab_index = headers.len() as u32 + 1 + file.sections.iter().filter(|section| !section.relocs.is_empty()).count() as u32 + 1;
+
+	for (i, section) in file.sections.iter().enumerate() {
+		if section.relocs.is_empty() {
+			continue;
+		}
+		align_buffer(&mut buffer, 8);
+		let offset = buffer.len() as u64;
+		for reloc in &section.relocs {
+			let kind = match reloc.kind {
+				ObjectRelocKind::Rel32 => R_X86_64_PC32,
+				ObjectRelocKind::Branch32 => R_X86_64_PLT32,
+				ObjectRelocKind::Abs32 => R_X86_64_32,
+				ObjectRelocKind::Abs64 => R_X86_64_64,
+			};
+			let symbol = match reloc.target {
+				ObjectRelocTarget::Section(section) => section_sym_index(section),
+				ObjectRelocTarget::Extern(index) => extern_sym_index(index),
+			};
+			write_u64(&mut buffer, reloc.offset as u64);
+			write_u64(&mut buffer, (symbol << 32) | kind as u64);
+			write_u64(&mut buffer, reloc.addend as u64);
+		}
+		let mut name = String::from(".rela");
+		name.push_str(&section.name);
+		headers.push(SectionHeader {
+			name: shstrtab.add(&name),
+			kind: SHT_RELA,
+			flags: SHF_INFO_LINK,
+			offset,
+			size: (section.relocs.len() * RELA_SIZE) as u64,
+			link: symtab_index,
+			info: i as u32 + 1,
+			alignment: 8,
+			entry_size: RELA_SIZE as u64,
+		});
+	}
+
+	// Tell the linker that the stack doesn't need to be executable
+	headers.push(SectionHeader {
+		name: shstrtab.add(".note.GNU-stack"),
+		kind: SHT_PROGBITS,
+		flags: 0,
+		offset: buffer.len() as u64,
+		size: 0,
+		link: 0,
+		info: 0,
+		alignment: 1,
+		entry_size: 0,
+	});
+
+	align_buffer(&mut buffer, 8);
+	let offset = buffer.len() as u64;
+	let write_symbol = |buffer: &mut Vec<u8>, name: u32, info: u8, section: u16, value: u64| {
+		write_u32(buffer, name);
+		buffer.push(info);
+		buffer.push(0);
+		write_u16(buffer, section);
+		write_u64(buffer, value);
+		write_u64(buffer, 0);
+	};
+	write_symbol(&mut buffer, 0, 0, SHN_UNDEF, 0);
+	for i in 0..file.sections.len() {
+		write_symbol(&mut buffer, 0, (STB_LOCAL << 4) | STT_SECTION, i as u16 + 1, 0);
+	}
+	for export in &file.exports {
+		let kind = if export.is_function { STT_FUNC } else { STT_OBJECT };
+		write_symbol(&mut buffer, strtab.add(export.name), (STB_GLOBAL << 4) | kind, export.section as u16 + 1, export.offset as u64);
+	}
+	for name in &file.externs {
+		write_symbol(&mut buffer, strtab.add(name), (STB_GLOBAL << 4) | STT_NOTYPE, SHN_UNDEF, 0);
+	}
+	let symbol_count = 1 + file.sections.len() + file.exports.len() + file.externs.len();
+	debug_assert_eq!(headers.len() as u32 + 1, symtab_index);
+	headers.push(SectionHeader {
+		name: shstrtab.add(".symtab"),
+		kind: SHT_SYMTAB,
+		flags: 0,
+		offset,
+		size: (symbol_count * SYM_SIZE) as u64,
+		link: symtab_index + 1,
+		info: first_global,
+		alignment: 8,
+		entry_size: SYM_SIZE as u64,
+	});
+
+	let offset = buffer.len() as u64;
+	buffer.extend_from_slice(&strtab.data);
+	headers.push(SectionHeader {
+		name: shstrtab.add(".strtab"),
+		kind: SHT_STRTAB,
+		flags: 0,
+		offset,
+		size: strtab.data.len() as u64,
+		link: 0,
+		info: 0,
+		alignment: 1,
+		entry_size: 0,
+	});
+
+	let name = shstrtab.add(".shstrtab");
+	let offset = buffer.len() as u64;
+	buffer.extend_from_slice(&shstrtab.data);
+	headers.push(SectionHeader {
+		name,
+		kind: SHT_STRTAB,
+		flags: 0,
+		offset,
+		size: shstrtab.data.len() as u64,
+		link: 0,
+		info: 0,
+		alignment: 1,
+		entry_size: 0,
+	});
+
+	let section_count = headers.len() + 1;
+	align_buffer(&mut buffer, 8);
+	let headers_offset = buffer.len() as u64;
+	buffer.extend_from_slice(&[0; SHDR_SIZE]);
+	for header in &headers {
+		write_u32(&mut buffer, header.name);
+		write_u32(&mut buffer, header.kind);
+		write_u64(&mut buffer, header.flags);
+		write_u64(&mut buffer, 0);
+		write_u64(&mut buffer, header.offset);
+		write_u64(&mut buffer, header.size);
+		write_u32(&mut buffer, header.link);
+		write_u32(&mut buffer, header.info);
+		write_u64(&mut buffer, header.alignment);
+		write_u64(&mut buffer, header.entry_size);
+	}
+
+	let mut ehdr = Vec::with_capacity(EHDR_SIZE);
+	// ELFCLASS64, ELFDATA2LSB, EV_CURRENT, ELFOSABI_NONE
+	ehdr.extend_from_slice(b"\x7FELF\x02\x01\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00");
+	write_u16(&mut ehdr, ET_REL);
+	write_u16(&mut ehdr, EM_X86_64);
+	write_u32(&mut ehdr, 1);
+	write_u64(&mut ehdr, 0);
+	write_u64(&mut ehdr, 0);
+	write_u64(&mut ehdr, headers_offset);
+	write_u32(&mut ehdr, 0);
+	write_u16(&mut ehdr, EHDR_SIZE as u16);
+	write_u16(&mut ehdr, 0);
+	write_u16(&mut ehdr, 0);
+	write_u16(&mut ehdr, SHDR_SIZE as u16);
+	write_u16(&mut ehdr, section_count as u16);
+	write_u16(&mut ehdr, section_count as u16 - 1);
+	debug_assert_eq!(ehdr.len(), EHDR_SIZE);
+	buffer[0..EHDR_SIZE].copy_from_slice(&ehdr);
+
+	Ok(buffer)
+}
@@ -0,0 +1,339 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::op_state::{CodeAsmOpState, MemoryOperandSize};
+use crate::code_asm::*;
+use crate::{IcedError, Register, RoundingControl};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AsmOperandKind {
+	Register(Register),
+	Memory(AsmMemoryOperand),
+	Immediate(i64),
+	ImmediateU64(u64),
+	Label(CodeLabel),
+}
+
+/// An operand passed to [`CodeAssembler::emit()`].
+///
+/// It's a register, a memory operand, an immediate or a label and it's mostly useful if
+/// the mnemonic and operands aren't known at compile time (eg. language bindings or scripts).
+/// All typed registers, [`Register`], memory operands, labels and integers can be converted to it.
+///
+/// [`CodeAssembler::emit()`]: struct.CodeAssembler.html#method.emit
+/// [`Register`]: ../enum.Register.html
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::code_asm::*;
+/// use iced_x86::{Mnemonic, Register};
+///
+/// # fn main() -> Result<(), IcedError> {
+/// let mut a = CodeAssembler::new(64)?;
+/// a.emit(Mnemonic::Add, &[Register::RAX.into(), qword_ptr(rcx + 0x10).into()])?;
+/// let mem = AsmOperand::memory(Register::RSP, Register::None, 1, 8)?.with_memory_size(4)?;
+/// a.emit(Mnemonic::Mov, &[mem, 0x1234.into()])?;
+/// let bytes = a.assemble(0x1234_5678)?;
+/// assert_eq!(bytes, b"\x48\x03\x41\x10\xC7\x44\x24\x08\x34\x12\x00\x00");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsmOperand {
+	pub(crate) kind: AsmOperandKind,
+	pub(crate) state: CodeAsmOpState,
+}
+
+impl AsmOperand {
+	#[must_use]
+	#[inline]
+	fn new(kind: AsmOperandKind) -> Self {
+		Self { kind, state: CodeAsmOpState::new() }
+	}
+
+	/// Creates a memory operand with no size hint
+	///
+	/// # Errors
+	///
+	/// Fails if `scale` isn't 1, 2, 4 or 8
+	///
+	/// # Arguments
+	///
+	/// * `base`: Base register or [`Register::None`]
+	/// * `index`: Index register or [`Register::None`]
+	/// * `scale`: Index register scale (1, 2, 4, or 8)
+	/// * `displacement`: Memory displacement
+	///
+	/// [`Register::None`]: ../enum.Register.html#variant.None
+	#[inline]
+	pub fn memory(base: Register, index: Register, scale: u32, displacement: i64) -> Result<Self, IcedError> {
+		let mem = AsmMemoryOperand::from(base);
+		let mem = match scale {
+			1 | 2 | 4 | 8 => AsmMemoryOperand::with_index(mem, index, scale as u8),
+			_ => return Err(IcedError::new("Invalid scale, must be 1, 2, 4 or 8")),
+		};
+		Ok(Self::new(AsmOperandKind::Memory(mem + displacement)))
+	}
+
+	/// Sets the size of a memory operand
+	///
+	/// # Errors
+	///
+	/// Fails if this isn't a memory operand or if the size is invalid
+	///
+	/// # Arguments
+	///
+	/// * `size`: Size in bytes: 0 (no size hint), 1, 2, 4, 6, 8, 10, 16, 32 or 64
+	#[inline]
+	pub fn with_memory_size(mut self, size: u32) -> Result<Self, IcedError> {
+		let size = to_memory_operand_size(size)?;
+		match &mut self.kind {
+			AsmOperandKind::Memory(mem) => mem.state_mut().ptr(size),
+			_ => return Err(IcedError::new("Only memory operands have a size")),
+		}
+		Ok(self)
+	}
+
+	/// Makes this memory operand a broadcast memory operand
+	///
+	/// # Errors
+	///
+	/// Fails if this isn't a memory operand or if the size is invalid
+	///
+	/// # Arguments
+	///
+	/// * `element_size`: Size of each element in bytes: 2, 4 or 8
+	#[inline]
+	pub fn with_broadcast(mut self, element_size: u32) -> Result<Self, IcedError> {
+		let size = match element_size {
+			2 | 4 | 8 => to_memory_operand_size(element_size)?,
+			_ => return Err(IcedError::new("Invalid broadcast element size, must be 2, 4 or 8")),
+		};
+		match &mut self.kind {
+			AsmOperandKind::Memory(mem) => mem.state_mut().bcst(size),
+			_ => return Err(IcedError::new("Only memory operands can be broadcast")),
+		}
+		Ok(self)
+	}
+
+	/// Sets the segment override of a memory operand
+	///
+	/// # Errors
+	///
+	/// Fails if this isn't a memory operand or if `segment` isn't a segment register
+	///
+	/// # Arguments
+	///
+	/// * `segment`: Segment register (eg. [`Register::FS`])
+	///
+	/// [`Register::FS`]: ../enum.Register.html#variant.FS
+	#[inline]
+	pub fn with_segment(mut self, segment: Register) -> Result<Self, IcedError> {
+		let mem = match &mut self.kind {
+			AsmOperandKind::Memory(mem) => mem,
+			_ => return Err(IcedError::new("Only memory operands can have a segment override")),
+		};
+		let state = mem.state_mut();
+		match segment {
+			Register::ES => state.set_es(),
+			Register::CS => state.set_cs(),
+			Register::SS => state.set_ss(),
+			Register::DS => state.set_ds(),
+			Register::FS => state.set_fs(),
+			Register::GS => state.set_gs(),
+			_ => return Err(IcedError::new("Invalid segment register")),
+		}
+		Ok(self)
+	}
+
+	/// Sets the op mask register (`{k1}`-`{k7}`)
+	///
+	/// # Errors
+	///
+	/// Fails if this is an immediate or label operand or if `op_mask` isn't one of `K1`-`K7`
+	///
+	/// # Arguments
+	///
+	/// * `op_mask`: Op mask register (`K1`-`K7`)
+	#[inline]
+	pub fn with_op_mask(mut self, op_mask: Register) -> Result<Self, IcedError> {
+		let state = self.reg_mem_state_mut()?;
+		match op_mask {
+			Register::K1 => state.set_k1(),
+			Register::K2 => state.set_k2(),
+			Register::K3 => state.set_k3(),
+			Register::K4 => state.set_k4(),
+			Register::K5 => state.set_k5(),
+			Register::K6 => state.set_k6(),
+			Register::K7 => state.set_k7(),
+			_ => return Err(IcedError::new("Invalid op mask register, must be one of K1-K7")),
+		}
+		Ok(self)
+	}
+
+	/// Enables zeroing-masking (`{z}`)
+	///
+	/// # Errors
+	///
+	/// Fails if this isn't a register operand
+	#[inline]
+	pub fn with_zeroing_masking(mut self) -> Result<Self, IcedError> {
+		self.reg_state_mut()?.set_zeroing_masking();
+		Ok(self)
+	}
+
+	/// Enables suppress-all-exceptions (`{sae}`)
+	///
+	/// # Errors
+	///
+	/// Fails if this isn't a register operand
+	#[inline]
+	pub fn with_suppress_all_exceptions(mut self) -> Result<Self, IcedError> {
+		self.reg_state_mut()?.set_suppress_all_exceptions();
+		Ok(self)
+	}
+
+	/// Sets the rounding control (`{rn-sae}`, `{rd-sae}`, `{ru-sae}`, `{rz-sae}`)
+	///
+	/// # Errors
+	///
+	/// Fails if this isn't a register operand
+	///
+	/// # Arguments
+	///
+	/// * `rounding_control`: Rounding control
+	#[inline]
+	pub fn with_rounding_control(mut self, rounding_control: RoundingControl) -> Result<Self, IcedError> {
+		let state = self.reg_state_mut()?;
+		match rounding_control {
+			RoundingControl::None => {}
+			RoundingControl::RoundToNearest => state.rn_sae(),
+			RoundingControl::RoundDown => state.rd_sae(),
+			RoundingControl::RoundUp => state.ru_sae(),
+			RoundingControl::RoundTowardZero => state.rz_sae(),
+		}
+		Ok(self)
+	}
+
+	#[inline]
+	fn reg_state_mut(&mut self) -> Result<&mut CodeAsmOpState, IcedError> {
+		match self.kind {
+			AsmOperandKind::Register(_) => Ok(&mut self.state),
+			_ => Err(IcedError::new("Only register operands can use {z}, {sae} or rounding control")),
+		}
+	}
+
+	#[inline]
+	fn reg_mem_state_mut(&mut self) -> Result<&mut CodeAsmOpState, IcedError> {
+		match &mut self.kind {
+			AsmOperandKind::Register(_) => Ok(&mut self.state),
+			AsmOperandKind::Memory(mem) => Ok(mem.state_mut()),
+			_ => Err(IcedError::new("Only register and memory operands can use an op mask register")),
+		}
+	}
+
+	#[must_use]
+	#[inline]
+	pub(crate) fn full_state(&self) -> CodeAsmOpState {
+		match &self.kind {
+			AsmOperandKind::Memory(mem) => mem.state(),
+			_ => self.state,
+		}
+	}
+}
+
+fn to_memory_operand_size(size: u32) -> Result<MemoryOperandSize, IcedError> {
+	Ok(match size {
+		0 => MemoryOperandSize::None,
+		1 => MemoryOperandSize::Byte,
+		2 => MemoryOperandSize::Word,
+		4 => MemoryOperandSize::Dword,
+		6 => MemoryOperandSize::Fword,
+		8 => MemoryOperandSize::Qword,
+		10 => MemoryOperandSize::Tbyte,
+		16 => MemoryOperandSize::Xword,
+		32 => MemoryOperandSize::Yword,
+		64 => MemoryOperandSize::Zword,
+		_ => return Err(IcedError::new("Invalid memory operand size")),
+	})
+}
+
+impl From<Register> for AsmOperand {
+	#[inline]
+	fn from(value: Register) -> Self {
+		Self::new(AsmOperandKind::Register(value))
+	}
+}
+
+macro_rules! reg_to_operand {
+	($($reg_ty:ty)+) => {
+		$(
+			impl From<$reg_ty> for AsmOperand {
+				#[inline]
+				fn from(value: $reg_ty) -> Self {
+					Self::new(AsmOperandKind::Register(value.register()))
+				}
+			}
+		)+
+	};
+}
+reg_to_operand!(AsmRegister8 AsmRegister16 AsmRegisterSegment AsmRegisterCr AsmRegisterDr AsmRegisterTr AsmRegisterSt AsmRegisterMm AsmRegisterBnd AsmRegisterTmm);
+
+macro_rules! reg_state_to_operand {
+	($($reg_ty:ty)+) => {
+		$(
+			impl From<$reg_ty> for AsmOperand {
+				#[inline]
+				fn from(value: $reg_ty) -> Self {
+					Self { kind: AsmOperandKind::Register(value.register()), state: value.state() }
+				}
+			}
+		)+
+	};
+}
+reg_state_to_operand!(AsmRegister32 AsmRegister64 AsmRegisterXmm AsmRegisterYmm AsmRegisterZmm AsmRegisterK);
+
+impl From<AsmMemoryOperand> for AsmOperand {
+	#[inline]
+	fn from(value: AsmMemoryOperand) -> Self {
+		Self::new(AsmOperandKind::Memory(value))
+	}
+}
+
+impl From<CodeLabel> for AsmOperand {
+	#[inline]
+	fn from(value: CodeLabel) -> Self {
+		Self::new(AsmOperandKind::Label(value))
+	}
+}
+
+macro_rules! signed_to_operand {
+	($($ty:ty)+) => {
+		$(
+			impl From<$ty> for AsmOperand {
+				#[inline]
+				#[allow(trivial_numeric_casts)]
+				fn from(value: $ty) -> Self {
+					Self::new(AsmOperandKind::Immediate(value as i64))
+				}
+			}
+		)+
+	};
+}
+signed_to_operand!(i8 i16 i32 i64);
+
+macro_rules! unsigned_to_operand {
+	($($ty:ty)+) => {
+		$(
+			impl From<$ty> for AsmOperand {
+				#[inline]
+				#[allow(trivial_numeric_casts)]
+				fn from(value: $ty) -> Self {
+					Self::new(AsmOperandKind::ImmediateU64(value as u64))
+				}
+			}
+		)+
+	};
+}
+unsigned_to_operand!(u8 u16 u32 u64);
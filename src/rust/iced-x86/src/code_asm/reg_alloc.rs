@@ -0,0 +1,1020 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::CallingConvention;
+use crate::iced_constants::IcedConstants;
+use crate::{
+	BlockDirective, BlockEncoderResult, Code, EncodingKind, FlowControl, IcedError, Instruction, InstructionInfoFactory, InstructionInfoOptions,
+	MemoryOperand, OpAccess, OpCodeOperandKind, OpKind, Register,
+};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+/// Register allocator options, see [`CodeAssembler::set_register_allocator_options()`]
+///
+/// [`CodeAssembler::set_register_allocator_options()`]: struct.CodeAssembler.html#method.set_register_allocator_options
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RegisterAllocatorOptions {
+	calling_convention: CallingConvention,
+	saved_registers: Vec<Register>,
+	spill_base: Register,
+	spill_displacement: i32,
+	spill_size: u32,
+}
+
+impl RegisterAllocatorOptions {
+	/// Creates default options. No callee-saved register can be allocated and there's no spill area.
+	///
+	/// # Arguments
+	///
+	/// * `calling_convention`: Calling convention
+	#[must_use]
+	#[inline]
+	pub fn new(calling_convention: CallingConvention) -> Self {
+		Self { calling_convention, saved_registers: Vec::new(), spill_base: Register::None, spill_displacement: 0, spill_size: 0 }
+	}
+
+	/// Calling convention, only 64-bit calling conventions are supported. Default is [`CallingConvention::SystemV64`]
+	///
+	/// [`CallingConvention::SystemV64`]: enum.CallingConvention.html#variant.SystemV64
+	#[must_use]
+	#[inline]
+	pub fn calling_convention(&self) -> CallingConvention {
+		self.calling_convention
+	}
+
+	/// Calling convention, only 64-bit calling conventions are supported. Default is [`CallingConvention::SystemV64`]
+	///
+	/// [`CallingConvention::SystemV64`]: enum.CallingConvention.html#variant.SystemV64
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_calling_convention(&mut self, new_value: CallingConvention) {
+		self.calling_convention = new_value;
+	}
+
+	/// Callee-saved registers that are saved and restored by your own prologue and epilogue. Only these callee-saved
+	/// registers can be allocated. Default is an empty slice.
+	#[must_use]
+	#[inline]
+	pub fn saved_registers(&self) -> &[Register] {
+		&self.saved_registers
+	}
+
+	/// Callee-saved registers that are saved and restored by your own prologue and epilogue. Only these callee-saved
+	/// registers can be allocated. Default is an empty slice.
+	///
+	/// Valid registers are `RBX`, `RBP` and `R12`-`R15` (and `RSI`, `RDI`, `XMM6`-`XMM15` if it's [`CallingConvention::Microsoft64`]).
+	///
+	/// [`CallingConvention::Microsoft64`]: enum.CallingConvention.html#variant.Microsoft64
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_saved_registers(&mut self, new_value: &[Register]) {
+		self.saved_registers.clear();
+		self.saved_registers.extend_from_slice(new_value);
+	}
+
+	/// Base register of the spill area or [`Register::None`] (default) if there's no spill area
+	///
+	/// [`Register::None`]: ../enum.Register.html#variant.None
+	#[must_use]
+	#[inline]
+	pub fn spill_base(&self) -> Register {
+		self.spill_base
+	}
+
+	/// Displacement of the spill area relative to [`spill_base()`]
+	///
+	/// [`spill_base()`]: #method.spill_base
+	#[must_use]
+	#[inline]
+	pub fn spill_displacement(&self) -> i32 {
+		self.spill_displacement
+	}
+
+	/// Size of the spill area in bytes
+	#[must_use]
+	#[inline]
+	pub fn spill_size(&self) -> u32 {
+		self.spill_size
+	}
+
+	/// Sets the spill area. Virtual registers that don't get a register are stored in `[base + displacement]`.
+	/// The area must be reserved by your prologue, eg. `sub rsp, size` and `base` = `RSP`. `base` is never allocated.
+	///
+	/// # Arguments
+	///
+	/// * `base`: Base register (a 64-bit GPR) or [`Register::None`] if there's no spill area
+	/// * `displacement`: Displacement relative to `base`
+	/// * `size`: Size of the spill area in bytes
+	///
+	/// [`Register::None`]: ../enum.Register.html#variant.None
+	#[inline]
+	pub fn set_spill_area(&mut self, base: Register, displacement: i32, size: u32) {
+		self.spill_base = base;
+		self.spill_displacement = displacement;
+		self.spill_size = size;
+	}
+}
+
+impl Default for RegisterAllocatorOptions {
+	#[inline]
+	fn default() -> Self {
+		RegisterAllocatorOptions::new(CallingConvention::default())
+	}
+}
+
+// Each register that can be allocated is a unit, a bit in a u64 mask:
+// GPRs are bits 0-15, vector registers bits 16-47 and opmask registers bits 48-55
+const GPR_UNIT: u32 = 0;
+const VECTOR_UNIT: u32 = 16;
+const K_UNIT: u32 = 48;
+const UNIT_COUNT: usize = 56;
+const ALL_VECTORS: u64 = 0xFFFF_FFFF << VECTOR_UNIT;
+const ALL_K: u64 = 0xFF << K_UNIT;
+const RSP_UNIT: u32 = GPR_UNIT + 4;
+// Registers that are never allocated. A virtual register operand is replaced with one of these registers when getting
+// the physical registers used by an instruction.
+const DUMMY_UNITS: u64 = (1 << RSP_UNIT) | vectors(16, 16) | (1 << K_UNIT);
+const SPILLED: u32 = u32::MAX;
+
+#[must_use]
+const fn gprs(numbers: &[u32]) -> u64 {
+	let mut mask = 0;
+	let mut i = 0;
+	while i < numbers.len() {
+		mask |= 1 << (GPR_UNIT + numbers[i]);
+		i += 1;
+	}
+	mask
+}
+
+#[must_use]
+const fn vectors(first: u32, count: u32) -> u64 {
+	((1 << count) - 1) << (VECTOR_UNIT + first)
+}
+
+struct ConventionInfo {
+	// Registers that aren't preserved by a callee
+	volatile: u64,
+	// Registers that can contain arguments
+	arguments: u64,
+	// Registers that can contain return values
+	returns: u64,
+	// Preserved vector registers whose upper bits (bits 128+) aren't preserved by a callee
+	volatile_upper: u64,
+	gpr_order: &'static [u32],
+	vector_order: &'static [u32],
+}
+
+static SYSTEM_V64: ConventionInfo = ConventionInfo {
+	volatile: gprs(&[0, 1, 2, 6, 7, 8, 9, 10, 11]) | ALL_VECTORS | ALL_K,
+	arguments: gprs(&[0, 1, 2, 6, 7, 8, 9]) | vectors(0, 8),
+	returns: gprs(&[0, 2]) | vectors(0, 2),
+	volatile_upper: 0,
+	gpr_order: &[10, 11, 0, 9, 8, 1, 2, 6, 7, 3, 12, 13, 14, 15, 5],
+	vector_order: &[8, 9, 10, 11, 12, 13, 14, 15, 7, 6, 5, 4, 3, 2, 1, 0],
+};
+
+static MICROSOFT64: ConventionInfo = ConventionInfo {
+	volatile: gprs(&[0, 1, 2, 8, 9, 10, 11]) | vectors(0, 6) | vectors(16, 16) | ALL_K,
+	arguments: gprs(&[1, 2, 8, 9]) | vectors(0, 4),
+	returns: gprs(&[0]) | vectors(0, 1),
+	volatile_upper: vectors(6, 10),
+	gpr_order: &[10, 11, 0, 9, 8, 2, 1, 3, 6, 7, 12, 13, 14, 15, 5],
+	vector_order: &[5, 4, 3, 2, 1, 0, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+};
+
+static K_ORDER: [u32; 7] = [1, 2, 3, 4, 5, 6, 7];
+
+impl ConventionInfo {
+	fn get(calling_convention: CallingConvention) -> Result<&'static Self, IcedError> {
+		match calling_convention {
+			CallingConvention::SystemV64 => Ok(&SYSTEM_V64),
+			CallingConvention::Microsoft64 => Ok(&MICROSOFT64),
+			_ => Err(IcedError::with_string(format!("The register allocator doesn't support the {} calling convention", calling_convention))),
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum RegisterClass {
+	Gpr = 0,
+	Vector = 1,
+	K = 2,
+}
+const CLASS_COUNT: usize = 3;
+
+impl RegisterClass {
+	#[must_use]
+	fn first_unit(self) -> u32 {
+		match self {
+			RegisterClass::Gpr => GPR_UNIT,
+			RegisterClass::Vector => VECTOR_UNIT,
+			RegisterClass::K => K_UNIT,
+		}
+	}
+
+	#[must_use]
+	fn dummy_unit(self) -> u32 {
+		match self {
+			RegisterClass::Gpr => RSP_UNIT,
+			RegisterClass::Vector => VECTOR_UNIT + 31,
+			RegisterClass::K => K_UNIT,
+		}
+	}
+
+	#[must_use]
+	fn contains(self, register: Register) -> bool {
+		match self {
+			RegisterClass::Gpr => register.is_gpr(),
+			RegisterClass::Vector => register.is_vector_register(),
+			RegisterClass::K => register.is_k(),
+		}
+	}
+
+	// A virtual register is a register that can't be used by an operand of its class
+	#[must_use]
+	fn is_placeholder(self, register: Register) -> bool {
+		!matches!(register, Register::None | Register::EIP | Register::RIP) && !self.contains(register)
+	}
+
+	#[must_use]
+	fn from_operand_kind(kind: OpCodeOperandKind) -> Option<Self> {
+		match kind {
+			OpCodeOperandKind::r8_or_mem
+			| OpCodeOperandKind::r16_or_mem
+			| OpCodeOperandKind::r32_or_mem
+			| OpCodeOperandKind::r32_or_mem_mpx
+			| OpCodeOperandKind::r64_or_mem
+			| OpCodeOperandKind::r64_or_mem_mpx
+			| OpCodeOperandKind::r8_reg
+			| OpCodeOperandKind::r8_opcode
+			| OpCodeOperandKind::r16_reg
+			| OpCodeOperandKind::r16_reg_mem
+			| OpCodeOperandKind::r16_rm
+			| OpCodeOperandKind::r16_opcode
+			| OpCodeOperandKind::r32_reg
+			| OpCodeOperandKind::r32_reg_mem
+			| OpCodeOperandKind::r32_rm
+			| OpCodeOperandKind::r32_opcode
+			| OpCodeOperandKind::r32_vvvv
+			| OpCodeOperandKind::r64_reg
+			| OpCodeOperandKind::r64_reg_mem
+			| OpCodeOperandKind::r64_rm
+			| OpCodeOperandKind::r64_opcode
+			| OpCodeOperandKind::r64_vvvv => Some(RegisterClass::Gpr),
+			OpCodeOperandKind::xmm_or_mem
+			| OpCodeOperandKind::ymm_or_mem
+			| OpCodeOperandKind::zmm_or_mem
+			| OpCodeOperandKind::xmm_reg
+			| OpCodeOperandKind::xmm_rm
+			| OpCodeOperandKind::xmm_vvvv
+			| OpCodeOperandKind::xmm_is4
+			| OpCodeOperandKind::xmm_is5
+			| OpCodeOperandKind::ymm_reg
+			| OpCodeOperandKind::ymm_rm
+			| OpCodeOperandKind::ymm_vvvv
+			| OpCodeOperandKind::ymm_is4
+			| OpCodeOperandKind::ymm_is5
+			| OpCodeOperandKind::zmm_reg
+			| OpCodeOperandKind::zmm_rm
+			| OpCodeOperandKind::zmm_vvvv => Some(RegisterClass::Vector),
+			OpCodeOperandKind::k_or_mem | OpCodeOperandKind::k_reg | OpCodeOperandKind::k_rm | OpCodeOperandKind::k_vvvv => Some(RegisterClass::K),
+			_ => None,
+		}
+	}
+}
+
+#[must_use]
+fn is_vsib(kind: OpCodeOperandKind) -> bool {
+	matches!(
+		kind,
+		OpCodeOperandKind::mem_vsib32x
+			| OpCodeOperandKind::mem_vsib64x
+			| OpCodeOperandKind::mem_vsib32y
+			| OpCodeOperandKind::mem_vsib64y
+			| OpCodeOperandKind::mem_vsib32z
+			| OpCodeOperandKind::mem_vsib64z
+	)
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum VirtualRegisterKind {
+	Gpr8,
+	Gpr16,
+	Gpr32,
+	Gpr64,
+	Xmm,
+	Ymm,
+	Zmm,
+	K,
+}
+
+impl VirtualRegisterKind {
+	#[must_use]
+	fn class(self) -> RegisterClass {
+		match self {
+			VirtualRegisterKind::Gpr8 | VirtualRegisterKind::Gpr16 | VirtualRegisterKind::Gpr32 | VirtualRegisterKind::Gpr64 => RegisterClass::Gpr,
+			VirtualRegisterKind::Xmm | VirtualRegisterKind::Ymm | VirtualRegisterKind::Zmm => RegisterClass::Vector,
+			VirtualRegisterKind::K => RegisterClass::K,
+		}
+	}
+
+	// Size of its spill slot
+	#[must_use]
+	fn size(self) -> u32 {
+		match self {
+			VirtualRegisterKind::Gpr8
+			| VirtualRegisterKind::Gpr16
+			| VirtualRegisterKind::Gpr32
+			| VirtualRegisterKind::Gpr64
+			| VirtualRegisterKind::K => 8,
+			VirtualRegisterKind::Xmm => 16,
+			VirtualRegisterKind::Ymm => 32,
+			VirtualRegisterKind::Zmm => 64,
+		}
+	}
+
+	#[must_use]
+	fn dummy_register(self) -> Register {
+		self.register(self.class().dummy_unit())
+	}
+
+	#[must_use]
+	fn register(self, unit: u32) -> Register {
+		let number = (unit - self.class().first_unit()) as usize;
+		let register = match self {
+			VirtualRegisterKind::Gpr8 => {
+				if number < 4 {
+					Register::AL as usize + number
+				} else if number < 8 {
+					Register::SPL as usize + number - 4
+				} else {
+					Register::R8L as usize + number - 8
+				}
+			}
+			VirtualRegisterKind::Gpr16 => Register::AX as usize + number,
+			VirtualRegisterKind::Gpr32 => Register::EAX as usize + number,
+			VirtualRegisterKind::Gpr64 => Register::RAX as usize + number,
+			VirtualRegisterKind::Xmm => Register::XMM0 as usize + number,
+			VirtualRegisterKind::Ymm => Register::YMM0 as usize + number,
+			VirtualRegisterKind::Zmm => Register::ZMM0 as usize + number,
+			VirtualRegisterKind::K => Register::K0 as usize + number,
+		};
+		Register::try_from(register).unwrap_or_default()
+	}
+
+	// Returns the load and store instructions used to spill a register. `encoding` is the encoding of the instruction
+	// that uses the register so legacy SSE code doesn't get VEX loads and stores.
+	#[must_use]
+	fn load_store(self, encoding: EncodingKind) -> (Code, Code) {
+		match self {
+			VirtualRegisterKind::Gpr8 => (Code::Mov_r8_rm8, Code::Mov_rm8_r8),
+			VirtualRegisterKind::Gpr16 => (Code::Mov_r16_rm16, Code::Mov_rm16_r16),
+			VirtualRegisterKind::Gpr32 => (Code::Mov_r32_rm32, Code::Mov_rm32_r32),
+			VirtualRegisterKind::Gpr64 => (Code::Mov_r64_rm64, Code::Mov_rm64_r64),
+			VirtualRegisterKind::Xmm => {
+				if encoding == EncodingKind::Legacy {
+					(Code::Movups_xmm_xmmm128, Code::Movups_xmmm128_xmm)
+				} else {
+					(Code::VEX_Vmovups_xmm_xmmm128, Code::VEX_Vmovups_xmmm128_xmm)
+				}
+			}
+			VirtualRegisterKind::Ymm => (Code::VEX_Vmovups_ymm_ymmm256, Code::VEX_Vmovups_ymmm256_ymm),
+			VirtualRegisterKind::Zmm => (Code::EVEX_Vmovups_zmm_k1z_zmmm512, Code::EVEX_Vmovups_zmmm512_k1z_zmm),
+			VirtualRegisterKind::K => (Code::VEX_Kmovq_kr_km64, Code::VEX_Kmovq_m64_kr),
+		}
+	}
+
+	// `true` if the register uses more than the lower 128 bits of a vector register
+	#[must_use]
+	fn is_wide_vector(self) -> bool {
+		matches!(self, VirtualRegisterKind::Ymm | VirtualRegisterKind::Zmm)
+	}
+}
+
+#[must_use]
+fn physical_unit(register: Register) -> Option<u32> {
+	if register.is_gpr() {
+		Some(GPR_UNIT + register.full_register().number() as u32)
+	} else if register.is_vector_register() {
+		Some(VECTOR_UNIT + register.number() as u32)
+	} else if register.is_k() {
+		Some(K_UNIT + register.number() as u32)
+	} else {
+		None
+	}
+}
+
+// Writing to an 8/16-bit GPR or to an XMM register with a legacy instruction doesn't modify the other bits
+#[must_use]
+fn is_partial_write(register: Register) -> bool {
+	register.is_gpr8() || register.is_gpr16() || register.is_xmm() || register.is_ymm()
+}
+
+#[must_use]
+fn read_write(access: OpAccess) -> (bool, bool) {
+	match access {
+		OpAccess::Read | OpAccess::CondRead => (true, false),
+		OpAccess::Write => (false, true),
+		OpAccess::CondWrite | OpAccess::ReadWrite | OpAccess::ReadCondWrite => (true, true),
+		_ => (false, false),
+	}
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Slot {
+	Register(u32),
+	MemoryBase,
+	MemoryIndex,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct VirtualOperand {
+	slot: Slot,
+	// Index into `Function::vregs`
+	vreg: usize,
+	read: bool,
+	write: bool,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Successors {
+	None,
+	Next,
+	Target(usize),
+	NextAndTarget(usize),
+	AllLabels,
+}
+
+// Sorted, non-overlapping ranges
+#[must_use]
+fn overlaps(ranges: &[(u32, u32)], start: u32, end: u32) -> bool {
+	let index = ranges.partition_point(|&(_, range_end)| range_end < start);
+	index < ranges.len() && ranges[index].0 <= end
+}
+
+#[must_use]
+fn contains_point(points: &[u32], start: u32, end: u32) -> bool {
+	let index = points.partition_point(|&point| point < start);
+	index < points.len() && points[index] <= end
+}
+
+// All virtual registers used by a section. Instruction `i` reads its operands at point `2 * i` and writes them at point `2 * i + 1`.
+struct Function<'a> {
+	conv: &'static ConventionInfo,
+	instructions: &'a [Instruction],
+	// Kinds of all virtual registers used by the section
+	vregs: Vec<VirtualRegisterKind>,
+	operands: Vec<VirtualOperand>,
+	// Index of the first operand of each instruction, len = instructions.len() + 1
+	first_operand: Vec<usize>,
+	starts: Vec<u32>,
+	ends: Vec<u32>,
+	// Points where a physical register is alive or used, indexed by unit
+	physical: Vec<Vec<(u32, u32)>>,
+	// Write points of all calls
+	calls: Vec<u32>,
+}
+
+impl Function<'_> {
+	#[must_use]
+	fn operands(&self, index: usize) -> &[VirtualOperand] {
+		&self.operands[self.first_operand[index]..self.first_operand[index + 1]]
+	}
+
+	#[must_use]
+	fn is_free(&self, unit: u32, vreg: usize) -> bool {
+		let (start, end) = (self.starts[vreg], self.ends[vreg]);
+		if overlaps(&self.physical[unit as usize], start, end) {
+			return false;
+		}
+		// The upper bits of some preserved vector registers are clobbered by a call
+		!(self.vregs[vreg].is_wide_vector() && (self.conv.volatile_upper & (1 << unit)) != 0 && contains_point(&self.calls, start, end))
+	}
+
+	// Returns the unit of each virtual register or `SPILLED`
+	#[must_use]
+	fn linear_scan(&self, orders: &[&[u32]; CLASS_COUNT]) -> Vec<u32> {
+		let mut sorted: Vec<usize> = (0..self.vregs.len()).collect();
+		sorted.sort_by_key(|&vreg| (self.starts[vreg], vreg));
+		let mut assigned = vec![SPILLED; self.vregs.len()];
+		let mut active: Vec<usize> = Vec::new();
+		for vreg in sorted {
+			let start = self.starts[vreg];
+			active.retain(|&other| self.ends[other] >= start);
+			let order = orders[self.vregs[vreg].class() as usize];
+			let busy = active.iter().fold(0u64, |mask, &other| mask | (1 << assigned[other]));
+			if let Some(&unit) = order.iter().find(|&&unit| (busy & (1 << unit)) == 0 && self.is_free(unit, vreg)) {
+				assigned[vreg] = unit;
+				active.push(vreg);
+				continue;
+			}
+			// Spill the interval that ends last, which could be this one
+			let candidate = active
+				.iter()
+				.enumerate()
+				.filter(|&(_, &other)| self.ends[other] > self.ends[vreg] && order.contains(&assigned[other]) && self.is_free(assigned[other], vreg))
+				.max_by_key(|&(_, &other)| self.ends[other])
+				.map(|(index, &other)| (index, other));
+			if let Some((index, other)) = candidate {
+				assigned[vreg] = assigned[other];
+				assigned[other] = SPILLED;
+				active[index] = vreg;
+			}
+		}
+		assigned
+	}
+
+	// Returns the max number of spilled virtual registers used by an instruction, per class
+	#[must_use]
+	fn spilled_per_instruction(&self, assigned: &[u32]) -> [usize; CLASS_COUNT] {
+		let mut result = [0; CLASS_COUNT];
+		for i in 0..self.instructions.len() {
+			let operands = self.operands(i);
+			let mut counts = [0; CLASS_COUNT];
+			for (index, operand) in operands.iter().enumerate() {
+				if assigned[operand.vreg] == SPILLED && !operands[..index].iter().any(|other| other.vreg == operand.vreg) {
+					counts[self.vregs[operand.vreg].class() as usize] += 1;
+				}
+			}
+			for (result, count) in result.iter_mut().zip(counts.iter()) {
+				*result = (*result).max(*count);
+			}
+		}
+		result
+	}
+}
+
+/// Result of allocating the registers of a section
+pub(crate) struct AllocatedSection {
+	pub(crate) instructions: Vec<Instruction>,
+	pub(crate) directives: Vec<(usize, BlockDirective)>,
+	// Index of the first added instruction (a load or the instruction itself) and index of the rewritten instruction of each original instruction
+	indexes: Vec<(usize, usize)>,
+	pub(crate) spill_size: u32,
+}
+
+impl AllocatedSection {
+	/// Converts the instruction offsets to offsets of the original instructions
+	pub(crate) fn remap_result(&self, result: &mut BlockEncoderResult) {
+		if !result.new_instruction_offsets.is_empty() {
+			result.new_instruction_offsets = self.indexes.iter().map(|&(first, _)| result.new_instruction_offsets[first]).collect();
+		}
+		if !result.constant_offsets.is_empty() {
+			result.constant_offsets = self.indexes.iter().map(|&(_, index)| result.constant_offsets[index]).collect();
+		}
+	}
+}
+
+/// Virtual registers created by a `CodeAssembler` and the register allocator options
+pub(crate) struct RegisterAllocator {
+	options: RegisterAllocatorOptions,
+	kinds: Vec<VirtualRegisterKind>,
+	// Virtual register index (or u32::MAX) of each (operand class, placeholder register)
+	ids: Vec<u32>,
+}
+
+impl RegisterAllocator {
+	#[must_use]
+	#[inline]
+	pub(crate) fn new() -> Self {
+		Self { options: RegisterAllocatorOptions::default(), kinds: Vec::new(), ids: Vec::new() }
+	}
+
+	#[must_use]
+	#[inline]
+	pub(crate) fn options(&self) -> &RegisterAllocatorOptions {
+		&self.options
+	}
+
+	#[inline]
+	pub(crate) fn set_options(&mut self, options: RegisterAllocatorOptions) {
+		self.options = options;
+	}
+
+	#[inline]
+	pub(crate) fn reset(&mut self) {
+		self.kinds.clear();
+		self.ids.clear();
+	}
+
+	/// Creates a virtual register and returns its placeholder register
+	pub(crate) fn create(&mut self, kind: VirtualRegisterKind) -> Result<Register, IcedError> {
+		let class = kind.class();
+		let count = self.kinds.iter().filter(|other| other.class() == class).count();
+		let placeholder = if let Some(placeholder) = Register::values().filter(|&register| class.is_placeholder(register)).nth(count) {
+			placeholder
+		} else {
+			return Err(IcedError::new("Too many virtual registers"));
+		};
+		if self.ids.is_empty() {
+			self.ids = vec![u32::MAX; CLASS_COUNT * IcedConstants::REGISTER_ENUM_COUNT];
+		}
+		self.ids[class as usize * IcedConstants::REGISTER_ENUM_COUNT + placeholder as usize] = self.kinds.len() as u32;
+		self.kinds.push(kind);
+		Ok(placeholder)
+	}
+
+	#[must_use]
+	fn id(&self, class: RegisterClass, register: Register) -> Option<usize> {
+		match self.ids.get(class as usize * IcedConstants::REGISTER_ENUM_COUNT + register as usize) {
+			Some(&id) if id != u32::MAX => Some(id as usize),
+			_ => None,
+		}
+	}
+
+	// Returns the units that can be allocated, per class
+	fn allocation_orders(&self, conv: &ConventionInfo) -> Result<[Vec<u32>; CLASS_COUNT], IcedError> {
+		let mut saved = 0u64;
+		for &register in &self.options.saved_registers {
+			match physical_unit(register) {
+				Some(unit) if (register.is_gpr64() || register.is_xmm()) && unit != RSP_UNIT && (conv.volatile & (1 << unit)) == 0 => {
+					saved |= 1 << unit
+				}
+				_ => {
+					return Err(IcedError::with_string(format!(
+						"{:?} isn't a callee-saved register in the {} calling convention",
+						register, self.options.calling_convention
+					)))
+				}
+			}
+		}
+		let mut excluded = 1u64 << RSP_UNIT;
+		if self.options.spill_base != Register::None {
+			match physical_unit(self.options.spill_base) {
+				Some(unit) if self.options.spill_base.is_gpr64() => excluded |= 1 << unit,
+				_ => return Err(IcedError::new("The spill area base register must be a 64-bit GPR")),
+			}
+		}
+		let allocatable = (conv.volatile | saved) & !excluded;
+		let order = |class: RegisterClass, numbers: &[u32]| -> Vec<u32> {
+			numbers.iter().map(|&number| class.first_unit() + number).filter(|&unit| (allocatable & (1 << unit)) != 0).collect()
+		};
+		Ok([order(RegisterClass::Gpr, conv.gpr_order), order(RegisterClass::Vector, conv.vector_order), order(RegisterClass::K, &K_ORDER)])
+	}
+
+	/// Allocates all virtual registers used by the instructions of a section. Returns `None` if no virtual register is used.
+	pub(crate) fn allocate(
+		&self, instructions: &[Instruction], directives: &[(usize, BlockDirective)],
+	) -> Result<Option<AllocatedSection>, IcedError> {
+		if self.kinds.is_empty() {
+			return Ok(None);
+		}
+		let conv = ConventionInfo::get(self.options.calling_convention)?;
+		let count = instructions.len();
+
+		// Find all virtual register operands and all physical registers used by each instruction
+		let mut factory = InstructionInfoFactory::new();
+		let mut vregs = Vec::new();
+		let mut locals = vec![usize::MAX; self.kinds.len()];
+		let mut operands: Vec<VirtualOperand> = Vec::new();
+		let mut first_operand = Vec::with_capacity(count + 1);
+		let mut reads = Vec::with_capacity(count);
+		let mut writes = Vec::with_capacity(count);
+		let mut calls = Vec::new();
+		let mut returns = Vec::new();
+		for (i, instruction) in instructions.iter().enumerate() {
+			first_operand.push(operands.len());
+			let mut physical = *instruction;
+			let op_code = instruction.op_code();
+			let mut add_operand = |slot: Slot, id: usize| {
+				if locals[id] == usize::MAX {
+					locals[id] = vregs.len();
+					vregs.push(self.kinds[id]);
+				}
+				operands.push(VirtualOperand { slot, vreg: locals[id], read: true, write: false });
+			};
+			for op in 0..instruction.op_count() {
+				match instruction.op_kind(op) {
+					OpKind::Register => {
+						if let Some(class) = RegisterClass::from_operand_kind(op_code.op_kind(op)) {
+							if let Some(id) = self.id(class, instruction.op_register(op)) {
+								add_operand(Slot::Register(op), id);
+								physical.try_set_op_register(op, self.kinds[id].dummy_register())?;
+							}
+						}
+					}
+					OpKind::Memory => {
+						if let Some(id) = self.id(RegisterClass::Gpr, instruction.memory_base()) {
+							add_operand(Slot::MemoryBase, id);
+							physical.set_memory_base(self.kinds[id].dummy_register());
+						}
+						let index_class = if is_vsib(op_code.op_kind(op)) { RegisterClass::Vector } else { RegisterClass::Gpr };
+						if let Some(id) = self.id(index_class, instruction.memory_index()) {
+							add_operand(Slot::MemoryIndex, id);
+							physical.set_memory_index(self.kinds[id].dummy_register());
+						}
+					}
+					_ => {}
+				}
+			}
+			let start = first_operand[i];
+			if operands.len() != start {
+				let info = factory.info_options(instruction, InstructionInfoOptions::NO_MEMORY_USAGE | InstructionInfoOptions::NO_REGISTER_USAGE);
+				for operand in &mut operands[start..] {
+					if let Slot::Register(op) = operand.slot {
+						let (read, write) = read_write(info.op_access(op));
+						operand.read = read;
+						operand.write = write;
+					}
+				}
+			}
+
+			let mut read_units = 0u64;
+			let mut write_units = 0u64;
+			for used in factory.info_options(&physical, InstructionInfoOptions::NO_MEMORY_USAGE).used_registers() {
+				if let Some(unit) = physical_unit(used.register()).filter(|&unit| (DUMMY_UNITS & (1 << unit)) == 0) {
+					let (read, write) = read_write(used.access());
+					if read || (write && is_partial_write(used.register())) {
+						read_units |= 1 << unit;
+					}
+					if write {
+						write_units |= 1 << unit;
+					}
+				}
+			}
+			match instruction.flow_control() {
+				FlowControl::Call | FlowControl::IndirectCall => {
+					read_units |= conv.arguments;
+					write_units |= conv.volatile;
+					calls.push(2 * i as u32 + 1);
+				}
+				FlowControl::Return => returns.push(i),
+				_ => {}
+			}
+			reads.push(read_units);
+			writes.push(write_units);
+		}
+		first_operand.push(operands.len());
+		if vregs.is_empty() {
+			return Ok(None);
+		}
+		// Only return values written by this code are read by a RET
+		let written = writes.iter().fold(0, |mask, &units| mask | units);
+		for &i in &returns {
+			reads[i] |= conv.returns & written;
+		}
+
+		// Create the control flow graph. Instructions with labels have an `ip` equal to the label id.
+		let mut labels: Vec<(u64, usize)> =
+			instructions.iter().enumerate().filter(|(_, instruction)| instruction.ip() != 0).map(|(i, instruction)| (instruction.ip(), i)).collect();
+		labels.sort_unstable();
+		let successors: Vec<Successors> = instructions
+			.iter()
+			.map(|instruction| {
+				let target = match instruction.op0_kind() {
+					OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 => {
+						labels.binary_search_by_key(&instruction.near_branch_target(), |&(id, _)| id).ok().map(|index| labels[index].1)
+					}
+					_ => None,
+				};
+				match instruction.flow_control() {
+					FlowControl::Next | FlowControl::Call | FlowControl::IndirectCall | FlowControl::Interrupt => Successors::Next,
+					FlowControl::UnconditionalBranch => target.map_or(Successors::None, Successors::Target),
+					FlowControl::ConditionalBranch | FlowControl::XbeginXabortXend => target.map_or(Successors::Next, Successors::NextAndTarget),
+					FlowControl::IndirectBranch => Successors::AllLabels,
+					_ => Successors::None,
+				}
+			})
+			.collect();
+
+		// Liveness analysis
+		let words = (vregs.len() + 63) / 64;
+		let mut uses = vec![0u64; count * words];
+		let mut defs = vec![0u64; count * words];
+		for i in 0..count {
+			for operand in &operands[first_operand[i]..first_operand[i + 1]] {
+				let (word, bit) = (i * words + operand.vreg / 64, 1u64 << (operand.vreg % 64));
+				if operand.read {
+					uses[word] |= bit;
+				} else if operand.write {
+					defs[word] |= bit;
+				}
+			}
+		}
+		let mut live_in = vec![0u64; count * words];
+		let mut live_out = vec![0u64; count * words];
+		let mut physical_in = vec![0u64; count];
+		let mut physical_out = vec![0u64; count];
+		let mut changed = true;
+		while changed {
+			changed = false;
+			for i in (0..count).rev() {
+				let mut out_units = 0u64;
+				let mut add_successor = |live_out: &mut [u64], successor: usize| {
+					if successor < count {
+						for word in 0..words {
+							live_out[i * words + word] |= live_in[successor * words + word];
+						}
+						out_units |= physical_in[successor];
+					}
+				};
+				match successors[i] {
+					Successors::None => {}
+					Successors::Next => add_successor(&mut live_out, i + 1),
+					Successors::Target(target) => add_successor(&mut live_out, target),
+					Successors::NextAndTarget(target) => {
+						add_successor(&mut live_out, i + 1);
+						add_successor(&mut live_out, target);
+					}
+					Successors::AllLabels => {
+						for &(_, target) in &labels {
+							add_successor(&mut live_out, target);
+						}
+					}
+				}
+				physical_out[i] = out_units;
+				let in_units = reads[i] | (out_units & !writes[i]);
+				if in_units != physical_in[i] {
+					physical_in[i] = in_units;
+					changed = true;
+				}
+				for word in i * words..(i + 1) * words {
+					let value = uses[word] | (live_out[word] & !defs[word]);
+					if value != live_in[word] {
+						live_in[word] = value;
+						changed = true;
+					}
+				}
+			}
+		}
+
+		// Live intervals
+		let mut starts = vec![u32::MAX; vregs.len()];
+		let mut ends = vec![0u32; vregs.len()];
+		let mut mark = |vreg: usize, point: u32| {
+			starts[vreg] = starts[vreg].min(point);
+			ends[vreg] = ends[vreg].max(point);
+		};
+		for i in 0..count {
+			let (before, after) = (2 * i as u32, 2 * i as u32 + 1);
+			for word in 0..words {
+				for (point, mut bits) in [(before, live_in[i * words + word]), (after, live_out[i * words + word])] {
+					while bits != 0 {
+						mark(word * 64 + bits.trailing_zeros() as usize, point);
+						bits &= bits - 1;
+					}
+				}
+			}
+			for operand in &operands[first_operand[i]..first_operand[i + 1]] {
+				if operand.read || !operand.write {
+					mark(operand.vreg, before);
+				}
+				if operand.write {
+					mark(operand.vreg, after);
+				}
+			}
+		}
+		let mut physical: Vec<Vec<(u32, u32)>> = vec![Vec::new(); UNIT_COUNT];
+		for i in 0..count {
+			let (before, after) = (2 * i as u32, 2 * i as u32 + 1);
+			for (point, mut units) in [(before, physical_in[i] | reads[i]), (after, physical_out[i] | writes[i])] {
+				units &= (1 << UNIT_COUNT) - 1;
+				while units != 0 {
+					let ranges = &mut physical[units.trailing_zeros() as usize];
+					match ranges.last_mut() {
+						Some(last) if last.1 + 1 >= point => last.1 = point,
+						_ => ranges.push((point, point)),
+					}
+					units &= units - 1;
+				}
+			}
+		}
+
+		let function = Function { conv, instructions, vregs, operands, first_operand, starts, ends, physical, calls };
+		self.rewrite(&function, directives).map(Some)
+	}
+
+	fn rewrite(&self, function: &Function<'_>, directives: &[(usize, BlockDirective)]) -> Result<AllocatedSection, IcedError> {
+		// Allocate registers. The last registers of each class are reserved for spilled virtual registers.
+		let orders = self.allocation_orders(function.conv)?;
+		let mut reserved = [0usize; CLASS_COUNT];
+		let assigned = loop {
+			let mut usable: [&[u32]; CLASS_COUNT] = [&[], &[], &[]];
+			for (class, usable) in usable.iter_mut().enumerate() {
+				*usable = &orders[class][..orders[class].len() - reserved[class]];
+			}
+			let assigned = function.linear_scan(&usable);
+			let mut again = false;
+			for (class, &needed) in function.spilled_per_instruction(&assigned).iter().enumerate() {
+				let needed = needed.min(orders[class].len());
+				if needed > reserved[class] {
+					reserved[class] = needed;
+					again = true;
+				}
+			}
+			if !again {
+				break assigned;
+			}
+		};
+
+		// Assign spill slots, largest slots first so they're all naturally aligned
+		let mut spilled: Vec<usize> = (0..function.vregs.len()).filter(|&vreg| assigned[vreg] == SPILLED).collect();
+		spilled.sort_by_key(|&vreg| core::cmp::Reverse(function.vregs[vreg].size()));
+		let mut slots = vec![0u32; function.vregs.len()];
+		let mut spill_size = 0u32;
+		for &vreg in &spilled {
+			slots[vreg] = spill_size;
+			spill_size += function.vregs[vreg].size();
+		}
+		if spill_size != 0 {
+			if self.options.spill_base == Register::None {
+				return Err(IcedError::with_string(format!(
+					"Not enough registers, a {}-byte spill area is needed. See `RegisterAllocatorOptions::set_spill_area()`",
+					spill_size
+				)));
+			}
+			if spill_size > self.options.spill_size {
+				return Err(IcedError::with_string(format!(
+					"The spill area is too small: {} bytes, {} bytes are needed",
+					self.options.spill_size, spill_size
+				)));
+			}
+		}
+
+		let mut allocated: Vec<Vec<(u32, u32)>> = vec![Vec::new(); UNIT_COUNT];
+		for (vreg, &unit) in assigned.iter().enumerate() {
+			if unit != SPILLED {
+				allocated[unit as usize].push((function.starts[vreg], function.ends[vreg]));
+			}
+		}
+		for ranges in &mut allocated {
+			ranges.sort_unstable();
+		}
+
+		let mut instructions = Vec::with_capacity(function.instructions.len() + spilled.len());
+		let mut indexes = Vec::with_capacity(function.instructions.len());
+		let mut scratch: Vec<(usize, u32)> = Vec::new();
+		let mut stores = Vec::new();
+		for (i, original) in function.instructions.iter().enumerate() {
+			let (before, after) = (2 * i as u32, 2 * i as u32 + 1);
+			let mut instruction = *original;
+			scratch.clear();
+			let mut used = 0u64;
+			for operand in function.operands(i) {
+				let kind = function.vregs[operand.vreg];
+				let unit = if assigned[operand.vreg] != SPILLED {
+					assigned[operand.vreg]
+				} else if let Some(&(_, unit)) = scratch.iter().find(|&&(vreg, _)| vreg == operand.vreg) {
+					unit
+				} else {
+					// Prefer the reserved registers at the end of the allocation order
+					let unit = orders[kind.class() as usize].iter().rev().copied().find(|&unit| {
+						(used & (1 << unit)) == 0
+							&& !overlaps(&function.physical[unit as usize], before, after)
+							&& !overlaps(&allocated[unit as usize], before, after)
+					});
+					let unit = if let Some(unit) = unit {
+						unit
+					} else {
+						return Err(IcedError::new("Not enough registers to load a spilled virtual register"));
+					};
+					used |= 1 << unit;
+					scratch.push((operand.vreg, unit));
+					unit
+				};
+				let register = kind.register(unit);
+				match operand.slot {
+					Slot::Register(op) => instruction.try_set_op_register(op, register)?,
+					Slot::MemoryBase => instruction.set_memory_base(register),
+					Slot::MemoryIndex => instruction.set_memory_index(register),
+				}
+			}
+
+			let first = instructions.len();
+			stores.clear();
+			for &(vreg, unit) in &scratch {
+				let kind = function.vregs[vreg];
+				let register = kind.register(unit);
+				let memory = MemoryOperand::with_base_displ(self.options.spill_base, self.options.spill_displacement as i64 + slots[vreg] as i64);
+				let (load, store) = kind.load_store(original.encoding());
+				let operands = function.operands(i).iter().filter(|operand| operand.vreg == vreg);
+				let (read, write) = operands.fold((false, false), |(read, write), operand| (read || operand.read, write || operand.write));
+				if read {
+					instructions.push(Instruction::with2(load, register, memory)?);
+				}
+				if write {
+					stores.push(Instruction::with2(store, memory, register)?);
+				}
+			}
+			// The label (if any) is now the first load
+			if first != instructions.len() {
+				instructions[first].set_ip(instruction.ip());
+				instruction.set_ip(0);
+			}
+			indexes.push((first, instructions.len()));
+			instructions.push(instruction);
+			instructions.extend_from_slice(&stores);
+		}
+
+		let directives = directives.iter().map(|&(index, directive)| (indexes[index].1, directive)).collect();
+		Ok(AllocatedSection { instructions, directives, indexes, spill_size })
+	}
+}
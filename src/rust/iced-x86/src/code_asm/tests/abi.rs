@@ -0,0 +1,377 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::*;
+use crate::{Code, Instruction, Register};
+use alloc::vec::Vec;
+
+const IP: u64 = 0x1000;
+
+fn assemble(a: &mut CodeAssembler) -> Vec<u8> {
+	a.assemble(IP).unwrap()
+}
+
+fn frame_options(calling_convention: CallingConvention, saved_registers: &[Register], local_size: u32) -> FrameOptions {
+	let mut options = FrameOptions::new(calling_convention);
+	options.set_saved_registers(saved_registers);
+	options.set_local_size(local_size);
+	options
+}
+
+#[test]
+fn calling_convention_registers() {
+	let conventions = [
+		CallingConvention::SystemV64,
+		CallingConvention::Microsoft64,
+		CallingConvention::Cdecl,
+		CallingConvention::Stdcall,
+		CallingConvention::Fastcall,
+	];
+	for &cc in &conventions {
+		for &register in &[Register::RAX, Register::RCX, Register::RDX, Register::R8, Register::R11, Register::XMM0, Register::XMM5, Register::EAX] {
+			assert!(!cc.is_callee_saved(register), "{} {:?}", cc, register);
+		}
+	}
+	assert_eq!(CallingConvention::default(), CallingConvention::SystemV64);
+	assert!(!CallingConvention::SystemV64.is_callee_saved(Register::XMM6));
+	assert!(CallingConvention::Microsoft64.is_callee_saved(Register::XMM15));
+	assert!(!CallingConvention::Microsoft64.is_callee_saved(Register::XMM16));
+	assert!(!CallingConvention::Cdecl.is_callee_saved(Register::RBX));
+	assert!(!CallingConvention::Cdecl.callee_pops_arguments());
+	assert!(CallingConvention::Stdcall.callee_pops_arguments());
+	assert!(CallingConvention::Fastcall.callee_pops_arguments());
+	assert!(!CallingConvention::Microsoft64.callee_pops_arguments());
+}
+
+#[test]
+fn system_v64_frame_pointer() {
+	let mut options = frame_options(CallingConvention::SystemV64, &[Register::RBX, Register::R12], 0x18);
+	options.set_frame_pointer(true);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	a.mov(qword_ptr(rsp + frame.locals_offset()), rdi).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(64).unwrap();
+	e.push(rbp).unwrap();
+	e.mov(rbp, rsp).unwrap();
+	e.push(rbx).unwrap();
+	e.push(r12).unwrap();
+	e.sub(rsp, 0x20).unwrap();
+	e.mov(qword_ptr(rsp), rdi).unwrap();
+	e.lea(rsp, ptr(rbp - 0x10)).unwrap();
+	e.pop(r12).unwrap();
+	e.pop(rbx).unwrap();
+	e.pop(rbp).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+
+	assert_eq!(frame.locals_offset(), 0);
+	assert_eq!(frame.size(), 0x38);
+	// Stack pointer + pushed registers + return address must be a multiple of 16
+	assert_eq!((frame.size() + 8) % 16, 0);
+	assert_eq!(frame.stack_arguments_offset(), 0x40);
+	assert_eq!(frame.prologue_size(), 11);
+	assert!(frame.unwind_info().is_err());
+}
+
+#[test]
+fn system_v64_only_frame_pointer() {
+	let mut options = FrameOptions::new(CallingConvention::SystemV64);
+	options.set_frame_pointer(true);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(64).unwrap();
+	e.push(rbp).unwrap();
+	e.mov(rbp, rsp).unwrap();
+	e.mov(rsp, rbp).unwrap();
+	e.pop(rbp).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+	assert_eq!(frame.size(), 8);
+}
+
+#[test]
+fn microsoft64_frame() {
+	let mut options = frame_options(CallingConvention::Microsoft64, &[Register::XMM6, Register::RSI, Register::XMM7], 8);
+	options.set_frame_pointer(true);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(64).unwrap();
+	e.push(rbp).unwrap();
+	e.push(rsi).unwrap();
+	e.sub(rsp, 0x58).unwrap();
+	e.mov(rbp, rsp).unwrap();
+	e.movaps(xmmword_ptr(rsp + 0x30), xmm6).unwrap();
+	e.movaps(xmmword_ptr(rsp + 0x40), xmm7).unwrap();
+	e.movaps(xmm6, xmmword_ptr(rbp + 0x30)).unwrap();
+	e.movaps(xmm7, xmmword_ptr(rbp + 0x40)).unwrap();
+	e.lea(rsp, ptr(rbp + 0x58)).unwrap();
+	e.pop(rsi).unwrap();
+	e.pop(rbp).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+
+	// Shadow space + locals
+	assert_eq!(frame.locals_offset(), 0x20);
+	assert_eq!(frame.stack_arguments_offset(), 0x58 + 0x10 + 8 + 0x20);
+	assert_eq!(frame.prologue_size(), 19);
+	#[rustfmt::skip]
+	let expected = [
+		0x01, 19, 8, 0x05,
+		// SAVE_XMM128 XMM7
+		19, 0x78, 0x04, 0x00,
+		// SAVE_XMM128 XMM6
+		14, 0x68, 0x03, 0x00,
+		// SET_FPREG
+		9, 0x03,
+		// ALLOC_SMALL 0x58
+		6, 0xA2,
+		// PUSH_NONVOL RSI
+		2, 0x60,
+		// PUSH_NONVOL RBP
+		1, 0x50,
+	];
+	assert_eq!(frame.unwind_info().unwrap(), expected);
+}
+
+#[test]
+fn microsoft64_leaf_frame() {
+	let mut options = FrameOptions::new(CallingConvention::Microsoft64);
+	options.set_leaf(true);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(64).unwrap();
+	e.sub(rsp, 8).unwrap();
+	e.add(rsp, 8).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+	assert_eq!(frame.locals_offset(), 0);
+	assert_eq!(frame.unwind_info().unwrap(), [0x01, 4, 1, 0x00, 4, 0x02, 0x00, 0x00]);
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	assert!(a.call_with_args(&frame, CallingConvention::Microsoft64, rax, &[]).is_err());
+}
+
+#[test]
+fn microsoft64_large_allocations() {
+	let mut options = frame_options(CallingConvention::Microsoft64, &[], 0x1000);
+	options.set_leaf(true);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	let mut e = CodeAssembler::new(64).unwrap();
+	e.sub(rsp, 0x1008).unwrap();
+	assert_eq!(assemble(&mut a), assemble(&mut e));
+	assert_eq!(frame.unwind_info().unwrap(), [0x01, 7, 2, 0x00, 7, 0x01, 0x01, 0x02]);
+
+	options.set_local_size(0x80000);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	assert_eq!(frame.size(), 0x80008);
+	assert_eq!(frame.unwind_info().unwrap(), [0x01, 7, 3, 0x00, 7, 0x11, 0x08, 0x00, 0x08, 0x00, 0x00, 0x00]);
+}
+
+#[test]
+fn stdcall_frame() {
+	let mut options = frame_options(CallingConvention::Stdcall, &[Register::ESI], 0);
+	options.set_frame_pointer(true);
+	options.set_argument_stack_size(12);
+	let mut a = CodeAssembler::new(32).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	a.mov(eax, dword_ptr(esp + frame.stack_arguments_offset())).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(32).unwrap();
+	e.push(ebp).unwrap();
+	e.mov(ebp, esp).unwrap();
+	e.push(esi).unwrap();
+	e.sub(esp, 4).unwrap();
+	e.mov(eax, dword_ptr(esp + 0x10)).unwrap();
+	e.lea(esp, ptr(ebp - 4)).unwrap();
+	e.pop(esi).unwrap();
+	e.pop(ebp).unwrap();
+	e.ret_1(12).unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+}
+
+#[test]
+fn cdecl_frame() {
+	let options = frame_options(CallingConvention::Cdecl, &[Register::EBX, Register::EDI], 0x10);
+	let mut a = CodeAssembler::new(32).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(32).unwrap();
+	e.push(ebx).unwrap();
+	e.push(edi).unwrap();
+	e.sub(esp, 0x14).unwrap();
+	e.add(esp, 0x14).unwrap();
+	e.pop(edi).unwrap();
+	e.pop(ebx).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+	assert_eq!((frame.size() + 4) % 16, 0);
+}
+
+#[test]
+fn invalid_frames() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	assert!(a.prologue(&FrameOptions::new(CallingConvention::Cdecl)).is_err());
+	assert!(a.prologue(&frame_options(CallingConvention::SystemV64, &[Register::RSI], 0)).is_err());
+	assert!(a.prologue(&frame_options(CallingConvention::SystemV64, &[Register::EBX], 0)).is_err());
+	assert!(a.prologue(&frame_options(CallingConvention::SystemV64, &[Register::RBX, Register::RBX], 0)).is_err());
+	assert!(a.prologue(&frame_options(CallingConvention::Microsoft64, &[Register::XMM5], 0)).is_err());
+	let mut options = frame_options(CallingConvention::SystemV64, &[Register::RBP], 0);
+	options.set_frame_pointer(true);
+	assert!(a.prologue(&options).is_err());
+	assert!(a.instructions().is_empty());
+
+	let mut a = CodeAssembler::new(32).unwrap();
+	assert!(a.prologue(&FrameOptions::new(CallingConvention::SystemV64)).is_err());
+	let mut options = FrameOptions::new(CallingConvention::Cdecl);
+	options.set_argument_stack_size(4);
+	assert!(a.prologue(&options).is_err());
+}
+
+#[test]
+fn system_v64_call() {
+	let mut options = FrameOptions::new(CallingConvention::SystemV64);
+	options.set_outgoing_stack_size(0x10);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	let args = [
+		CallArgument::Register(Register::RSI),
+		CallArgument::Float64(Register::XMM1),
+		CallArgument::Register(Register::RDX),
+		CallArgument::Register(Register::EDI),
+		CallArgument::Float32(Register::XMM0),
+		CallArgument::Immediate(-1),
+		CallArgument::Immediate(0x1234_5678_9ABC),
+		CallArgument::Immediate(0xFFFF_FFFF),
+		CallArgument::Register(Register::RBX),
+		CallArgument::Immediate(-2),
+	];
+	a.call_with_args(&frame, CallingConvention::SystemV64, qword_ptr(rbx), &args).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(64).unwrap();
+	e.sub(rsp, 0x18).unwrap();
+	e.mov(qword_ptr(rsp), rbx).unwrap();
+	e.add_instruction(Instruction::with2(Code::Mov_rm64_imm32, crate::MemoryOperand::with_base_displ(Register::RSP, 8), -2).unwrap()).unwrap();
+	// rdi <- rsi, rsi <- rdx, rdx <- edi
+	e.xchg(rdi, rsi).unwrap();
+	// xmm0 <- xmm1, xmm1 <- xmm0
+	e.movaps(xmm15, xmm0).unwrap();
+	e.movaps(xmm0, xmm1).unwrap();
+	e.movaps(xmm1, xmm15).unwrap();
+	e.xchg(rsi, rdx).unwrap();
+	e.add_instruction(Instruction::with2(Code::Mov_rm64_imm32, Register::RCX, -1).unwrap()).unwrap();
+	e.add_instruction(Instruction::with2(Code::Mov_r64_imm64, Register::R8, 0x1234_5678_9ABCu64).unwrap()).unwrap();
+	e.mov(r9d, 0xFFFF_FFFFu32).unwrap();
+	e.call(qword_ptr(rbx)).unwrap();
+	e.add(rsp, 0x18).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+}
+
+#[test]
+fn microsoft64_call() {
+	let mut options = FrameOptions::new(CallingConvention::Microsoft64);
+	options.set_outgoing_stack_size(0x10);
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&options).unwrap();
+	let args = [
+		CallArgument::Register(Register::RDX),
+		CallArgument::Register(Register::RCX),
+		CallArgument::Float64(Register::XMM0),
+		CallArgument::Immediate(-1),
+		CallArgument::Register(Register::RAX),
+		CallArgument::Float32(Register::XMM1),
+	];
+	a.call_with_args(&frame, CallingConvention::Microsoft64, r11, &args).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(64).unwrap();
+	e.sub(rsp, 0x38).unwrap();
+	e.mov(qword_ptr(rsp + 0x20), rax).unwrap();
+	e.movss(dword_ptr(rsp + 0x28), xmm1).unwrap();
+	e.movaps(xmm2, xmm0).unwrap();
+	e.xchg(rcx, rdx).unwrap();
+	e.add_instruction(Instruction::with2(Code::Mov_rm64_imm32, Register::R9, -1).unwrap()).unwrap();
+	e.call(r11).unwrap();
+	e.add(rsp, 0x38).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	let frame = a.prologue(&FrameOptions::new(CallingConvention::Microsoft64)).unwrap();
+	assert!(a.call_with_args(&frame, CallingConvention::Microsoft64, r11, &args[..4]).is_ok());
+	assert!(a.call_with_args(&frame, CallingConvention::Microsoft64, r11, &args).is_err());
+	assert!(a.call_with_args(&frame, CallingConvention::Microsoft64, r11, &[CallArgument::Float32(Register::RAX)]).is_err());
+	assert!(a.call_with_args(&frame, CallingConvention::Microsoft64, r11, &[CallArgument::Register(Register::AX)]).is_err());
+	assert!(a.call_with_args(&frame, CallingConvention::Cdecl, r11, &[]).is_err());
+}
+
+#[test]
+fn cdecl_call() {
+	let mut a = CodeAssembler::new(32).unwrap();
+	let frame = a.prologue(&FrameOptions::new(CallingConvention::Cdecl)).unwrap();
+	let args =
+		[CallArgument::Register(Register::EAX), CallArgument::Immediate(0x1000), CallArgument::Float64(Register::XMM0), CallArgument::Immediate(-1)];
+	a.call_with_args(&frame, CallingConvention::Cdecl, ebx, &args).unwrap();
+	a.epilogue(&frame).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(32).unwrap();
+	e.sub(esp, 0xC).unwrap();
+	e.sub(esp, 0xC).unwrap();
+	e.add_instruction(Instruction::with1(Code::Pushd_imm8, -1).unwrap()).unwrap();
+	e.sub(esp, 8).unwrap();
+	e.movsd_2(qword_ptr(esp), xmm0).unwrap();
+	e.add_instruction(Instruction::with1(Code::Pushd_imm32, 0x1000).unwrap()).unwrap();
+	e.push(eax).unwrap();
+	e.call(ebx).unwrap();
+	e.add(esp, 0x20).unwrap();
+	e.add(esp, 0xC).unwrap();
+	e.ret().unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+}
+
+#[test]
+fn fastcall_call() {
+	let mut a = CodeAssembler::new(32).unwrap();
+	let frame = a.prologue(&FrameOptions::new(CallingConvention::Fastcall)).unwrap();
+	let args = [CallArgument::Register(Register::EAX), CallArgument::Immediate(3), CallArgument::Register(Register::ECX)];
+	a.call_with_args(&frame, CallingConvention::Stdcall, ebx, &args[..1]).unwrap();
+	a.call_with_args(&frame, CallingConvention::Fastcall, ebx, &args).unwrap();
+	let bytes = assemble(&mut a);
+
+	let mut e = CodeAssembler::new(32).unwrap();
+	e.sub(esp, 0xC).unwrap();
+	e.sub(esp, 0xC).unwrap();
+	e.push(eax).unwrap();
+	e.call(ebx).unwrap();
+	e.add(esp, 0xC).unwrap();
+	e.sub(esp, 0xC).unwrap();
+	e.push(ecx).unwrap();
+	e.mov(ecx, eax).unwrap();
+	e.mov(edx, 3u32).unwrap();
+	e.call(ebx).unwrap();
+	e.add(esp, 0xC).unwrap();
+	assert_eq!(bytes, assemble(&mut e));
+}
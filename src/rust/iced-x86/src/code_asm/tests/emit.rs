@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::*;
+use crate::{Code, Mnemonic, Register, RoundingControl};
+use alloc::vec::Vec;
+
+fn test_emit<F, G>(bitness: u32, typed: F, dynamic: G)
+where
+	F: FnOnce(&mut CodeAssembler) -> Result<(), IcedError>,
+	G: FnOnce(&mut CodeAssembler) -> Result<(), IcedError>,
+{
+	let mut expected = CodeAssembler::new(bitness).unwrap();
+	typed(&mut expected).unwrap();
+	let mut actual = CodeAssembler::new(bitness).unwrap();
+	dynamic(&mut actual).unwrap();
+	assert_eq!(actual.instructions(), expected.instructions());
+	assert_eq!(actual.assemble(0x1234_5678).unwrap(), expected.assemble(0x1234_5678).unwrap());
+}
+
+#[test]
+fn emit_same_as_typed_methods_64() {
+	test_emit(
+		64,
+		|a| {
+			a.nop()?;
+			a.mov(rax, rcx)?;
+			a.mov(eax, 0x1234)?;
+			a.mov(rax, 0x1234_5678_9ABC_DEF0u64)?;
+			a.add(al, 1)?;
+			a.add(ecx, 0x7F)?;
+			a.add(eax, 0x1234)?;
+			a.add(dword_ptr(rax + rcx * 4 - 0x10), 0x80)?;
+			a.add(rcx, qword_ptr(rsp + 8))?;
+			a.shl(ecx, 1)?;
+			a.shl(ecx, 5)?;
+			a.shl(ecx, cl)?;
+			a.push(rbp)?;
+			a.push(1)?;
+			a.push(0x1234)?;
+			a.pop(rbp)?;
+			a.lea(rax, ptr(rsp + 0x10))?;
+			a.movzx(eax, byte_ptr(rdx))?;
+			a.call(rax)?;
+			a.jmp(qword_ptr(rax))?;
+			a.imul_3(eax, ecx, 0x10)?;
+			a.in_(al, dx)?;
+			a.fld(st0)?;
+			a.fadd_2(st0, st3)?;
+			a.movaps(xmm1, xmmword_ptr(rax))?;
+			a.vaddps(ymm0, ymm1, ymmword_ptr(rcx))?;
+			a.vaddps(zmm0.k1().z(), zmm1, zmm2.rn_sae())?;
+			a.vaddps(zmm0, zmm1, dword_bcst(rax))?;
+			a.vaddps(xmm0, xmm1, xmm2)?;
+			a.vpgatherdd(xmm0.k1(), ptr(rax + xmm1 * 4))?;
+			a.kmovw(k1, eax)?;
+			a.movsb()?;
+			a.stosd()?;
+			a.lodsq()?;
+			a.xlatb()?;
+			a.ret()?;
+			a.ret_1(8)?;
+			a.int3()?;
+			Ok(())
+		},
+		|a| {
+			a.emit(Mnemonic::Nop, &[])?;
+			a.emit(Mnemonic::Mov, &[rax.into(), rcx.into()])?;
+			a.emit(Mnemonic::Mov, &[eax.into(), 0x1234.into()])?;
+			a.emit(Mnemonic::Mov, &[rax.into(), 0x1234_5678_9ABC_DEF0u64.into()])?;
+			a.emit(Mnemonic::Add, &[al.into(), 1.into()])?;
+			a.emit(Mnemonic::Add, &[ecx.into(), 0x7F.into()])?;
+			a.emit(Mnemonic::Add, &[eax.into(), 0x1234.into()])?;
+			a.emit(Mnemonic::Add, &[dword_ptr(rax + rcx * 4 - 0x10).into(), 0x80.into()])?;
+			a.emit(Mnemonic::Add, &[Register::RCX.into(), qword_ptr(rsp + 8).into()])?;
+			a.emit(Mnemonic::Shl, &[ecx.into(), 1.into()])?;
+			a.emit(Mnemonic::Shl, &[ecx.into(), 5.into()])?;
+			a.emit(Mnemonic::Shl, &[ecx.into(), cl.into()])?;
+			a.emit(Mnemonic::Push, &[rbp.into()])?;
+			a.emit(Mnemonic::Push, &[1.into()])?;
+			a.emit(Mnemonic::Push, &[0x1234.into()])?;
+			a.emit(Mnemonic::Pop, &[rbp.into()])?;
+			a.emit(Mnemonic::Lea, &[rax.into(), ptr(rsp + 0x10).into()])?;
+			a.emit(Mnemonic::Movzx, &[eax.into(), byte_ptr(rdx).into()])?;
+			a.emit(Mnemonic::Call, &[rax.into()])?;
+			a.emit(Mnemonic::Jmp, &[qword_ptr(rax).into()])?;
+			a.emit(Mnemonic::Imul, &[eax.into(), ecx.into(), 0x10.into()])?;
+			a.emit(Mnemonic::In, &[al.into(), dx.into()])?;
+			a.emit(Mnemonic::Fld, &[st0.into()])?;
+			a.emit(Mnemonic::Fadd, &[st0.into(), st3.into()])?;
+			a.emit(Mnemonic::Movaps, &[xmm1.into(), xmmword_ptr(rax).into()])?;
+			a.emit(Mnemonic::Vaddps, &[ymm0.into(), ymm1.into(), ymmword_ptr(rcx).into()])?;
+			let zmm0_k1z = AsmOperand::from(Register::ZMM0).with_op_mask(Register::K1)?.with_zeroing_masking()?;
+			let zmm2_rn_sae = AsmOperand::from(Register::ZMM2).with_rounding_control(RoundingControl::RoundToNearest)?;
+			a.emit(Mnemonic::Vaddps, &[zmm0_k1z, zmm1.into(), zmm2_rn_sae])?;
+			let bcst = AsmOperand::memory(Register::RAX, Register::None, 1, 0)?.with_broadcast(4)?;
+			a.emit(Mnemonic::Vaddps, &[zmm0.into(), zmm1.into(), bcst])?;
+			a.emit(Mnemonic::Vaddps, &[xmm0.into(), xmm1.into(), xmm2.into()])?;
+			a.emit(Mnemonic::Vpgatherdd, &[xmm0.k1().into(), AsmOperand::memory(Register::RAX, Register::XMM1, 4, 0)?])?;
+			a.emit(Mnemonic::Kmovw, &[k1.into(), eax.into()])?;
+			a.emit(Mnemonic::Movsb, &[])?;
+			a.emit(Mnemonic::Stosd, &[])?;
+			a.emit(Mnemonic::Lodsq, &[])?;
+			a.emit(Mnemonic::Xlatb, &[])?;
+			a.emit(Mnemonic::Ret, &[])?;
+			a.emit(Mnemonic::Ret, &[8.into()])?;
+			a.emit(Mnemonic::Int3, &[])?;
+			Ok(())
+		},
+	);
+}
+
+#[test]
+fn emit_same_as_typed_methods_32() {
+	test_emit(
+		32,
+		|a| {
+			a.nop()?;
+			a.push(0x1234)?;
+			a.push(ax)?;
+			a.inc(ecx)?;
+			a.mov(al, byte_ptr(0x1234_5678))?;
+			a.jmp(dword_ptr(eax))?;
+			a.pushfd()?;
+			a.movsw()?;
+			Ok(())
+		},
+		|a| {
+			a.emit(Mnemonic::Nop, &[])?;
+			a.emit(Mnemonic::Push, &[0x1234.into()])?;
+			a.emit(Mnemonic::Push, &[ax.into()])?;
+			a.emit(Mnemonic::Inc, &[ecx.into()])?;
+			a.emit(Mnemonic::Mov, &[al.into(), byte_ptr(0x1234_5678).into()])?;
+			a.emit(Mnemonic::Jmp, &[dword_ptr(eax).into()])?;
+			a.emit(Mnemonic::Pushfd, &[])?;
+			a.emit(Mnemonic::Movsw, &[])?;
+			Ok(())
+		},
+	);
+}
+
+#[test]
+fn emit_same_as_typed_methods_16() {
+	test_emit(
+		16,
+		|a| {
+			a.nop()?;
+			a.push(0x1234)?;
+			a.inc(cx)?;
+			a.mov(ax, word_ptr(bx + si + 2))?;
+			a.stosb()?;
+			Ok(())
+		},
+		|a| {
+			a.emit(Mnemonic::Nop, &[])?;
+			a.emit(Mnemonic::Push, &[0x1234.into()])?;
+			a.emit(Mnemonic::Inc, &[cx.into()])?;
+			a.emit(Mnemonic::Mov, &[ax.into(), word_ptr(bx + si + 2).into()])?;
+			a.emit(Mnemonic::Stosb, &[])?;
+			Ok(())
+		},
+	);
+}
+
+#[test]
+fn emit_branches() {
+	for &prefer_short_branch in &[true, false] {
+		test_emit(
+			64,
+			|a| {
+				a.set_prefer_short_branch(prefer_short_branch);
+				let mut label1 = a.create_label();
+				let mut label2 = a.create_label();
+				a.set_label(&mut label1)?;
+				a.nop()?;
+				a.je(label1)?;
+				a.jmp(label2)?;
+				a.call(label2)?;
+				a.loop_(label1)?;
+				a.xbegin(label1)?;
+				a.jmp(0x1234_5678u64)?;
+				a.set_label(&mut label2)?;
+				a.lea(rax, ptr(label1))?;
+				Ok(())
+			},
+			|a| {
+				a.set_prefer_short_branch(prefer_short_branch);
+				let mut label1 = a.create_label();
+				let mut label2 = a.create_label();
+				a.set_label(&mut label1)?;
+				a.emit(Mnemonic::Nop, &[])?;
+				a.emit(Mnemonic::Je, &[label1.into()])?;
+				a.emit(Mnemonic::Jmp, &[label2.into()])?;
+				a.emit(Mnemonic::Call, &[label2.into()])?;
+				a.emit(Mnemonic::Loop, &[label1.into()])?;
+				a.emit(Mnemonic::Xbegin, &[label1.into()])?;
+				a.emit(Mnemonic::Jmp, &[0x1234_5678u64.into()])?;
+				a.set_label(&mut label2)?;
+				a.emit(Mnemonic::Lea, &[rax.into(), ptr(label1).into()])?;
+				Ok(())
+			},
+		);
+	}
+}
+
+#[test]
+fn emit_prefixes() {
+	test_emit(
+		64,
+		|a| {
+			a.rep().stosb()?;
+			a.lock().add(dword_ptr(rax), ecx)?;
+			a.evex().vaddps(xmm0, xmm1, xmm2)?;
+			a.set_prefer_vex(false);
+			a.vaddps(xmm0, xmm1, xmm2)?;
+			a.vex().vaddps(xmm0, xmm1, xmm2)?;
+			Ok(())
+		},
+		|a| {
+			a.rep().emit(Mnemonic::Stosb, &[])?;
+			a.lock().emit(Mnemonic::Add, &[dword_ptr(rax).into(), ecx.into()])?;
+			a.evex().emit(Mnemonic::Vaddps, &[xmm0.into(), xmm1.into(), xmm2.into()])?;
+			a.set_prefer_vex(false);
+			a.emit(Mnemonic::Vaddps, &[xmm0.into(), xmm1.into(), xmm2.into()])?;
+			a.vex().emit(Mnemonic::Vaddps, &[xmm0.into(), xmm1.into(), xmm2.into()])?;
+			Ok(())
+		},
+	);
+}
+
+#[test]
+fn emit_uses_shortest_encoding() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.emit(Mnemonic::Mov, &[rax.into(), (-1).into()]).unwrap();
+	a.emit(Mnemonic::Mov, &[rax.into(), 0xFFFF_FFFFu32.into()]).unwrap();
+	a.emit(Mnemonic::Add, &[eax.into(), 1.into()]).unwrap();
+	a.emit(Mnemonic::Add, &[eax.into(), 0x1234.into()]).unwrap();
+	a.emit(Mnemonic::Vaddps, &[xmm0.k2().into(), xmm1.into(), xmm2.into()]).unwrap();
+	a.emit(Mnemonic::Vaddps, &[xmm20.into(), xmm1.into(), xmm2.into()]).unwrap();
+	let codes: Vec<Code> = a.instructions().iter().map(Instruction::code).collect();
+	assert_eq!(
+		codes,
+		vec![
+			Code::Mov_rm64_imm32,
+			Code::Mov_r64_imm64,
+			Code::Add_rm32_imm8,
+			Code::Add_EAX_imm32,
+			Code::EVEX_Vaddps_xmm_k1z_xmm_xmmm128b32,
+			Code::EVEX_Vaddps_xmm_k1z_xmm_xmmm128b32
+		]
+	);
+}
+
+#[test]
+fn emit_errors() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	// Wrong number of operands
+	assert!(a.emit(Mnemonic::Int3, &[eax.into()]).is_err());
+	assert!(a.emit(Mnemonic::Mov, &[eax.into()]).is_err());
+	// Operand sizes don't match
+	assert!(a.emit(Mnemonic::Mov, &[eax.into(), cx.into()]).is_err());
+	// Invalid immediate
+	assert!(a.emit(Mnemonic::Add, &[al.into(), 0x1234.into()]).is_err());
+	assert!(a.emit(Mnemonic::Int, &[0x100.into()]).is_err());
+	// Ambiguous memory operand size
+	assert!(a.emit(Mnemonic::Inc, &[ptr(rax).into()]).is_err());
+	assert!(a.emit(Mnemonic::Movzx, &[eax.into(), ptr(rax).into()]).is_err());
+	// Not available in 64-bit mode
+	assert!(a.emit(Mnemonic::Pusha, &[]).is_err());
+	// {z} requires EVEX and an op mask can't be used with this instruction
+	assert!(a.emit(Mnemonic::Add, &[ecx.k1().into(), eax.into()]).is_err());
+	// Label must be created by the assembler
+	assert!(a.emit(Mnemonic::Jmp, &[CodeLabel::default().into()]).is_err());
+	assert!(a.instructions().is_empty());
+}
+
+#[test]
+fn asm_operand_errors() {
+	assert!(AsmOperand::memory(Register::RAX, Register::RCX, 3, 0).is_err());
+	assert!(AsmOperand::from(Register::RAX).with_memory_size(8).is_err());
+	assert!(AsmOperand::from(ptr(rax)).with_memory_size(3).is_err());
+	assert!(AsmOperand::from(ptr(rax)).with_broadcast(16).is_err());
+	assert!(AsmOperand::from(1).with_broadcast(4).is_err());
+	assert!(AsmOperand::from(ptr(rax)).with_segment(Register::RAX).is_err());
+	assert!(AsmOperand::from(xmm0).with_segment(Register::FS).is_err());
+	assert!(AsmOperand::from(xmm0).with_op_mask(Register::K0).is_err());
+	assert!(AsmOperand::from(1).with_op_mask(Register::K1).is_err());
+	assert!(AsmOperand::from(ptr(rax)).with_zeroing_masking().is_err());
+	assert!(AsmOperand::from(ptr(rax)).with_suppress_all_exceptions().is_err());
+	assert!(AsmOperand::from(ptr(rax)).with_rounding_control(RoundingControl::RoundUp).is_err());
+	assert_eq!(
+		AsmOperand::memory(Register::RAX, Register::RCX, 8, -0x10).unwrap().with_memory_size(4).unwrap().with_segment(Register::FS).unwrap(),
+		AsmOperand::from(dword_ptr(rax + rcx * 8 - 0x10).fs())
+	);
+}
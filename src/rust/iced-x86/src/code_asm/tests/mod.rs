@@ -1,12 +1,20 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2018-present iced project and contributors
 
+#[cfg(feature = "abi")]
+mod abi;
+#[cfg(feature = "op_code_info")]
+mod emit;
 mod instr16;
 mod instr32;
 mod instr64;
+#[cfg(feature = "object_writer")]
+mod object;
+#[cfg(feature = "reg_alloc")]
+mod reg_alloc;
 
 use crate::code_asm::*;
-use crate::{BlockEncoderOptions, Code, Decoder, DecoderOptions, MemoryOperand, Register};
+use crate::{BlockEncoderOptions, BlockEncoderResult, Code, Decoder, DecoderOptions, Instruction, MemoryOperand, Register, RelocInfo, RelocKind};
 use core::convert::TryInto;
 
 #[test]
@@ -111,11 +119,11 @@ fn prefixes_without_instr_fails_assemble() {
 #[test]
 fn test_instrs_method() {
 	let mut a = CodeAssembler::new(64).unwrap();
-	assert_eq!(a.instructions, &[]);
+	assert_eq!(a.instructions(), &[]);
 	a.rdtsc().unwrap();
-	assert_eq!(a.instructions, &[Instruction::with(Code::Rdtsc)]);
+	assert_eq!(a.instructions(), &[Instruction::with(Code::Rdtsc)]);
 	a.xor(ecx, 0x1234_5678).unwrap();
-	assert_eq!(a.instructions, &[Instruction::with(Code::Rdtsc), Instruction::with2(Code::Xor_rm32_imm32, Register::ECX, 0x1234_5678).unwrap()]);
+	assert_eq!(a.instructions(), &[Instruction::with(Code::Rdtsc), Instruction::with2(Code::Xor_rm32_imm32, Register::ECX, 0x1234_5678).unwrap()]);
 }
 
 #[test]
@@ -525,6 +533,287 @@ fn nops_errors() {
 	assert!(a.notrack().nops_with_size(2000).is_err());
 }
 
+#[test]
+fn test_align() {
+	for &bitness in &[16, 32, 64] {
+		for &alignment in &[1, 2, 4, 8, 16, 32, 64] {
+			for ip in 0x1000..0x1000 + alignment as u64 + 1 {
+				let mut a = CodeAssembler::new(bitness).unwrap();
+				let mut label = a.create_label();
+				a.int3().unwrap();
+				a.align(alignment).unwrap();
+				a.set_label(&mut label).unwrap();
+				a.int3().unwrap();
+				let result = a.assemble_options(ip, BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS).unwrap();
+				assert_eq!(result.label_ip(&label).unwrap() % alignment as u64, 0);
+				let bytes = &result.inner.code_buffer;
+				assert_eq!(bytes.len() as u64, result.label_ip(&label).unwrap() - ip + 1);
+				for instr in &mut Decoder::new(bitness, &bytes[1..bytes.len() - 1], DecoderOptions::NONE) {
+					match instr.code() {
+						Code::Nopw | Code::Nopd | Code::Nopq | Code::Nop_rm16 | Code::Nop_rm32 | Code::Nop_rm64 => {}
+						_ => panic!("Unexpected NOP instruction: {:?}", instr.code()),
+					}
+				}
+			}
+		}
+	}
+}
+
+#[test]
+fn test_align_after_branch_optimization() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut loop_lbl = a.create_label();
+	a.set_prefer_short_branch(false);
+	a.jmp(loop_lbl).unwrap();
+	a.align(16).unwrap();
+	a.set_label(&mut loop_lbl).unwrap();
+	a.dec(ecx).unwrap();
+	a.jne(loop_lbl).unwrap();
+	let bytes = a.assemble(0x1000).unwrap();
+	assert_eq!(bytes.len(), 0x10 + 2 + 2);
+	assert_eq!(&bytes[0..2], b"\xEB\x0E");
+	assert_eq!(&bytes[0x10..], b"\xFF\xC9\x75\xFC");
+}
+
+#[test]
+fn test_align_errors() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	assert!(a.align(0).is_err());
+	assert!(a.align(3).is_err());
+	assert!(a.align(24).is_err());
+	assert!(a.lock().align(16).is_err());
+}
+
+#[test]
+fn test_label_addresses() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut table = a.create_label();
+	let mut case0 = a.create_label();
+	let mut case1 = a.create_label();
+	a.lea(rax, ptr(table)).unwrap();
+	a.jmp(qword_ptr(rax + rcx * 8)).unwrap();
+	a.align(8).unwrap();
+	a.set_label(&mut table).unwrap();
+	a.dq_label(&[case0, case1]).unwrap();
+	a.dd_label(&[case0]).unwrap();
+	a.dd_label_rel(&[case0, case1], table).unwrap();
+	a.dq_label_rel(&[case1], table).unwrap();
+	a.set_label(&mut case0).unwrap();
+	a.xor(eax, eax).unwrap();
+	a.set_label(&mut case1).unwrap();
+	a.ret().unwrap();
+	let result =
+		a.assemble_options(0x1234_5678, BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS | BlockEncoderOptions::RETURN_RELOC_INFOS).unwrap();
+	let table_ip = result.label_ip(&table).unwrap();
+	let case0_ip = result.label_ip(&case0).unwrap();
+	let case1_ip = result.label_ip(&case1).unwrap();
+	assert_eq!(table_ip % 8, 0);
+	assert_eq!(case0_ip, table_ip + 8 * 2 + 4 + 4 * 2 + 8);
+	assert_eq!(case1_ip, case0_ip + 2);
+
+	let table_bytes = &result.inner.code_buffer[(table_ip - result.inner.rip) as usize..(case0_ip - result.inner.rip) as usize];
+	assert_eq!(u64::from_le_bytes(table_bytes[0..8].try_into().unwrap()), case0_ip);
+	assert_eq!(u64::from_le_bytes(table_bytes[8..16].try_into().unwrap()), case1_ip);
+	assert_eq!(u32::from_le_bytes(table_bytes[16..20].try_into().unwrap()) as u64, case0_ip);
+	assert_eq!(u32::from_le_bytes(table_bytes[20..24].try_into().unwrap()) as u64, case0_ip - table_ip);
+	assert_eq!(u32::from_le_bytes(table_bytes[24..28].try_into().unwrap()) as u64, case1_ip - table_ip);
+	assert_eq!(u64::from_le_bytes(table_bytes[28..36].try_into().unwrap()), case1_ip - table_ip);
+	assert_eq!(result.inner.reloc_infos, [RelocInfo::new(RelocKind::Offset64, table_ip), RelocInfo::new(RelocKind::Offset64, table_ip + 8)]);
+}
+
+#[test]
+fn test_label_address_errors() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let label = a.create_label();
+	assert!(a.dq_label(&[CodeLabel::default()]).is_err());
+	assert!(a.dd_label(&[label, CodeLabel::default()]).is_err());
+	assert!(a.dd_label_rel(&[label], CodeLabel::default()).is_err());
+	assert!(a.dq_label_rel(&[CodeLabel::default()], label).is_err());
+	assert!(a.rep().dq_label(&[label]).is_err());
+	assert_eq!(a.instructions().len(), 0);
+
+	// Labels that don't fit in 32 bits
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut label = a.create_label();
+	a.set_label(&mut label).unwrap();
+	a.dd_label(&[label]).unwrap();
+	assert!(a.assemble(0x1_0000_0000).is_err());
+}
+
+#[test]
+fn test_reset_clears_directives() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.align(16).unwrap();
+	a.reset();
+	a.nop().unwrap();
+	assert_eq!(a.assemble(0x1001).unwrap(), b"\x90");
+}
+
+#[test]
+fn test_sections() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let rodata = a.create_section(".rodata", SectionKind::ReadOnlyData, 16).unwrap();
+	let code2 = a.create_section(".text2", SectionKind::Code, 32).unwrap();
+	let data = a.create_section(".data", SectionKind::Data, 8).unwrap();
+	a.set_section_ip(data, 0x8000).unwrap();
+	let mut func = a.create_label();
+	let mut constant = a.create_label();
+	let mut variable = a.create_label();
+	let mut table = a.create_label();
+
+	assert_eq!(a.section(), a.text_section());
+	a.movaps(xmm0, xmmword_ptr(constant)).unwrap();
+	a.call(func).unwrap();
+	a.mov(dword_ptr(variable), eax).unwrap();
+	a.ret().unwrap();
+
+	a.set_section(code2).unwrap();
+	assert_eq!(a.section(), code2);
+	a.set_label(&mut func).unwrap();
+	a.mov(eax, dword_ptr(variable)).unwrap();
+	a.lea(rcx, ptr(table)).unwrap();
+	a.ret().unwrap();
+
+	a.set_section(rodata).unwrap();
+	a.set_label(&mut constant).unwrap();
+	a.dd(&[1, 2, 3, 4]).unwrap();
+	a.set_label(&mut table).unwrap();
+	a.dq_label(&[func]).unwrap();
+
+	a.set_section(data).unwrap();
+	a.set_label(&mut variable).unwrap();
+	a.dd(&[0x1234_5678]).unwrap();
+	assert_eq!(a.instructions().len(), 1);
+
+	let result = a.assemble_options(0x1001, BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS).unwrap();
+	assert_eq!(result.inner.rip, 0x1001);
+	assert_eq!(result.inner.code_buffer.len(), 7 + 5 + 6 + 1);
+	assert_eq!(result.sections.len(), 3);
+	assert_eq!(result.sections[0].name, ".rodata");
+	assert_eq!(result.sections[0].kind, SectionKind::ReadOnlyData);
+	assert_eq!(result.sections[0].alignment, 16);
+	assert_eq!(result.sections[0].inner.rip, 0x1020);
+	assert_eq!(result.sections[1].name, ".text2");
+	assert_eq!(result.sections[1].kind, SectionKind::Code);
+	assert_eq!(result.sections[1].inner.rip, 0x1040);
+	assert_eq!(result.sections[2].name, ".data");
+	assert_eq!(result.sections[2].kind, SectionKind::Data);
+	assert_eq!(result.sections[2].inner.rip, 0x8000);
+	assert_eq!(result.sections[2].inner.code_buffer, b"\x78\x56\x34\x12");
+	assert_eq!(result.section(rodata).unwrap().rip, 0x1020);
+	assert_eq!(result.section(a.text_section()).unwrap().rip, 0x1001);
+
+	assert_eq!(result.label_ip(&constant).unwrap(), 0x1020);
+	assert_eq!(result.label_ip(&table).unwrap(), 0x1030);
+	assert_eq!(result.label_ip(&func).unwrap(), 0x1040);
+	assert_eq!(result.label_ip(&variable).unwrap(), 0x8000);
+
+	let decode = |result: &BlockEncoderResult| -> Vec<Instruction> {
+		Decoder::with_ip(64, &result.code_buffer, result.rip, DecoderOptions::NONE).into_iter().collect()
+	};
+	let text = decode(&result.inner);
+	assert_eq!(text[0].memory_displacement64(), 0x1020);
+	assert_eq!(text[1].near_branch_target(), 0x1040);
+	assert_eq!(text[2].memory_displacement64(), 0x8000);
+	let text2 = decode(&result.sections[1].inner);
+	assert_eq!(text2[0].memory_displacement64(), 0x8000);
+	assert_eq!(text2[1].memory_displacement64(), 0x1030);
+	assert_eq!(&result.sections[0].inner.code_buffer[16..], &0x1040u64.to_le_bytes());
+}
+
+#[test]
+fn test_sections_are_placed_after_previous_section() {
+	for &bitness in &[16, 32, 64] {
+		let mut a = CodeAssembler::new(bitness).unwrap();
+		let code2 = a.create_section(".text2", SectionKind::Code, 4).unwrap();
+		let mut label = a.create_label();
+		// A long section so the first estimate of the address of `.text2` is wrong
+		a.set_prefer_short_branch(false);
+		for _ in 0..100 {
+			a.jmp(label).unwrap();
+		}
+		a.set_section(code2).unwrap();
+		a.set_label(&mut label).unwrap();
+		a.jmp(label).unwrap();
+		let result = a.assemble_options(0x1000, BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS).unwrap();
+		let end = result.inner.rip + result.inner.code_buffer.len() as u64;
+		assert_eq!(result.sections[0].inner.rip, (end + 3) & !3);
+		assert_eq!(result.label_ip(&label).unwrap(), result.sections[0].inner.rip);
+		for instr in Decoder::with_ip(bitness, &result.inner.code_buffer, result.inner.rip, DecoderOptions::NONE) {
+			assert_eq!(instr.near_branch_target(), result.sections[0].inner.rip);
+		}
+	}
+}
+
+#[test]
+fn test_section_errors() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	assert!(a.create_section("", SectionKind::Data, 1).is_err());
+	assert!(a.create_section(".text", SectionKind::Code, 1).is_err());
+	assert!(a.create_section(".data", SectionKind::Data, 0).is_err());
+	assert!(a.create_section(".data", SectionKind::Data, 3).is_err());
+	let data = a.create_section(".data", SectionKind::Data, 1).unwrap();
+	assert!(a.create_section(".data", SectionKind::Data, 1).is_err());
+	let text = a.text_section();
+	assert!(a.set_section_ip(text, 0x1000).is_err());
+	assert!(a.set_section(CodeSection::new(2)).is_err());
+	assert!(a.set_section_ip(CodeSection::new(2), 0x1000).is_err());
+
+	let mut label = a.create_label();
+	a.set_label(&mut label).unwrap();
+	assert!(a.set_section(data).is_err());
+	a.nop().unwrap();
+	assert!(a.lock().set_section(data).is_err());
+	a.add(dword_ptr(rax), 1).unwrap();
+	a.anonymous_label().unwrap();
+	assert!(a.set_section(data).is_err());
+	a.nop().unwrap();
+	a.set_section(data).unwrap();
+	a.db(b"\xCC").unwrap();
+
+	// There's more than one section
+	assert!(a.assemble(0x1000).is_err());
+
+	// Overlapping sections
+	a.set_section_ip(data, 0x1003).unwrap();
+	assert!(a.assemble_options(0x1000, BlockEncoderOptions::NONE).is_err());
+	a.set_section_ip(data, 0x1008).unwrap();
+	assert!(a.assemble_options(0x1000, BlockEncoderOptions::NONE).is_ok());
+}
+
+#[test]
+fn test_take_instrs_keeps_other_sections() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let data = a.create_section(".data", SectionKind::Data, 1).unwrap();
+	a.nop().unwrap();
+	a.set_section(data).unwrap();
+	a.db(b"\xCC").unwrap();
+	a.set_section(a.text_section()).unwrap();
+	assert_eq!(a.take_instructions(), &[Instruction::with(Code::Nopd)]);
+	assert!(a.instructions().is_empty());
+	a.set_section(data).unwrap();
+	assert_eq!(a.instructions().len(), 1);
+	assert_eq!(a.take_instructions().len(), 1);
+	// All sections are empty so it was reset
+	assert_eq!(a.section(), a.text_section());
+	assert!(a.create_section(".data", SectionKind::Data, 1).is_ok());
+}
+
+#[test]
+fn test_reset_clears_sections() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let data = a.create_section(".data", SectionKind::Data, 1).unwrap();
+	a.set_section(data).unwrap();
+	a.db(b"\xCC").unwrap();
+	a.reset();
+	assert_eq!(a.section(), a.text_section());
+	a.nop().unwrap();
+	let result = a.assemble_options(0x1000, BlockEncoderOptions::NONE).unwrap();
+	assert_eq!(result.inner.code_buffer, b"\x90");
+	assert!(result.sections.is_empty());
+	assert!(a.create_section(".data", SectionKind::Data, 1).is_ok());
+}
+
 #[test]
 fn invalid_instr_fails() {
 	let mut a = CodeAssembler::new(64).unwrap();
@@ -962,12 +1251,12 @@ fn create_asm(bitness: u32, flags: u32) -> CodeAssembler {
 fn test_instr(bitness: u32, create: fn(&mut CodeAssembler), mut expected: Instruction, flags: u32, decoder_options: u32) {
 	let mut a = create_asm(bitness, flags);
 	create(&mut a);
-	assert_eq!(a.instructions.len(), 1);
+	assert_eq!(a.instructions().len(), 1);
 
 	if (flags & TestInstrFlags::BROADCAST) != 0 {
 		expected.set_is_broadcast(true);
 	}
-	let mut asm_instr = a.instructions[0];
+	let mut asm_instr = a.instructions()[0];
 	assert_eq!(asm_instr, expected);
 
 	let rip = 0;
@@ -1040,7 +1329,7 @@ fn test_instr(bitness: u32, create: fn(&mut CodeAssembler), mut expected: Instru
 fn test_invalid_instr(bitness: u32, create: fn(&mut CodeAssembler), flags: u32) {
 	let mut a = create_asm(bitness, flags);
 	create(&mut a);
-	assert_eq!(a.instructions.len(), 0);
+	assert_eq!(a.instructions().len(), 0);
 }
 
 const FIRST_LABEL_ID: u64 = 1;
@@ -0,0 +1,530 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::object::*;
+use crate::code_asm::*;
+use crate::{Decoder, DecoderOptions};
+use core::convert::TryInto;
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+	u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+	u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+	u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn c_str(data: &[u8], offset: usize) -> String {
+	let end = data[offset..].iter().position(|&b| b == 0).unwrap();
+	String::from_utf8(data[offset..offset + end].to_vec()).unwrap()
+}
+
+#[derive(Debug)]
+struct ElfSection {
+	name: String,
+	kind: u32,
+	flags: u64,
+	data: Vec<u8>,
+	link: u32,
+	info: u32,
+	alignment: u64,
+}
+
+#[derive(Debug, PartialEq)]
+struct ElfSymbol {
+	name: String,
+	info: u8,
+	section: u16,
+	value: u64,
+}
+
+#[derive(Debug, PartialEq)]
+struct ElfReloc {
+	offset: u64,
+	symbol: String,
+	kind: u32,
+	addend: i64,
+}
+
+struct Elf {
+	sections: Vec<ElfSection>,
+	symbols: Vec<ElfSymbol>,
+}
+
+impl Elf {
+	fn parse(data: &[u8]) -> Self {
+		assert_eq!(&data[0..8], b"\x7FELF\x02\x01\x01\x00");
+		assert_eq!(u16_at(data, 0x10), 1);
+		assert_eq!(u16_at(data, 0x12), 62);
+		let shoff = u64_at(data, 0x28) as usize;
+		assert_eq!(u16_at(data, 0x3A), 64);
+		let shnum = u16_at(data, 0x3C) as usize;
+		let shstrndx = u16_at(data, 0x3E) as usize;
+		let header = |i: usize| shoff + i * 64;
+		let shstrtab_offset = u64_at(data, header(shstrndx) + 0x18) as usize;
+		let mut sections = Vec::new();
+		for i in 0..shnum {
+			let h = header(i);
+			let offset = u64_at(data, h + 0x18) as usize;
+			let size = u64_at(data, h + 0x20) as usize;
+			sections.push(ElfSection {
+				name: c_str(data, shstrtab_offset + u32_at(data, h) as usize),
+				kind: u32_at(data, h + 4),
+				flags: u64_at(data, h + 8),
+				data: if i == 0 { Vec::new() } else { data[offset..offset + size].to_vec() },
+				link: u32_at(data, h + 0x28),
+				info: u32_at(data, h + 0x2C),
+				alignment: u64_at(data, h + 0x30),
+			});
+		}
+		let symtab = sections.iter().find(|s| s.kind == 2).unwrap();
+		let strtab = &sections[symtab.link as usize].data;
+		let mut symbols = Vec::new();
+		for sym in symtab.data.chunks(24) {
+			symbols.push(ElfSymbol { name: c_str(strtab, u32_at(sym, 0) as usize), info: sym[4], section: u16_at(sym, 6), value: u64_at(sym, 8) });
+		}
+		for (i, symbol) in symbols.iter().enumerate() {
+			// Locals must come first
+			assert_eq!(symbol.info >> 4 == 0, i < symtab.info as usize);
+		}
+		Self { sections, symbols }
+	}
+
+	fn section(&self, name: &str) -> &ElfSection {
+		self.sections.iter().find(|s| s.name == name).unwrap()
+	}
+
+	fn relocs(&self, name: &str) -> Vec<ElfReloc> {
+		let mut rela_name = String::from(".rela");
+		rela_name.push_str(name);
+		let rela = match self.sections.iter().find(|s| s.name == rela_name) {
+			Some(rela) => rela,
+			None => return Vec::new(),
+		};
+		assert_eq!(rela.kind, 4);
+		assert_eq!(self.sections[rela.info as usize].name, name);
+		assert_eq!(self.sections[rela.link as usize].kind, 2);
+		rela.data
+			.chunks(24)
+			.map(|r| {
+				let info = u64_at(r, 8);
+				let symbol = &self.symbols[(info >> 32) as usize];
+				let symbol = if symbol.info & 0xF == 3 { self.sections[symbol.section as usize].name.clone() } else { symbol.name.clone() };
+				ElfReloc { offset: u64_at(r, 0), symbol, kind: info as u32, addend: u64_at(r, 16) as i64 }
+			})
+			.collect()
+	}
+}
+
+#[derive(Debug, PartialEq)]
+struct CoffReloc {
+	offset: u32,
+	symbol: String,
+	kind: u16,
+}
+
+struct CoffSection {
+	name: String,
+	characteristics: u32,
+	data: Vec<u8>,
+	relocs: Vec<CoffReloc>,
+}
+
+#[derive(Debug, PartialEq)]
+struct CoffSymbol {
+	name: String,
+	value: u32,
+	section: u16,
+	kind: u16,
+	class: u8,
+}
+
+struct Coff {
+	sections: Vec<CoffSection>,
+	symbols: Vec<CoffSymbol>,
+}
+
+impl Coff {
+	fn parse(data: &[u8]) -> Self {
+		assert_eq!(u16_at(data, 0), 0x8664);
+		let section_count = u16_at(data, 2) as usize;
+		let symtab_offset = u32_at(data, 8) as usize;
+		let symbol_count = u32_at(data, 12) as usize;
+		assert_eq!(u16_at(data, 16), 0);
+		let strtab_offset = symtab_offset + symbol_count * 18;
+		let name = |bytes: &[u8], is_section: bool| -> String {
+			if is_section && bytes[0] == b'/' {
+				let offset: usize = c_str(&[bytes, b"\0"].concat(), 1).parse().unwrap();
+				c_str(data, strtab_offset + offset)
+			} else if !is_section && u32_at(bytes, 0) == 0 {
+				c_str(data, strtab_offset + u32_at(bytes, 4) as usize)
+			} else {
+				c_str(&[bytes, b"\0"].concat(), 0)
+			}
+		};
+
+		let mut symbols = Vec::new();
+		let mut i = 0;
+		while i < symbol_count {
+			let sym = &data[symtab_offset + i * 18..symtab_offset + i * 18 + 18];
+			symbols.push(CoffSymbol {
+				name: name(&sym[0..8], false),
+				value: u32_at(sym, 8),
+				section: u16_at(sym, 12),
+				kind: u16_at(sym, 14),
+				class: sym[16],
+			});
+			// Aux symbols get an empty entry so the indexes match
+			for _ in 0..sym[17] {
+				symbols.push(CoffSymbol { name: String::new(), value: 0, section: 0, kind: 0, class: 0 });
+			}
+			i += 1 + sym[17] as usize;
+		}
+
+		let mut sections = Vec::new();
+		for i in 0..section_count {
+			let h = &data[20 + i * 40..20 + i * 40 + 40];
+			let size = u32_at(h, 16) as usize;
+			let offset = u32_at(h, 20) as usize;
+			let relocs_offset = u32_at(h, 24) as usize;
+			let reloc_count = u16_at(h, 32) as usize;
+			let relocs = (0..reloc_count)
+				.map(|j| {
+					let r = &data[relocs_offset + j * 10..relocs_offset + j * 10 + 10];
+					CoffReloc { offset: u32_at(r, 0), symbol: symbols[u32_at(r, 4) as usize].name.clone(), kind: u16_at(r, 8) }
+				})
+				.collect();
+			sections.push(CoffSection {
+				name: name(&h[0..8], true),
+				characteristics: u32_at(h, 36),
+				data: data[offset..offset + size].to_vec(),
+				relocs,
+			});
+		}
+		Self { sections, symbols }
+	}
+
+	fn section(&self, name: &str) -> &CoffSection {
+		self.sections.iter().find(|s| s.name == name).unwrap()
+	}
+}
+
+// Returns the assembler, the object writer and the offset of `bump` in `.text`
+fn create_program() -> (CodeAssembler, ObjectWriter, usize) {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut obj = ObjectWriter::new();
+	let rodata = a.create_section(".rodata", SectionKind::ReadOnlyData, 8).unwrap();
+	let data = a.create_section(".data", SectionKind::Data, 4).unwrap();
+	let puts = obj.add_extern(&mut a, "puts").unwrap();
+	let errno_value = obj.add_extern(&mut a, "a_very_long_extern_symbol").unwrap();
+	let mut hello = a.create_label();
+	let mut main = a.create_label();
+	let mut bump = a.create_label();
+	let mut counter = a.create_label();
+	let mut table = a.create_label();
+	let mut skip = a.create_label();
+
+	// int main(void)
+	a.set_label(&mut main).unwrap();
+	a.sub(rsp, 8).unwrap();
+	a.lea(rdi, ptr(hello)).unwrap();
+	a.call(puts).unwrap();
+	a.call(bump).unwrap();
+	a.mov(rax, qword_ptr(table)).unwrap();
+	a.call(rax).unwrap();
+	a.mov(eax, dword_ptr(counter)).unwrap();
+	a.sub(eax, 2).unwrap();
+	a.add(rsp, 8).unwrap();
+	a.ret().unwrap();
+
+	// Same section, no relocation
+	a.set_label(&mut bump).unwrap();
+	a.test(eax, eax).unwrap();
+	a.je(skip).unwrap();
+	a.add(dword_ptr(counter), 1).unwrap();
+	a.set_label(&mut skip).unwrap();
+	a.ret().unwrap();
+
+	a.set_section(rodata).unwrap();
+	a.set_label(&mut hello).unwrap();
+	a.db(b"Hello, world!\0").unwrap();
+	a.align(8).unwrap();
+	a.set_label(&mut table).unwrap();
+	a.dq_label(&[bump, errno_value]).unwrap();
+
+	a.set_section(data).unwrap();
+	a.set_label(&mut counter).unwrap();
+	a.dd(&[1]).unwrap();
+
+	obj.add_export("main", main).unwrap();
+	obj.add_export("counter", counter).unwrap();
+
+	(a, obj, 4 + 7 + 5 + 5 + 7 + 2 + 6 + 5 + 4 + 1)
+}
+
+#[test]
+fn write_elf() {
+	let (mut a, obj, bump_offset) = create_program();
+	let bytes = obj.write(&mut a, ObjectFormat::Elf).unwrap();
+	let elf = Elf::parse(&bytes);
+
+	let text = elf.section(".text");
+	assert_eq!(text.kind, 1);
+	assert_eq!(text.flags, 6);
+	assert_eq!(text.alignment, 16);
+	let rodata = elf.section(".rodata");
+	assert_eq!(rodata.flags, 2);
+	assert_eq!(rodata.alignment, 8);
+	assert_eq!(&rodata.data[0..14], b"Hello, world!\0");
+	assert_eq!(rodata.data.len(), 16 + 16);
+	let data = elf.section(".data");
+	assert_eq!(data.flags, 3);
+	assert_eq!(data.data, b"\x01\x00\x00\x00");
+	assert_eq!(elf.section(".note.GNU-stack").data.len(), 0);
+
+	let text_index = elf.sections.iter().position(|s| s.name == ".text").unwrap() as u16;
+	let data_index = elf.sections.iter().position(|s| s.name == ".data").unwrap() as u16;
+	let exports: Vec<_> = elf.symbols.iter().filter(|s| s.info >> 4 == 1 && s.section != 0).collect();
+	assert_eq!(
+		exports,
+		[
+			&ElfSymbol { name: "main".into(), info: 0x12, section: text_index, value: 0 },
+			&ElfSymbol { name: "counter".into(), info: 0x11, section: data_index, value: 0 }
+		]
+	);
+	let externs: Vec<_> = elf.symbols.iter().filter(|s| s.info >> 4 == 1 && s.section == 0).map(|s| s.name.as_str()).collect();
+	assert_eq!(externs, ["puts", "a_very_long_extern_symbol"]);
+
+	assert_eq!(
+		elf.relocs(".text"),
+		[
+			// lea rdi,[hello]
+			ElfReloc { offset: 4 + 3, symbol: ".rodata".into(), kind: 2, addend: -4 },
+			// call puts
+			ElfReloc { offset: 11 + 1, symbol: "puts".into(), kind: 4, addend: -4 },
+			// mov rax,[table]
+			ElfReloc { offset: 21 + 3, symbol: ".rodata".into(), kind: 2, addend: 16 - 4 },
+			// mov eax,[counter]
+			ElfReloc { offset: 30 + 2, symbol: ".data".into(), kind: 2, addend: -4 },
+			// add dword ptr [counter],1: there's an immediate after the displacement
+			ElfReloc { offset: bump_offset as u64 + 4 + 2, symbol: ".data".into(), kind: 2, addend: -5 },
+		]
+	);
+	assert_eq!(
+		elf.relocs(".rodata"),
+		[
+			ElfReloc { offset: 16, symbol: ".text".into(), kind: 1, addend: bump_offset as i64 },
+			ElfReloc { offset: 24, symbol: "a_very_long_extern_symbol".into(), kind: 1, addend: 0 },
+		]
+	);
+	assert!(elf.relocs(".data").is_empty());
+
+	// The relocated fields are cleared and the branch in the same section is still valid
+	let instrs: Vec<_> = Decoder::new(64, &text.data, DecoderOptions::NONE).into_iter().collect();
+	assert_eq!(instrs.len(), 14);
+	assert_eq!(instrs[1].memory_displacement64(), 11);
+	assert_eq!(instrs[2].near_branch_target(), 16);
+	assert_eq!(instrs[3].near_branch_target(), bump_offset as u64);
+	assert_eq!(instrs[11].near_branch_target(), bump_offset as u64 + 11);
+	assert_eq!(&rodata.data[16..], &[0; 16]);
+}
+
+#[test]
+fn write_coff() {
+	let (mut a, obj, bump_offset) = create_program();
+	let bytes = obj.write(&mut a, ObjectFormat::Coff).unwrap();
+	let coff = Coff::parse(&bytes);
+
+	assert_eq!(coff.sections.len(), 3);
+	let text = coff.section(".text");
+	assert_eq!(text.characteristics, 0x6050_0020);
+	let rodata = coff.section(".rodata");
+	assert_eq!(rodata.characteristics, 0x4040_0040);
+	assert_eq!(&rodata.data[0..14], b"Hello, world!\0");
+	let data = coff.section(".data");
+	assert_eq!(data.characteristics, 0xC030_0040);
+	assert_eq!(data.data, b"\x01\x00\x00\x00");
+
+	let symbols: Vec<_> = coff.symbols.iter().filter(|s| !s.name.is_empty()).collect();
+	assert_eq!(
+		symbols,
+		[
+			&CoffSymbol { name: ".text".into(), value: 0, section: 1, kind: 0, class: 3 },
+			&CoffSymbol { name: ".rodata".into(), value: 0, section: 2, kind: 0, class: 3 },
+			&CoffSymbol { name: ".data".into(), value: 0, section: 3, kind: 0, class: 3 },
+			&CoffSymbol { name: "main".into(), value: 0, section: 1, kind: 0x20, class: 2 },
+			&CoffSymbol { name: "counter".into(), value: 0, section: 3, kind: 0, class: 2 },
+			&CoffSymbol { name: "puts".into(), value: 0, section: 0, kind: 0, class: 2 },
+			&CoffSymbol { name: "a_very_long_extern_symbol".into(), value: 0, section: 0, kind: 0, class: 2 },
+		]
+	);
+
+	assert_eq!(
+		text.relocs,
+		[
+			CoffReloc { offset: 7, symbol: ".rodata".into(), kind: 4 },
+			CoffReloc { offset: 12, symbol: "puts".into(), kind: 4 },
+			CoffReloc { offset: 24, symbol: ".rodata".into(), kind: 4 },
+			CoffReloc { offset: 32, symbol: ".data".into(), kind: 4 },
+			CoffReloc { offset: bump_offset as u32 + 6, symbol: ".data".into(), kind: 4 },
+		]
+	);
+	// The addends are stored in the data
+	assert_eq!(u32_at(&text.data, 7), 0);
+	assert_eq!(u32_at(&text.data, 12), 0);
+	assert_eq!(u32_at(&text.data, 24), 16);
+	assert_eq!(u32_at(&text.data, bump_offset + 6) as i32, -1);
+	assert_eq!(
+		rodata.relocs,
+		[CoffReloc { offset: 16, symbol: ".text".into(), kind: 1 }, CoffReloc { offset: 24, symbol: "a_very_long_extern_symbol".into(), kind: 1 }]
+	);
+	assert_eq!(u64_at(&rodata.data, 16), bump_offset as u64);
+	assert_eq!(u64_at(&rodata.data, 24), 0);
+}
+
+#[test]
+fn branches_to_other_sections() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let cold = a.create_section(".text.cold", SectionKind::Code, 1).unwrap();
+	let mut func = a.create_label();
+	let mut unlikely = a.create_label();
+	let mut done = a.create_label();
+
+	a.set_label(&mut func).unwrap();
+	a.test(eax, eax).unwrap();
+	a.jne(unlikely).unwrap();
+	a.set_label(&mut done).unwrap();
+	a.ret().unwrap();
+
+	a.set_section(cold).unwrap();
+	a.set_label(&mut unlikely).unwrap();
+	a.xor(eax, eax).unwrap();
+	a.jmp(done).unwrap();
+
+	let mut obj = ObjectWriter::new();
+	obj.add_export("func", func).unwrap();
+	let elf = Elf::parse(&obj.write(&mut a, ObjectFormat::Elf).unwrap());
+	// test eax,eax / jne near unlikely / ret
+	assert_eq!(elf.section(".text").data, b"\x85\xC0\x0F\x85\x00\x00\x00\x00\xC3");
+	assert_eq!(elf.relocs(".text"), [ElfReloc { offset: 4, symbol: ".text.cold".into(), kind: 4, addend: -4 }]);
+	// xor eax,eax / jmp near done
+	assert_eq!(elf.section(".text.cold").data, b"\x31\xC0\xE9\x00\x00\x00\x00");
+	assert_eq!(elf.relocs(".text.cold"), [ElfReloc { offset: 3, symbol: ".text".into(), kind: 4, addend: 8 - 4 }]);
+
+	let coff = Coff::parse(&obj.write(&mut a, ObjectFormat::Coff).unwrap());
+	assert_eq!(coff.section(".text").relocs, [CoffReloc { offset: 4, symbol: ".text.cold".into(), kind: 4 }]);
+	assert_eq!(coff.section(".text.cold").relocs, [CoffReloc { offset: 3, symbol: ".text".into(), kind: 4 }]);
+	// COFF stores the addend in the field
+	assert_eq!(u32_at(&coff.section(".text.cold").data, 3), 8);
+}
+
+#[test]
+fn long_section_names() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let section = a.create_section(".rodata.long_name", SectionKind::ReadOnlyData, 1).unwrap();
+	a.nop().unwrap();
+	a.set_section(section).unwrap();
+	a.db(b"\x12").unwrap();
+	let obj = ObjectWriter::new();
+	let elf = Elf::parse(&obj.write(&mut a, ObjectFormat::Elf).unwrap());
+	assert_eq!(elf.section(".rodata.long_name").data, b"\x12");
+	let coff = Coff::parse(&obj.write(&mut a, ObjectFormat::Coff).unwrap());
+	assert_eq!(coff.section(".rodata.long_name").data, b"\x12");
+	assert_eq!(coff.symbols[2].name, ".rodata.long_name");
+}
+
+#[test]
+fn invalid_symbols_fail() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut obj = ObjectWriter::new();
+	let label = a.create_label();
+	assert!(obj.add_extern(&mut a, "").is_err());
+	assert!(obj.add_extern(&mut a, "a\0b").is_err());
+	let _ = obj.add_extern(&mut a, "func").unwrap();
+	assert!(obj.add_extern(&mut a, "func").is_err());
+	assert!(obj.add_export("func", label).is_err());
+	assert!(obj.add_export("func2", CodeLabel::default()).is_err());
+}
+
+#[test]
+fn invalid_programs_fail() {
+	let write = |f: &dyn Fn(&mut CodeAssembler, &mut ObjectWriter)| -> Result<Vec<u8>, IcedError> {
+		let mut a = CodeAssembler::new(64).unwrap();
+		let mut obj = ObjectWriter::new();
+		f(&mut a, &mut obj);
+		obj.write(&mut a, ObjectFormat::Elf)
+	};
+
+	assert!(write(&|a, _| a.nop().unwrap()).is_ok());
+
+	// 32-bit code
+	let mut a = CodeAssembler::new(32).unwrap();
+	a.nop().unwrap();
+	assert!(ObjectWriter::new().write(&mut a, ObjectFormat::Elf).is_err());
+
+	// An exported label that wasn't set
+	assert!(write(&|a, obj| {
+		let label = a.create_label();
+		obj.add_export("func", label).unwrap();
+		a.nop().unwrap();
+	})
+	.is_err());
+
+	// An extern symbol that was set
+	assert!(write(&|a, obj| {
+		let mut label = obj.add_extern(a, "func").unwrap();
+		a.set_label(&mut label).unwrap();
+		a.nop().unwrap();
+	})
+	.is_err());
+
+	// Exported extern symbol
+	assert!(write(&|a, obj| {
+		let label = obj.add_extern(a, "func").unwrap();
+		obj.add_export("func2", label).unwrap();
+		a.nop().unwrap();
+	})
+	.is_err());
+
+	// A branch to an address that isn't a label
+	assert!(write(&|a, _| a.call(0x1234_5678u64).unwrap()).is_err());
+
+	// A branch with an 8-bit displacement to an extern symbol
+	assert!(write(&|a, obj| {
+		let label = obj.add_extern(a, "func").unwrap();
+		a.jrcxz(label).unwrap();
+	})
+	.is_err());
+
+	// A section with a fixed address
+	assert!(write(&|a, _| {
+		let section = a.create_section(".data", SectionKind::Data, 1).unwrap();
+		a.set_section_ip(section, 0x1000).unwrap();
+	})
+	.is_err());
+
+	// An align directive with a bigger alignment than the section
+	assert!(write(&|a, _| {
+		let section = a.create_section(".data", SectionKind::Data, 4).unwrap();
+		a.set_section(section).unwrap();
+		a.align(8).unwrap();
+	})
+	.is_err());
+
+	// Relative label addresses in different sections
+	assert!(write(&|a, _| {
+		let section = a.create_section(".rodata", SectionKind::ReadOnlyData, 4).unwrap();
+		let mut label1 = a.create_label();
+		let mut label2 = a.create_label();
+		a.set_label(&mut label1).unwrap();
+		a.nop().unwrap();
+		a.set_section(section).unwrap();
+		a.set_label(&mut label2).unwrap();
+		a.dd_label_rel(&[label1], label2).unwrap();
+	})
+	.is_err());
+}
@@ -0,0 +1,508 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::code_asm::*;
+use crate::{BlockEncoderOptions, Code, Decoder, DecoderOptions, Instruction, OpKind, Register};
+use alloc::vec::Vec;
+
+const IP: u64 = 0x1000;
+const STACK: u64 = 0x8000;
+
+fn decode(bytes: &[u8]) -> Vec<Instruction> {
+	let instructions: Vec<Instruction> = Decoder::with_ip(64, bytes, IP, DecoderOptions::NONE).into_iter().collect();
+	assert!(instructions.iter().all(|instruction| !instruction.is_invalid()));
+	instructions
+}
+
+// Runs the few instructions used by the tests and returns RAX. A CALL doesn't call anything, it only trashes all volatile GPRs.
+fn run(bytes: &[u8], args: &[(Register, u64)]) -> u64 {
+	let instructions = decode(bytes);
+	let mut regs = [0x5555_5555_5555_5555u64; 16];
+	let mut stack = vec![0xCCu8; 0x200];
+	for &(register, value) in args {
+		regs[register.number()] = value;
+	}
+	regs[Register::RSP.number()] = STACK;
+	let mut index = 0;
+	let mut zf = false;
+	for _ in 0..10_000 {
+		let instruction = &instructions[index];
+		index += 1;
+		let address = |regs: &[u64; 16]| -> usize {
+			assert_eq!(instruction.memory_index(), Register::None);
+			let address = regs[instruction.memory_base().number()].wrapping_add(instruction.memory_displacement64());
+			(address - (STACK - 0x100)) as usize
+		};
+		let read = |regs: &[u64; 16], stack: &[u8], op: u32| -> u64 {
+			match instruction.op_kind(op) {
+				OpKind::Register => regs[instruction.op_register(op).number()],
+				OpKind::Memory => {
+					let address = address(regs);
+					u64::from_le_bytes(stack[address..address + 8].try_into().unwrap())
+				}
+				_ => instruction.immediate(op),
+			}
+		};
+		let write = |regs: &mut [u64; 16], stack: &mut [u8], value: u64| match instruction.op0_kind() {
+			OpKind::Register => regs[instruction.op0_register().number()] = value,
+			OpKind::Memory => {
+				let address = address(regs);
+				stack[address..address + 8].copy_from_slice(&value.to_le_bytes());
+			}
+			_ => unreachable!(),
+		};
+		match instruction.code() {
+			Code::Mov_r64_rm64 | Code::Mov_rm64_r64 | Code::Mov_r64_imm64 | Code::Mov_rm64_imm32 => {
+				let value = read(&regs, &stack, 1);
+				write(&mut regs, &mut stack, value);
+			}
+			Code::Add_r64_rm64 | Code::Add_rm64_r64 | Code::Add_rm64_imm8 => {
+				let value = read(&regs, &stack, 0).wrapping_add(read(&regs, &stack, 1));
+				write(&mut regs, &mut stack, value);
+			}
+			Code::Lea_r64_m => {
+				let value = regs[instruction.memory_base().number()]
+					.wrapping_add(regs[instruction.memory_index().number()].wrapping_mul(instruction.memory_index_scale() as u64));
+				write(&mut regs, &mut stack, value);
+			}
+			Code::Dec_rm64 => {
+				let value = read(&regs, &stack, 0).wrapping_sub(1);
+				zf = value == 0;
+				write(&mut regs, &mut stack, value);
+			}
+			Code::Jne_rel8_64 | Code::Jne_rel32_64 | Code::Jmp_rel8_64 => {
+				if instruction.code() == Code::Jmp_rel8_64 || !zf {
+					index = instructions.iter().position(|target| target.ip() == instruction.near_branch_target()).unwrap();
+				}
+			}
+			Code::Push_r64 => {
+				regs[Register::RSP.number()] -= 8;
+				let address = (regs[Register::RSP.number()] - (STACK - 0x100)) as usize;
+				stack[address..address + 8].copy_from_slice(&regs[instruction.op0_register().number()].to_le_bytes());
+			}
+			Code::Pop_r64 => {
+				let address = (regs[Register::RSP.number()] - (STACK - 0x100)) as usize;
+				regs[instruction.op0_register().number()] = u64::from_le_bytes(stack[address..address + 8].try_into().unwrap());
+				regs[Register::RSP.number()] += 8;
+			}
+			Code::Call_rel32_64 => {
+				for register in [
+					Register::RAX,
+					Register::RCX,
+					Register::RDX,
+					Register::RSI,
+					Register::RDI,
+					Register::R8,
+					Register::R9,
+					Register::R10,
+					Register::R11,
+				] {
+					regs[register.number()] = 0xDEAD_0000 + register.number() as u64;
+				}
+			}
+			Code::Retnq => {
+				assert_eq!(regs[Register::RSP.number()], STACK);
+				return regs[Register::RAX.number()];
+			}
+			code => panic!("Unsupported instruction: {:?}", code),
+		}
+	}
+	panic!("Infinite loop")
+}
+
+fn used_registers(bytes: &[u8]) -> Vec<Register> {
+	let mut registers = Vec::new();
+	for instruction in decode(bytes) {
+		for op in 0..instruction.op_count() {
+			if instruction.op_kind(op) == OpKind::Register {
+				registers.push(instruction.op_register(op));
+			}
+		}
+	}
+	registers
+}
+
+fn spill_options(size: u32) -> RegisterAllocatorOptions {
+	let mut options = RegisterAllocatorOptions::default();
+	options.set_spill_area(Register::RSP, -0x80, size);
+	options
+}
+
+#[test]
+fn options() {
+	let mut options = RegisterAllocatorOptions::default();
+	assert_eq!(options.calling_convention(), CallingConvention::SystemV64);
+	assert!(options.saved_registers().is_empty());
+	assert_eq!(options.spill_base(), Register::None);
+	options.set_calling_convention(CallingConvention::Microsoft64);
+	options.set_saved_registers(&[Register::RBX, Register::XMM6]);
+	options.set_spill_area(Register::RBP, -0x40, 0x40);
+	assert_eq!(options.calling_convention(), CallingConvention::Microsoft64);
+	assert_eq!(options.saved_registers(), &[Register::RBX, Register::XMM6]);
+	assert_eq!((options.spill_base(), options.spill_displacement(), options.spill_size()), (Register::RBP, -0x40, 0x40));
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	assert_eq!(a.register_allocator_options(), &RegisterAllocatorOptions::default());
+	a.set_register_allocator_options(options.clone());
+	assert_eq!(a.register_allocator_options(), &options);
+	a.reset();
+	assert_eq!(a.register_allocator_options(), &options);
+}
+
+#[test]
+fn virtual_registers_require_64_bit_code() {
+	for &bitness in &[16, 32] {
+		let mut a = CodeAssembler::new(bitness).unwrap();
+		assert!(a.create_virtual_gpr32().is_err());
+	}
+	let mut a = CodeAssembler::new(64).unwrap();
+	assert!(a.create_virtual_gpr8().is_ok());
+	assert!(a.create_virtual_gpr16().is_ok());
+	assert!(a.create_virtual_gpr32().is_ok());
+	assert!(a.create_virtual_gpr64().is_ok());
+	assert!(a.create_virtual_xmm().is_ok());
+	assert!(a.create_virtual_ymm().is_ok());
+	assert!(a.create_virtual_zmm().is_ok());
+	assert!(a.create_virtual_k().is_ok());
+}
+
+#[test]
+fn too_many_virtual_registers() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut count = 0;
+	while a.create_virtual_gpr64().is_ok() {
+		count += 1;
+	}
+	assert!(count >= 100);
+	a.reset();
+	assert!(a.create_virtual_gpr64().is_ok());
+}
+
+#[test]
+fn no_virtual_registers() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let _ = a.create_virtual_gpr64().unwrap();
+	a.mov(rax, rdi).unwrap();
+	a.ret().unwrap();
+	assert_eq!(a.assemble(IP).unwrap(), b"\x48\x89\xF8\xC3");
+}
+
+#[test]
+fn allocate_registers() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let sum = a.create_virtual_gpr64().unwrap();
+	let tmp = a.create_virtual_gpr64().unwrap();
+	a.mov(sum, rdi).unwrap();
+	a.lea(tmp, ptr(rsi + rsi)).unwrap();
+	a.add(sum, tmp).unwrap();
+	a.mov(rax, sum).unwrap();
+	a.ret().unwrap();
+	// The instructions still use the placeholder registers
+	assert_ne!(a.instructions()[0].op0_register(), Register::R10);
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(used_registers(&bytes), [Register::R10, Register::RDI, Register::R11, Register::R10, Register::R11, Register::RAX, Register::R10]);
+	assert_eq!(run(&bytes, &[(Register::RDI, 3), (Register::RSI, 4)]), 11);
+}
+
+#[test]
+fn registers_are_reused() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let regs: Vec<_> = (0..30).map(|_| a.create_virtual_gpr64().unwrap()).collect();
+	a.mov(rax, 0u64).unwrap();
+	for (i, &reg) in regs.iter().enumerate() {
+		a.mov(reg, i as u64 + 1).unwrap();
+		a.add(rax, reg).unwrap();
+	}
+	a.ret().unwrap();
+	let bytes = a.assemble(IP).unwrap();
+	assert!(used_registers(&bytes).iter().all(|&register| register == Register::RAX || register == Register::R10));
+	assert_eq!(run(&bytes, &[]), 465);
+}
+
+#[test]
+fn physical_registers_are_preserved() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let value = a.create_virtual_gpr64().unwrap();
+	let value2 = a.create_virtual_gpr64().unwrap();
+	a.mov(value, 1u64).unwrap();
+	a.mov(r10, 0x100u64).unwrap();
+	a.mov(value2, 2u64).unwrap();
+	a.add(value, rdi).unwrap();
+	a.add(value, rsi).unwrap();
+	a.add(value, r10).unwrap();
+	a.add(value, value2).unwrap();
+	a.mov(rax, value).unwrap();
+	a.ret().unwrap();
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(run(&bytes, &[(Register::RDI, 0x10), (Register::RSI, 0x20)]), 0x133);
+}
+
+#[test]
+fn spill_registers() {
+	let create = |a: &mut CodeAssembler| {
+		let regs: Vec<_> = (0..20).map(|_| a.create_virtual_gpr64().unwrap()).collect();
+		for (i, &reg) in regs.iter().enumerate() {
+			a.mov(reg, i as u64 + 1).unwrap();
+		}
+		a.mov(rax, 0u64).unwrap();
+		for &reg in regs.iter().rev() {
+			a.add(rax, reg).unwrap();
+		}
+		a.ret().unwrap();
+	};
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	create(&mut a);
+	assert!(a.assemble(IP).is_err());
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(spill_options(8));
+	create(&mut a);
+	assert!(a.assemble(IP).is_err());
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(spill_options(0x80));
+	create(&mut a);
+	let result = a.assemble_options(IP, BlockEncoderOptions::NONE).unwrap();
+	// RAX is used by all ADDs so 7 GPRs are allocated and one GPR is used to load the spilled registers
+	assert_eq!(result.spill_size, 13 * 8);
+	assert!(!used_registers(&result.inner.code_buffer).contains(&Register::RSP));
+	assert_eq!(run(&result.inner.code_buffer, &[]), 210);
+}
+
+#[test]
+fn spilled_registers_in_memory_operands() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(spill_options(0x80));
+	let regs: Vec<_> = (0..16).map(|_| a.create_virtual_gpr64().unwrap()).collect();
+	for (i, &reg) in regs.iter().enumerate() {
+		a.mov(reg, i as u64 + 1).unwrap();
+	}
+	a.mov(rax, 0u64).unwrap();
+	for w in regs.windows(2) {
+		a.lea(w[0], ptr(w[0] + w[1] * 2)).unwrap();
+		a.add(rax, w[0]).unwrap();
+	}
+	a.ret().unwrap();
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(run(&bytes, &[]), (1..16u64).map(|i| i + (i + 1) * 2).sum::<u64>());
+}
+
+#[test]
+fn values_live_across_calls() {
+	let create = |a: &mut CodeAssembler| {
+		let value = a.create_virtual_gpr64().unwrap();
+		let tmp = a.create_virtual_gpr64().unwrap();
+		a.push(rbx).unwrap();
+		a.mov(value, rdi).unwrap();
+		a.mov(tmp, 5u64).unwrap();
+		a.mov(rdi, tmp).unwrap();
+		a.call(0x2000).unwrap();
+		a.add(value, 1).unwrap();
+		a.mov(rax, value).unwrap();
+		a.pop(rbx).unwrap();
+		a.ret().unwrap();
+	};
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	create(&mut a);
+	assert!(a.assemble(IP).is_err());
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut options = RegisterAllocatorOptions::default();
+	options.set_saved_registers(&[Register::RBX]);
+	a.set_register_allocator_options(options);
+	create(&mut a);
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(decode(&bytes)[1].op0_register(), Register::RBX);
+	assert_eq!(run(&bytes, &[(Register::RDI, 0x41)]), 0x42);
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(spill_options(8));
+	create(&mut a);
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(run(&bytes, &[(Register::RDI, 0x41)]), 0x42);
+}
+
+#[test]
+fn loops() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let sum = a.create_virtual_gpr64().unwrap();
+	let counter = a.create_virtual_gpr64().unwrap();
+	let tmp = a.create_virtual_gpr64().unwrap();
+	let mut top = a.create_label();
+	a.mov(sum, 0u64).unwrap();
+	a.mov(counter, rdi).unwrap();
+	a.set_label(&mut top).unwrap();
+	a.mov(tmp, counter).unwrap();
+	a.add(tmp, tmp).unwrap();
+	a.add(sum, tmp).unwrap();
+	a.dec(counter).unwrap();
+	a.jne(top).unwrap();
+	a.mov(rax, sum).unwrap();
+	a.ret().unwrap();
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(run(&bytes, &[(Register::RDI, 10)]), 110);
+}
+
+#[test]
+fn loops_with_spilled_registers() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(spill_options(0x80));
+	let regs: Vec<_> = (0..16).map(|_| a.create_virtual_gpr64().unwrap()).collect();
+	let counter = a.create_virtual_gpr64().unwrap();
+	let mut top = a.create_label();
+	for &reg in &regs {
+		a.mov(reg, 0u64).unwrap();
+	}
+	a.mov(counter, 3u64).unwrap();
+	a.set_label(&mut top).unwrap();
+	// The registers that are added last are spilled
+	for (i, &reg) in regs.iter().enumerate().rev() {
+		a.add(reg, i as i32 + 1).unwrap();
+	}
+	a.dec(counter).unwrap();
+	a.jne(top).unwrap();
+	a.mov(rax, 0u64).unwrap();
+	for &reg in &regs {
+		a.add(rax, reg).unwrap();
+	}
+	a.ret().unwrap();
+	let result = a.assemble_options(IP, BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS).unwrap();
+	assert!(result.spill_size > 0);
+	assert_eq!(run(&result.inner.code_buffer, &[]), 3 * (1..=16).sum::<u64>());
+	// The label is at the first load of a spilled register
+	let label_ip = result.label_ip(&top).unwrap();
+	let instructions = decode(&result.inner.code_buffer);
+	let instruction = instructions.iter().find(|instruction| instruction.ip() == label_ip).unwrap();
+	assert_eq!(instruction.code(), Code::Mov_r64_rm64);
+	assert_eq!(instruction.memory_base(), Register::RSP);
+	assert!(instructions.iter().any(|instruction| instruction.code() == Code::Jne_rel32_64 && instruction.near_branch_target() == label_ip));
+}
+
+#[test]
+fn vector_registers() {
+	let create = |a: &mut CodeAssembler| {
+		let value = a.create_virtual_xmm().unwrap();
+		a.movaps(value, xmm0).unwrap();
+		a.call(0x2000).unwrap();
+		a.addps(value, xmm0).unwrap();
+		a.movaps(xmm0, value).unwrap();
+		a.ret().unwrap();
+	};
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	let mut options = RegisterAllocatorOptions::new(CallingConvention::Microsoft64);
+	a.set_register_allocator_options(options.clone());
+	create(&mut a);
+	assert!(a.assemble(IP).is_err());
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	options.set_saved_registers(&[Register::XMM7]);
+	a.set_register_allocator_options(options.clone());
+	create(&mut a);
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(used_registers(&bytes), [Register::XMM7, Register::XMM0, Register::XMM7, Register::XMM0, Register::XMM0, Register::XMM7]);
+
+	// The upper bits of XMM6-XMM15 aren't preserved
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(options.clone());
+	let value = a.create_virtual_ymm().unwrap();
+	a.vmovaps(value, ymm0).unwrap();
+	a.call(0x2000).unwrap();
+	a.vmovaps(ymm0, value).unwrap();
+	a.ret().unwrap();
+	assert!(a.assemble(IP).is_err());
+
+	// Legacy SSE instructions use legacy loads and stores
+	let mut a = CodeAssembler::new(64).unwrap();
+	options.set_saved_registers(&[]);
+	options.set_spill_area(Register::RSP, 8, 0x10);
+	a.set_register_allocator_options(options);
+	create(&mut a);
+	let result = a.assemble_options(IP, BlockEncoderOptions::NONE).unwrap();
+	assert_eq!(result.spill_size, 0x10);
+	let codes: Vec<Code> = decode(&result.inner.code_buffer).iter().map(Instruction::code).collect();
+	assert_eq!(
+		codes,
+		[
+			Code::Movaps_xmm_xmmm128,
+			Code::Movups_xmmm128_xmm,
+			Code::Call_rel32_64,
+			Code::Movups_xmm_xmmm128,
+			Code::Addps_xmm_xmmm128,
+			Code::Movups_xmmm128_xmm,
+			Code::Movups_xmm_xmmm128,
+			Code::Movaps_xmm_xmmm128,
+			Code::Retnq,
+		]
+	);
+}
+
+#[test]
+fn invalid_options() {
+	let mut options = RegisterAllocatorOptions::default();
+	options.set_saved_registers(&[Register::RSI]);
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(options);
+	let value = a.create_virtual_gpr64().unwrap();
+	a.mov(value, 1u64).unwrap();
+	assert!(a.assemble(IP).is_err());
+
+	let mut a = CodeAssembler::new(64).unwrap();
+	a.set_register_allocator_options(spill_options(0x10));
+	let mut options = spill_options(0x10);
+	options.set_spill_area(Register::EBP, 0, 0x10);
+	a.set_register_allocator_options(options);
+	let value = a.create_virtual_gpr64().unwrap();
+	a.mov(value, 1u64).unwrap();
+	assert!(a.assemble(IP).is_err());
+}
+
+#[test]
+fn other_register_sizes() {
+	let mut a = CodeAssembler::new(64).unwrap();
+	let byte = a.create_virtual_gpr8().unwrap();
+	let word = a.create_virtual_gpr16().unwrap();
+	let dword = a.create_virtual_gpr32().unwrap();
+	let mask = a.create_virtual_k().unwrap();
+	let zmm = a.create_virtual_zmm().unwrap();
+	a.mov(byte, 1).unwrap();
+	a.mov(word, 2).unwrap();
+	a.mov(dword, 3).unwrap();
+	a.movzx(eax, byte).unwrap();
+	a.movzx(ecx, word).unwrap();
+	a.add(eax, dword).unwrap();
+	a.kmovw(mask, ecx).unwrap();
+	a.vmovaps(zmm.k1(), zmm0).unwrap();
+	a.vaddps(zmm0.k1(), zmm0, zmm).unwrap();
+	a.vmovaps(zmm1.k1(), zmm0).unwrap();
+	a.kmovw(k2, mask).unwrap();
+	a.ret().unwrap();
+	let bytes = a.assemble(IP).unwrap();
+	assert_eq!(
+		used_registers(&bytes),
+		[
+			Register::R10L,
+			Register::R11W,
+			Register::R9D,
+			Register::EAX,
+			Register::R10L,
+			Register::ECX,
+			Register::R11W,
+			Register::EAX,
+			Register::R9D,
+			Register::K2,
+			Register::ECX,
+			Register::ZMM8,
+			Register::ZMM0,
+			Register::ZMM0,
+			Register::ZMM0,
+			Register::ZMM8,
+			Register::ZMM1,
+			Register::ZMM0,
+			Register::K2,
+			Register::K2,
+		]
+	);
+}
@@ -87,12 +87,16 @@ macro_rules! read_op_mem_stmt {
 	};
 }
 
+#[cfg(feature = "instr_info")]
+mod cpu_profile;
 mod enums;
 mod handlers;
 mod table_de;
 #[cfg(test)]
 pub(crate) mod tests;
 
+#[cfg(feature = "instr_info")]
+pub use crate::decoder::cpu_profile::*;
 use crate::decoder::handlers::tables::TABLES;
 use crate::decoder::handlers::{OpCodeHandler, OpCodeHandlerDecodeFn};
 use crate::iced_constants::IcedConstants;
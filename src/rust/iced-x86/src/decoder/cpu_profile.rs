@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::decoder::StateFlags;
+use crate::instruction_internal;
+use crate::*;
+use core::iter::FusedIterator;
+
+/// Decodes instructions like a [`Decoder`] but only instructions supported by a CPU are valid. An instruction is
+/// supported if all its [`cpuid_features()`] are in the [`CpuFeatureProfile`].
+///
+/// If `invalidate` is `true`, unsupported instructions are decoded as [`Code::INVALID`] and [`last_error()`]
+/// returns [`DecoderError::InvalidInstruction`]. The length of the instruction is the length of the unsupported
+/// instruction so the next instruction is decoded at the same position as before. If it's `false`, the
+/// instruction is decoded as usual and [`is_outside_cpu_profile()`] returns `true`.
+///
+/// [`Decoder`]: struct.Decoder.html
+/// [`cpuid_features()`]: struct.Instruction.html#method.cpuid_features
+/// [`CpuFeatureProfile`]: struct.CpuFeatureProfile.html
+/// [`Code::INVALID`]: enum.Code.html#variant.INVALID
+/// [`last_error()`]: #method.last_error
+/// [`DecoderError::InvalidInstruction`]: enum.DecoderError.html#variant.InvalidInstruction
+/// [`is_outside_cpu_profile()`]: #method.is_outside_cpu_profile
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::*;
+///
+/// // popcnt eax,ecx
+/// // vpaddd ymm1,ymm2,ymm3
+/// let bytes = b"\xF3\x0F\xB8\xC1\xC5\xED\xFE\xCB";
+/// let decoder = Decoder::with_ip(64, bytes, 0x1234_5678, DecoderOptions::NONE);
+/// let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::x86_64_v2(), true);
+///
+/// let instr = decoder.decode();
+/// assert_eq!(instr.code(), Code::Popcnt_r32_rm32);
+/// assert_eq!(decoder.last_error(), DecoderError::None);
+///
+/// let instr = decoder.decode();
+/// assert_eq!(instr.code(), Code::INVALID);
+/// assert_eq!(instr.len(), 4);
+/// assert_eq!(decoder.last_error(), DecoderError::InvalidInstruction);
+/// assert!(decoder.is_outside_cpu_profile());
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct CpuProfileDecoder<'a> {
+	decoder: Decoder<'a>,
+	profile: CpuFeatureProfile,
+	// true if instructions not in profile are invalid, false if they're only flagged
+	invalidate: bool,
+	// true if the last decoded instruction isn't supported by profile
+	outside_cpu_profile: bool,
+}
+
+impl<'a> CpuProfileDecoder<'a> {
+	/// Creates a decoder that only decodes the instructions supported by a CPU
+	///
+	/// # Arguments
+	///
+	/// * `decoder`: Decoder
+	/// * `profile`: Supported features
+	/// * `invalidate`: `true` if unsupported instructions are invalid, `false` if they're only flagged
+	#[must_use]
+	#[inline]
+	pub fn new(decoder: Decoder<'a>, profile: CpuFeatureProfile, invalidate: bool) -> Self {
+		Self { decoder, profile, invalidate, outside_cpu_profile: false }
+	}
+
+	/// Gets the decoder
+	#[must_use]
+	#[inline]
+	pub fn decoder(&self) -> &Decoder<'a> {
+		&self.decoder
+	}
+
+	/// Gets the decoder, eg. to change its position
+	#[must_use]
+	#[inline]
+	pub fn decoder_mut(&mut self) -> &mut Decoder<'a> {
+		&mut self.decoder
+	}
+
+	/// Returns the decoder
+	#[must_use]
+	#[inline]
+	pub fn into_decoder(self) -> Decoder<'a> {
+		self.decoder
+	}
+
+	/// Gets the CPU profile
+	#[must_use]
+	#[inline]
+	pub fn cpu_profile(&self) -> &CpuFeatureProfile {
+		&self.profile
+	}
+
+	/// `true` if unsupported instructions are invalid, `false` if they're only flagged
+	#[must_use]
+	#[inline]
+	pub fn invalidate(&self) -> bool {
+		self.invalidate
+	}
+
+	/// `true` if the last decoded instruction isn't supported by the CPU profile
+	#[must_use]
+	#[inline]
+	pub fn is_outside_cpu_profile(&self) -> bool {
+		self.outside_cpu_profile
+	}
+
+	/// Returns `true` if there's at least one more byte to decode, see [`Decoder::can_decode()`]
+	///
+	/// [`Decoder::can_decode()`]: struct.Decoder.html#method.can_decode
+	#[must_use]
+	#[inline]
+	pub fn can_decode(&self) -> bool {
+		self.decoder.can_decode()
+	}
+
+	/// Gets the last decoder error, see [`Decoder::last_error()`]. It's [`DecoderError::InvalidInstruction`] if the last
+	/// instruction was invalidated because it's not supported by the CPU profile.
+	///
+	/// [`Decoder::last_error()`]: struct.Decoder.html#method.last_error
+	/// [`DecoderError::InvalidInstruction`]: enum.DecoderError.html#variant.InvalidInstruction
+	#[must_use]
+	#[inline]
+	pub fn last_error(&self) -> DecoderError {
+		self.decoder.last_error()
+	}
+
+	/// Decodes and returns the next instruction, see [`Decoder::decode()`]
+	///
+	/// [`Decoder::decode()`]: struct.Decoder.html#method.decode
+	#[must_use]
+	#[inline]
+	pub fn decode(&mut self) -> Instruction {
+		let mut instruction = self.decoder.decode();
+		self.check_cpu_profile(&mut instruction);
+		instruction
+	}
+
+	/// Decodes the next instruction, see [`Decoder::decode_out()`]
+	///
+	/// [`Decoder::decode_out()`]: struct.Decoder.html#method.decode_out
+	///
+	/// # Arguments
+	///
+	/// * `instruction`: Updated with the decoded instruction.
+	#[inline]
+	pub fn decode_out(&mut self, instruction: &mut Instruction) {
+		self.decoder.decode_out(instruction);
+		self.check_cpu_profile(instruction);
+	}
+
+	fn check_cpu_profile(&mut self, instruction: &mut Instruction) {
+		self.outside_cpu_profile = instruction.code() != Code::INVALID && !self.profile.supports(instruction);
+		if self.outside_cpu_profile && self.invalidate {
+			let len = instruction.len();
+			let next_ip = instruction.next_ip();
+			*instruction = Instruction::default();
+			instruction_internal::internal_set_len(instruction, len as u32);
+			instruction.set_next_ip(next_ip);
+			instruction_internal::internal_set_code_size(instruction, self.decoder.default_code_size);
+			self.decoder.state.flags |= StateFlags::IS_INVALID;
+		}
+	}
+}
+
+impl Iterator for CpuProfileDecoder<'_> {
+	type Item = Instruction;
+
+	#[inline]
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.decoder.can_decode() {
+			Some(self.decode())
+		} else {
+			None
+		}
+	}
+}
+
+impl FusedIterator for CpuProfileDecoder<'_> {}
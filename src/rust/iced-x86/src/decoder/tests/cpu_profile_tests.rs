@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::*;
+use alloc::vec::Vec;
+
+// popcnt eax,ecx / vpaddd ymm1,ymm2,ymm3 / nop
+const BYTES: &[u8] = b"\xF3\x0F\xB8\xC1\xC5\xED\xFE\xCB\x90";
+
+#[test]
+fn all_features_decodes_everything() {
+	let decoder = Decoder::with_ip(64, BYTES, 0x1000, DecoderOptions::NONE);
+	let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::all(), true);
+	assert_eq!(decoder.cpu_profile(), &CpuFeatureProfile::all());
+	assert!(decoder.invalidate());
+	let codes: Vec<Code> = decoder.by_ref().map(|instr| instr.code()).collect();
+	assert_eq!(codes, [Code::Popcnt_r32_rm32, Code::VEX_Vpaddd_ymm_ymm_ymmm256, Code::Nopd]);
+	assert!(!decoder.is_outside_cpu_profile());
+	assert!(!decoder.can_decode());
+}
+
+#[test]
+fn invalidate_unsupported_instructions() {
+	let decoder = Decoder::with_ip(64, BYTES, 0x1000, DecoderOptions::NONE);
+	let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::x86_64_v1(), true);
+
+	let instr = decoder.decode();
+	assert_eq!(instr.code(), Code::INVALID);
+	assert_eq!(instr.len(), 4);
+	assert_eq!(instr.ip(), 0x1000);
+	assert_eq!(instr.next_ip(), 0x1004);
+	assert_eq!(instr.code_size(), CodeSize::Code64);
+	assert_eq!(decoder.last_error(), DecoderError::InvalidInstruction);
+	assert!(decoder.is_outside_cpu_profile());
+
+	let mut instr = Instruction::default();
+	decoder.decode_out(&mut instr);
+	assert_eq!(instr.code(), Code::INVALID);
+	assert_eq!(instr.len(), 4);
+	assert_eq!(decoder.last_error(), DecoderError::InvalidInstruction);
+	assert!(decoder.is_outside_cpu_profile());
+
+	let instr = decoder.decode();
+	assert_eq!(instr.code(), Code::Nopd);
+	assert_eq!(decoder.last_error(), DecoderError::None);
+	assert!(!decoder.is_outside_cpu_profile());
+	assert!(!decoder.can_decode());
+}
+
+#[test]
+fn flag_unsupported_instructions() {
+	let decoder = Decoder::with_ip(64, BYTES, 0x1000, DecoderOptions::NONE);
+	let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::x86_64_v2(), false);
+
+	let instr = decoder.decode();
+	assert_eq!(instr.code(), Code::Popcnt_r32_rm32);
+	assert!(!decoder.is_outside_cpu_profile());
+
+	let instr = decoder.decode();
+	assert_eq!(instr.code(), Code::VEX_Vpaddd_ymm_ymm_ymmm256);
+	assert_eq!(decoder.last_error(), DecoderError::None);
+	assert!(decoder.is_outside_cpu_profile());
+
+	decoder.decoder_mut().set_position(0).unwrap();
+	assert_eq!(decoder.into_decoder().decode().code(), Code::Popcnt_r32_rm32);
+}
+
+#[test]
+fn invalid_instructions_are_not_flagged() {
+	let decoder = Decoder::with_ip(64, b"\xF0\x01\xCE", 0x1000, DecoderOptions::NONE);
+	let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::new(), false);
+	let instr = decoder.decode();
+	assert_eq!(instr.code(), Code::INVALID);
+	assert!(!decoder.is_outside_cpu_profile());
+}
+
+#[test]
+fn no_more_bytes_has_priority() {
+	let decoder = Decoder::with_ip(64, b"\xC5\xED\xFE", 0x1000, DecoderOptions::NONE);
+	let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::x86_64_v1(), true);
+	let _ = decoder.decode();
+	assert_eq!(decoder.last_error(), DecoderError::NoMoreBytes);
+	assert_eq!(decoder.decoder().last_error(), DecoderError::NoMoreBytes);
+}
+
+#[test]
+fn cet_nops_are_supported() {
+	// endbr64 / rdsspq rax / endbr32
+	let bytes = b"\xF3\x0F\x1E\xFA\xF3\x48\x0F\x1E\xC8\xF3\x0F\x1E\xFB";
+	let decoder = Decoder::with_ip(64, bytes, 0x1000, DecoderOptions::NONE);
+	let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::x86_64_v2(), true);
+	for code in [Code::Endbr64, Code::Rdsspq_r64, Code::Endbr32] {
+		assert_eq!(decoder.decode().code(), code);
+		assert_eq!(decoder.last_error(), DecoderError::None);
+		assert!(!decoder.is_outside_cpu_profile());
+	}
+
+	// They're multi-byte NOPs so they need MULTIBYTENOP
+	let decoder = Decoder::with_ip(64, bytes, 0x1000, DecoderOptions::NONE);
+	let mut decoder = CpuProfileDecoder::new(decoder, CpuFeatureProfile::new(), false);
+	assert_eq!(decoder.decode().code(), Code::Endbr64);
+	assert!(decoder.is_outside_cpu_profile());
+}
+
+#[test]
+fn presets() {
+	for name in CpuFeatureProfile::names() {
+		let profile = CpuFeatureProfile::from_name(name).unwrap();
+		assert!(profile.is_superset(&CpuFeatureProfile::x86_64_v1()), "{}", name);
+		assert_eq!(CpuFeatureProfile::from_name(&name.to_uppercase()), Some(profile));
+	}
+	assert_eq!(CpuFeatureProfile::from_name("x86-64"), Some(CpuFeatureProfile::x86_64_v1()));
+	assert!(CpuFeatureProfile::from_name("pentium4").is_none());
+
+	let v1 = CpuFeatureProfile::x86_64_v1();
+	let v2 = CpuFeatureProfile::x86_64_v2();
+	let v3 = CpuFeatureProfile::x86_64_v3();
+	let v4 = CpuFeatureProfile::x86_64_v4();
+	assert!(v2.is_superset(&v1) && v3.is_superset(&v2) && v4.is_superset(&v3));
+	assert!(!v1.is_superset(&v2));
+	assert_eq!(v2.len(), v1.len() + 6);
+	assert_eq!(v3.len(), v2.len() + 9);
+	assert_eq!(v4.len(), v3.len() + 5);
+
+	let haswell = CpuFeatureProfile::from_name("haswell").unwrap();
+	assert!(haswell.is_superset(&v3));
+	assert!(!haswell.contains(CpuidFeature::AVX512F));
+	let skx = CpuFeatureProfile::from_name("skylake-avx512").unwrap();
+	assert!(skx.is_superset(&v4));
+	let adl = CpuFeatureProfile::from_name("alderlake").unwrap();
+	assert!(adl.is_superset(&v3));
+	assert!(!adl.contains(CpuidFeature::AVX512F) && !adl.contains(CpuidFeature::RTM) && !adl.contains(CpuidFeature::MPX));
+	let spr = CpuFeatureProfile::from_name("sapphirerapids").unwrap();
+	assert!(spr.contains(CpuidFeature::AMX_TILE) && !spr.contains(CpuidFeature::MPX));
+	let zen3 = CpuFeatureProfile::from_name("znver3").unwrap();
+	assert!(zen3.is_superset(&v3) && !zen3.contains(CpuidFeature::AVX512F) && zen3.contains(CpuidFeature::SSE4A));
+	let zen4 = CpuFeatureProfile::from_name("znver4").unwrap();
+	assert!(zen4.is_superset(&v4) && zen4.is_superset(&zen3));
+}
+
+#[test]
+fn add_remove_features() {
+	let mut profile = CpuFeatureProfile::new();
+	assert!(profile.is_empty());
+	profile.add(CpuidFeature::AVX2);
+	profile.add_all(&[CpuidFeature::AVX, CpuidFeature::AVX2]);
+	assert_eq!(profile.len(), 2);
+	assert!(profile.contains_all(&[CpuidFeature::AVX, CpuidFeature::AVX2]));
+	profile.remove(CpuidFeature::AVX);
+	assert_eq!(profile.iter().collect::<Vec<_>>(), [CpuidFeature::AVX2]);
+
+	let all = CpuFeatureProfile::all();
+	assert_eq!(all.len(), CpuidFeature::values().count());
+	assert!(all.contains(CpuidFeature::AVX_VNNI_INT16));
+	assert_eq!(CpuFeatureProfile::with_features(&[CpuidFeature::AVX2]), profile);
+
+	let mut union = CpuFeatureProfile::with_features(&[CpuidFeature::SSE]);
+	union.union_with(&profile);
+	assert_eq!(union.iter().collect::<Vec<_>>(), [CpuidFeature::AVX2, CpuidFeature::SSE]);
+}
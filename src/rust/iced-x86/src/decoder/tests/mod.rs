@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2018-present iced project and contributors
 
+#[cfg(feature = "instr_info")]
+mod cpu_profile_tests;
 mod decoder_mem_test_case;
 mod decoder_test_case;
 pub(crate) mod enums;
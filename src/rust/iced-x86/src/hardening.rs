@@ -0,0 +1,347 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+//! Rewrites indirect branches so they can't be used for branch target injection (Spectre v2)
+//!
+//! This requires the `hardening` feature to use (not enabled by default). Add it to your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.iced-x86]
+//! version = "1.21.0"
+//! features = ["hardening"]
+//! ```
+//!
+//! Indirect `CALL`s and `JMP`s are replaced with a direct `CALL`/`JMP` to a thunk, one thunk per target register.
+//! The thunks are created by a [`CodeAssembler`] and all instructions are re-encoded by a [`BlockEncoder`].
+//!
+//! [`CodeAssembler`]: ../code_asm/struct.CodeAssembler.html
+//! [`BlockEncoder`]: ../struct.BlockEncoder.html
+
+#[cfg(all(test, feature = "decoder"))]
+mod tests;
+
+use crate::code_asm::{dword_ptr, esp, qword_ptr, rsp, AsmRegister32, AsmRegister64, CodeAssembler};
+use crate::{BlockDirective, BlockEncoder, BlockEncoderOptions, Code, FlowControl, IcedError, Instruction, InstructionBlock, OpKind, Register};
+use alloc::vec::Vec;
+
+/// How an indirect branch is hardened by an [`IndirectBranchRewriter`]
+///
+/// [`IndirectBranchRewriter`]: struct.IndirectBranchRewriter.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum IndirectBranchMitigation {
+	/// The thunk stores the target on the stack and uses a `RET` to jump to it. A speculatively executed `RET` is
+	/// captured in a `PAUSE`/`LFENCE` loop. This isn't compatible with CET shadow stacks.
+	Retpoline,
+	/// The thunk executes an `LFENCE` before the indirect `JMP`. It's compatible with CET: the target must still start
+	/// with an `ENDBR64`/`ENDBR32` instruction unless the original branch has a `NOTRACK` prefix.
+	Lfence,
+}
+
+/// Result of [`IndirectBranchRewriter::rewrite()`]
+///
+/// [`IndirectBranchRewriter::rewrite()`]: struct.IndirectBranchRewriter.html#method.rewrite
+#[derive(Debug)]
+pub struct IndirectBranchRewriterResult {
+	/// Base IP of all encoded instructions
+	pub rip: u64,
+
+	/// The bytes of all encoded instructions followed by the thunks
+	pub code_buffer: Vec<u8>,
+
+	/// Offsets of the input instructions relative to the base IP. If an indirect branch was rewritten, it's the offset of the
+	/// first new instruction. If a branch was rewritten by the [`BlockEncoder`] because the target is too far away, the value
+	/// `u32::MAX` is stored in that element.
+	///
+	/// [`BlockEncoder`]: ../struct.BlockEncoder.html
+	pub new_instruction_offsets: Vec<u32>,
+
+	/// Number of rewritten indirect branches
+	pub rewritten_branches: usize,
+}
+
+/// Replaces indirect `CALL`s and `JMP`s with retpolines or `LFENCE` thunks
+///
+/// A `CALL reg`/`JMP reg` is replaced with a `CALL`/`JMP` to the thunk of `reg`. A `CALL [mem]`/`JMP [mem]` first loads the
+/// target into the scratch register (`R11`/`EAX` by default) which is then used as the target register.
+/// Only 32-bit and 64-bit code is supported. Far branches aren't rewritten.
+///
+/// The IP of a rewritten branch is the IP of its first new instruction so branches and `ENDBR64`/`ENDBR32` instructions
+/// at branch targets aren't moved.
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::hardening::*;
+/// use iced_x86::*;
+///
+/// # fn main() -> Result<(), IcedError> {
+/// // 0x1000: call rax
+/// // 0x1002: ret
+/// let instructions: Vec<_> = Decoder::with_ip(64, b"\xFF\xD0\xC3", 0x1000, DecoderOptions::NONE).into_iter().collect();
+/// let rewriter = IndirectBranchRewriter::new(64, IndirectBranchMitigation::Lfence)?;
+/// let result = rewriter.rewrite(&instructions, 0x1000)?;
+/// assert_eq!(result.rewritten_branches, 1);
+/// assert_eq!(result.new_instruction_offsets, [0, 5]);
+/// // call 1010h ; ret ; nops ; lfence ; jmp rax
+/// assert_eq!(&result.code_buffer[..6], b"\xE8\x0B\x00\x00\x00\xC3");
+/// assert_eq!(&result.code_buffer[0x10..], b"\x0F\xAE\xE8\xFF\xE0");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct IndirectBranchRewriter {
+	bitness: u32,
+	mitigation: IndirectBranchMitigation,
+	scratch_register: Register,
+	block_encoder_options: u32,
+}
+
+impl IndirectBranchRewriter {
+	/// Creates a rewriter
+	///
+	/// # Errors
+	///
+	/// Fails if `bitness` is not one of 32, 64.
+	///
+	/// # Arguments
+	///
+	/// * `bitness`: 32 or 64
+	/// * `mitigation`: How indirect branches are hardened
+	#[inline]
+	pub fn new(bitness: u32, mitigation: IndirectBranchMitigation) -> Result<Self, IcedError> {
+		let scratch_register = match bitness {
+			32 => Register::EAX,
+			64 => Register::R11,
+			_ => return Err(IcedError::new("Invalid bitness")),
+		};
+		Ok(Self { bitness, mitigation, scratch_register, block_encoder_options: BlockEncoderOptions::NONE })
+	}
+
+	/// Gets the bitness
+	#[must_use]
+	#[inline]
+	pub fn bitness(&self) -> u32 {
+		self.bitness
+	}
+
+	/// How indirect branches are hardened
+	#[must_use]
+	#[inline]
+	pub fn mitigation(&self) -> IndirectBranchMitigation {
+		self.mitigation
+	}
+
+	/// How indirect branches are hardened
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_mitigation(&mut self, new_value: IndirectBranchMitigation) {
+		self.mitigation = new_value;
+	}
+
+	/// Register that is overwritten with the target of a `CALL [mem]`/`JMP [mem]`. Default is `R11` (64-bit) or `EAX` (32-bit).
+	#[must_use]
+	#[inline]
+	pub fn scratch_register(&self) -> Register {
+		self.scratch_register
+	}
+
+	/// Register that is overwritten with the target of a `CALL [mem]`/`JMP [mem]`. Default is `R11` (64-bit) or `EAX` (32-bit).
+	///
+	/// # Errors
+	///
+	/// Fails if it's not a 64-bit (64-bit code) or 32-bit (32-bit code) GPR or if it's the stack pointer.
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_scratch_register(&mut self, new_value: Register) -> Result<(), IcedError> {
+		let is_valid =
+			if self.bitness == 64 { new_value.is_gpr64() && new_value != Register::RSP } else { new_value.is_gpr32() && new_value != Register::ESP };
+		if !is_valid {
+			return Err(IcedError::new("Invalid scratch register"));
+		}
+		self.scratch_register = new_value;
+		Ok(())
+	}
+
+	/// Extra [`BlockEncoderOptions`] used when the code is encoded, eg. [`BlockEncoderOptions::JCC_ERRATUM`]
+	///
+	/// [`BlockEncoderOptions`]: ../struct.BlockEncoderOptions.html
+	/// [`BlockEncoderOptions::JCC_ERRATUM`]: ../struct.BlockEncoderOptions.html#associatedconstant.JCC_ERRATUM
+	#[must_use]
+	#[inline]
+	pub fn block_encoder_options(&self) -> u32 {
+		self.block_encoder_options
+	}
+
+	/// Extra [`BlockEncoderOptions`] used when the code is encoded, eg. [`BlockEncoderOptions::JCC_ERRATUM`]
+	///
+	/// [`BlockEncoderOptions`]: ../struct.BlockEncoderOptions.html
+	/// [`BlockEncoderOptions::JCC_ERRATUM`]: ../struct.BlockEncoderOptions.html#associatedconstant.JCC_ERRATUM
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_block_encoder_options(&mut self, new_value: u32) {
+		self.block_encoder_options = new_value;
+	}
+
+	/// Rewrites all indirect branches and encodes the instructions followed by the thunks
+	///
+	/// # Errors
+	///
+	/// Fails if the instructions couldn't be encoded
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Decoded instructions. Branch targets and `RIP`-relative memory operands use their original IPs.
+	/// * `rip`: Base IP of all encoded instructions
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn rewrite(&self, instructions: &[Instruction], rip: u64) -> Result<IndirectBranchRewriterResult, IcedError> {
+		let (load_code, call_code, jmp_code) = if self.bitness == 64 {
+			(Code::Mov_r64_rm64, Code::Call_rel32_64, Code::Jmp_rel32_64)
+		} else {
+			(Code::Mov_r32_rm32, Code::Call_rel32_32, Code::Jmp_rel32_32)
+		};
+
+		// The new instructions need IPs that aren't used by the input instructions or referenced by them
+		let mut next_ip = instructions
+			.iter()
+			.map(|instruction| {
+				let ip_rel = if instruction.is_ip_rel_memory_operand() { instruction.ip_rel_memory_address() } else { 0 };
+				instruction.next_ip().max(instruction.near_branch_target()).max(ip_rel)
+			})
+			.max()
+			.unwrap_or_default();
+		let mut create_ip = || {
+			next_ip = next_ip.checked_add(1).ok_or_else(|| IcedError::new("There are no free IPs"))?;
+			Ok::<u64, IcedError>(next_ip)
+		};
+
+		let mut new_instructions = Vec::with_capacity(instructions.len());
+		let mut first_indexes = Vec::with_capacity(instructions.len());
+		// Target register, NOTRACK prefix and IP of the thunk
+		let mut thunks: Vec<(Register, bool, u64)> = Vec::new();
+		let mut rewritten_branches = 0;
+		for instruction in instructions {
+			first_indexes.push(new_instructions.len());
+			let is_call = match instruction.flow_control() {
+				FlowControl::IndirectCall => true,
+				FlowControl::IndirectBranch => false,
+				_ => {
+					new_instructions.push(*instruction);
+					continue;
+				}
+			};
+			if !matches!(
+				(self.bitness, instruction.code()),
+				(64, Code::Call_rm64) | (64, Code::Jmp_rm64) | (32, Code::Call_rm32) | (32, Code::Jmp_rm32)
+			) {
+				new_instructions.push(*instruction);
+				continue;
+			}
+
+			let (register, ip) = if instruction.op0_kind() == OpKind::Register {
+				(instruction.op0_register(), instruction.ip())
+			} else {
+				let mut load = *instruction;
+				load.set_code(load_code);
+				load.set_op0_kind(OpKind::Register);
+				load.set_op0_register(self.scratch_register);
+				load.set_op1_kind(OpKind::Memory);
+				new_instructions.push(load);
+				(self.scratch_register, create_ip()?)
+			};
+			// Only LFENCE thunks use an indirect branch
+			let notrack = self.mitigation == IndirectBranchMitigation::Lfence && instruction.has_notrack_prefix();
+			let thunk_ip = match thunks.iter().find(|&&(reg, nt, _)| reg == register && nt == notrack) {
+				Some(&(_, _, thunk_ip)) => thunk_ip,
+				None => {
+					let thunk_ip = create_ip()?;
+					thunks.push((register, notrack, thunk_ip));
+					thunk_ip
+				}
+			};
+			let mut branch = Instruction::with_branch(if is_call { call_code } else { jmp_code }, thunk_ip)?;
+			branch.set_ip(ip);
+			new_instructions.push(branch);
+			rewritten_branches += 1;
+		}
+
+		let mut directives = Vec::new();
+		if !thunks.is_empty() {
+			let mut align = Instruction::with(Code::Zero_bytes);
+			align.set_ip(create_ip()?);
+			directives.push((new_instructions.len(), BlockDirective::Align(16)));
+			new_instructions.push(align);
+		}
+		for &(register, notrack, thunk_ip) in &thunks {
+			let mut thunk = self.create_thunk(register, notrack)?;
+			// Labels are IPs in the CodeAssembler's instructions so they must be updated
+			let mut label_ips = Vec::new();
+			for (i, instruction) in thunk.iter_mut().enumerate() {
+				let ip = if i == 0 { thunk_ip } else { create_ip()? };
+				if instruction.ip() != 0 {
+					label_ips.push((instruction.ip(), ip));
+				}
+				instruction.set_ip(ip);
+			}
+			for instruction in &mut thunk {
+				if let Some(&(_, ip)) = label_ips.iter().find(|&&(label, _)| label == instruction.near_branch_target()) {
+					match instruction.op0_kind() {
+						OpKind::NearBranch64 => instruction.set_near_branch64(ip),
+						OpKind::NearBranch32 => instruction.set_near_branch32(ip as u32),
+						_ => {}
+					}
+				}
+			}
+			new_instructions.extend(thunk);
+		}
+
+		let block = InstructionBlock::with_directives(&new_instructions, rip, &directives);
+		let result = BlockEncoder::encode(self.bitness, block, self.block_encoder_options | BlockEncoderOptions::RETURN_NEW_INSTRUCTION_OFFSETS)?;
+		let new_instruction_offsets = first_indexes.iter().map(|&index| result.new_instruction_offsets[index]).collect();
+		Ok(IndirectBranchRewriterResult { rip: result.rip, code_buffer: result.code_buffer, new_instruction_offsets, rewritten_branches })
+	}
+
+	fn create_thunk(&self, register: Register, notrack: bool) -> Result<Vec<Instruction>, IcedError> {
+		let mut a = CodeAssembler::new(self.bitness)?;
+		match self.mitigation {
+			IndirectBranchMitigation::Retpoline => {
+				// call setup ; capture: pause ; lfence ; jmp capture ; setup: mov [rsp],reg ; ret
+				let mut capture = a.create_label();
+				let mut setup = a.create_label();
+				a.call(setup)?;
+				a.set_label(&mut capture)?;
+				a.pause()?;
+				a.lfence()?;
+				a.jmp(capture)?;
+				a.set_label(&mut setup)?;
+				if self.bitness == 64 {
+					a.mov(qword_ptr(rsp), AsmRegister64::new(register))?;
+				} else {
+					a.mov(dword_ptr(esp), AsmRegister32::new(register))?;
+				}
+				a.ret()?;
+			}
+			IndirectBranchMitigation::Lfence => {
+				a.lfence()?;
+				if notrack {
+					let _ = a.notrack();
+				}
+				if self.bitness == 64 {
+					a.jmp(AsmRegister64::new(register))?;
+				} else {
+					a.jmp(AsmRegister32::new(register))?;
+				}
+			}
+		}
+		Ok(a.take_instructions())
+	}
+}
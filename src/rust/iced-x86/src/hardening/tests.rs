@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::hardening::*;
+use crate::*;
+use alloc::vec::Vec;
+
+const IP: u64 = 0x1000;
+
+fn decode(bitness: u32, ip: u64, bytes: &[u8]) -> Vec<Instruction> {
+	let instructions: Vec<Instruction> = Decoder::with_ip(bitness, bytes, ip, DecoderOptions::NONE).into_iter().collect();
+	assert!(instructions.iter().all(|instruction| !instruction.is_invalid()));
+	instructions
+}
+
+fn rewrite(bitness: u32, mitigation: IndirectBranchMitigation, bytes: &[u8]) -> (IndirectBranchRewriterResult, Vec<Instruction>) {
+	let rewriter = IndirectBranchRewriter::new(bitness, mitigation).unwrap();
+	let result = rewriter.rewrite(&decode(bitness, IP, bytes), IP).unwrap();
+	// Skip the alignment NOPs before the thunks
+	let new_instructions =
+		decode(bitness, IP, &result.code_buffer).into_iter().filter(|instruction| instruction.mnemonic() != Mnemonic::Nop).collect();
+	(result, new_instructions)
+}
+
+fn codes(instructions: &[Instruction]) -> Vec<Code> {
+	instructions.iter().map(Instruction::code).collect()
+}
+
+#[test]
+fn invalid_arguments() {
+	assert!(IndirectBranchRewriter::new(16, IndirectBranchMitigation::Retpoline).is_err());
+	assert!(IndirectBranchRewriter::new(0, IndirectBranchMitigation::Retpoline).is_err());
+
+	let mut rewriter = IndirectBranchRewriter::new(64, IndirectBranchMitigation::Retpoline).unwrap();
+	assert_eq!(rewriter.scratch_register(), Register::R11);
+	assert!(rewriter.set_scratch_register(Register::RSP).is_err());
+	assert!(rewriter.set_scratch_register(Register::EAX).is_err());
+	assert!(rewriter.set_scratch_register(Register::R10).is_ok());
+	assert_eq!(rewriter.scratch_register(), Register::R10);
+
+	let mut rewriter = IndirectBranchRewriter::new(32, IndirectBranchMitigation::Lfence).unwrap();
+	assert_eq!(rewriter.scratch_register(), Register::EAX);
+	assert!(rewriter.set_scratch_register(Register::RAX).is_err());
+	assert!(rewriter.set_scratch_register(Register::ESP).is_err());
+	assert!(rewriter.set_scratch_register(Register::EDX).is_ok());
+}
+
+#[test]
+fn retpoline_64() {
+	// call rax ; ret
+	let (result, instrs) = rewrite(64, IndirectBranchMitigation::Retpoline, b"\xFF\xD0\xC3");
+	assert_eq!(result.rewritten_branches, 1);
+	assert_eq!(result.new_instruction_offsets, [0, 5]);
+	assert_eq!(
+		codes(&instrs),
+		[Code::Call_rel32_64, Code::Retnq, Code::Call_rel32_64, Code::Pause, Code::Lfence, Code::Jmp_rel8_64, Code::Mov_rm64_r64, Code::Retnq]
+	);
+	// The thunk is aligned
+	assert_eq!(instrs[0].near_branch_target(), IP + 0x10);
+	assert_eq!(instrs[2].ip(), IP + 0x10);
+	assert_eq!(instrs[2].near_branch_target(), instrs[6].ip());
+	assert_eq!(instrs[5].near_branch_target(), instrs[3].ip());
+	assert_eq!(instrs[6].memory_base(), Register::RSP);
+	assert_eq!(instrs[6].op1_register(), Register::RAX);
+}
+
+#[test]
+fn retpoline_32() {
+	// jmp ecx
+	let (result, instrs) = rewrite(32, IndirectBranchMitigation::Retpoline, b"\xFF\xE1");
+	assert_eq!(result.rewritten_branches, 1);
+	assert_eq!(
+		codes(&instrs),
+		[Code::Jmp_rel8_32, Code::Call_rel32_32, Code::Pause, Code::Lfence, Code::Jmp_rel8_32, Code::Mov_rm32_r32, Code::Retnd]
+	);
+	assert_eq!(instrs[0].near_branch_target(), instrs[1].ip());
+	assert_eq!(instrs[5].memory_base(), Register::ESP);
+	assert_eq!(instrs[5].op1_register(), Register::ECX);
+}
+
+#[test]
+fn memory_operand_uses_scratch_register() {
+	// call qword ptr [rbx+8] ; jmp qword ptr [rcx]
+	let (result, instrs) = rewrite(64, IndirectBranchMitigation::Lfence, b"\xFF\x53\x08\xFF\x21");
+	assert_eq!(result.rewritten_branches, 2);
+	assert_eq!(codes(&instrs), [Code::Mov_r64_rm64, Code::Call_rel32_64, Code::Mov_r64_rm64, Code::Jmp_rel8_64, Code::Lfence, Code::Jmp_rm64]);
+	assert_eq!(result.new_instruction_offsets, [0, 9]);
+	assert_eq!(instrs[0].op0_register(), Register::R11);
+	assert_eq!(instrs[0].memory_base(), Register::RBX);
+	assert_eq!(instrs[0].memory_displacement64(), 8);
+	assert_eq!(instrs[2].memory_base(), Register::RCX);
+	// Both branches use the same thunk
+	assert_eq!(instrs[1].near_branch_target(), instrs[4].ip());
+	assert_eq!(instrs[3].near_branch_target(), instrs[4].ip());
+	assert_eq!(instrs[5].op0_register(), Register::R11);
+}
+
+#[test]
+fn ip_rel_memory_operand() {
+	// jmp qword ptr [rip+10h]
+	let (_, instrs) = rewrite(64, IndirectBranchMitigation::Lfence, b"\xFF\x25\x10\x00\x00\x00");
+	assert_eq!(instrs[0].code(), Code::Mov_r64_rm64);
+	assert!(instrs[0].is_ip_rel_memory_operand());
+	assert_eq!(instrs[0].ip_rel_memory_address(), 0x1016);
+}
+
+#[test]
+fn branch_targets_are_updated() {
+	// 0x1000: call rax
+	// 0x1002: jne 1000h
+	let (result, instrs) = rewrite(64, IndirectBranchMitigation::Lfence, b"\xFF\xD0\x75\xFC");
+	assert_eq!(result.new_instruction_offsets, [0, 5]);
+	assert_eq!(instrs[1].code(), Code::Jne_rel8_64);
+	assert_eq!(instrs[1].near_branch_target(), IP);
+}
+
+#[test]
+fn notrack_uses_notrack_thunk() {
+	// notrack jmp rax ; jmp rax
+	let (result, instrs) = rewrite(64, IndirectBranchMitigation::Lfence, b"\x3E\xFF\xE0\xFF\xE0");
+	assert_eq!(result.rewritten_branches, 2);
+	assert_eq!(codes(&instrs), [Code::Jmp_rel8_64, Code::Jmp_rel8_64, Code::Lfence, Code::Jmp_rm64, Code::Lfence, Code::Jmp_rm64]);
+	assert_eq!(instrs[0].near_branch_target(), instrs[2].ip());
+	assert_eq!(instrs[1].near_branch_target(), instrs[4].ip());
+	assert_eq!(instrs[3].segment_prefix(), Register::DS);
+	assert_eq!(instrs[5].segment_prefix(), Register::None);
+}
+
+#[test]
+fn other_branches_are_not_rewritten() {
+	// jmp fword ptr [rax] ; call 1000h ; ret
+	let bytes = b"\xFF\x28\xE8\xF9\xFF\xFF\xFF\xC3";
+	let (result, _) = rewrite(64, IndirectBranchMitigation::Retpoline, bytes);
+	assert_eq!(result.rewritten_branches, 0);
+	assert_eq!(result.code_buffer, bytes);
+	assert_eq!(result.new_instruction_offsets, [0, 2, 7]);
+}
+
+#[test]
+fn block_encoder_options_are_used() {
+	// 0x101E: call rax
+	let instructions = decode(64, 0x101E, b"\xFF\xD0");
+	let mut rewriter = IndirectBranchRewriter::new(64, IndirectBranchMitigation::Lfence).unwrap();
+	rewriter.set_block_encoder_options(BlockEncoderOptions::JCC_ERRATUM);
+	let result = rewriter.rewrite(&instructions, 0x101E).unwrap();
+	assert_eq!(result.new_instruction_offsets, [2]);
+}
@@ -0,0 +1,418 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::iced_constants::IcedConstants;
+use crate::*;
+use core::fmt;
+
+const WORDS: usize = (IcedConstants::CPUID_FEATURE_ENUM_COUNT + 31) / 32;
+
+/// A set of [`CpuidFeature`]s supported by a CPU, eg. all features supported by Ivy Bridge or by `x86-64-v3` CPUs.
+///
+/// It can be passed to a [`CpuProfileDecoder`] to decode instructions as a CPU with these features would.
+///
+/// [`CpuidFeature`]: enum.CpuidFeature.html
+/// [`CpuProfileDecoder`]: struct.CpuProfileDecoder.html
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::*;
+///
+/// let profile = CpuFeatureProfile::from_name("x86-64-v2").unwrap();
+/// assert!(profile.contains(CpuidFeature::SSE4_2));
+/// assert!(!profile.contains(CpuidFeature::AVX2));
+///
+/// // vpaddd ymm1,ymm2,ymm3
+/// let instr = Decoder::new(64, b"\xC5\xED\xFE\xCB", DecoderOptions::NONE).decode();
+/// assert!(!profile.supports(&instr));
+/// assert!(CpuFeatureProfile::from_name("haswell").unwrap().supports(&instr));
+/// ```
+#[derive(Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct CpuFeatureProfile {
+	bits: [u32; WORDS],
+}
+
+impl CpuFeatureProfile {
+	/// Creates an empty profile
+	#[must_use]
+	#[inline]
+	pub const fn new() -> Self {
+		Self { bits: [0; WORDS] }
+	}
+
+	/// Creates a profile with all features
+	#[must_use]
+	#[inline]
+	pub fn all() -> Self {
+		CpuidFeature::values().collect()
+	}
+
+	/// Creates a profile with the features in `features`
+	///
+	/// # Arguments
+	///
+	/// * `features`: Supported features
+	#[must_use]
+	#[inline]
+	pub fn with_features(features: &[CpuidFeature]) -> Self {
+		features.iter().copied().collect()
+	}
+
+	/// Creates a profile from a name. These are the same names used by GCC/Clang's `-march=` option:
+	///
+	/// - `x86-64`, `x86-64-v2`, `x86-64-v3`, `x86-64-v4` (`x86-64-v1` is an alias of `x86-64`)
+	/// - Intel: `nehalem`, `westmere`, `sandybridge`, `ivybridge`, `haswell`, `broadwell`, `skylake`,
+	///   `skylake-avx512`, `icelake-client`, `icelake-server`, `alderlake`, `sapphirerapids`
+	/// - AMD: `znver1`, `znver2`, `znver3`, `znver4`
+	///
+	/// Privileged instructions that any CPU of that generation supports (eg. `VMX`/`SVM`) are included.
+	///
+	/// # Arguments
+	///
+	/// * `name`: Name of profile (case insensitive)
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn from_name(name: &str) -> Option<Self> {
+		PRESETS.iter().find(|&&(preset_name, _)| preset_name.eq_ignore_ascii_case(name)).map(|&(_, groups)| {
+			let mut profile = Self::new();
+			for &group in groups {
+				profile.add_all(group);
+			}
+			profile
+		})
+	}
+
+	/// Gets all names that can be passed to [`from_name()`]
+	///
+	/// [`from_name()`]: #method.from_name
+	#[inline]
+	pub fn names() -> impl Iterator<Item = &'static str> {
+		PRESETS.iter().map(|&(name, _)| name)
+	}
+
+	/// Baseline `x86-64` (`x86-64-v1`) profile
+	#[must_use]
+	#[inline]
+	pub fn x86_64_v1() -> Self {
+		Self::from_name("x86-64-v1").unwrap_or_default()
+	}
+
+	/// `x86-64-v2` profile: `x86-64-v1` + `CMPXCHG16B`, `POPCNT`, `SSE3`, `SSSE3`, `SSE4.1`, `SSE4.2`
+	#[must_use]
+	#[inline]
+	pub fn x86_64_v2() -> Self {
+		Self::from_name("x86-64-v2").unwrap_or_default()
+	}
+
+	/// `x86-64-v3` profile: `x86-64-v2` + `AVX`, `AVX2`, `BMI1`, `BMI2`, `F16C`, `FMA`, `LZCNT`, `MOVBE`, `XSAVE`
+	#[must_use]
+	#[inline]
+	pub fn x86_64_v3() -> Self {
+		Self::from_name("x86-64-v3").unwrap_or_default()
+	}
+
+	/// `x86-64-v4` profile: `x86-64-v3` + `AVX512F`, `AVX512BW`, `AVX512CD`, `AVX512DQ`, `AVX512VL`
+	#[must_use]
+	#[inline]
+	pub fn x86_64_v4() -> Self {
+		Self::from_name("x86-64-v4").unwrap_or_default()
+	}
+
+	/// Checks if the profile has no features
+	#[must_use]
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.bits.iter().all(|&b| b == 0)
+	}
+
+	/// Gets the number of features
+	#[must_use]
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.bits.iter().map(|&b| b.count_ones() as usize).sum()
+	}
+
+	/// Checks if `feature` is supported
+	///
+	/// # Arguments
+	///
+	/// * `feature`: Feature
+	#[must_use]
+	#[inline]
+	pub const fn contains(&self, feature: CpuidFeature) -> bool {
+		let index = feature as usize;
+		(self.bits[index / 32] & (1 << (index % 32))) != 0
+	}
+
+	/// Checks if all features in `features` are supported
+	///
+	/// # Arguments
+	///
+	/// * `features`: Features
+	#[must_use]
+	#[inline]
+	pub fn contains_all(&self, features: &[CpuidFeature]) -> bool {
+		features.iter().all(|&feature| self.contains(feature))
+	}
+
+	/// Checks if the instruction can be executed by a CPU with these features.
+	///
+	/// CET instructions in the reserved `NOP` space (`ENDBR32`, `ENDBR64`, `RDSSPD`, `RDSSPQ`) are `NOP`s on CPUs
+	/// without CET so they're supported if the profile has [`CpuidFeature::MULTIBYTENOP`].
+	///
+	/// [`CpuidFeature::MULTIBYTENOP`]: enum.CpuidFeature.html#variant.MULTIBYTENOP
+	///
+	/// # Arguments
+	///
+	/// * `instruction`: Instruction
+	#[must_use]
+	#[inline]
+	pub fn supports(&self, instruction: &Instruction) -> bool {
+		if is_cet_nop(instruction.code()) && self.contains(CpuidFeature::MULTIBYTENOP) {
+			return true;
+		}
+		self.contains_all(instruction.cpuid_features())
+	}
+
+	/// Adds a feature
+	///
+	/// # Arguments
+	///
+	/// * `feature`: Feature
+	#[inline]
+	pub fn add(&mut self, feature: CpuidFeature) {
+		let index = feature as usize;
+		self.bits[index / 32] |= 1 << (index % 32);
+	}
+
+	/// Adds all features in `features`
+	///
+	/// # Arguments
+	///
+	/// * `features`: Features
+	#[inline]
+	pub fn add_all(&mut self, features: &[CpuidFeature]) {
+		for &feature in features {
+			self.add(feature);
+		}
+	}
+
+	/// Removes a feature
+	///
+	/// # Arguments
+	///
+	/// * `feature`: Feature
+	#[inline]
+	pub fn remove(&mut self, feature: CpuidFeature) {
+		let index = feature as usize;
+		self.bits[index / 32] &= !(1 << (index % 32));
+	}
+
+	/// Adds all features in `other`
+	///
+	/// # Arguments
+	///
+	/// * `other`: Other profile
+	#[inline]
+	pub fn union_with(&mut self, other: &Self) {
+		for (a, &b) in self.bits.iter_mut().zip(other.bits.iter()) {
+			*a |= b;
+		}
+	}
+
+	/// Checks if all features in `other` are also in this profile
+	///
+	/// # Arguments
+	///
+	/// * `other`: Other profile
+	#[must_use]
+	#[inline]
+	pub fn is_superset(&self, other: &Self) -> bool {
+		self.bits.iter().zip(other.bits.iter()).all(|(&a, &b)| (a & b) == b)
+	}
+
+	/// Iterates over all features
+	#[inline]
+	pub fn iter(&self) -> impl Iterator<Item = CpuidFeature> + '_ {
+		CpuidFeature::values().filter(move |&feature| self.contains(feature))
+	}
+}
+
+impl FromIterator<CpuidFeature> for CpuFeatureProfile {
+	#[inline]
+	fn from_iter<T: IntoIterator<Item = CpuidFeature>>(iter: T) -> Self {
+		let mut profile = Self::new();
+		for feature in iter {
+			profile.add(feature);
+		}
+		profile
+	}
+}
+
+impl Extend<CpuidFeature> for CpuFeatureProfile {
+	#[inline]
+	fn extend<T: IntoIterator<Item = CpuidFeature>>(&mut self, iter: T) {
+		for feature in iter {
+			self.add(feature);
+		}
+	}
+}
+
+impl fmt::Debug for CpuFeatureProfile {
+	#[allow(clippy::missing_inline_in_public_items)]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_set().entries(self.iter()).finish()
+	}
+}
+
+// Features that all 64-bit CPUs support
+#[rustfmt::skip]
+// CET instructions that are NOPs on CPUs without CET
+#[must_use]
+#[inline]
+fn is_cet_nop(code: Code) -> bool {
+	matches!(code, Code::Endbr32 | Code::Endbr64 | Code::Rdsspd_r32 | Code::Rdsspq_r64)
+}
+
+static X86_64: &[CpuidFeature] = &[
+	CpuidFeature::INTEL8086,
+	CpuidFeature::INTEL186,
+	CpuidFeature::INTEL286,
+	CpuidFeature::INTEL386,
+	CpuidFeature::INTEL486,
+	CpuidFeature::X64,
+	CpuidFeature::FPU,
+	CpuidFeature::FPU287,
+	CpuidFeature::FPU387,
+	CpuidFeature::CPUID,
+	CpuidFeature::TSC,
+	CpuidFeature::MSR,
+	CpuidFeature::CX8,
+	CpuidFeature::CMOV,
+	CpuidFeature::MMX,
+	CpuidFeature::SSE,
+	CpuidFeature::SSE2,
+	CpuidFeature::FXSR,
+	CpuidFeature::SYSCALL,
+	CpuidFeature::SEP,
+	CpuidFeature::MULTIBYTENOP,
+	CpuidFeature::PAUSE,
+	CpuidFeature::CLFSH,
+	CpuidFeature::RDPMC,
+	CpuidFeature::SMM,
+];
+#[rustfmt::skip]
+static X86_64_V2: &[CpuidFeature] = &[
+	CpuidFeature::CMPXCHG16B, CpuidFeature::POPCNT, CpuidFeature::SSE3, CpuidFeature::SSSE3, CpuidFeature::SSE4_1, CpuidFeature::SSE4_2,
+];
+#[rustfmt::skip]
+static X86_64_V3: &[CpuidFeature] = &[
+	CpuidFeature::AVX, CpuidFeature::AVX2, CpuidFeature::BMI1, CpuidFeature::BMI2, CpuidFeature::F16C, CpuidFeature::FMA,
+	CpuidFeature::LZCNT, CpuidFeature::MOVBE, CpuidFeature::XSAVE,
+];
+#[rustfmt::skip]
+static X86_64_V4: &[CpuidFeature] = &[
+	CpuidFeature::AVX512F, CpuidFeature::AVX512BW, CpuidFeature::AVX512CD, CpuidFeature::AVX512DQ, CpuidFeature::AVX512VL,
+];
+
+#[rustfmt::skip]
+static NEHALEM: &[CpuidFeature] = &[
+	CpuidFeature::RDTSCP, CpuidFeature::MONITOR, CpuidFeature::VMX, CpuidFeature::INVEPT, CpuidFeature::INVVPID, CpuidFeature::SMX,
+];
+static WESTMERE: &[CpuidFeature] = &[CpuidFeature::AES, CpuidFeature::PCLMULQDQ];
+static SANDY_BRIDGE: &[CpuidFeature] = &[CpuidFeature::AVX, CpuidFeature::XSAVE, CpuidFeature::XSAVEOPT];
+static IVY_BRIDGE: &[CpuidFeature] = &[CpuidFeature::F16C, CpuidFeature::RDRAND, CpuidFeature::FSGSBASE];
+#[rustfmt::skip]
+static HASWELL: &[CpuidFeature] = &[
+	CpuidFeature::AVX2, CpuidFeature::BMI1, CpuidFeature::BMI2, CpuidFeature::FMA, CpuidFeature::LZCNT, CpuidFeature::MOVBE,
+	CpuidFeature::INVPCID, CpuidFeature::HLE, CpuidFeature::RTM,
+];
+static BROADWELL: &[CpuidFeature] = &[CpuidFeature::ADX, CpuidFeature::RDSEED, CpuidFeature::SMAP, CpuidFeature::PREFETCHW];
+#[rustfmt::skip]
+static SKYLAKE: &[CpuidFeature] = &[
+	CpuidFeature::CLFLUSHOPT, CpuidFeature::XSAVEC, CpuidFeature::XSAVES, CpuidFeature::SGX1, CpuidFeature::MPX,
+];
+#[rustfmt::skip]
+static SKYLAKE_AVX512: &[CpuidFeature] = &[
+	CpuidFeature::AVX512F, CpuidFeature::AVX512CD, CpuidFeature::AVX512BW, CpuidFeature::AVX512DQ, CpuidFeature::AVX512VL,
+	CpuidFeature::CLWB, CpuidFeature::PKU,
+];
+#[rustfmt::skip]
+static ICE_LAKE: &[CpuidFeature] = &[
+	CpuidFeature::AVX512_IFMA, CpuidFeature::AVX512_VBMI, CpuidFeature::AVX512_VBMI2, CpuidFeature::AVX512_VNNI,
+	CpuidFeature::AVX512_BITALG, CpuidFeature::AVX512_VPOPCNTDQ, CpuidFeature::GFNI, CpuidFeature::VAES, CpuidFeature::VPCLMULQDQ,
+	CpuidFeature::RDPID, CpuidFeature::SHA,
+];
+static ICE_LAKE_SERVER: &[CpuidFeature] = &[CpuidFeature::PCONFIG, CpuidFeature::WBNOINVD];
+// Alder Lake has no AVX-512, TSX, SGX or MPX so it's not based on Skylake/Ice Lake
+#[rustfmt::skip]
+static ALDER_LAKE: &[CpuidFeature] = &[
+	CpuidFeature::RDTSCP, CpuidFeature::MONITOR, CpuidFeature::VMX, CpuidFeature::INVEPT, CpuidFeature::INVVPID, CpuidFeature::SMX,
+	CpuidFeature::AES, CpuidFeature::PCLMULQDQ, CpuidFeature::XSAVEOPT, CpuidFeature::RDRAND, CpuidFeature::FSGSBASE,
+	CpuidFeature::INVPCID, CpuidFeature::ADX, CpuidFeature::RDSEED, CpuidFeature::SMAP, CpuidFeature::PREFETCHW,
+	CpuidFeature::CLFLUSHOPT, CpuidFeature::XSAVEC, CpuidFeature::XSAVES, CpuidFeature::CLWB, CpuidFeature::PKU, CpuidFeature::GFNI,
+	CpuidFeature::VAES, CpuidFeature::VPCLMULQDQ, CpuidFeature::RDPID, CpuidFeature::SHA, CpuidFeature::AVX_VNNI,
+	CpuidFeature::MOVDIRI, CpuidFeature::MOVDIR64B, CpuidFeature::WAITPKG, CpuidFeature::SERIALIZE, CpuidFeature::PTWRITE,
+	CpuidFeature::HRESET, CpuidFeature::KL, CpuidFeature::AESKLE, CpuidFeature::WIDE_KL, CpuidFeature::CET_SS, CpuidFeature::CET_IBT,
+	CpuidFeature::PCONFIG, CpuidFeature::WBNOINVD,
+];
+#[rustfmt::skip]
+static SAPPHIRE_RAPIDS: &[CpuidFeature] = &[
+	CpuidFeature::AVX512_BF16, CpuidFeature::AVX512_FP16, CpuidFeature::AMX_TILE, CpuidFeature::AMX_INT8, CpuidFeature::AMX_BF16,
+	CpuidFeature::AVX_VNNI, CpuidFeature::SERIALIZE, CpuidFeature::TSXLDTRK, CpuidFeature::ENQCMD, CpuidFeature::MOVDIRI,
+	CpuidFeature::MOVDIR64B, CpuidFeature::CLDEMOTE, CpuidFeature::WAITPKG, CpuidFeature::UINTR, CpuidFeature::PTWRITE,
+	CpuidFeature::CET_SS, CpuidFeature::CET_IBT,
+];
+// Sapphire Rapids doesn't support MPX
+#[rustfmt::skip]
+static SAPPHIRE_RAPIDS_BASE: &[CpuidFeature] = &[
+	CpuidFeature::RDTSCP, CpuidFeature::MONITOR, CpuidFeature::VMX, CpuidFeature::INVEPT, CpuidFeature::INVVPID, CpuidFeature::SMX,
+	CpuidFeature::AES, CpuidFeature::PCLMULQDQ, CpuidFeature::XSAVEOPT, CpuidFeature::RDRAND, CpuidFeature::FSGSBASE,
+	CpuidFeature::INVPCID, CpuidFeature::HLE, CpuidFeature::RTM, CpuidFeature::ADX, CpuidFeature::RDSEED, CpuidFeature::SMAP,
+	CpuidFeature::PREFETCHW, CpuidFeature::CLFLUSHOPT, CpuidFeature::XSAVEC, CpuidFeature::XSAVES, CpuidFeature::SGX1,
+];
+#[rustfmt::skip]
+static ZEN: &[CpuidFeature] = &[
+	CpuidFeature::ADX, CpuidFeature::AES, CpuidFeature::PCLMULQDQ, CpuidFeature::RDRAND, CpuidFeature::RDSEED, CpuidFeature::SHA,
+	CpuidFeature::SMAP, CpuidFeature::CLFLUSHOPT, CpuidFeature::XSAVEC, CpuidFeature::XSAVES, CpuidFeature::XSAVEOPT,
+	CpuidFeature::FSGSBASE, CpuidFeature::SSE4A, CpuidFeature::MONITOR, CpuidFeature::MONITORX, CpuidFeature::CLZERO,
+	CpuidFeature::PREFETCHW, CpuidFeature::RDTSCP, CpuidFeature::SVM, CpuidFeature::SKINIT,
+];
+static ZEN2: &[CpuidFeature] = &[CpuidFeature::CLWB, CpuidFeature::RDPID, CpuidFeature::WBNOINVD, CpuidFeature::RDPRU];
+#[rustfmt::skip]
+static ZEN3: &[CpuidFeature] = &[
+	CpuidFeature::PKU, CpuidFeature::INVPCID, CpuidFeature::VAES, CpuidFeature::VPCLMULQDQ, CpuidFeature::SEV_SNP,
+	CpuidFeature::INVLPGB, CpuidFeature::CET_SS,
+];
+#[rustfmt::skip]
+static ZEN4: &[CpuidFeature] = &[
+	CpuidFeature::AVX512_IFMA, CpuidFeature::AVX512_VBMI, CpuidFeature::AVX512_VBMI2, CpuidFeature::AVX512_VNNI,
+	CpuidFeature::AVX512_BITALG, CpuidFeature::AVX512_VPOPCNTDQ, CpuidFeature::AVX512_BF16, CpuidFeature::GFNI,
+];
+
+// Name (GCC/Clang `-march=` name) and the features
+#[rustfmt::skip]
+static PRESETS: &[(&str, &[&[CpuidFeature]])] = &[
+	("x86-64", &[X86_64]),
+	("x86-64-v1", &[X86_64]),
+	("x86-64-v2", &[X86_64, X86_64_V2]),
+	("x86-64-v3", &[X86_64, X86_64_V2, X86_64_V3]),
+	("x86-64-v4", &[X86_64, X86_64_V2, X86_64_V3, X86_64_V4]),
+	("nehalem", &[X86_64, X86_64_V2, NEHALEM]),
+	("westmere", &[X86_64, X86_64_V2, NEHALEM, WESTMERE]),
+	("sandybridge", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE]),
+	("ivybridge", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE, IVY_BRIDGE]),
+	("haswell", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE, IVY_BRIDGE, HASWELL]),
+	("broadwell", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE, IVY_BRIDGE, HASWELL, BROADWELL]),
+	("skylake", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE, IVY_BRIDGE, HASWELL, BROADWELL, SKYLAKE]),
+	("skylake-avx512", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE, IVY_BRIDGE, HASWELL, BROADWELL, SKYLAKE, SKYLAKE_AVX512]),
+	("icelake-client", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE, IVY_BRIDGE, HASWELL, BROADWELL, SKYLAKE, SKYLAKE_AVX512, ICE_LAKE]),
+	("icelake-server", &[X86_64, X86_64_V2, NEHALEM, WESTMERE, SANDY_BRIDGE, IVY_BRIDGE, HASWELL, BROADWELL, SKYLAKE, SKYLAKE_AVX512, ICE_LAKE, ICE_LAKE_SERVER]),
+	("alderlake", &[X86_64, X86_64_V2, X86_64_V3, ALDER_LAKE]),
+	("sapphirerapids", &[X86_64, X86_64_V2, X86_64_V3, X86_64_V4, SAPPHIRE_RAPIDS_BASE, SKYLAKE_AVX512, ICE_LAKE, ICE_LAKE_SERVER, SAPPHIRE_RAPIDS]),
+	("znver1", &[X86_64, X86_64_V2, X86_64_V3, ZEN]),
+	("znver2", &[X86_64, X86_64_V2, X86_64_V3, ZEN, ZEN2]),
+	("znver3", &[X86_64, X86_64_V2, X86_64_V3, ZEN, ZEN2, ZEN3]),
+	("znver4", &[X86_64, X86_64_V2, X86_64_V3, X86_64_V4, ZEN, ZEN2, ZEN3, ZEN4]),
+];
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2018-present iced project and contributors
 
+mod cpu_profile;
 pub(crate) mod cpuid_table;
 pub(crate) mod enums;
 pub(crate) mod factory;
@@ -10,6 +11,7 @@ pub(crate) mod rflags_table;
 mod tests;
 
 use crate::iced_constants::IcedConstants;
+pub use crate::info::cpu_profile::*;
 pub use crate::info::factory::*;
 use crate::*;
 use alloc::vec::Vec;
@@ -487,6 +487,50 @@ impl Instruction {
 		}
 	}
 
+	/// `true` if it's a near indirect `CALL`/`JMP` with the `NOTRACK` prefix (`3E`). It's stored as a `DS` segment prefix.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::*;
+	///
+	/// // notrack jmp rax
+	/// // jmp rax
+	/// let bytes = b"\x3E\xFF\xE0\xFF\xE0";
+	/// let mut decoder = Decoder::new(64, bytes, DecoderOptions::NONE);
+	/// assert!(decoder.decode().has_notrack_prefix());
+	/// assert!(!decoder.decode().has_notrack_prefix());
+	/// ```
+	#[must_use]
+	#[inline]
+	pub fn has_notrack_prefix(&self) -> bool {
+		self.segment_prefix() == Register::DS && self.is_near_indirect_branch()
+	}
+
+	/// `true` if it's a near indirect `CALL`/`JMP` with the `NOTRACK` prefix (`3E`). It's stored as a `DS` segment prefix.
+	/// Other instructions can't have a `NOTRACK` prefix and aren't modified.
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_has_notrack_prefix(&mut self, new_value: bool) {
+		if !self.is_near_indirect_branch() {
+			return;
+		}
+		if new_value {
+			self.set_segment_prefix(Register::DS);
+		} else if self.segment_prefix() == Register::DS {
+			self.set_segment_prefix(Register::None);
+		}
+	}
+
+	#[must_use]
+	#[inline]
+	fn is_near_indirect_branch(&self) -> bool {
+		matches!(self.code(), Code::Call_rm16 | Code::Call_rm32 | Code::Call_rm64 | Code::Jmp_rm16 | Code::Jmp_rm32 | Code::Jmp_rm64)
+	}
+
 	/// Gets operand #0's kind if the operand exists (see [`op_count()`] and [`try_op_kind()`])
 	///
 	/// [`op_count()`]: #method.op_count
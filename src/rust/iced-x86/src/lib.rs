@@ -97,7 +97,10 @@ compile_error!("`std` or `no_std` feature must be defined");
 		feature = "intel",
 		feature = "masm",
 		feature = "nasm",
-		feature = "fast_fmt"
+		feature = "fast_fmt",
+		feature = "analysis",
+		feature = "perf_info",
+		feature = "peephole"
 	),
 	macro_use
 )]
@@ -126,6 +129,8 @@ mod iced_assert {
 	}
 }
 
+#[cfg(feature = "analysis")]
+pub mod analysis;
 #[cfg(all(feature = "encoder", feature = "block_encoder"))]
 mod block_enc;
 mod code;
@@ -142,6 +147,8 @@ mod encoder;
 mod enums;
 #[cfg(any(feature = "gas", feature = "intel", feature = "masm", feature = "nasm", feature = "fast_fmt"))]
 mod formatter;
+#[cfg(feature = "hardening")]
+pub mod hardening;
 pub(crate) mod iced_constants;
 mod iced_error;
 mod iced_features;
@@ -158,6 +165,10 @@ mod mnemonic;
 mod mnemonics;
 #[cfg(feature = "mvex")]
 mod mvex;
+#[cfg(feature = "peephole")]
+pub mod peephole;
+#[cfg(feature = "perf_info")]
+pub mod perf_info;
 mod register;
 #[cfg(test)]
 pub(crate) mod test;
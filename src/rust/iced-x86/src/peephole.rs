@@ -0,0 +1,507 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+//! Peephole optimizer that rewrites instructions into equivalent shorter or faster instructions
+//!
+//! This requires the `peephole` feature to use (not enabled by default). Add it to your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.iced-x86]
+//! version = "1.21.0"
+//! features = ["peephole"]
+//! ```
+//!
+//! The input is usually created by [`CodeAssembler::take_instructions()`] and the output can be encoded with a
+//! [`BlockEncoder`]. A rewrite that modifies `RFLAGS` is only used if the modified flags aren't read before they're
+//! written again.
+//!
+//! [`CodeAssembler::take_instructions()`]: ../code_asm/struct.CodeAssembler.html#method.take_instructions
+//! [`BlockEncoder`]: ../struct.BlockEncoder.html
+
+#[cfg(all(test, feature = "decoder", feature = "block_encoder"))]
+mod tests;
+
+use crate::{Code, FlowControl, IcedError, Instruction, MemoryOperand, Mnemonic, OpKind, Register, RflagsBits};
+use alloc::vec::Vec;
+
+/// [`PeepholeOptimizer`] options
+///
+/// [`PeepholeOptimizer`]: struct.PeepholeOptimizer.html
+#[allow(missing_copy_implementations)]
+#[allow(missing_debug_implementations)]
+pub struct PeepholeOptions;
+impl PeepholeOptions {
+	/// No option is set
+	pub const NONE: u32 = 0x0000_0000;
+	/// `MOV r64, imm` -> `MOV r32, imm` if the immediate is zero extended from 32 bits
+	pub const MOV_IMM32: u32 = 0x0000_0001;
+	/// `MOV r32, 0` / `MOV r64, 0` -> `XOR r32, r32` if the status flags are dead
+	pub const ZERO_IDIOM: u32 = 0x0000_0002;
+	/// `CMP reg, 0` -> `TEST reg, reg` if `AF` is dead
+	pub const TEST: u32 = 0x0000_0004;
+	/// `LEA reg, [reg2]` -> `MOV reg, reg2`, `LEA reg, [reg]` is removed and two adjacent `LEA`s are folded, eg.
+	/// `LEA RAX, [RBX+8]` + `LEA RAX, [RAX+8]` -> `LEA RAX, [RBX+10h]`
+	pub const LEA: u32 = 0x0000_0008;
+	/// Redundant moves are removed: `MOV reg, reg` (same register), `MOV A, B` + `MOV B, A` and `MOV A, B` + `MOV A, B`
+	pub const REDUNDANT_MOV: u32 = 0x0000_0010;
+	/// All rewrites
+	pub const ALL: u32 = 0x0000_001F;
+}
+
+// Flags that are assumed to be live if the next instruction is unknown
+const ALL_FLAGS: u32 = u32::MAX;
+// Flags written by `XOR`
+const STATUS_FLAGS: u32 = RflagsBits::OF | RflagsBits::SF | RflagsBits::ZF | RflagsBits::AF | RflagsBits::CF | RflagsBits::PF;
+
+/// Rewrites instructions into equivalent shorter or faster instructions
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::peephole::*;
+/// use iced_x86::*;
+///
+/// # fn main() -> Result<(), IcedError> {
+/// let mut instructions = vec![
+///     Instruction::with2(Code::Cmp_rm64_imm8, Register::RDX, 0)?,
+///     Instruction::with2(Code::Mov_r64_imm64, Register::RAX, 0u64)?,
+///     Instruction::with2(Code::Mov_rm64_imm32, Register::RCX, 0x1234)?,
+///     Instruction::with1(Code::Sete_rm8, Register::AL)?,
+///     Instruction::with(Code::Retnq),
+/// ];
+/// let optimizer = PeepholeOptimizer::new(64)?;
+/// assert_eq!(optimizer.optimize(&mut instructions), 3);
+/// assert_eq!(instructions[0].code(), Code::Test_rm64_r64);
+/// // ZF is read by SETE so it's not possible to use `XOR EAX,EAX`
+/// assert_eq!(instructions[1].code(), Code::Mov_r32_imm32);
+/// assert_eq!(instructions[1].op0_register(), Register::EAX);
+/// assert_eq!(instructions[2].code(), Code::Mov_r32_imm32);
+/// assert_eq!(instructions[2].op0_register(), Register::ECX);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct PeepholeOptimizer {
+	bitness: u32,
+	options: u32,
+}
+
+impl PeepholeOptimizer {
+	/// Creates an optimizer that uses all rewrites ([`PeepholeOptions::ALL`])
+	///
+	/// [`PeepholeOptions::ALL`]: struct.PeepholeOptions.html#associatedconstant.ALL
+	///
+	/// # Errors
+	///
+	/// Fails if `bitness` is not one of 16, 32, 64.
+	///
+	/// # Arguments
+	///
+	/// * `bitness`: 16, 32 or 64
+	#[inline]
+	pub fn new(bitness: u32) -> Result<Self, IcedError> {
+		match bitness {
+			16 | 32 | 64 => Ok(Self { bitness, options: PeepholeOptions::ALL }),
+			_ => Err(IcedError::new("Invalid bitness")),
+		}
+	}
+
+	/// Gets the enabled rewrites, see [`PeepholeOptions`]
+	///
+	/// [`PeepholeOptions`]: struct.PeepholeOptions.html
+	#[must_use]
+	#[inline]
+	pub fn options(&self) -> u32 {
+		self.options
+	}
+
+	/// Sets the enabled rewrites, see [`PeepholeOptions`]
+	///
+	/// [`PeepholeOptions`]: struct.PeepholeOptions.html
+	///
+	/// # Arguments
+	///
+	/// * `new_value`: New value
+	#[inline]
+	pub fn set_options(&mut self, new_value: u32) {
+		self.options = new_value;
+	}
+
+	/// Rewrites the instructions and returns the number of rewritten and removed instructions.
+	///
+	/// The flags are assumed to be live after the last instruction and after branches to targets outside of `instructions`.
+	/// Calls and returns are assumed to follow the ABI: the status flags aren't passed to or returned from a function.
+	///
+	/// An instruction is only removed if no branch or `RIP`-relative memory operand in `instructions` references its IP or if the
+	/// IP can be moved to the next instruction (eg. a [`CodeAssembler`] label). The IPs of all other instructions aren't changed.
+	///
+	/// [`CodeAssembler`]: ../code_asm/struct.CodeAssembler.html
+	///
+	/// # Arguments
+	///
+	/// * `instructions`: Instructions
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn optimize(&self, instructions: &mut Vec<Instruction>) -> usize {
+		let targets = get_targets(instructions);
+		let live_out = Self::live_flags(instructions, &targets);
+		let mut removed = vec![false; instructions.len()];
+		let mut count = 0;
+		for i in 0..instructions.len() {
+			if removed[i] || has_prefix(&instructions[i]) {
+				continue;
+			}
+			let next = if i + 1 < instructions.len()
+				&& !removed[i + 1]
+				&& !has_prefix(&instructions[i + 1])
+				&& !is_target(&targets, instructions[i + 1].ip())
+			{
+				Some(instructions[i + 1])
+			} else {
+				None
+			};
+			let rewrite = self.rewrite(&instructions[i], next.as_ref(), live_out[i]);
+			match rewrite {
+				Rewrite::None => {}
+				Rewrite::Replace(mut new_instruction) => {
+					new_instruction.set_ip(instructions[i].ip());
+					instructions[i] = new_instruction;
+					count += 1;
+				}
+				Rewrite::Remove => {
+					if Self::remove(instructions, &targets, i) {
+						removed[i] = true;
+						count += 1;
+					}
+				}
+				Rewrite::ReplaceAndRemoveNext(mut new_instruction) => {
+					new_instruction.set_ip(instructions[i].ip());
+					instructions[i] = new_instruction;
+					removed[i + 1] = true;
+					count += 2;
+				}
+				Rewrite::RemoveNext => {
+					removed[i + 1] = true;
+					count += 1;
+				}
+			}
+		}
+		if removed.iter().any(|&r| r) {
+			let mut index = 0;
+			instructions.retain(|_| {
+				index += 1;
+				!removed[index - 1]
+			});
+		}
+		count
+	}
+
+	// Removes an instruction if its IP isn't referenced or if it can be moved to the next instruction
+	fn remove(instructions: &mut [Instruction], targets: &[u64], index: usize) -> bool {
+		let ip = instructions[index].ip();
+		if !is_target(targets, ip) {
+			return true;
+		}
+		match instructions.get_mut(index + 1) {
+			Some(next) if !is_target(targets, next.ip()) => {
+				next.set_ip(ip);
+				true
+			}
+			_ => false,
+		}
+	}
+
+	// Gets the flags that are live after each instruction
+	fn live_flags(instructions: &[Instruction], targets: &[u64]) -> Vec<u32> {
+		// Indexes of all instructions that are branch targets, sorted by IP
+		let mut target_indexes: Vec<(u64, usize)> = instructions
+			.iter()
+			.enumerate()
+			.filter(|(_, instruction)| is_target(targets, instruction.ip()))
+			.map(|(i, instr)| (instr.ip(), i))
+			.collect();
+		target_indexes.sort_unstable();
+		let target_index =
+			|target: u64| -> Option<usize> { target_indexes.binary_search_by_key(&target, |&(ip, _)| ip).ok().map(|index| target_indexes[index].1) };
+
+		let count = instructions.len();
+		let mut live_in = vec![0u32; count + 1];
+		live_in[count] = ALL_FLAGS;
+		let mut live_out = vec![0u32; count];
+		loop {
+			let mut updated = false;
+			for i in (0..count).rev() {
+				let instruction = &instructions[i];
+				let branch_target = || target_index(instruction.near_branch_target()).map_or(ALL_FLAGS, |index| live_in[index]);
+				let (out, read, modified) = match instruction.flow_control() {
+					FlowControl::Next => (live_in[i + 1], instruction.rflags_read(), flags_modified(instruction)),
+					FlowControl::Call | FlowControl::IndirectCall => (live_in[i + 1], 0, STATUS_FLAGS),
+					FlowControl::Return => (0, 0, 0),
+					FlowControl::UnconditionalBranch => (branch_target(), instruction.rflags_read(), flags_modified(instruction)),
+					FlowControl::ConditionalBranch => (live_in[i + 1] | branch_target(), instruction.rflags_read(), flags_modified(instruction)),
+					_ => (ALL_FLAGS, ALL_FLAGS, 0),
+				};
+				let new_live_in = read | (out & !modified);
+				if live_out[i] != out || live_in[i] != new_live_in {
+					live_out[i] = out;
+					live_in[i] = new_live_in;
+					updated = true;
+				}
+			}
+			if !updated {
+				break;
+			}
+		}
+		live_out
+	}
+
+	fn rewrite(&self, instruction: &Instruction, next: Option<&Instruction>, live_out: u32) -> Rewrite {
+		let code = instruction.code();
+		let options = self.options;
+
+		// MOV r64,imm -> MOV r32,imm
+		let mut mov_imm = None;
+		if instruction.op_count() == 2 && instruction.op0_kind() == OpKind::Register {
+			let register = instruction.op0_register();
+			match code {
+				Code::Mov_r64_imm64 if instruction.immediate64() <= u32::MAX as u64 => {
+					mov_imm = Some((register.full_register32(), instruction.immediate64()))
+				}
+				Code::Mov_rm64_imm32 if instruction.immediate32to64() >= 0 => {
+					mov_imm = Some((register.full_register32(), instruction.immediate32to64() as u64))
+				}
+				Code::Mov_r32_imm32 | Code::Mov_rm32_imm32 => mov_imm = Some((register, instruction.immediate32() as u64)),
+				_ => {}
+			}
+		}
+		if let Some((register, value)) = mov_imm {
+			if (options & PeepholeOptions::ZERO_IDIOM) != 0 && value == 0 && (live_out & STATUS_FLAGS) == 0 {
+				return replace(Instruction::with2(Code::Xor_rm32_r32, register, register));
+			}
+			if (options & PeepholeOptions::MOV_IMM32) != 0 && code != Code::Mov_r32_imm32 {
+				return replace(Instruction::with2(Code::Mov_r32_imm32, register, value as u32));
+			}
+			return Rewrite::None;
+		}
+
+		// CMP reg,0 -> TEST reg,reg
+		if (options & PeepholeOptions::TEST) != 0
+			&& instruction.mnemonic() == Mnemonic::Cmp
+			&& instruction.op_count() == 2
+			&& instruction.op0_kind() == OpKind::Register
+			&& is_immediate(instruction.op1_kind())
+			&& instruction.immediate(1) == 0
+			&& (live_out & RflagsBits::AF) == 0
+		{
+			let register = instruction.op0_register();
+			let test = match register.size() {
+				1 => Code::Test_rm8_r8,
+				2 => Code::Test_rm16_r16,
+				4 => Code::Test_rm32_r32,
+				_ => Code::Test_rm64_r64,
+			};
+			return replace(Instruction::with2(test, register, register));
+		}
+
+		if (options & PeepholeOptions::LEA) != 0 {
+			if let Some(rewrite) = Self::rewrite_lea(self.bitness, instruction, next) {
+				return rewrite;
+			}
+		}
+
+		if (options & PeepholeOptions::REDUNDANT_MOV) != 0 {
+			if let Some((destination, source)) = reg_reg_mov(instruction) {
+				// MOV r32,r32 clears the upper 32 bits in 64-bit mode
+				let zero_extends = self.bitness == 64 && destination.is_gpr32();
+				if destination == source && !zero_extends {
+					return Rewrite::Remove;
+				}
+				if let Some((next_destination, next_source)) = next.and_then(reg_reg_mov) {
+					if destination != source
+						&& ((next_destination == destination && next_source == source)
+							|| (next_destination == source && next_source == destination && !zero_extends))
+					{
+						return Rewrite::RemoveNext;
+					}
+				}
+			}
+		}
+
+		Rewrite::None
+	}
+
+	fn rewrite_lea(bitness: u32, instruction: &Instruction, next: Option<&Instruction>) -> Option<Rewrite> {
+		let lea = lea_operands(instruction)?;
+		let destination = instruction.op0_register();
+		// The address must be as big as the destination register so the result isn't truncated
+		if address_register(&lea) != Register::None && address_register(&lea).size() != destination.size() {
+			return None;
+		}
+
+		if lea.index == Register::None && lea.displacement == 0 && lea.base != Register::None {
+			if lea.base == destination {
+				// LEA EAX,[EAX] clears the upper 32 bits of RAX
+				if bitness == 64 && destination.is_gpr32() {
+					return None;
+				}
+				return Some(Rewrite::Remove);
+			}
+			let mov = if destination.is_gpr64() { Code::Mov_rm64_r64 } else { Code::Mov_rm32_r32 };
+			return Some(replace(Instruction::with2(mov, destination, lea.base)));
+		}
+
+		// LEA reg,[mem] + LEA reg,[reg+displ] -> LEA reg,[mem+displ]
+		let next = next?;
+		let next_lea = lea_operands(next)?;
+		if next.code() != instruction.code()
+			|| next.op0_register() != destination
+			|| next_lea.base != destination
+			|| next_lea.index != Register::None
+			|| lea.base == Register::RIP
+			|| lea.base == Register::EIP
+		{
+			return None;
+		}
+		let displacement = if destination.is_gpr64() {
+			let displacement = lea.displacement.checked_add(next_lea.displacement)?;
+			if displacement < i32::MIN as i64 || displacement > i32::MAX as i64 {
+				return None;
+			}
+			displacement
+		} else {
+			(lea.displacement as u32).wrapping_add(next_lea.displacement as u32) as i32 as i64
+		};
+		let displ_size = if displacement == 0 && lea.base != Register::None { 0 } else { 1 };
+		let memory = MemoryOperand::with_base_index_scale_displ_size(lea.base, lea.index, lea.scale, displacement, displ_size);
+		Some(match Instruction::with2(instruction.code(), destination, memory) {
+			Ok(new_instruction) => Rewrite::ReplaceAndRemoveNext(new_instruction),
+			Err(_) => Rewrite::None,
+		})
+	}
+}
+
+enum Rewrite {
+	None,
+	Replace(Instruction),
+	Remove,
+	ReplaceAndRemoveNext(Instruction),
+	RemoveNext,
+}
+
+fn replace(instruction: Result<Instruction, IcedError>) -> Rewrite {
+	match instruction {
+		Ok(instruction) => Rewrite::Replace(instruction),
+		Err(_) => Rewrite::None,
+	}
+}
+
+struct LeaOperands {
+	base: Register,
+	index: Register,
+	scale: u32,
+	displacement: i64,
+}
+
+fn lea_operands(instruction: &Instruction) -> Option<LeaOperands> {
+	if !matches!(instruction.code(), Code::Lea_r32_m | Code::Lea_r64_m) {
+		return None;
+	}
+	let base = instruction.memory_base();
+	let index = instruction.memory_index();
+	let address_size = if base != Register::None { base.size() } else { index.size() };
+	let displacement = match address_size {
+		8 => instruction.memory_displacement64() as i64,
+		4 => instruction.memory_displacement32() as i32 as i64,
+		_ => return None,
+	};
+	Some(LeaOperands { base, index, scale: instruction.memory_index_scale(), displacement })
+}
+
+#[must_use]
+fn address_register(lea: &LeaOperands) -> Register {
+	if lea.base == Register::RIP || lea.base == Register::EIP {
+		Register::None
+	} else if lea.base != Register::None {
+		lea.base
+	} else {
+		lea.index
+	}
+}
+
+// Gets the registers of a general purpose register MOV without a memory operand
+fn reg_reg_mov(instruction: &Instruction) -> Option<(Register, Register)> {
+	match instruction.code() {
+		Code::Mov_rm8_r8
+		| Code::Mov_r8_rm8
+		| Code::Mov_rm16_r16
+		| Code::Mov_r16_rm16
+		| Code::Mov_rm32_r32
+		| Code::Mov_r32_rm32
+		| Code::Mov_rm64_r64
+		| Code::Mov_r64_rm64
+			if instruction.op0_kind() == OpKind::Register && instruction.op1_kind() == OpKind::Register =>
+		{
+			Some((instruction.op0_register(), instruction.op1_register()))
+		}
+		_ => None,
+	}
+}
+
+#[must_use]
+fn is_immediate(op_kind: OpKind) -> bool {
+	matches!(
+		op_kind,
+		OpKind::Immediate8
+			| OpKind::Immediate16
+			| OpKind::Immediate32
+			| OpKind::Immediate8to16
+			| OpKind::Immediate8to32
+			| OpKind::Immediate8to64
+			| OpKind::Immediate32to64
+	)
+}
+
+#[must_use]
+fn has_prefix(instruction: &Instruction) -> bool {
+	instruction.has_lock_prefix() || instruction.has_rep_prefix() || instruction.has_repne_prefix() || instruction.segment_prefix() != Register::None
+}
+
+// Gets the flags that are always written. Shifts and rotates don't modify any flags if the count is 0.
+#[must_use]
+fn flags_modified(instruction: &Instruction) -> u32 {
+	match instruction.mnemonic() {
+		Mnemonic::Shl
+		| Mnemonic::Sal
+		| Mnemonic::Shr
+		| Mnemonic::Sar
+		| Mnemonic::Rol
+		| Mnemonic::Ror
+		| Mnemonic::Rcl
+		| Mnemonic::Rcr
+		| Mnemonic::Shld
+		| Mnemonic::Shrd => 0,
+		_ if instruction.has_rep_prefix() || instruction.has_repne_prefix() => 0,
+		_ => instruction.rflags_modified(),
+	}
+}
+
+// Gets all IPs referenced by branches and RIP-relative memory operands, sorted
+fn get_targets(instructions: &[Instruction]) -> Vec<u64> {
+	let mut targets = Vec::new();
+	for instruction in instructions {
+		match instruction.op0_kind() {
+			OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64 => targets.push(instruction.near_branch_target()),
+			_ => {}
+		}
+		if instruction.is_ip_rel_memory_operand() {
+			targets.push(instruction.ip_rel_memory_address());
+		}
+	}
+	targets.sort_unstable();
+	targets.dedup();
+	targets
+}
+
+#[must_use]
+fn is_target(targets: &[u64], ip: u64) -> bool {
+	targets.binary_search(&ip).is_ok()
+}
@@ -0,0 +1,193 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::peephole::*;
+use crate::*;
+use alloc::vec::Vec;
+
+const IP: u64 = 0x1000;
+
+fn decode(bitness: u32, bytes: &[u8]) -> Vec<Instruction> {
+	let instructions: Vec<Instruction> = Decoder::with_ip(bitness, bytes, IP, DecoderOptions::NONE).into_iter().collect();
+	assert!(instructions.iter().all(|instruction| !instruction.is_invalid()));
+	instructions
+}
+
+fn encode(bitness: u32, instructions: &[Instruction]) -> Vec<u8> {
+	BlockEncoder::encode(bitness, InstructionBlock::new(instructions, IP), BlockEncoderOptions::NONE).unwrap().code_buffer
+}
+
+fn optimize_with(bitness: u32, options: u32, bytes: &[u8], expected_count: usize, expected: &[u8]) {
+	let mut instructions = decode(bitness, bytes);
+	let mut optimizer = PeepholeOptimizer::new(bitness).unwrap();
+	optimizer.set_options(options);
+	assert_eq!(optimizer.optimize(&mut instructions), expected_count);
+	assert_eq!(encode(bitness, &instructions), expected);
+}
+
+fn optimize(bitness: u32, bytes: &[u8], expected_count: usize, expected: &[u8]) {
+	optimize_with(bitness, PeepholeOptions::ALL, bytes, expected_count, expected);
+}
+
+#[test]
+fn invalid_bitness() {
+	assert!(PeepholeOptimizer::new(0).is_err());
+	assert!(PeepholeOptimizer::new(128).is_err());
+	assert_eq!(PeepholeOptimizer::new(64).unwrap().options(), PeepholeOptions::ALL);
+}
+
+#[test]
+fn mov_imm32() {
+	#[rustfmt::skip]
+	let bytes = [
+		// mov rax,12345678h
+		0x48, 0xC7, 0xC0, 0x78, 0x56, 0x34, 0x12,
+		// mov rcx,0FFFFFFFFh
+		0x48, 0xB9, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00,
+		// mov rdx,0FFFFFFFFFFFFFFFFh
+		0x48, 0xC7, 0xC2, 0xFF, 0xFF, 0xFF, 0xFF,
+		// mov rbx,100000000h
+		0x48, 0xBB, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+		// mov qword ptr [rax],1
+		0x48, 0xC7, 0x00, 0x01, 0x00, 0x00, 0x00,
+		// mov r9d,1 (C7 form)
+		0x41, 0xC7, 0xC1, 0x01, 0x00, 0x00, 0x00,
+		// ret
+		0xC3,
+	];
+	#[rustfmt::skip]
+	let expected = [
+		// mov eax,12345678h
+		0xB8, 0x78, 0x56, 0x34, 0x12,
+		// mov ecx,0FFFFFFFFh
+		0xB9, 0xFF, 0xFF, 0xFF, 0xFF,
+		0x48, 0xC7, 0xC2, 0xFF, 0xFF, 0xFF, 0xFF,
+		0x48, 0xBB, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+		0x48, 0xC7, 0x00, 0x01, 0x00, 0x00, 0x00,
+		// mov r9d,1
+		0x41, 0xB9, 0x01, 0x00, 0x00, 0x00,
+		0xC3,
+	];
+	optimize(64, &bytes, 3, &expected);
+	optimize_with(64, PeepholeOptions::ALL & !PeepholeOptions::MOV_IMM32, &bytes, 0, &bytes);
+}
+
+#[test]
+fn zero_idiom() {
+	// mov rax,0 / ret
+	optimize(64, b"\x48\xC7\xC0\x00\x00\x00\x00\xC3", 1, b"\x31\xC0\xC3");
+	// mov eax,0 / ret
+	optimize(32, b"\xB8\x00\x00\x00\x00\xC3", 1, b"\x31\xC0\xC3");
+	// cmp ecx,edx / mov eax,0 / sete al / ret
+	optimize(64, b"\x39\xD1\xB8\x00\x00\x00\x00\x0F\x94\xC0\xC3", 0, b"\x39\xD1\xB8\x00\x00\x00\x00\x0F\x94\xC0\xC3");
+	// mov eax,0 / add ecx,1 / jne 1000h
+	optimize(64, b"\xB8\x00\x00\x00\x00\x83\xC1\x01\x75\xF6", 1, b"\x31\xC0\x83\xC1\x01\x75\xF9");
+	// mov eax,0 / mov ecx,0 (flags are live after the last instruction)
+	optimize(64, b"\xB8\x00\x00\x00\x00\xB9\x00\x00\x00\x00", 0, b"\xB8\x00\x00\x00\x00\xB9\x00\x00\x00\x00");
+	// mov rax,0 / jmp rax: only MOV_IMM32
+	optimize(64, b"\x48\xC7\xC0\x00\x00\x00\x00\xFF\xE0", 1, b"\xB8\x00\x00\x00\x00\xFF\xE0");
+}
+
+#[test]
+fn zero_idiom_uses_branch_targets() {
+	// 1000: mov eax,0 / dec ecx / jne 1000h / ret
+	// DEC doesn't write CF but CF isn't read
+	optimize(64, b"\xB8\x00\x00\x00\x00\xFF\xC9\x75\xF7\xC3", 1, b"\x31\xC0\xFF\xC9\x75\xFA\xC3");
+	// 1000: mov eax,0 / dec ecx / jne 1000h / jb 1000h / ret
+	// CF is read by JB
+	optimize(64, b"\xB8\x00\x00\x00\x00\xFF\xC9\x75\xF7\x72\xF5\xC3", 0, b"\xB8\x00\x00\x00\x00\xFF\xC9\x75\xF7\x72\xF5\xC3");
+	// mov eax,0 / jmp 1008h / int3 / 1008: jne 1000h / ret
+	optimize(64, b"\xB8\x00\x00\x00\x00\xEB\x01\xCC\x75\xF6\xC3", 0, b"\xB8\x00\x00\x00\x00\xEB\x01\xCC\x75\xF6\xC3");
+	// mov eax,0 / jmp 1008h / int3 / 1008: add eax,1 / ret
+	optimize(64, b"\xB8\x00\x00\x00\x00\xEB\x01\xCC\x83\xC0\x01\xC3", 1, b"\x31\xC0\xEB\x01\xCC\x83\xC0\x01\xC3");
+	// mov eax,0 / jmp 2000h (unknown target)
+	optimize(64, b"\xB8\x00\x00\x00\x00\xE9\xF6\x0F\x00\x00", 0, b"\xB8\x00\x00\x00\x00\xE9\xF6\x0F\x00\x00");
+	// mov eax,0 / shl edx,cl / jb 1000h / ret
+	// SHL doesn't modify the flags if the count is 0
+	optimize(64, b"\xB8\x00\x00\x00\x00\xD3\xE2\x72\xF7\xC3", 0, b"\xB8\x00\x00\x00\x00\xD3\xE2\x72\xF7\xC3");
+	// mov eax,0 / call 2000h / jb 1000h / ret
+	// The status flags aren't returned by a function
+	optimize(64, b"\xB8\x00\x00\x00\x00\xE8\xF6\x0F\x00\x00\x72\xF4\xC3", 1, b"\x31\xC0\xE8\xF9\x0F\x00\x00\x72\xF7\xC3");
+}
+
+#[test]
+fn test() {
+	// cmp rax,0 / je 1000h / ret
+	optimize(64, b"\x48\x83\xF8\x00\x74\xFA\xC3", 1, b"\x48\x85\xC0\x74\xFB\xC3");
+	// cmp al,0 / jl 1000h / ret
+	optimize(64, b"\x3C\x00\x7C\xFC\xC3", 1, b"\x84\xC0\x7C\xFC\xC3");
+	// cmp cx,0 / jg 1000h / ret
+	optimize(32, b"\x66\x81\xF9\x00\x00\x7F\xF9\xC3", 1, b"\x66\x85\xC9\x7F\xFB\xC3");
+	// cmp rax,0 / je 1000h: the flags are live after the last instruction
+	optimize(64, b"\x48\x83\xF8\x00\x74\xFA", 0, b"\x48\x83\xF8\x00\x74\xFA");
+	// cmp eax,0 / lahf / ret
+	// AF is read by LAHF
+	optimize(64, b"\x83\xF8\x00\x9F\xC3", 0, b"\x83\xF8\x00\x9F\xC3");
+	// cmp eax,1 / je 1000h / ret
+	optimize(64, b"\x83\xF8\x01\x74\xFB\xC3", 0, b"\x83\xF8\x01\x74\xFB\xC3");
+	// cmp dword ptr [rax],0 / je 1000h / ret
+	optimize(64, b"\x83\x38\x00\x74\xFB\xC3", 0, b"\x83\x38\x00\x74\xFB\xC3");
+}
+
+#[test]
+fn lea() {
+	// lea rax,[rbx] / ret
+	optimize(64, b"\x48\x8D\x03\xC3", 1, b"\x48\x89\xD8\xC3");
+	// lea rcx,[rcx] / ret
+	optimize(64, b"\x48\x8D\x09\xC3", 1, b"\xC3");
+	// lea eax,[ebx] / ret
+	optimize(32, b"\x8D\x03\xC3", 1, b"\x89\xD8\xC3");
+	// lea eax,[eax] / ret: the upper 32 bits are cleared
+	optimize(64, b"\x67\x8D\x00\xC3", 0, b"\x67\x8D\x00\xC3");
+	optimize(32, b"\x8D\x00\xC3", 1, b"\xC3");
+	// lea eax,[rbx] / ret: the address is truncated
+	optimize(64, b"\x8D\x03\xC3", 0, b"\x8D\x03\xC3");
+	// lea rax,[rbx+rcx*4+8] / lea rax,[rax+10h] / ret
+	optimize(64, b"\x48\x8D\x44\x8B\x08\x48\x8D\x40\x10\xC3", 2, b"\x48\x8D\x44\x8B\x18\xC3");
+	// lea rax,[rbx+8] / lea rax,[rax-8] / ret
+	optimize(64, b"\x48\x8D\x43\x08\x48\x8D\x40\xF8\xC3", 2, b"\x48\x8D\x03\xC3");
+	// lea eax,[ebx+0FFFFFFFFh] / lea eax,[eax+2] / ret
+	optimize(32, b"\x8D\x43\xFF\x8D\x40\x02\xC3", 2, b"\x8D\x43\x01\xC3");
+	// lea rax,[rbx+7FFFFFFFh] / lea rax,[rax+1] / ret: the displacement doesn't fit in 32 bits
+	optimize(64, b"\x48\x8D\x83\xFF\xFF\xFF\x7F\x48\x8D\x40\x01\xC3", 0, b"\x48\x8D\x83\xFF\xFF\xFF\x7F\x48\x8D\x40\x01\xC3");
+	// lea rax,[rbx+8] / lea rcx,[rax+8] / ret: RAX is used
+	optimize(64, b"\x48\x8D\x43\x08\x48\x8D\x48\x08\xC3", 0, b"\x48\x8D\x43\x08\x48\x8D\x48\x08\xC3");
+	// lea rax,[rbx+8] / lea rax,[rax+rcx+8] / ret
+	optimize(64, b"\x48\x8D\x43\x08\x48\x8D\x44\x08\x08\xC3", 0, b"\x48\x8D\x43\x08\x48\x8D\x44\x08\x08\xC3");
+	// lea rax,[1000h] / lea rax,[rax+8] / ret
+	optimize(64, b"\x48\x8D\x05\xF9\xFF\xFF\xFF\x48\x8D\x40\x08\xC3", 0, b"\x48\x8D\x05\xF9\xFF\xFF\xFF\x48\x8D\x40\x08\xC3");
+}
+
+#[test]
+fn redundant_mov() {
+	// mov rax,rax / mov cl,cl / mov dx,dx / ret
+	optimize(64, b"\x48\x89\xC0\x88\xC9\x66\x89\xD2\xC3", 3, b"\xC3");
+	// mov eax,eax / ret: the upper 32 bits are cleared
+	optimize(64, b"\x89\xC0\xC3", 0, b"\x89\xC0\xC3");
+	optimize(32, b"\x89\xC0\xC3", 1, b"\xC3");
+	// mov rax,rbx / mov rbx,rax / ret
+	optimize(64, b"\x48\x89\xD8\x48\x89\xC3\xC3", 1, b"\x48\x89\xD8\xC3");
+	// mov eax,ebx / mov ebx,eax / ret
+	optimize(64, b"\x89\xD8\x89\xC3\xC3", 0, b"\x89\xD8\x89\xC3\xC3");
+	optimize(32, b"\x89\xD8\x89\xC3\xC3", 1, b"\x89\xD8\xC3");
+	// mov rcx,rdx / mov rcx,rdx / ret
+	optimize(64, b"\x48\x89\xD1\x48\x8B\xCA\xC3", 1, b"\x48\x89\xD1\xC3");
+	// mov rax,rbx / mov rbx,rcx / ret
+	optimize(64, b"\x48\x89\xD8\x48\x89\xCB\xC3", 0, b"\x48\x89\xD8\x48\x89\xCB\xC3");
+	optimize_with(64, PeepholeOptions::NONE, b"\x48\x89\xC0\xC3", 0, b"\x48\x89\xC0\xC3");
+}
+
+#[test]
+fn removed_branch_targets() {
+	// jmp 1002h / 1002: mov rax,rax / ret
+	optimize(64, b"\xEB\x00\x48\x89\xC0\xC3", 1, b"\xEB\x00\xC3");
+	// mov rax,rbx / 1003: mov rbx,rax / jmp 1003h
+	optimize(64, b"\x48\x89\xD8\x48\x89\xC3\xEB\xFB", 0, b"\x48\x89\xD8\x48\x89\xC3\xEB\xFB");
+	// jmp 1002h / 1002: mov rax,rax / 1005: ret / jmp 1005h
+	// The IP can't be moved to the next instruction
+	optimize(64, b"\xEB\x00\x48\x89\xC0\xC3\xEB\xFD", 0, b"\xEB\x00\x48\x89\xC0\xC3\xEB\xFD");
+	// jmp 1002h / 1002: mov rax,rax / mov rcx,rcx / ret
+	optimize(64, b"\xEB\x00\x48\x89\xC0\x48\x89\xC9\xC3", 2, b"\xEB\x00\xC3");
+	// lea rax,[1003h] / 1003: mov rax,rax / ret
+	optimize(64, b"\x48\x8D\x05\xF9\xFF\xFF\xFF\x48\x89\xC0\xC3", 1, b"\x48\x8D\x05\xF9\xFF\xFF\xFF\xC3");
+}
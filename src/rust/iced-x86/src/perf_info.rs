@@ -0,0 +1,366 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+//! Per-microarchitecture estimates of instruction latency, throughput and port usage and a static basic block throughput estimator
+//!
+//! This requires the `perf_info` feature to use (not enabled by default). Add it to your `Cargo.toml`:
+//!
+//! ```toml
+//! [dependencies.iced-x86]
+//! version = "1.21.0"
+//! features = ["perf_info"]
+//! ```
+//!
+//! The estimates are looked up by [`Code`]. Each `Code` is in one of about 40 classes with similar characteristics
+//! (eg. integer ALU, shifts, vector shuffles, FP adds) and it uses the numbers of its class unless the microarchitecture
+//! has numbers for that `Code` (eg. 8-bit `MUL`, 256-bit `VDIVPS`). Most `Code`s don't have their own numbers so the
+//! numbers of an individual instruction can differ from the measured values, especially if they depend on the input
+//! values (eg. `DIV`). The numbers are adjusted for memory operands and 512-bit registers. Instructions that aren't in
+//! any class (eg. most x87, string and system instructions) have no estimate.
+//!
+//! The numbers are approximations of published measurements (eg. uops.info and Agner Fog's instruction tables).
+//!
+//! [`Code`]: ../enum.Code.html
+
+mod class;
+mod estimator;
+mod tables;
+#[cfg(all(test, feature = "decoder"))]
+mod tests;
+
+use crate::perf_info::class::{classify, get_mem_access, is_zero_idiom, MemAccess, PerfClass};
+pub use crate::perf_info::estimator::*;
+use crate::perf_info::tables::UarchInfo;
+use crate::{CpuFeatureProfile, Instruction, OpKind};
+use alloc::vec::Vec;
+use core::{cmp, fmt};
+use lazy_static::lazy_static;
+
+lazy_static! {
+	// Indexed by Microarchitecture
+	static ref PROFILES: Vec<CpuFeatureProfile> = Microarchitecture::values()
+		.iter()
+		.map(|&uarch| {
+			#[allow(clippy::unwrap_used)]
+			CpuFeatureProfile::from_name(uarch.info().profile_name).unwrap()
+		})
+		.collect();
+}
+
+/// A CPU microarchitecture
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum Microarchitecture {
+	/// Intel Skylake (client), ports `p0`-`p7`
+	Skylake,
+	/// Intel Ice Lake (client), ports `p0`-`p9`
+	IceLake,
+	/// Intel Golden Cove (Alder Lake P-cores, Sapphire Rapids), ports `p0`-`p11`
+	GoldenCove,
+	/// AMD Zen 2, ports `ALU0`-`ALU3`, `AGU0`-`AGU2`, `FP0`-`FP3`
+	Zen2,
+	/// AMD Zen 3, ports `ALU0`-`ALU3`, `AGU0`-`AGU2`, `FP0`-`FP3`
+	Zen3,
+	/// AMD Zen 4, ports `ALU0`-`ALU3`, `AGU0`-`AGU2`, `FP0`-`FP3`
+	Zen4,
+}
+
+impl Microarchitecture {
+	/// Gets all microarchitectures
+	#[must_use]
+	#[inline]
+	pub const fn values() -> &'static [Microarchitecture] {
+		&[
+			Microarchitecture::Skylake,
+			Microarchitecture::IceLake,
+			Microarchitecture::GoldenCove,
+			Microarchitecture::Zen2,
+			Microarchitecture::Zen3,
+			Microarchitecture::Zen4,
+		]
+	}
+
+	/// Gets the name, eg. `Skylake`
+	#[must_use]
+	#[inline]
+	pub const fn name(self) -> &'static str {
+		match self {
+			Microarchitecture::Skylake => "Skylake",
+			Microarchitecture::IceLake => "Ice Lake",
+			Microarchitecture::GoldenCove => "Golden Cove",
+			Microarchitecture::Zen2 => "Zen 2",
+			Microarchitecture::Zen3 => "Zen 3",
+			Microarchitecture::Zen4 => "Zen 4",
+		}
+	}
+
+	#[inline]
+	const fn info(self) -> &'static UarchInfo {
+		match self {
+			Microarchitecture::Skylake => &tables::SKYLAKE,
+			Microarchitecture::IceLake => &tables::ICE_LAKE,
+			Microarchitecture::GoldenCove => &tables::GOLDEN_COVE,
+			Microarchitecture::Zen2 => &tables::ZEN2,
+			Microarchitecture::Zen3 => &tables::ZEN3,
+			Microarchitecture::Zen4 => &tables::ZEN4,
+		}
+	}
+
+	/// Gets the names of the execution ports. Bit `N` in [`PortUsage::ports()`] is the port `port_names()[N]`.
+	///
+	/// [`PortUsage::ports()`]: struct.PortUsage.html#method.ports
+	#[must_use]
+	#[inline]
+	pub const fn port_names(self) -> &'static [&'static str] {
+		self.info().port_names
+	}
+
+	/// Gets the max number of (fused) uops that can be issued per cycle
+	#[must_use]
+	#[inline]
+	pub const fn issue_width(self) -> u32 {
+		self.info().issue_width
+	}
+
+	/// Gets the CPU features supported by this microarchitecture. [`GoldenCove`] assumes AVX-512 is available (Sapphire Rapids).
+	///
+	/// [`GoldenCove`]: #variant.GoldenCove
+	#[must_use]
+	#[inline]
+	pub fn profile(self) -> CpuFeatureProfile {
+		*self.profile_ref()
+	}
+
+	#[inline]
+	fn profile_ref(self) -> &'static CpuFeatureProfile {
+		&PROFILES[self as usize]
+	}
+
+	/// Estimates the latency, throughput and port usage of an instruction (see the module docs).
+	/// `None` is returned if it's not in any class or if it's not supported by this microarchitecture (see [`profile()`]).
+	///
+	/// [`profile()`]: #method.profile
+	///
+	/// # Arguments
+	///
+	/// - `instruction`: Instruction
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use iced_x86::*;
+	/// use iced_x86::perf_info::*;
+	///
+	/// // imul eax,ecx
+	/// let mut decoder = Decoder::new(64, b"\x0F\xAF\xC1", DecoderOptions::NONE);
+	/// let instr = decoder.decode();
+	/// let info = Microarchitecture::Skylake.estimate_perf(&instr).unwrap();
+	/// assert_eq!(info.latency(), 3);
+	/// assert_eq!(info.reciprocal_throughput(), 1.0);
+	/// assert_eq!(info.uops(), 1);
+	/// assert_eq!(info.port_usage().len(), 1);
+	/// assert_eq!(Microarchitecture::Skylake.port_names()[info.port_usage()[0].ports().trailing_zeros() as usize], "p1");
+	/// ```
+	#[must_use]
+	#[inline]
+	pub fn estimate_perf(self, instruction: &Instruction) -> Option<PerfEstimate> {
+		if !self.profile_ref().supports(instruction) {
+			return None;
+		}
+		get_perf_estimate(self.info(), instruction)
+	}
+}
+
+impl fmt::Display for Microarchitecture {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+/// Uops that can execute on any of a set of ports, see [`PerfEstimate::port_usage()`]
+///
+/// [`PerfEstimate::port_usage()`]: struct.PerfEstimate.html#method.port_usage
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PortUsage {
+	count: u8,
+	ports: u16,
+}
+
+impl PortUsage {
+	/// Number of uops
+	#[must_use]
+	#[inline]
+	pub const fn count(&self) -> u32 {
+		self.count as u32
+	}
+
+	/// Each bit is a port that can execute the uops, see [`Microarchitecture::port_names()`]
+	///
+	/// [`Microarchitecture::port_names()`]: enum.Microarchitecture.html#method.port_names
+	#[must_use]
+	#[inline]
+	pub const fn ports(&self) -> u32 {
+		self.ports as u32
+	}
+}
+
+const MAX_PORT_USAGE: usize = 8;
+
+/// Estimated latency, throughput and port usage of an instruction, see [`Microarchitecture::estimate_perf()`].
+/// It's usually the estimate of the instruction's class, not a measured value of the instruction.
+///
+/// [`Microarchitecture::estimate_perf()`]: enum.Microarchitecture.html#method.estimate_perf
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PerfEstimate {
+	latency: u16,
+	rthroughput: u16,
+	uops: u8,
+	// Added to the latency if the input is the address of the memory operand
+	load_latency: u8,
+	is_zero_idiom: bool,
+	port_usage_len: u8,
+	port_usage: [PortUsage; MAX_PORT_USAGE],
+}
+
+impl PerfEstimate {
+	/// Latency in cycles from the inputs to the outputs. If it reads memory, it includes the load latency (see [`load_latency()`]).
+	///
+	/// [`load_latency()`]: #method.load_latency
+	#[must_use]
+	#[inline]
+	pub const fn latency(&self) -> u32 {
+		self.latency as u32 + self.load_latency as u32
+	}
+
+	/// Load latency in cycles or 0 if it doesn't read memory. It's only part of the latency from the address registers to the outputs.
+	#[must_use]
+	#[inline]
+	pub const fn load_latency(&self) -> u32 {
+		self.load_latency as u32
+	}
+
+	/// Reciprocal throughput, ie., average number of cycles per instruction if there are no dependencies
+	#[must_use]
+	#[inline]
+	pub fn reciprocal_throughput(&self) -> f64 {
+		self.rthroughput as f64 / 100.0
+	}
+
+	/// Number of fused domain uops
+	#[must_use]
+	#[inline]
+	pub const fn uops(&self) -> u32 {
+		self.uops as u32
+	}
+
+	/// `true` if it's a zero idiom (eg. `xor eax,eax`). It doesn't depend on its inputs and doesn't use an execution port.
+	#[must_use]
+	#[inline]
+	pub const fn is_zero_idiom(&self) -> bool {
+		self.is_zero_idiom
+	}
+
+	/// Unfused domain uops and the ports they can execute on
+	#[must_use]
+	#[inline]
+	pub fn port_usage(&self) -> &[PortUsage] {
+		&self.port_usage[..self.port_usage_len as usize]
+	}
+
+	fn add_ports(&mut self, count: u8, ports: u16) {
+		if count != 0 && ports != 0 && (self.port_usage_len as usize) < self.port_usage.len() {
+			self.port_usage[self.port_usage_len as usize] = PortUsage { count, ports };
+			self.port_usage_len += 1;
+		}
+	}
+}
+
+fn uses_zmm(instruction: &Instruction) -> bool {
+	(0..instruction.op_count()).any(|i| instruction.op_kind(i) == OpKind::Register && instruction.op_register(i).is_zmm())
+}
+
+fn uses_vector_registers(instruction: &Instruction) -> bool {
+	(0..instruction.op_count()).any(|i| instruction.op_kind(i) == OpKind::Register && instruction.op_register(i).is_vector_register())
+}
+
+fn get_perf_estimate(info: &UarchInfo, instruction: &Instruction) -> Option<PerfEstimate> {
+	let class = classify(instruction)?;
+	let entry = info.get_entry(instruction.code(), class);
+	let mut result = PerfEstimate {
+		latency: entry.latency as u16,
+		rthroughput: entry.rthroughput,
+		uops: entry.uops,
+		load_latency: 0,
+		is_zero_idiom: false,
+		port_usage_len: 0,
+		port_usage: [PortUsage::default(); MAX_PORT_USAGE],
+	};
+
+	if is_zero_idiom(instruction) {
+		result.latency = 0;
+		result.rthroughput = (100 / info.issue_width) as u16;
+		result.uops = 1;
+		result.is_zero_idiom = true;
+		return Some(result);
+	}
+
+	let zmm = uses_zmm(instruction);
+	for &(count, ports) in entry.ports {
+		if zmm {
+			if info.zmm_double_pumped {
+				result.add_ports(count * 2, ports);
+			} else {
+				result.add_ports(count, (info.zmm_ports)(ports));
+			}
+		} else {
+			result.add_ports(count, ports);
+		}
+	}
+	if zmm && info.zmm_double_pumped {
+		result.rthroughput = result.rthroughput.saturating_mul(2);
+	}
+
+	let load_latency = if uses_vector_registers(instruction) { info.vec_load_latency } else { info.load_latency };
+	let is_move = class == PerfClass::Move || class == PerfClass::VecMove;
+	match get_mem_access(instruction, class) {
+		MemAccess::None => {}
+		MemAccess::Load if is_move => {
+			result.latency = 0;
+			result.load_latency = load_latency;
+			result.rthroughput = info.load_rthroughput;
+			result.uops = 1;
+			result.port_usage_len = 0;
+			result.add_ports(1, info.load_ports);
+		}
+		MemAccess::Store if is_move => {
+			result.latency = 1;
+			result.rthroughput = info.store_rthroughput;
+			result.uops = 1;
+			result.port_usage_len = 0;
+			result.add_ports(1, info.store_address_ports);
+			result.add_ports(1, info.store_data_ports);
+		}
+		MemAccess::Load => {
+			result.load_latency = load_latency;
+			result.rthroughput = cmp::max(result.rthroughput, info.load_rthroughput);
+			result.add_ports(1, info.load_ports);
+		}
+		MemAccess::Store => {
+			result.rthroughput = cmp::max(result.rthroughput, info.store_rthroughput);
+			result.uops = result.uops.saturating_add(1);
+			result.add_ports(1, info.store_address_ports);
+			result.add_ports(1, info.store_data_ports);
+		}
+		MemAccess::LoadStore => {
+			result.load_latency = load_latency;
+			result.rthroughput = cmp::max(result.rthroughput, info.store_rthroughput);
+			result.uops = result.uops.saturating_add(1);
+			result.add_ports(1, info.load_ports);
+			result.add_ports(1, info.store_address_ports);
+			result.add_ports(1, info.store_data_ports);
+		}
+	}
+
+	Some(result)
+}
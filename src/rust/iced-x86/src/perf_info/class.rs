@@ -0,0 +1,841 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::iced_constants::IcedConstants;
+use crate::{Code, Instruction, Mnemonic, OpCodeInfo, OpCodeOperandKind, OpKind};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use lazy_static::lazy_static;
+
+// Instructions with the same performance characteristics. The tables have one entry per class and microarchitecture.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(super) enum PerfClass {
+	Nop,
+	Move,
+	Alu,
+	AluCarry,
+	Lea,
+	Shift,
+	ShiftCl,
+	Cmov,
+	Setcc,
+	Imul,
+	MulWide,
+	Div32,
+	Div64,
+	BitCount,
+	BitScan,
+	Bmi,
+	Pdep,
+	Branch,
+	IndirectBranch,
+	Call,
+	Ret,
+	Push,
+	Pop,
+	VecMove,
+	VecLogic,
+	VecIntAlu,
+	VecShift,
+	VecShuffle,
+	VecLaneCross,
+	VecIntMul,
+	VecIntMul32,
+	FpAdd,
+	FpMul,
+	Fma,
+	FpDiv32,
+	FpDiv64,
+	FpSqrt32,
+	FpSqrt64,
+	Convert,
+	Aes,
+	Pclmul,
+	Crc32,
+}
+
+impl PerfClass {
+	pub(super) const COUNT: usize = PerfClass::Crc32 as usize + 1;
+}
+
+// How the instruction accesses its memory operand
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(super) enum MemAccess {
+	None,
+	Load,
+	Store,
+	LoadStore,
+}
+
+lazy_static! {
+	static ref CODE_CLASSES: Box<[Option<PerfClass>; IcedConstants::CODE_ENUM_COUNT]> = {
+		let result: Vec<_> = Code::values().map(classify_code).collect();
+		#[allow(clippy::unwrap_used)]
+		result.into_boxed_slice().try_into().ok().unwrap()
+	};
+}
+
+#[must_use]
+pub(super) fn classify(instruction: &Instruction) -> Option<PerfClass> {
+	let class = CODE_CLASSES[instruction.code() as usize]?;
+	// movd/movq with a memory operand is a vector load/store and not a move between a GPR and a vector register
+	if class == PerfClass::Convert
+		&& matches!(instruction.mnemonic(), Mnemonic::Movd | Mnemonic::Movq | Mnemonic::Vmovd | Mnemonic::Vmovq)
+		&& (0..instruction.op_count()).any(|i| instruction.op_kind(i) == OpKind::Memory)
+	{
+		return Some(PerfClass::VecMove);
+	}
+	Some(class)
+}
+
+// The class of the register form of an instruction
+#[must_use]
+fn classify_code(code: Code) -> Option<PerfClass> {
+	let op_code = code.op_code();
+	if is_string_instruction(op_code) {
+		return None;
+	}
+	Some(match code.mnemonic() {
+		Mnemonic::Nop | Mnemonic::Fnop | Mnemonic::Endbr32 | Mnemonic::Endbr64 => PerfClass::Nop,
+
+		Mnemonic::Mov | Mnemonic::Movzx | Mnemonic::Movsx | Mnemonic::Movsxd | Mnemonic::Cbw | Mnemonic::Cwde | Mnemonic::Cdqe => {
+			if is_segment_or_control_move(op_code) {
+				return None;
+			}
+			PerfClass::Move
+		}
+		Mnemonic::Add
+		| Mnemonic::Sub
+		| Mnemonic::And
+		| Mnemonic::Or
+		| Mnemonic::Xor
+		| Mnemonic::Cmp
+		| Mnemonic::Test
+		| Mnemonic::Inc
+		| Mnemonic::Dec
+		| Mnemonic::Neg
+		| Mnemonic::Not
+		| Mnemonic::Bt
+		| Mnemonic::Bswap
+		| Mnemonic::Movbe
+		| Mnemonic::Cwd
+		| Mnemonic::Cdq
+		| Mnemonic::Cqo
+		| Mnemonic::Clc
+		| Mnemonic::Stc
+		| Mnemonic::Cmc => PerfClass::Alu,
+		Mnemonic::Adc | Mnemonic::Sbb | Mnemonic::Adcx | Mnemonic::Adox | Mnemonic::Btc | Mnemonic::Btr | Mnemonic::Bts => PerfClass::AluCarry,
+		Mnemonic::Lea => PerfClass::Lea,
+		Mnemonic::Shl | Mnemonic::Sal | Mnemonic::Shr | Mnemonic::Sar | Mnemonic::Rol | Mnemonic::Ror => {
+			if op_code.op_count() >= 2 && op_code.op_kind(1) == OpCodeOperandKind::cl {
+				PerfClass::ShiftCl
+			} else {
+				PerfClass::Shift
+			}
+		}
+		Mnemonic::Cmova
+		| Mnemonic::Cmovae
+		| Mnemonic::Cmovb
+		| Mnemonic::Cmovbe
+		| Mnemonic::Cmove
+		| Mnemonic::Cmovg
+		| Mnemonic::Cmovge
+		| Mnemonic::Cmovl
+		| Mnemonic::Cmovle
+		| Mnemonic::Cmovne
+		| Mnemonic::Cmovno
+		| Mnemonic::Cmovnp
+		| Mnemonic::Cmovns
+		| Mnemonic::Cmovo
+		| Mnemonic::Cmovp
+		| Mnemonic::Cmovs => PerfClass::Cmov,
+		Mnemonic::Seta
+		| Mnemonic::Setae
+		| Mnemonic::Setb
+		| Mnemonic::Setbe
+		| Mnemonic::Sete
+		| Mnemonic::Setg
+		| Mnemonic::Setge
+		| Mnemonic::Setl
+		| Mnemonic::Setle
+		| Mnemonic::Setne
+		| Mnemonic::Setno
+		| Mnemonic::Setnp
+		| Mnemonic::Setns
+		| Mnemonic::Seto
+		| Mnemonic::Setp
+		| Mnemonic::Sets => PerfClass::Setcc,
+		// 1-operand IMUL writes rDX:rAX
+		Mnemonic::Imul if op_code.op_count() >= 2 => PerfClass::Imul,
+		Mnemonic::Imul | Mnemonic::Mul | Mnemonic::Mulx => PerfClass::MulWide,
+		Mnemonic::Div | Mnemonic::Idiv => {
+			if op_code.operand_size() == 64 {
+				PerfClass::Div64
+			} else {
+				PerfClass::Div32
+			}
+		}
+		Mnemonic::Popcnt | Mnemonic::Lzcnt | Mnemonic::Tzcnt => PerfClass::BitCount,
+		Mnemonic::Bsf | Mnemonic::Bsr => PerfClass::BitScan,
+		Mnemonic::Andn
+		| Mnemonic::Blsi
+		| Mnemonic::Blsmsk
+		| Mnemonic::Blsr
+		| Mnemonic::Bzhi
+		| Mnemonic::Bextr
+		| Mnemonic::Sarx
+		| Mnemonic::Shlx
+		| Mnemonic::Shrx
+		| Mnemonic::Rorx => PerfClass::Bmi,
+		Mnemonic::Pdep | Mnemonic::Pext => PerfClass::Pdep,
+
+		Mnemonic::Ja
+		| Mnemonic::Jae
+		| Mnemonic::Jb
+		| Mnemonic::Jbe
+		| Mnemonic::Je
+		| Mnemonic::Jg
+		| Mnemonic::Jge
+		| Mnemonic::Jl
+		| Mnemonic::Jle
+		| Mnemonic::Jne
+		| Mnemonic::Jno
+		| Mnemonic::Jnp
+		| Mnemonic::Jns
+		| Mnemonic::Jo
+		| Mnemonic::Jp
+		| Mnemonic::Js => PerfClass::Branch,
+		Mnemonic::Jmp if is_near_branch(op_code) => PerfClass::Branch,
+		Mnemonic::Jmp if is_near_indirect_branch(op_code) => PerfClass::IndirectBranch,
+		Mnemonic::Call if is_near_branch(op_code) || is_near_indirect_branch(op_code) => PerfClass::Call,
+		Mnemonic::Ret => PerfClass::Ret,
+		Mnemonic::Push if !is_segment_register_op0(op_code) => PerfClass::Push,
+		Mnemonic::Pop if !is_segment_register_op0(op_code) => PerfClass::Pop,
+
+		Mnemonic::Movaps
+		| Mnemonic::Movapd
+		| Mnemonic::Movups
+		| Mnemonic::Movupd
+		| Mnemonic::Movdqa
+		| Mnemonic::Movdqu
+		| Mnemonic::Movd
+		| Mnemonic::Movq
+		| Mnemonic::Movss
+		| Mnemonic::Movsd
+		| Mnemonic::Lddqu
+		| Mnemonic::Vmovaps
+		| Mnemonic::Vmovapd
+		| Mnemonic::Vmovups
+		| Mnemonic::Vmovupd
+		| Mnemonic::Vmovdqa
+		| Mnemonic::Vmovdqu
+		| Mnemonic::Vmovdqa32
+		| Mnemonic::Vmovdqa64
+		| Mnemonic::Vmovdqu8
+		| Mnemonic::Vmovdqu16
+		| Mnemonic::Vmovdqu32
+		| Mnemonic::Vmovdqu64
+		| Mnemonic::Vmovd
+		| Mnemonic::Vmovq
+		| Mnemonic::Vmovss
+		| Mnemonic::Vmovsd
+		| Mnemonic::Vlddqu
+		| Mnemonic::Vbroadcastss
+		| Mnemonic::Vbroadcastsd
+		| Mnemonic::Vpbroadcastd
+		| Mnemonic::Vpbroadcastq => {
+			// movd/movq between GPRs and vector registers
+			if op_code.op_count() == 2 && (is_gpr_operand(op_code.op_kind(0)) || is_gpr_operand(op_code.op_kind(1))) {
+				PerfClass::Convert
+			} else {
+				PerfClass::VecMove
+			}
+		}
+		Mnemonic::Pand
+		| Mnemonic::Pandn
+		| Mnemonic::Por
+		| Mnemonic::Pxor
+		| Mnemonic::Andps
+		| Mnemonic::Andpd
+		| Mnemonic::Andnps
+		| Mnemonic::Andnpd
+		| Mnemonic::Orps
+		| Mnemonic::Orpd
+		| Mnemonic::Xorps
+		| Mnemonic::Xorpd
+		| Mnemonic::Vpand
+		| Mnemonic::Vpandd
+		| Mnemonic::Vpandq
+		| Mnemonic::Vpandn
+		| Mnemonic::Vpandnd
+		| Mnemonic::Vpandnq
+		| Mnemonic::Vpor
+		| Mnemonic::Vpord
+		| Mnemonic::Vporq
+		| Mnemonic::Vpxor
+		| Mnemonic::Vpxord
+		| Mnemonic::Vpxorq
+		| Mnemonic::Vpternlogd
+		| Mnemonic::Vpternlogq
+		| Mnemonic::Vandps
+		| Mnemonic::Vandpd
+		| Mnemonic::Vandnps
+		| Mnemonic::Vandnpd
+		| Mnemonic::Vorps
+		| Mnemonic::Vorpd
+		| Mnemonic::Vxorps
+		| Mnemonic::Vxorpd
+		| Mnemonic::Blendps
+		| Mnemonic::Blendpd
+		| Mnemonic::Vblendps
+		| Mnemonic::Vblendpd
+		| Mnemonic::Vpblendd
+		| Mnemonic::Vblendmps
+		| Mnemonic::Vblendmpd
+		| Mnemonic::Vpblendmd
+		| Mnemonic::Vpblendmq => PerfClass::VecLogic,
+		Mnemonic::Paddb
+		| Mnemonic::Paddw
+		| Mnemonic::Paddd
+		| Mnemonic::Paddq
+		| Mnemonic::Paddsb
+		| Mnemonic::Paddsw
+		| Mnemonic::Paddusb
+		| Mnemonic::Paddusw
+		| Mnemonic::Psubb
+		| Mnemonic::Psubw
+		| Mnemonic::Psubd
+		| Mnemonic::Psubq
+		| Mnemonic::Psubsb
+		| Mnemonic::Psubsw
+		| Mnemonic::Psubusb
+		| Mnemonic::Psubusw
+		| Mnemonic::Pcmpeqb
+		| Mnemonic::Pcmpeqw
+		| Mnemonic::Pcmpeqd
+		| Mnemonic::Pcmpeqq
+		| Mnemonic::Pcmpgtb
+		| Mnemonic::Pcmpgtw
+		| Mnemonic::Pcmpgtd
+		| Mnemonic::Pminsb
+		| Mnemonic::Pminsw
+		| Mnemonic::Pminsd
+		| Mnemonic::Pminub
+		| Mnemonic::Pminuw
+		| Mnemonic::Pminud
+		| Mnemonic::Pmaxsb
+		| Mnemonic::Pmaxsw
+		| Mnemonic::Pmaxsd
+		| Mnemonic::Pmaxub
+		| Mnemonic::Pmaxuw
+		| Mnemonic::Pmaxud
+		| Mnemonic::Pavgb
+		| Mnemonic::Pavgw
+		| Mnemonic::Pabsb
+		| Mnemonic::Pabsw
+		| Mnemonic::Pabsd
+		| Mnemonic::Psignb
+		| Mnemonic::Psignw
+		| Mnemonic::Psignd
+		| Mnemonic::Pblendw
+		| Mnemonic::Vpaddb
+		| Mnemonic::Vpaddw
+		| Mnemonic::Vpaddd
+		| Mnemonic::Vpaddq
+		| Mnemonic::Vpaddsb
+		| Mnemonic::Vpaddsw
+		| Mnemonic::Vpaddusb
+		| Mnemonic::Vpaddusw
+		| Mnemonic::Vpsubb
+		| Mnemonic::Vpsubw
+		| Mnemonic::Vpsubd
+		| Mnemonic::Vpsubq
+		| Mnemonic::Vpsubsb
+		| Mnemonic::Vpsubsw
+		| Mnemonic::Vpsubusb
+		| Mnemonic::Vpsubusw
+		| Mnemonic::Vpcmpeqb
+		| Mnemonic::Vpcmpeqw
+		| Mnemonic::Vpcmpeqd
+		| Mnemonic::Vpcmpeqq
+		| Mnemonic::Vpcmpgtb
+		| Mnemonic::Vpcmpgtw
+		| Mnemonic::Vpcmpgtd
+		| Mnemonic::Vpminsb
+		| Mnemonic::Vpminsw
+		| Mnemonic::Vpminsd
+		| Mnemonic::Vpminsq
+		| Mnemonic::Vpminub
+		| Mnemonic::Vpminuw
+		| Mnemonic::Vpminud
+		| Mnemonic::Vpminuq
+		| Mnemonic::Vpmaxsb
+		| Mnemonic::Vpmaxsw
+		| Mnemonic::Vpmaxsd
+		| Mnemonic::Vpmaxsq
+		| Mnemonic::Vpmaxub
+		| Mnemonic::Vpmaxuw
+		| Mnemonic::Vpmaxud
+		| Mnemonic::Vpmaxuq
+		| Mnemonic::Vpavgb
+		| Mnemonic::Vpavgw
+		| Mnemonic::Vpabsb
+		| Mnemonic::Vpabsw
+		| Mnemonic::Vpabsd
+		| Mnemonic::Vpabsq
+		| Mnemonic::Vpsignb
+		| Mnemonic::Vpsignw
+		| Mnemonic::Vpsignd
+		| Mnemonic::Vpblendw => PerfClass::VecIntAlu,
+		Mnemonic::Psllw
+		| Mnemonic::Pslld
+		| Mnemonic::Psllq
+		| Mnemonic::Psrlw
+		| Mnemonic::Psrld
+		| Mnemonic::Psrlq
+		| Mnemonic::Psraw
+		| Mnemonic::Psrad
+		| Mnemonic::Vpsllw
+		| Mnemonic::Vpslld
+		| Mnemonic::Vpsllq
+		| Mnemonic::Vpsrlw
+		| Mnemonic::Vpsrld
+		| Mnemonic::Vpsrlq
+		| Mnemonic::Vpsraw
+		| Mnemonic::Vpsrad
+		| Mnemonic::Vpsraq
+		| Mnemonic::Vpsllvd
+		| Mnemonic::Vpsllvq
+		| Mnemonic::Vpsrlvd
+		| Mnemonic::Vpsrlvq
+		| Mnemonic::Vpsravd
+		| Mnemonic::Vprold
+		| Mnemonic::Vprolq
+		| Mnemonic::Vprord
+		| Mnemonic::Vprorq => PerfClass::VecShift,
+		Mnemonic::Pshufb
+		| Mnemonic::Pshufd
+		| Mnemonic::Pshufhw
+		| Mnemonic::Pshuflw
+		| Mnemonic::Punpcklbw
+		| Mnemonic::Punpcklwd
+		| Mnemonic::Punpckldq
+		| Mnemonic::Punpcklqdq
+		| Mnemonic::Punpckhbw
+		| Mnemonic::Punpckhwd
+		| Mnemonic::Punpckhdq
+		| Mnemonic::Punpckhqdq
+		| Mnemonic::Packsswb
+		| Mnemonic::Packssdw
+		| Mnemonic::Packuswb
+		| Mnemonic::Packusdw
+		| Mnemonic::Palignr
+		| Mnemonic::Pslldq
+		| Mnemonic::Psrldq
+		| Mnemonic::Shufps
+		| Mnemonic::Shufpd
+		| Mnemonic::Unpcklps
+		| Mnemonic::Unpcklpd
+		| Mnemonic::Unpckhps
+		| Mnemonic::Unpckhpd
+		| Mnemonic::Movhlps
+		| Mnemonic::Movlhps
+		| Mnemonic::Movddup
+		| Mnemonic::Movshdup
+		| Mnemonic::Movsldup
+		| Mnemonic::Insertps
+		| Mnemonic::Vpshufb
+		| Mnemonic::Vpshufd
+		| Mnemonic::Vpshufhw
+		| Mnemonic::Vpshuflw
+		| Mnemonic::Vpunpcklbw
+		| Mnemonic::Vpunpcklwd
+		| Mnemonic::Vpunpckldq
+		| Mnemonic::Vpunpcklqdq
+		| Mnemonic::Vpunpckhbw
+		| Mnemonic::Vpunpckhwd
+		| Mnemonic::Vpunpckhdq
+		| Mnemonic::Vpunpckhqdq
+		| Mnemonic::Vpacksswb
+		| Mnemonic::Vpackssdw
+		| Mnemonic::Vpackuswb
+		| Mnemonic::Vpackusdw
+		| Mnemonic::Vpalignr
+		| Mnemonic::Vpslldq
+		| Mnemonic::Vpsrldq
+		| Mnemonic::Vshufps
+		| Mnemonic::Vshufpd
+		| Mnemonic::Vunpcklps
+		| Mnemonic::Vunpcklpd
+		| Mnemonic::Vunpckhps
+		| Mnemonic::Vunpckhpd
+		| Mnemonic::Vmovhlps
+		| Mnemonic::Vmovlhps
+		| Mnemonic::Vmovddup
+		| Mnemonic::Vmovshdup
+		| Mnemonic::Vmovsldup
+		| Mnemonic::Vinsertps
+		| Mnemonic::Vpermilps
+		| Mnemonic::Vpermilpd => PerfClass::VecShuffle,
+		Mnemonic::Vpermd
+		| Mnemonic::Vpermq
+		| Mnemonic::Vpermps
+		| Mnemonic::Vpermpd
+		| Mnemonic::Vperm2i128
+		| Mnemonic::Vperm2f128
+		| Mnemonic::Vinserti128
+		| Mnemonic::Vinsertf128
+		| Mnemonic::Vextracti128
+		| Mnemonic::Vextractf128
+		| Mnemonic::Vpmovzxbw
+		| Mnemonic::Vpmovzxwd
+		| Mnemonic::Vpmovzxdq
+		| Mnemonic::Vpmovsxbw
+		| Mnemonic::Vpmovsxwd
+		| Mnemonic::Vpmovsxdq
+		| Mnemonic::Pmovzxbw
+		| Mnemonic::Pmovzxwd
+		| Mnemonic::Pmovzxdq
+		| Mnemonic::Pmovsxbw
+		| Mnemonic::Pmovsxwd
+		| Mnemonic::Pmovsxdq => PerfClass::VecLaneCross,
+		Mnemonic::Pmullw
+		| Mnemonic::Pmulhw
+		| Mnemonic::Pmulhuw
+		| Mnemonic::Pmulhrsw
+		| Mnemonic::Pmuludq
+		| Mnemonic::Pmuldq
+		| Mnemonic::Pmaddwd
+		| Mnemonic::Pmaddubsw
+		| Mnemonic::Psadbw
+		| Mnemonic::Vpmullw
+		| Mnemonic::Vpmulhw
+		| Mnemonic::Vpmulhuw
+		| Mnemonic::Vpmulhrsw
+		| Mnemonic::Vpmuludq
+		| Mnemonic::Vpmuldq
+		| Mnemonic::Vpmaddwd
+		| Mnemonic::Vpmaddubsw
+		| Mnemonic::Vpsadbw
+		| Mnemonic::Vpdpbusd
+		| Mnemonic::Vpdpbusds
+		| Mnemonic::Vpdpwssd
+		| Mnemonic::Vpdpwssds => PerfClass::VecIntMul,
+		Mnemonic::Pmulld | Mnemonic::Vpmulld => PerfClass::VecIntMul32,
+		Mnemonic::Addps
+		| Mnemonic::Addpd
+		| Mnemonic::Addss
+		| Mnemonic::Addsd
+		| Mnemonic::Subps
+		| Mnemonic::Subpd
+		| Mnemonic::Subss
+		| Mnemonic::Subsd
+		| Mnemonic::Minps
+		| Mnemonic::Minpd
+		| Mnemonic::Minss
+		| Mnemonic::Minsd
+		| Mnemonic::Maxps
+		| Mnemonic::Maxpd
+		| Mnemonic::Maxss
+		| Mnemonic::Maxsd
+		| Mnemonic::Cmpps
+		| Mnemonic::Cmppd
+		| Mnemonic::Cmpss
+		| Mnemonic::Cmpsd
+		| Mnemonic::Addsubps
+		| Mnemonic::Addsubpd
+		| Mnemonic::Vaddps
+		| Mnemonic::Vaddpd
+		| Mnemonic::Vaddss
+		| Mnemonic::Vaddsd
+		| Mnemonic::Vsubps
+		| Mnemonic::Vsubpd
+		| Mnemonic::Vsubss
+		| Mnemonic::Vsubsd
+		| Mnemonic::Vminps
+		| Mnemonic::Vminpd
+		| Mnemonic::Vminss
+		| Mnemonic::Vminsd
+		| Mnemonic::Vmaxps
+		| Mnemonic::Vmaxpd
+		| Mnemonic::Vmaxss
+		| Mnemonic::Vmaxsd
+		| Mnemonic::Vcmpps
+		| Mnemonic::Vcmppd
+		| Mnemonic::Vcmpss
+		| Mnemonic::Vcmpsd
+		| Mnemonic::Vaddsubps
+		| Mnemonic::Vaddsubpd => PerfClass::FpAdd,
+		Mnemonic::Mulps
+		| Mnemonic::Mulpd
+		| Mnemonic::Mulss
+		| Mnemonic::Mulsd
+		| Mnemonic::Vmulps
+		| Mnemonic::Vmulpd
+		| Mnemonic::Vmulss
+		| Mnemonic::Vmulsd => PerfClass::FpMul,
+		Mnemonic::Vfmadd132ps
+		| Mnemonic::Vfmadd132pd
+		| Mnemonic::Vfmadd132ss
+		| Mnemonic::Vfmadd132sd
+		| Mnemonic::Vfmadd213ps
+		| Mnemonic::Vfmadd213pd
+		| Mnemonic::Vfmadd213ss
+		| Mnemonic::Vfmadd213sd
+		| Mnemonic::Vfmadd231ps
+		| Mnemonic::Vfmadd231pd
+		| Mnemonic::Vfmadd231ss
+		| Mnemonic::Vfmadd231sd
+		| Mnemonic::Vfmsub132ps
+		| Mnemonic::Vfmsub132pd
+		| Mnemonic::Vfmsub132ss
+		| Mnemonic::Vfmsub132sd
+		| Mnemonic::Vfmsub213ps
+		| Mnemonic::Vfmsub213pd
+		| Mnemonic::Vfmsub213ss
+		| Mnemonic::Vfmsub213sd
+		| Mnemonic::Vfmsub231ps
+		| Mnemonic::Vfmsub231pd
+		| Mnemonic::Vfmsub231ss
+		| Mnemonic::Vfmsub231sd
+		| Mnemonic::Vfnmadd132ps
+		| Mnemonic::Vfnmadd132pd
+		| Mnemonic::Vfnmadd132ss
+		| Mnemonic::Vfnmadd132sd
+		| Mnemonic::Vfnmadd213ps
+		| Mnemonic::Vfnmadd213pd
+		| Mnemonic::Vfnmadd213ss
+		| Mnemonic::Vfnmadd213sd
+		| Mnemonic::Vfnmadd231ps
+		| Mnemonic::Vfnmadd231pd
+		| Mnemonic::Vfnmadd231ss
+		| Mnemonic::Vfnmadd231sd
+		| Mnemonic::Vfnmsub132ps
+		| Mnemonic::Vfnmsub132pd
+		| Mnemonic::Vfnmsub132ss
+		| Mnemonic::Vfnmsub132sd
+		| Mnemonic::Vfnmsub213ps
+		| Mnemonic::Vfnmsub213pd
+		| Mnemonic::Vfnmsub213ss
+		| Mnemonic::Vfnmsub213sd
+		| Mnemonic::Vfnmsub231ps
+		| Mnemonic::Vfnmsub231pd
+		| Mnemonic::Vfnmsub231ss
+		| Mnemonic::Vfnmsub231sd => PerfClass::Fma,
+		Mnemonic::Divps | Mnemonic::Divss | Mnemonic::Vdivps | Mnemonic::Vdivss => PerfClass::FpDiv32,
+		Mnemonic::Divpd | Mnemonic::Divsd | Mnemonic::Vdivpd | Mnemonic::Vdivsd => PerfClass::FpDiv64,
+		Mnemonic::Sqrtps | Mnemonic::Sqrtss | Mnemonic::Vsqrtps | Mnemonic::Vsqrtss => PerfClass::FpSqrt32,
+		Mnemonic::Sqrtpd | Mnemonic::Sqrtsd | Mnemonic::Vsqrtpd | Mnemonic::Vsqrtsd => PerfClass::FpSqrt64,
+		Mnemonic::Cvtdq2ps
+		| Mnemonic::Cvtps2dq
+		| Mnemonic::Cvttps2dq
+		| Mnemonic::Cvtdq2pd
+		| Mnemonic::Cvtpd2dq
+		| Mnemonic::Cvttpd2dq
+		| Mnemonic::Cvtps2pd
+		| Mnemonic::Cvtpd2ps
+		| Mnemonic::Cvtss2sd
+		| Mnemonic::Cvtsd2ss
+		| Mnemonic::Cvtsi2ss
+		| Mnemonic::Cvtsi2sd
+		| Mnemonic::Cvtss2si
+		| Mnemonic::Cvtsd2si
+		| Mnemonic::Cvttss2si
+		| Mnemonic::Cvttsd2si
+		| Mnemonic::Roundps
+		| Mnemonic::Roundpd
+		| Mnemonic::Roundss
+		| Mnemonic::Roundsd
+		| Mnemonic::Movmskps
+		| Mnemonic::Movmskpd
+		| Mnemonic::Pmovmskb
+		| Mnemonic::Ptest
+		| Mnemonic::Comiss
+		| Mnemonic::Comisd
+		| Mnemonic::Ucomiss
+		| Mnemonic::Ucomisd
+		| Mnemonic::Pextrb
+		| Mnemonic::Pextrw
+		| Mnemonic::Pextrd
+		| Mnemonic::Pextrq
+		| Mnemonic::Pinsrb
+		| Mnemonic::Pinsrw
+		| Mnemonic::Pinsrd
+		| Mnemonic::Pinsrq
+		| Mnemonic::Vcvtdq2ps
+		| Mnemonic::Vcvtps2dq
+		| Mnemonic::Vcvttps2dq
+		| Mnemonic::Vcvtdq2pd
+		| Mnemonic::Vcvtpd2dq
+		| Mnemonic::Vcvttpd2dq
+		| Mnemonic::Vcvtps2pd
+		| Mnemonic::Vcvtpd2ps
+		| Mnemonic::Vcvtss2sd
+		| Mnemonic::Vcvtsd2ss
+		| Mnemonic::Vcvtsi2ss
+		| Mnemonic::Vcvtsi2sd
+		| Mnemonic::Vcvtss2si
+		| Mnemonic::Vcvtsd2si
+		| Mnemonic::Vcvttss2si
+		| Mnemonic::Vcvttsd2si
+		| Mnemonic::Vcvtph2ps
+		| Mnemonic::Vcvtps2ph
+		| Mnemonic::Vroundps
+		| Mnemonic::Vroundpd
+		| Mnemonic::Vroundss
+		| Mnemonic::Vroundsd
+		| Mnemonic::Vmovmskps
+		| Mnemonic::Vmovmskpd
+		| Mnemonic::Vpmovmskb
+		| Mnemonic::Vptest
+		| Mnemonic::Vcomiss
+		| Mnemonic::Vcomisd
+		| Mnemonic::Vucomiss
+		| Mnemonic::Vucomisd
+		| Mnemonic::Vpextrb
+		| Mnemonic::Vpextrw
+		| Mnemonic::Vpextrd
+		| Mnemonic::Vpextrq
+		| Mnemonic::Vpinsrb
+		| Mnemonic::Vpinsrw
+		| Mnemonic::Vpinsrd
+		| Mnemonic::Vpinsrq => PerfClass::Convert,
+		Mnemonic::Aesenc
+		| Mnemonic::Aesenclast
+		| Mnemonic::Aesdec
+		| Mnemonic::Aesdeclast
+		| Mnemonic::Vaesenc
+		| Mnemonic::Vaesenclast
+		| Mnemonic::Vaesdec
+		| Mnemonic::Vaesdeclast => PerfClass::Aes,
+		Mnemonic::Pclmulqdq | Mnemonic::Vpclmulqdq => PerfClass::Pclmul,
+		Mnemonic::Crc32 => PerfClass::Crc32,
+		_ => return None,
+	})
+}
+
+#[must_use]
+pub(super) fn get_mem_access(instruction: &Instruction, class: PerfClass) -> MemAccess {
+	let mut first_mem_op = None;
+	for i in 0..instruction.op_count() {
+		if instruction.op_kind(i) == OpKind::Memory {
+			first_mem_op = Some(i);
+			break;
+		}
+	}
+	match first_mem_op {
+		None => MemAccess::None,
+		// LEA and NOP don't access memory
+		Some(_) if class == PerfClass::Lea || class == PerfClass::Nop => MemAccess::None,
+		// The stack access is part of the class
+		Some(_) if class == PerfClass::Push => MemAccess::Load,
+		Some(_) if class == PerfClass::Pop => MemAccess::Store,
+		Some(0) => match class {
+			PerfClass::Move | PerfClass::VecMove | PerfClass::Setcc | PerfClass::Convert | PerfClass::VecLaneCross => MemAccess::Store,
+			PerfClass::IndirectBranch | PerfClass::Call | PerfClass::Div32 | PerfClass::Div64 | PerfClass::MulWide => MemAccess::Load,
+			PerfClass::Alu => match instruction.mnemonic() {
+				Mnemonic::Cmp | Mnemonic::Test | Mnemonic::Bt => MemAccess::Load,
+				Mnemonic::Movbe => MemAccess::Store,
+				_ => MemAccess::LoadStore,
+			},
+			_ => MemAccess::LoadStore,
+		},
+		Some(_) => MemAccess::Load,
+	}
+}
+
+// Zero idioms, eg. `xor eax,eax`, don't depend on the old value and are handled by the renamer
+#[must_use]
+pub(super) fn is_zero_idiom(instruction: &Instruction) -> bool {
+	match instruction.mnemonic() {
+		Mnemonic::Xor
+		| Mnemonic::Sub
+		| Mnemonic::Pxor
+		| Mnemonic::Xorps
+		| Mnemonic::Xorpd
+		| Mnemonic::Psubb
+		| Mnemonic::Psubw
+		| Mnemonic::Psubd
+		| Mnemonic::Psubq
+		| Mnemonic::Pcmpgtb
+		| Mnemonic::Pcmpgtw
+		| Mnemonic::Pcmpgtd => {
+			instruction.op_count() == 2
+				&& instruction.op0_kind() == OpKind::Register
+				&& instruction.op1_kind() == OpKind::Register
+				&& instruction.op0_register() == instruction.op1_register()
+		}
+		Mnemonic::Vpxor
+		| Mnemonic::Vpxord
+		| Mnemonic::Vpxorq
+		| Mnemonic::Vxorps
+		| Mnemonic::Vxorpd
+		| Mnemonic::Vpsubb
+		| Mnemonic::Vpsubw
+		| Mnemonic::Vpsubd
+		| Mnemonic::Vpsubq => {
+			instruction.op_count() == 3
+				&& instruction.op1_kind() == OpKind::Register
+				&& instruction.op2_kind() == OpKind::Register
+				&& instruction.op1_register() == instruction.op2_register()
+		}
+		_ => false,
+	}
+}
+
+// movs, cmps, etc. (but not xlat)
+fn is_string_instruction(op_code: &OpCodeInfo) -> bool {
+	op_code.op_kinds().iter().any(|&kind| matches!(kind, OpCodeOperandKind::seg_rSI | OpCodeOperandKind::es_rDI | OpCodeOperandKind::seg_rDI))
+}
+
+fn is_near_branch(op_code: &OpCodeInfo) -> bool {
+	op_code.op_count() == 1
+		&& matches!(
+			op_code.op_kind(0),
+			OpCodeOperandKind::br16_1
+				| OpCodeOperandKind::br32_1
+				| OpCodeOperandKind::br64_1
+				| OpCodeOperandKind::br16_2
+				| OpCodeOperandKind::br32_4
+				| OpCodeOperandKind::br64_4
+		)
+}
+
+fn is_near_indirect_branch(op_code: &OpCodeInfo) -> bool {
+	op_code.op_count() == 1
+		&& matches!(op_code.op_kind(0), OpCodeOperandKind::r16_or_mem | OpCodeOperandKind::r32_or_mem | OpCodeOperandKind::r64_or_mem)
+}
+
+// The operand can be a GPR (the caller checks if it's a memory operand)
+fn is_gpr_operand(kind: OpCodeOperandKind) -> bool {
+	matches!(
+		kind,
+		OpCodeOperandKind::r32_or_mem
+			| OpCodeOperandKind::r64_or_mem
+			| OpCodeOperandKind::r32_reg
+			| OpCodeOperandKind::r32_rm
+			| OpCodeOperandKind::r64_reg
+			| OpCodeOperandKind::r64_rm
+	)
+}
+
+fn is_segment_register_op0(op_code: &OpCodeInfo) -> bool {
+	op_code.op_count() != 0
+		&& matches!(
+			op_code.op_kind(0),
+			OpCodeOperandKind::es
+				| OpCodeOperandKind::cs
+				| OpCodeOperandKind::ss
+				| OpCodeOperandKind::ds
+				| OpCodeOperandKind::fs
+				| OpCodeOperandKind::gs
+		)
+}
+
+fn is_segment_or_control_move(op_code: &OpCodeInfo) -> bool {
+	op_code
+		.op_kinds()
+		.iter()
+		.any(|&kind| matches!(kind, OpCodeOperandKind::seg_reg | OpCodeOperandKind::cr_reg | OpCodeOperandKind::dr_reg | OpCodeOperandKind::tr_reg))
+}
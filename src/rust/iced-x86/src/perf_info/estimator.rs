@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::iced_constants::IcedConstants;
+use crate::perf_info::class::{classify, PerfClass};
+use crate::perf_info::{get_perf_estimate, Microarchitecture, PerfEstimate};
+use crate::{Instruction, InstructionInfoFactory, OpAccess, Register};
+use alloc::vec::Vec;
+use core::fmt;
+
+// Number of simulated iterations of the block when calculating the loop carried dependency chains
+const ITERATIONS: u32 = 8;
+const MAX_PORTS: usize = 16;
+
+/// What limits the throughput of a basic block, see [`ThroughputEstimate`]
+///
+/// [`ThroughputEstimate`]: struct.ThroughputEstimate.html
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub enum Bottleneck {
+	/// The front end can't issue the uops fast enough
+	Issue,
+	/// One or more execution ports (or a non-pipelined unit, eg. the divider) are saturated
+	Ports,
+	/// A loop carried dependency chain
+	Dependencies,
+}
+
+impl Default for Bottleneck {
+	#[inline]
+	fn default() -> Self {
+		Bottleneck::Issue
+	}
+}
+
+impl fmt::Display for Bottleneck {
+	#[inline]
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Bottleneck::Issue => "issue",
+			Bottleneck::Ports => "ports",
+			Bottleneck::Dependencies => "dependencies",
+		})
+	}
+}
+
+/// The estimated throughput of a basic block, see [`ThroughputEstimator`]
+///
+/// [`ThroughputEstimator`]: struct.ThroughputEstimator.html
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(not(feature = "exhaustive_enums"), non_exhaustive)]
+pub struct ThroughputEstimate {
+	/// Estimated number of cycles per iteration if the block is executed in a loop. It's the max of
+	/// [`issue_cycles`], [`port_cycles`] and [`dependency_cycles`].
+	///
+	/// [`issue_cycles`]: #structfield.issue_cycles
+	/// [`port_cycles`]: #structfield.port_cycles
+	/// [`dependency_cycles`]: #structfield.dependency_cycles
+	pub cycles: f64,
+	/// What limits the throughput
+	pub bottleneck: Bottleneck,
+	/// Total number of fused domain uops
+	pub uops: u32,
+	/// Cycles needed to issue all uops
+	pub issue_cycles: f64,
+	/// Cycles needed by the most used execution port or non-pipelined unit
+	pub port_cycles: f64,
+	/// Length of the longest loop carried dependency chain (registers and flags) in cycles per iteration
+	pub dependency_cycles: f64,
+	/// Length of the longest dependency chain in cycles if the block is executed once
+	pub latency: u32,
+	/// Cycles used by each execution port per iteration, see [`Microarchitecture::port_names()`]
+	///
+	/// [`Microarchitecture::port_names()`]: enum.Microarchitecture.html#method.port_names
+	pub port_pressure: Vec<f64>,
+	/// Number of instructions that were ignored because they're unknown or not supported by the microarchitecture
+	pub unknown_instructions: usize,
+}
+
+/// Estimates the throughput of a basic block, similar to `llvm-mca`.
+///
+/// The block is assumed to be executed in a loop with all data in the L1 cache and without any branch mispredictions.
+/// It models the front end issue width, execution port pressure and dependency chains through registers and flags.
+/// Dependencies through memory are ignored.
+///
+/// # Examples
+///
+/// ```
+/// use iced_x86::*;
+/// use iced_x86::perf_info::*;
+///
+/// // loop: add rax,[rsi]
+/// //       add rsi,8
+/// //       dec rcx
+/// //       jne loop
+/// let bytes = b"\x48\x03\x06\x48\x83\xC6\x08\x48\xFF\xC9\x75\xF4";
+/// let instructions: Vec<_> = Decoder::new(64, bytes, DecoderOptions::NONE).into_iter().collect();
+///
+/// let mut estimator = ThroughputEstimator::new(Microarchitecture::Skylake);
+/// let estimate = estimator.estimate(&instructions);
+/// assert_eq!(estimate.uops, 4);
+/// assert_eq!(estimate.cycles, 1.0);
+/// assert_eq!(estimate.bottleneck, Bottleneck::Issue);
+/// ```
+#[allow(missing_debug_implementations)]
+pub struct ThroughputEstimator {
+	uarch: Microarchitecture,
+	factory: InstructionInfoFactory,
+}
+
+impl ThroughputEstimator {
+	/// Creates a new instance
+	///
+	/// # Arguments
+	///
+	/// - `uarch`: Microarchitecture
+	#[must_use]
+	#[inline]
+	pub fn new(uarch: Microarchitecture) -> Self {
+		Self { uarch, factory: InstructionInfoFactory::new() }
+	}
+
+	/// Gets the microarchitecture
+	#[must_use]
+	#[inline]
+	pub const fn microarchitecture(&self) -> Microarchitecture {
+		self.uarch
+	}
+
+	/// Estimates the throughput of a basic block
+	///
+	/// # Arguments
+	///
+	/// - `instructions`: All instructions in the basic block
+	#[must_use]
+	#[allow(clippy::missing_inline_in_public_items)]
+	pub fn estimate(&mut self, instructions: &[Instruction]) -> ThroughputEstimate {
+		let info = self.uarch.info();
+		let profile = self.uarch.profile_ref();
+		let port_count = self.uarch.port_names().len();
+		let mut result = ThroughputEstimate { port_pressure: vec![0.0; port_count], ..Default::default() };
+
+		let mut infos: Vec<Option<PerfEstimate>> = Vec::with_capacity(instructions.len());
+		let mut port_pressure = [0u32; MAX_PORTS];
+		let mut class_cycles = [0u32; PerfClass::COUNT];
+		for instruction in instructions {
+			let perf_info = if profile.supports(instruction) { get_perf_estimate(info, instruction) } else { None };
+			if let Some(perf_info) = &perf_info {
+				result.uops += perf_info.uops();
+				for usage in perf_info.port_usage() {
+					// Each uop goes to the least used port it can execute on
+					for _ in 0..usage.count() {
+						let mut best = None;
+						for port in 0..port_count {
+							if (usage.ports() & (1 << port)) != 0 && best.map_or(true, |best: usize| port_pressure[port] < port_pressure[best]) {
+								best = Some(port);
+							}
+						}
+						if let Some(best) = best {
+							port_pressure[best] += 1;
+						}
+					}
+				}
+				if let Some(class) = classify(instruction) {
+					class_cycles[class as usize] += perf_info.rthroughput as u32;
+				}
+			} else {
+				result.unknown_instructions += 1;
+			}
+			infos.push(perf_info);
+		}
+
+		for (pressure, &cycles) in result.port_pressure.iter_mut().zip(port_pressure.iter()) {
+			*pressure = cycles as f64;
+		}
+		let max_port_cycles = port_pressure.iter().copied().max().unwrap_or(0) as f64;
+		// Non-pipelined units (eg. the divider) and instructions with a lower throughput than their port usage
+		let max_class_cycles = class_cycles.iter().copied().max().unwrap_or(0) as f64 / 100.0;
+		result.port_cycles = if max_port_cycles > max_class_cycles { max_port_cycles } else { max_class_cycles };
+		result.issue_cycles = result.uops as f64 / self.uarch.issue_width() as f64;
+
+		let (latency, dependency_cycles) = self.simulate_dependencies(instructions, &infos);
+		result.latency = latency;
+		result.dependency_cycles = dependency_cycles;
+
+		result.bottleneck = Bottleneck::Issue;
+		result.cycles = result.issue_cycles;
+		if result.port_cycles > result.cycles {
+			result.bottleneck = Bottleneck::Ports;
+			result.cycles = result.port_cycles;
+		}
+		if result.dependency_cycles > result.cycles {
+			result.bottleneck = Bottleneck::Dependencies;
+			result.cycles = result.dependency_cycles;
+		}
+		result
+	}
+
+	// Returns the latency of one iteration and the number of cycles per iteration of the longest loop carried dependency chain
+	fn simulate_dependencies(&mut self, instructions: &[Instruction], infos: &[Option<PerfEstimate>]) -> (u32, f64) {
+		let mut ready = [0u32; IcedConstants::REGISTER_ENUM_COUNT];
+		let mut flags_ready = 0u32;
+		let mut end_time = 0u32;
+		let mut first_end_time = 0u32;
+		let mut mid_end_time = 0u32;
+		for iteration in 1..=ITERATIONS {
+			for (instruction, perf_info) in instructions.iter().zip(infos.iter()) {
+				let perf_info = match perf_info {
+					Some(perf_info) => perf_info,
+					None => continue,
+				};
+				// The stack engine handles the RSP updates of PUSH/POP/CALL/RET
+				let ignore_rsp = instruction.is_stack_instruction();
+				let instr_info = self.factory.info(instruction);
+				let used_registers = instr_info.used_registers();
+
+				// Only the address registers must wait for the load
+				let mut address_ready = 0;
+				for mem in instr_info.used_memory() {
+					for reg in [mem.base(), mem.index()] {
+						if reg != Register::None && !(ignore_rsp && reg.full_register() == Register::RSP) {
+							address_ready = address_ready.max(ready[reg.full_register() as usize]);
+						}
+					}
+				}
+				let mut start = 0;
+				if !perf_info.is_zero_idiom() {
+					for used_reg in used_registers {
+						let reg = used_reg.register().full_register();
+						if (ignore_rsp && reg == Register::RSP) || !is_read(used_reg.access()) {
+							continue;
+						}
+						start = start.max(ready[reg as usize]);
+					}
+					if instruction.rflags_read() != 0 {
+						start = start.max(flags_ready);
+					}
+				}
+
+				let latency = perf_info.latency();
+				let done = (start + latency - perf_info.load_latency()).max(address_ready + latency);
+				for used_reg in used_registers {
+					let reg = used_reg.register().full_register();
+					if (ignore_rsp && reg == Register::RSP) || !is_write(used_reg.access()) {
+						continue;
+					}
+					ready[reg as usize] = done;
+				}
+				if instruction.rflags_modified() != 0 {
+					flags_ready = done;
+				}
+				end_time = end_time.max(done);
+			}
+			if iteration == 1 {
+				first_end_time = end_time;
+			}
+			if iteration == ITERATIONS / 2 {
+				mid_end_time = end_time;
+			}
+		}
+		(first_end_time, (end_time - mid_end_time) as f64 / (ITERATIONS - ITERATIONS / 2) as f64)
+	}
+}
+
+#[inline]
+fn is_read(access: OpAccess) -> bool {
+	matches!(access, OpAccess::Read | OpAccess::CondRead | OpAccess::ReadWrite | OpAccess::ReadCondWrite)
+}
+
+#[inline]
+fn is_write(access: OpAccess) -> bool {
+	matches!(access, OpAccess::Write | OpAccess::CondWrite | OpAccess::ReadWrite | OpAccess::ReadCondWrite)
+}
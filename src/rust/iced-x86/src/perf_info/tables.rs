@@ -0,0 +1,420 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+// Approximate numbers for the register forms of each instruction class and of the instructions that differ from
+// their class (eg. 8-bit MUL, 256-bit FP divs). They're based on published measurements (uops.info, Agner Fog's
+// instruction tables) and are rounded to a typical value if they depend on the input values. Memory operands are
+// handled by the caller.
+
+use crate::perf_info::class::PerfClass;
+use crate::Code;
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct Entry {
+	pub(super) latency: u8,
+	// Reciprocal throughput * 100
+	pub(super) rthroughput: u16,
+	pub(super) uops: u8,
+	// (count, ports)
+	pub(super) ports: &'static [(u8, u16)],
+}
+
+const fn e(latency: u8, rthroughput: u16, uops: u8, ports: &'static [(u8, u16)]) -> Entry {
+	Entry { latency, rthroughput, uops, ports }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub(super) struct UarchInfo {
+	pub(super) port_names: &'static [&'static str],
+	pub(super) issue_width: u32,
+	pub(super) load_latency: u8,
+	pub(super) vec_load_latency: u8,
+	pub(super) load_ports: u16,
+	pub(super) load_rthroughput: u16,
+	pub(super) store_address_ports: u16,
+	pub(super) store_data_ports: u16,
+	pub(super) store_rthroughput: u16,
+	pub(super) profile_name: &'static str,
+	// true if 512-bit instructions use each unit twice (Zen 4)
+	pub(super) zmm_double_pumped: bool,
+	pub(super) zmm_ports: fn(u16) -> u16,
+	pub(super) entry: fn(PerfClass) -> Entry,
+	// Instructions that don't use the numbers of their class
+	pub(super) code_entry: fn(Code) -> Option<Entry>,
+}
+
+impl UarchInfo {
+	#[inline]
+	pub(super) fn get_entry(&self, code: Code, class: PerfClass) -> Entry {
+		(self.code_entry)(code).unwrap_or_else(|| (self.entry)(class))
+	}
+}
+
+mod intel {
+	use super::{e, Entry};
+	use crate::Code;
+
+	pub(super) const P0: u16 = 1 << 0;
+	pub(super) const P1: u16 = 1 << 1;
+	pub(super) const P2: u16 = 1 << 2;
+	pub(super) const P3: u16 = 1 << 3;
+	pub(super) const P4: u16 = 1 << 4;
+	pub(super) const P5: u16 = 1 << 5;
+	pub(super) const P6: u16 = 1 << 6;
+	pub(super) const P7: u16 = 1 << 7;
+	pub(super) const P8: u16 = 1 << 8;
+	pub(super) const P9: u16 = 1 << 9;
+	pub(super) const P10: u16 = 1 << 10;
+	pub(super) const P11: u16 = 1 << 11;
+	pub(super) const P01: u16 = P0 | P1;
+	pub(super) const P06: u16 = P0 | P6;
+	pub(super) const P15: u16 = P1 | P5;
+	pub(super) const P015: u16 = P0 | P1 | P5;
+	pub(super) const P0156: u16 = P0 | P1 | P5 | P6;
+
+	pub(super) static SKYLAKE_PORT_NAMES: [&str; 8] = ["p0", "p1", "p2", "p3", "p4", "p5", "p6", "p7"];
+	pub(super) static ICE_LAKE_PORT_NAMES: [&str; 10] = ["p0", "p1", "p2", "p3", "p4", "p5", "p6", "p7", "p8", "p9"];
+	pub(super) static GOLDEN_COVE_PORT_NAMES: [&str; 12] = ["p0", "p1", "p2", "p3", "p4", "p5", "p6", "p7", "p8", "p9", "p10", "p11"];
+
+	// 512-bit instructions: the vector ALU on port 1 is fused with port 0 and there's one FMA unit (port 0)
+	pub(super) fn client_zmm_ports(ports: u16) -> u16 {
+		if (ports & P1) == 0 {
+			ports
+		} else if (ports & (P0 | P5)) == 0 {
+			(ports & !P1) | P0
+		} else {
+			ports & !P1
+		}
+	}
+
+	// 512-bit instructions: port 0+1 are fused and there's a 2nd FMA unit on port 5
+	pub(super) fn server_zmm_ports(ports: u16) -> u16 {
+		if (ports & P1) == 0 {
+			ports
+		} else {
+			(ports & !P1) | P0 | P5
+		}
+	}
+
+	#[rustfmt::skip]
+	pub(super) fn code_entry(code: Code) -> Option<Entry> {
+		Some(match code {
+			// Only writes AX
+			Code::Mul_rm8 | Code::Imul_rm8 => e(3, 100, 1, &[(1, P1)]),
+			Code::Mul_rm64 | Code::Imul_rm64 => e(3, 100, 2, &[(1, P1), (1, P5)]),
+			// The 256-bit forms use the 128-bit divider twice
+			Code::VEX_Vdivps_ymm_ymm_ymmm256 | Code::EVEX_Vdivps_ymm_k1z_ymm_ymmm256b32 => e(11, 500, 1, &[(1, P0)]),
+			Code::VEX_Vdivpd_ymm_ymm_ymmm256 | Code::EVEX_Vdivpd_ymm_k1z_ymm_ymmm256b64 => e(14, 800, 1, &[(1, P0)]),
+			Code::VEX_Vsqrtps_ymm_ymmm256 | Code::EVEX_Vsqrtps_ymm_k1z_ymmm256b32 => e(12, 600, 1, &[(1, P0)]),
+			Code::VEX_Vsqrtpd_ymm_ymmm256 | Code::EVEX_Vsqrtpd_ymm_k1z_ymmm256b64 => e(16, 900, 1, &[(1, P0)]),
+			_ => return None,
+		})
+	}
+}
+
+mod amd {
+	use super::{e, Entry};
+	use crate::Code;
+
+	pub(super) const ALU0: u16 = 1 << 0;
+	pub(super) const ALU1: u16 = 1 << 1;
+	pub(super) const ALU2: u16 = 1 << 2;
+	pub(super) const ALU3: u16 = 1 << 3;
+	pub(super) const AGU0: u16 = 1 << 4;
+	pub(super) const AGU1: u16 = 1 << 5;
+	pub(super) const AGU2: u16 = 1 << 6;
+	pub(super) const FP0: u16 = 1 << 7;
+	pub(super) const FP1: u16 = 1 << 8;
+	pub(super) const FP2: u16 = 1 << 9;
+	pub(super) const FP3: u16 = 1 << 10;
+	pub(super) const ALU: u16 = ALU0 | ALU1 | ALU2 | ALU3;
+	pub(super) const ALU12: u16 = ALU1 | ALU2;
+	pub(super) const BRANCH: u16 = ALU0 | ALU3;
+	pub(super) const FP01: u16 = FP0 | FP1;
+	pub(super) const FP03: u16 = FP0 | FP3;
+	pub(super) const FP12: u16 = FP1 | FP2;
+	pub(super) const FP23: u16 = FP2 | FP3;
+	pub(super) const FP013: u16 = FP0 | FP1 | FP3;
+	pub(super) const FP0123: u16 = FP0 | FP1 | FP2 | FP3;
+
+	pub(super) static PORT_NAMES: [&str; 11] = ["ALU0", "ALU1", "ALU2", "ALU3", "AGU0", "AGU1", "AGU2", "FP0", "FP1", "FP2", "FP3"];
+
+	pub(super) fn zmm_ports(ports: u16) -> u16 {
+		ports
+	}
+
+	pub(super) fn code_entry(code: Code) -> Option<Entry> {
+		match code {
+			// Only writes AX
+			Code::Mul_rm8 | Code::Imul_rm8 => Some(e(3, 100, 1, &[(1, ALU1)])),
+			_ => None,
+		}
+	}
+}
+
+#[rustfmt::skip]
+fn skylake(class: PerfClass) -> Entry {
+	use self::intel::*;
+	const P23: u16 = P2 | P3;
+	const P237: u16 = P2 | P3 | P7;
+	match class {
+		PerfClass::Nop => e(0, 25, 1, &[]),
+		PerfClass::Move | PerfClass::Alu => e(1, 25, 1, &[(1, P0156)]),
+		PerfClass::AluCarry => e(1, 50, 1, &[(1, P06)]),
+		PerfClass::Lea => e(1, 50, 1, &[(1, P15)]),
+		PerfClass::Shift | PerfClass::Cmov | PerfClass::Setcc => e(1, 50, 1, &[(1, P06)]),
+		PerfClass::ShiftCl => e(2, 100, 3, &[(2, P06), (1, P0156)]),
+		PerfClass::Imul => e(3, 100, 1, &[(1, P1)]),
+		PerfClass::MulWide => e(4, 100, 2, &[(1, P1), (1, P5)]),
+		PerfClass::Div32 => e(26, 600, 10, &[(1, P0), (9, P0156)]),
+		PerfClass::Div64 => e(42, 2400, 36, &[(2, P0), (34, P0156)]),
+		PerfClass::BitCount | PerfClass::BitScan | PerfClass::Pdep | PerfClass::Crc32 => e(3, 100, 1, &[(1, P1)]),
+		PerfClass::Bmi => e(1, 50, 1, &[(1, P15)]),
+		PerfClass::Branch => e(1, 50, 1, &[(1, P06)]),
+		PerfClass::IndirectBranch => e(1, 200, 1, &[(1, P6)]),
+		PerfClass::Call => e(1, 200, 2, &[(1, P6), (1, P237), (1, P4)]),
+		PerfClass::Ret => e(1, 200, 2, &[(1, P6), (1, P23)]),
+		PerfClass::Push => e(1, 100, 1, &[(1, P237), (1, P4)]),
+		PerfClass::Pop => e(5, 50, 1, &[(1, P23)]),
+		PerfClass::VecMove | PerfClass::VecLogic | PerfClass::VecIntAlu => e(1, 33, 1, &[(1, P015)]),
+		PerfClass::VecShift => e(1, 50, 1, &[(1, P01)]),
+		PerfClass::VecShuffle => e(1, 100, 1, &[(1, P5)]),
+		PerfClass::VecLaneCross => e(3, 100, 1, &[(1, P5)]),
+		PerfClass::VecIntMul => e(5, 50, 1, &[(1, P01)]),
+		PerfClass::VecIntMul32 => e(10, 100, 2, &[(2, P01)]),
+		PerfClass::FpAdd | PerfClass::FpMul | PerfClass::Fma => e(4, 50, 1, &[(1, P01)]),
+		PerfClass::FpDiv32 => e(11, 300, 1, &[(1, P0)]),
+		PerfClass::FpDiv64 => e(14, 400, 1, &[(1, P0)]),
+		PerfClass::FpSqrt32 => e(12, 300, 1, &[(1, P0)]),
+		PerfClass::FpSqrt64 => e(16, 600, 1, &[(1, P0)]),
+		PerfClass::Convert => e(4, 100, 2, &[(1, P01), (1, P5)]),
+		PerfClass::Aes => e(4, 100, 1, &[(1, P0)]),
+		PerfClass::Pclmul => e(7, 100, 1, &[(1, P5)]),
+	}
+}
+
+#[rustfmt::skip]
+fn ice_lake(class: PerfClass) -> Entry {
+	use self::intel::*;
+	const P23: u16 = P2 | P3;
+	const P78: u16 = P7 | P8;
+	const P49: u16 = P4 | P9;
+	match class {
+		PerfClass::Div32 => e(12, 600, 4, &[(4, P0156)]),
+		PerfClass::Div64 => e(15, 1000, 4, &[(4, P0156)]),
+		PerfClass::Call => e(1, 200, 2, &[(1, P6), (1, P78), (1, P49)]),
+		PerfClass::Ret => e(1, 200, 2, &[(1, P6), (1, P23)]),
+		PerfClass::Push => e(1, 50, 1, &[(1, P78), (1, P49)]),
+		PerfClass::Aes => e(3, 50, 1, &[(1, P01)]),
+		PerfClass::Pclmul => e(6, 100, 1, &[(1, P5)]),
+		_ => skylake(class),
+	}
+}
+
+#[rustfmt::skip]
+fn golden_cove(class: PerfClass) -> Entry {
+	use self::intel::*;
+	const ALU: u16 = P0156 | P10;
+	const P23_11: u16 = P2 | P3 | P11;
+	const P78: u16 = P7 | P8;
+	const P49: u16 = P4 | P9;
+	match class {
+		PerfClass::Nop => e(0, 17, 1, &[]),
+		PerfClass::Move | PerfClass::Alu | PerfClass::Lea => e(1, 20, 1, &[(1, ALU)]),
+		PerfClass::ShiftCl => e(2, 100, 3, &[(2, P06), (1, ALU)]),
+		PerfClass::Div32 => e(12, 600, 4, &[(4, ALU)]),
+		PerfClass::Div64 => e(15, 1000, 4, &[(4, ALU)]),
+		PerfClass::Call => e(1, 200, 2, &[(1, P6), (1, P78), (1, P49)]),
+		PerfClass::Ret => e(1, 200, 2, &[(1, P6), (1, P23_11)]),
+		PerfClass::Push => e(1, 50, 1, &[(1, P78), (1, P49)]),
+		PerfClass::Pop => e(5, 33, 1, &[(1, P23_11)]),
+		PerfClass::VecShuffle => e(1, 50, 1, &[(1, P15)]),
+		PerfClass::FpAdd => e(2, 50, 1, &[(1, P15)]),
+		PerfClass::Aes => e(3, 50, 1, &[(1, P01)]),
+		PerfClass::Pclmul => e(3, 100, 1, &[(1, P0)]),
+		_ => skylake(class),
+	}
+}
+
+#[rustfmt::skip]
+fn zen2(class: PerfClass) -> Entry {
+	use self::amd::*;
+	const LOAD: u16 = AGU0 | AGU1;
+	match class {
+		PerfClass::Nop => e(0, 20, 1, &[]),
+		PerfClass::Move | PerfClass::Alu | PerfClass::AluCarry | PerfClass::Lea | PerfClass::Cmov | PerfClass::Setcc
+		| PerfClass::BitCount | PerfClass::Bmi => e(1, 25, 1, &[(1, ALU)]),
+		PerfClass::Shift | PerfClass::ShiftCl => e(1, 50, 1, &[(1, ALU12)]),
+		PerfClass::Imul => e(3, 100, 1, &[(1, ALU1)]),
+		PerfClass::MulWide => e(3, 200, 2, &[(2, ALU1)]),
+		PerfClass::Div32 => e(20, 2000, 2, &[(2, ALU2)]),
+		PerfClass::Div64 => e(30, 3000, 2, &[(2, ALU2)]),
+		PerfClass::BitScan => e(3, 300, 6, &[(6, ALU)]),
+		PerfClass::Pdep => e(19, 1900, 19, &[(19, ALU)]),
+		PerfClass::Branch => e(1, 50, 1, &[(1, BRANCH)]),
+		PerfClass::IndirectBranch => e(1, 200, 1, &[(1, BRANCH)]),
+		PerfClass::Call => e(1, 200, 2, &[(1, BRANCH), (1, AGU2)]),
+		PerfClass::Ret => e(1, 200, 2, &[(1, BRANCH), (1, LOAD)]),
+		PerfClass::Push => e(1, 100, 1, &[(1, AGU2)]),
+		PerfClass::Pop => e(4, 50, 1, &[(1, LOAD)]),
+		PerfClass::VecMove | PerfClass::VecLogic => e(1, 25, 1, &[(1, FP0123)]),
+		PerfClass::VecIntAlu => e(1, 33, 1, &[(1, FP013)]),
+		PerfClass::VecShift | PerfClass::VecShuffle => e(1, 50, 1, &[(1, FP12)]),
+		PerfClass::VecLaneCross => e(4, 200, 2, &[(2, FP12)]),
+		PerfClass::VecIntMul => e(3, 100, 1, &[(1, FP0)]),
+		PerfClass::VecIntMul32 => e(4, 100, 1, &[(1, FP0)]),
+		PerfClass::FpAdd => e(3, 50, 1, &[(1, FP23)]),
+		PerfClass::FpMul => e(3, 50, 1, &[(1, FP01)]),
+		PerfClass::Fma => e(5, 50, 1, &[(1, FP01)]),
+		PerfClass::FpDiv32 => e(10, 350, 1, &[(1, FP3)]),
+		PerfClass::FpDiv64 => e(13, 450, 1, &[(1, FP3)]),
+		PerfClass::FpSqrt32 => e(14, 500, 1, &[(1, FP3)]),
+		PerfClass::FpSqrt64 => e(20, 800, 1, &[(1, FP3)]),
+		PerfClass::Convert => e(4, 100, 1, &[(1, FP3)]),
+		PerfClass::Aes => e(4, 50, 1, &[(1, FP01)]),
+		PerfClass::Pclmul => e(4, 200, 1, &[(1, FP0)]),
+		PerfClass::Crc32 => e(3, 100, 1, &[(1, ALU1)]),
+	}
+}
+
+#[rustfmt::skip]
+fn zen3(class: PerfClass) -> Entry {
+	use self::amd::*;
+	const LOAD: u16 = AGU0 | AGU1 | AGU2;
+	const STORE: u16 = AGU0 | AGU1;
+	match class {
+		PerfClass::Nop => e(0, 17, 1, &[]),
+		PerfClass::MulWide => e(3, 100, 2, &[(1, ALU1), (1, ALU)]),
+		PerfClass::Div32 => e(11, 600, 2, &[(2, ALU2)]),
+		PerfClass::Div64 => e(14, 700, 2, &[(2, ALU2)]),
+		PerfClass::BitScan => e(1, 25, 1, &[(1, ALU)]),
+		PerfClass::Pdep => e(3, 100, 1, &[(1, ALU1)]),
+		PerfClass::Call => e(1, 200, 2, &[(1, BRANCH), (1, STORE)]),
+		PerfClass::Ret => e(1, 200, 2, &[(1, BRANCH), (1, LOAD)]),
+		PerfClass::Push => e(1, 50, 1, &[(1, STORE)]),
+		PerfClass::Pop => e(4, 33, 1, &[(1, LOAD)]),
+		PerfClass::VecIntAlu => e(1, 25, 1, &[(1, FP0123)]),
+		PerfClass::VecLaneCross => e(3, 100, 1, &[(1, FP12)]),
+		PerfClass::VecIntMul | PerfClass::VecIntMul32 => e(3, 50, 1, &[(1, FP03)]),
+		PerfClass::Fma => e(4, 50, 1, &[(1, FP01)]),
+		PerfClass::FpDiv32 => e(10, 300, 1, &[(1, FP1)]),
+		PerfClass::FpDiv64 => e(13, 450, 1, &[(1, FP1)]),
+		PerfClass::FpSqrt32 => e(14, 500, 1, &[(1, FP1)]),
+		PerfClass::FpSqrt64 => e(20, 900, 1, &[(1, FP1)]),
+		PerfClass::Convert => e(3, 100, 1, &[(1, FP23)]),
+		PerfClass::Pclmul => e(4, 100, 1, &[(1, FP01)]),
+		_ => zen2(class),
+	}
+}
+
+#[rustfmt::skip]
+fn zen4(class: PerfClass) -> Entry {
+	use self::amd::*;
+	match class {
+		PerfClass::Div32 => e(10, 600, 2, &[(2, ALU2)]),
+		PerfClass::VecLaneCross => e(4, 100, 1, &[(1, FP12)]),
+		_ => zen3(class),
+	}
+}
+
+pub(super) static SKYLAKE: UarchInfo = UarchInfo {
+	port_names: &intel::SKYLAKE_PORT_NAMES,
+	issue_width: 4,
+	load_latency: 5,
+	vec_load_latency: 7,
+	load_ports: intel::P2 | intel::P3,
+	load_rthroughput: 50,
+	store_address_ports: intel::P2 | intel::P3 | intel::P7,
+	store_data_ports: intel::P4,
+	store_rthroughput: 100,
+	profile_name: "skylake",
+	zmm_double_pumped: false,
+	zmm_ports: intel::client_zmm_ports,
+	entry: skylake,
+	code_entry: intel::code_entry,
+};
+
+pub(super) static ICE_LAKE: UarchInfo = UarchInfo {
+	port_names: &intel::ICE_LAKE_PORT_NAMES,
+	issue_width: 5,
+	load_latency: 5,
+	vec_load_latency: 7,
+	load_ports: intel::P2 | intel::P3,
+	load_rthroughput: 50,
+	store_address_ports: intel::P7 | intel::P8,
+	store_data_ports: intel::P4 | intel::P9,
+	store_rthroughput: 50,
+	profile_name: "icelake-client",
+	zmm_double_pumped: false,
+	zmm_ports: intel::client_zmm_ports,
+	entry: ice_lake,
+	code_entry: intel::code_entry,
+};
+
+pub(super) static GOLDEN_COVE: UarchInfo = UarchInfo {
+	port_names: &intel::GOLDEN_COVE_PORT_NAMES,
+	issue_width: 6,
+	load_latency: 5,
+	vec_load_latency: 7,
+	load_ports: intel::P2 | intel::P3 | intel::P11,
+	load_rthroughput: 33,
+	store_address_ports: intel::P7 | intel::P8,
+	store_data_ports: intel::P4 | intel::P9,
+	store_rthroughput: 50,
+	profile_name: "sapphirerapids",
+	zmm_double_pumped: false,
+	zmm_ports: intel::server_zmm_ports,
+	entry: golden_cove,
+	code_entry: intel::code_entry,
+};
+
+pub(super) static ZEN2: UarchInfo = UarchInfo {
+	port_names: &amd::PORT_NAMES,
+	issue_width: 5,
+	load_latency: 4,
+	vec_load_latency: 7,
+	load_ports: amd::AGU0 | amd::AGU1,
+	load_rthroughput: 50,
+	store_address_ports: amd::AGU2,
+	store_data_ports: 0,
+	store_rthroughput: 100,
+	profile_name: "znver2",
+	zmm_double_pumped: false,
+	zmm_ports: amd::zmm_ports,
+	entry: zen2,
+	code_entry: amd::code_entry,
+};
+
+pub(super) static ZEN3: UarchInfo = UarchInfo {
+	port_names: &amd::PORT_NAMES,
+	issue_width: 6,
+	load_latency: 4,
+	vec_load_latency: 7,
+	load_ports: amd::AGU0 | amd::AGU1 | amd::AGU2,
+	load_rthroughput: 33,
+	store_address_ports: amd::AGU0 | amd::AGU1,
+	store_data_ports: 0,
+	store_rthroughput: 50,
+	profile_name: "znver3",
+	zmm_double_pumped: false,
+	zmm_ports: amd::zmm_ports,
+	entry: zen3,
+	code_entry: amd::code_entry,
+};
+
+pub(super) static ZEN4: UarchInfo = UarchInfo {
+	port_names: &amd::PORT_NAMES,
+	issue_width: 6,
+	load_latency: 4,
+	vec_load_latency: 7,
+	load_ports: amd::AGU0 | amd::AGU1 | amd::AGU2,
+	load_rthroughput: 33,
+	store_address_ports: amd::AGU0 | amd::AGU1,
+	store_data_ports: 0,
+	store_rthroughput: 50,
+	profile_name: "znver4",
+	zmm_double_pumped: true,
+	zmm_ports: amd::zmm_ports,
+	entry: zen4,
+	code_entry: amd::code_entry,
+};
@@ -0,0 +1,284 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+use crate::perf_info::*;
+use crate::*;
+use alloc::vec::Vec;
+
+fn decode(bytes: &[u8]) -> Vec<Instruction> {
+	Decoder::new(64, bytes, DecoderOptions::NONE).into_iter().collect()
+}
+
+fn decode1(bytes: &[u8]) -> Instruction {
+	let instrs = decode(bytes);
+	assert_eq!(instrs.len(), 1);
+	instrs[0]
+}
+
+fn port_names(uarch: Microarchitecture, usage: &PortUsage) -> Vec<&'static str> {
+	let names = uarch.port_names();
+	(0..names.len()).filter(|&i| (usage.ports() & (1 << i)) != 0).map(|i| names[i]).collect()
+}
+
+#[test]
+fn all_uarchs_have_data() {
+	// imul eax,ecx
+	let instr = decode1(b"\x0F\xAF\xC1");
+	for &uarch in Microarchitecture::values() {
+		let info = uarch.estimate_perf(&instr).unwrap();
+		assert_eq!(info.latency(), 3, "{}", uarch);
+		assert_eq!(info.uops(), 1, "{}", uarch);
+		assert!(!info.port_usage().is_empty(), "{}", uarch);
+		assert!(uarch.issue_width() >= 4);
+		assert!(uarch.port_names().len() <= 16);
+		for usage in info.port_usage() {
+			assert_eq!(usage.ports() >> uarch.port_names().len(), 0);
+		}
+		assert!(!uarch.profile().is_empty());
+	}
+}
+
+#[test]
+fn profile_names_are_valid() {
+	for &uarch in Microarchitecture::values() {
+		assert!(CpuFeatureProfile::from_name(uarch.info().profile_name).is_some(), "{}", uarch);
+	}
+}
+
+#[test]
+fn port_usage() {
+	// add eax,ecx
+	let add = decode1(b"\x01\xC8");
+	let info = Microarchitecture::Skylake.estimate_perf(&add).unwrap();
+	assert_eq!(info.reciprocal_throughput(), 0.25);
+	assert_eq!(port_names(Microarchitecture::Skylake, &info.port_usage()[0]), ["p0", "p1", "p5", "p6"]);
+	let info = Microarchitecture::Zen3.estimate_perf(&add).unwrap();
+	assert_eq!(port_names(Microarchitecture::Zen3, &info.port_usage()[0]), ["ALU0", "ALU1", "ALU2", "ALU3"]);
+
+	// vaddps ymm0,ymm1,ymm2
+	let vaddps = decode1(b"\xC5\xF4\x58\xC2");
+	let info = Microarchitecture::Skylake.estimate_perf(&vaddps).unwrap();
+	assert_eq!(info.latency(), 4);
+	assert_eq!(port_names(Microarchitecture::Skylake, &info.port_usage()[0]), ["p0", "p1"]);
+	let info = Microarchitecture::GoldenCove.estimate_perf(&vaddps).unwrap();
+	assert_eq!(info.latency(), 2);
+	assert_eq!(port_names(Microarchitecture::GoldenCove, &info.port_usage()[0]), ["p1", "p5"]);
+}
+
+#[test]
+fn memory_operands() {
+	// add rax,[rsi]
+	let load = decode1(b"\x48\x03\x06");
+	let info = Microarchitecture::Skylake.estimate_perf(&load).unwrap();
+	assert_eq!(info.load_latency(), 5);
+	assert_eq!(info.latency(), 6);
+	assert_eq!(info.uops(), 1);
+	assert_eq!(info.port_usage().len(), 2);
+	assert_eq!(port_names(Microarchitecture::Skylake, &info.port_usage()[1]), ["p2", "p3"]);
+
+	// mov [rdi],eax
+	let store = decode1(b"\x89\x07");
+	let info = Microarchitecture::Skylake.estimate_perf(&store).unwrap();
+	assert_eq!(info.load_latency(), 0);
+	assert_eq!(info.uops(), 1);
+	assert_eq!(info.reciprocal_throughput(), 1.0);
+	assert_eq!(port_names(Microarchitecture::Skylake, &info.port_usage()[0]), ["p2", "p3", "p7"]);
+	assert_eq!(port_names(Microarchitecture::Skylake, &info.port_usage()[1]), ["p4"]);
+	let info = Microarchitecture::IceLake.estimate_perf(&store).unwrap();
+	assert_eq!(info.reciprocal_throughput(), 0.5);
+
+	// add [rdi],eax
+	let rmw = decode1(b"\x01\x07");
+	let info = Microarchitecture::Skylake.estimate_perf(&rmw).unwrap();
+	assert_eq!(info.uops(), 2);
+	assert_eq!(info.port_usage().len(), 4);
+}
+
+#[test]
+fn zero_idioms() {
+	// xor eax,eax
+	let info = Microarchitecture::Skylake.estimate_perf(&decode1(b"\x31\xC0")).unwrap();
+	assert!(info.is_zero_idiom());
+	assert_eq!(info.latency(), 0);
+	assert!(info.port_usage().is_empty());
+	// xor eax,ecx
+	let info = Microarchitecture::Skylake.estimate_perf(&decode1(b"\x31\xC8")).unwrap();
+	assert!(!info.is_zero_idiom());
+	assert_eq!(info.latency(), 1);
+	// vpxor xmm0,xmm1,xmm1
+	let info = Microarchitecture::Zen2.estimate_perf(&decode1(b"\xC5\xF1\xEF\xC1")).unwrap();
+	assert!(info.is_zero_idiom());
+}
+
+#[test]
+fn class_estimates() {
+	// add eax,ecx
+	let add = decode1(b"\x01\xC8");
+	// xor eax,ecx
+	let xor = decode1(b"\x31\xC8");
+	for &uarch in Microarchitecture::values() {
+		assert_eq!(uarch.estimate_perf(&add), uarch.estimate_perf(&xor), "{}", uarch);
+	}
+
+	// fsin
+	let fsin = decode1(b"\xD9\xFE");
+	// cpuid
+	let cpuid = decode1(b"\x0F\xA2");
+	for &uarch in Microarchitecture::values() {
+		assert!(uarch.estimate_perf(&fsin).is_none(), "{}", uarch);
+		assert!(uarch.estimate_perf(&cpuid).is_none(), "{}", uarch);
+	}
+}
+
+#[test]
+fn code_estimates() {
+	let uarch = Microarchitecture::Skylake;
+	// mul cl
+	let mul8 = uarch.estimate_perf(&decode1(b"\xF6\xE1")).unwrap();
+	// mul ecx
+	let mul32 = uarch.estimate_perf(&decode1(b"\xF7\xE1")).unwrap();
+	assert_eq!(mul8.uops(), 1);
+	assert_eq!(mul32.uops(), 2);
+
+	// vdivps xmm0,xmm1,xmm2
+	let div128 = uarch.estimate_perf(&decode1(b"\xC5\xF0\x5E\xC2")).unwrap();
+	// vdivps ymm0,ymm1,ymm2
+	let div256 = uarch.estimate_perf(&decode1(b"\xC5\xF4\x5E\xC2")).unwrap();
+	assert_eq!(div128.reciprocal_throughput(), 3.0);
+	assert_eq!(div256.reciprocal_throughput(), 5.0);
+	assert_eq!(div128.latency(), div256.latency());
+
+	// movd xmm0,[rax]
+	let info = uarch.estimate_perf(&decode1(b"\x66\x0F\x6E\x00")).unwrap();
+	assert_eq!(info.latency(), info.load_latency());
+	assert_eq!(info.uops(), 1);
+	// movd xmm0,eax
+	let info = uarch.estimate_perf(&decode1(b"\x66\x0F\x6E\xC0")).unwrap();
+	assert_eq!(info.load_latency(), 0);
+
+	// movsd, cmpsd (string instructions)
+	for bytes in [b"\xA5", b"\xA7"] {
+		for &uarch in Microarchitecture::values() {
+			assert!(uarch.estimate_perf(&decode1(bytes)).is_none(), "{}", uarch);
+		}
+	}
+}
+
+#[test]
+fn unsupported_instructions() {
+	// vaddps zmm0,zmm1,zmm2
+	let vaddps = decode1(b"\x62\xF1\x74\x48\x58\xC2");
+	assert!(Microarchitecture::Skylake.estimate_perf(&vaddps).is_none());
+	assert!(Microarchitecture::Zen2.estimate_perf(&vaddps).is_none());
+	assert!(Microarchitecture::Zen3.estimate_perf(&vaddps).is_none());
+
+	let info = Microarchitecture::IceLake.estimate_perf(&vaddps).unwrap();
+	assert_eq!(port_names(Microarchitecture::IceLake, &info.port_usage()[0]), ["p0"]);
+	let info = Microarchitecture::GoldenCove.estimate_perf(&vaddps).unwrap();
+	assert_eq!(port_names(Microarchitecture::GoldenCove, &info.port_usage()[0]), ["p0", "p5"]);
+	let info = Microarchitecture::Zen4.estimate_perf(&vaddps).unwrap();
+	assert_eq!(info.reciprocal_throughput(), 1.0);
+	assert_eq!(info.port_usage()[0].count(), 2);
+
+	// endbr64 is a NOP on CPUs without CET
+	let endbr64 = decode1(b"\xF3\x0F\x1E\xFA");
+	for &uarch in Microarchitecture::values() {
+		assert_eq!(uarch.estimate_perf(&endbr64).unwrap().port_usage().len(), 0, "{}", uarch);
+	}
+}
+
+#[test]
+fn estimate_empty() {
+	let estimate = ThroughputEstimator::new(Microarchitecture::Skylake).estimate(&[]);
+	assert_eq!(estimate.cycles, 0.0);
+	assert_eq!(estimate.uops, 0);
+	assert_eq!(estimate.latency, 0);
+	assert_eq!(estimate.port_pressure.len(), 8);
+	assert_eq!(estimate.unknown_instructions, 0);
+}
+
+#[test]
+fn estimate_dependency_chain() {
+	// imul rax,rcx
+	// add rax,rdx
+	let instrs = decode(b"\x48\x0F\xAF\xC1\x48\x01\xD0");
+	let mut estimator = ThroughputEstimator::new(Microarchitecture::Skylake);
+	let estimate = estimator.estimate(&instrs);
+	assert_eq!(estimate.bottleneck, Bottleneck::Dependencies);
+	assert_eq!(estimate.dependency_cycles, 4.0);
+	assert_eq!(estimate.cycles, 4.0);
+	assert_eq!(estimate.latency, 4);
+
+	// The RAX chain is broken by the zero idiom
+	// xor eax,eax
+	// imul rax,rcx
+	// add rax,rdx
+	let instrs = decode(b"\x31\xC0\x48\x0F\xAF\xC1\x48\x01\xD0");
+	let estimate = estimator.estimate(&instrs);
+	assert_eq!(estimate.latency, 4);
+	assert_eq!(estimate.dependency_cycles, 0.0);
+	assert_eq!(estimate.bottleneck, Bottleneck::Ports);
+	assert_eq!(estimate.cycles, 1.0);
+}
+
+#[test]
+fn estimate_load_address_dependency() {
+	// Only the pointer chasing load is loop carried
+	// mov rsi,[rsi]
+	// add rax,[rsi+8]
+	let instrs = decode(b"\x48\x8B\x36\x48\x03\x46\x08");
+	let estimate = ThroughputEstimator::new(Microarchitecture::Skylake).estimate(&instrs);
+	assert_eq!(estimate.dependency_cycles, 5.0);
+	assert_eq!(estimate.latency, 11);
+	assert_eq!(estimate.bottleneck, Bottleneck::Dependencies);
+}
+
+#[test]
+fn estimate_ports() {
+	// vmulps ymm0,ymm0,ymm1
+	// vmulps ymm2,ymm2,ymm1
+	// vmulps ymm3,ymm3,ymm1
+	// vmulps ymm4,ymm4,ymm1
+	// vmulps ymm5,ymm5,ymm1
+	// vmulps ymm6,ymm6,ymm1
+	// vmulps ymm7,ymm7,ymm1
+	// vmulps ymm8,ymm8,ymm1
+	// vmulps ymm9,ymm9,ymm1
+	// vmulps ymm10,ymm10,ymm1
+	let mut bytes = Vec::new();
+	for &reg in &[0u8, 2, 3, 4, 5, 6, 7] {
+		bytes.extend_from_slice(&[0xC5, 0xFC ^ (reg << 3), 0x59, 0xC1 | (reg << 3)]);
+	}
+	for &reg in &[0u8, 1, 2] {
+		bytes.extend_from_slice(&[0xC4, 0x61, 0x3C ^ (reg << 3), 0x59, 0xC1 | (reg << 3)]);
+	}
+	let instrs = decode(&bytes);
+	assert_eq!(instrs.len(), 10);
+	let estimate = ThroughputEstimator::new(Microarchitecture::Skylake).estimate(&instrs);
+	assert_eq!(estimate.bottleneck, Bottleneck::Ports);
+	assert_eq!(estimate.cycles, 5.0);
+	assert_eq!(estimate.port_pressure[0], 5.0);
+	assert_eq!(estimate.port_pressure[1], 5.0);
+	assert_eq!(estimate.dependency_cycles, 4.0);
+}
+
+#[test]
+fn estimate_divider() {
+	// div rcx
+	// add rbx,1
+	let instrs = decode(b"\x48\xF7\xF1\x48\x83\xC3\x01");
+	let estimate = ThroughputEstimator::new(Microarchitecture::Skylake).estimate(&instrs);
+	assert_eq!(estimate.port_cycles, 24.0);
+	let estimate = ThroughputEstimator::new(Microarchitecture::IceLake).estimate(&instrs);
+	assert_eq!(estimate.port_cycles, 10.0);
+}
+
+#[test]
+fn estimate_unknown_instructions() {
+	// cpuid
+	// add eax,ecx
+	let instrs = decode(b"\x0F\xA2\x01\xC8");
+	let estimate = ThroughputEstimator::new(Microarchitecture::Zen3).estimate(&instrs);
+	assert_eq!(estimate.unknown_instructions, 1);
+	assert_eq!(estimate.uops, 1);
+}
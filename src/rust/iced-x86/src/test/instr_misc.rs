@@ -698,3 +698,25 @@ fn verify_get_set_immediate() {
 fn verify_instruction_size() {
 	const _: () = assert!(mem::size_of::<Instruction>() == INSTRUCTION_TOTAL_SIZE);
 }
+
+#[test]
+#[cfg(feature = "encoder")]
+fn notrack_prefix() {
+	let mut instr = Instruction::with1(Code::Jmp_rm64, Register::RAX).unwrap();
+	assert!(!instr.has_notrack_prefix());
+	instr.set_has_notrack_prefix(true);
+	assert!(instr.has_notrack_prefix());
+	assert_eq!(instr.segment_prefix(), Register::DS);
+	instr.set_has_notrack_prefix(false);
+	assert!(!instr.has_notrack_prefix());
+	assert_eq!(instr.segment_prefix(), Register::None);
+
+	// Only near indirect branches can have a NOTRACK prefix
+	let mut instr = Instruction::with2(Code::Mov_r64_rm64, Register::RAX, MemoryOperand::with_base(Register::RCX)).unwrap();
+	instr.set_segment_prefix(Register::DS);
+	assert!(!instr.has_notrack_prefix());
+	instr.set_segment_prefix(Register::None);
+	instr.set_has_notrack_prefix(true);
+	assert!(!instr.has_notrack_prefix());
+	assert_eq!(instr.segment_prefix(), Register::None);
+}
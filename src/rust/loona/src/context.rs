@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+#![allow(clippy::missing_errors_doc)]
+
+use crate::error::LuaError;
+use crate::lua::{Lua, LuaUserData};
+use crate::tofrom::{ToLua, TryFromLua};
+use libc::c_int;
+use std::ops::{Deref, DerefMut};
+
+/// Safe access to the Lua stack of the currently executing C function.
+///
+/// It's passed to functions created by [`LuaContext::create_function()`] and by [`lua_safe_module!`].
+/// Nothing here raises a Lua error (which would `longjmp` over Rust frames), errors are returned as [`LuaError`]
+/// and are raised after the Rust function has returned. The exception is out of memory errors which can still
+/// be raised by any function that allocates Lua memory.
+///
+/// [`lua_safe_module!`]: crate::lua_safe_module
+#[allow(missing_debug_implementations)]
+pub struct LuaContext<'a, 'lua> {
+	lua: &'a Lua<'lua>,
+	arg_count: c_int,
+}
+
+impl<'a, 'lua> LuaContext<'a, 'lua> {
+	/// Creates a context. All values on the stack are the function's arguments.
+	///
+	/// # Safety
+	///
+	/// `lua` must be the state passed to the currently executing C function and only this context may modify
+	/// the stack until it's dropped.
+	#[inline]
+	pub unsafe fn new(lua: &'a Lua<'lua>) -> Self {
+		Self { lua, arg_count: unsafe { lua.get_top() } }
+	}
+
+	/// Gets the Lua state. All its methods are `unsafe`.
+	#[inline]
+	#[must_use]
+	pub fn lua(&self) -> &'a Lua<'lua> {
+		self.lua
+	}
+
+	/// Gets the number of arguments passed to the function
+	#[inline]
+	#[must_use]
+	pub fn arg_count(&self) -> c_int {
+		self.arg_count
+	}
+
+	/// Gets the number of values on the stack (arguments and pushed values)
+	#[inline]
+	#[must_use]
+	pub fn top(&self) -> c_int {
+		unsafe { self.lua.get_top() }
+	}
+
+	#[inline]
+	pub(crate) fn is_valid_index(&self, idx: c_int) -> bool {
+		idx > 0 && idx <= self.top()
+	}
+
+	/// Makes sure there's room for `count` more values on the stack
+	#[inline]
+	pub fn check_stack(&self, count: c_int) -> Result<(), LuaError> {
+		if unsafe { self.lua.check_stack(count) } {
+			Ok(())
+		} else {
+			Err(LuaError::MessageStr("Couldn't grow the stack"))
+		}
+	}
+
+	/// Converts argument `n` (1-based). Missing arguments are `nil`.
+	#[inline]
+	pub fn arg<T: for<'x> TryFromLua<'x>>(&self, n: c_int) -> Result<T, LuaError> {
+		let result = if n >= 1 && n <= self.arg_count { unsafe { T::try_from_lua(self.lua, n) } } else { self.convert_nil() };
+		result.map_err(|e| LuaError::Message(format!("bad argument #{}: {}", n, e)))
+	}
+
+	fn convert_nil<T: for<'x> TryFromLua<'x>>(&self) -> Result<T, LuaError> {
+		self.check_stack(1)?;
+		unsafe {
+			self.lua.push_nil();
+			let result = T::try_from_lua(self.lua, self.lua.get_top());
+			self.lua.pop(1);
+			result
+		}
+	}
+
+	/// Converts the value at stack index `idx` (1-based)
+	#[inline]
+	pub fn get<T: for<'x> TryFromLua<'x>>(&self, idx: c_int) -> Result<T, LuaError> {
+		if self.is_valid_index(idx) {
+			unsafe { T::try_from_lua(self.lua, idx) }
+		} else {
+			self.convert_nil()
+		}
+	}
+
+	/// Gets the userdata at stack index `idx` (1-based)
+	#[inline]
+	pub fn user_data<T: LuaUserData>(&self, idx: c_int) -> Result<&T, LuaError> {
+		if self.is_valid_index(idx) {
+			if let Some(ud) = unsafe { self.lua.try_get_user_data(idx) } {
+				return Ok(ud);
+			}
+		}
+		Err(LuaError::Message(format!("bad argument #{}: Expected a userdata", idx)))
+	}
+
+	/// Gets the userdata at stack index `idx` (1-based). It borrows the context mutably so there can't be any
+	/// other references to it.
+	#[inline]
+	pub fn user_data_mut<T: LuaUserData>(&mut self, idx: c_int) -> Result<&mut T, LuaError> {
+		if self.is_valid_index(idx) {
+			if let Some(ud) = unsafe { self.lua.try_get_user_data_mut(idx) } {
+				return Ok(ud);
+			}
+		}
+		Err(LuaError::Message(format!("bad argument #{}: Expected a userdata", idx)))
+	}
+
+	/// Pushes a value on the stack and returns its index
+	#[inline]
+	pub fn push<T: ToLua>(&mut self, value: T) -> Result<c_int, LuaError> {
+		self.check_stack(1)?;
+		unsafe {
+			self.lua.push(value);
+			Ok(self.lua.get_top())
+		}
+	}
+
+	/// Pushes a new userdata on the stack and sets its metatable. `init_metatable()` is called if the metatable
+	/// hasn't been created yet (see [`lua_get_or_init_metatable!`]).
+	///
+	/// [`lua_get_or_init_metatable!`]: crate::lua_get_or_init_metatable
+	#[inline]
+	pub fn push_user_data<T: LuaUserData>(&mut self, ud: T, init_metatable: unsafe fn(&Lua<'_>)) -> Result<c_int, LuaError> {
+		self.check_stack(3)?;
+		unsafe {
+			let _ = self.lua.push_user_data(ud);
+			if self.lua.new_registry_metatable(T::METATABLE_KEY) {
+				init_metatable(self.lua);
+			}
+			let _ = self.lua.set_metatable(-2);
+			Ok(self.lua.get_top())
+		}
+	}
+
+	/// Returns a guard that pops everything pushed after this call when it's dropped
+	#[inline]
+	pub fn stack_guard(&mut self) -> StackGuard<'_, 'a, 'lua> {
+		let top = self.top();
+		StackGuard { ctx: self, top }
+	}
+}
+
+/// Restores the stack top when it's dropped, see [`LuaContext::stack_guard()`]
+#[allow(missing_debug_implementations)]
+pub struct StackGuard<'c, 'a, 'lua> {
+	ctx: &'c mut LuaContext<'a, 'lua>,
+	top: c_int,
+}
+
+impl<'a, 'lua> Deref for StackGuard<'_, 'a, 'lua> {
+	type Target = LuaContext<'a, 'lua>;
+
+	#[inline]
+	fn deref(&self) -> &Self::Target {
+		self.ctx
+	}
+}
+
+impl DerefMut for StackGuard<'_, '_, '_> {
+	#[inline]
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.ctx
+	}
+}
+
+impl Drop for StackGuard<'_, '_, '_> {
+	#[inline]
+	fn drop(&mut self) {
+		if self.ctx.top() > self.top {
+			unsafe { self.ctx.lua.set_top(self.top) };
+		}
+	}
+}
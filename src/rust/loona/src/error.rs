@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2018-present iced project and contributors
 
+use crate::lua::ConvErr;
 use std::error::Error;
 use std::fmt;
 
@@ -8,6 +9,7 @@ use std::fmt;
 #[allow(missing_copy_implementations)]
 pub enum LuaError {
 	MessageStr(&'static str),
+	Message(String),
 }
 
 impl Error for LuaError {}
@@ -17,6 +19,17 @@ impl fmt::Display for LuaError {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
 			LuaError::MessageStr(s) => fmt::Display::fmt(s, f),
+			LuaError::Message(s) => fmt::Display::fmt(s, f),
+		}
+	}
+}
+
+impl From<ConvErr> for LuaError {
+	#[inline]
+	fn from(e: ConvErr) -> Self {
+		match e {
+			ConvErr::OutOfRange => LuaError::MessageStr("Value is out of range"),
+			ConvErr::NotANumber => LuaError::MessageStr("Expected a number"),
 		}
 	}
 }
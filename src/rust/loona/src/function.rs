@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+#![allow(clippy::missing_errors_doc)]
+
+use crate::context::LuaContext;
+use crate::error::LuaError;
+use crate::lua::Lua;
+use crate::lua_api::lua_State;
+use crate::tofrom::{ToLua, TryFromLua};
+use libc::c_int;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::{mem, ptr};
+
+/// Converts the arguments passed to a function created by [`LuaContext::create_function()`].
+/// It's implemented by `()` and tuples of up to 8 values.
+pub trait FromLuaArgs: Sized {
+	/// Converts the arguments
+	fn from_lua_args(ctx: &LuaContext<'_, '_>) -> Result<Self, LuaError>;
+}
+
+/// Pushes the values returned by a function created by [`LuaContext::create_function()`].
+/// It's implemented by `()` (no return values), all [`ToLua`] types (one return value) and tuples of
+/// up to 8 values.
+pub trait ToLuaMulti {
+	/// Pushes the values and returns the number of pushed values
+	fn push_all(self, ctx: &mut LuaContext<'_, '_>) -> Result<c_int, LuaError>;
+}
+
+impl FromLuaArgs for () {
+	#[inline]
+	fn from_lua_args(_ctx: &LuaContext<'_, '_>) -> Result<Self, LuaError> {
+		Ok(())
+	}
+}
+
+impl ToLuaMulti for () {
+	#[inline]
+	fn push_all(self, _ctx: &mut LuaContext<'_, '_>) -> Result<c_int, LuaError> {
+		Ok(0)
+	}
+}
+
+impl<T: ToLua> ToLuaMulti for T {
+	#[inline]
+	fn push_all(self, ctx: &mut LuaContext<'_, '_>) -> Result<c_int, LuaError> {
+		let _ = ctx.push(self)?;
+		Ok(1)
+	}
+}
+
+macro_rules! impl_tuples {
+	($($count:literal => ($($name:ident : $n:literal),+),)+) => {
+		$(
+			impl<$($name: for<'x> TryFromLua<'x>),+> FromLuaArgs for ($($name,)+) {
+				#[inline]
+				fn from_lua_args(ctx: &LuaContext<'_, '_>) -> Result<Self, LuaError> {
+					Ok(($(ctx.arg::<$name>($n)?,)+))
+				}
+			}
+
+			impl<$($name: ToLua),+> ToLuaMulti for ($($name,)+) {
+				#[inline]
+				#[allow(non_snake_case)]
+				fn push_all(self, ctx: &mut LuaContext<'_, '_>) -> Result<c_int, LuaError> {
+					ctx.check_stack($count)?;
+					let ($($name,)+) = self;
+					// SAFETY: there's room for all values
+					unsafe {
+						$(ctx.lua().push($name);)+
+					}
+					Ok($count)
+				}
+			}
+		)+
+	};
+}
+
+impl_tuples! {
+	1 => (A:1),
+	2 => (A:1, B:2),
+	3 => (A:1, B:2, C:3),
+	4 => (A:1, B:2, C:3, D:4),
+	5 => (A:1, B:2, C:3, D:4, E:5),
+	6 => (A:1, B:2, C:3, D:4, E:5, F:6),
+	7 => (A:1, B:2, C:3, D:4, E:5, F:6, G:7),
+	8 => (A:1, B:2, C:3, D:4, E:5, F:6, G:7, H:8),
+}
+
+impl<'a, 'lua> LuaContext<'a, 'lua> {
+	/// Pushes a new function that calls `f`. The arguments are converted to `A` and the returned values are pushed
+	/// on the stack. If `f` returns an error or panics, a Lua error is raised.
+	///
+	/// # Examples
+	///
+	/// ```ignore
+	/// let add = ctx.create_function(|_ctx, (a, b): (i64, Option<i64>)| Ok(a + b.unwrap_or(1)))?;
+	/// ```
+	#[inline]
+	pub fn create_function<F, A, R>(&mut self, f: F) -> Result<c_int, LuaError>
+	where
+		F: Fn(&mut LuaContext<'_, '_>, A) -> Result<R, LuaError> + 'static,
+		A: FromLuaArgs,
+		R: ToLuaMulti,
+	{
+		self.check_stack(4)?;
+		let lua = self.lua();
+		// SAFETY: there's room for the values we push. The userdata is only accessed by call_function() and
+		// drop_function() and it's always an `Option<Box<F>>` since it's only reachable from our closure
+		// (the metatable is protected by `__metatable`).
+		unsafe {
+			let ptr = lua.new_user_data(mem::size_of::<Option<Box<F>>>()).cast::<Option<Box<F>>>();
+			ptr::write(ptr, Some(Box::new(f)));
+			lua.create_table(0, 2);
+			lua.push("__gc");
+			lua.push_c_function(drop_function::<F>);
+			lua.raw_set(-3);
+			lua.push("__metatable");
+			lua.push(false);
+			lua.raw_set(-3);
+			let _ = lua.set_metatable(-2);
+			lua.push_c_closure(call_function::<F, A, R>, 1);
+			Ok(lua.get_top())
+		}
+	}
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+	if let Some(s) = payload.downcast_ref::<&str>() {
+		format!("Rust panic: {}", s)
+	} else if let Some(s) = payload.downcast_ref::<String>() {
+		format!("Rust panic: {}", s)
+	} else {
+		"Rust panic".to_owned()
+	}
+}
+
+/// Calls `f` and raises a Lua error if it returns an error or panics. All Rust values are dropped before
+/// the error is raised.
+///
+/// # Safety
+///
+/// `L` must be the state passed to the currently executing C function.
+#[doc(hidden)]
+#[inline]
+#[allow(non_snake_case)]
+pub unsafe fn call_safe<R, F>(L: lua_State, f: F) -> c_int
+where
+	R: ToLuaMulti,
+	F: FnOnce(&mut LuaContext<'_, '_>) -> Result<R, LuaError>,
+{
+	let lua = unsafe { Lua::new(&L) };
+	let result = panic::catch_unwind(AssertUnwindSafe(|| {
+		// SAFETY: caller guarantees it's the state of the currently executing C function
+		let mut ctx = unsafe { LuaContext::new(&lua) };
+		let values = f(&mut ctx)?;
+		// Lua only uses the top `count` values so it's fine if there are other values below them
+		values.push_all(&mut ctx)
+	}));
+	let msg = match result {
+		Ok(Ok(count)) => return count,
+		Ok(Err(e)) => e.to_string(),
+		Err(payload) => panic_message(&*payload),
+	};
+	unsafe { lua.push(msg.as_str()) };
+	drop(msg);
+	unsafe { lua.error() }
+}
+
+#[allow(non_snake_case)]
+unsafe extern "C" fn call_function<F, A, R>(L: lua_State) -> c_int
+where
+	F: Fn(&mut LuaContext<'_, '_>, A) -> Result<R, LuaError> + 'static,
+	A: FromLuaArgs,
+	R: ToLuaMulti,
+{
+	unsafe {
+		call_safe(L, |ctx| {
+			// SAFETY: upvalue 1 is the userdata created by create_function()
+			let f = &*ctx.lua().to_user_data(Lua::up_value_index(1)).cast::<Option<Box<F>>>();
+			let f = f.as_ref().ok_or(LuaError::MessageStr("The function has been freed"))?;
+			let args = A::from_lua_args(ctx)?;
+			f(ctx, args)
+		})
+	}
+}
+
+#[allow(non_snake_case)]
+unsafe extern "C" fn drop_function<F: 'static>(L: lua_State) -> c_int {
+	unsafe {
+		let lua = Lua::new(&L);
+		let ptr = lua.to_user_data(1).cast::<Option<Box<F>>>();
+		if !ptr.is_null() {
+			// Ignore panics, we can't raise an error in a finalizer
+			let _ = panic::catch_unwind(AssertUnwindSafe(|| drop((*ptr).take())));
+		}
+	}
+	0
+}
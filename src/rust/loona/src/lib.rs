@@ -66,11 +66,14 @@
 #![warn(clippy::useless_transmute)]
 #![warn(clippy::zero_sized_map_values)]
 
+pub mod context;
 pub mod error;
+pub mod function;
 #[doc(hidden)]
 pub mod libc;
 pub mod lua;
 pub mod lua_api;
 pub mod macros;
 pub mod prelude;
+pub mod table;
 pub mod tofrom;
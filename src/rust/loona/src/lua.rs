@@ -198,6 +198,12 @@ impl<'lua> Lua<'lua> {
 		Self { state: *state, _phantom: PhantomData }
 	}
 
+	/// Gets the wrapped `lua_State`
+	#[inline]
+	pub fn state(&self) -> lua_State {
+		self.state
+	}
+
 	#[inline]
 	pub unsafe fn push_user_data<T: LuaUserData>(&self, ud: T) -> &'lua mut T {
 		unsafe {
@@ -329,6 +335,12 @@ impl<'lua> Lua<'lua> {
 	// If 5.1 and 5.2, convert an f64 to an i64 instead of using lua_tointegerx() for extra precision
 	#[inline]
 	unsafe fn to_i64(&self, idx: c_int, isnum: *mut c_int) -> i64 {
+		#[cfg(feature = "luajit")]
+		unsafe {
+			if let Some(value) = self.try_get_cdata_int64(idx, isnum) {
+				return value as i64;
+			}
+		}
 		#[cfg(any(feature = "lua5_1", feature = "lua5_2"))]
 		unsafe {
 			let value = self.to_number_x(idx, isnum);
@@ -349,6 +361,12 @@ impl<'lua> Lua<'lua> {
 	// If 5.1 and 5.2, convert an f64 to a u64 instead of using lua_tointegerx() for extra precision
 	#[inline]
 	unsafe fn to_u64(&self, idx: c_int, isnum: *mut c_int) -> u64 {
+		#[cfg(feature = "luajit")]
+		unsafe {
+			if let Some(value) = self.try_get_cdata_int64(idx, isnum) {
+				return value;
+			}
+		}
 		#[cfg(any(feature = "lua5_1", feature = "lua5_2"))]
 		unsafe {
 			// 0xFEDCBA9876543000 fails if we use lua_tointeger{,x}() with Lua 5.1 and 5.2
@@ -365,6 +383,100 @@ impl<'lua> Lua<'lua> {
 		}
 	}
 
+	// LuaJIT: pushes a field of the `ffi` module. If `load` is true, `require("ffi")` is called if it hasn't
+	// been loaded yet. Returns false (and pushes nothing) if it failed. Needs 3 free stack slots.
+	#[cfg(feature = "luajit")]
+	unsafe fn push_ffi_field(&self, name: LuaCStr<'_>, load: bool) -> bool {
+		unsafe {
+			let top = self.get_top();
+			self.get_field(LUA_REGISTRYINDEX, LuaCStr(b"_LOADED\0"));
+			if self.is_table(-1) {
+				self.get_field(-1, LuaCStr(b"ffi\0"));
+				if !self.is_table(-1) && load {
+					self.pop(1);
+					self.get_global(LuaCStr(b"require\0"));
+					self.push_literal("ffi");
+					if self.pcall(1, 1, 0) != 0 {
+						self.pop(1);
+						self.push_nil();
+					}
+				}
+				if self.is_table(-1) {
+					self.get_field(-1, name);
+					if self.is_function(-1) {
+						self.replace(top + 1);
+						self.set_top(top + 1);
+						return true;
+					}
+				}
+			}
+			self.set_top(top);
+			false
+		}
+	}
+
+	// LuaJIT: reads an `int64_t` or `uint64_t` cdata value, eg. `0x123ULL`. Other values return None.
+	#[cfg(feature = "luajit")]
+	unsafe fn try_get_cdata_int64(&self, idx: c_int, isnum: *mut c_int) -> Option<u64> {
+		unsafe {
+			if self.type_(idx) != LUA_TCDATA || !self.check_stack(4) {
+				return None;
+			}
+			let top = self.get_top();
+			let idx = if idx < 0 && idx > LUA_REGISTRYINDEX { top + idx + 1 } else { idx };
+			let mut result = None;
+			// We must check the type since lua_topointer() returns a pointer to the payload which could be smaller than 8 bytes
+			if self.push_ffi_field(LuaCStr(b"istype\0"), false) {
+				for ctype in ["uint64_t", "int64_t"] {
+					self.push_value(top + 1);
+					self.push_literal(ctype);
+					self.push_value(idx);
+					if self.pcall(2, 1, 0) == 0 && self.to_boolean(-1) {
+						result = Some(ptr::read_unaligned(self.to_pointer(idx).cast::<u64>()));
+						break;
+					}
+					self.pop(1);
+				}
+			}
+			self.set_top(top);
+			if result.is_some() && !isnum.is_null() {
+				*isnum = 1;
+			}
+			result
+		}
+	}
+
+	// LuaJIT: pushes an `int64_t` or `uint64_t` cdata value. Returns false (and pushes nothing) if the `ffi`
+	// module isn't available.
+	#[cfg(feature = "luajit")]
+	unsafe fn try_push_cdata_int64(&self, ctype: &str, value: u64) -> bool {
+		unsafe {
+			if !self.check_stack(3) || !self.push_ffi_field(LuaCStr(b"new\0"), true) {
+				return false;
+			}
+			self.push_literal(ctype);
+			if self.pcall(1, 1, 0) == 0 && self.type_(-1) == LUA_TCDATA {
+				ptr::write_unaligned(self.to_pointer(-1).cast::<u64>() as *mut u64, value);
+				true
+			} else {
+				self.pop(1);
+				false
+			}
+		}
+	}
+
+	#[inline]
+	#[cfg(feature = "luajit")]
+	pub unsafe fn set_jit_mode(&self, idx: c_int, mode: c_int) -> bool {
+		unsafe { luaJIT_setmode(self.state, idx, mode) != 0 }
+	}
+
+	#[inline]
+	#[cfg(feature = "luajit")]
+	pub unsafe fn is_cdata(&self, idx: c_int) -> bool {
+		unsafe { self.type_(idx) == LUA_TCDATA }
+	}
+
 	#[inline]
 	pub unsafe fn try_get_i64(&self, idx: c_int) -> Result<i64, ConvErr> {
 		unsafe {
@@ -774,6 +886,13 @@ impl<'lua> Lua<'lua> {
 
 	#[inline]
 	pub unsafe fn push_i64(&self, value: i64) {
+		// LuaJIT: use an `int64_t` if a double can't represent it
+		#[cfg(feature = "luajit")]
+		unsafe {
+			if value.unsigned_abs() > LUAJIT_MAX_SAFE_INTEGER && self.try_push_cdata_int64("int64_t", value as u64) {
+				return;
+			}
+		}
 		unsafe {
 			#[allow(trivial_numeric_casts)]
 			self.push_integer(value as lua_Integer);
@@ -820,6 +939,13 @@ impl<'lua> Lua<'lua> {
 
 	#[inline]
 	pub unsafe fn push_u64(&self, value: u64) {
+		// LuaJIT: use a `uint64_t` if a double can't represent it
+		#[cfg(feature = "luajit")]
+		unsafe {
+			if value > LUAJIT_MAX_SAFE_INTEGER && self.try_push_cdata_int64("uint64_t", value) {
+				return;
+			}
+		}
 		unsafe {
 			#[allow(trivial_numeric_casts)]
 			self.push_integer(value as lua_Integer);
@@ -1272,6 +1398,7 @@ impl<'lua> Lua<'lua> {
 		}
 	}
 
+	// LuaJIT: 64-bit targets only support pointers that fit in 47 bits, other pointers raise an error
 	#[inline]
 	pub unsafe fn push_light_user_data(&self, p: *const c_void) {
 		unsafe {
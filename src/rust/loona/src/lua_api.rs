@@ -11,6 +11,8 @@ mod lua5_2;
 mod lua5_3;
 #[cfg(feature = "lua5_4")]
 mod lua5_4;
+#[cfg(feature = "luajit")]
+mod luajit;
 
 #[cfg(feature = "lua5_1")]
 pub use crate::lua_api::lua5_1::*;
@@ -20,3 +22,5 @@ pub use crate::lua_api::lua5_2::*;
 pub use crate::lua_api::lua5_3::*;
 #[cfg(feature = "lua5_4")]
 pub use crate::lua_api::lua5_4::*;
+#[cfg(feature = "luajit")]
+pub use crate::lua_api::luajit::*;
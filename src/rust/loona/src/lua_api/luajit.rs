@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+// LuaJIT `luajit.h` converted to Rust. The rest of the API is the Lua 5.1 API (`lua5_1.rs`).
+//
+// Only LuaJIT 2.1 is supported. Notes:
+// - 64-bit integers are `int64_t`/`uint64_t` cdata (eg. `0x123ULL`) if the `ffi` module is used.
+//   `lua_topointer()` returns a pointer to the cdata's payload (2.0 returns the GC object).
+// - Light userdata pointers must fit in 47 bits on 64-bit targets or `lua_pushlightuserdata()` raises an error.
+// - `lua_newstate()` (custom allocator) isn't supported by 64-bit builds, it returns NULL.
+
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+#![allow(clippy::missing_safety_doc)]
+
+use crate::lua_api::lua5_1::lua_State;
+use libc::c_int;
+
+pub const LUAJIT_MODE_MASK: c_int = 0x00FF;
+
+pub const LUAJIT_MODE_ENGINE: c_int = 0;
+pub const LUAJIT_MODE_DEBUG: c_int = 1;
+pub const LUAJIT_MODE_FUNC: c_int = 2;
+pub const LUAJIT_MODE_ALLFUNC: c_int = 3;
+pub const LUAJIT_MODE_ALLSUBFUNC: c_int = 4;
+pub const LUAJIT_MODE_TRACE: c_int = 5;
+pub const LUAJIT_MODE_WRAPCFUNC: c_int = 0x10;
+pub const LUAJIT_MODE_MAX: c_int = 0x11;
+
+pub const LUAJIT_MODE_OFF: c_int = 0x0000;
+pub const LUAJIT_MODE_ON: c_int = 0x0100;
+pub const LUAJIT_MODE_FLUSH: c_int = 0x0200;
+
+// Not part of the public headers (lj_obj.h) but it's what `lua_type()` returns for cdata
+pub const LUA_TCDATA: c_int = 10;
+
+// Integers in this range can be stored in a `double` without losing precision
+pub const LUAJIT_MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+extern "C" {
+	pub fn luaJIT_setmode(L: lua_State, idx: c_int, mode: c_int) -> c_int;
+}
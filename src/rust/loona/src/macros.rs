@@ -32,6 +32,22 @@ macro_rules! lua_module {
 	};
 }
 
+/// Creates a module entry point (`luaopen_*()`) that uses the safe API. The block returns a
+/// `Result<R, LuaError>` where `R` is the module value, usually a [`LuaTable`](crate::table::LuaTable).
+#[macro_export]
+macro_rules! lua_safe_module {
+	($(#[$attr:meta])* fn $modname:ident($ctx:ident) $block:block) => {
+		$(#[$attr])*
+		#[no_mangle]
+		#[allow(non_snake_case)]
+		unsafe extern "C" fn $modname(L: $crate::lua_api::lua_State) -> $crate::libc::c_int {
+			unsafe {
+				$crate::function::call_safe(L, |$ctx: &mut $crate::context::LuaContext<'_, '_>| -> Result<_, $crate::error::LuaError> { $block })
+			}
+		}
+	};
+}
+
 #[macro_export]
 macro_rules! lua_struct_module {
 	($mod_name:ident : $class:ident) => {
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2018-present iced project and contributors
 
+pub use crate::context::{LuaContext, StackGuard};
 pub use crate::error::*;
+pub use crate::function::{FromLuaArgs, ToLuaMulti};
 pub use crate::lua::{Lua, LuaUserData};
+pub use crate::table::{ArrayIter, LuaTable, Pairs, TableBuilder};
 pub use crate::tofrom::*;
-pub use crate::{lua_get_or_init_metatable, lua_methods, lua_module, lua_pub_methods, lua_struct_module};
+pub use crate::{lua_get_or_init_metatable, lua_methods, lua_module, lua_pub_methods, lua_safe_module, lua_struct_module};
@@ -0,0 +1,311 @@
+// SPDX-License-Identifier: MIT
+// Copyright (C) 2018-present iced project and contributors
+
+#![allow(clippy::missing_errors_doc)]
+
+use crate::context::LuaContext;
+use crate::error::LuaError;
+use crate::function::{FromLuaArgs, ToLuaMulti};
+use crate::lua::Lua;
+use crate::lua_api::lua_GetIType;
+use crate::tofrom::{ToLua, TryFromLua};
+use libc::c_int;
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+
+/// A table on the Lua stack, the index is stored in the instance.
+///
+/// The index is verified to still be a table every time it's used so it's not an error to use it after the table
+/// has been popped, it just returns an error.
+#[derive(Debug, Clone, Copy)]
+pub struct LuaTable {
+	idx: c_int,
+}
+
+impl LuaTable {
+	/// Gets the stack index of the table
+	#[inline]
+	#[must_use]
+	pub fn index(&self) -> c_int {
+		self.idx
+	}
+
+	#[inline]
+	fn check(&self, ctx: &LuaContext<'_, '_>) -> Result<c_int, LuaError> {
+		if ctx.is_valid_index(self.idx) && unsafe { ctx.lua().is_table(self.idx) } {
+			Ok(self.idx)
+		} else {
+			Err(LuaError::MessageStr("The table is no longer on the stack"))
+		}
+	}
+}
+
+impl<'lua> TryFromLua<'lua> for LuaTable {
+	#[inline]
+	unsafe fn try_from_lua(lua: &Lua<'lua>, idx: c_int) -> Result<Self, LuaError> {
+		// SAFETY: caller guarantees `idx` is valid to pass to these functions
+		unsafe {
+			// Pseudo indices (eg. the registry) aren't supported, they become <= 0
+			let idx = if idx < 0 { lua.get_top() + idx + 1 } else { idx };
+			if idx > 0 && lua.is_table(idx) {
+				Ok(Self { idx })
+			} else {
+				Err(LuaError::MessageStr("Expected a table"))
+			}
+		}
+	}
+}
+
+impl ToLua for LuaTable {
+	#[inline]
+	unsafe fn to_lua(&self, lua: &Lua<'_>) {
+		// SAFETY: caller guarantees we can push a value
+		unsafe {
+			if self.idx > 0 && self.idx <= lua.get_top() && lua.is_table(self.idx) {
+				lua.push_value(self.idx);
+			} else {
+				lua.push_nil();
+			}
+		}
+	}
+}
+
+impl<'a, 'lua> LuaContext<'a, 'lua> {
+	/// Pushes a new empty table
+	#[inline]
+	pub fn create_table(&mut self) -> Result<LuaTable, LuaError> {
+		self.check_stack(1)?;
+		unsafe {
+			self.lua().new_table();
+			Ok(LuaTable { idx: self.lua().get_top() })
+		}
+	}
+
+	/// Pushes a new empty table and returns a builder that can add fields to it
+	#[inline]
+	pub fn table_builder(&mut self) -> Result<TableBuilder<'_, 'a, 'lua>, LuaError> {
+		let table = self.create_table()?;
+		Ok(TableBuilder { ctx: self, table, len: 0 })
+	}
+
+	/// Gets `table[key]` without invoking any metamethods
+	#[inline]
+	pub fn raw_get<K: ToLua, V: for<'x> TryFromLua<'x>>(&mut self, table: LuaTable, key: K) -> Result<V, LuaError> {
+		let idx = table.check(self)?;
+		self.check_stack(2)?;
+		let guard = self.stack_guard();
+		unsafe {
+			guard.lua().push(key);
+			guard.lua().raw_get(idx);
+			V::try_from_lua(guard.lua(), guard.lua().get_top())
+		}
+	}
+
+	/// Sets `table[key] = value` without invoking any metamethods. `key` can't be `nil` or NaN.
+	#[inline]
+	pub fn raw_set<K: ToLua, V: ToLua>(&mut self, table: LuaTable, key: K, value: V) -> Result<(), LuaError> {
+		let idx = table.check(self)?;
+		self.check_stack(2)?;
+		let guard = self.stack_guard();
+		unsafe {
+			guard.lua().push(key);
+			if guard.lua().is_nil(-1) {
+				return Err(LuaError::MessageStr("Table index is nil"));
+			}
+			if guard.lua().is_number(-1) && guard.lua().try_get_f64(-1).map_or(false, f64::is_nan) {
+				return Err(LuaError::MessageStr("Table index is NaN"));
+			}
+			guard.lua().push(value);
+			guard.lua().raw_set(idx);
+		}
+		Ok(())
+	}
+
+	/// Gets the length of the table without invoking any metamethods
+	#[inline]
+	pub fn raw_len(&self, table: LuaTable) -> Result<usize, LuaError> {
+		let idx = table.check(self)?;
+		Ok(unsafe { self.lua().raw_len(idx) })
+	}
+
+	/// Iterates over the values of `table[1]`, `table[2]`, ..., `table[#table]`
+	#[inline]
+	pub fn array<T: for<'x> TryFromLua<'x>>(&mut self, table: LuaTable) -> Result<ArrayIter<'_, 'a, 'lua, T>, LuaError> {
+		let len = self.raw_len(table)?;
+		Ok(ArrayIter { ctx: self, table, index: 0, len, _phantom: PhantomData })
+	}
+
+	/// Iterates over all keys and values of `table` (in no particular order), see `pairs()`
+	#[inline]
+	pub fn pairs<K: for<'x> TryFromLua<'x>, V: for<'x> TryFromLua<'x>>(&mut self, table: LuaTable) -> Result<Pairs<'_, 'a, 'lua, K, V>, LuaError> {
+		let idx = table.check(self)?;
+		self.check_stack(4)?;
+		let top = self.top();
+		unsafe { self.lua().push_nil() };
+		Ok(Pairs { ctx: self, idx, top, done: false, _phantom: PhantomData })
+	}
+}
+
+/// Adds fields to a table, see [`LuaContext::table_builder()`]
+#[allow(missing_debug_implementations)]
+pub struct TableBuilder<'c, 'a, 'lua> {
+	ctx: &'c mut LuaContext<'a, 'lua>,
+	table: LuaTable,
+	len: lua_GetIType,
+}
+
+impl TableBuilder<'_, '_, '_> {
+	/// Sets `table[key] = value`
+	#[inline]
+	pub fn field<K: ToLua, V: ToLua>(self, key: K, value: V) -> Result<Self, LuaError> {
+		self.ctx.raw_set(self.table, key, value)?;
+		Ok(self)
+	}
+
+	/// Appends a value to the array part of the table, starting at index 1
+	#[inline]
+	pub fn item<V: ToLua>(mut self, value: V) -> Result<Self, LuaError> {
+		let index = self.len.checked_add(1).ok_or(LuaError::MessageStr("Too many table items"))?;
+		let idx = self.table.check(self.ctx)?;
+		self.ctx.check_stack(1)?;
+		unsafe {
+			self.ctx.lua().push(value);
+			self.ctx.lua().raw_set_i(idx, index);
+		}
+		self.len = index;
+		Ok(self)
+	}
+
+	/// Sets `table[name]` to a new function, see [`LuaContext::create_function()`]
+	#[inline]
+	pub fn function<F, A, R>(self, name: &str, f: F) -> Result<Self, LuaError>
+	where
+		F: Fn(&mut LuaContext<'_, '_>, A) -> Result<R, LuaError> + 'static,
+		A: FromLuaArgs,
+		R: ToLuaMulti,
+	{
+		let idx = self.table.check(self.ctx)?;
+		self.ctx.check_stack(1)?;
+		let mut guard = self.ctx.stack_guard();
+		unsafe { guard.lua().push(name) };
+		let _ = guard.create_function(f)?;
+		unsafe { guard.lua().raw_set(idx) };
+		drop(guard);
+		Ok(self)
+	}
+
+	/// Returns the table. It's left on the stack.
+	#[inline]
+	#[must_use]
+	pub fn build(self) -> LuaTable {
+		self.table
+	}
+}
+
+/// Iterates over the array part of a table, see [`LuaContext::array()`]
+#[allow(missing_debug_implementations)]
+pub struct ArrayIter<'c, 'a, 'lua, T> {
+	ctx: &'c mut LuaContext<'a, 'lua>,
+	table: LuaTable,
+	index: usize,
+	len: usize,
+	_phantom: PhantomData<fn() -> T>,
+}
+
+impl<T: for<'x> TryFromLua<'x>> Iterator for ArrayIter<'_, '_, '_, T> {
+	type Item = Result<T, LuaError>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.index >= self.len {
+			return None;
+		}
+		self.index += 1;
+		let key = match lua_GetIType::try_from(self.index) {
+			Ok(key) => key,
+			Err(_) => return Some(Err(LuaError::MessageStr("Too many table items"))),
+		};
+		let result = self.table.check(self.ctx).and_then(|idx| {
+			self.ctx.check_stack(1)?;
+			let guard = self.ctx.stack_guard();
+			unsafe {
+				guard.lua().raw_get_i(idx, key);
+				T::try_from_lua(guard.lua(), guard.lua().get_top())
+			}
+		});
+		if result.is_err() {
+			self.index = self.len;
+		}
+		Some(result)
+	}
+
+	#[inline]
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, Some(self.len - self.index))
+	}
+}
+
+/// Iterates over all keys and values of a table, see [`LuaContext::pairs()`]
+///
+/// The key is left on the stack between calls to `next()` and is popped when the iterator is dropped.
+#[allow(missing_debug_implementations)]
+pub struct Pairs<'c, 'a, 'lua, K, V> {
+	ctx: &'c mut LuaContext<'a, 'lua>,
+	idx: c_int,
+	top: c_int,
+	done: bool,
+	_phantom: PhantomData<fn() -> (K, V)>,
+}
+
+impl<K, V> Pairs<'_, '_, '_, K, V> {
+	fn finish(&mut self) {
+		self.done = true;
+		unsafe { self.ctx.lua().set_top(self.top) };
+	}
+}
+
+impl<K: for<'x> TryFromLua<'x>, V: for<'x> TryFromLua<'x>> Iterator for Pairs<'_, '_, '_, K, V> {
+	type Item = Result<(K, V), LuaError>;
+
+	#[inline]
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.done {
+			return None;
+		}
+		let lua = self.ctx.lua();
+		// SAFETY: the table and the previous key (pushed by pairs() or the previous call) are on the stack
+		// and there's room for 3 more values (checked by pairs())
+		unsafe {
+			if lua.get_top() != self.top + 1 || !lua.is_table(self.idx) {
+				self.finish();
+				return Some(Err(LuaError::MessageStr("The table is no longer on the stack")));
+			}
+			if lua.next(self.idx) == 0 {
+				self.finish();
+				return None;
+			}
+			// Convert a copy of the key since lua_tolstring() would modify the key and confuse lua_next()
+			lua.push_value(-2);
+			let key = K::try_from_lua(lua, lua.get_top());
+			lua.pop(1);
+			let value = V::try_from_lua(lua, lua.get_top());
+			lua.pop(1);
+			match (key, value) {
+				(Ok(key), Ok(value)) => Some(Ok((key, value))),
+				(Err(e), _) | (_, Err(e)) => {
+					self.finish();
+					Some(Err(e))
+				}
+			}
+		}
+	}
+}
+
+impl<K, V> Drop for Pairs<'_, '_, '_, K, V> {
+	#[inline]
+	fn drop(&mut self) {
+		if !self.done {
+			self.finish();
+		}
+	}
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 // Copyright (C) 2018-present iced project and contributors
 
+use crate::error::LuaError;
 use crate::lua::Lua;
 use crate::lua_api::lua_CFunction;
 use crate::prelude::LuaUserData;
@@ -16,6 +17,18 @@ pub trait FromLua<'lua> {
 	unsafe fn from_lua(lua: &Lua<'lua>, idx: c_int) -> Self::RetType;
 }
 
+/// Like [`FromLua`] but returns an error instead of raising a Lua error
+pub trait TryFromLua<'lua>: Sized {
+	/// # Errors
+	///
+	/// If the value has the wrong type or is out of range
+	///
+	/// # Safety
+	///
+	/// `idx` must be a valid index that can be passed to Lua API functions.
+	unsafe fn try_from_lua(lua: &Lua<'lua>, idx: c_int) -> Result<Self, LuaError>;
+}
+
 pub trait ToLua {
 	/// # Safety
 	///
@@ -66,6 +79,17 @@ impl<'a, 'lua: 'a> FromLua<'lua> for LuaLossyString<'a> {
 	}
 }
 
+impl<'a, 'lua: 'a> TryFromLua<'lua> for LuaLossyString<'a> {
+	#[inline]
+	unsafe fn try_from_lua(lua: &Lua<'lua>, idx: c_int) -> Result<Self, LuaError> {
+		// SAFETY: caller guarantees `idx` is valid to pass to this function
+		match unsafe { lua.try_get_byte_slice(idx) } {
+			Some(bytes) => Ok(Self(String::from_utf8_lossy(bytes))),
+			None => Err(LuaError::MessageStr("Expected a string")),
+		}
+	}
+}
+
 macro_rules! create_lua_option {
     ($lua:ident, $idx:ident { $($struct_name:ident : $ty:ty => $expr:expr,)+ }) => {
 		$(
@@ -152,6 +176,65 @@ impl_from_lua! { 'lua, lua, idx {
 	Vec<u8> => unsafe { lua.get_bytes(idx) },
 }}
 
+macro_rules! impl_try_from_lua {
+	($lua:ident, $idx:ident { $($ty:ty => $cvt:expr,)+ }) => {
+		$(
+			impl<'lua> $crate::tofrom::TryFromLua<'lua> for $ty {
+				#[inline]
+				unsafe fn try_from_lua($lua: &Lua<'lua>, $idx: c_int) -> Result<Self, LuaError> {
+					$cvt
+				}
+			}
+		)+
+	};
+}
+
+// SAFETY: caller guarantees `idx` is valid to pass to these functions
+impl_try_from_lua! { lua, idx {
+	bool => unsafe { lua.try_get_bool(idx) }.ok_or(LuaError::MessageStr("Expected a boolean")),
+	char => unsafe { lua.try_get_char(idx) }.map_err(|_| LuaError::MessageStr("Expected a char (unicode code point)")),
+	i8 => Ok(unsafe { lua.try_get_i8(idx) }?),
+	i16 => Ok(unsafe { lua.try_get_i16(idx) }?),
+	i32 => Ok(unsafe { lua.try_get_i32(idx) }?),
+	i64 => Ok(unsafe { lua.try_get_i64(idx) }?),
+	isize => Ok(unsafe { lua.try_get_isize(idx) }?),
+	u8 => Ok(unsafe { lua.try_get_u8(idx) }?),
+	u16 => Ok(unsafe { lua.try_get_u16(idx) }?),
+	u32 => Ok(unsafe { lua.try_get_u32(idx) }?),
+	u64 => Ok(unsafe { lua.try_get_u64(idx) }?),
+	usize => Ok(unsafe { lua.try_get_usize(idx) }?),
+	f64 => Ok(unsafe { lua.try_get_f64(idx) }?),
+	Vec<u8> => unsafe { lua.try_get_bytes(idx) }.ok_or(LuaError::MessageStr("Expected a string (bytes)")),
+	String => match unsafe { lua.try_get_byte_slice(idx) } {
+		Some(bytes) => std::str::from_utf8(bytes).map(ToOwned::to_owned).map_err(|_| LuaError::MessageStr("Expected a UTF-8 string")),
+		None => Err(LuaError::MessageStr("Expected a string")),
+	},
+	LuaIgnore => { let _ = (lua, idx); Ok(LuaIgnore) },
+	LuaAny => { let _ = lua; Ok(LuaAny { idx }) },
+}}
+
+impl<'lua> TryFromLua<'lua> for &'lua [u8] {
+	#[inline]
+	unsafe fn try_from_lua(lua: &Lua<'lua>, idx: c_int) -> Result<Self, LuaError> {
+		// SAFETY: caller guarantees `idx` is valid to pass to this function
+		unsafe { lua.try_get_byte_slice(idx) }.ok_or(LuaError::MessageStr("Expected a string (bytes)"))
+	}
+}
+
+impl<'lua, T: TryFromLua<'lua>> TryFromLua<'lua> for Option<T> {
+	#[inline]
+	unsafe fn try_from_lua(lua: &Lua<'lua>, idx: c_int) -> Result<Self, LuaError> {
+		// SAFETY: caller guarantees `idx` is valid to pass to these functions
+		unsafe {
+			if lua.is_none_or_nil(idx) {
+				Ok(None)
+			} else {
+				T::try_from_lua(lua, idx).map(Some)
+			}
+		}
+	}
+}
+
 impl<'lua, T: LuaUserData + 'lua> FromLua<'lua> for &T {
 	type RetType = &'lua T;
 
@@ -249,6 +332,8 @@ impl_to_lua! { lua, value {
 	u32 => unsafe { lua.push_u32(*value) },
 	u64 => unsafe { lua.push_u64(*value) },
 	usize => unsafe { lua.push_usize(*value) },
+	f32 => unsafe { lua.push_f32(*value) },
+	f64 => unsafe { lua.push_f64(*value) },
 	&str => unsafe { lua.push_literal(value) },
 	String => unsafe { lua.push_literal(value) },
 	&String => unsafe { lua.push_literal(value) },